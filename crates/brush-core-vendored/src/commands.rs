@@ -1,6 +1,6 @@
 //! Command execution
 
-use std::{borrow::Cow, ffi::OsStr, fmt::Display, process::Stdio, sync::Arc};
+use std::{borrow::Cow, ffi::{OsStr, OsString}, fmt::Display, process::Stdio, sync::Arc};
 
 use brush_parser::ast;
 use itertools::Itertools;
@@ -403,15 +403,33 @@ pub(crate) fn execute_external_command(
     // Figure out if we should be setting up a new process group.
     let new_pg = context.should_cmd_lead_own_process_group();
 
-    // Compose the std::process::Command that encapsulates what we want to launch.
+    // Compose the std::process::Command that encapsulates what we want to launch,
+    // going through the embedder's wrapper program if it has one.
+    let spawn_hooks = context.params.spawn_hooks();
+    let wrapper = spawn_hooks.as_ref().and_then(|hooks| hooks.command_wrapper());
     #[allow(unused_mut, reason = "only mutated on unix platforms")]
-    let mut cmd = compose_std_command(
-        &context,
-        executable_path,
-        context.command_name.as_str(),
-        cmd_args.as_slice(),
-        false, /* empty environment? */
-    )?;
+    let mut cmd = match wrapper.as_deref() {
+        Some([program, leading_args @ ..]) => {
+            let mut wrapped_args: Vec<OsString> = leading_args.to_vec();
+            wrapped_args.push(executable_path.into());
+            wrapped_args.extend(cmd_args.iter().map(OsString::from));
+            let program = program.to_string_lossy();
+            compose_std_command(
+                &context,
+                program.as_ref(),
+                program.as_ref(),
+                wrapped_args.as_slice(),
+                false, /* empty environment? */
+            )?
+        }
+        _ => compose_std_command(
+            &context,
+            executable_path,
+            context.command_name.as_str(),
+            cmd_args.as_slice(),
+            false, /* empty environment? */
+        )?,
+    };
 
     // Set up process group state.
     if new_pg {
@@ -431,6 +449,11 @@ pub(crate) fn execute_external_command(
         cmd.take_foreground();
     }
 
+    // Give the embedder a chance to adjust the command before it is spawned.
+    if let Some(hooks) = &spawn_hooks {
+        hooks.before_spawn(&mut cmd);
+    }
+
     // When tracing is enabled, report.
     tracing::debug!(
         target: trace_categories::COMMANDS,
@@ -450,12 +473,15 @@ pub(crate) fn execute_external_command(
                 if new_pg {
                     *process_group_id = Some(*pid);
                 }
+                if let Some(hooks) = &spawn_hooks {
+                    hooks.after_spawn(*pid);
+                }
             } else {
                 tracing::warn!("could not retrieve pid for child process");
             }
 
             Ok(ExecutionSpawnResult::StartedProcess(
-                processes::ChildProcess::new(pid, child),
+                processes::ChildProcess::new(pid, child, spawn_hooks),
             ))
         }
         Err(spawn_err) => {
@@ -553,6 +579,13 @@ pub(crate) async fn invoke_command_in_subshell_and_get_output(
     params: &ExecutionParameters,
     s: String,
 ) -> Result<String, error::Error> {
+    if params.forbid_command_substitution {
+        return Err(error::ErrorKind::CheckedExpansionError(
+            "command substitution is not allowed".into(),
+        )
+        .into());
+    }
+
     // Instantiate a subshell to run the command in.
     let subshell = shell.clone();
 
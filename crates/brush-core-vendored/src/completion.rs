@@ -1070,8 +1070,10 @@ async fn get_file_completions(
     must_be_dir: bool,
 ) -> IndexSet<String> {
     // Basic-expand the token-to-be-completed; it won't have been expanded to this point.
+    // Completing must not run anything the user is still typing.
     let mut throwaway_shell = shell.clone();
-    let params = throwaway_shell.default_exec_params();
+    let mut params = throwaway_shell.default_exec_params();
+    params.forbid_command_substitution = true;
     let expanded_token = throwaway_shell
         .basic_expand_string(&params, token_to_complete)
         .await
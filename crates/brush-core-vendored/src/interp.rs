@@ -1,6 +1,7 @@
 use brush_parser::ast::{self, CommandPrefixOrSuffixItem};
 use itertools::Itertools;
 use std::collections::VecDeque;
+use std::sync::Arc;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use tokio_util::sync::CancellationToken;
@@ -67,6 +68,38 @@ pub struct ExecutionParameters {
     pub process_group_policy: ProcessGroupPolicy,
     /// Optional cancellation token shared with callers.
     cancel_token: Option<CancellationToken>,
+    /// Optional hooks invoked around spawning external commands.
+    spawn_hooks: Option<Arc<dyn SpawnHooks>>,
+    /// Fail command substitutions instead of running them, so expansion
+    /// cannot execute anything.
+    pub forbid_command_substitution: bool,
+}
+
+/// Hooks invoked around spawning external commands.
+///
+/// Lets embedders adjust the composed command (resource limits, priority,
+/// sandboxing) and observe the spawned process ids.
+pub trait SpawnHooks: Send + Sync {
+    /// Returns a program and leading arguments to launch every external
+    /// command through; the command's own path and arguments follow them.
+    fn command_wrapper(&self) -> Option<Vec<std::ffi::OsString>> {
+        None
+    }
+
+    /// Called before the shell itself opens a redirection target or sourced
+    /// file; an error fails the open.
+    fn before_open(&self, _path: &std::path::Path, _write: bool) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// Called with the fully composed command just before it is spawned.
+    fn before_spawn(&self, _cmd: &mut std::process::Command) {}
+
+    /// Called with the process id of each successfully spawned command.
+    fn after_spawn(&self, _pid: i32) {}
+
+    /// Called with the exit status of each spawned command once it is reaped.
+    fn after_exit(&self, _pid: i32, _status: &std::process::ExitStatus) {}
 }
 
 impl ExecutionParameters {
@@ -80,6 +113,16 @@ impl ExecutionParameters {
         self.cancel_token.clone()
     }
 
+	/// Assigns hooks invoked around spawning external commands.
+	pub fn set_spawn_hooks(&mut self, hooks: Arc<dyn SpawnHooks>) {
+		self.spawn_hooks = Some(hooks);
+	}
+
+	/// Returns the spawn hooks, if present.
+	pub fn spawn_hooks(&self) -> Option<Arc<dyn SpawnHooks>> {
+		self.spawn_hooks.clone()
+	}
+
 	/// Returns true when cancellation has been requested.
 	pub fn is_cancelled(&self) -> bool {
 		self
@@ -1433,7 +1476,7 @@ pub(crate) async fn setup_redirect(
                 .append(*append);
 
             let stdout_file = shell
-                .open_file(&file_options, &expanded_file_path, params)
+                .open_file(&file_options, &expanded_file_path, true, params)
                 .map_err(|err| {
                     error::ErrorKind::RedirectionFailure(
                         expanded_file_path.to_string_lossy().to_string(),
@@ -1510,9 +1553,13 @@ pub(crate) async fn setup_redirect(
                     }
 
                     let fd_num = specified_fd_num.unwrap_or(default_fd_if_unspecified);
+                    let write = !matches!(
+                        kind,
+                        ast::IoFileRedirectKind::Read | ast::IoFileRedirectKind::DuplicateInput
+                    );
 
                     let opened_file = shell
-                        .open_file(&options, &expanded_file_path, params)
+                        .open_file(&options, &expanded_file_path, write, params)
                         .map_err(|err| {
                             error::ErrorKind::RedirectionFailure(
                                 expanded_file_path.to_string_lossy().to_string(),
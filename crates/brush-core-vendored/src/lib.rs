@@ -41,7 +41,7 @@ mod wellknownvars;
 
 pub use commands::{CommandArg, ExecutionContext};
 pub use error::{BuiltinError, Error, ErrorKind};
-pub use interp::{ExecutionParameters, ProcessGroupPolicy};
+pub use interp::{ExecutionParameters, ProcessGroupPolicy, SpawnHooks};
 pub use results::{ExecutionControlFlow, ExecutionExitCode, ExecutionResult, ExecutionSpawnResult};
 pub use shell::{CreateOptions, Shell, ShellBuilder, ShellBuilderState, ShellFd};
 pub use variables::{ShellValue, ShellVariable};
@@ -1,8 +1,10 @@
 //! Process management
 
+use std::sync::Arc;
+
 use tokio_util::sync::CancellationToken;
 
-use crate::{error, sys};
+use crate::{error, interp::SpawnHooks, sys};
 
 /// Tracks a child process being awaited.
 pub struct ChildProcess {
@@ -12,12 +14,18 @@ pub struct ChildProcess {
     child: sys::process::Child,
     /// Tracks whether this process has already been reaped.
     reaped: bool,
+    /// Hooks notified of the exit status once the process is reaped.
+    spawn_hooks: Option<Arc<dyn SpawnHooks>>,
 }
 
 impl ChildProcess {
     /// Wraps a child process and its future.
-    pub fn new(pid: Option<sys::process::ProcessId>, child: sys::process::Child) -> Self {
-        Self { pid, child, reaped: false }
+    pub fn new(
+        pid: Option<sys::process::ProcessId>,
+        child: sys::process::Child,
+        spawn_hooks: Option<Arc<dyn SpawnHooks>>,
+    ) -> Self {
+        Self { pid, child, reaped: false, spawn_hooks }
     }
 
     /// Returns the process's ID.
@@ -73,6 +81,7 @@ impl ChildProcess {
                 Some(status) => {
                     let status = status?;
                     self.reaped = true;
+                    self.report_exit(&status);
                     Ok(ProcessWaitResult::Completed(output_from_status(status)))
                 }
                 None => {
@@ -87,6 +96,13 @@ impl ChildProcess {
         }
     }
 
+    /// Passes the exit status of the reaped process to the spawn hooks.
+    fn report_exit(&self, status: &std::process::ExitStatus) {
+        if let (Some(hooks), Some(pid)) = (&self.spawn_hooks, self.pid) {
+            hooks.after_exit(pid, status);
+        }
+    }
+
     /// Sends a kill signal and attempts a synchronous reap if still running.
     fn kill(&mut self) {
         if self.reaped {
@@ -107,6 +123,7 @@ impl ChildProcess {
         match self.child.try_wait() {
             Ok(Some(status)) => {
                 self.reaped = true;
+                self.report_exit(&status);
                 Some(Ok(output_from_status(status)))
             }
             Ok(None) => None,
@@ -399,7 +399,7 @@ impl Shell {
                 options.read(true);
 
                 if let Ok(history_file) =
-                    shell.open_file(&options, history_path, &shell.default_exec_params())
+                    shell.open_file(&options, history_path, false, &shell.default_exec_params())
                 {
                     shell.history = Some(history::History::import(history_file)?);
                 }
@@ -705,7 +705,7 @@ impl Shell {
         options.read(true);
 
         let opened_file: openfiles::OpenFile = self
-            .open_file(&options, path, params)
+            .open_file(&options, path, false, params)
             .map_err(|e| error::ErrorKind::FailedSourcingFile(path.to_owned(), e))?;
 
         if opened_file.is_dir() {
@@ -1372,11 +1372,13 @@ impl Shell {
     ///
     /// * `options` - The options to use opening the file.
     /// * `path` - The path to the file to open; may be relative to the shell's working directory.
+    /// * `write` - Whether the file is opened for writing.
     /// * `params` - Execution parameters.
     pub(crate) fn open_file(
         &self,
         options: &std::fs::OpenOptions,
         path: impl AsRef<Path>,
+        write: bool,
         params: &ExecutionParameters,
     ) -> Result<openfiles::OpenFile, std::io::Error> {
         let path_to_open = self.absolute_path(path.as_ref());
@@ -1396,6 +1398,10 @@ impl Shell {
             }
         }
 
+        if let Some(hooks) = params.spawn_hooks() {
+            hooks.before_open(&path_to_open, write)?;
+        }
+
         Ok(options.open(path_to_open)?.into())
     }
 
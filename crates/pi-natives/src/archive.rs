@@ -0,0 +1,751 @@
+//! Archive extraction and creation without `tar` or `unzip`.
+//!
+//! # Overview
+//! Supports plain, gzip- and zstd-compressed tarballs and zip files.
+//! Archives to extract are recognized by their magic bytes, not their
+//! extension.
+//!
+//! # Path Safety
+//! Extraction never writes outside the destination:
+//! - entry names with `..`, absolute paths or drive prefixes are rejected
+//! - symlinks must be relative and resolve inside the destination
+//! - directories are created one level at a time, and every symlink on the way
+//!   must resolve inside the destination, so an existing or extracted symlink
+//!   cannot redirect a write
+//! - hard links and device, FIFO and other special entries are skipped
+//!
+//! Extraction is refused up front when the unpacked size, as far as the
+//! archive records it, exceeds the free space at the destination, and stops
+//! before any file that would no longer fit.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.extractArchive("release.tar.gz", "vendor/tool", { stripComponents: 1 })
+//! // JS: await native.createArchive(["dist"], "dist.zip")
+//! ```
+
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, BufReader, Read, Seek, SeekFrom, Write},
+	path::{Component, Path, PathBuf},
+};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+	system_info::{disk, format_bytes},
+	task, workspace,
+};
+
+/// Options for `extractArchive`.
+#[napi(object)]
+pub struct ExtractArchiveOptions<'env> {
+	/// Leading path components to remove from entry names, like `tar
+	/// --strip-components`. Entries with no components left are skipped.
+	#[napi(js_name = "stripComponents")]
+	pub strip_components:        Option<u32>,
+	/// Entry types to extract (default: files and directories). Other entries
+	/// are skipped and reported.
+	#[napi(js_name = "allowedTypes", ts_type = "Array<\"file\" | \"directory\" | \"symlink\">")]
+	pub allowed_types:           Option<Vec<String>>,
+	/// Abort signal for cancelling the operation.
+	pub signal:                  Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the operation.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:              Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Result of `extractArchive`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ExtractArchiveResult {
+	/// Regular files written.
+	pub files:       u32,
+	/// Directories created.
+	pub directories: u32,
+	/// Symlinks created.
+	pub symlinks:    u32,
+	/// Bytes of file content written.
+	pub bytes:       f64,
+	/// Entry names that were skipped because their type is not allowed or
+	/// not supported.
+	pub skipped:     Vec<String>,
+}
+
+/// Result of `createArchive`.
+#[napi(object)]
+pub struct CreateArchiveResult {
+	/// Files, directories and symlinks added.
+	pub entries: u32,
+	/// Size of the written archive in bytes.
+	pub bytes:   f64,
+}
+
+/// Archive names `createArchive` infers the format from.
+const EXTENSIONS: &[(&str, Format)] = &[
+	(".tar.gz", Format::TarGz),
+	(".tgz", Format::TarGz),
+	(".tar.zst", Format::TarZst),
+	(".tzst", Format::TarZst),
+	(".tar", Format::Tar),
+	(".zip", Format::Zip),
+];
+
+/// Archive container and compression.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+	Tar,
+	TarGz,
+	TarZst,
+	Zip,
+}
+
+impl Format {
+	/// Parse the `format` argument, or infer it from the archive name.
+	fn parse(option: Option<&str>, dest: &Path) -> Result<Self> {
+		match option {
+			Some("tar") => Ok(Self::Tar),
+			Some("tar.gz") => Ok(Self::TarGz),
+			Some("tar.zst") => Ok(Self::TarZst),
+			Some("zip") => Ok(Self::Zip),
+			Some(other) => Err(Error::from_reason(format!(
+				"Invalid archive format: {other} (expected \"tar\", \"tar.gz\", \"tar.zst\" or \
+				 \"zip\")"
+			))),
+			None => {
+				let name = dest.file_name().unwrap_or_default().to_string_lossy();
+				let name = name.to_ascii_lowercase();
+				EXTENSIONS
+					.iter()
+					.find(|(extension, _)| name.ends_with(extension))
+					.map(|&(_, format)| format)
+					.ok_or_else(|| {
+						Error::from_reason(format!(
+							"Cannot infer archive format from {}; pass \"tar\", \"tar.gz\", \"tar.zst\" \
+							 or \"zip\"",
+							dest.display()
+						))
+					})
+			},
+		}
+	}
+
+	/// Recognize an archive by its leading bytes.
+	fn sniff(header: &[u8]) -> Option<Self> {
+		if header.starts_with(&[0x1f, 0x8b]) {
+			Some(Self::TarGz)
+		} else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+			Some(Self::TarZst)
+		} else if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+			Some(Self::Zip)
+		} else if header.get(257..262) == Some(b"ustar") {
+			Some(Self::Tar)
+		} else {
+			None
+		}
+	}
+}
+
+/// Entry types `extractArchive` may create.
+struct AllowedTypes {
+	file:      bool,
+	directory: bool,
+	symlink:   bool,
+}
+
+impl AllowedTypes {
+	fn parse(option: Option<&[String]>) -> Result<Self> {
+		let Some(types) = option else {
+			return Ok(Self { file: true, directory: true, symlink: false });
+		};
+		let mut allowed = Self { file: false, directory: false, symlink: false };
+		for kind in types {
+			match kind.as_str() {
+				"file" => allowed.file = true,
+				"directory" => allowed.directory = true,
+				"symlink" => allowed.symlink = true,
+				other => {
+					return Err(Error::from_reason(format!(
+						"Invalid entry type: {other} (expected \"file\", \"directory\" or \"symlink\")"
+					)));
+				},
+			}
+		}
+		Ok(allowed)
+	}
+}
+
+fn other_error(message: String) -> io::Error {
+	io::Error::other(message)
+}
+
+/// Writes archive entries below a destination directory.
+struct Extractor {
+	/// Canonical destination directory.
+	dest:    PathBuf,
+	strip:   usize,
+	allowed: AllowedTypes,
+	/// Bytes free at the destination when extraction started.
+	space:   u64,
+	result:  ExtractArchiveResult,
+}
+
+impl Extractor {
+	/// Path below the destination for entry `name`, or `None` when stripping
+	/// leaves nothing of it.
+	fn target(&self, name: &Path) -> io::Result<Option<PathBuf>> {
+		let mut relative = PathBuf::new();
+		for component in name.components() {
+			match component {
+				Component::Normal(part) => relative.push(part),
+				Component::CurDir => {},
+				Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+					return Err(other_error(format!(
+						"Refusing to extract {}: path escapes the destination",
+						name.display()
+					)));
+				},
+			}
+		}
+		let stripped: PathBuf = relative.components().skip(self.strip).collect();
+		Ok((!stripped.as_os_str().is_empty()).then(|| self.dest.join(stripped)))
+	}
+
+	/// Create `dir` one level at a time, checking that every existing symlink
+	/// on the way resolves inside the destination before descending into it.
+	fn create_dirs(&self, dir: &Path, name: &Path) -> io::Result<()> {
+		let relative = dir
+			.strip_prefix(&self.dest)
+			.unwrap_or_else(|_| Path::new(""));
+		let mut current = self.dest.clone();
+		for component in relative.components() {
+			current.push(component);
+			match fs::symlink_metadata(&current) {
+				Ok(meta) if meta.file_type().is_symlink() => {
+					if !fs::canonicalize(&current)?.starts_with(&self.dest) {
+						return Err(other_error(format!(
+							"Refusing to extract {}: path escapes the destination through a symlink",
+							name.display()
+						)));
+					}
+				},
+				Ok(_) => {},
+				Err(err) if err.kind() == io::ErrorKind::NotFound => fs::create_dir(&current)?,
+				Err(err) => return Err(err),
+			}
+		}
+		Ok(())
+	}
+
+	/// Create the parent directories of `path` inside the destination.
+	fn prepare_parent(&self, path: &Path, name: &Path) -> io::Result<()> {
+		self.create_dirs(path.parent().unwrap_or(&self.dest), name)?;
+		// Never write through an existing symlink at the entry itself.
+		if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink()) {
+			fs::remove_file(path)?;
+		}
+		Ok(())
+	}
+
+	fn skip(&mut self, name: &Path) {
+		self
+			.result
+			.skipped
+			.push(name.to_string_lossy().into_owned());
+	}
+
+	fn file(
+		&mut self,
+		name: &Path,
+		content: &mut impl Read,
+		size: u64,
+		mode: Option<u32>,
+	) -> io::Result<()> {
+		if !self.allowed.file {
+			self.skip(name);
+			return Ok(());
+		}
+		let Some(path) = self.target(name)? else {
+			return Ok(());
+		};
+		let left = self.space.saturating_sub(self.result.bytes as u64);
+		if size > left {
+			return Err(other_error(format!(
+				"Not enough disk space for {}: {} needed, {} left",
+				name.display(),
+				format_bytes(size),
+				format_bytes(left)
+			)));
+		}
+		self.prepare_parent(&path, name)?;
+		let mut file = OpenOptions::new()
+			.write(true)
+			.create(true)
+			.truncate(true)
+			.open(&path)?;
+		let written = io::copy(content, &mut file)?;
+		set_mode(&file, mode)?;
+		self.result.files += 1;
+		self.result.bytes += written as f64;
+		Ok(())
+	}
+
+	fn directory(&mut self, name: &Path) -> io::Result<()> {
+		if !self.allowed.directory {
+			self.skip(name);
+			return Ok(());
+		}
+		let Some(path) = self.target(name)? else {
+			return Ok(());
+		};
+		self.create_dirs(&path, name)?;
+		self.result.directories += 1;
+		Ok(())
+	}
+
+	fn symlink(&mut self, name: &Path, link: &Path) -> io::Result<()> {
+		if !self.allowed.symlink {
+			self.skip(name);
+			return Ok(());
+		}
+		let Some(path) = self.target(name)? else {
+			return Ok(());
+		};
+		if !link_stays_inside(&self.dest, &path, link) {
+			return Err(other_error(format!(
+				"Refusing to extract {}: symlink target {} is outside the destination",
+				name.display(),
+				link.display()
+			)));
+		}
+		self.prepare_parent(&path, name)?;
+		if fs::symlink_metadata(&path).is_ok() {
+			fs::remove_file(&path)?;
+		}
+		create_symlink(link, &path)?;
+		self.result.symlinks += 1;
+		Ok(())
+	}
+}
+
+/// Whether the relative symlink `link` at `path` resolves inside `dest`,
+/// judged from its components alone. Writes through it are checked
+/// separately once it exists.
+fn link_stays_inside(dest: &Path, path: &Path, link: &Path) -> bool {
+	let Ok(parent) = path.parent().unwrap_or(dest).strip_prefix(dest) else {
+		return false;
+	};
+	let mut depth = parent.components().count();
+	for component in link.components() {
+		match component {
+			Component::Normal(_) => depth += 1,
+			Component::CurDir => {},
+			Component::ParentDir => {
+				let Some(up) = depth.checked_sub(1) else {
+					return false;
+				};
+				depth = up;
+			},
+			Component::RootDir | Component::Prefix(_) => return false,
+		}
+	}
+	true
+}
+
+#[cfg(unix)]
+fn set_mode(file: &File, mode: Option<u32>) -> io::Result<()> {
+	use std::os::unix::fs::PermissionsExt;
+	// Keep the permission bits only; setuid and friends are dropped.
+	match mode {
+		Some(mode) => file.set_permissions(fs::Permissions::from_mode(mode & 0o777)),
+		None => Ok(()),
+	}
+}
+
+#[cfg(windows)]
+#[allow(clippy::unnecessary_wraps, reason = "unix permissions do not apply on Windows")]
+const fn set_mode(_file: &File, _mode: Option<u32>) -> io::Result<()> {
+	Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(link: &Path, path: &Path) -> io::Result<()> {
+	std::os::unix::fs::symlink(link, path)
+}
+
+#[cfg(windows)]
+fn create_symlink(link: &Path, path: &Path) -> io::Result<()> {
+	std::os::windows::fs::symlink_file(link, path)
+}
+
+fn extract_tar(reader: impl Read, extractor: &mut Extractor, ct: &task::CancelToken) -> Result<()> {
+	let mut archive = tar::Archive::new(reader);
+	let entries = archive.entries().map_err(extract_error)?;
+	for entry in entries {
+		ct.heartbeat()?;
+		let mut entry = entry.map_err(extract_error)?;
+		let name = entry.path().map_err(extract_error)?.into_owned();
+		let kind = entry.header().entry_type();
+		let outcome = if kind.is_file() {
+			let mode = entry.header().mode().ok();
+			let size = entry.header().size().unwrap_or(0);
+			extractor.file(&name, &mut entry, size, mode)
+		} else if kind.is_dir() {
+			extractor.directory(&name)
+		} else if kind.is_symlink() {
+			let link = entry
+				.link_name()
+				.map_err(extract_error)?
+				.unwrap_or_default();
+			extractor.symlink(&name, &link)
+		} else {
+			extractor.skip(&name);
+			Ok(())
+		};
+		outcome.map_err(extract_error)?;
+	}
+	Ok(())
+}
+
+fn extract_zip(file: File, extractor: &mut Extractor, ct: &task::CancelToken) -> Result<()> {
+	let mut archive = ZipArchive::new(BufReader::new(file)).map_err(extract_error)?;
+	for index in 0..archive.len() {
+		ct.heartbeat()?;
+		let mut entry = archive.by_index(index).map_err(extract_error)?;
+		let Some(name) = entry.enclosed_name() else {
+			return Err(Error::from_reason(format!(
+				"Refusing to extract {}: path escapes the destination",
+				entry.name()
+			)));
+		};
+		let outcome = if entry.is_dir() {
+			extractor.directory(&name)
+		} else if entry.is_symlink() {
+			let mut link = String::new();
+			entry.read_to_string(&mut link).map_err(extract_error)?;
+			extractor.symlink(&name, Path::new(&link))
+		} else if entry.is_file() {
+			let mode = entry.unix_mode();
+			let size = entry.size();
+			extractor.file(&name, &mut entry, size, mode)
+		} else {
+			extractor.skip(&name);
+			Ok(())
+		};
+		outcome.map_err(extract_error)?;
+	}
+	Ok(())
+}
+
+/// Unpacked size of an archive as far as it records it: exact for tar and
+/// zip, the size in the gzip trailer (modulo 4 GiB) or zstd frame header
+/// otherwise, and never less than the archive itself.
+fn unpacked_size(file: &File, format: Format, header: &[u8]) -> io::Result<u64> {
+	let len = file.metadata()?.len();
+	let recorded = match format {
+		Format::Tar => 0,
+		Format::TarGz if len >= 4 => {
+			let mut trailer = [0; 4];
+			(&*file).seek(SeekFrom::End(-4))?;
+			(&*file).read_exact(&mut trailer)?;
+			u64::from(u32::from_le_bytes(trailer))
+		},
+		Format::TarGz => 0,
+		Format::TarZst => zstd::zstd_safe::get_frame_content_size(header)
+			.ok()
+			.flatten()
+			.unwrap_or(0),
+		Format::Zip => {
+			let mut archive = ZipArchive::new(BufReader::new(file)).map_err(io::Error::other)?;
+			(0..archive.len())
+				.filter_map(|index| archive.by_index_raw(index).ok().map(|entry| entry.size()))
+				.sum()
+		},
+	};
+	(&*file).seek(SeekFrom::Start(0))?;
+	Ok(recorded.max(len))
+}
+
+fn extract_error(err: impl std::fmt::Display) -> Error {
+	Error::from_reason(format!("Failed to extract archive: {err}"))
+}
+
+fn extract_archive_sync(
+	path: &str,
+	dest: &str,
+	strip: usize,
+	allowed: AllowedTypes,
+	ct: &task::CancelToken,
+) -> Result<ExtractArchiveResult> {
+	let mut file = File::open(path)
+		.map_err(|err| Error::from_reason(format!("Failed to open {path}: {err}")))?;
+	let mut header = [0; 512];
+	let mut len = 0;
+	while len < header.len() {
+		match file.read(&mut header[len..]).map_err(extract_error)? {
+			0 => break,
+			read => len += read,
+		}
+	}
+	file.seek(SeekFrom::Start(0)).map_err(extract_error)?;
+	let format = Format::sniff(&header[..len]).ok_or_else(|| {
+		Error::from_reason(format!(
+			"Unsupported archive format: {path} is not a tar, tar.gz, tar.zst or zip archive"
+		))
+	})?;
+
+	fs::create_dir_all(dest)
+		.map_err(|err| Error::from_reason(format!("Failed to create {dest}: {err}")))?;
+	let dest = fs::canonicalize(dest)
+		.map_err(|err| Error::from_reason(format!("Failed to resolve {dest}: {err}")))?;
+	let size = unpacked_size(&file, format, &header[..len]).map_err(extract_error)?;
+	disk::ensure(&dest, size, &format!("extract {path}"))?;
+	let space = disk::space(&dest).map_or(u64::MAX, |(available, _)| available);
+	let mut extractor =
+		Extractor { dest, strip, allowed, space, result: ExtractArchiveResult::default() };
+
+	match format {
+		Format::Tar => extract_tar(BufReader::new(file), &mut extractor, ct)?,
+		Format::TarGz => extract_tar(GzDecoder::new(BufReader::new(file)), &mut extractor, ct)?,
+		Format::TarZst => {
+			let decoder = zstd::Decoder::new(file).map_err(extract_error)?;
+			extract_tar(decoder, &mut extractor, ct)?;
+		},
+		Format::Zip => extract_zip(file, &mut extractor, ct)?,
+	}
+	Ok(extractor.result)
+}
+
+/// Extract a tar, tar.gz, tar.zst or zip archive into `dest`, creating it if
+/// needed.
+///
+/// # Errors
+/// Returns an error if the archive cannot be read or its format is not
+/// recognized, an entry would be written outside `dest`, `path` or `dest` is
+/// outside the workspace, or an option is invalid. Entries extracted before
+/// the error are left in place.
+#[napi(js_name = "extractArchive")]
+pub fn extract_archive(
+	path: String,
+	dest: String,
+	options: Option<ExtractArchiveOptions>,
+) -> Result<task::Async<ExtractArchiveResult>> {
+	let (strip, allowed, allow_outside, ct) = match options {
+		Some(ExtractArchiveOptions {
+			strip_components,
+			allowed_types,
+			signal,
+			timeout_ms,
+			allow_outside_workspace,
+		}) => (
+			strip_components.unwrap_or(0) as usize,
+			AllowedTypes::parse(allowed_types.as_deref())?,
+			allow_outside_workspace,
+			task::CancelToken::new(timeout_ms, signal),
+		),
+		None => (0, AllowedTypes::parse(None)?, None, task::CancelToken::default()),
+	};
+	Ok(task::blocking("extract_archive", ct, move |ct| {
+		workspace::check(&path, allow_outside)?;
+		workspace::check(&dest, allow_outside)?;
+		extract_archive_sync(&path, &dest, strip, allowed, &ct)
+	}))
+}
+
+/// A file, directory or symlink to add to an archive.
+struct Source {
+	path: PathBuf,
+	/// Name inside the archive, `/`-separated.
+	name: String,
+	kind: SourceKind,
+}
+
+enum SourceKind {
+	File,
+	Directory,
+	Symlink(PathBuf),
+}
+
+/// Collect `path` and, for directories, everything below it in name order.
+fn collect_sources(path: &Path, name: String, sources: &mut Vec<Source>) -> io::Result<()> {
+	let meta = fs::symlink_metadata(path)?;
+	if meta.file_type().is_symlink() {
+		let link = fs::read_link(path)?;
+		sources.push(Source { path: path.to_path_buf(), name, kind: SourceKind::Symlink(link) });
+	} else if meta.is_dir() {
+		let mut children: Vec<_> = fs::read_dir(path)?
+			.map(|entry| entry.map(|entry| entry.file_name()))
+			.collect::<io::Result<_>>()?;
+		children.sort();
+		sources.push(Source {
+			path: path.to_path_buf(),
+			name: name.clone(),
+			kind: SourceKind::Directory,
+		});
+		for child in children {
+			let child_name = format!("{name}/{}", child.to_string_lossy());
+			collect_sources(&path.join(&child), child_name, sources)?;
+		}
+	} else {
+		sources.push(Source { path: path.to_path_buf(), name, kind: SourceKind::File });
+	}
+	Ok(())
+}
+
+fn write_tar<W: Write>(writer: W, sources: &[Source], ct: &task::CancelToken) -> Result<W> {
+	let mut builder = tar::Builder::new(writer);
+	builder.follow_symlinks(false);
+	for source in sources {
+		ct.heartbeat()?;
+		builder
+			.append_path_with_name(&source.path, &source.name)
+			.map_err(|err| create_error(&source.path, err))?;
+	}
+	builder
+		.into_inner()
+		.map_err(|err| create_error(Path::new("archive"), err))
+}
+
+fn write_zip(file: File, sources: &[Source], ct: &task::CancelToken) -> Result<File> {
+	let mut writer = ZipWriter::new(file);
+	let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+	for source in sources {
+		ct.heartbeat()?;
+		let error = |err| create_error(&source.path, err);
+		match &source.kind {
+			SourceKind::Directory => {
+				writer
+					.add_directory(&source.name, options)
+					.map_err(|err| error(err.into()))?;
+			},
+			SourceKind::Symlink(link) => {
+				writer
+					.add_symlink(&source.name, link.to_string_lossy(), options)
+					.map_err(|err| error(err.into()))?;
+			},
+			SourceKind::File => {
+				let mut content = File::open(&source.path).map_err(error)?;
+				let options = match file_mode(&content) {
+					Some(mode) => options.unix_permissions(mode),
+					None => options,
+				};
+				writer
+					.start_file(&source.name, options)
+					.map_err(|err| error(err.into()))?;
+				io::copy(&mut content, &mut writer).map_err(error)?;
+			},
+		}
+	}
+	writer
+		.finish()
+		.map_err(|err| create_error(Path::new("archive"), err.into()))
+}
+
+#[cfg(unix)]
+fn file_mode(file: &File) -> Option<u32> {
+	use std::os::unix::fs::PermissionsExt;
+	file
+		.metadata()
+		.ok()
+		.map(|meta| meta.permissions().mode() & 0o777)
+}
+
+#[cfg(windows)]
+const fn file_mode(_file: &File) -> Option<u32> {
+	None
+}
+
+fn create_error(path: &Path, err: io::Error) -> Error {
+	Error::from_reason(format!("Failed to archive {}: {err}", path.display()))
+}
+
+fn create_archive_sync(
+	paths: &[String],
+	dest: &Path,
+	format: Format,
+	ct: &task::CancelToken,
+) -> Result<CreateArchiveResult> {
+	let mut sources = Vec::new();
+	for path in paths {
+		let path = Path::new(path);
+		let name = path
+			.canonicalize()
+			.ok()
+			.as_deref()
+			.and_then(Path::file_name)
+			.map(|name| name.to_string_lossy().into_owned())
+			.ok_or_else(|| {
+				Error::from_reason(format!("Failed to archive {}: no such file", path.display()))
+			})?;
+		collect_sources(path, name, &mut sources).map_err(|err| create_error(path, err))?;
+	}
+	// Leave out an earlier copy of the archive itself.
+	if let Ok(existing) = fs::canonicalize(dest) {
+		sources.retain(|source| fs::canonicalize(&source.path).ok().as_ref() != Some(&existing));
+	}
+
+	if let Some(parent) = dest
+		.parent()
+		.filter(|parent| !parent.as_os_str().is_empty())
+	{
+		fs::create_dir_all(parent).map_err(|err| create_error(dest, err))?;
+	}
+	let file = File::create(dest).map_err(|err| create_error(dest, err))?;
+	let written = match format {
+		Format::TarGz => write_tar(GzEncoder::new(file, Compression::default()), &sources, ct)
+			.and_then(|encoder| encoder.finish().map_err(|err| create_error(dest, err))),
+		Format::TarZst => zstd::Encoder::new(file, 0)
+			.map_err(|err| create_error(dest, err))
+			.and_then(|encoder| write_tar(encoder, &sources, ct))
+			.and_then(|encoder| encoder.finish().map_err(|err| create_error(dest, err))),
+		Format::Tar => write_tar(file, &sources, ct),
+		Format::Zip => write_zip(file, &sources, ct),
+	};
+	let file = match written {
+		Ok(file) => file,
+		Err(err) => {
+			let _ = fs::remove_file(dest);
+			return Err(err);
+		},
+	};
+	let bytes = file
+		.metadata()
+		.map_err(|err| create_error(dest, err))?
+		.len();
+	Ok(CreateArchiveResult { entries: sources.len() as u32, bytes: bytes as f64 })
+}
+
+/// Pack files and directories into a tar, tar.gz, tar.zst or zip archive at
+/// `dest`. Each path is stored under its own name, with directories
+/// recursively; symlinks are stored as links.
+///
+/// # Errors
+/// Returns an error if the format is invalid or cannot be inferred from
+/// `dest`, a path cannot be read, or a path or `dest` is outside the
+/// workspace (unless `allowOutsideWorkspace`). No partial archive is left
+/// behind.
+#[napi(js_name = "createArchive")]
+pub fn create_archive(
+	paths: Vec<String>,
+	dest: String,
+	#[napi(ts_arg_type = "\"tar\" | \"tar.gz\" | \"tar.zst\" | \"zip\" | undefined | null")] format: Option<
+		String,
+	>,
+	allow_outside_workspace: Option<bool>,
+) -> Result<task::Async<CreateArchiveResult>> {
+	let dest = PathBuf::from(dest);
+	let format = Format::parse(format.as_deref(), &dest)?;
+	Ok(task::blocking("create_archive", (), move |ct| {
+		for path in &paths {
+			workspace::check(path, allow_outside_workspace)?;
+		}
+		workspace::check(&dest, allow_outside_workspace)?;
+		create_archive_sync(&paths, &dest, format, &ct)
+	}))
+}
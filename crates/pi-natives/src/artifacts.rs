@@ -0,0 +1,460 @@
+//! Content-addressable store for tool outputs.
+//!
+//! # Overview
+//! Artifacts are addressed by the BLAKE3 hash of their content and stored
+//! zstd-compressed under `~/.omp/agent/artifacts` by default, sharded by the
+//! first two hex digits of the hash. Storing the same content twice keeps a
+//! single copy. Ids are `agent://<hash>` URIs, so they resolve through the
+//! same internal URL router as task outputs.
+//!
+//! Objects are written to a temporary file and renamed into place, so a
+//! reader never sees a partial artifact. Storing or reading an artifact
+//! refreshes its modification time, which `gcArtifacts` uses to evict the
+//! least recently used ones first.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { id } = await native.putArtifact(Buffer.from(output))
+//! // JS: const bytes = await native.getArtifact(id)
+//! // JS: await native.gcArtifacts({ maxAgeMs: 7 * 86_400_000, maxTotalBytes: 512 << 20 })
+//! ```
+
+use std::{
+	cmp::Reverse,
+	collections::HashSet,
+	fs::{self, File},
+	io::{self, Cursor, Read},
+	path::{Path, PathBuf},
+	process,
+	sync::atomic::{AtomicU64, Ordering},
+	time::{Duration, SystemTime},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{store, system_info::disk, task};
+
+/// Scheme of artifact ids.
+const SCHEME: &str = "agent://";
+
+/// zstd compression level; favors speed, tool output compresses well anyway.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Prefix of in-progress writes in the store directory.
+const TEMP_PREFIX: &str = ".tmp-";
+
+/// In-progress writes older than this were left behind by a crash.
+const STALE_TEMP: Duration = Duration::from_hours(1);
+
+/// Distinguishes concurrent writes from the same process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Options selecting the artifact store.
+#[napi(object)]
+#[derive(Default)]
+pub struct ArtifactStoreOptions {
+	/// Store directory (default: `~/.omp/agent/artifacts`).
+	pub dir: Option<String>,
+}
+
+/// Result of `putArtifact`.
+#[napi(object)]
+pub struct StoredArtifact {
+	/// `agent://<hash>` id of the artifact.
+	pub id:           String,
+	/// BLAKE3 hash of the content, lowercase hex.
+	pub hash:         String,
+	/// Content size in bytes.
+	pub size:         f64,
+	/// Compressed size on disk in bytes.
+	#[napi(js_name = "storedSize")]
+	pub stored_size:  f64,
+	/// Whether the content was already stored.
+	pub deduplicated: bool,
+}
+
+/// Eviction policy for `gcArtifacts`.
+#[napi(object)]
+#[derive(Default)]
+pub struct GcArtifactsPolicy {
+	/// Store directory (default: `~/.omp/agent/artifacts`).
+	pub dir:             Option<String>,
+	/// Remove artifacts not stored or read for this many milliseconds.
+	#[napi(js_name = "maxAgeMs")]
+	pub max_age_ms:      Option<f64>,
+	/// Remove the least recently used artifacts until the store is at most
+	/// this many bytes on disk.
+	#[napi(js_name = "maxTotalBytes")]
+	pub max_total_bytes: Option<f64>,
+	/// Ids of artifacts to keep regardless of age and size.
+	pub keep:            Option<Vec<String>>,
+}
+
+/// Result of `gcArtifacts`.
+#[napi(object)]
+pub struct GcArtifactsResult {
+	/// Artifacts removed.
+	pub removed:         u32,
+	/// Bytes on disk freed.
+	#[napi(js_name = "freedBytes")]
+	pub freed_bytes:     f64,
+	/// Artifacts left in the store.
+	pub remaining:       u32,
+	/// Bytes on disk left in the store.
+	#[napi(js_name = "remainingBytes")]
+	pub remaining_bytes: f64,
+}
+
+/// Content to store.
+enum Source {
+	Bytes(Vec<u8>),
+	File(String),
+}
+
+/// Reader that hashes everything read through it.
+struct HashingReader<R> {
+	inner:  R,
+	hasher: blake3::Hasher,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let read = self.inner.read(buf)?;
+		self.hasher.update(&buf[..read]);
+		Ok(read)
+	}
+}
+
+/// A stored object found while collecting garbage.
+struct Object {
+	hash:     String,
+	path:     PathBuf,
+	size:     u64,
+	modified: SystemTime,
+}
+
+/// `artifacts` in the agent directory.
+fn default_dir() -> Result<PathBuf> {
+	store::agent_dir()
+		.map(|dir| dir.join("artifacts"))
+		.ok_or_else(|| Error::from_reason("Cannot locate artifact store: no home directory"))
+}
+
+fn store_dir(dir: Option<String>) -> Result<PathBuf> {
+	dir.map_or_else(default_dir, |dir| Ok(PathBuf::from(dir)))
+}
+
+/// Hash of an artifact id, with or without the `agent://` scheme.
+fn parse_id(id: &str) -> Result<&str> {
+	let hash = id.strip_prefix(SCHEME).unwrap_or(id);
+	if hash.len() == 64
+		&& hash
+			.bytes()
+			.all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+	{
+		Ok(hash)
+	} else {
+		Err(Error::from_reason(format!("Invalid artifact id: {id}")))
+	}
+}
+
+fn object_path(dir: &Path, hash: &str) -> PathBuf {
+	dir.join(&hash[..2]).join(format!("{hash}.zst"))
+}
+
+/// Refresh the modification time of a stored object and return its size,
+/// or `None` if it does not exist.
+fn touch(path: &Path) -> Option<u64> {
+	let meta = fs::metadata(path).ok()?;
+	// Read-only stores still serve artifacts; they just age from creation.
+	if let Ok(file) = File::options().write(true).open(path) {
+		let _ = file.set_modified(SystemTime::now());
+	}
+	Some(meta.len())
+}
+
+fn write_error(err: io::Error) -> Error {
+	Error::from_reason(format!("Failed to write artifact: {err}"))
+}
+
+/// Compress `reader` into a temporary file in `dir`, returning the file and
+/// the hash and size of the content.
+fn write_temp(dir: &Path, reader: impl Read) -> io::Result<(PathBuf, blake3::Hash, u64)> {
+	fs::create_dir_all(dir)?;
+	let temp = dir.join(format!(
+		"{TEMP_PREFIX}{}-{}",
+		process::id(),
+		TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+	));
+	let written = (|| {
+		let mut reader = HashingReader { inner: reader, hasher: blake3::Hasher::new() };
+		let mut encoder = zstd::Encoder::new(File::create(&temp)?, ZSTD_LEVEL)?;
+		io::copy(&mut reader, &mut encoder)?;
+		encoder.finish()?.sync_all()?;
+		Ok((reader.hasher.finalize(), reader.hasher.count()))
+	})();
+	match written {
+		Ok((hash, size)) => Ok((temp, hash, size)),
+		Err(err) => {
+			let _ = fs::remove_file(&temp);
+			Err(err)
+		},
+	}
+}
+
+fn put_artifact_sync(dir: &Path, source: Source) -> Result<StoredArtifact> {
+	let stored = |hash: String, size: u64, stored_size: u64, deduplicated: bool| StoredArtifact {
+		id: format!("{SCHEME}{hash}"),
+		hash,
+		size: size as f64,
+		stored_size: stored_size as f64,
+		deduplicated,
+	};
+
+	// In-memory content can be checked for a stored copy before compressing.
+	if let Source::Bytes(bytes) = &source {
+		let hash = blake3::hash(bytes).to_hex().to_string();
+		if let Some(stored_size) = touch(&object_path(dir, &hash)) {
+			return Ok(stored(hash, bytes.len() as u64, stored_size, true));
+		}
+	}
+
+	// Compressed content is no larger than the source, give or take a header.
+	let (temp, hash, size) = match source {
+		Source::Bytes(bytes) => {
+			disk::ensure(dir, bytes.len() as u64, "store artifact")?;
+			write_temp(dir, Cursor::new(bytes))
+		},
+		Source::File(path) => {
+			let file = File::open(&path)
+				.map_err(|err| Error::from_reason(format!("Failed to read {path}: {err}")))?;
+			let len = file.metadata().map_or(0, |meta| meta.len());
+			disk::ensure(dir, len, &format!("store {path}"))?;
+			write_temp(dir, file)
+		},
+	}
+	.map_err(write_error)?;
+
+	let hash = hash.to_hex().to_string();
+	let path = object_path(dir, &hash);
+	if let Some(stored_size) = touch(&path) {
+		let _ = fs::remove_file(&temp);
+		return Ok(stored(hash, size, stored_size, true));
+	}
+	let placed = path
+		.parent()
+		.map_or(Ok(()), fs::create_dir_all)
+		.and_then(|()| fs::rename(&temp, &path))
+		.and_then(|()| fs::metadata(&path));
+	match placed {
+		Ok(meta) => Ok(stored(hash, size, meta.len(), false)),
+		Err(err) => {
+			let _ = fs::remove_file(&temp);
+			Err(write_error(err))
+		},
+	}
+}
+
+/// Store `bytes` from Rust, in `dir` or the default store.
+pub(crate) fn store_bytes(dir: Option<String>, bytes: Vec<u8>) -> Result<StoredArtifact> {
+	put_artifact_sync(&store_dir(dir)?, Source::Bytes(bytes))
+}
+
+fn get_artifact_sync(dir: &Path, id: &str) -> Result<Option<Vec<u8>>> {
+	let hash = parse_id(id)?;
+	let path = object_path(dir, hash);
+	let file = match File::open(&path) {
+		Ok(file) => file,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(err) => {
+			return Err(Error::from_reason(format!("Failed to read artifact {id}: {err}")));
+		},
+	};
+	let data = zstd::decode_all(file)
+		.map_err(|err| Error::from_reason(format!("Failed to read artifact {id}: {err}")))?;
+	if blake3::hash(&data).to_hex().as_str() != hash {
+		return Err(Error::from_reason(format!("Artifact {id} is corrupt")));
+	}
+	touch(&path);
+	Ok(Some(data))
+}
+
+/// Fail if `bytes` of content would not fit in `dir` or the default store.
+pub(crate) fn ensure_space(dir: Option<String>, bytes: u64, action: &str) -> Result<()> {
+	disk::ensure(&store_dir(dir)?, bytes, action)
+}
+
+/// Store the file at `path` from Rust, in `dir` or the default store.
+pub(crate) fn store_file(dir: Option<String>, path: &Path) -> Result<StoredArtifact> {
+	put_artifact_sync(&store_dir(dir)?, Source::File(path.to_string_lossy().into_owned()))
+}
+
+/// Id the content of the file at `path` is stored under, without storing it.
+pub(crate) fn file_id(path: &Path) -> io::Result<String> {
+	let mut hasher = blake3::Hasher::new();
+	hasher.update_reader(File::open(path)?)?;
+	Ok(format!("{SCHEME}{}", hasher.finalize().to_hex()))
+}
+
+/// Read artifact `id` from Rust, from `dir` or the default store.
+pub(crate) fn load_bytes(dir: Option<String>, id: &str) -> Result<Option<Vec<u8>>> {
+	get_artifact_sync(&store_dir(dir)?, id)
+}
+
+/// Stored objects in `dir`, removing stale temporary files on the way.
+fn collect_objects(dir: &Path, now: SystemTime) -> io::Result<Vec<Object>> {
+	let mut objects = Vec::new();
+	let entries = match fs::read_dir(dir) {
+		Ok(entries) => entries,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(objects),
+		Err(err) => return Err(err),
+	};
+	for entry in entries {
+		let entry = entry?;
+		let name = entry.file_name();
+		let name = name.to_string_lossy();
+		let meta = entry.metadata()?;
+		if name.starts_with(TEMP_PREFIX) {
+			let age = meta
+				.modified()
+				.ok()
+				.and_then(|modified| now.duration_since(modified).ok());
+			if age.is_some_and(|age| age > STALE_TEMP) {
+				let _ = fs::remove_file(entry.path());
+			}
+			continue;
+		}
+		if !meta.is_dir() || name.len() != 2 {
+			continue;
+		}
+		for object in fs::read_dir(entry.path())? {
+			let object = object?;
+			let file_name = object.file_name();
+			let Some(hash) = file_name
+				.to_str()
+				.and_then(|name| name.strip_suffix(".zst"))
+				.filter(|hash| parse_id(hash).is_ok())
+			else {
+				continue;
+			};
+			let meta = object.metadata()?;
+			objects.push(Object {
+				hash:     hash.to_string(),
+				path:     object.path(),
+				size:     meta.len(),
+				modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+			});
+		}
+	}
+	Ok(objects)
+}
+
+fn gc_artifacts_sync(
+	dir: &Path,
+	max_age: Option<Duration>,
+	max_total: Option<u64>,
+	keep: &HashSet<String>,
+) -> Result<GcArtifactsResult> {
+	let now = SystemTime::now();
+	let mut objects = collect_objects(dir, now)
+		.map_err(|err| Error::from_reason(format!("Failed to scan artifact store: {err}")))?;
+	// Newest first, so the size budget goes to the most recently used.
+	objects.sort_by_key(|object| Reverse(object.modified));
+
+	let mut kept_bytes: u64 = objects
+		.iter()
+		.filter(|object| keep.contains(&object.hash))
+		.map(|object| object.size)
+		.sum();
+	let mut result = GcArtifactsResult {
+		removed:         0,
+		freed_bytes:     0.0,
+		remaining:       0,
+		remaining_bytes: 0.0,
+	};
+	for object in &objects {
+		let expired = max_age.is_some_and(|max_age| {
+			now.duration_since(object.modified)
+				.is_ok_and(|age| age > max_age)
+		});
+		let over_budget = max_total.is_some_and(|max_total| kept_bytes + object.size > max_total);
+		let pinned = keep.contains(&object.hash);
+		if !pinned && (expired || over_budget) && fs::remove_file(&object.path).is_ok() {
+			result.removed += 1;
+			result.freed_bytes += object.size as f64;
+			if let Some(shard) = object.path.parent() {
+				// Fails while the shard still holds other objects.
+				let _ = fs::remove_dir(shard);
+			}
+			continue;
+		}
+		if !pinned {
+			kept_bytes += object.size;
+		}
+		result.remaining += 1;
+		result.remaining_bytes += object.size as f64;
+	}
+	Ok(result)
+}
+
+/// Store an artifact, given as bytes or as a path to a file, and return its
+/// `agent://` id. Identical content is stored once.
+///
+/// # Errors
+/// Returns an error if the source file cannot be read or the store cannot be
+/// written.
+#[napi(js_name = "putArtifact")]
+pub fn put_artifact(
+	data: Either<Uint8Array, String>,
+	options: Option<ArtifactStoreOptions>,
+) -> task::Async<StoredArtifact> {
+	let dir = options.unwrap_or_default().dir;
+	let source = match data {
+		Either::A(bytes) => Source::Bytes(bytes.to_vec()),
+		Either::B(path) => Source::File(path),
+	};
+	task::blocking("artifacts.put", (), move |_| put_artifact_sync(&store_dir(dir)?, source))
+}
+
+/// Read an artifact by id, or `null` if it is not stored.
+///
+/// # Errors
+/// Returns an error if the id is malformed, or the stored object cannot be
+/// read or does not match its hash.
+#[napi(js_name = "getArtifact")]
+pub fn get_artifact(
+	id: String,
+	options: Option<ArtifactStoreOptions>,
+) -> task::Async<Option<Buffer>> {
+	let dir = options.unwrap_or_default().dir;
+	task::blocking("artifacts.get", (), move |_| {
+		Ok(get_artifact_sync(&store_dir(dir)?, &id)?.map(Buffer::from))
+	})
+}
+
+/// Remove artifacts that are older than `maxAgeMs` or, least recently used
+/// first, do not fit in `maxTotalBytes`. Artifacts in `keep` are never
+/// removed but count toward the size budget.
+///
+/// # Errors
+/// Returns an error if an id in `keep` is malformed or the store cannot be
+/// scanned.
+#[napi(js_name = "gcArtifacts")]
+pub fn gc_artifacts(policy: Option<GcArtifactsPolicy>) -> Result<task::Async<GcArtifactsResult>> {
+	let policy = policy.unwrap_or_default();
+	let keep = policy
+		.keep
+		.unwrap_or_default()
+		.iter()
+		.map(|id| parse_id(id).map(str::to_string))
+		.collect::<Result<HashSet<_>>>()?;
+	let max_age = policy
+		.max_age_ms
+		.map(|ms| Duration::from_millis(ms.max(0.0) as u64));
+	let max_total = policy.max_total_bytes.map(|bytes| bytes.max(0.0) as u64);
+	let dir = policy.dir;
+	Ok(task::blocking("artifacts.gc", (), move |_| {
+		gc_artifacts_sync(&store_dir(dir)?, max_age, max_total, &keep)
+	}))
+}
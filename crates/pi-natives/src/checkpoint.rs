@@ -0,0 +1,625 @@
+//! Checkpoints of a directory tree.
+//!
+//! # Overview
+//! `createCheckpoint` records every file, symlink and directory under a root
+//! (honoring `.gitignore` by default, never entering `.git`) in the session
+//! store, with file contents in the artifact store. Unchanged content is
+//! stored once, and files whose size and modification time match the root's
+//! previous checkpoint are not read again, so checkpointing after every
+//! agent turn stays cheap. Files over `maxFileSize` are recorded as skipped
+//! and left alone by diffs and restores.
+//!
+//! `diffCheckpoint` lists what changed since a checkpoint, against the tree
+//! as it is now or a later checkpoint, and `restoreCheckpoint` puts the tree
+//! back: changed and deleted entries are rewritten and entries created since
+//! are removed. Nothing depends on git, so this works in any directory.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { id } = await native.createCheckpoint(process.cwd());
+//! // JS: const changes = await native.diffCheckpoint(id);
+//! // JS: await native.restoreCheckpoint(id);
+//! ```
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs, io,
+	path::{Path, PathBuf},
+	time::UNIX_EPOCH,
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::{artifacts, fs_cache, store, task, workspace, write};
+
+const DEFAULT_MAX_FILES: u32 = 20_000;
+
+const DEFAULT_MAX_FILE_SIZE: f64 = 10.0 * 1024.0 * 1024.0;
+
+/// Options for `createCheckpoint`.
+#[napi(object)]
+#[derive(Default)]
+pub struct CreateCheckpointOptions {
+	/// What the checkpoint is, e.g. the turn it was taken before.
+	pub label:                   Option<String>,
+	/// Leave out files ignored by `.gitignore` and similar files (default:
+	/// true).
+	pub gitignore:               Option<bool>,
+	/// Refuse to checkpoint more entries than this (default: 20000).
+	#[napi(js_name = "maxFiles")]
+	pub max_files:               Option<u32>,
+	/// Skip files larger than this many bytes (default: 10 MiB).
+	#[napi(js_name = "maxFileSize")]
+	pub max_file_size:           Option<f64>,
+	/// Database file (default: the session store, as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:                 Option<String>,
+	/// Artifact store for file contents (default: `~/.omp/agent/artifacts`).
+	#[napi(js_name = "artifactsDir")]
+	pub artifacts_dir:           Option<String>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Options for `diffCheckpoint`.
+#[napi(object)]
+#[derive(Default)]
+pub struct DiffCheckpointOptions {
+	/// Later checkpoint of the same root to compare with (default: the tree
+	/// as it is now).
+	pub against: Option<i64>,
+	/// Database file (default: the session store, as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path: Option<String>,
+}
+
+/// Options for `restoreCheckpoint`.
+#[napi(object)]
+#[derive(Default)]
+pub struct RestoreCheckpointOptions {
+	/// Database file (default: the session store, as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:       Option<String>,
+	/// Artifact store the checkpoint was created with (default:
+	/// `~/.omp/agent/artifacts`).
+	#[napi(js_name = "artifactsDir")]
+	pub artifacts_dir: Option<String>,
+}
+
+/// A recorded checkpoint.
+#[napi(object)]
+pub struct Checkpoint {
+	/// Id to pass to `diffCheckpoint` and `restoreCheckpoint`.
+	pub id:          i64,
+	/// Absolute root directory, with symlinks resolved.
+	pub root:        String,
+	/// Label given to `createCheckpoint`.
+	pub label:       Option<String>,
+	/// Files and symlinks recorded.
+	pub files:       u32,
+	/// Directories recorded.
+	pub directories: u32,
+	/// Total size of the recorded files, in bytes.
+	pub bytes:       f64,
+	/// Files over `maxFileSize`, relative to the root, left out.
+	pub skipped:     Vec<String>,
+	/// Creation time in milliseconds since the Unix epoch.
+	#[napi(js_name = "createdAt")]
+	pub created_at:  f64,
+}
+
+/// An entry that differs from a checkpoint.
+#[napi(object)]
+pub struct CheckpointChange {
+	/// Path relative to the root, with `/` separators.
+	pub path:   String,
+	/// How the entry changed since the checkpoint.
+	#[napi(ts_type = "\"added\" | \"modified\" | \"deleted\"")]
+	pub status: String,
+	/// Kind of the entry, now or, when deleted, in the checkpoint.
+	#[napi(ts_type = "\"file\" | \"symlink\" | \"directory\"")]
+	pub kind:   String,
+	/// Artifact id of the file content in the checkpoint.
+	pub before: Option<String>,
+	/// Artifact id of the file content in the checkpoint compared with.
+	pub after:  Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+	File,
+	Symlink,
+	Directory,
+}
+
+impl Kind {
+	const fn as_str(self) -> &'static str {
+		match self {
+			Self::File => "file",
+			Self::Symlink => "symlink",
+			Self::Directory => "directory",
+		}
+	}
+
+	fn parse(kind: &str) -> Self {
+		match kind {
+			"symlink" => Self::Symlink,
+			"directory" => Self::Directory,
+			_ => Self::File,
+		}
+	}
+}
+
+/// An entry of a checkpoint or of the tree as it is now.
+#[derive(Clone)]
+struct Entry {
+	kind:     Kind,
+	/// Content of a file; `None` for a checkpointed file that was skipped,
+	/// and for every file of the current tree.
+	artifact: Option<String>,
+	/// Target of a symlink.
+	link:     Option<String>,
+	/// Unix permission bits.
+	mode:     Option<u32>,
+	size:     u64,
+	mtime:    i64,
+}
+
+/// Checkpoint settings and entries.
+struct Stored {
+	root:      PathBuf,
+	gitignore: bool,
+	entries:   BTreeMap<String, Entry>,
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps, reason = "modes are only recorded on Unix")]
+fn mode_of(meta: &fs::Metadata) -> Option<u32> {
+	use std::os::unix::fs::PermissionsExt;
+	Some(meta.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+const fn mode_of(_meta: &fs::Metadata) -> Option<u32> {
+	None
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+	use std::os::unix::fs::PermissionsExt;
+	fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps, reason = "modes are not recorded on Windows")]
+const fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+	Ok(())
+}
+
+#[cfg(unix)]
+fn make_link(target: &str, path: &Path) -> io::Result<()> {
+	std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn make_link(target: &str, path: &Path) -> io::Result<()> {
+	let resolved = path
+		.parent()
+		.map_or_else(|| PathBuf::from(target), |dir| dir.join(target));
+	if resolved.is_dir() {
+		std::os::windows::fs::symlink_dir(target, path)
+	} else {
+		std::os::windows::fs::symlink_file(target, path)
+	}
+}
+
+fn io_error(action: &str, path: &Path, err: &io::Error) -> Error {
+	Error::from_reason(format!("Failed to {action} {}: {err}", path.display()))
+}
+
+/// Every entry under `root` but the root itself, by relative path.
+fn scan(root: &Path, gitignore: bool, max_files: usize) -> Result<BTreeMap<String, Entry>> {
+	let mut builder = fs_cache::build_walker(root, true, gitignore);
+	builder.filter_entry(|entry| entry.file_name() != ".git");
+	let mut entries = BTreeMap::new();
+	for entry in builder.build() {
+		let entry = entry.map_err(|err| Error::from_reason(format!("Failed to scan: {err}")))?;
+		if entry.depth() == 0 {
+			continue;
+		}
+		let path = entry.path();
+		let meta = fs::symlink_metadata(path).map_err(|err| io_error("read", path, &err))?;
+		let kind = if meta.is_symlink() {
+			Kind::Symlink
+		} else if meta.is_dir() {
+			Kind::Directory
+		} else {
+			Kind::File
+		};
+		let link = if kind == Kind::Symlink {
+			let target = fs::read_link(path).map_err(|err| io_error("read link", path, &err))?;
+			Some(target.to_string_lossy().into_owned())
+		} else {
+			None
+		};
+		let mtime = meta
+			.modified()
+			.ok()
+			.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+			.map_or(0, |since| i64::try_from(since.as_millis()).unwrap_or(i64::MAX));
+		let relative = fs_cache::normalize_relative_path(root, path).into_owned();
+		entries.insert(relative, Entry {
+			kind,
+			artifact: None,
+			link,
+			mode: (kind != Kind::Symlink).then(|| mode_of(&meta)).flatten(),
+			size: if kind == Kind::File { meta.len() } else { 0 },
+			mtime,
+		});
+		if entries.len() > max_files {
+			return Err(Error::from_reason(format!(
+				"Refusing to checkpoint {}: more than {max_files} entries",
+				root.display()
+			)));
+		}
+	}
+	Ok(entries)
+}
+
+fn load_entries(conn: &Connection, id: i64) -> rusqlite::Result<BTreeMap<String, Entry>> {
+	let mut stmt = conn.prepare(
+		"SELECT path, kind, artifact, link, mode, size, mtime FROM checkpoint_files
+		 WHERE checkpoint_id = ?1",
+	)?;
+	let rows = stmt.query_map(params![id], |row| {
+		Ok((row.get::<_, String>(0)?, Entry {
+			kind:     Kind::parse(&row.get::<_, String>(1)?),
+			artifact: row.get(2)?,
+			link:     row.get(3)?,
+			mode:     row.get(4)?,
+			size:     row.get::<_, i64>(5)?.try_into().unwrap_or(0),
+			mtime:    row.get(6)?,
+		}))
+	})?;
+	rows.collect()
+}
+
+fn load(conn: &Connection, id: i64) -> Result<Stored> {
+	let (root, gitignore) = conn
+		.query_row("SELECT root, gitignore FROM checkpoints WHERE id = ?1", params![id], |row| {
+			Ok((row.get::<_, String>(0)?, row.get(1)?))
+		})
+		.optional()
+		.map_err(store::sql_error)?
+		.ok_or_else(|| Error::from_reason(format!("No checkpoint with id {id}")))?;
+	let entries = load_entries(conn, id).map_err(store::sql_error)?;
+	Ok(Stored { root: PathBuf::from(root), gitignore, entries })
+}
+
+fn create_sync(root: &str, options: CreateCheckpointOptions) -> Result<Checkpoint> {
+	workspace::check(root, options.allow_outside_workspace)?;
+	let root_path = workspace::resolve(Path::new(root))?;
+	if !root_path.is_dir() {
+		return Err(Error::from_reason(format!("{root} is not a directory")));
+	}
+	let gitignore = options.gitignore.unwrap_or(true);
+	let max_files = options.max_files.unwrap_or(DEFAULT_MAX_FILES) as usize;
+	let max_file_size = options
+		.max_file_size
+		.unwrap_or(DEFAULT_MAX_FILE_SIZE)
+		.max(0.0) as u64;
+	let mut entries = scan(&root_path, gitignore, max_files)?;
+
+	let store = store::open_store(options.db_path)?;
+	let root_str = root_path.to_string_lossy().into_owned();
+	let previous = {
+		let conn = store.lock();
+		let latest: Option<i64> = conn
+			.query_row("SELECT max(id) FROM checkpoints WHERE root = ?1", params![root_str], |row| {
+				row.get(0)
+			})
+			.map_err(store::sql_error)?;
+		match latest {
+			Some(id) => load_entries(&conn, id).map_err(store::sql_error)?,
+			None => BTreeMap::new(),
+		}
+	};
+
+	let mut checkpoint = Checkpoint {
+		id:          0,
+		root:        root_str,
+		label:       options.label,
+		files:       0,
+		directories: 0,
+		bytes:       0.0,
+		skipped:     Vec::new(),
+		created_at:  store::now_ms() as f64,
+	};
+	// Files to store, changed since the previous checkpoint.
+	let mut pending = Vec::new();
+	let mut pending_bytes = 0;
+	for (relative, entry) in &mut entries {
+		match entry.kind {
+			Kind::Directory => {
+				checkpoint.directories += 1;
+				continue;
+			},
+			Kind::Symlink => {
+				checkpoint.files += 1;
+				continue;
+			},
+			Kind::File => {},
+		}
+		if entry.size > max_file_size {
+			checkpoint.skipped.push(relative.clone());
+			continue;
+		}
+		let unchanged = previous.get(relative).and_then(|old| {
+			(old.kind == Kind::File && old.size == entry.size && old.mtime == entry.mtime)
+				.then(|| old.artifact.clone())
+				.flatten()
+		});
+		if unchanged.is_none() {
+			pending.push(relative.clone());
+			pending_bytes += entry.size;
+		}
+		entry.artifact = unchanged;
+		checkpoint.files += 1;
+		checkpoint.bytes += entry.size as f64;
+	}
+	artifacts::ensure_space(
+		options.artifacts_dir.clone(),
+		pending_bytes,
+		&format!("checkpoint {root}"),
+	)?;
+	for relative in pending {
+		let id = artifacts::store_file(options.artifacts_dir.clone(), &root_path.join(&relative))?.id;
+		if let Some(entry) = entries.get_mut(&relative) {
+			entry.artifact = Some(id);
+		}
+	}
+
+	let mut conn = store.lock();
+	let tx = conn.transaction().map_err(store::sql_error)?;
+	tx.execute(
+		"INSERT INTO checkpoints (root, label, gitignore, created_at) VALUES (?1, ?2, ?3, ?4)",
+		params![checkpoint.root, checkpoint.label, gitignore, checkpoint.created_at as i64],
+	)
+	.map_err(store::sql_error)?;
+	checkpoint.id = tx.last_insert_rowid();
+	{
+		let mut insert = tx
+			.prepare(
+				"INSERT INTO checkpoint_files (checkpoint_id, path, kind, artifact, link, mode, size, \
+				 mtime)
+				 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+			)
+			.map_err(store::sql_error)?;
+		for (relative, entry) in &entries {
+			insert
+				.execute(params![
+					checkpoint.id,
+					relative,
+					entry.kind.as_str(),
+					entry.artifact,
+					entry.link,
+					entry.mode,
+					i64::try_from(entry.size).unwrap_or(i64::MAX),
+					entry.mtime
+				])
+				.map_err(store::sql_error)?;
+		}
+	}
+	tx.commit().map_err(store::sql_error)?;
+	Ok(checkpoint)
+}
+
+/// Whether `entry` differs from checkpointed `old`. `current` entries have
+/// no artifact, so their content is hashed unless size and time match.
+fn differs(root: &Path, relative: &str, old: &Entry, entry: &Entry, current: bool) -> Result<bool> {
+	if old.kind != entry.kind || old.mode != entry.mode {
+		return Ok(true);
+	}
+	Ok(match entry.kind {
+		Kind::Directory => false,
+		Kind::Symlink => old.link != entry.link,
+		Kind::File if !current => old.artifact != entry.artifact,
+		Kind::File if old.size != entry.size => true,
+		Kind::File if old.mtime == entry.mtime => false,
+		Kind::File => {
+			let path = root.join(relative);
+			let id = artifacts::file_id(&path).map_err(|err| io_error("read", &path, &err))?;
+			old.artifact.as_deref() != Some(id.as_str())
+		},
+	})
+}
+
+/// Changes from `old` to `new`, leaving out files skipped by either.
+fn compare(
+	root: &Path,
+	old: &BTreeMap<String, Entry>,
+	new: &BTreeMap<String, Entry>,
+	current: bool,
+) -> Result<Vec<CheckpointChange>> {
+	let skipped = |entry: &Entry| entry.kind == Kind::File && entry.artifact.is_none();
+	let paths: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+	let mut changes = Vec::new();
+	for path in paths {
+		let (before, after) = (old.get(path), new.get(path));
+		if before.is_some_and(skipped) || (!current && after.is_some_and(skipped)) {
+			continue;
+		}
+		let status = match (before, after) {
+			(Some(_), None) => "deleted",
+			(None, Some(_)) => "added",
+			(Some(old), Some(new)) if differs(root, path, old, new, current)? => "modified",
+			_ => continue,
+		};
+		let kind = after.or(before).map_or(Kind::File, |entry| entry.kind);
+		changes.push(CheckpointChange {
+			path:   path.clone(),
+			status: status.to_string(),
+			kind:   kind.as_str().to_string(),
+			before: before.and_then(|entry| entry.artifact.clone()),
+			after:  after.and_then(|entry| entry.artifact.clone()),
+		});
+	}
+	Ok(changes)
+}
+
+fn diff_sync(id: i64, options: DiffCheckpointOptions) -> Result<Vec<CheckpointChange>> {
+	let store = store::open_store(options.db_path)?;
+	let (checkpoint, against) = {
+		let conn = store.lock();
+		let checkpoint = load(&conn, id)?;
+		let against = options
+			.against
+			.map(|other| load(&conn, other))
+			.transpose()?;
+		(checkpoint, against)
+	};
+	let Some(against) = against else {
+		let current = scan(&checkpoint.root, checkpoint.gitignore, usize::MAX)?;
+		return compare(&checkpoint.root, &checkpoint.entries, &current, true);
+	};
+	if against.root != checkpoint.root {
+		return Err(Error::from_reason(format!(
+			"Checkpoints {id} and {} are of different directories",
+			options.against.unwrap_or_default()
+		)));
+	}
+	compare(&checkpoint.root, &checkpoint.entries, &against.entries, false)
+}
+
+/// Remove the entry at `path` without following symlinks.
+fn remove_entry(path: &Path) -> io::Result<()> {
+	match fs::symlink_metadata(path) {
+		Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+		Ok(_) => fs::remove_file(path).or_else(|err| {
+			// Directory symlinks on Windows are removed as directories.
+			if cfg!(windows) {
+				fs::remove_dir(path)
+			} else {
+				Err(err)
+			}
+		}),
+		Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+		Err(err) => Err(err),
+	}
+}
+
+fn restore_sync(id: i64, options: RestoreCheckpointOptions) -> Result<Vec<CheckpointChange>> {
+	let store = store::open_store(options.db_path)?;
+	let checkpoint = load(&store.lock(), id)?;
+	let root = &checkpoint.root;
+	if !root.is_dir() {
+		return Err(Error::from_reason(format!("{} no longer exists", root.display())));
+	}
+	let current = scan(root, checkpoint.gitignore, usize::MAX)?;
+	let changes = compare(root, &checkpoint.entries, &current, true)?;
+
+	// Load every content first, so an evicted artifact changes nothing.
+	let mut contents = BTreeMap::new();
+	for change in &changes {
+		if let Some(artifact) = &change.before
+			&& change.status != "added"
+		{
+			let bytes =
+				artifacts::load_bytes(options.artifacts_dir.clone(), artifact)?.ok_or_else(|| {
+					Error::from_reason(format!(
+						"Cannot restore checkpoint {id}: the content of {} is no longer stored",
+						change.path
+					))
+				})?;
+			contents.insert(change.path.as_str(), bytes);
+		}
+	}
+
+	// Entries created since go first, deepest first, then the rest is put
+	// back parents first.
+	for change in changes
+		.iter()
+		.rev()
+		.filter(|change| change.status == "added")
+	{
+		let path = root.join(&change.path);
+		if change.kind == Kind::Directory.as_str() {
+			// A directory holding ignored files stays.
+			let _ = fs::remove_dir(&path);
+		} else {
+			remove_entry(&path).map_err(|err| io_error("remove", &path, &err))?;
+		}
+	}
+	for change in changes.iter().filter(|change| change.status != "added") {
+		let path = root.join(&change.path);
+		let entry = &checkpoint.entries[&change.path];
+		let current_kind = current.get(&change.path).map(|entry| entry.kind);
+		let restored = (|| {
+			if current_kind.is_some_and(|kind| kind != entry.kind || kind == Kind::Symlink) {
+				remove_entry(&path)?;
+			}
+			match entry.kind {
+				Kind::Directory => fs::create_dir_all(&path)?,
+				Kind::Symlink => make_link(entry.link.as_deref().unwrap_or_default(), &path)?,
+				Kind::File => {
+					if let Some(parent) = path.parent() {
+						fs::create_dir_all(parent)?;
+					}
+					write::replace_file(&path, &contents[change.path.as_str()])?;
+				},
+			}
+			match entry.mode {
+				Some(mode) => set_mode(&path, mode),
+				None => Ok(()),
+			}
+		})();
+		restored.map_err(|err| io_error("restore", &path, &err))?;
+	}
+	Ok(changes)
+}
+
+/// Record the files, symlinks and directories under `root` so they can be
+/// compared with and restored later.
+///
+/// # Errors
+/// Returns an error if the root is outside the workspace, is not a
+/// directory, holds more than `maxFiles` entries, or cannot be read or
+/// stored.
+#[napi(js_name = "createCheckpoint")]
+pub fn create_checkpoint(
+	root: String,
+	options: Option<CreateCheckpointOptions>,
+) -> task::Async<Checkpoint> {
+	task::blocking("checkpoint.create", (), move |_| create_sync(&root, options.unwrap_or_default()))
+}
+
+/// Entries that changed since checkpoint `id`, by path.
+///
+/// # Errors
+/// Returns an error for unknown checkpoints, checkpoints of different roots,
+/// or a tree that cannot be read.
+#[napi(js_name = "diffCheckpoint")]
+pub fn diff_checkpoint(
+	id: i64,
+	options: Option<DiffCheckpointOptions>,
+) -> task::Async<Vec<CheckpointChange>> {
+	task::blocking("checkpoint.diff", (), move |_| diff_sync(id, options.unwrap_or_default()))
+}
+
+/// Put the tree under the root of checkpoint `id` back the way it was,
+/// returning the changes undone.
+///
+/// # Errors
+/// Returns an error for unknown checkpoints or evicted contents, in which
+/// case nothing changes, or when an entry cannot be restored, leaving the
+/// entries before it restored.
+#[napi(js_name = "restoreCheckpoint")]
+pub fn restore_checkpoint(
+	id: i64,
+	options: Option<RestoreCheckpointOptions>,
+) -> task::Async<Vec<CheckpointChange>> {
+	task::blocking("checkpoint.restore", (), move |_| restore_sync(id, options.unwrap_or_default()))
+}
@@ -1,8 +1,8 @@
 //! Clipboard utilities backed by arboard.
 //!
 //! # Overview
-//! Provides text copy and image read support across Linux, macOS, and Windows
-//! without shelling out to platform-specific commands.
+//! Provides text and image read/write support across Linux, macOS, and
+//! Windows without shelling out to platform-specific commands.
 //!
 //! # Example
 //! ```ignore
@@ -14,10 +14,10 @@
 //! # }
 //! ```
 
-use std::io::Cursor;
+use std::{borrow::Cow, io::Cursor};
 
 use arboard::{Clipboard, Error as ClipboardError, ImageData};
-use image::{DynamicImage, ImageFormat, RgbaImage};
+use image::{DynamicImage, ImageFormat, ImageReader, RgbaImage};
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
@@ -49,6 +49,30 @@ fn encode_png(image: ImageData<'_>) -> Result<Vec<u8>> {
 	Ok(output)
 }
 
+fn decode_rgba(bytes: &[u8]) -> Result<ImageData<'static>> {
+	let image = ImageReader::new(Cursor::new(bytes))
+		.with_guessed_format()
+		.map_err(|err| Error::from_reason(format!("Failed to detect image format: {err}")))?
+		.decode()
+		.map_err(|err| Error::from_reason(format!("Failed to decode image: {err}")))?
+		.into_rgba8();
+	Ok(ImageData {
+		width:  image.width() as usize,
+		height: image.height() as usize,
+		bytes:  Cow::Owned(image.into_raw()),
+	})
+}
+
+fn open_clipboard() -> Result<Clipboard> {
+	Clipboard::new().map_err(|err| Error::from_reason(format!("Failed to access clipboard: {err}")))
+}
+
+fn set_text(text: String) -> Result<()> {
+	open_clipboard()?
+		.set_text(text)
+		.map_err(|err| Error::from_reason(format!("Failed to copy to clipboard: {err}")))
+}
+
 /// Copy plain text to the system clipboard.
 ///
 /// # Parameters
@@ -58,13 +82,53 @@ fn encode_png(image: ImageData<'_>) -> Result<Vec<u8>> {
 /// Returns an error if clipboard access fails.
 #[napi(js_name = "copyToClipboard")]
 pub fn copy_to_clipboard(text: String) -> task::Async<()> {
-	task::blocking("clipboard.copy", (), move |_| -> Result<()> {
-		let mut clipboard = Clipboard::new()
-			.map_err(|err| Error::from_reason(format!("Failed to access clipboard: {err}")))?;
-		clipboard
-			.set_text(text)
-			.map_err(|err| Error::from_reason(format!("Failed to copy to clipboard: {err}")))?;
-		Ok(())
+	task::blocking("clipboard.copy", (), move |_| set_text(text))
+}
+
+/// Write plain text to the system clipboard.
+///
+/// Same as `copyToClipboard`; the JS wrapper of this one reports failures
+/// instead of swallowing them.
+///
+/// # Errors
+/// Returns an error if clipboard access fails.
+#[napi(js_name = "writeClipboardText")]
+pub fn write_clipboard_text(text: String) -> task::Async<()> {
+	task::blocking("clipboard.write_text", (), move |_| set_text(text))
+}
+
+/// Read plain text from the system clipboard.
+///
+/// Returns `Ok(None)` when the clipboard holds no text.
+///
+/// # Errors
+/// Returns an error if clipboard access fails.
+#[napi(js_name = "readClipboardText")]
+pub fn read_clipboard_text() -> task::Async<Option<String>> {
+	task::blocking("clipboard.read_text", (), move |_| -> Result<Option<String>> {
+		match open_clipboard()?.get_text() {
+			Ok(text) => Ok(Some(text)),
+			Err(ClipboardError::ContentNotAvailable) => Ok(None),
+			Err(err) => Err(Error::from_reason(format!("Failed to read clipboard text: {err}"))),
+		}
+	})
+}
+
+/// Place an image on the system clipboard.
+///
+/// # Parameters
+/// - `data`: Encoded image bytes (PNG, JPEG, WebP, or GIF).
+///
+/// # Errors
+/// Returns an error if the image cannot be decoded or clipboard access fails.
+#[napi(js_name = "writeImageToClipboard")]
+pub fn write_image_to_clipboard(data: Uint8Array) -> task::Async<()> {
+	let bytes = data.to_vec();
+	task::blocking("clipboard.write_image", (), move |_| -> Result<()> {
+		let image = decode_rgba(&bytes)?;
+		open_clipboard()?
+			.set_image(image)
+			.map_err(|err| Error::from_reason(format!("Failed to copy image to clipboard: {err}")))
 	})
 }
 
@@ -77,9 +141,7 @@ pub fn copy_to_clipboard(text: String) -> task::Async<()> {
 #[napi(js_name = "readImageFromClipboard")]
 pub fn read_image_from_clipboard() -> task::Async<Option<ClipboardImage>> {
 	task::blocking("clipboard.read_image", (), move |_| -> Result<Option<ClipboardImage>> {
-		let mut clipboard = Clipboard::new()
-			.map_err(|err| Error::from_reason(format!("Failed to access clipboard: {err}")))?;
-		match clipboard.get_image() {
+		match open_clipboard()?.get_image() {
 			Ok(image) => {
 				let bytes = encode_png(image)?;
 				Ok(Some(ClipboardImage {
@@ -0,0 +1,852 @@
+//! Structured diagnostics from compiler, linter and test runner output.
+//!
+//! # Overview
+//! Output is read line by line and matched against the formats of common
+//! toolchains:
+//! - **cargo**: rustc errors and warnings (human and `--message-format=json`)
+//!   and test panics
+//! - **tsc**: plain and `--pretty` output
+//! - **eslint**: stylish (default), compact, unix and json formatters
+//! - **pytest**: traceback locations, with the `E` lines as message, and the
+//!   short test summary
+//! - **go**: build and vet errors and `go test` failures
+//! - **gcc**: gcc and clang errors, warnings and notes (also mypy and other
+//!   tools using the `file:line:col: severity: message` convention)
+//!
+//! Multi-line diagnostics are kept together: a diagnostic is complete once a
+//! line that cannot continue it arrives, so the streaming parser holds the
+//! last one back until more output or `finish()`. ANSI colors are ignored.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const diagnostics = native.parseDiagnostics(output, { tool: "tsc" })
+//! // JS: const parser = new native.DiagnosticParser(); parser.push(chunk); parser.finish()
+//! ```
+
+use std::{collections::HashSet, sync::LazyLock};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use regex::Regex;
+use serde_json::Value;
+
+static ANSI: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)").unwrap()
+});
+static CARGO_HEADER: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^(error|warning)(?:\[(\w+)\])?: (.+)$").unwrap());
+static CARGO_LOCATION: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^\s*--> (.+?):(\d+):(\d+)$").unwrap());
+static CARGO_PANIC: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^thread '(.*)' panicked at (.+?):(\d+):(\d+):$").unwrap());
+static CARGO_PANIC_OLD: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^thread '.*' panicked at '(.*)', (.+?):(\d+):(\d+)$").unwrap());
+static TSC: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"^(.+?)(?:\((\d+),(\d+)\):|:(\d+):(\d+) -) (error|warning|message) (TS\d+): (.*)$")
+		.unwrap()
+});
+static GCC: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(
+		r"^(.+?):(\d+)(?::(\d+))?: (fatal error|error|warning|note|remark): (.*?)(?: +\[(-W[^\]]+|[a-z][\w-]*)\])?$",
+	)
+	.unwrap()
+});
+static GO: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^(\s*)(\S+\.go):(\d+)(?::(\d+))?: (.*)$").unwrap());
+static ESLINT_ENTRY: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"^\s+(\d+):(\d+)\s+(error|warning)\s+(.+?)(?:\s{2,}(\S+))?$").unwrap()
+});
+static ESLINT_FILE: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^(?:[A-Za-z]:)?[^\s:]*[/\\]\S*\.\w+$").unwrap());
+static ESLINT_COMPACT: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"^(.+?): line (\d+), col (\d+), (Error|Warning) - (.*?)(?: \(([^()\s]+)\))?$")
+		.unwrap()
+});
+static ESLINT_UNIX: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"^(.+?):(\d+):(\d+): (.*) \[(Error|Warning)(?:/([^\]]+))?\]$").unwrap()
+});
+static PYTEST_SECTION: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^_{3,} (.+?) _{3,}$").unwrap());
+static PYTEST_LOCATION: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^(\S+\.py):(\d+): (.+)$").unwrap());
+static PYTEST_SUMMARY: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"^(FAILED|ERROR) (\S+?\.py)(?:::(\S+))?(?: - (.*))?$").unwrap());
+
+/// Toolchain whose output format to parse.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tool {
+	Cargo,
+	Tsc,
+	Eslint,
+	Pytest,
+	Go,
+	Gcc,
+}
+
+impl Tool {
+	fn parse(option: Option<&str>) -> Result<Option<Self>> {
+		match option {
+			None => Ok(None),
+			Some("cargo") => Ok(Some(Self::Cargo)),
+			Some("tsc") => Ok(Some(Self::Tsc)),
+			Some("eslint") => Ok(Some(Self::Eslint)),
+			Some("pytest") => Ok(Some(Self::Pytest)),
+			Some("go") => Ok(Some(Self::Go)),
+			Some("gcc") => Ok(Some(Self::Gcc)),
+			Some(other) => Err(Error::from_reason(format!(
+				"Invalid diagnostics tool: {other} (expected \"cargo\", \"tsc\", \"eslint\", \
+				 \"pytest\", \"go\" or \"gcc\")"
+			))),
+		}
+	}
+
+	const fn name(self) -> &'static str {
+		match self {
+			Self::Cargo => "cargo",
+			Self::Tsc => "tsc",
+			Self::Eslint => "eslint",
+			Self::Pytest => "pytest",
+			Self::Go => "go",
+			Self::Gcc => "gcc",
+		}
+	}
+}
+
+/// A compiler error, lint warning or test failure.
+#[napi(object)]
+pub struct Diagnostic {
+	/// Format the diagnostic was recognized as.
+	#[napi(ts_type = "DiagnosticTool")]
+	pub tool:       String,
+	/// File as printed by the tool, usually relative to its working
+	/// directory.
+	pub file:       String,
+	/// 1-based line, when reported.
+	pub line:       Option<u32>,
+	/// 1-based column, when reported.
+	pub column:     Option<u32>,
+	/// 1-based last line of the span, when reported.
+	#[napi(js_name = "endLine")]
+	pub end_line:   Option<u32>,
+	/// 1-based column after the span, when reported.
+	#[napi(js_name = "endColumn")]
+	pub end_column: Option<u32>,
+	/// Severity; tools' other levels (help, remark, message) map to `note`.
+	#[napi(ts_type = "\"error\" | \"warning\" | \"note\"")]
+	pub severity:   String,
+	/// Message, including continuation lines.
+	pub message:    String,
+	/// Error code, lint rule or exception type (`E0308`, `TS2322`,
+	/// `no-unused-vars`, `-Wunused-variable`, `AssertionError`).
+	pub code:       Option<String>,
+	/// Source excerpt and notes printed with the diagnostic.
+	pub detail:     Option<String>,
+}
+
+/// Options for `parseDiagnostics` and `DiagnosticParser`.
+#[napi(object)]
+#[derive(Default)]
+pub struct DiagnosticOptions {
+	/// Only recognize this tool's format (default: all).
+	#[napi(ts_type = "DiagnosticTool")]
+	pub tool: Option<String>,
+}
+
+/// How lines after a diagnostic's first line extend it.
+#[derive(Clone, Copy)]
+enum Continuation {
+	/// rustc: every line up to a blank one, the location among them.
+	Cargo,
+	/// Test panic: the message follows on its own lines.
+	Panic,
+	/// Indented lines extend the message.
+	Message,
+	/// Indented lines are a source excerpt.
+	Detail,
+	/// Lines indented deeper than the first extend the message.
+	Deeper(usize),
+}
+
+struct Pending {
+	diagnostic:   Diagnostic,
+	continuation: Continuation,
+}
+
+/// Line-by-line diagnostic recognizer.
+struct Parser {
+	tool:            Option<Tool>,
+	pending:         Option<Pending>,
+	/// Line not yet terminated, when streaming.
+	partial:         String,
+	/// File whose problems eslint's stylish format is listing.
+	eslint_file:     Option<String>,
+	/// Test of the current pytest failure section.
+	pytest_test:     Option<String>,
+	/// `E` lines of the current pytest traceback.
+	pytest_errors:   Vec<String>,
+	/// Innermost frame of the current pytest traceback, for `--tb=short`,
+	/// which prints no location after the `E` lines.
+	pytest_frame:    Option<(String, u32)>,
+	/// Tests already reported from their traceback.
+	pytest_reported: HashSet<String>,
+	done:            Vec<Diagnostic>,
+}
+
+fn number(text: Option<regex::Match>) -> Option<u32> {
+	text.and_then(|text| text.as_str().parse().ok())
+}
+
+fn diagnostic(tool: Tool, file: &str, severity: &str, message: &str) -> Diagnostic {
+	Diagnostic {
+		tool:       tool.name().to_string(),
+		file:       file.to_string(),
+		line:       None,
+		column:     None,
+		end_line:   None,
+		end_column: None,
+		severity:   severity.to_string(),
+		message:    message.trim().to_string(),
+		code:       None,
+		detail:     None,
+	}
+}
+
+fn append(text: &mut Option<String>, line: &str) {
+	match text {
+		Some(text) => {
+			text.push('\n');
+			text.push_str(line);
+		},
+		None => *text = Some(line.to_string()),
+	}
+}
+
+fn indent(line: &str) -> usize {
+	line.len() - line.trim_start().len()
+}
+
+/// Test name without parameters or class, to match section headers with
+/// summary node ids.
+fn test_key(name: &str) -> &str {
+	let name = name.split('[').next().unwrap_or(name);
+	name.rsplit(['.', ':']).next().unwrap_or(name)
+}
+
+impl Parser {
+	fn new(tool: Option<Tool>) -> Self {
+		Self {
+			tool,
+			pending: None,
+			partial: String::new(),
+			eslint_file: None,
+			pytest_test: None,
+			pytest_errors: Vec::new(),
+			pytest_frame: None,
+			pytest_reported: HashSet::new(),
+			done: Vec::new(),
+		}
+	}
+
+	fn enabled(&self, tool: Tool) -> bool {
+		self.tool.is_none_or(|only| only == tool)
+	}
+
+	fn start(&mut self, diagnostic: Diagnostic, continuation: Continuation) {
+		self.pending = Some(Pending { diagnostic, continuation });
+	}
+
+	fn emit(&mut self, diagnostic: Diagnostic) {
+		if !diagnostic.file.is_empty() {
+			self.done.push(diagnostic);
+		}
+	}
+
+	fn flush(&mut self) {
+		if let Some(pending) = self.pending.take() {
+			self.emit(pending.diagnostic);
+		}
+	}
+
+	/// Feed streamed output; complete lines are parsed, the rest is kept.
+	fn push(&mut self, chunk: &str) {
+		self.partial.push_str(chunk);
+		let Some(end) = self.partial.rfind('\n') else {
+			return;
+		};
+		let rest = self.partial.split_off(end + 1);
+		let complete = std::mem::replace(&mut self.partial, rest);
+		for line in complete.lines() {
+			self.line(line);
+		}
+	}
+
+	fn finish(&mut self) {
+		let partial = std::mem::take(&mut self.partial);
+		if !partial.is_empty() {
+			self.line(&partial);
+		}
+		self.flush();
+		self.pytest_traceback_end();
+	}
+
+	fn take(&mut self) -> Vec<Diagnostic> {
+		std::mem::take(&mut self.done)
+	}
+
+	fn line(&mut self, raw: &str) {
+		let stripped;
+		let line = if raw.contains('\x1b') {
+			stripped = ANSI.replace_all(raw, "");
+			stripped.trim_end_matches('\r')
+		} else {
+			raw.trim_end_matches('\r')
+		};
+		if self.continue_pending(line) {
+			return;
+		}
+		self.flush();
+		self.start_line(line);
+	}
+
+	/// Extend the pending diagnostic with `line`, if it belongs to it.
+	fn continue_pending(&mut self, line: &str) -> bool {
+		let Some(pending) = &mut self.pending else {
+			return false;
+		};
+		let diagnostic = &mut pending.diagnostic;
+		let blank = line.trim().is_empty();
+		match pending.continuation {
+			Continuation::Cargo => {
+				if blank || CARGO_HEADER.is_match(line) {
+					return false;
+				}
+				if diagnostic.file.is_empty()
+					&& let Some(caps) = CARGO_LOCATION.captures(line)
+				{
+					diagnostic.file = caps[1].to_string();
+					diagnostic.line = number(caps.get(2));
+					diagnostic.column = number(caps.get(3));
+				}
+				append(&mut diagnostic.detail, line);
+			},
+			Continuation::Panic => {
+				if blank || line.starts_with("note:") || line.starts_with("stack backtrace:") {
+					return false;
+				}
+				if diagnostic.message.is_empty() {
+					diagnostic.message = line.trim().to_string();
+				} else {
+					diagnostic.message.push('\n');
+					diagnostic.message.push_str(line.trim_end());
+				}
+			},
+			Continuation::Message => {
+				if blank || indent(line) == 0 {
+					return false;
+				}
+				diagnostic.message.push('\n');
+				diagnostic.message.push_str(line.trim());
+			},
+			Continuation::Detail => {
+				if blank || indent(line) == 0 {
+					return false;
+				}
+				append(&mut diagnostic.detail, line);
+			},
+			Continuation::Deeper(depth) => {
+				if blank || indent(line) <= depth {
+					return false;
+				}
+				diagnostic.message.push('\n');
+				diagnostic.message.push_str(line.trim());
+			},
+		}
+		true
+	}
+
+	fn start_line(&mut self, line: &str) {
+		if line.starts_with('{') || line.starts_with('[') {
+			self.json_line(line);
+			return;
+		}
+		if self.enabled(Tool::Cargo) && self.cargo_line(line) {
+			return;
+		}
+		if self.enabled(Tool::Tsc)
+			&& let Some(caps) = TSC.captures(line)
+		{
+			let severity = if &caps[6] == "message" {
+				"note"
+			} else {
+				&caps[6]
+			};
+			let mut diagnostic = diagnostic(Tool::Tsc, &caps[1], severity, &caps[8]);
+			diagnostic.line = number(caps.get(2).or_else(|| caps.get(4)));
+			diagnostic.column = number(caps.get(3).or_else(|| caps.get(5)));
+			diagnostic.code = Some(caps[7].to_string());
+			self.start(diagnostic, Continuation::Message);
+			return;
+		}
+		if self.enabled(Tool::Eslint) && self.eslint_line(line) {
+			return;
+		}
+		if self.enabled(Tool::Gcc)
+			&& let Some(caps) = GCC.captures(line)
+		{
+			let severity = match &caps[4] {
+				"fatal error" | "error" => "error",
+				"warning" => "warning",
+				_ => "note",
+			};
+			let mut diagnostic = diagnostic(Tool::Gcc, &caps[1], severity, &caps[5]);
+			diagnostic.line = number(caps.get(2));
+			diagnostic.column = number(caps.get(3));
+			diagnostic.code = caps.get(6).map(|code| code.as_str().to_string());
+			self.start(diagnostic, Continuation::Detail);
+			return;
+		}
+		if self.enabled(Tool::Go)
+			&& let Some(caps) = GO.captures(line)
+		{
+			let mut diagnostic = diagnostic(Tool::Go, &caps[2], "error", &caps[5]);
+			diagnostic.line = number(caps.get(3));
+			diagnostic.column = number(caps.get(4));
+			self.start(diagnostic, Continuation::Deeper(caps[1].len()));
+			return;
+		}
+		if self.enabled(Tool::Pytest) {
+			self.pytest_line(line);
+		}
+	}
+
+	fn cargo_line(&mut self, line: &str) -> bool {
+		if let Some(caps) = CARGO_HEADER.captures(line) {
+			let mut diagnostic = diagnostic(Tool::Cargo, "", &caps[1], &caps[3]);
+			diagnostic.code = caps.get(2).map(|code| code.as_str().to_string());
+			self.start(diagnostic, Continuation::Cargo);
+			return true;
+		}
+		if let Some(caps) = CARGO_PANIC.captures(line) {
+			let mut diagnostic = diagnostic(Tool::Cargo, &caps[2], "error", "");
+			diagnostic.line = number(caps.get(3));
+			diagnostic.column = number(caps.get(4));
+			diagnostic.code = Some("panic".to_string());
+			diagnostic.detail = Some(format!("thread '{}' panicked", &caps[1]));
+			self.start(diagnostic, Continuation::Panic);
+			return true;
+		}
+		if let Some(caps) = CARGO_PANIC_OLD.captures(line) {
+			let mut diagnostic = diagnostic(Tool::Cargo, &caps[2], "error", &caps[1]);
+			diagnostic.line = number(caps.get(3));
+			diagnostic.column = number(caps.get(4));
+			diagnostic.code = Some("panic".to_string());
+			self.emit(diagnostic);
+			return true;
+		}
+		false
+	}
+
+	fn eslint_line(&mut self, line: &str) -> bool {
+		if let Some(file) = &self.eslint_file
+			&& let Some(caps) = ESLINT_ENTRY.captures(line)
+		{
+			let mut diagnostic = diagnostic(Tool::Eslint, file, &caps[3], &caps[4]);
+			diagnostic.line = number(caps.get(1));
+			diagnostic.column = number(caps.get(2));
+			diagnostic.code = caps.get(5).map(|code| code.as_str().to_string());
+			self.emit(diagnostic);
+			return true;
+		}
+		// Compact puts the severity before the message, unix after it.
+		let found = ESLINT_COMPACT
+			.captures(line)
+			.map(|caps| (caps, 4, 5))
+			.or_else(|| ESLINT_UNIX.captures(line).map(|caps| (caps, 5, 4)));
+		if let Some((caps, severity, message)) = found {
+			let severity = caps[severity].to_ascii_lowercase();
+			let mut diagnostic = diagnostic(Tool::Eslint, &caps[1], &severity, &caps[message]);
+			diagnostic.line = number(caps.get(2));
+			diagnostic.column = number(caps.get(3));
+			diagnostic.code = caps.get(6).map(|code| code.as_str().to_string());
+			self.emit(diagnostic);
+			return true;
+		}
+		if ESLINT_FILE.is_match(line) {
+			self.eslint_file = Some(line.to_string());
+			return true;
+		}
+		false
+	}
+
+	fn pytest_line(&mut self, line: &str) {
+		if let Some(caps) = PYTEST_SECTION.captures(line) {
+			self.pytest_traceback_end();
+			self.pytest_test = Some(caps[1].to_string());
+			return;
+		}
+		if line.starts_with("====") {
+			self.pytest_traceback_end();
+			self.pytest_test = None;
+			return;
+		}
+		if self.pytest_test.is_some()
+			&& let Some(error) = line.strip_prefix('E')
+			&& (error.is_empty() || error.starts_with(' '))
+		{
+			self.pytest_errors.push(error.trim().to_string());
+			return;
+		}
+		if let Some(caps) = PYTEST_LOCATION.captures(line) {
+			let text = &caps[3];
+			if text
+				.strip_prefix("in ")
+				.is_some_and(|function| !function.contains(' '))
+			{
+				self.pytest_frame = number(caps.get(2)).map(|line| (caps[1].to_string(), line));
+				return;
+			}
+			let name = text.split(':').next().unwrap_or(text);
+			let code = (!name.is_empty()
+				&& name
+					.chars()
+					.all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '.'))
+			.then(|| name.to_string());
+			let severity = if code
+				.as_deref()
+				.is_some_and(|code| code.ends_with("Warning"))
+			{
+				"warning"
+			} else {
+				"error"
+			};
+			let message = if self.pytest_errors.is_empty() {
+				text.to_string()
+			} else {
+				self.pytest_errors.join("\n")
+			};
+			let mut diagnostic = diagnostic(Tool::Pytest, &caps[1], severity, &message);
+			diagnostic.line = number(caps.get(2));
+			diagnostic.code = code;
+			self.pytest_report(diagnostic);
+			return;
+		}
+		if let Some(caps) = PYTEST_SUMMARY.captures(line) {
+			let test = caps.get(3).map(|test| test_key(test.as_str()));
+			if test.is_some_and(|test| self.pytest_reported.contains(test)) {
+				return;
+			}
+			let message = caps
+				.get(4)
+				.map_or_else(|| caps[1].to_lowercase(), |message| message.as_str().to_string());
+			let mut diagnostic = diagnostic(Tool::Pytest, &caps[2], "error", &message);
+			diagnostic.detail = caps.get(3).map(|test| test.as_str().to_string());
+			self.emit(diagnostic);
+		}
+	}
+
+	fn pytest_report(&mut self, diagnostic: Diagnostic) {
+		self.pytest_errors.clear();
+		self.pytest_frame = None;
+		if let Some(test) = &self.pytest_test {
+			self.pytest_reported.insert(test_key(test).to_string());
+		}
+		self.emit(diagnostic);
+	}
+
+	/// Report a traceback that ended without a final location line.
+	fn pytest_traceback_end(&mut self) {
+		if self.pytest_errors.is_empty() {
+			return;
+		}
+		match self.pytest_frame.take() {
+			Some((file, line)) => {
+				let message = self.pytest_errors.join("\n");
+				let mut diagnostic = diagnostic(Tool::Pytest, &file, "error", &message);
+				diagnostic.line = Some(line);
+				self.pytest_report(diagnostic);
+			},
+			None => self.pytest_errors.clear(),
+		}
+	}
+
+	/// `cargo --message-format=json` and `eslint -f json` output.
+	fn json_line(&mut self, line: &str) {
+		let Ok(value) = serde_json::from_str::<Value>(line) else {
+			return;
+		};
+		if self.enabled(Tool::Cargo) && value["reason"] == "compiler-message" {
+			self.cargo_json(&value["message"]);
+		} else if self.enabled(Tool::Eslint)
+			&& let Some(results) = value.as_array()
+		{
+			for result in results {
+				self.eslint_json(result);
+			}
+		}
+	}
+
+	fn cargo_json(&mut self, message: &Value) {
+		let Some(span) = message["spans"]
+			.as_array()
+			.and_then(|spans| spans.iter().find(|span| span["is_primary"] == true))
+		else {
+			return;
+		};
+		let level = message["level"].as_str().unwrap_or("error");
+		let severity = if level.starts_with("error") {
+			"error"
+		} else if level == "warning" {
+			"warning"
+		} else {
+			"note"
+		};
+		let json_number = |value: &Value| value.as_u64().and_then(|n| u32::try_from(n).ok());
+		let mut diagnostic = diagnostic(
+			Tool::Cargo,
+			span["file_name"].as_str().unwrap_or_default(),
+			severity,
+			message["message"].as_str().unwrap_or_default(),
+		);
+		diagnostic.line = json_number(&span["line_start"]);
+		diagnostic.column = json_number(&span["column_start"]);
+		diagnostic.end_line = json_number(&span["line_end"]);
+		diagnostic.end_column = json_number(&span["column_end"]);
+		diagnostic.code = message["code"]["code"].as_str().map(str::to_string);
+		diagnostic.detail = message["rendered"]
+			.as_str()
+			.map(|rendered| rendered.trim_end().to_string());
+		self.emit(diagnostic);
+	}
+
+	fn eslint_json(&mut self, result: &Value) {
+		let (Some(file), Some(messages)) =
+			(result["filePath"].as_str(), result["messages"].as_array())
+		else {
+			return;
+		};
+		let json_number = |value: &Value| value.as_u64().and_then(|n| u32::try_from(n).ok());
+		for message in messages {
+			let severity = if message["severity"] == 2 {
+				"error"
+			} else {
+				"warning"
+			};
+			let mut diagnostic = diagnostic(
+				Tool::Eslint,
+				file,
+				severity,
+				message["message"].as_str().unwrap_or_default(),
+			);
+			diagnostic.line = json_number(&message["line"]);
+			diagnostic.column = json_number(&message["column"]);
+			diagnostic.end_line = json_number(&message["endLine"]);
+			diagnostic.end_column = json_number(&message["endColumn"]);
+			diagnostic.code = message["ruleId"].as_str().map(str::to_string);
+			self.emit(diagnostic);
+		}
+	}
+}
+
+/// Parse complete tool output into diagnostics, in output order.
+///
+/// # Errors
+/// Returns an error if `tool` is not a supported toolchain.
+#[napi(js_name = "parseDiagnostics")]
+pub fn parse_diagnostics(
+	output: String,
+	options: Option<DiagnosticOptions>,
+) -> Result<Vec<Diagnostic>> {
+	let tool = Tool::parse(options.unwrap_or_default().tool.as_deref())?;
+	let mut parser = Parser::new(tool);
+	for line in output.lines() {
+		parser.line(line);
+	}
+	parser.finish();
+	Ok(parser.take())
+}
+
+/// Incremental diagnostics parser for streamed output.
+#[napi]
+pub struct DiagnosticParser {
+	parser: Parser,
+}
+
+#[napi]
+impl DiagnosticParser {
+	/// # Errors
+	/// Returns an error if `tool` is not a supported toolchain.
+	#[napi(constructor)]
+	pub fn new(options: Option<DiagnosticOptions>) -> Result<Self> {
+		let tool = Tool::parse(options.unwrap_or_default().tool.as_deref())?;
+		Ok(Self { parser: Parser::new(tool) })
+	}
+
+	/// Feed an output chunk and return the diagnostics it completed. Chunks
+	/// may split lines anywhere.
+	#[napi]
+	pub fn push(&mut self, chunk: String) -> Vec<Diagnostic> {
+		self.parser.push(&chunk);
+		self.parser.take()
+	}
+
+	/// Return the diagnostics still held back at the end of output.
+	#[napi]
+	pub fn finish(&mut self) -> Vec<Diagnostic> {
+		self.parser.finish();
+		self.parser.take()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(output: &str) -> Vec<Diagnostic> {
+		parse_diagnostics(output.to_string(), None).expect("no tool filter")
+	}
+
+	fn location(diagnostic: &Diagnostic) -> (&str, &str, Option<u32>, Option<u32>) {
+		(&diagnostic.tool, &diagnostic.file, diagnostic.line, diagnostic.column)
+	}
+
+	#[test]
+	fn test_cargo() {
+		let output = "\
+error[E0308]: mismatched types
+ --> src/main.rs:4:18
+  |
+4 |     let x: u32 = \"a\";
+  |            ---   ^^^ expected `u32`, found `&str`
+
+warning: unused variable: `y`
+ --> src/lib.rs:10:9
+";
+		let diagnostics = parse(output);
+		assert_eq!(diagnostics.len(), 2);
+		assert_eq!(location(&diagnostics[0]), ("cargo", "src/main.rs", Some(4), Some(18)));
+		assert_eq!(diagnostics[0].severity, "error");
+		assert_eq!(diagnostics[0].message, "mismatched types");
+		assert_eq!(diagnostics[0].code.as_deref(), Some("E0308"));
+		assert_eq!(location(&diagnostics[1]), ("cargo", "src/lib.rs", Some(10), Some(9)));
+		assert_eq!(diagnostics[1].severity, "warning");
+	}
+
+	#[test]
+	fn test_cargo_panic() {
+		let output = "thread 'tests::adds' panicked at src/lib.rs:12:5:\nassertion failed: 1 == 2\n";
+		let diagnostics = parse(output);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(location(&diagnostics[0]), ("cargo", "src/lib.rs", Some(12), Some(5)));
+		assert_eq!(diagnostics[0].message, "assertion failed: 1 == 2");
+	}
+
+	#[test]
+	fn test_tsc() {
+		let plain =
+			"src/a.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n";
+		let pretty =
+			"src/a.ts:3:7 - error TS2322: Type 'string' is not assignable to type 'number'.\n";
+		for output in [plain, pretty] {
+			let diagnostics = parse(output);
+			assert_eq!(diagnostics.len(), 1, "{output}");
+			assert_eq!(location(&diagnostics[0]), ("tsc", "src/a.ts", Some(3), Some(7)));
+			assert_eq!(diagnostics[0].code.as_deref(), Some("TS2322"));
+			assert_eq!(diagnostics[0].severity, "error");
+		}
+	}
+
+	#[test]
+	fn test_eslint_stylish() {
+		let output = "\
+/repo/src/a.js
+  1:10  error    'foo' is defined but never used  no-unused-vars
+  2:1   warning  Unexpected console statement     no-console
+
+✖ 2 problems (1 error, 1 warning)
+";
+		let diagnostics = parse(output);
+		assert_eq!(diagnostics.len(), 2);
+		assert_eq!(location(&diagnostics[0]), ("eslint", "/repo/src/a.js", Some(1), Some(10)));
+		assert_eq!(diagnostics[0].code.as_deref(), Some("no-unused-vars"));
+		assert_eq!(diagnostics[0].message, "'foo' is defined but never used");
+		assert_eq!(diagnostics[1].severity, "warning");
+		assert_eq!(diagnostics[1].code.as_deref(), Some("no-console"));
+	}
+
+	#[test]
+	fn test_pytest() {
+		let output = "\
+=================================== FAILURES ===================================
+__________________________________ test_add ____________________________________
+
+    def test_add():
+>       assert add(1, 2) == 4
+E       assert 3 == 4
+
+tests/test_math.py:5: AssertionError
+=========================== short test summary info ============================
+FAILED tests/test_math.py::test_add - assert 3 == 4
+";
+		let diagnostics = parse(output);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(location(&diagnostics[0]), ("pytest", "tests/test_math.py", Some(5), None));
+		assert_eq!(diagnostics[0].message, "assert 3 == 4");
+		assert_eq!(diagnostics[0].code.as_deref(), Some("AssertionError"));
+	}
+
+	#[test]
+	fn test_go() {
+		let output = "./main.go:12:2: undefined: foo\n";
+		let diagnostics = parse(output);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(location(&diagnostics[0]), ("go", "./main.go", Some(12), Some(2)));
+		assert_eq!(diagnostics[0].message, "undefined: foo");
+	}
+
+	#[test]
+	fn test_gcc() {
+		let output = "\
+main.c:3:5: warning: unused variable 'x' [-Wunused-variable]
+    3 |     int x;
+      |         ^
+";
+		let diagnostics = parse(output);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(location(&diagnostics[0]), ("gcc", "main.c", Some(3), Some(5)));
+		assert_eq!(diagnostics[0].severity, "warning");
+		assert_eq!(diagnostics[0].code.as_deref(), Some("-Wunused-variable"));
+		assert_eq!(diagnostics[0].message, "unused variable 'x'");
+	}
+
+	#[test]
+	fn test_tool_filter() {
+		let output = "src/a.ts(3,7): error TS2322: Bad.\nmain.c:3:5: error: bad\n";
+		let only_gcc = DiagnosticOptions { tool: Some("gcc".to_string()) };
+		let diagnostics = parse_diagnostics(output.to_string(), Some(only_gcc)).unwrap();
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].tool, "gcc");
+		let unknown = DiagnosticOptions { tool: Some("make".to_string()) };
+		assert!(parse_diagnostics(String::new(), Some(unknown)).is_err());
+	}
+
+	#[test]
+	fn test_streaming_matches_batch() {
+		let output = "main.c:3:5: warning: unused variable 'x'\n    3 |     int x;\nmain.c:4:1: \
+		              error: expected ';'\n";
+		let mut parser = Parser::new(None);
+		let mut streamed = Vec::new();
+		for chunk in output.as_bytes().chunks(7) {
+			parser.push(std::str::from_utf8(chunk).unwrap());
+			streamed.append(&mut parser.take());
+		}
+		parser.finish();
+		streamed.append(&mut parser.take());
+		let batch = parse(output);
+		assert_eq!(streamed.len(), batch.len());
+		for (streamed, batch) in streamed.iter().zip(&batch) {
+			assert_eq!(location(streamed), location(batch));
+			assert_eq!(streamed.message, batch.message);
+			assert_eq!(streamed.detail, batch.detail);
+		}
+	}
+}
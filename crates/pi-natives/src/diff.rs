@@ -0,0 +1,570 @@
+//! Diff rendering for display.
+//!
+//! # Overview
+//! `renderDiff` diffs two texts by line (histogram or Myers) and lays the
+//! changes out in hunks with `context` unchanged lines around them:
+//! - `unified`: removed lines followed by the lines replacing them
+//! - `split`: old and new lines side by side, replacements paired up
+//!
+//! With `wordLevel` (default), each removed line paired with an added line is
+//! diffed again by word, and the spans that differ are marked `changed`.
+//! Lines with nothing but whitespace in common are left unmarked.
+//!
+//! Rows are returned structured for custom rendering, and as ANSI-styled text
+//! with `ansi`.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { ansi } = await native.renderDiff(before, after, { mode: "split", width: 120, ansi: true })
+//! ```
+
+use std::{fmt::Write as _, iter::Copied, ops::Range, slice};
+
+use gix::diff::blob::{
+	Algorithm,
+	intern::{InternedInput, TokenSource},
+	sources::lines_with_terminator,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use unicode_width::UnicodeWidthChar;
+
+use crate::task;
+
+const DEFAULT_CONTEXT: u32 = 3;
+const DEFAULT_WIDTH: u32 = 160;
+/// Tabs are drawn as this many spaces, as in the TUI.
+const TAB: &str = "   ";
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const DEFAULT_FG: &str = "\x1b[39m";
+const DIM: &str = "\x1b[2m";
+const NORMAL: &str = "\x1b[22m";
+const INVERSE: &str = "\x1b[7m";
+const NO_INVERSE: &str = "\x1b[27m";
+const SEPARATOR: &str = " │ ";
+
+/// Options for `renderDiff`.
+#[napi(object)]
+#[derive(Default)]
+pub struct RenderDiffOptions {
+	/// Layout (default: "unified").
+	#[napi(ts_type = "\"unified\" | \"split\"")]
+	pub mode:       Option<String>,
+	/// Unchanged lines shown around each change (default: 3).
+	pub context:    Option<u32>,
+	/// Line diff algorithm (default: "histogram").
+	#[napi(ts_type = "\"histogram\" | \"myers\"")]
+	pub algorithm:  Option<String>,
+	/// Mark the words that changed within replaced lines (default: true).
+	#[napi(js_name = "wordLevel")]
+	pub word_level: Option<bool>,
+	/// Also render the rows as ANSI-styled text (default: false).
+	pub ansi:       Option<bool>,
+	/// Width of the ANSI text in `split` mode, in cells (default: 160).
+	/// Lines longer than their column are cut off with "…".
+	pub width:      Option<u32>,
+}
+
+/// A run of text within a diff line.
+#[napi(object)]
+pub struct DiffSpan {
+	/// Text of the run.
+	pub text:    String,
+	/// Whether the run differs from the paired line on the other side.
+	pub changed: bool,
+}
+
+/// One side of a diff row.
+#[napi(object)]
+pub struct DiffRowLine {
+	/// Line number, 1-indexed.
+	pub line:  u32,
+	/// Text of the line, without its terminator, in runs.
+	pub spans: Vec<DiffSpan>,
+}
+
+/// A row of a rendered diff.
+#[napi(object)]
+pub struct DiffRow {
+	/// "hunk" starts a hunk, "context" is an unchanged line, "removed" and
+	/// "added" are one-sided, and "changed" pairs a removed line with its
+	/// replacement (`split` mode only).
+	#[napi(ts_type = "\"hunk\" | \"context\" | \"removed\" | \"added\" | \"changed\"")]
+	pub kind:   String,
+	/// Hunk header like `@@ -1,4 +1,5 @@`, for "hunk" rows.
+	pub header: Option<String>,
+	/// Old line, for "context", "removed" and "changed" rows.
+	pub old:    Option<DiffRowLine>,
+	/// New line, for "context", "added" and "changed" rows.
+	pub new:    Option<DiffRowLine>,
+}
+
+/// Result of `renderDiff`.
+#[napi(object)]
+pub struct RenderDiffResult {
+	/// Hunk headers and lines, in order.
+	pub rows:    Vec<DiffRow>,
+	/// Number of added lines.
+	pub added:   u32,
+	/// Number of removed lines.
+	pub removed: u32,
+	/// The rows as ANSI-styled lines joined with "\n", when `ansi` is set.
+	pub ansi:    Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+	Unified,
+	Split,
+}
+
+struct Options {
+	mode:       Mode,
+	context:    usize,
+	algorithm:  Algorithm,
+	word_level: bool,
+	ansi:       bool,
+	width:      usize,
+}
+
+fn parse_options(options: Option<RenderDiffOptions>) -> Result<Options> {
+	let options = options.unwrap_or_default();
+	let mode = match options.mode.as_deref() {
+		None | Some("unified") => Mode::Unified,
+		Some("split") => Mode::Split,
+		Some(other) => return Err(Error::from_reason(format!("Unknown diff mode: {other}"))),
+	};
+	let algorithm = match options.algorithm.as_deref() {
+		None | Some("histogram") => Algorithm::Histogram,
+		Some("myers") => Algorithm::Myers,
+		Some(other) => return Err(Error::from_reason(format!("Unknown diff algorithm: {other}"))),
+	};
+	Ok(Options {
+		mode,
+		context: options.context.unwrap_or(DEFAULT_CONTEXT) as usize,
+		algorithm,
+		word_level: options.word_level.unwrap_or(true),
+		ansi: options.ansi.unwrap_or(false),
+		width: options.width.unwrap_or(DEFAULT_WIDTH) as usize,
+	})
+}
+
+/// A change from old lines `old` to new lines `new`.
+struct Change {
+	old: Range<usize>,
+	new: Range<usize>,
+}
+
+fn line_changes(old: &str, new: &str, algorithm: Algorithm) -> Vec<Change> {
+	let input = InternedInput::new(lines_with_terminator(old), lines_with_terminator(new));
+	let mut changes = Vec::new();
+	gix::diff::blob::diff(algorithm, &input, |old: Range<u32>, new: Range<u32>| {
+		changes.push(Change {
+			old: old.start as usize..old.end as usize,
+			new: new.start as usize..new.end as usize,
+		});
+	});
+	changes
+}
+
+fn strip_terminator(line: &str) -> &str {
+	line
+		.strip_suffix('\n')
+		.map_or(line, |line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+/// Words, whitespace runs and single punctuation characters of a line, as
+/// tokens for the word diff.
+struct Words<'a>(&'a [&'a str]);
+
+impl<'a> TokenSource for Words<'a> {
+	type Token = &'a str;
+	type Tokenizer = Copied<slice::Iter<'a, &'a str>>;
+
+	fn tokenize(&self) -> Self::Tokenizer {
+		self.0.iter().copied()
+	}
+
+	fn estimate_tokens(&self) -> u32 {
+		self.0.len() as u32
+	}
+}
+
+#[derive(PartialEq, Eq)]
+enum CharClass {
+	Word,
+	Space,
+	Other,
+}
+
+impl CharClass {
+	fn of(c: char) -> Self {
+		if c.is_alphanumeric() || c == '_' {
+			Self::Word
+		} else if c.is_whitespace() {
+			Self::Space
+		} else {
+			Self::Other
+		}
+	}
+}
+
+fn split_words(line: &str) -> Vec<&str> {
+	let mut words = Vec::new();
+	let mut chars = line.char_indices().peekable();
+	while let Some((start, c)) = chars.next() {
+		let class = CharClass::of(c);
+		let mut end = start + c.len_utf8();
+		if class != CharClass::Other {
+			while let Some(&(next, c)) = chars.peek() {
+				if CharClass::of(c) != class {
+					break;
+				}
+				end = next + c.len_utf8();
+				chars.next();
+			}
+		}
+		words.push(&line[start..end]);
+	}
+	words
+}
+
+/// Spans of `words`, merging neighbors with the same `changed` flag.
+fn merge_spans(words: &[&str], changed: &[bool]) -> Vec<DiffSpan> {
+	let mut spans: Vec<DiffSpan> = Vec::new();
+	for (word, &changed) in words.iter().zip(changed) {
+		match spans.last_mut() {
+			Some(span) if span.changed == changed => span.text.push_str(word),
+			_ => spans.push(DiffSpan { text: (*word).to_string(), changed }),
+		}
+	}
+	spans
+}
+
+fn plain_spans(line: &str) -> Vec<DiffSpan> {
+	if line.is_empty() {
+		Vec::new()
+	} else {
+		vec![DiffSpan { text: line.to_string(), changed: false }]
+	}
+}
+
+/// Spans of a removed line and its replacement, marking the words that
+/// differ.
+fn word_spans(old: &str, new: &str) -> (Vec<DiffSpan>, Vec<DiffSpan>) {
+	let old_words = split_words(old);
+	let new_words = split_words(new);
+	let mut old_changed = vec![false; old_words.len()];
+	let mut new_changed = vec![false; new_words.len()];
+	let input = InternedInput::new(Words(&old_words), Words(&new_words));
+	gix::diff::blob::diff(Algorithm::Histogram, &input, |old: Range<u32>, new: Range<u32>| {
+		old_changed[old.start as usize..old.end as usize].fill(true);
+		new_changed[new.start as usize..new.end as usize].fill(true);
+	});
+	// Marking every word of unrelated lines only adds noise.
+	let related = old_words
+		.iter()
+		.zip(&old_changed)
+		.any(|(word, &changed)| !changed && !word.trim().is_empty());
+	if !related {
+		return (plain_spans(old), plain_spans(new));
+	}
+	(merge_spans(&old_words, &old_changed), merge_spans(&new_words, &new_changed))
+}
+
+const fn row_line(index: usize, spans: Vec<DiffSpan>) -> DiffRowLine {
+	DiffRowLine { line: (index + 1) as u32, spans }
+}
+
+/// `start,len` of a hunk header, in git's format.
+fn header_range(start: usize, len: usize) -> String {
+	match len {
+		0 => format!("{start},0"),
+		1 => format!("{}", start + 1),
+		_ => format!("{},{len}", start + 1),
+	}
+}
+
+struct Renderer<'a> {
+	old_lines: Vec<&'a str>,
+	new_lines: Vec<&'a str>,
+	options:   &'a Options,
+	rows:      Vec<DiffRow>,
+}
+
+impl Renderer<'_> {
+	fn push_row(&mut self, kind: &str, old: Option<DiffRowLine>, new: Option<DiffRowLine>) {
+		self
+			.rows
+			.push(DiffRow { kind: kind.to_string(), header: None, old, new });
+	}
+
+	fn push_context(&mut self, old: Range<usize>, new_start: usize) {
+		for (offset, index) in old.enumerate() {
+			let old_line = row_line(index, plain_spans(self.old_lines[index]));
+			let new_line = row_line(new_start + offset, plain_spans(self.old_lines[index]));
+			self.push_row("context", Some(old_line), Some(new_line));
+		}
+	}
+
+	fn push_change(&mut self, change: &Change) {
+		let old = &self.old_lines[change.old.clone()];
+		let new = &self.new_lines[change.new.clone()];
+		let mut old_spans: Vec<Vec<DiffSpan>> = old.iter().map(|line| plain_spans(line)).collect();
+		let mut new_spans: Vec<Vec<DiffSpan>> = new.iter().map(|line| plain_spans(line)).collect();
+		if self.options.word_level {
+			for (i, (old, new)) in old.iter().zip(new).enumerate() {
+				(old_spans[i], new_spans[i]) = word_spans(old, new);
+			}
+		}
+		let old_rows = old_spans
+			.into_iter()
+			.zip(change.old.clone())
+			.map(|(spans, index)| row_line(index, spans));
+		let new_rows = new_spans
+			.into_iter()
+			.zip(change.new.clone())
+			.map(|(spans, index)| row_line(index, spans));
+		match self.options.mode {
+			Mode::Unified => {
+				for line in old_rows {
+					self.push_row("removed", Some(line), None);
+				}
+				for line in new_rows {
+					self.push_row("added", None, Some(line));
+				}
+			},
+			Mode::Split => {
+				let mut old_rows = old_rows.fuse();
+				let mut new_rows = new_rows.fuse();
+				loop {
+					match (old_rows.next(), new_rows.next()) {
+						(None, None) => break,
+						(Some(old), Some(new)) => self.push_row("changed", Some(old), Some(new)),
+						(old @ Some(_), None) => self.push_row("removed", old, None),
+						(None, new) => self.push_row("added", None, new),
+					}
+				}
+			},
+		}
+	}
+
+	/// Push the hunks of `changes`, merging changes whose context overlaps.
+	fn push_hunks(&mut self, changes: &[Change]) {
+		let context = self.options.context;
+		let mut rest = changes;
+		while let Some(first) = rest.first() {
+			let mut len = 1;
+			while len < rest.len() && rest[len].old.start - rest[len - 1].old.end <= 2 * context {
+				len += 1;
+			}
+			let (hunk, next) = rest.split_at(len);
+			rest = next;
+			let last = &hunk[len - 1];
+
+			let old_start = first.old.start.saturating_sub(context);
+			let new_start = first.new.start - (first.old.start - old_start);
+			let old_end = (last.old.end + context).min(self.old_lines.len());
+			let new_end = last.new.end + (old_end - last.old.end);
+			self.rows.push(DiffRow {
+				kind:   "hunk".to_string(),
+				header: Some(format!(
+					"@@ -{} +{} @@",
+					header_range(old_start, old_end - old_start),
+					header_range(new_start, new_end - new_start)
+				)),
+				old:    None,
+				new:    None,
+			});
+
+			let (mut old_pos, mut new_pos) = (old_start, new_start);
+			for change in hunk {
+				self.push_context(old_pos..change.old.start, new_pos);
+				self.push_change(change);
+				(old_pos, new_pos) = (change.old.end, change.new.end);
+			}
+			self.push_context(old_pos..old_end, new_pos);
+		}
+	}
+}
+
+/// Append `spans`, with changed runs in inverse video, cutting them off with
+/// "…" beyond `cells` columns. Returns the columns used.
+fn push_spans(out: &mut String, spans: &[DiffSpan], cells: Option<usize>) -> usize {
+	let char_width = |c: char| {
+		if c == '\t' {
+			TAB.len()
+		} else {
+			c.width().unwrap_or(0)
+		}
+	};
+	let total: usize = spans
+		.iter()
+		.flat_map(|span| span.text.chars())
+		.map(char_width)
+		.sum();
+	let limit = match cells {
+		Some(cells) if total > cells => cells.saturating_sub(1),
+		_ => usize::MAX,
+	};
+	let mut used = 0;
+	let mut truncated = false;
+	for span in spans {
+		if span.changed {
+			out.push_str(INVERSE);
+		}
+		for c in span.text.chars() {
+			let width = char_width(c);
+			if used + width > limit {
+				truncated = true;
+				break;
+			}
+			if c == '\t' {
+				out.push_str(TAB);
+			} else {
+				out.push(c);
+			}
+			used += width;
+		}
+		if span.changed {
+			out.push_str(NO_INVERSE);
+		}
+		if truncated {
+			break;
+		}
+	}
+	if truncated && cells.is_some_and(|cells| cells > 0) {
+		out.push('…');
+		used += 1;
+	}
+	used
+}
+
+fn render_unified(rows: &[DiffRow]) -> String {
+	let mut out = String::new();
+	for row in rows {
+		if !out.is_empty() {
+			out.push('\n');
+		}
+		let (sign, color, line) = match (row.kind.as_str(), &row.old, &row.new) {
+			("removed", Some(line), _) => ('-', Some(RED), line),
+			("added", _, Some(line)) => ('+', Some(GREEN), line),
+			(_, _, Some(line)) => (' ', None, line),
+			_ => {
+				let _ = write!(out, "{CYAN}{}{DEFAULT_FG}", row.header.as_deref().unwrap_or_default());
+				continue;
+			},
+		};
+		if let Some(color) = color {
+			out.push_str(color);
+		}
+		out.push(sign);
+		push_spans(&mut out, &line.spans, None);
+		if color.is_some() {
+			out.push_str(DEFAULT_FG);
+		}
+	}
+	out
+}
+
+/// One column of a split row: line number, sign and text, padded to `cells`.
+fn push_cell(
+	out: &mut String,
+	line: Option<&DiffRowLine>,
+	sign: char,
+	color: Option<&str>,
+	cells: usize,
+	number_width: usize,
+) {
+	let Some(line) = line else {
+		out.extend(std::iter::repeat_n(' ', cells));
+		return;
+	};
+	let _ = write!(out, "{DIM}{:>number_width$}{NORMAL} ", line.line);
+	if let Some(color) = color {
+		out.push_str(color);
+	}
+	out.push(sign);
+	let text_cells = cells.saturating_sub(number_width + 2);
+	let used = push_spans(out, &line.spans, Some(text_cells));
+	if color.is_some() {
+		out.push_str(DEFAULT_FG);
+	}
+	out.extend(std::iter::repeat_n(' ', text_cells.saturating_sub(used)));
+}
+
+fn render_split(rows: &[DiffRow], width: usize, number_width: usize) -> String {
+	let cells = width.saturating_sub(SEPARATOR.chars().count()) / 2;
+	let mut out = String::new();
+	for row in rows {
+		if !out.is_empty() {
+			out.push('\n');
+		}
+		let (old_sign, new_sign, color) = match row.kind.as_str() {
+			"hunk" => {
+				let header = row.header.as_deref().unwrap_or_default();
+				out.push_str(CYAN);
+				push_spans(&mut out, &plain_spans(header), Some(width));
+				out.push_str(DEFAULT_FG);
+				continue;
+			},
+			"context" => (' ', ' ', false),
+			_ => ('-', '+', true),
+		};
+		let (old_color, new_color) = if color {
+			(Some(RED), Some(GREEN))
+		} else {
+			(None, None)
+		};
+		push_cell(&mut out, row.old.as_ref(), old_sign, old_color, cells, number_width);
+		out.push_str(SEPARATOR);
+		push_cell(&mut out, row.new.as_ref(), new_sign, new_color, cells, number_width);
+		// No trailing padding.
+		out.truncate(out.trim_end_matches(' ').len());
+	}
+	out
+}
+
+fn render(old: &str, new: &str, options: &Options) -> RenderDiffResult {
+	let changes = line_changes(old, new, options.algorithm);
+	let mut renderer = Renderer {
+		old_lines: lines_with_terminator(old).map(strip_terminator).collect(),
+		new_lines: lines_with_terminator(new).map(strip_terminator).collect(),
+		options,
+		rows: Vec::new(),
+	};
+	renderer.push_hunks(&changes);
+	let ansi = options.ansi.then(|| match options.mode {
+		Mode::Unified => render_unified(&renderer.rows),
+		Mode::Split => {
+			let lines = renderer.old_lines.len().max(renderer.new_lines.len());
+			render_split(&renderer.rows, options.width, lines.to_string().len())
+		},
+	});
+	RenderDiffResult {
+		rows: renderer.rows,
+		added: changes.iter().map(|change| change.new.len() as u32).sum(),
+		removed: changes.iter().map(|change| change.old.len() as u32).sum(),
+		ansi,
+	}
+}
+
+/// Diff `old_text` against `new_text` by line and lay the changes out for
+/// display, with changed words marked.
+///
+/// # Errors
+/// Returns an error for an unknown `mode` or `algorithm`.
+#[napi(js_name = "renderDiff")]
+pub fn render_diff(
+	old_text: String,
+	new_text: String,
+	options: Option<RenderDiffOptions>,
+) -> task::Async<RenderDiffResult> {
+	task::blocking("render_diff", (), move |_| {
+		let options = parse_options(options)?;
+		Ok(render(&old_text, &new_text, &options))
+	})
+}
@@ -0,0 +1,339 @@
+//! Compact directory tree summaries.
+//!
+//! # Overview
+//! `dirTree` walks a directory up to `maxDepth` levels, honoring ignore files
+//! like `glob`, and renders at most `maxEntries` entries as an indented tree
+//! for prompts. The budget is shared out breadth-first, one entry per
+//! directory per round, so every top-level directory shows some content
+//! before any directory shows all of its own.
+//!
+//! `.git` is never listed. `node_modules` and directories at the depth limit
+//! are listed with their entry count but not expanded; directories cut short
+//! by the budget end with a `… N more` line.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { tree } = await native.dirTree(".", { maxDepth: 2, maxEntries: 100 })
+//! // crate/
+//! //   src/
+//! //     lib.rs
+//! //     … 12 more (2 dirs, 10 files)
+//! //   Cargo.toml
+//! ```
+
+use std::{
+	fmt::Write,
+	path::{Path, PathBuf},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{fs_cache, system_info, task, workspace};
+
+const DEFAULT_MAX_DEPTH: u32 = 3;
+const DEFAULT_MAX_ENTRIES: u32 = 200;
+
+/// Options for `dirTree`.
+#[napi(object)]
+pub struct DirTreeOptions<'env> {
+	/// Directory levels to expand below the root (default: 3).
+	#[napi(js_name = "maxDepth")]
+	pub max_depth:               Option<u32>,
+	/// Maximum number of entries to list (default: 200).
+	#[napi(js_name = "maxEntries")]
+	pub max_entries:             Option<u32>,
+	/// Respect .gitignore files (default: true).
+	#[napi(js_name = "respectGitignore")]
+	pub respect_gitignore:       Option<bool>,
+	/// Include hidden files (default: false).
+	pub hidden:                  Option<bool>,
+	/// Append file sizes (default: false).
+	#[napi(js_name = "includeSizes")]
+	pub include_sizes:           Option<bool>,
+	/// Abort signal for cancelling the walk.
+	pub signal:                  Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the walk.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:              Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Result of `dirTree`.
+#[napi(object)]
+pub struct DirTreeResult {
+	/// Rendered tree, one entry per line, ending with a newline.
+	pub tree:      String,
+	/// Number of entries listed in `tree`, excluding the root.
+	pub shown:     u32,
+	/// Number of entries found within `maxDepth`.
+	pub total:     u32,
+	/// Whether entries were left out to stay within `maxEntries`.
+	pub truncated: bool,
+}
+
+struct Node {
+	name:     String,
+	is_dir:   bool,
+	size:     Option<u64>,
+	link:     Option<String>,
+	/// Walked children, directories first, then by name.
+	children: Vec<usize>,
+	/// Whether the walk descended into this directory.
+	expanded: bool,
+	/// Children listed in the tree.
+	shown:    usize,
+}
+
+impl Node {
+	const fn new(name: String, is_dir: bool) -> Self {
+		Self { name, is_dir, size: None, link: None, children: Vec::new(), expanded: false, shown: 0 }
+	}
+}
+
+fn is_node_modules(path: &Path) -> bool {
+	path.file_name().is_some_and(|name| name == "node_modules")
+}
+
+/// Walk `root` into a node arena whose first node is the root.
+fn walk(
+	root: &Path,
+	max_depth: usize,
+	use_gitignore: bool,
+	include_hidden: bool,
+	ct: &task::CancelToken,
+) -> Result<Vec<Node>> {
+	let root_name = root
+		.file_name()
+		.unwrap_or(root.as_os_str())
+		.to_string_lossy()
+		.into_owned();
+	let mut nodes = vec![Node::new(root_name, true)];
+	nodes[0].expanded = max_depth > 0;
+
+	let mut builder = fs_cache::build_walker(root, include_hidden, use_gitignore);
+	builder.max_depth(Some(max_depth)).filter_entry(|entry| {
+		entry.file_name() != ".git" && !entry.path().parent().is_some_and(is_node_modules)
+	});
+	// Walk order is depth-first, so the open directories form a stack.
+	let mut stack = vec![0];
+	for entry in builder.build() {
+		ct.heartbeat()?;
+		let Ok(entry) = entry else { continue };
+		let depth = entry.depth();
+		if depth == 0 {
+			continue;
+		}
+		stack.truncate(depth);
+		let Some(&parent) = stack.last() else {
+			continue;
+		};
+		let Some(file_type) = entry.file_type() else {
+			continue;
+		};
+
+		let mut node =
+			Node::new(entry.file_name().to_string_lossy().into_owned(), file_type.is_dir());
+		if file_type.is_symlink() {
+			node.link = std::fs::read_link(entry.path())
+				.ok()
+				.map(|target| target.to_string_lossy().into_owned());
+		} else if file_type.is_file() {
+			node.size = entry.metadata().ok().map(|metadata| metadata.len());
+		}
+		node.expanded = node.is_dir && depth < max_depth && !is_node_modules(entry.path());
+
+		let index = nodes.len();
+		nodes[parent].children.push(index);
+		if node.is_dir {
+			stack.push(index);
+		}
+		nodes.push(node);
+	}
+
+	for index in 0..nodes.len() {
+		let mut children = std::mem::take(&mut nodes[index].children);
+		children.sort_by(|&a, &b| {
+			nodes[b]
+				.is_dir
+				.cmp(&nodes[a].is_dir)
+				.then_with(|| nodes[a].name.cmp(&nodes[b].name))
+		});
+		nodes[index].children = children;
+	}
+	Ok(nodes)
+}
+
+/// Share `budget` entries out level by level, one child per directory per
+/// round; returns the number of entries listed.
+fn allocate(nodes: &mut [Node], mut budget: usize) -> usize {
+	let mut listed = 0;
+	let mut level = vec![0];
+	while !level.is_empty() && budget > 0 {
+		let mut round = 0;
+		let mut next = Vec::new();
+		loop {
+			let mut progressed = false;
+			for &dir in &level {
+				if budget == 0 {
+					break;
+				}
+				if round >= nodes[dir].children.len() {
+					continue;
+				}
+				nodes[dir].shown += 1;
+				budget -= 1;
+				listed += 1;
+				progressed = true;
+			}
+			if !progressed {
+				break;
+			}
+			round += 1;
+		}
+		for &dir in &level {
+			next.extend(
+				nodes[dir].children[..nodes[dir].shown]
+					.iter()
+					.copied()
+					.filter(|&child| nodes[child].expanded),
+			);
+		}
+		level = next;
+	}
+	listed
+}
+
+fn count_entries(path: &Path) -> Option<usize> {
+	Some(std::fs::read_dir(path).ok()?.count())
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+	format!("{count} {}", if count == 1 { one } else { many })
+}
+
+struct Renderer<'a> {
+	nodes:         &'a [Node],
+	include_sizes: bool,
+	out:           String,
+}
+
+impl Renderer<'_> {
+	fn line(&mut self, depth: usize, text: &str) {
+		for _ in 0..depth {
+			self.out.push_str("  ");
+		}
+		self.out.push_str(text);
+		self.out.push('\n');
+	}
+
+	fn render(&mut self, index: usize, path: &mut PathBuf, depth: usize) {
+		let node = &self.nodes[index];
+		let mut text = node.name.clone();
+		if node.is_dir {
+			text.push('/');
+		}
+		if let Some(link) = &node.link {
+			text.push_str(" -> ");
+			text.push_str(link);
+		}
+		if self.include_sizes
+			&& let Some(size) = node.size
+		{
+			let _ = write!(text, " ({})", system_info::format_bytes(size));
+		}
+		let unlisted = if node.expanded {
+			(node.shown == 0 && !node.children.is_empty()).then_some(node.children.len())
+		} else if node.is_dir {
+			count_entries(path).filter(|&count| count > 0)
+		} else {
+			None
+		};
+		if let Some(count) = unlisted {
+			let _ = write!(text, " ({})", plural(count, "entry", "entries"));
+		}
+		self.line(depth, &text);
+		if unlisted.is_some() || node.shown == 0 {
+			return;
+		}
+
+		for &child in &node.children[..node.shown] {
+			path.push(&self.nodes[child].name);
+			self.render(child, path, depth + 1);
+			path.pop();
+		}
+		let rest = &node.children[node.shown..];
+		if !rest.is_empty() {
+			let dirs = rest
+				.iter()
+				.filter(|&&child| self.nodes[child].is_dir)
+				.count();
+			let files = rest.len() - dirs;
+			let kinds = match (dirs, files) {
+				(0, _) | (_, 0) => String::new(),
+				_ => format!(" ({}, {})", plural(dirs, "dir", "dirs"), plural(files, "file", "files")),
+			};
+			self.line(depth + 1, &format!("… {} more{kinds}", rest.len()));
+		}
+	}
+}
+
+/// Walk `root` and render the tree within the budget.
+fn dir_tree_sync(
+	root: &Path,
+	max_depth: usize,
+	max_entries: usize,
+	use_gitignore: bool,
+	include_hidden: bool,
+	include_sizes: bool,
+	ct: &task::CancelToken,
+) -> Result<DirTreeResult> {
+	let mut nodes = walk(root, max_depth, use_gitignore, include_hidden, ct)?;
+	let total = nodes.len() - 1;
+	let shown = allocate(&mut nodes, max_entries);
+	let mut renderer = Renderer { nodes: &nodes, include_sizes, out: String::new() };
+	let mut path = root.to_path_buf();
+	renderer.render(0, &mut path, 0);
+	Ok(DirTreeResult {
+		tree:      renderer.out,
+		shown:     shown as u32,
+		total:     total as u32,
+		truncated: shown < total,
+	})
+}
+
+/// Summarize the directory tree under `root` for prompts.
+///
+/// # Errors
+/// Returns an error when `root` is not a directory or is outside the
+/// workspace, or on cancellation or timeout.
+#[napi(js_name = "dirTree")]
+pub fn dir_tree(root: String, options: Option<DirTreeOptions<'_>>) -> task::Async<DirTreeResult> {
+	let (max_depth, max_entries, use_gitignore, include_hidden, include_sizes, allow_outside, ct) =
+		match options {
+			Some(options) => (
+				options.max_depth,
+				options.max_entries,
+				options.respect_gitignore,
+				options.hidden,
+				options.include_sizes,
+				options.allow_outside_workspace,
+				task::CancelToken::new(options.timeout_ms, options.signal),
+			),
+			None => (None, None, None, None, None, None, task::CancelToken::default()),
+		};
+	task::blocking("dir_tree", ct, move |ct| {
+		workspace::check(&root, allow_outside)?;
+		dir_tree_sync(
+			&fs_cache::resolve_search_path(&root)?,
+			max_depth.unwrap_or(DEFAULT_MAX_DEPTH) as usize,
+			max_entries.unwrap_or(DEFAULT_MAX_ENTRIES) as usize,
+			use_gitignore.unwrap_or(true),
+			include_hidden.unwrap_or(false),
+			include_sizes.unwrap_or(false),
+			&ct,
+		)
+	})
+}
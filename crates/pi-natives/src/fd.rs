@@ -8,7 +8,7 @@ use std::path::Path;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::{fs_cache, task};
+use crate::{fs_cache, task, workspace};
 
 // ═══════════════════════════════════════════════════════════════════════════
 // Public types
@@ -18,23 +18,26 @@ use crate::{fs_cache, task};
 #[napi(object)]
 pub struct FuzzyFindOptions<'env> {
 	/// Fuzzy query to match against file paths (case-insensitive).
-	pub query:       String,
+	pub query:                   String,
 	/// Directory to search.
-	pub path:        String,
+	pub path:                    String,
 	/// Include hidden files (default: false).
-	pub hidden:      Option<bool>,
+	pub hidden:                  Option<bool>,
 	/// Respect .gitignore (default: true).
-	pub gitignore:   Option<bool>,
+	pub gitignore:               Option<bool>,
 	/// Enable shared filesystem scan cache (default: false).
-	pub cache:       Option<bool>,
+	pub cache:                   Option<bool>,
 	/// Maximum number of matches to return (default: 100).
 	#[napi(js_name = "maxResults")]
-	pub max_results: Option<u32>,
+	pub max_results:             Option<u32>,
 	/// Abort signal for cancelling the operation.
-	pub signal:      Option<Unknown<'env>>,
+	pub signal:                  Option<Unknown<'env>>,
 	/// Timeout in milliseconds for the operation.
 	#[napi(js_name = "timeoutMs")]
-	pub timeout_ms:  Option<u32>,
+	pub timeout_ms:              Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
 }
 
 /// A single match in fuzzy find results.
@@ -244,9 +247,21 @@ fn score_entries(
 /// Matching file and directory entries sorted by match quality.
 #[napi(js_name = "fuzzyFind")]
 pub fn fuzzy_find(options: FuzzyFindOptions<'_>) -> task::Async<FuzzyFindResult> {
-	let FuzzyFindOptions { query, path, hidden, gitignore, cache, max_results, timeout_ms, signal } =
-		options;
+	let FuzzyFindOptions {
+		query,
+		path,
+		hidden,
+		gitignore,
+		cache,
+		max_results,
+		timeout_ms,
+		signal,
+		allow_outside_workspace,
+	} = options;
 	let ct = task::CancelToken::new(timeout_ms, signal);
 	let config = FuzzyFindConfig { query, path, hidden, gitignore, max_results, cache };
-	task::blocking("fuzzy_find", ct, move |ct| fuzzy_find_sync(config, ct))
+	task::blocking("fuzzy_find", ct, move |ct| {
+		workspace::check(&config.path, allow_outside_workspace)?;
+		fuzzy_find_sync(config, ct)
+	})
 }
@@ -0,0 +1,338 @@
+//! HTTP requests with size and time limits.
+//!
+//! # Overview
+//! A single request per call, with rustls (webpki and system roots), proxies
+//! from the environment and the OS, and transparent gzip/brotli/zstd/deflate
+//! decoding. The response body is streamed: into memory up to `maxBytes`, or
+//! straight to `outputPath` for downloads.
+//!
+//! Redirects are followed up to `maxRedirects` unless `allowRedirects` is
+//! false, in which case the redirect response itself is returned.
+//! Credentials are dropped when a redirect leaves the original host.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.fetchUrl({ url: "https://example.com", maxBytes: 1 << 20, timeoutMs: 10_000 })
+//! ```
+
+use std::{collections::HashMap, path::Path, str::FromStr, time::Instant};
+
+use encoding_rs::{Encoding, UTF_8};
+use napi::{
+	bindgen_prelude::*,
+	tokio::{self, fs, io::AsyncWriteExt as _},
+};
+use napi_derive::napi;
+use reqwest::{
+	Method, Response,
+	header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue},
+	redirect,
+};
+
+use crate::task;
+
+const DEFAULT_MAX_REDIRECTS: u32 = 10;
+
+/// Options for `fetchUrl`.
+#[napi(object)]
+pub struct FetchOptions<'env> {
+	/// URL to request (http or https).
+	pub url:             String,
+	/// HTTP method (default: GET).
+	pub method:          Option<String>,
+	/// Request headers.
+	pub headers:         Option<HashMap<String, String>>,
+	/// Request body.
+	pub body:            Option<Either<String, Uint8Array>>,
+	/// Stop reading the response body after this many bytes and report it as
+	/// `truncated` (default: no limit).
+	#[napi(js_name = "maxBytes")]
+	pub max_bytes:       Option<i64>,
+	/// Follow redirects (default: true). When false a redirect response is
+	/// returned as is.
+	#[napi(js_name = "allowRedirects")]
+	pub allow_redirects: Option<bool>,
+	/// Maximum number of redirects to follow before failing (default: 10).
+	#[napi(js_name = "maxRedirects")]
+	pub max_redirects:   Option<u32>,
+	/// Write the response body to this file instead of returning it.
+	#[napi(js_name = "outputPath")]
+	pub output_path:     Option<String>,
+	/// Abort signal for cancelling the operation.
+	pub signal:          Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the whole request, including the body.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:      Option<u32>,
+}
+
+/// Result of `fetchUrl`.
+#[napi(object)]
+pub struct FetchResult {
+	/// HTTP status code.
+	pub status:      u32,
+	/// Canonical reason phrase for the status code.
+	#[napi(js_name = "statusText")]
+	pub status_text: String,
+	/// Final URL, after redirects.
+	pub url:         String,
+	/// Response headers with lowercase names; repeated headers are joined
+	/// with `, `.
+	pub headers:     HashMap<String, String>,
+	/// Raw response body, after content decoding. Absent with `outputPath`.
+	pub body:        Option<Buffer>,
+	/// Body decoded with the charset of a textual `content-type` (default:
+	/// UTF-8). Absent for binary content and with `outputPath`.
+	pub text:        Option<String>,
+	/// Body bytes received (written to `outputPath` when given).
+	pub bytes:       f64,
+	/// Whether the body was cut off at `maxBytes`.
+	pub truncated:   bool,
+	/// Wall-clock duration of the request in milliseconds.
+	#[napi(js_name = "durationMs")]
+	pub duration_ms: f64,
+}
+
+/// Request parameters, validated on the JS thread.
+struct FetchRequest {
+	url:         reqwest::Url,
+	method:      Method,
+	headers:     HeaderMap,
+	body:        Option<Vec<u8>>,
+	max_bytes:   Option<u64>,
+	redirect:    redirect::Policy,
+	output_path: Option<String>,
+}
+
+impl FetchRequest {
+	fn parse(options: FetchOptions) -> Result<(Self, task::CancelToken)> {
+		let url = reqwest::Url::parse(&options.url)
+			.map_err(|err| Error::from_reason(format!("Invalid URL {}: {err}", options.url)))?;
+		if !matches!(url.scheme(), "http" | "https") {
+			return Err(Error::from_reason(format!(
+				"Unsupported URL scheme: {} (expected http or https)",
+				url.scheme()
+			)));
+		}
+		let method = options.method.as_deref().unwrap_or("GET");
+		let method = Method::from_str(&method.to_ascii_uppercase())
+			.map_err(|_| Error::from_reason(format!("Invalid HTTP method: {method}")))?;
+		let mut headers = HeaderMap::new();
+		for (name, value) in options.headers.unwrap_or_default() {
+			let header = HeaderName::from_str(&name)
+				.map_err(|_| Error::from_reason(format!("Invalid header name: {name}")))?;
+			let value = HeaderValue::from_str(&value)
+				.map_err(|_| Error::from_reason(format!("Invalid value for header {name}")))?;
+			headers.insert(header, value);
+		}
+		let body = options.body.map(|body| match body {
+			Either::A(text) => text.into_bytes(),
+			Either::B(bytes) => bytes.to_vec(),
+		});
+		let redirect = if options.allow_redirects.unwrap_or(true) {
+			redirect::Policy::limited(options.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS) as usize)
+		} else {
+			redirect::Policy::none()
+		};
+		let request = Self {
+			url,
+			method,
+			headers,
+			body,
+			max_bytes: options.max_bytes.map(|bytes| bytes.max(0) as u64),
+			redirect,
+			output_path: options.output_path,
+		};
+		Ok((request, task::CancelToken::new(options.timeout_ms, options.signal)))
+	}
+}
+
+/// Charset of a textual content type, or `None` for binary content.
+fn text_encoding(content_type: Option<&str>) -> Option<&'static Encoding> {
+	let Some(content_type) = content_type else {
+		return Some(UTF_8);
+	};
+	let mut params = content_type.split(';');
+	let mime = params
+		.next()
+		.unwrap_or_default()
+		.trim()
+		.to_ascii_lowercase();
+	let textual = mime.starts_with("text/")
+		|| mime.ends_with("+json")
+		|| mime.ends_with("+xml")
+		|| matches!(
+			mime.as_str(),
+			"application/json"
+				| "application/xml"
+				| "application/javascript"
+				| "application/ecmascript"
+				| "application/x-www-form-urlencoded"
+		);
+	if !textual {
+		return None;
+	}
+	let charset = params.find_map(|param| {
+		let (name, value) = param.split_once('=')?;
+		name
+			.trim()
+			.eq_ignore_ascii_case("charset")
+			.then(|| value.trim().trim_matches('"'))
+	});
+	Some(
+		charset
+			.and_then(|label| Encoding::for_label(label.as_bytes()))
+			.unwrap_or(UTF_8),
+	)
+}
+
+fn response_headers(headers: &HeaderMap) -> HashMap<String, String> {
+	let mut map: HashMap<String, String> = HashMap::new();
+	for (name, value) in headers {
+		let value = String::from_utf8_lossy(value.as_bytes());
+		map.entry(name.as_str().to_string())
+			.and_modify(|joined| {
+				joined.push_str(", ");
+				joined.push_str(&value);
+			})
+			.or_insert_with(|| value.into_owned());
+	}
+	map
+}
+
+/// Where the response body goes.
+enum Sink {
+	Memory(Vec<u8>),
+	File(fs::File),
+}
+
+/// Stream the body of `response` into `sink`, stopping at `max_bytes`.
+/// Returns the bytes kept and whether the body was cut off.
+async fn read_body(
+	response: &mut Response,
+	sink: &mut Sink,
+	max_bytes: Option<u64>,
+) -> std::result::Result<(u64, bool), String> {
+	let mut received = 0u64;
+	while let Some(chunk) = response.chunk().await.map_err(|err| err.to_string())? {
+		let remaining = max_bytes.map_or(u64::MAX, |max| max - received);
+		let take = chunk
+			.len()
+			.min(usize::try_from(remaining).unwrap_or(usize::MAX));
+		match sink {
+			Sink::Memory(buf) => buf.extend_from_slice(&chunk[..take]),
+			Sink::File(file) => file
+				.write_all(&chunk[..take])
+				.await
+				.map_err(|err| err.to_string())?,
+		}
+		received += take as u64;
+		if take < chunk.len() {
+			return Ok((received, true));
+		}
+	}
+	Ok((received, false))
+}
+
+async fn fetch(request: FetchRequest) -> Result<FetchResult> {
+	let started = Instant::now();
+	let client = reqwest::Client::builder()
+		.redirect(request.redirect)
+		.build()
+		.map_err(|err| Error::from_reason(format!("Failed to create HTTP client: {err}")))?;
+	let mut builder = client
+		.request(request.method, request.url.clone())
+		.headers(request.headers);
+	if let Some(body) = request.body {
+		builder = builder.body(body);
+	}
+	let mut response = builder
+		.send()
+		.await
+		.map_err(|err| Error::from_reason(format!("Fetch failed for {}: {err}", request.url)))?;
+
+	let status = response.status();
+	let url = response.url().to_string();
+	let headers = response_headers(response.headers());
+	let encoding = text_encoding(
+		response
+			.headers()
+			.get(CONTENT_TYPE)
+			.and_then(|value| value.to_str().ok()),
+	);
+
+	let mut sink = match &request.output_path {
+		Some(path) => Sink::File(
+			fs::File::create(path)
+				.await
+				.map_err(|err| Error::from_reason(format!("Failed to create {path}: {err}")))?,
+		),
+		None => Sink::Memory(Vec::new()),
+	};
+	let (bytes, truncated) = match read_body(&mut response, &mut sink, request.max_bytes).await {
+		Ok(read) => read,
+		Err(err) => {
+			drop(sink);
+			if let Some(path) = &request.output_path {
+				let _ = fs::remove_file(Path::new(path)).await;
+			}
+			return Err(Error::from_reason(format!("Fetch failed reading {url}: {err}")));
+		},
+	};
+
+	let (body, text) = match sink {
+		Sink::Memory(buf) => {
+			let text = encoding.map(|encoding| encoding.decode(&buf).0.into_owned());
+			(Some(buf.into()), text)
+		},
+		Sink::File(mut file) => {
+			file.flush().await.map_err(|err| {
+				Error::from_reason(format!(
+					"Failed to write {}: {err}",
+					request.output_path.unwrap_or_default()
+				))
+			})?;
+			(None, None)
+		},
+	};
+
+	Ok(FetchResult {
+		status: u32::from(status.as_u16()),
+		status_text: status.canonical_reason().unwrap_or_default().to_string(),
+		url,
+		headers,
+		body,
+		text,
+		bytes: bytes as f64,
+		truncated,
+		duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+	})
+}
+
+/// Make an HTTP request and return the response, reading at most `maxBytes`
+/// of its body.
+///
+/// Non-2xx statuses are returned as results, not errors.
+///
+/// # Errors
+/// Returns an error for invalid options, connection, TLS or protocol
+/// failures, too many redirects, and on abort or timeout (`Aborted: ...`).
+/// A partially written `outputPath` is removed when reading the body fails.
+#[napi(js_name = "fetchUrl")]
+pub fn fetch_url<'env>(
+	env: &'env Env,
+	options: FetchOptions<'env>,
+) -> Result<PromiseRaw<'env, FetchResult>> {
+	let (request, ct) = FetchRequest::parse(options)?;
+	let output_path = request.output_path.clone();
+	task::future(env, "fetch_url", async move {
+		tokio::select! {
+			result = fetch(request) => result,
+			reason = ct.wait() => {
+				if let Some(path) = output_path {
+					let _ = fs::remove_file(path).await;
+				}
+				Err(Error::from_reason(format!("Aborted: {reason:?}")))
+			},
+		}
+	})
+}
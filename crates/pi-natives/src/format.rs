@@ -0,0 +1,426 @@
+//! Source formatting through the project's own formatter.
+//!
+//! # Overview
+//! `formatFile` pipes a file's content through a formatter's stdin mode and
+//! returns the formatted text with a unified diff against the input. The
+//! formatter runs from the file's directory, so it picks up the same
+//! configuration it would from an editor, in its own process group; its
+//! process tree is killed on timeout or abort.
+//!
+//! With `formatter: "auto"` the formatter is chosen from the file extension
+//! and the configuration found above the file:
+//! - `.rs`: rustfmt, with the edition of the nearest `Cargo.toml`
+//! - JS, TS, JSON, CSS and GraphQL: Biome when a `biome.json(c)` is found,
+//!   otherwise Prettier, which also handles Markdown, YAML, HTML and more
+//! - `.py`: Ruff when configured (`ruff.toml`, `[tool.ruff]`), otherwise Black
+//! - `.go`: gofmt
+//!
+//! Biome and Prettier run from the nearest `node_modules/.bin` when installed
+//! there, otherwise from `PATH`.
+//!
+//! With `range`, only the changes touching those lines are kept, so an edit
+//! can be formatted without restyling the rest of the file. The result keeps
+//! the file's line endings, encoding and BOM whatever the formatter emits.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { changed, diff } = await native.formatFile("src/main.rs", { range: { start: 10, end: 20 } });
+//! // JS: await native.formatFile("src/app.ts", { formatter: "prettier", write: true });
+//! ```
+
+use std::{
+	io,
+	ops::Range,
+	path::{Path, PathBuf},
+	process::Stdio,
+	sync::LazyLock,
+};
+
+use encoding_rs::UTF_8;
+use gix::diff::blob::{Algorithm, intern::InternedInput, sources::lines_with_terminator};
+use napi::{
+	bindgen_prelude::*,
+	tokio::{self, io::AsyncWriteExt as _, process::Command},
+};
+use napi_derive::napi;
+use regex::Regex;
+
+use crate::{
+	git, ps, read, task,
+	text_profile::{self, TextProfile},
+	workspace, write,
+};
+
+const DEFAULT_TIMEOUT_MS: u32 = 10_000;
+
+const DIFF_CONTEXT_LINES: u32 = 3;
+
+/// `SIGKILL`; the signal is ignored on Windows.
+const KILL_SIGNAL: i32 = 9;
+
+static CARGO_EDITION: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r#"(?m)^\s*edition\s*=\s*"(\d{4})""#).unwrap());
+
+/// An inclusive, 1-indexed range of lines.
+#[napi(object)]
+pub struct FormatLineRange {
+	/// First line.
+	pub start: u32,
+	/// Last line.
+	pub end:   u32,
+}
+
+/// Options for `formatFile`.
+#[napi(object)]
+#[derive(Default)]
+pub struct FormatOptions<'env> {
+	/// Formatter to run: `rustfmt`, `prettier`, `biome`, `black`, `ruff`,
+	/// `gofmt`, or `auto` (default) to choose from the file extension and
+	/// project configuration.
+	pub formatter:               Option<String>,
+	/// Only keep changes touching these lines of the input.
+	pub range:                   Option<FormatLineRange>,
+	/// Content to format instead of the file on disk. The path still selects
+	/// the formatter and its configuration.
+	pub text:                    Option<String>,
+	/// Write the result back to the file when it changed (default: false).
+	/// Fails with a conflict if the file changed while it was formatted.
+	pub write:                   Option<bool>,
+	/// Abort signal for cancelling the operation.
+	pub signal:                  Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the formatter (default: 10000).
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:              Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Result of `formatFile`.
+#[napi(object)]
+pub struct FormatResult {
+	/// Formatter that ran.
+	pub formatter: String,
+	/// Whether the formatted text differs from the input.
+	pub changed:   bool,
+	/// The formatted text.
+	pub text:      String,
+	/// Unified diff from the input to `text`; empty when unchanged.
+	pub diff:      String,
+}
+
+#[derive(Clone, Copy)]
+enum Formatter {
+	Rustfmt,
+	Prettier,
+	Biome,
+	Black,
+	Ruff,
+	Gofmt,
+}
+
+impl Formatter {
+	fn parse(name: &str) -> Option<Self> {
+		Some(match name {
+			"rustfmt" => Self::Rustfmt,
+			"prettier" => Self::Prettier,
+			"biome" => Self::Biome,
+			"black" => Self::Black,
+			"ruff" => Self::Ruff,
+			"gofmt" => Self::Gofmt,
+			_ => return None,
+		})
+	}
+
+	const fn name(self) -> &'static str {
+		match self {
+			Self::Rustfmt => "rustfmt",
+			Self::Prettier => "prettier",
+			Self::Biome => "biome",
+			Self::Black => "black",
+			Self::Ruff => "ruff",
+			Self::Gofmt => "gofmt",
+		}
+	}
+
+	/// Program and arguments formatting `path` from stdin to stdout.
+	fn command(self, path: &Path, dir: &Path) -> (PathBuf, Vec<String>) {
+		let path = path.to_string_lossy().into_owned();
+		match self {
+			Self::Rustfmt => {
+				let edition = cargo_edition(dir).unwrap_or_else(|| "2021".to_string());
+				(PathBuf::from("rustfmt"), vec!["--edition".to_string(), edition])
+			},
+			Self::Prettier => (node_bin(dir, "prettier"), vec!["--stdin-filepath".to_string(), path]),
+			Self::Biome => (node_bin(dir, "biome"), vec![
+				"format".to_string(),
+				format!("--stdin-file-path={path}"),
+			]),
+			Self::Black => (PathBuf::from("black"), vec![
+				"--quiet".to_string(),
+				"--stdin-filename".to_string(),
+				path,
+				"-".to_string(),
+			]),
+			Self::Ruff => (PathBuf::from("ruff"), vec![
+				"format".to_string(),
+				"--stdin-filename".to_string(),
+				path,
+			]),
+			Self::Gofmt => (PathBuf::from("gofmt"), Vec::new()),
+		}
+	}
+}
+
+/// Nearest file named one of `names` in `dir` or its ancestors.
+fn find_up(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+	dir.ancestors()
+		.flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+		.find(|path| path.is_file())
+}
+
+/// Edition of the nearest `Cargo.toml` that sets one, skipping manifests
+/// that inherit it from the workspace.
+fn cargo_edition(dir: &Path) -> Option<String> {
+	dir.ancestors().find_map(|dir| {
+		let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+		Some(CARGO_EDITION.captures(&manifest)?[1].to_string())
+	})
+}
+
+/// `name` from the nearest `node_modules/.bin`, or `name` for a `PATH`
+/// lookup.
+fn node_bin(dir: &Path, name: &str) -> PathBuf {
+	let file = if cfg!(windows) {
+		format!("{name}.cmd")
+	} else {
+		name.to_string()
+	};
+	dir.ancestors()
+		.map(|dir| dir.join("node_modules").join(".bin").join(&file))
+		.find(|path| path.is_file())
+		.unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// Formatter for `path` from its extension and project configuration.
+fn detect(path: &Path, dir: &Path) -> Result<Formatter> {
+	let ext = path
+		.extension()
+		.and_then(|ext| ext.to_str())
+		.unwrap_or_default()
+		.to_ascii_lowercase();
+	match ext.as_str() {
+		"rs" => Ok(Formatter::Rustfmt),
+		"go" => Ok(Formatter::Gofmt),
+		"py" | "pyi" => {
+			let ruff = find_up(dir, &["ruff.toml", ".ruff.toml"]).is_some()
+				|| find_up(dir, &["pyproject.toml"])
+					.and_then(|path| std::fs::read_to_string(path).ok())
+					.is_some_and(|manifest| manifest.contains("[tool.ruff"));
+			Ok(if ruff {
+				Formatter::Ruff
+			} else {
+				Formatter::Black
+			})
+		},
+		"js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "json" | "jsonc" | "css"
+		| "graphql" | "gql"
+			if find_up(dir, &["biome.json", "biome.jsonc"]).is_some() =>
+		{
+			Ok(Formatter::Biome)
+		},
+		"js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "json" | "jsonc" | "json5"
+		| "css" | "scss" | "less" | "graphql" | "gql" | "html" | "vue" | "svelte" | "md" | "mdx"
+		| "yaml" | "yml" => Ok(Formatter::Prettier),
+		_ => Err(Error::from_reason(format!("No formatter for {}", path.display()))),
+	}
+}
+
+/// Kills a formatter's process tree unless disarmed, so a timed out or
+/// aborted run leaves nothing behind.
+struct ProcessTree(Option<u32>);
+
+impl Drop for ProcessTree {
+	fn drop(&mut self) {
+		if let Some(pid) = self.0 {
+			ps::kill_tree(pid as i32, KILL_SIGNAL);
+			#[cfg(unix)]
+			ps::kill_process_group(pid as i32, KILL_SIGNAL);
+		}
+	}
+}
+
+async fn run(formatter: Formatter, path: &Path, dir: &Path, input: String) -> Result<String> {
+	let name = formatter.name();
+	let (program, args) = formatter.command(path, dir);
+	let mut cmd = Command::new(program);
+	cmd.args(args)
+		.current_dir(dir)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped());
+	#[cfg(unix)]
+	cmd.process_group(0);
+	let mut child = cmd.spawn().map_err(|err| match err.kind() {
+		io::ErrorKind::NotFound => Error::from_reason(format!("{name} is not installed")),
+		_ => Error::from_reason(format!("Failed to start {name}: {err}")),
+	})?;
+	let mut tree = ProcessTree(child.id());
+	let mut stdin = child
+		.stdin
+		.take()
+		.ok_or_else(|| Error::from_reason(format!("Failed to open {name} stdin")))?;
+	let feed = async move {
+		// A formatter that exits early reports why on stderr.
+		let _ = stdin.write_all(input.as_bytes()).await;
+	};
+	let ((), output) = tokio::join!(feed, child.wait_with_output());
+	tree.0 = None;
+	let output = output.map_err(|err| Error::from_reason(format!("Failed to run {name}: {err}")))?;
+	if !output.status.success() {
+		let stderr = String::from_utf8_lossy(&output.stderr);
+		return Err(Error::from_reason(format!("{name} failed: {}", stderr.trim())));
+	}
+	String::from_utf8(output.stdout)
+		.map_err(|_| Error::from_reason(format!("{name} produced invalid UTF-8")))
+}
+
+/// `after` with only the changes that touch lines `start..=end` (1-indexed)
+/// of `before` applied.
+fn restrict(before: &str, after: &str, start: u32, end: u32) -> String {
+	let old = lines_with_terminator(before).collect::<Vec<_>>();
+	let new = lines_with_terminator(after).collect::<Vec<_>>();
+	let (first, last) = (start.saturating_sub(1) as usize, end as usize);
+	let mut text = String::with_capacity(after.len());
+	let mut pos = 0;
+	let input = InternedInput::new(lines_with_terminator(before), lines_with_terminator(after));
+	gix::diff::blob::diff(Algorithm::Histogram, &input, |removed: Range<u32>, added: Range<u32>| {
+		let removed = removed.start as usize..removed.end as usize;
+		text.extend(old[pos..removed.start].iter().copied());
+		// Insertions count when they sit inside or right after the range.
+		let touches = if removed.is_empty() {
+			(first..=last).contains(&removed.start)
+		} else {
+			removed.start < last && removed.end > first
+		};
+		if touches {
+			text.extend(
+				new[added.start as usize..added.end as usize]
+					.iter()
+					.copied(),
+			);
+		} else {
+			text.extend(old[removed.clone()].iter().copied());
+		}
+		pos = removed.end;
+	});
+	text.extend(old[pos..].iter().copied());
+	text
+}
+
+struct FormatRequest {
+	path:      PathBuf,
+	formatter: Option<String>,
+	range:     Option<(u32, u32)>,
+	text:      Option<String>,
+	write:     bool,
+}
+
+async fn format(request: FormatRequest) -> Result<FormatResult> {
+	let FormatRequest { path, formatter, range, text, write: write_back } = request;
+	let dir = match path.parent() {
+		Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+		_ => PathBuf::from("."),
+	};
+	let formatter = match formatter.as_deref() {
+		None | Some("auto") => detect(&path, &dir)?,
+		Some(name) => Formatter::parse(name)
+			.ok_or_else(|| Error::from_reason(format!("Unknown formatter: {name}")))?,
+	};
+	// Text from disk is decoded, and the result keeps its line endings,
+	// encoding and BOM.
+	let (input, encoding, bom, disk_hash) = if let Some(text) = text {
+		(text, UTF_8, false, None)
+	} else {
+		let bytes = tokio::fs::read(&path)
+			.await
+			.map_err(|err| Error::from_reason(format!("Failed to read {}: {err}", path.display())))?;
+		let (text, encoding, bom) = read::decode(&bytes).ok_or_else(|| {
+			Error::from_reason(format!("Cannot format binary file {}", path.display()))
+		})?;
+		(text, encoding, bom, Some(write::content_hash(&bytes)))
+	};
+	let profile = text_profile::detect(&input, encoding, bom);
+
+	let formatted = run(formatter, &path, &dir, input.clone()).await?;
+	let profile = TextProfile { final_newline: formatted.ends_with('\n'), ..profile };
+	let formatted = text_profile::normalize(&profile, &formatted);
+	let text = match range {
+		Some((start, end)) => restrict(&input, &formatted, start, end),
+		None => formatted,
+	};
+	let changed = text != input;
+	let diff = if changed {
+		let name = path.to_string_lossy();
+		let (from, to) = if path.is_absolute() {
+			(name.to_string(), name.to_string())
+		} else {
+			(format!("a/{name}"), format!("b/{name}"))
+		};
+		let hunks = git::diff_hunks(input.as_bytes(), text.as_bytes(), DIFF_CONTEXT_LINES);
+		format!("--- {from}\n+++ {to}\n{}", hunks.text)
+	} else {
+		String::new()
+	};
+
+	if changed && write_back {
+		let profile = TextProfile { final_newline: text.ends_with('\n'), ..profile };
+		let options = write::WriteFileOptions {
+			expected_hash: disk_hash,
+			profile: Some(profile),
+			..Default::default()
+		};
+		let (path, text) = (path.to_string_lossy().into_owned(), text.clone());
+		tokio::task::spawn_blocking(move || write::write_file_sync(&path, &text, options))
+			.await
+			.map_err(|err| Error::from_reason(format!("Failed to write formatted file: {err}")))??;
+	}
+
+	Ok(FormatResult { formatter: formatter.name().to_string(), changed, text, diff })
+}
+
+/// Format a file with the project's formatter and return the result and a
+/// diff against the input.
+///
+/// # Errors
+/// Returns an error when no formatter applies to the file, the formatter is
+/// not installed or fails (with its stderr), the file cannot be read or
+/// written back (`Conflict: ...` if it changed meanwhile), the path is
+/// outside the workspace, and on abort or timeout (`Aborted: ...`).
+#[napi(js_name = "formatFile")]
+pub fn format_file<'env>(
+	env: &'env Env,
+	path: String,
+	options: Option<FormatOptions<'env>>,
+) -> Result<PromiseRaw<'env, FormatResult>> {
+	let options = options.unwrap_or_default();
+	let ct = task::CancelToken::new(
+		Some(options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+		options.signal,
+	);
+	let allow_outside = options.allow_outside_workspace;
+	let request = FormatRequest {
+		path:      PathBuf::from(path),
+		formatter: options.formatter,
+		range:     options.range.map(|range| (range.start, range.end)),
+		text:      options.text,
+		write:     options.write.unwrap_or(false),
+	};
+	task::future(env, "format_file", async move {
+		workspace::check(&request.path, allow_outside)?;
+		tokio::select! {
+			result = format(request) => result,
+			reason = ct.wait() => Err(Error::from_reason(format!("Aborted: {reason:?}"))),
+		}
+	})
+}
@@ -51,6 +51,8 @@ pub struct GlobMatch {
 	/// Modification time in milliseconds since Unix epoch (from
 	/// `symlink_metadata`).
 	pub mtime:     Option<f64>,
+	/// Size in bytes (regular files only).
+	pub size:      Option<f64>,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -186,7 +188,8 @@ pub fn should_skip_path(path: &Path, mentions_node_modules: bool) -> bool {
 	false
 }
 
-pub fn classify_file_type(path: &Path) -> Option<(FileType, Option<f64>)> {
+/// Classify `path` and read its mtime (ms) and, for regular files, its size.
+pub fn classify_file_type(path: &Path) -> Option<(FileType, Option<f64>, Option<f64>)> {
 	let metadata = std::fs::symlink_metadata(path).ok()?;
 	let file_type = metadata.file_type();
 	let mtime_ms = metadata
@@ -195,11 +198,11 @@ pub fn classify_file_type(path: &Path) -> Option<(FileType, Option<f64>)> {
 		.and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
 		.map(|d| d.as_millis() as f64);
 	if file_type.is_symlink() {
-		Some((FileType::Symlink, mtime_ms))
+		Some((FileType::Symlink, mtime_ms, None))
 	} else if file_type.is_dir() {
-		Some((FileType::Dir, mtime_ms))
+		Some((FileType::Dir, mtime_ms, None))
 	} else {
-		Some((FileType::File, mtime_ms))
+		Some((FileType::File, mtime_ms, Some(metadata.len() as f64)))
 	}
 }
 
@@ -267,11 +270,11 @@ fn collect_entries(
 			continue;
 		}
 
-		let Some((file_type, mtime)) = classify_file_type(path) else {
+		let Some((file_type, mtime, size)) = classify_file_type(path) else {
 			continue;
 		};
 
-		entries.push(GlobMatch { path: relative.into_owned(), file_type, mtime });
+		entries.push(GlobMatch { path: relative.into_owned(), file_type, mtime, size });
 	}
 
 	Ok(entries)
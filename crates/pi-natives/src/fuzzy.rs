@@ -0,0 +1,430 @@
+//! fzf-style fuzzy matching for pickers.
+//!
+//! # Overview
+//! `fuzzyMatch` scores every candidate against a query in parallel and
+//! returns the best matches with the positions to highlight.
+//!
+//! Scoring follows fzf's v2 algorithm: a query character matches once per
+//! candidate character, in order, and the alignment with the best score is
+//! found by dynamic programming. Matches earn bonuses at word boundaries
+//! (after whitespace, `/`, `_`, a camelCase hump or a digit run) and for
+//! runs of consecutive characters, and pay penalties for gaps, so `fb`
+//! ranks `foo/bar.rs` above `fooxbar.rs`.
+//!
+//! Whitespace separates query terms that must all match, in any order. The
+//! match is case-insensitive unless the query has an uppercase letter.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const top = await native.fuzzyMatch("srmain", paths, { limit: 50 })
+//! // JS: top[0] // { index: 42, score: 212, positions: [0, 1, 4, 5, 6, 7] }
+//! ```
+
+use std::cmp::Ordering;
+
+use napi_derive::napi;
+use rayon::prelude::*;
+
+use crate::task;
+
+const DEFAULT_LIMIT: usize = 100;
+
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP_START: i32 = -3;
+const SCORE_GAP_EXTENSION: i32 = -1;
+const BONUS_BOUNDARY: i32 = SCORE_MATCH / 2;
+const BONUS_BOUNDARY_WHITE: i32 = BONUS_BOUNDARY + 2;
+const BONUS_BOUNDARY_DELIMITER: i32 = BONUS_BOUNDARY + 1;
+const BONUS_NON_WORD: i32 = SCORE_MATCH / 2;
+const BONUS_CAMEL_123: i32 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+const BONUS_CONSECUTIVE: i32 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+/// Score of cells no alignment reaches.
+const UNREACHABLE: i32 = i32::MIN / 2;
+
+/// Options for `fuzzyMatch`.
+#[napi(object)]
+#[derive(Default)]
+pub struct FuzzyMatchOptions {
+	/// Maximum number of matches to return (default: 100).
+	pub limit:          Option<u32>,
+	/// Match case exactly (default: only when the query has an uppercase
+	/// letter).
+	#[napi(js_name = "caseSensitive")]
+	pub case_sensitive: Option<bool>,
+}
+
+/// A candidate matching the query.
+#[napi(object)]
+pub struct FuzzyMatch {
+	/// Index of the candidate in the input.
+	pub index:     u32,
+	/// Match score (higher is better).
+	pub score:     i32,
+	/// UTF-16 offsets of the matched characters, ascending.
+	pub positions: Vec<u32>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CharClass {
+	White,
+	NonWord,
+	Delimiter,
+	Lower,
+	Upper,
+	Letter,
+	Number,
+}
+
+fn char_class(c: char) -> CharClass {
+	match c {
+		' ' | '\t' | '\n' | '\r' => CharClass::White,
+		'/' | ',' | ':' | ';' | '|' | '\\' => CharClass::Delimiter,
+		c if c.is_ascii_lowercase() => CharClass::Lower,
+		c if c.is_ascii_uppercase() => CharClass::Upper,
+		c if c.is_ascii_digit() => CharClass::Number,
+		c if c.is_lowercase() => CharClass::Lower,
+		c if c.is_uppercase() => CharClass::Upper,
+		c if c.is_alphabetic() => CharClass::Letter,
+		c if c.is_numeric() => CharClass::Number,
+		c if c.is_whitespace() => CharClass::White,
+		_ => CharClass::NonWord,
+	}
+}
+
+/// Bonus for matching a character of class `class` after one of `prev`.
+fn bonus_for(prev: CharClass, class: CharClass) -> i32 {
+	if class > CharClass::NonWord {
+		match prev {
+			CharClass::White => return BONUS_BOUNDARY_WHITE,
+			CharClass::Delimiter => return BONUS_BOUNDARY_DELIMITER,
+			CharClass::NonWord => return BONUS_BOUNDARY,
+			_ => {},
+		}
+	}
+	if (prev == CharClass::Lower && class == CharClass::Upper)
+		|| (prev != CharClass::Number && class == CharClass::Number)
+	{
+		return BONUS_CAMEL_123;
+	}
+	match class {
+		CharClass::NonWord | CharClass::Delimiter => BONUS_NON_WORD,
+		CharClass::White => BONUS_BOUNDARY_WHITE,
+		_ => 0,
+	}
+}
+
+/// Fold `c` for case-insensitive comparison.
+fn fold(c: char) -> char {
+	if c.is_ascii() {
+		c.to_ascii_lowercase()
+	} else {
+		c.to_lowercase().next().unwrap_or(c)
+	}
+}
+
+/// Whether every term's characters occur in `text` in order.
+fn is_candidate(text: &str, terms: &[Vec<char>], case_sensitive: bool) -> bool {
+	terms.iter().all(|term| {
+		let mut wanted = term.iter().peekable();
+		for c in text.chars() {
+			let c = if case_sensitive { c } else { fold(c) };
+			if wanted.next_if(|&&w| w == c).is_some() && wanted.peek().is_none() {
+				return true;
+			}
+		}
+		false
+	})
+}
+
+/// Per-thread buffers reused across candidates.
+#[derive(Default)]
+struct Scratch {
+	chars:  Vec<char>,
+	bonus:  Vec<i32>,
+	scores: Vec<i32>,
+	runs:   Vec<u32>,
+}
+
+impl Scratch {
+	fn load(&mut self, text: &str, case_sensitive: bool) {
+		self.chars.clear();
+		self.bonus.clear();
+		let mut prev = CharClass::White;
+		for c in text.chars() {
+			let class = char_class(c);
+			self.bonus.push(bonus_for(prev, class));
+			self.chars.push(if case_sensitive { c } else { fold(c) });
+			prev = class;
+		}
+	}
+
+	/// Best alignment of `term` in the loaded text: its score, and the
+	/// matched character indices pushed to `positions`.
+	fn align(&mut self, term: &[char], positions: &mut Vec<usize>) -> Option<i32> {
+		let text = &self.chars;
+		let m = term.len();
+		// Narrow to the first possible start and last possible end.
+		let mut first = None;
+		let mut next = 0;
+		for (j, &c) in text.iter().enumerate() {
+			if c == term[next] {
+				first.get_or_insert(j);
+				next += 1;
+				if next == m {
+					break;
+				}
+			}
+		}
+		let first = first.filter(|_| next == m)?;
+		let last = text.iter().rposition(|&c| c == term[m - 1])? + 1;
+		let width = last - first;
+
+		self.scores.clear();
+		self.scores.resize(m * width, UNREACHABLE);
+		self.runs.clear();
+		self.runs.resize(m * width, 0);
+		for (i, &qc) in term.iter().enumerate() {
+			let row = i * width;
+			for col in 0..width {
+				let j = first + col;
+				// Skipping this character: a new or continued gap.
+				let gap = if col == 0 {
+					UNREACHABLE
+				} else {
+					let left = self.scores[row + col - 1];
+					left
+						+ if self.runs[row + col - 1] > 0 {
+							SCORE_GAP_START
+						} else {
+							SCORE_GAP_EXTENSION
+						}
+				};
+				let mut best = gap;
+				let mut run = 0;
+				if text[j] == qc {
+					let (diag, diag_run) = match (i, col) {
+						(0, _) => (0, 0),
+						(_, 0) => (UNREACHABLE, 0),
+						_ => {
+							let prev = row - width + col - 1;
+							(self.scores[prev], self.runs[prev])
+						},
+					};
+					if diag > UNREACHABLE {
+						let mut consecutive = diag_run + 1;
+						let mut bonus = self.bonus[j];
+						if consecutive > 1 {
+							let run_bonus = self.bonus[j + 1 - consecutive as usize];
+							// A boundary inside a run starts a new run.
+							if bonus >= BONUS_BOUNDARY && bonus > run_bonus {
+								consecutive = 1;
+							} else {
+								bonus = bonus.max(BONUS_CONSECUTIVE).max(run_bonus);
+							}
+						}
+						if i == 0 {
+							bonus *= BONUS_FIRST_CHAR_MULTIPLIER;
+						}
+						let score = diag + SCORE_MATCH + bonus;
+						if score >= best {
+							best = score;
+							run = consecutive;
+						}
+					}
+				}
+				self.scores[row + col] = best;
+				self.runs[row + col] = run;
+			}
+		}
+
+		let last_row = (m - 1) * width;
+		let (mut col, &score) = self.scores[last_row..last_row + width]
+			.iter()
+			.enumerate()
+			.rev()
+			.max_by_key(|&(_, score)| *score)?;
+		if score <= UNREACHABLE {
+			return None;
+		}
+		// A cell with a run ended in a match; otherwise it skipped a character.
+		let start = positions.len();
+		for i in (0..m).rev() {
+			while self.runs[i * width + col] == 0 {
+				col -= 1;
+			}
+			positions.push(first + col);
+			col = col.saturating_sub(1);
+		}
+		positions[start..].reverse();
+		Some(score)
+	}
+}
+
+struct Scored {
+	index:     usize,
+	score:     i32,
+	len:       usize,
+	positions: Vec<usize>,
+}
+
+/// Best first; ties go to shorter, then earlier candidates.
+fn rank(a: &Scored, b: &Scored) -> Ordering {
+	b.score
+		.cmp(&a.score)
+		.then(a.len.cmp(&b.len))
+		.then(a.index.cmp(&b.index))
+}
+
+/// UTF-16 offsets of the character indices `positions` (ascending) in `text`.
+fn utf16_positions(text: &str, positions: &[usize]) -> Vec<u32> {
+	let mut offsets = Vec::with_capacity(positions.len());
+	let mut wanted = positions.iter().peekable();
+	let mut offset = 0;
+	for (index, c) in text.chars().enumerate() {
+		if wanted.next_if(|&&position| position == index).is_some() {
+			offsets.push(offset);
+		}
+		offset += c.len_utf16() as u32;
+	}
+	offsets
+}
+
+fn fuzzy_match_sync(
+	query: &str,
+	candidates: &[String],
+	limit: usize,
+	case_sensitive: bool,
+) -> Vec<FuzzyMatch> {
+	let terms = query
+		.split_whitespace()
+		.map(|term| {
+			term
+				.chars()
+				.map(|c| if case_sensitive { c } else { fold(c) })
+				.collect::<Vec<_>>()
+		})
+		.collect::<Vec<_>>();
+	if terms.is_empty() {
+		return (0..candidates.len().min(limit))
+			.map(|index| FuzzyMatch { index: index as u32, score: 0, positions: Vec::new() })
+			.collect();
+	}
+
+	let mut scored = candidates
+		.par_iter()
+		.enumerate()
+		.map_init(Scratch::default, |scratch, (index, text)| {
+			if !is_candidate(text, &terms, case_sensitive) {
+				return None;
+			}
+			scratch.load(text, case_sensitive);
+			let mut positions = Vec::new();
+			let mut score = 0;
+			for term in &terms {
+				score += scratch.align(term, &mut positions)?;
+			}
+			Some(Scored { index, score, len: scratch.chars.len(), positions })
+		})
+		.flatten()
+		.collect::<Vec<_>>();
+	if scored.len() > limit && limit > 0 {
+		scored.select_nth_unstable_by(limit - 1, rank);
+	}
+	scored.truncate(limit);
+	scored.sort_unstable_by(rank);
+
+	scored
+		.into_iter()
+		.map(|mut matched| {
+			matched.positions.sort_unstable();
+			matched.positions.dedup();
+			FuzzyMatch {
+				index:     matched.index as u32,
+				score:     matched.score,
+				positions: utf16_positions(&candidates[matched.index], &matched.positions),
+			}
+		})
+		.collect()
+}
+
+/// Rank `candidates` against `query` with fzf-style scoring, best first.
+///
+/// An empty query matches every candidate, in input order.
+#[napi(js_name = "fuzzyMatch")]
+pub fn fuzzy_match(
+	query: String,
+	candidates: Vec<String>,
+	options: Option<FuzzyMatchOptions>,
+) -> task::Async<Vec<FuzzyMatch>> {
+	let options = options.unwrap_or_default();
+	let limit = options.limit.map_or(DEFAULT_LIMIT, |limit| limit as usize);
+	let case_sensitive = options
+		.case_sensitive
+		.unwrap_or_else(|| query.chars().any(char::is_uppercase));
+	task::blocking("fuzzy_match", (), move |_| {
+		Ok(fuzzy_match_sync(&query, &candidates, limit, case_sensitive))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn ranked(query: &str, candidates: &[&str]) -> Vec<String> {
+		let candidates: Vec<String> = candidates.iter().map(|c| (*c).to_string()).collect();
+		fuzzy_match_sync(query, &candidates, DEFAULT_LIMIT, false)
+			.into_iter()
+			.map(|matched| candidates[matched.index as usize].clone())
+			.collect()
+	}
+
+	#[test]
+	fn test_boundary_bonus() {
+		assert_eq!(ranked("fb", &["fooxbar.rs", "foo/bar.rs"]), ["foo/bar.rs", "fooxbar.rs"]);
+		assert_eq!(ranked("gm", &["program", "git_main"]), ["git_main", "program"]);
+		assert_eq!(ranked("fb", &["afoobar", "fooBar"]), ["fooBar", "afoobar"]);
+	}
+
+	#[test]
+	fn test_consecutive_bonus() {
+		assert_eq!(ranked("main", &["m_a_i_n.rs", "src/main.rs"]), ["src/main.rs", "m_a_i_n.rs"]);
+	}
+
+	#[test]
+	fn test_ties_prefer_shorter_then_earlier() {
+		assert_eq!(ranked("abc", &["abc/long", "abc/x", "abc/y"]), ["abc/x", "abc/y", "abc/long"]);
+	}
+
+	#[test]
+	fn test_terms_match_in_any_order() {
+		assert_eq!(ranked("rs main", &["src/main.rs", "src/lib.rs", "main.go"]), ["src/main.rs"]);
+	}
+
+	#[test]
+	fn test_case_sensitivity() {
+		let candidates = vec!["Makefile".to_string(), "makefile".to_string()];
+		let insensitive = fuzzy_match_sync("make", &candidates, DEFAULT_LIMIT, false);
+		assert_eq!(insensitive.len(), 2);
+		let sensitive = fuzzy_match_sync("Make", &candidates, DEFAULT_LIMIT, true);
+		assert_eq!(sensitive.len(), 1);
+		assert_eq!(sensitive[0].index, 0);
+	}
+
+	#[test]
+	fn test_positions_are_utf16_offsets() {
+		let candidates = vec!["😀ab".to_string()];
+		let matches = fuzzy_match_sync("ab", &candidates, DEFAULT_LIMIT, false);
+		assert_eq!(matches[0].positions, [2, 3]);
+	}
+
+	#[test]
+	fn test_empty_query_and_limit() {
+		let candidates: Vec<String> = ["b", "a", "c"].iter().map(|c| (*c).to_string()).collect();
+		let all = fuzzy_match_sync("  ", &candidates, 2, false);
+		assert_eq!(all.iter().map(|matched| matched.index).collect::<Vec<_>>(), [0, 1]);
+		let candidates: Vec<String> = (0..10).map(|n| format!("file{n}")).collect();
+		assert_eq!(fuzzy_match_sync("file", &candidates, 3, false).len(), 3);
+	}
+}
@@ -0,0 +1,937 @@
+//! Git repository inspection without the git CLI.
+//!
+//! # Overview
+//! Backed by [`gix`] (gitoxide). Repositories are discovered upwards from the
+//! given root, like `git -C <root>`.
+//!
+//! - `gitStatus` reports, per path, the staged change (`HEAD` vs index) and the
+//!   unstaged change (index vs worktree), plus untracked files.
+//! - `gitDiff` renders unified patches for either side. Worktree files pass
+//!   through the repository's filters (e.g. CRLF normalization) first, so the
+//!   diff matches what git would show.
+//! - `gitLog` walks history from a revision, newest first.
+//! - `gitBlame` attributes each line of a file, as of a revision, to the commit
+//!   that last changed it, following renames.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { branch, entries } = await native.gitStatus(".");
+//! // JS: const { files, patch } = await native.gitDiff({ root: ".", staged: true });
+//! // JS: const commits = await native.gitLog({ root: ".", limit: 10 });
+//! // JS: const { lines } = await native.gitBlame("src/main.rs", { range: { start: 10, end: 20 } });
+//! ```
+
+use std::{
+	collections::{BTreeMap, HashMap, hash_map::Entry},
+	fmt::Write as _,
+	fs,
+	io::{self, Read},
+	path::{Path, PathBuf},
+};
+
+use gix::{
+	bstr::{BStr, BString, ByteSlice},
+	diff::blob::{
+		Algorithm, UnifiedDiff,
+		intern::InternedInput,
+		sources::byte_lines_with_terminator,
+		unified_diff::{ConsumeHunk, ContextSize, DiffLineKind, HunkHeader},
+	},
+	filter::plumbing::pipeline::convert::ToGitOutcome,
+	status::{self, UntrackedFiles, index_worktree},
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::task;
+
+const DEFAULT_LOG_LIMIT: u32 = 50;
+
+const DEFAULT_CONTEXT_LINES: u32 = 3;
+
+/// Bytes inspected for NUL when deciding whether a blob is binary, as git
+/// does.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Bits of an entry mode that select file, symlink, or submodule.
+const FILE_TYPE_MASK: u32 = 0o170_000;
+
+/// Object cache for repeated blob lookups while diffing.
+const OBJECT_CACHE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Kind of change to a path.
+#[napi]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GitChangeKind {
+	/// Newly added (or intent-to-add) path.
+	Added      = 1,
+	/// Content or mode changed.
+	Modified   = 2,
+	/// Path removed.
+	Deleted    = 3,
+	/// Moved from `oldPath`.
+	Renamed    = 4,
+	/// Copied from `oldPath`.
+	Copied     = 5,
+	/// Changed between file, symlink, or submodule.
+	TypeChange = 6,
+	/// Not tracked by the index.
+	Untracked  = 7,
+	/// Unresolved merge conflict.
+	Conflicted = 8,
+}
+
+/// Options for `gitStatus`.
+#[napi(object)]
+pub struct GitStatusOptions<'env> {
+	/// Include untracked files (default: true).
+	pub untracked:  Option<bool>,
+	/// Abort signal for cancelling the operation.
+	pub signal:     Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the operation.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+}
+
+/// Status of a single path.
+#[napi(object)]
+pub struct GitStatusEntry {
+	/// Path relative to the repository root (uses `/` separators).
+	pub path:     String,
+	/// Previous path of a rename or copy.
+	#[napi(js_name = "oldPath")]
+	pub old_path: Option<String>,
+	/// Change between `HEAD` and the index.
+	pub staged:   Option<GitChangeKind>,
+	/// Change between the index and the worktree.
+	pub unstaged: Option<GitChangeKind>,
+}
+
+/// Result of `gitStatus`.
+#[napi(object)]
+pub struct GitStatus {
+	/// Absolute path of the worktree root.
+	pub root:    String,
+	/// Checked-out branch, absent when `HEAD` is detached.
+	pub branch:  Option<String>,
+	/// Commit `HEAD` points to, absent before the first commit.
+	pub head:    Option<String>,
+	/// Changed paths, sorted by path.
+	pub entries: Vec<GitStatusEntry>,
+}
+
+/// Options for `gitDiff`.
+#[napi(object)]
+pub struct GitDiffOptions<'env> {
+	/// Directory inside the repository.
+	pub root:       String,
+	/// Diff `HEAD` against the index instead of the index against the
+	/// worktree (default: false).
+	pub staged:     Option<bool>,
+	/// Limit the diff to these paths (files or directories, relative to the
+	/// repository root).
+	pub paths:      Option<Vec<String>>,
+	/// Lines of context around each hunk (default: 3).
+	pub context:    Option<u32>,
+	/// Abort signal for cancelling the operation.
+	pub signal:     Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the operation.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+}
+
+/// Diff of a single file.
+#[napi(object)]
+pub struct GitFileDiff {
+	/// Path relative to the repository root (uses `/` separators).
+	pub path:      String,
+	/// Previous path of a rename or copy.
+	#[napi(js_name = "oldPath")]
+	pub old_path:  Option<String>,
+	/// Kind of change.
+	pub kind:      GitChangeKind,
+	/// Whether either side is binary; binary files have no hunks.
+	pub binary:    bool,
+	/// Number of added lines.
+	pub additions: u32,
+	/// Number of removed lines.
+	pub deletions: u32,
+	/// Unified diff of this file, including its `diff --git` header.
+	pub patch:     String,
+}
+
+/// Result of `gitDiff`.
+#[napi(object)]
+pub struct GitDiff {
+	/// Changed files, sorted by path.
+	pub files: Vec<GitFileDiff>,
+	/// Concatenated patch of all files, as printed by `git diff`.
+	pub patch: String,
+}
+
+/// Options for `gitLog`.
+#[napi(object)]
+pub struct GitLogOptions<'env> {
+	/// Directory inside the repository.
+	pub root:         String,
+	/// Maximum number of commits to return (default: 50).
+	pub limit:        Option<u32>,
+	/// Revision to start from (default: "HEAD").
+	#[napi(js_name = "ref")]
+	pub reference:    Option<String>,
+	/// Follow only the first parent of merge commits (default: false).
+	#[napi(js_name = "firstParent")]
+	pub first_parent: Option<bool>,
+	/// Abort signal for cancelling the operation.
+	pub signal:       Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the operation.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:   Option<u32>,
+}
+
+/// A commit returned by `gitLog`.
+#[napi(object)]
+pub struct GitCommit {
+	/// Full commit hash.
+	pub sha:     String,
+	/// Parent commit hashes.
+	pub parents: Vec<String>,
+	/// Author name.
+	pub author:  String,
+	/// Author email.
+	pub email:   String,
+	/// Author time in milliseconds since the Unix epoch.
+	pub time:    f64,
+	/// First line of the message.
+	pub summary: String,
+	/// Full commit message.
+	pub message: String,
+}
+
+/// An inclusive, 1-indexed range of lines.
+#[napi(object)]
+pub struct GitLineRange {
+	/// First line.
+	pub start: u32,
+	/// Last line.
+	pub end:   u32,
+}
+
+/// Options for `gitBlame`.
+#[napi(object)]
+pub struct GitBlameOptions<'env> {
+	/// Only blame these lines.
+	pub range:      Option<GitLineRange>,
+	/// Revision to blame the file at (default: "HEAD").
+	#[napi(js_name = "ref")]
+	pub reference:  Option<String>,
+	/// Abort signal for cancelling the operation.
+	pub signal:     Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the operation.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+}
+
+/// Attribution of a single line.
+#[napi(object)]
+pub struct GitBlameLine {
+	/// Line number in the blamed file, 1-indexed.
+	pub line:          u32,
+	/// Line text without its terminator.
+	pub content:       String,
+	/// Hash of the commit that last changed the line.
+	pub sha:           String,
+	/// Author name.
+	pub author:        String,
+	/// Author email.
+	pub email:         String,
+	/// Author time in milliseconds since the Unix epoch.
+	pub time:          f64,
+	/// First line of the commit message.
+	pub summary:       String,
+	/// Line number in that commit's version of the file, 1-indexed.
+	#[napi(js_name = "originalLine")]
+	pub original_line: u32,
+	/// Path of the file in that commit, when it was since renamed.
+	#[napi(js_name = "originalPath")]
+	pub original_path: Option<String>,
+}
+
+/// Result of `gitBlame`.
+#[napi(object)]
+pub struct GitBlame {
+	/// Path relative to the repository root (uses `/` separators).
+	pub path:  String,
+	/// Commit the file was blamed at.
+	pub head:  String,
+	/// Blamed lines in file order.
+	pub lines: Vec<GitBlameLine>,
+}
+
+fn open(root: impl AsRef<Path>) -> Result<gix::Repository> {
+	let root = root.as_ref();
+	let mut repo = gix::discover(root).map_err(|err| {
+		Error::from_reason(format!("Failed to open repository at {}: {err}", root.display()))
+	})?;
+	repo.object_cache_size_if_unset(OBJECT_CACHE_BYTES);
+	Ok(repo)
+}
+
+/// Resolve a revision such as `HEAD`, a branch, or a hash to its commit.
+fn resolve_commit<'repo>(repo: &'repo gix::Repository, spec: &str) -> Result<gix::Commit<'repo>> {
+	repo
+		.rev_parse_single(spec)
+		.map_err(|err| git_error(&format!("resolve {spec}"), err))?
+		.object()
+		.map_err(|err| git_error("read object", err))?
+		.peel_to_commit()
+		.map_err(|err| git_error(&format!("resolve {spec}"), err))
+}
+
+fn git_error(action: &str, err: impl std::fmt::Display) -> Error {
+	Error::from_reason(format!("Failed to {action}: {err}"))
+}
+
+fn path_string(path: &BStr) -> String {
+	path.to_str_lossy().into_owned()
+}
+
+/// Whether `path` is one of `filters` or lies below one of them.
+fn path_selected(path: &BStr, filters: &[String]) -> bool {
+	filters.is_empty()
+		|| filters.iter().any(|filter| {
+			let filter = filter.trim_end_matches('/').as_bytes();
+			filter.is_empty()
+				|| path
+					.strip_prefix(filter)
+					.is_some_and(|rest| rest.is_empty() || rest[0] == b'/')
+		})
+}
+
+fn index_worktree_kind(item: &index_worktree::Item) -> Option<GitChangeKind> {
+	use index_worktree::iter::Summary;
+	Some(match item.summary()? {
+		Summary::Added => GitChangeKind::Untracked,
+		Summary::Removed => GitChangeKind::Deleted,
+		Summary::Modified => GitChangeKind::Modified,
+		Summary::TypeChange => GitChangeKind::TypeChange,
+		Summary::Renamed => GitChangeKind::Renamed,
+		Summary::Copied => GitChangeKind::Copied,
+		Summary::IntentToAdd => GitChangeKind::Added,
+		Summary::Conflict => GitChangeKind::Conflicted,
+	})
+}
+
+const fn tree_index_kind(change: &gix::diff::index::Change) -> GitChangeKind {
+	use gix::diff::index::ChangeRef;
+	match change {
+		ChangeRef::Addition { .. } => GitChangeKind::Added,
+		ChangeRef::Deletion { .. } => GitChangeKind::Deleted,
+		ChangeRef::Modification { previous_entry_mode, entry_mode, .. }
+			if previous_entry_mode.bits() & FILE_TYPE_MASK != entry_mode.bits() & FILE_TYPE_MASK =>
+		{
+			GitChangeKind::TypeChange
+		},
+		ChangeRef::Modification { .. } => GitChangeKind::Modified,
+		ChangeRef::Rewrite { copy: true, .. } => GitChangeKind::Copied,
+		ChangeRef::Rewrite { .. } => GitChangeKind::Renamed,
+	}
+}
+
+fn status_platform(
+	repo: &gix::Repository,
+	untracked: bool,
+) -> Result<status::Platform<'_, gix::progress::Discard>> {
+	let platform = repo
+		.status(gix::progress::Discard)
+		.map_err(|err| git_error("read status", err))?;
+	Ok(platform.untracked_files(if untracked {
+		UntrackedFiles::Files
+	} else {
+		UntrackedFiles::None
+	}))
+}
+
+fn git_status_sync(root: &str, untracked: bool, ct: &task::CancelToken) -> Result<GitStatus> {
+	let repo = open(root)?;
+	let mut entries: BTreeMap<String, GitStatusEntry> = BTreeMap::new();
+	let iter = status_platform(&repo, untracked)?
+		.into_iter(None)
+		.map_err(|err| git_error("read status", err))?;
+	for item in iter {
+		ct.heartbeat()?;
+		let item = item.map_err(|err| git_error("read status", err))?;
+		let (path, old_path, staged, unstaged) = match &item {
+			status::Item::IndexWorktree(item) => {
+				let Some(kind) = index_worktree_kind(item) else {
+					continue;
+				};
+				let old_path = match item {
+					index_worktree::Item::Rewrite { source, .. } => {
+						Some(path_string(source.rela_path()))
+					},
+					_ => None,
+				};
+				(path_string(item.rela_path()), old_path, None, Some(kind))
+			},
+			status::Item::TreeIndex(change) => {
+				let old_path = match change {
+					gix::diff::index::ChangeRef::Rewrite { source_location, .. } => {
+						Some(path_string(source_location.as_ref()))
+					},
+					_ => None,
+				};
+				(path_string(change.location()), old_path, Some(tree_index_kind(change)), None)
+			},
+		};
+		let entry = entries
+			.entry(path.clone())
+			.or_insert_with(|| GitStatusEntry { path, old_path: None, staged: None, unstaged: None });
+		entry.old_path = entry.old_path.take().or(old_path);
+		entry.staged = entry.staged.take().or(staged);
+		entry.unstaged = entry.unstaged.take().or(unstaged);
+	}
+
+	Ok(GitStatus {
+		root:    repo
+			.workdir()
+			.map_or_else(|| repo.git_dir().to_string_lossy(), Path::to_string_lossy)
+			.into_owned(),
+		branch:  repo
+			.head_name()
+			.ok()
+			.flatten()
+			.map(|name| path_string(name.shorten())),
+		head:    repo.head_id().ok().map(|id| id.to_string()),
+		entries: entries.into_values().collect(),
+	})
+}
+
+/// Report staged, unstaged and untracked changes of the repository containing
+/// `root`.
+///
+/// # Errors
+/// Returns an error if no repository is found or its status cannot be read.
+#[napi(js_name = "gitStatus")]
+pub fn git_status(root: String, options: Option<GitStatusOptions<'_>>) -> task::Async<GitStatus> {
+	let (untracked, ct) = match options {
+		Some(GitStatusOptions { untracked, signal, timeout_ms }) => {
+			(untracked.unwrap_or(true), task::CancelToken::new(timeout_ms, signal))
+		},
+		None => (true, task::CancelToken::default()),
+	};
+	task::blocking("git_status", ct, move |ct| git_status_sync(&root, untracked, &ct))
+}
+
+/// One side of a file diff.
+enum Side {
+	/// The path does not exist on this side.
+	Missing,
+	/// A blob in the object database.
+	Blob(gix::ObjectId),
+	/// The worktree file at this repository-relative path.
+	Worktree(gix::bstr::BString),
+}
+
+/// A file to diff, before its contents are loaded.
+struct FileChange {
+	path:     String,
+	old_path: Option<String>,
+	kind:     GitChangeKind,
+	before:   Side,
+	after:    Side,
+	/// Mode of the added or deleted entry, for the `diff --git` header.
+	mode:     u32,
+}
+
+fn staged_change(change: gix::diff::index::Change) -> FileChange {
+	use gix::diff::index::ChangeRef;
+	let kind = tree_index_kind(&change);
+	let path = path_string(change.location());
+	match change {
+		ChangeRef::Addition { id, entry_mode, .. } => FileChange {
+			path,
+			old_path: None,
+			kind,
+			before: Side::Missing,
+			after: Side::Blob(id.into_owned()),
+			mode: entry_mode.bits(),
+		},
+		ChangeRef::Deletion { id, entry_mode, .. } => FileChange {
+			path,
+			old_path: None,
+			kind,
+			before: Side::Blob(id.into_owned()),
+			after: Side::Missing,
+			mode: entry_mode.bits(),
+		},
+		ChangeRef::Modification { previous_id, id, entry_mode, .. } => FileChange {
+			path,
+			old_path: None,
+			kind,
+			before: Side::Blob(previous_id.into_owned()),
+			after: Side::Blob(id.into_owned()),
+			mode: entry_mode.bits(),
+		},
+		ChangeRef::Rewrite { source_location, source_id, id, entry_mode, .. } => FileChange {
+			path,
+			old_path: Some(path_string(source_location.as_ref())),
+			kind,
+			before: Side::Blob(source_id.into_owned()),
+			after: Side::Blob(id.into_owned()),
+			mode: entry_mode.bits(),
+		},
+	}
+}
+
+fn unstaged_change(item: index_worktree::Item) -> Option<FileChange> {
+	let kind = index_worktree_kind(&item)?;
+	// Untracked files and conflicts have no single index blob to diff against;
+	// submodules have no blob at all.
+	let index_worktree::Item::Modification { entry, rela_path, .. } = item else {
+		return None;
+	};
+	if entry.mode.contains(gix::index::entry::Mode::COMMIT) || kind == GitChangeKind::Conflicted {
+		return None;
+	}
+	let before = if kind == GitChangeKind::Added {
+		Side::Missing
+	} else {
+		Side::Blob(entry.id)
+	};
+	let after = if kind == GitChangeKind::Deleted {
+		Side::Missing
+	} else {
+		Side::Worktree(rela_path.clone())
+	};
+	Some(FileChange {
+		path: path_string(rela_path.as_ref()),
+		old_path: None,
+		kind,
+		before,
+		after,
+		mode: entry.mode.bits(),
+	})
+}
+
+/// Worktree contents converted to their git representation.
+fn read_worktree(
+	repo: &gix::Repository,
+	pipeline: &mut gix::filter::Pipeline<'_>,
+	index: &gix::index::State,
+	rela_path: &BStr,
+) -> io::Result<Vec<u8>> {
+	let rel = gix::path::from_bstr(rela_path);
+	let path = repo.workdir().unwrap_or_else(|| repo.git_dir()).join(&rel);
+	if fs::symlink_metadata(&path)?.is_symlink() {
+		return Ok(gix::path::into_bstr(fs::read_link(&path)?).to_vec());
+	}
+	let file = fs::File::open(&path)?;
+	let mut bytes = Vec::new();
+	match pipeline
+		.convert_to_git(file, &rel, index)
+		.map_err(io::Error::other)?
+	{
+		ToGitOutcome::Unchanged(mut file) => file.read_to_end(&mut bytes)?,
+		ToGitOutcome::Process(mut read) => read.read_to_end(&mut bytes)?,
+		ToGitOutcome::Buffer(buf) => {
+			bytes.extend_from_slice(buf);
+			buf.len()
+		},
+	};
+	Ok(bytes)
+}
+
+/// A hunk range as git prints it: the length is omitted when it is 1, and an
+/// empty range starts at the line before it.
+fn hunk_range(start: u32, len: u32) -> String {
+	match len {
+		0 => format!("{},0", start.saturating_sub(1)),
+		1 => start.to_string(),
+		_ => format!("{start},{len}"),
+	}
+}
+
+/// Collects hunks into unified diff text and counts changed lines.
+#[derive(Default)]
+pub(crate) struct PatchWriter {
+	pub(crate) text:      String,
+	pub(crate) additions: u32,
+	pub(crate) deletions: u32,
+}
+
+impl ConsumeHunk for PatchWriter {
+	type Out = Self;
+
+	fn consume_hunk(
+		&mut self,
+		header: HunkHeader,
+		lines: &[(DiffLineKind, &[u8])],
+	) -> io::Result<()> {
+		let _ = writeln!(
+			self.text,
+			"@@ -{} +{} @@",
+			hunk_range(header.before_hunk_start, header.before_hunk_len),
+			hunk_range(header.after_hunk_start, header.after_hunk_len)
+		);
+		for (kind, line) in lines {
+			let prefix = match kind {
+				DiffLineKind::Context => ' ',
+				DiffLineKind::Add => {
+					self.additions += 1;
+					'+'
+				},
+				DiffLineKind::Remove => {
+					self.deletions += 1;
+					'-'
+				},
+			};
+			self.text.push(prefix);
+			self.text.push_str(&line.to_str_lossy());
+			if !line.ends_with(b"\n") {
+				self.text.push_str("\n\\ No newline at end of file\n");
+			}
+		}
+		Ok(())
+	}
+
+	fn finish(self) -> Self::Out {
+		self
+	}
+}
+
+/// Unified diff hunks from `before` to `after`, without file headers.
+pub(crate) fn diff_hunks(before: &[u8], after: &[u8], context: u32) -> PatchWriter {
+	let input =
+		InternedInput::new(byte_lines_with_terminator(before), byte_lines_with_terminator(after));
+	gix::diff::blob::diff(
+		Algorithm::Histogram,
+		&input,
+		UnifiedDiff::new(&input, PatchWriter::default(), ContextSize::symmetrical(context)),
+	)
+	.unwrap_or_default()
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+	bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+fn render_file(change: &FileChange, before: &[u8], after: &[u8], context: u32) -> GitFileDiff {
+	let old_path = change.old_path.as_deref().unwrap_or(&change.path);
+	let mut patch = format!("diff --git a/{old_path} b/{}\n", change.path);
+	match change.kind {
+		GitChangeKind::Added => {
+			let _ = writeln!(patch, "new file mode {:06o}", change.mode);
+		},
+		GitChangeKind::Deleted => {
+			let _ = writeln!(patch, "deleted file mode {:06o}", change.mode);
+		},
+		GitChangeKind::Renamed | GitChangeKind::Copied => {
+			let verb = if change.kind == GitChangeKind::Copied {
+				"copy"
+			} else {
+				"rename"
+			};
+			let _ = writeln!(patch, "{verb} from {old_path}\n{verb} to {}", change.path);
+		},
+		_ => {},
+	}
+
+	let binary = is_binary(before) || is_binary(after);
+	let (mut additions, mut deletions) = (0, 0);
+	if before != after {
+		let from = if matches!(change.before, Side::Missing) {
+			"/dev/null".to_string()
+		} else {
+			format!("a/{old_path}")
+		};
+		let to = if matches!(change.after, Side::Missing) {
+			"/dev/null".to_string()
+		} else {
+			format!("b/{}", change.path)
+		};
+		if binary {
+			let _ = writeln!(patch, "Binary files {from} and {to} differ");
+		} else {
+			let writer = diff_hunks(before, after, context);
+			let _ = write!(patch, "--- {from}\n+++ {to}\n{}", writer.text);
+			additions = writer.additions;
+			deletions = writer.deletions;
+		}
+	}
+
+	GitFileDiff {
+		path: change.path.clone(),
+		old_path: change.old_path.clone(),
+		kind: change.kind,
+		binary,
+		additions,
+		deletions,
+		patch,
+	}
+}
+
+struct DiffConfig {
+	root:    String,
+	staged:  bool,
+	paths:   Vec<String>,
+	context: u32,
+}
+
+fn git_diff_sync(config: &DiffConfig, ct: &task::CancelToken) -> Result<GitDiff> {
+	let repo = open(&config.root)?;
+	let iter = status_platform(&repo, false)?
+		.into_iter(None)
+		.map_err(|err| git_error("read status", err))?;
+	let mut changes = Vec::new();
+	for item in iter {
+		ct.heartbeat()?;
+		let item = item.map_err(|err| git_error("read status", err))?;
+		let change = match item {
+			status::Item::TreeIndex(change) if config.staged => staged_change(change),
+			status::Item::IndexWorktree(item) if !config.staged => {
+				let Some(change) = unstaged_change(item) else {
+					continue;
+				};
+				change
+			},
+			_ => continue,
+		};
+		let selected = path_selected(change.path.as_bytes().as_bstr(), &config.paths)
+			|| change
+				.old_path
+				.as_ref()
+				.is_some_and(|old| path_selected(old.as_bytes().as_bstr(), &config.paths));
+		if selected {
+			changes.push(change);
+		}
+	}
+	changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+	let (mut pipeline, index) = repo
+		.filter_pipeline(None)
+		.map_err(|err| git_error("load git filters", err))?;
+	let mut load = |side: &Side| -> Result<Vec<u8>> {
+		match side {
+			Side::Missing => Ok(Vec::new()),
+			Side::Blob(id) => repo
+				.find_object(*id)
+				.map(|object| object.detach().data)
+				.map_err(|err| git_error("read object", err)),
+			Side::Worktree(path) => read_worktree(&repo, &mut pipeline, &index, path.as_ref())
+				.map_err(|err| git_error(&format!("read {path}"), err)),
+		}
+	};
+
+	let mut files = Vec::with_capacity(changes.len());
+	for change in &changes {
+		ct.heartbeat()?;
+		let before = load(&change.before)?;
+		let after = load(&change.after)?;
+		files.push(render_file(change, &before, &after, config.context));
+	}
+	let patch = files.iter().map(|file| file.patch.as_str()).collect();
+	Ok(GitDiff { files, patch })
+}
+
+/// Diff the index against the worktree, or `HEAD` against the index when
+/// `staged` is set.
+///
+/// # Errors
+/// Returns an error if no repository is found or a file cannot be read.
+#[napi(js_name = "gitDiff")]
+pub fn git_diff(options: GitDiffOptions<'_>) -> task::Async<GitDiff> {
+	let GitDiffOptions { root, staged, paths, context, signal, timeout_ms } = options;
+	let config = DiffConfig {
+		root,
+		staged: staged.unwrap_or(false),
+		paths: paths.unwrap_or_default(),
+		context: context.unwrap_or(DEFAULT_CONTEXT_LINES),
+	};
+	let ct = task::CancelToken::new(timeout_ms, signal);
+	task::blocking("git_diff", ct, move |ct| git_diff_sync(&config, &ct))
+}
+
+fn commit_info(commit: &gix::Commit<'_>) -> Result<GitCommit> {
+	let author = commit
+		.author()
+		.map_err(|err| git_error("decode commit", err))?
+		.trim();
+	let time = author.time().map_or(0, |time| time.seconds);
+	let message = commit
+		.message_raw()
+		.map_err(|err| git_error("decode commit", err))?;
+	Ok(GitCommit {
+		sha:     commit.id.to_string(),
+		parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+		author:  path_string(author.name),
+		email:   path_string(author.email),
+		time:    time as f64 * 1000.0,
+		summary: path_string(message.lines().next().unwrap_or_default().trim().as_bstr()),
+		message: path_string(message.trim_end().as_bstr()),
+	})
+}
+
+struct LogConfig {
+	root:         String,
+	limit:        usize,
+	reference:    String,
+	first_parent: bool,
+}
+
+fn git_log_sync(config: &LogConfig, ct: &task::CancelToken) -> Result<Vec<GitCommit>> {
+	let repo = open(&config.root)?;
+	let start = resolve_commit(&repo, &config.reference)?;
+	let mut walk = start
+		.ancestors()
+		.sorting(gix::revision::walk::Sorting::ByCommitTime(
+			gix::traverse::commit::simple::CommitTimeOrder::NewestFirst,
+		));
+	if config.first_parent {
+		walk = walk.first_parent_only();
+	}
+	let walk = walk.all().map_err(|err| git_error("walk history", err))?;
+
+	let mut commits = Vec::new();
+	for info in walk.take(config.limit) {
+		ct.heartbeat()?;
+		let commit = info
+			.map_err(|err| git_error("walk history", err))?
+			.object()
+			.map_err(|err| git_error("read commit", err))?;
+		commits.push(commit_info(&commit)?);
+	}
+	Ok(commits)
+}
+
+/// List commits reachable from a revision, newest first.
+///
+/// # Errors
+/// Returns an error if no repository is found or the revision does not
+/// resolve to a commit.
+#[napi(js_name = "gitLog")]
+pub fn git_log(options: GitLogOptions<'_>) -> task::Async<Vec<GitCommit>> {
+	let GitLogOptions { root, limit, reference, first_parent, signal, timeout_ms } = options;
+	let config = LogConfig {
+		root,
+		limit: limit.unwrap_or(DEFAULT_LOG_LIMIT) as usize,
+		reference: reference.unwrap_or_else(|| "HEAD".to_string()),
+		first_parent: first_parent.unwrap_or(false),
+	};
+	let ct = task::CancelToken::new(timeout_ms, signal);
+	task::blocking("git_log", ct, move |ct| git_log_sync(&config, &ct))
+}
+
+/// Path of `file` relative to the worktree root of `repo`, with `/`
+/// separators.
+fn worktree_path(repo: &gix::Repository, file: &Path) -> Result<BString> {
+	let workdir = repo
+		.workdir()
+		.ok_or_else(|| Error::from_reason("Failed to blame: repository has no worktree"))?;
+	let resolve = |path: &Path| -> io::Result<PathBuf> {
+		// The file itself may be deleted in the worktree but present at the
+		// blamed revision, so only its directory has to exist.
+		let absolute = std::path::absolute(path)?;
+		match (absolute.parent(), absolute.file_name()) {
+			(Some(dir), Some(name)) => Ok(fs::canonicalize(dir)?.join(name)),
+			_ => fs::canonicalize(&absolute),
+		}
+	};
+	let display = file.display();
+	let file = resolve(file).map_err(|err| git_error(&format!("resolve {display}"), err))?;
+	let workdir = fs::canonicalize(workdir).map_err(|err| git_error("resolve worktree", err))?;
+	let relative = file.strip_prefix(&workdir).map_err(|_| {
+		Error::from_reason(format!("Failed to blame: {display} is outside the repository"))
+	})?;
+	Ok(gix::path::to_unix_separators_on_windows(gix::path::into_bstr(relative)).into_owned())
+}
+
+struct BlameConfig {
+	path:      String,
+	range:     Option<(u32, u32)>,
+	reference: String,
+}
+
+fn git_blame_sync(config: &BlameConfig, ct: &task::CancelToken) -> Result<GitBlame> {
+	let file = Path::new(&config.path);
+	let dir = file
+		.parent()
+		.filter(|dir| !dir.as_os_str().is_empty())
+		.unwrap_or_else(|| Path::new("."));
+	let repo = open(dir)?;
+	let path = worktree_path(&repo, file)?;
+	let head = resolve_commit(&repo, &config.reference)?;
+	let options = gix::blame::Options {
+		range: config
+			.range
+			.map_or_else(gix::blame::BlameRanges::new, |(start, end)| {
+				gix::blame::BlameRanges::from_range(start..=end)
+			}),
+		rewrites: Some(gix::diff::Rewrites::default()),
+		..Default::default()
+	};
+	let outcome = repo
+		.blame_file(path.as_ref(), head.id, options)
+		.map_err(|err| git_error(&format!("blame {path}"), err))?;
+	ct.heartbeat()?;
+
+	let content: Vec<&[u8]> = outcome.blob.lines().collect();
+	let mut commits: HashMap<gix::ObjectId, GitCommit> = HashMap::new();
+	let mut lines = Vec::new();
+	for entry in &outcome.entries {
+		ct.heartbeat()?;
+		let commit = match commits.entry(entry.commit_id) {
+			Entry::Occupied(commit) => commit.into_mut(),
+			Entry::Vacant(slot) => {
+				let commit = repo
+					.find_commit(entry.commit_id)
+					.map_err(|err| git_error("read commit", err))?;
+				slot.insert(commit_info(&commit)?)
+			},
+		};
+		for offset in 0..entry.len.get() {
+			let line = entry.start_in_blamed_file + offset;
+			lines.push(GitBlameLine {
+				line:          line + 1,
+				content:       content
+					.get(line as usize)
+					.map_or_else(String::new, |text| path_string(text.as_bstr())),
+				sha:           commit.sha.clone(),
+				author:        commit.author.clone(),
+				email:         commit.email.clone(),
+				time:          commit.time,
+				summary:       commit.summary.clone(),
+				original_line: entry.start_in_source_file + offset + 1,
+				original_path: entry
+					.source_file_name
+					.as_ref()
+					.map(|name| path_string(name.as_ref())),
+			});
+		}
+	}
+	lines.sort_by_key(|line| line.line);
+
+	Ok(GitBlame { path: path_string(path.as_ref()), head: head.id.to_string(), lines })
+}
+
+/// Attribute each line of a file to the commit that last changed it.
+///
+/// Lines are blamed as of `ref`; uncommitted changes are not considered.
+///
+/// # Errors
+/// Returns an error if the file is not in a repository, does not exist at
+/// the revision, or the range lies outside the file.
+#[napi(js_name = "gitBlame")]
+pub fn git_blame(path: String, options: Option<GitBlameOptions<'_>>) -> task::Async<GitBlame> {
+	let (range, reference, ct) = match options {
+		Some(GitBlameOptions { range, reference, signal, timeout_ms }) => (
+			range.map(|range| (range.start, range.end)),
+			reference,
+			task::CancelToken::new(timeout_ms, signal),
+		),
+		None => (None, None, task::CancelToken::default()),
+	};
+	let config =
+		BlameConfig { path, range, reference: reference.unwrap_or_else(|| "HEAD".to_string()) };
+	task::blocking("git_blame", ct, move |ct| git_blame_sync(&config, &ct))
+}
@@ -25,39 +25,52 @@ use napi_derive::napi;
 
 // Re-export entry types so existing `glob::FileType` / `glob::GlobMatch` paths still work.
 pub use crate::fs_cache::{FileType, GlobMatch};
-use crate::{fs_cache, task};
+use crate::{
+	fs_cache,
+	grep::{TypeFilter, matches_type_filter, resolve_type_filter},
+	task, workspace,
+};
 
 /// Input options for `glob`, including traversal, filtering, and cancellation.
 #[napi(object)]
 pub struct GlobOptions<'env> {
 	/// Glob pattern to match (e.g., "*.ts").
-	pub pattern:              String,
+	pub pattern:                 String,
 	/// Directory to search.
-	pub path:                 String,
+	pub path:                    String,
 	/// Filter by file type: "file", "dir", or "symlink".
 	#[napi(js_name = "fileType")]
-	pub file_type:            Option<FileType>,
+	pub file_type:               Option<FileType>,
+	/// Filter by language type, as in `grep` (e.g. "ts", "rust").
+	#[napi(js_name = "type")]
+	pub type_filter:             Option<String>,
+	/// Maximum directory depth below the search root (1 = direct children).
+	#[napi(js_name = "maxDepth")]
+	pub max_depth:               Option<u32>,
 	/// Include hidden files (default: false).
-	pub hidden:               Option<bool>,
+	pub hidden:                  Option<bool>,
 	/// Maximum number of results to return.
 	#[napi(js_name = "maxResults")]
-	pub max_results:          Option<u32>,
+	pub max_results:             Option<u32>,
 	/// Respect .gitignore files (default: true).
-	pub gitignore:            Option<bool>,
+	pub gitignore:               Option<bool>,
 	/// Enable shared filesystem scan cache (default: false).
-	pub cache:                Option<bool>,
+	pub cache:                   Option<bool>,
 	/// Sort results by mtime (most recent first) before applying limit.
 	#[napi(js_name = "sortByMtime")]
-	pub sort_by_mtime:        Option<bool>,
+	pub sort_by_mtime:           Option<bool>,
 	/// Include `node_modules` entries when the pattern does not explicitly
 	/// mention them.
 	#[napi(js_name = "includeNodeModules")]
-	pub include_node_modules: Option<bool>,
+	pub include_node_modules:    Option<bool>,
 	/// Abort signal for cancelling the operation.
-	pub signal:               Option<Unknown<'env>>,
+	pub signal:                  Option<Unknown<'env>>,
 	/// Timeout in milliseconds for the operation.
 	#[napi(js_name = "timeoutMs")]
-	pub timeout_ms:           Option<u32>,
+	pub timeout_ms:              Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
 }
 
 /// Result payload returned by a glob operation.
@@ -99,6 +112,8 @@ struct GlobConfig {
 	pattern:               String,
 	include_hidden:        bool,
 	file_type_filter:      Option<FileType>,
+	type_filter:           Option<TypeFilter>,
+	max_depth:             Option<usize>,
 	max_results:           usize,
 	use_gitignore:         bool,
 	mentions_node_modules: bool,
@@ -134,6 +149,17 @@ fn filter_entries(
 		{
 			continue;
 		}
+		if config
+			.max_depth
+			.is_some_and(|max| entry.path.split('/').count() > max)
+		{
+			continue;
+		}
+		if let Some(filter) = &config.type_filter
+			&& !matches_type_filter(Path::new(&entry.path), filter)
+		{
+			continue;
+		}
 		if let Some(callback) = on_match {
 			callback.call(Ok(entry.clone()), ThreadsafeFunctionCallMode::NonBlocking);
 		}
@@ -225,6 +251,8 @@ pub fn glob(
 		pattern,
 		path,
 		file_type,
+		type_filter,
+		max_depth,
 		hidden,
 		max_results,
 		gitignore,
@@ -233,6 +261,7 @@ pub fn glob(
 		include_node_modules,
 		timeout_ms,
 		signal,
+		allow_outside_workspace,
 	} = options;
 
 	let pattern = pattern.trim();
@@ -242,11 +271,14 @@ pub fn glob(
 	let ct = task::CancelToken::new(timeout_ms, signal);
 
 	task::blocking("glob", ct, move |ct| {
+		workspace::check(&path, allow_outside_workspace)?;
 		run_glob(
 			GlobConfig {
 				root: fs_cache::resolve_search_path(&path)?,
 				include_hidden: hidden.unwrap_or(false),
 				file_type_filter: file_type,
+				type_filter: resolve_type_filter(type_filter.as_deref()),
+				max_depth: max_depth.map(|depth| depth as usize),
 				max_results: max_results.map_or(usize::MAX, |value| value as usize),
 				use_gitignore: gitignore.unwrap_or(true),
 				mentions_node_modules: include_node_modules
@@ -0,0 +1,733 @@
+//! Shell-style glob expansion.
+//!
+//! # Overview
+//! `expandGlob` expands a pattern to the paths it names the way bash does
+//! with `globstar` and `extglob` on, so tools see the same semantics on
+//! every platform instead of those of whichever shell or library ran:
+//! - `{a,b}`, `{1..10}`, `{01..10..3}` and `{a..e}` expand first, nested braces
+//!   included, and each alternative is expanded in turn
+//! - `*`, `?` and `[...]` match within a path segment, with globset as in
+//!   `glob`
+//! - `**` as a whole segment matches any number of directories
+//! - `?(a|b)`, `*(a|b)`, `+(a|b)`, `@(a|b)` and `!(a|b)` match zero or one, any
+//!   number, one or more, exactly one, or none of the patterns
+//!
+//! Wildcards only match names starting with `.` when the segment starts
+//! with `.` or `dot` is set, and `**` only descends into symlinked
+//! directories with `follow`. The paths of each brace alternative are sorted;
+//! a pattern ending in `/` only matches directories, which keep the slash.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.expandGlob("src/**/*.@(ts|tsx)", { cwd: "/repo" })
+//! // JS: await native.expandGlob("logs/{2023..2024}-*.log")
+//! ```
+
+use std::{
+	collections::HashSet,
+	env, fs,
+	path::{Path, PathBuf},
+};
+
+use globset::{GlobBuilder, GlobMatcher};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{fs_cache, task, workspace};
+
+/// Most patterns brace expansion may produce.
+const MAX_BRACE_EXPANSIONS: usize = 10_000;
+
+/// Options for `expandGlob`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ExpandGlobOptions {
+	/// Directory relative patterns are expanded in (default: the working
+	/// directory).
+	pub cwd:                     Option<String>,
+	/// Let wildcards match names starting with `.` (default: false).
+	pub dot:                     Option<bool>,
+	/// Let `**` descend into symlinked directories (default: false).
+	pub follow:                  Option<bool>,
+	/// Stop after this many paths.
+	#[napi(js_name = "maxResults")]
+	pub max_results:             Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Brace expansion
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Index of the `}` closing the `{` at `open`, and the top-level commas.
+fn brace_group(chars: &[char], open: usize) -> Option<(usize, Vec<usize>)> {
+	let mut depth = 0;
+	let mut commas = Vec::new();
+	let mut index = open;
+	while index < chars.len() {
+		match chars[index] {
+			'\\' => index += 1,
+			'{' => depth += 1,
+			'}' => {
+				depth -= 1;
+				if depth == 0 {
+					return Some((index, commas));
+				}
+			},
+			',' if depth == 1 => commas.push(index),
+			_ => {},
+		}
+		index += 1;
+	}
+	None
+}
+
+fn too_many_expansions() -> Error {
+	Error::from_reason(format!(
+		"Invalid glob pattern: braces expand to more than {MAX_BRACE_EXPANSIONS} patterns"
+	))
+}
+
+/// Items of a `{start..end[..step]}` sequence, or `None` if `body` is not
+/// one.
+fn sequence(body: &str) -> Result<Option<Vec<String>>> {
+	let parts: Vec<&str> = body.split("..").collect();
+	if !(2..=3).contains(&parts.len()) {
+		return Ok(None);
+	}
+	let step = match parts.get(2) {
+		Some(step) => match step.parse::<i64>() {
+			Ok(step) => step.unsigned_abs().max(1),
+			Err(_) => return Ok(None),
+		},
+		None => 1,
+	};
+	let (start, end) = (parts[0], parts[1]);
+	let bounds = if let (Ok(from), Ok(to)) = (start.parse::<i64>(), end.parse::<i64>()) {
+		Some((from, to))
+	} else {
+		let mut start_chars = start.chars();
+		let mut end_chars = end.chars();
+		match (start_chars.next(), start_chars.next(), end_chars.next(), end_chars.next()) {
+			(Some(from), None, Some(to), None)
+				if from.is_ascii_alphabetic() && to.is_ascii_alphabetic() =>
+			{
+				Some((i64::from(u32::from(from)), i64::from(u32::from(to))))
+			},
+			_ => None,
+		}
+	};
+	let Some((from, to)) = bounds else {
+		return Ok(None);
+	};
+	if from.abs_diff(to) / step >= MAX_BRACE_EXPANSIONS as u64 {
+		return Err(too_many_expansions());
+	}
+	let numeric = start.parse::<i64>().is_ok();
+	// Zero-padded bounds pad every number to the widest bound.
+	let padded = |bound: &str| {
+		bound.trim_start_matches('-').len() > 1 && bound.trim_start_matches('-').starts_with('0')
+	};
+	let width = if numeric && (padded(start) || padded(end)) {
+		start.len().max(end.len())
+	} else {
+		0
+	};
+	let mut items = Vec::new();
+	let mut value = from;
+	loop {
+		items.push(if numeric {
+			format!("{value:0width$}")
+		} else {
+			u32::try_from(value)
+				.ok()
+				.and_then(char::from_u32)
+				.map_or_else(String::new, String::from)
+		});
+		if value == to || from.abs_diff(value) + step > from.abs_diff(to) {
+			break;
+		}
+		value = if from <= to {
+			value + step as i64
+		} else {
+			value - step as i64
+		};
+	}
+	Ok(Some(items))
+}
+
+/// Append the patterns `pattern` expands to, leaving braces that hold
+/// neither a comma nor a sequence as they are.
+fn expand_braces(pattern: &str, out: &mut Vec<String>) -> Result<()> {
+	let chars: Vec<char> = pattern.chars().collect();
+	let mut index = 0;
+	while index < chars.len() {
+		match chars[index] {
+			'\\' => index += 1,
+			'{' => {
+				if let Some((close, commas)) = brace_group(&chars, index) {
+					let body: String = chars[index + 1..close].iter().collect();
+					let alternatives = if commas.is_empty() {
+						sequence(&body)?
+					} else {
+						let mut bounds = vec![index];
+						bounds.extend(&commas);
+						bounds.push(close);
+						Some(
+							bounds
+								.windows(2)
+								.map(|pair| chars[pair[0] + 1..pair[1]].iter().collect())
+								.collect(),
+						)
+					};
+					if let Some(alternatives) = alternatives {
+						let prefix: String = chars[..index].iter().collect();
+						let suffix: String = chars[close + 1..].iter().collect();
+						for alternative in alternatives {
+							expand_braces(&format!("{prefix}{alternative}{suffix}"), out)?;
+						}
+						return Ok(());
+					}
+				}
+			},
+			_ => {},
+		}
+		index += 1;
+	}
+	if out.len() >= MAX_BRACE_EXPANSIONS {
+		return Err(too_many_expansions());
+	}
+	out.push(pattern.to_string());
+	Ok(())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Extended globs
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Part of a segment with extended globs.
+enum Node {
+	Char(char),
+	/// `?`
+	Any,
+	/// `*`
+	Star,
+	/// `[...]`, as inclusive ranges.
+	Class {
+		negated: bool,
+		ranges:  Vec<(char, char)>,
+	},
+	/// `?(...)`, `*(...)`, `+(...)`, `@(...)` or `!(...)`, with the
+	/// alternatives.
+	Extended(char, Vec<Vec<Self>>),
+}
+
+struct ExtParser<'a> {
+	chars: &'a [char],
+	pos:   usize,
+}
+
+impl ExtParser<'_> {
+	/// Nodes up to the end, or in a group up to its next `|` or `)`.
+	fn sequence(&mut self, in_group: bool) -> Result<Vec<Node>> {
+		let mut nodes = Vec::new();
+		loop {
+			let Some(&char) = self.chars.get(self.pos) else {
+				if in_group {
+					return Err(Error::from_reason("Invalid glob pattern: unclosed `(`"));
+				}
+				return Ok(nodes);
+			};
+			if in_group && matches!(char, '|' | ')') {
+				return Ok(nodes);
+			}
+			self.pos += 1;
+			let node = match char {
+				'\\' => {
+					let escaped = self.chars.get(self.pos).copied().unwrap_or('\\');
+					self.pos += 1;
+					Node::Char(escaped)
+				},
+				'?' | '*' | '+' | '@' | '!' if self.chars.get(self.pos) == Some(&'(') => {
+					self.pos += 1;
+					let mut alternatives = Vec::new();
+					loop {
+						alternatives.push(self.sequence(true)?);
+						self.pos += 1;
+						if self.chars[self.pos - 1] == ')' {
+							break;
+						}
+					}
+					Node::Extended(char, alternatives)
+				},
+				'?' => Node::Any,
+				'*' => Node::Star,
+				'[' => self.class().unwrap_or(Node::Char('[')),
+				char => Node::Char(char),
+			};
+			nodes.push(node);
+		}
+	}
+
+	/// A `[...]` class after its `[`, or `None`, consuming nothing, when it
+	/// is not closed.
+	fn class(&mut self) -> Option<Node> {
+		let mut pos = self.pos;
+		let negated = matches!(self.chars.get(pos), Some('!' | '^'));
+		if negated {
+			pos += 1;
+		}
+		let mut ranges = Vec::new();
+		let mut first = true;
+		loop {
+			let &char = self.chars.get(pos)?;
+			if char == ']' && !first {
+				self.pos = pos + 1;
+				return Some(Node::Class { negated, ranges });
+			}
+			first = false;
+			if self.chars.get(pos + 1) == Some(&'-')
+				&& let Some(&end) = self.chars.get(pos + 2)
+				&& end != ']'
+			{
+				ranges.push((char, end));
+				pos += 3;
+			} else {
+				ranges.push((char, char));
+				pos += 1;
+			}
+		}
+	}
+}
+
+fn any_matches(alternatives: &[Vec<Node>], text: &[char]) -> bool {
+	alternatives
+		.iter()
+		.any(|alternative| ext_matches(alternative, text))
+}
+
+/// Whether `text` is zero or more matches of `alternatives` followed by a
+/// match of `rest`.
+fn repeat_matches(alternatives: &[Vec<Node>], rest: &[Node], text: &[char]) -> bool {
+	ext_matches(rest, text)
+		|| (1..=text.len()).any(|split| {
+			any_matches(alternatives, &text[..split])
+				&& repeat_matches(alternatives, rest, &text[split..])
+		})
+}
+
+fn ext_matches(nodes: &[Node], text: &[char]) -> bool {
+	let Some((node, rest)) = nodes.split_first() else {
+		return text.is_empty();
+	};
+	let one = |matched: bool| matched && ext_matches(rest, &text[1..]);
+	match node {
+		Node::Char(char) => text.first() == Some(char) && ext_matches(rest, &text[1..]),
+		Node::Any => !text.is_empty() && ext_matches(rest, &text[1..]),
+		Node::Star => (0..=text.len()).any(|split| ext_matches(rest, &text[split..])),
+		Node::Class { negated, ranges } => text.first().is_some_and(|char| {
+			one(
+				ranges
+					.iter()
+					.any(|&(start, end)| (start..=end).contains(char))
+					!= *negated,
+			)
+		}),
+		Node::Extended(op, alternatives) => {
+			let splits = 0..=text.len();
+			match op {
+				'@' => splits.into_iter().any(|split| {
+					any_matches(alternatives, &text[..split]) && ext_matches(rest, &text[split..])
+				}),
+				'?' => {
+					ext_matches(rest, text)
+						|| splits.into_iter().any(|split| {
+							any_matches(alternatives, &text[..split]) && ext_matches(rest, &text[split..])
+						})
+				},
+				'!' => splits.into_iter().any(|split| {
+					!any_matches(alternatives, &text[..split]) && ext_matches(rest, &text[split..])
+				}),
+				'*' => repeat_matches(alternatives, rest, text),
+				_ => splits.into_iter().any(|split| {
+					any_matches(alternatives, &text[..split])
+						&& repeat_matches(alternatives, rest, &text[split..])
+				}),
+			}
+		},
+	}
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Expansion
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A `/`-separated part of a pattern.
+enum Segment {
+	/// A name without wildcards, looked up without listing its directory.
+	Literal(String),
+	/// `**`
+	Globstar,
+	/// Wildcards; `dot` when the segment starts with `.`.
+	Glob { matcher: GlobMatcher, dot: bool },
+	/// Wildcards with extended globs.
+	Extended { nodes: Vec<Node>, dot: bool },
+}
+
+/// Whether `segment` has an unescaped character of `special`, or an extended
+/// glob group when `special` is empty.
+fn has_unescaped(segment: &str, special: &[char]) -> bool {
+	let mut chars = segment.chars().peekable();
+	while let Some(char) = chars.next() {
+		if char == '\\' {
+			chars.next();
+		} else if special.is_empty() {
+			if matches!(char, '?' | '*' | '+' | '@' | '!') && chars.peek() == Some(&'(') {
+				return true;
+			}
+		} else if special.contains(&char) {
+			return true;
+		}
+	}
+	false
+}
+
+/// `segment` with backslash escapes removed.
+fn unescape(segment: &str) -> String {
+	let mut out = String::with_capacity(segment.len());
+	let mut chars = segment.chars();
+	while let Some(char) = chars.next() {
+		out.push(if char == '\\' {
+			chars.next().unwrap_or('\\')
+		} else {
+			char
+		});
+	}
+	out
+}
+
+impl Segment {
+	fn parse(segment: &str) -> Result<Self> {
+		if segment == "**" {
+			return Ok(Self::Globstar);
+		}
+		let dot = segment.starts_with('.');
+		if has_unescaped(segment, &[]) {
+			let chars: Vec<char> = segment.chars().collect();
+			let nodes = ExtParser { chars: &chars, pos: 0 }.sequence(false)?;
+			return Ok(Self::Extended { nodes, dot });
+		}
+		if !has_unescaped(segment, &['*', '?', '[']) {
+			return Ok(Self::Literal(unescape(segment)));
+		}
+		// Braces left after expansion are literal.
+		let mut escaped = String::with_capacity(segment.len());
+		let mut chars = segment.chars();
+		while let Some(char) = chars.next() {
+			match char {
+				'\\' => {
+					escaped.push(char);
+					escaped.extend(chars.next());
+				},
+				'{' | '}' => {
+					escaped.push('\\');
+					escaped.push(char);
+				},
+				_ => escaped.push(char),
+			}
+		}
+		let matcher = GlobBuilder::new(&escaped)
+			.literal_separator(true)
+			.backslash_escape(true)
+			.build()
+			.map_err(|err| Error::from_reason(format!("Invalid glob pattern: {err}")))?
+			.compile_matcher();
+		Ok(Self::Glob { matcher, dot })
+	}
+
+	/// Whether a directory entry named `name` matches.
+	fn matches(&self, name: &str, dot: bool) -> bool {
+		match self {
+			Self::Literal(literal) => literal == name,
+			Self::Globstar => dot || !name.starts_with('.'),
+			Self::Glob { matcher, dot: explicit } => {
+				(dot || *explicit || !name.starts_with('.')) && matcher.is_match(name)
+			},
+			Self::Extended { nodes, dot: explicit } => {
+				(dot || *explicit || !name.starts_with('.'))
+					&& ext_matches(nodes, &name.chars().collect::<Vec<_>>())
+			},
+		}
+	}
+}
+
+struct Expander {
+	dot:       bool,
+	follow:    bool,
+	dirs_only: bool,
+	found:     Vec<String>,
+}
+
+/// Names in `dir`, sorted; unreadable directories have none, as in a shell.
+fn list(dir: &Path) -> Vec<String> {
+	let mut names: Vec<String> = fs::read_dir(dir).map_or_else(
+		|_| Vec::new(),
+		|entries| {
+			entries
+				.flatten()
+				.map(|entry| entry.file_name().to_string_lossy().into_owned())
+				.collect()
+		},
+	);
+	names.sort();
+	names
+}
+
+impl Expander {
+	/// Record `shown` for `path` if it qualifies.
+	fn emit(&mut self, path: &Path, shown: String) {
+		if !self.dirs_only {
+			self.found.push(shown);
+		} else if path.is_dir() {
+			self.found.push(format!("{shown}/"));
+		}
+	}
+
+	/// Expand `segments` in `dir`, shown as `shown` (empty or ending in `/`).
+	fn expand(
+		&mut self,
+		dir: &Path,
+		shown: &str,
+		segments: &[Segment],
+		visited: &mut HashSet<PathBuf>,
+	) {
+		let Some((segment, rest)) = segments.split_first() else {
+			return;
+		};
+		match segment {
+			Segment::Literal(name) => {
+				let path = dir.join(name);
+				if fs::symlink_metadata(&path).is_err() {
+					return;
+				}
+				if rest.is_empty() {
+					self.emit(&path, format!("{shown}{name}"));
+				} else if path.is_dir() {
+					self.expand(&path, &format!("{shown}{name}/"), rest, visited);
+				}
+			},
+			Segment::Globstar => {
+				// Zero directories, then each subdirectory with `**` again.
+				if !rest.is_empty() {
+					self.expand(dir, shown, rest, visited);
+				}
+				for name in list(dir) {
+					if !segment.matches(&name, self.dot) {
+						continue;
+					}
+					let path = dir.join(&name);
+					if rest.is_empty() {
+						self.emit(&path, format!("{shown}{name}"));
+					}
+					let is_link = fs::symlink_metadata(&path).is_ok_and(|meta| meta.is_symlink());
+					// Following links, every directory is entered once, so cycles
+					// end.
+					let descend = path.is_dir()
+						&& if self.follow {
+							fs::canonicalize(&path).is_ok_and(|real| visited.insert(real))
+						} else {
+							!is_link
+						};
+					if descend {
+						self.expand(&path, &format!("{shown}{name}/"), segments, visited);
+					}
+				}
+			},
+			Segment::Glob { .. } | Segment::Extended { .. } => {
+				for name in list(dir) {
+					if !segment.matches(&name, self.dot) {
+						continue;
+					}
+					let path = dir.join(&name);
+					if rest.is_empty() {
+						self.emit(&path, format!("{shown}{name}"));
+					} else if path.is_dir() {
+						self.expand(&path, &format!("{shown}{name}/"), rest, visited);
+					}
+				}
+			},
+		}
+	}
+}
+
+/// Split off the root of an absolute pattern: `/`, or a drive on Windows.
+fn split_root(pattern: &str) -> (Option<&str>, &str) {
+	if let Some(rest) = pattern.strip_prefix('/') {
+		return (Some("/"), rest);
+	}
+	let bytes = pattern.as_bytes();
+	if cfg!(windows) && bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && &bytes[1..3] == b":/" {
+		return (Some(&pattern[..3]), &pattern[3..]);
+	}
+	(None, pattern)
+}
+
+fn expand_glob_sync(pattern: &str, options: ExpandGlobOptions) -> Result<Vec<String>> {
+	let cwd = match &options.cwd {
+		Some(cwd) => fs_cache::resolve_search_path(cwd)?,
+		None => env::current_dir()
+			.map_err(|err| Error::from_reason(format!("Failed to resolve cwd: {err}")))?,
+	};
+	let pattern = if cfg!(windows) {
+		pattern.replace('\\', "/")
+	} else {
+		pattern.to_string()
+	};
+	let max_results = options.max_results.map_or(usize::MAX, |max| max as usize);
+
+	let mut patterns = Vec::new();
+	expand_braces(&pattern, &mut patterns)?;
+	let mut seen = HashSet::new();
+	let mut results = Vec::new();
+	for pattern in patterns {
+		let (root, relative) = split_root(&pattern);
+		let segments = relative
+			.split('/')
+			.filter(|segment| !segment.is_empty())
+			.map(Segment::parse)
+			.collect::<Result<Vec<_>>>()?;
+		let base = root.map_or_else(|| cwd.clone(), PathBuf::from);
+		// Where the pattern reads directories from: its literal prefix.
+		let listed = segments
+			.iter()
+			.map_while(|segment| match segment {
+				Segment::Literal(name) => Some(name),
+				_ => None,
+			})
+			.fold(base.clone(), |dir, name| dir.join(name));
+		workspace::check(&listed, options.allow_outside_workspace)?;
+
+		let mut expander = Expander {
+			dot:       options.dot.unwrap_or(false),
+			follow:    options.follow.unwrap_or(false),
+			dirs_only: relative.ends_with('/'),
+			found:     Vec::new(),
+		};
+		if segments.is_empty() {
+			// Just a root, like `/`.
+			if root.is_some() {
+				expander.found.push(pattern.clone());
+			}
+		} else {
+			let mut visited: HashSet<_> = fs::canonicalize(&base).into_iter().collect();
+			expander.expand(&base, root.unwrap_or_default(), &segments, &mut visited);
+		}
+		expander.found.sort();
+		for path in expander.found {
+			if results.len() >= max_results {
+				return Ok(results);
+			}
+			if seen.insert(path.clone()) {
+				results.push(path);
+			}
+		}
+	}
+	Ok(results)
+}
+
+/// Expand a shell glob pattern, with braces, `**` and extended globs, to the
+/// paths it matches.
+///
+/// # Errors
+/// Returns an error for malformed patterns, too many brace expansions, a
+/// missing `cwd`, or a pattern reaching outside the workspace.
+#[napi(js_name = "expandGlob")]
+pub fn expand_glob(
+	pattern: String,
+	options: Option<ExpandGlobOptions>,
+) -> task::Async<Vec<String>> {
+	task::blocking("expand_glob", (), move |_| {
+		expand_glob_sync(&pattern, options.unwrap_or_default())
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn braces(pattern: &str) -> Vec<String> {
+		let mut out = Vec::new();
+		expand_braces(pattern, &mut out).unwrap();
+		out
+	}
+
+	fn matches(pattern: &str, name: &str) -> bool {
+		Segment::parse(pattern).unwrap().matches(name, false)
+	}
+
+	#[test]
+	fn test_brace_lists() {
+		assert_eq!(braces("a{b,c}d"), ["abd", "acd"]);
+		assert_eq!(braces("{a,b{c,d}}e"), ["ae", "bce", "bde"]);
+		assert_eq!(braces("{x,y}{1,2}"), ["x1", "x2", "y1", "y2"]);
+		assert_eq!(braces("src/{,lib/}mod.rs"), ["src/mod.rs", "src/lib/mod.rs"]);
+	}
+
+	#[test]
+	fn test_brace_sequences() {
+		assert_eq!(braces("{1..4}"), ["1", "2", "3", "4"]);
+		assert_eq!(braces("{3..1}"), ["3", "2", "1"]);
+		assert_eq!(braces("{01..10..3}"), ["01", "04", "07", "10"]);
+		assert_eq!(braces("{a..e..2}"), ["a", "c", "e"]);
+		assert_eq!(braces("{Y..b}"), ["Y", "Z", "[", "\\", "]", "^", "_", "`", "a", "b"]);
+	}
+
+	#[test]
+	fn test_braces_left_literal() {
+		assert_eq!(braces("{a}"), ["{a}"]);
+		assert_eq!(braces("a{b"), ["a{b"]);
+		assert_eq!(braces("\\{a,b}"), ["\\{a,b}"]);
+		assert_eq!(braces("{1..x}"), ["{1..x}"]);
+		assert_eq!(braces("{-..+}"), ["{-..+}"]);
+	}
+
+	#[test]
+	fn test_too_many_braces() {
+		let mut out = Vec::new();
+		assert!(expand_braces("{1..100}{1..100}{1..100}", &mut out).is_err());
+	}
+
+	#[test]
+	fn test_extglob() {
+		assert!(matches("*.@(ts|tsx)", "a.ts"));
+		assert!(matches("*.@(ts|tsx)", "a.tsx"));
+		assert!(!matches("*.@(ts|tsx)", "a.js"));
+		assert!(matches("file?(s).txt", "file.txt"));
+		assert!(matches("file?(s).txt", "files.txt"));
+		assert!(!matches("file?(s).txt", "filess.txt"));
+		assert!(matches("a+(b)c", "abbbc"));
+		assert!(!matches("a+(b)c", "ac"));
+		assert!(matches("a*(b|c)d", "ad"));
+		assert!(matches("a*(b|c)d", "abcbd"));
+		assert!(matches("!(*.log)", "main.rs"));
+		assert!(!matches("!(*.log)", "debug.log"));
+		assert!(Segment::parse("@(a|b").is_err());
+	}
+
+	#[test]
+	fn test_dot_files() {
+		assert!(!matches("*", ".hidden"));
+		assert!(matches(".*", ".hidden"));
+		assert!(!matches("!(x)", ".hidden"));
+		assert!(Segment::parse("*").unwrap().matches(".hidden", true));
+	}
+
+	#[test]
+	fn test_literal_segments() {
+		assert!(matches("a\\*b", "a*b"));
+		assert!(!matches("a\\*b", "axb"));
+		assert!(matches("x{a,b}*", "x{a,b}c"));
+	}
+}
@@ -28,7 +28,7 @@ use napi_derive::napi;
 use rayon::prelude::*;
 use smallvec::SmallVec;
 
-use crate::{fs_cache, task};
+use crate::{fs_cache, task, workspace};
 
 const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
 
@@ -72,46 +72,49 @@ pub struct SearchOptions {
 #[napi(object)]
 pub struct GrepOptions<'env> {
 	/// Regex pattern to search for.
-	pub pattern:        String,
+	pub pattern:                 String,
 	/// Directory or file to search.
-	pub path:           String,
+	pub path:                    String,
 	/// Glob filter for filenames (e.g., "*.ts").
-	pub glob:           Option<String>,
+	pub glob:                    Option<String>,
 	/// Filter by file type (e.g., "js", "py", "rust").
 	#[napi(js_name = "type")]
-	pub type_filter:    Option<String>,
+	pub type_filter:             Option<String>,
 	/// Case-insensitive search.
 	#[napi(js_name = "ignoreCase")]
-	pub ignore_case:    Option<bool>,
+	pub ignore_case:             Option<bool>,
 	/// Enable multiline matching.
-	pub multiline:      Option<bool>,
+	pub multiline:               Option<bool>,
 	/// Include hidden files (default: true).
-	pub hidden:         Option<bool>,
+	pub hidden:                  Option<bool>,
 	/// Enable shared filesystem scan cache (default: false).
-	pub cache:          Option<bool>,
+	pub cache:                   Option<bool>,
 	/// Maximum number of matches to return.
 	#[napi(js_name = "maxCount")]
-	pub max_count:      Option<u32>,
+	pub max_count:               Option<u32>,
 	/// Skip first N matches.
-	pub offset:         Option<u32>,
+	pub offset:                  Option<u32>,
 	/// Lines of context before matches.
 	#[napi(js_name = "contextBefore")]
-	pub context_before: Option<u32>,
+	pub context_before:          Option<u32>,
 	/// Lines of context after matches.
 	#[napi(js_name = "contextAfter")]
-	pub context_after:  Option<u32>,
+	pub context_after:           Option<u32>,
 	/// Lines of context before/after matches (legacy).
-	pub context:        Option<u32>,
+	pub context:                 Option<u32>,
 	/// Truncate lines longer than this (characters).
 	#[napi(js_name = "maxColumns")]
-	pub max_columns:    Option<u32>,
+	pub max_columns:             Option<u32>,
 	/// Output mode (content, filesWithMatches, or count).
-	pub mode:           Option<String>,
+	pub mode:                    Option<String>,
 	/// Abort signal for cancelling the operation.
-	pub signal:         Option<Unknown<'env>>,
+	pub signal:                  Option<Unknown<'env>>,
 	/// Timeout in milliseconds for the operation.
 	#[napi(js_name = "timeoutMs")]
-	pub timeout_ms:     Option<u32>,
+	pub timeout_ms:              Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
 }
 
 /// A context line (before or after a match).
@@ -130,6 +133,8 @@ pub struct Match {
 	/// 1-indexed line number.
 	#[napi(js_name = "lineNumber")]
 	pub line_number:    u32,
+	/// 1-indexed byte column of the first match on the line.
+	pub column:         u32,
 	/// The matched line content.
 	pub line:           String,
 	/// Context lines before the match.
@@ -166,6 +171,8 @@ pub struct GrepMatch {
 	/// 1-indexed line number (0 for count-only entries).
 	#[napi(js_name = "lineNumber")]
 	pub line_number:    u32,
+	/// 1-indexed byte column of the first match (0 for count-only entries).
+	pub column:         u32,
 	/// The matched line content (empty for count-only entries).
 	pub line:           String,
 	/// Context lines before the match.
@@ -200,7 +207,8 @@ pub struct GrepResult {
 	pub limit_reached:      Option<bool>,
 }
 
-enum TypeFilter {
+/// File type filter resolved from a ripgrep-style type name (e.g. `rust`).
+pub enum TypeFilter {
 	Known { exts: &'static [&'static str], names: &'static [&'static str] },
 	Custom(String),
 }
@@ -221,7 +229,8 @@ impl TypeFilter {
 	}
 }
 
-struct MatchCollector {
+struct MatchCollector<'m> {
+	matcher:         &'m grep_regex::RegexMatcher,
 	matches:         Vec<CollectedMatch>,
 	match_count:     u64,
 	collected_count: u64,
@@ -236,6 +245,7 @@ struct MatchCollector {
 
 struct CollectedMatch {
 	line_number:    u64,
+	column:         u64,
 	line:           String,
 	context_before: SmallVec<[ContextLine; 8]>,
 	context_after:  SmallVec<[ContextLine; 8]>,
@@ -256,18 +266,20 @@ struct FileEntry {
 
 struct FileSearchResult {
 	relative_path: String,
-	matches:       Vec<CollectedMatch>,
+	matches:       Vec<GrepMatch>,
 	match_count:   u64,
 }
 
-impl MatchCollector {
+impl<'m> MatchCollector<'m> {
 	fn new(
+		matcher: &'m grep_regex::RegexMatcher,
 		max_count: Option<u64>,
 		offset: u64,
 		max_columns: Option<usize>,
 		collect_matches: bool,
 	) -> Self {
 		Self {
+			matcher,
 			matches: Vec::new(),
 			match_count: 0,
 			collected_count: 0,
@@ -301,7 +313,7 @@ fn bytes_to_trimmed_string(bytes: &[u8]) -> String {
 	}
 }
 
-impl Sink for MatchCollector {
+impl Sink for MatchCollector<'_> {
 	type Error = io::Error;
 
 	fn matched(
@@ -327,9 +339,16 @@ impl Sink for MatchCollector {
 			let raw_line = bytes_to_trimmed_string(mat.bytes());
 			let (line, truncated) = self.truncate_line(&raw_line);
 			let line_number = mat.line_number().unwrap_or(0);
+			let column = self
+				.matcher
+				.find(mat.bytes())
+				.ok()
+				.flatten()
+				.map_or(0, |found| found.start() as u64);
 
 			self.matches.push(CollectedMatch {
 				line_number,
+				column: column + 1,
 				line,
 				context_before: std::mem::take(&mut self.context_before),
 				context_after: SmallVec::new(),
@@ -429,7 +448,8 @@ fn compile_glob(glob: Option<&str>) -> Result<Option<GlobSet>> {
 		.map_err(|err| Error::from_reason(format!("Failed to build glob matcher: {err}")))
 }
 
-fn resolve_type_filter(type_name: Option<&str>) -> Option<TypeFilter> {
+/// Resolve a type name to a filter; unknown names match that extension.
+pub fn resolve_type_filter(type_name: Option<&str>) -> Option<TypeFilter> {
 	let normalized = type_name
 		.map(str::trim)
 		.filter(|value| !value.is_empty())
@@ -471,7 +491,8 @@ fn resolve_type_filter(type_name: Option<&str>) -> Option<TypeFilter> {
 	Some(TypeFilter::Known { exts, names })
 }
 
-fn matches_type_filter(path: &Path, filter: &TypeFilter) -> bool {
+/// Check whether `path` belongs to `filter` by file name or extension.
+pub fn matches_type_filter(path: &Path, filter: &TypeFilter) -> bool {
 	let base_name = path
 		.file_name()
 		.and_then(|name| name.to_str())
@@ -545,6 +566,7 @@ fn run_search_reader<R: Read>(
 		},
 	);
 	let mut collector = MatchCollector::new(
+		matcher,
 		params.max_count,
 		params.offset,
 		params.max_columns.map(|v| v as usize),
@@ -572,6 +594,7 @@ fn to_public_match(matched: CollectedMatch) -> Match {
 	};
 	Match {
 		line_number: clamp_u32(matched.line_number),
+		column: clamp_u32(matched.column),
 		line: matched.line,
 		context_before,
 		context_after,
@@ -593,6 +616,7 @@ fn to_grep_match(path: &str, matched: CollectedMatch) -> GrepMatch {
 	GrepMatch {
 		path: path.to_string(),
 		line_number: clamp_u32(matched.line_number),
+		column: clamp_u32(matched.column),
 		line: matched.line,
 		context_before,
 		context_after,
@@ -601,6 +625,35 @@ fn to_grep_match(path: &str, matched: CollectedMatch) -> GrepMatch {
 	}
 }
 
+/// Convert one file's search results into public entries for `mode`.
+fn to_grep_matches(path: &str, search: SearchResultInternal, mode: OutputMode) -> Vec<GrepMatch> {
+	match mode {
+		OutputMode::Content => search
+			.matches
+			.into_iter()
+			.map(|matched| to_grep_match(path, matched))
+			.collect(),
+		OutputMode::Count => vec![GrepMatch {
+			path:           path.to_string(),
+			line_number:    0,
+			column:         0,
+			line:           String::new(),
+			context_before: None,
+			context_after:  None,
+			truncated:      None,
+			match_count:    Some(clamp_u32(search.match_count)),
+		}],
+	}
+}
+
+fn emit_matches(on_match: Option<&ThreadsafeFunction<GrepMatch>>, matches: &[GrepMatch]) {
+	if let Some(callback) = on_match {
+		for grep_match in matches {
+			callback.call(Ok(grep_match.clone()), ThreadsafeFunctionCallMode::NonBlocking);
+		}
+	}
+}
+
 const fn empty_search_result(error: Option<String>) -> SearchResult {
 	SearchResult { matches: Vec::new(), match_count: 0, limit_reached: false, error }
 }
@@ -663,39 +716,50 @@ fn build_matcher(
 		.map_err(|err| Error::from_reason(format!("Regex error: {err}")))
 }
 
+/// Search all entries in parallel, streaming each file's matches to
+/// `on_match` as soon as that file is done. Callback order follows completion;
+/// the returned results are sorted by path.
 fn run_parallel_search(
 	entries: &[FileEntry],
 	matcher: &grep_regex::RegexMatcher,
-	context_before: u32,
-	context_after: u32,
-	max_columns: Option<u32>,
-	mode: OutputMode,
-) -> Vec<FileSearchResult> {
-	let params =
-		SearchParams { context_before, context_after, max_columns, mode, max_count: None, offset: 0 };
+	params: SearchParams,
+	on_match: Option<&ThreadsafeFunction<GrepMatch>>,
+	ct: &task::CancelToken,
+) -> Result<Vec<FileSearchResult>> {
 	let mut results: Vec<FileSearchResult> = entries
 		.par_iter()
 		.filter_map(|entry| {
+			if ct.aborted() {
+				return None;
+			}
 			let file = File::open(&entry.path).ok()?;
 			let reader = file.take(MAX_FILE_BYTES);
 			let search = run_search_reader(matcher, reader, params).ok()?;
-			Some(FileSearchResult {
-				relative_path: entry.relative_path.clone(),
-				matches:       search.matches,
-				match_count:   search.match_count,
-			})
+			let match_count = search.match_count;
+			let matches = if match_count == 0 {
+				Vec::new()
+			} else {
+				to_grep_matches(&entry.relative_path, search, params.mode)
+			};
+			emit_matches(on_match, &matches);
+			Some(FileSearchResult { relative_path: entry.relative_path.clone(), matches, match_count })
 		})
 		.collect();
+	ct.heartbeat()?;
 
 	results.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
-	results
+	Ok(results)
 }
 
+/// Search entries in order, honoring the global offset and match limit.
+/// Matches are streamed to `on_match` after each file.
 fn run_sequential_search(
 	entries: &[FileEntry],
 	matcher: &grep_regex::RegexMatcher,
 	params: SearchParams,
-) -> (Vec<GrepMatch>, u64, u32, u32, bool) {
+	on_match: Option<&ThreadsafeFunction<GrepMatch>>,
+	ct: &task::CancelToken,
+) -> Result<(Vec<GrepMatch>, u64, u32, u32, bool)> {
 	let SearchParams { mode, max_count, offset, .. } = params;
 	let mut matches = Vec::new();
 	let mut total_matches = 0u64;
@@ -708,6 +772,7 @@ fn run_sequential_search(
 		if limit_reached {
 			break;
 		}
+		ct.heartbeat()?;
 
 		// Calculate offset for this file (skip matches we've already seen)
 		let file_offset = offset.saturating_sub(total_matches);
@@ -737,32 +802,16 @@ fn run_sequential_search(
 		files_with_matches = files_with_matches.saturating_add(1);
 		total_matches = total_matches.saturating_add(search.match_count);
 		collected = collected.saturating_add(search.collected);
-
-		match mode {
-			OutputMode::Content => {
-				for matched in search.matches {
-					matches.push(to_grep_match(&entry.relative_path, matched));
-				}
-			},
-			OutputMode::Count => {
-				matches.push(GrepMatch {
-					path:           entry.relative_path.clone(),
-					line_number:    0,
-					line:           String::new(),
-					context_before: None,
-					context_after:  None,
-					truncated:      None,
-					match_count:    Some(clamp_u32(search.match_count)),
-				});
-			},
-		}
-
 		if search.limit_reached || max_count.is_some_and(|max| collected >= max) {
 			limit_reached = true;
 		}
+
+		let file_matches = to_grep_matches(&entry.relative_path, search, mode);
+		emit_matches(on_match, &file_matches);
+		matches.extend(file_matches);
 	}
 
-	(matches, total_matches, files_with_matches, files_searched, limit_reached)
+	Ok((matches, total_matches, files_with_matches, files_searched, limit_reached))
 }
 
 fn search_sync(content: &[u8], options: SearchOptions) -> SearchResult {
@@ -868,33 +917,16 @@ fn grep_sync(
 			});
 		}
 
-		let path_string = search_path.to_string_lossy().to_string();
-		let mut matches = Vec::new();
-		match output_mode {
-			OutputMode::Content => {
-				for matched in search.matches {
-					matches.push(to_grep_match(&path_string, matched));
-				}
-			},
-			OutputMode::Count => {
-				matches.push(GrepMatch {
-					path:           path_string,
-					line_number:    0,
-					line:           String::new(),
-					context_before: None,
-					context_after:  None,
-					truncated:      None,
-					match_count:    Some(clamp_u32(search.match_count)),
-				});
-			},
-		}
-
 		let limit_reached =
 			search.limit_reached || max_count.is_some_and(|max| search.collected >= max);
+		let match_count = search.match_count;
+		let path_string = search_path.to_string_lossy().to_string();
+		let matches = to_grep_matches(&path_string, search, output_mode);
+		emit_matches(on_match, &matches);
 
 		return Ok(GrepResult {
 			matches,
-			total_matches: clamp_u32(search.match_count),
+			total_matches: clamp_u32(match_count),
 			files_with_matches: 1,
 			files_searched: 1,
 			limit_reached: if limit_reached { Some(true) } else { None },
@@ -926,16 +958,17 @@ fn grep_sync(
 		});
 	}
 
+	let params = SearchParams {
+		context_before,
+		context_after,
+		max_columns,
+		mode: output_mode,
+		max_count,
+		offset,
+	};
 	let allow_parallel = max_count.is_none() && offset == 0;
 	if allow_parallel {
-		let results = run_parallel_search(
-			&entries,
-			&matcher,
-			context_before,
-			context_after,
-			max_columns,
-			output_mode,
-		);
+		let results = run_parallel_search(&entries, &matcher, params, on_match, &ct)?;
 		let mut matches = Vec::new();
 		let mut total_matches = 0u64;
 		let mut files_with_matches = 0u32;
@@ -947,33 +980,7 @@ fn grep_sync(
 			}
 			files_with_matches = files_with_matches.saturating_add(1);
 			total_matches = total_matches.saturating_add(result.match_count);
-
-			match output_mode {
-				OutputMode::Content => {
-					for matched in result.matches {
-						let grep_match = to_grep_match(&result.relative_path, matched);
-						if let Some(callback) = on_match {
-							callback.call(Ok(grep_match.clone()), ThreadsafeFunctionCallMode::NonBlocking);
-						}
-						matches.push(grep_match);
-					}
-				},
-				OutputMode::Count => {
-					let grep_match = GrepMatch {
-						path:           result.relative_path.clone(),
-						line_number:    0,
-						line:           String::new(),
-						context_before: None,
-						context_after:  None,
-						truncated:      None,
-						match_count:    Some(clamp_u32(result.match_count)),
-					};
-					if let Some(callback) = on_match {
-						callback.call(Ok(grep_match.clone()), ThreadsafeFunctionCallMode::NonBlocking);
-					}
-					matches.push(grep_match);
-				},
-			}
+			matches.extend(result.matches);
 		}
 
 		return Ok(GrepResult {
@@ -986,21 +993,7 @@ fn grep_sync(
 	}
 
 	let (matches, total_matches, files_with_matches, files_searched, limit_reached) =
-		run_sequential_search(&entries, &matcher, SearchParams {
-			context_before,
-			context_after,
-			max_columns,
-			mode: output_mode,
-			max_count,
-			offset,
-		});
-
-	// Fire callbacks for sequential search results
-	if let Some(callback) = on_match {
-		for grep_match in &matches {
-			callback.call(Ok(grep_match.clone()), ThreadsafeFunctionCallMode::NonBlocking);
-		}
-	}
+		run_sequential_search(&entries, &matcher, params, on_match, &ct)?;
 
 	Ok(GrepResult {
 		matches,
@@ -1113,6 +1106,7 @@ pub fn grep(
 		mode,
 		timeout_ms,
 		signal,
+		allow_outside_workspace,
 	} = options;
 
 	let config = GrepConfig {
@@ -1134,5 +1128,8 @@ pub fn grep(
 	};
 
 	let ct = task::CancelToken::new(timeout_ms, signal);
-	task::blocking("grep", ct, move |ct| grep_sync(config, on_match.as_ref(), ct))
+	task::blocking("grep", ct, move |ct| {
+		workspace::check(&config.path, allow_outside_workspace)?;
+		grep_sync(config, on_match.as_ref(), ct)
+	})
 }
@@ -0,0 +1,171 @@
+//! Content hashing for files and in-memory data.
+//!
+//! # Overview
+//! Digests are returned as lowercase hex. Files are hashed in fixed-size
+//! chunks, so memory use stays flat regardless of file size; BLAKE3 hashes
+//! each chunk across the rayon pool.
+//!
+//! Algorithms:
+//! - **blake3**: fastest, for caches and deduplication
+//! - **sha256** (default): matches the hashes of `readFileSmart` and
+//!   `writeFileAtomic`
+//! - **md5**: for checksums published alongside downloads
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.hashFile("dist/app.tar.gz", "blake3")
+//! // JS: native.hashString(text, "sha256")
+//! ```
+
+use std::{fmt::Write as _, fs::File, io::Read};
+
+use md5::Md5;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use sha2::{Digest, Sha256};
+
+use crate::{task, workspace};
+
+/// Bytes read from a file per update.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Hash algorithm selected by name.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+	Blake3,
+	#[default]
+	Sha256,
+	Md5,
+}
+
+impl Algorithm {
+	/// Parse the `algorithm` argument.
+	pub fn parse(option: Option<&str>) -> Result<Self> {
+		match option {
+			Some("blake3") => Ok(Self::Blake3),
+			None | Some("sha256") => Ok(Self::Sha256),
+			Some("md5") => Ok(Self::Md5),
+			Some(other) => Err(Error::from_reason(format!(
+				"Invalid hash algorithm: {other} (expected \"blake3\", \"sha256\" or \"md5\")"
+			))),
+		}
+	}
+
+	fn hasher(self) -> Hasher {
+		match self {
+			Self::Blake3 => Hasher::Blake3(Box::default()),
+			Self::Sha256 => Hasher::Sha256(Sha256::new()),
+			Self::Md5 => Hasher::Md5(Md5::new()),
+		}
+	}
+
+	/// Hex digest of `bytes`.
+	pub fn hex_digest(self, bytes: &[u8]) -> String {
+		let mut hasher = self.hasher();
+		hasher.update(bytes);
+		hasher.finalize_hex()
+	}
+}
+
+/// Incremental hash state.
+enum Hasher {
+	Blake3(Box<blake3::Hasher>),
+	Sha256(Sha256),
+	Md5(Md5),
+}
+
+impl Hasher {
+	fn update(&mut self, bytes: &[u8]) {
+		match self {
+			// Splitting pays off only for large inputs; smaller ones hash inline.
+			Self::Blake3(hasher) if bytes.len() >= CHUNK_SIZE => {
+				hasher.update_rayon(bytes);
+			},
+			Self::Blake3(hasher) => {
+				hasher.update(bytes);
+			},
+			Self::Sha256(hasher) => hasher.update(bytes),
+			Self::Md5(hasher) => hasher.update(bytes),
+		}
+	}
+
+	fn finalize_hex(self) -> String {
+		match self {
+			Self::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+			Self::Sha256(hasher) => to_hex(&hasher.finalize()),
+			Self::Md5(hasher) => to_hex(&hasher.finalize()),
+		}
+	}
+}
+
+fn to_hex(digest: &[u8]) -> String {
+	let mut hex = String::with_capacity(digest.len() * 2);
+	for byte in digest {
+		let _ = write!(hex, "{byte:02x}");
+	}
+	hex
+}
+
+fn hash_file_sync(path: &str, algorithm: Algorithm, ct: &task::CancelToken) -> Result<String> {
+	let io_error = |err| Error::from_reason(format!("Failed to hash {path}: {err}"));
+	let mut file = File::open(path).map_err(io_error)?;
+	let mut hasher = algorithm.hasher();
+	let mut buf = vec![0; CHUNK_SIZE];
+	loop {
+		ct.heartbeat()?;
+		// Fill the whole buffer so BLAKE3 gets chunks large enough to split.
+		let mut filled = 0;
+		while filled < buf.len() {
+			match file.read(&mut buf[filled..]).map_err(io_error)? {
+				0 => break,
+				read => filled += read,
+			}
+		}
+		if filled == 0 {
+			break;
+		}
+		hasher.update(&buf[..filled]);
+	}
+	Ok(hasher.finalize_hex())
+}
+
+/// Hash a file's contents, streaming it from disk.
+///
+/// # Errors
+/// Returns an error if the algorithm is unknown, the file cannot be read, or
+/// it is outside the workspace (unless `allowOutsideWorkspace`).
+#[napi(js_name = "hashFile")]
+pub fn hash_file(
+	path: String,
+	#[napi(ts_arg_type = "\"blake3\" | \"sha256\" | \"md5\" | undefined | null")] algorithm: Option<
+		String,
+	>,
+	signal: Option<Unknown>,
+	allow_outside_workspace: Option<bool>,
+) -> Result<task::Async<String>> {
+	let algorithm = Algorithm::parse(algorithm.as_deref())?;
+	let ct = task::CancelToken::new(None, signal);
+	Ok(task::blocking("hash_file", ct, move |ct| {
+		workspace::check(&path, allow_outside_workspace)?;
+		hash_file_sync(&path, algorithm, &ct)
+	}))
+}
+
+/// Hash a string (as UTF-8) or buffer.
+///
+/// # Errors
+/// Returns an error if the algorithm is unknown.
+#[napi(js_name = "hashString")]
+pub fn hash_string(
+	data: Either<String, Uint8Array>,
+	#[napi(ts_arg_type = "\"blake3\" | \"sha256\" | \"md5\" | undefined | null")] algorithm: Option<
+		String,
+	>,
+) -> Result<String> {
+	let algorithm = Algorithm::parse(algorithm.as_deref())?;
+	let bytes: &[u8] = match &data {
+		Either::A(text) => text.as_bytes(),
+		Either::B(buffer) => buffer,
+	};
+	Ok(algorithm.hex_digest(bytes))
+}
@@ -4,15 +4,27 @@
 //! and maps syntect scopes to 11 semantic categories:
 //! - comment, keyword, function, variable, string, number, type, operator,
 //!   punctuation, inserted, deleted
+//!
+//! `highlightCodeWithTheme` instead colors code with one of syntect's bundled
+//! themes, as 24-bit ANSI or as HTML with inline styles.
 
-use std::{cell::RefCell, collections::HashMap, sync::OnceLock};
+use std::{cell::RefCell, collections::HashMap, fmt::Write as _, sync::OnceLock};
 
+use napi::{Error, Result};
 use napi_derive::napi;
-use syntect::parsing::{ParseState, Scope, ScopeStack, ScopeStackOp, SyntaxReference, SyntaxSet};
+use syntect::{
+	highlighting::{
+		Color, FontStyle, HighlightIterator, HighlightState, Highlighter, Style, Theme, ThemeSet,
+	},
+	parsing::{ParseState, Scope, ScopeStack, ScopeStackOp, SyntaxReference, SyntaxSet},
+};
 
 static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
 static SCOPE_MATCHERS: OnceLock<ScopeMatchers> = OnceLock::new();
 
+const DEFAULT_THEME: &str = "base16-ocean.dark";
+
 // Thread-local cache for scope -> color index lookups
 thread_local! {
 	static SCOPE_COLOR_CACHE: RefCell<HashMap<Scope, usize>> = RefCell::new(HashMap::with_capacity(256));
@@ -22,6 +34,10 @@ fn get_syntax_set() -> &'static SyntaxSet {
 	SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
 }
 
+fn get_theme_set() -> &'static ThemeSet {
+	THEME_SET.get_or_init(ThemeSet::load_defaults)
+}
+
 /// Pre-compiled scope patterns for fast matching.
 struct ScopeMatchers {
 	// Comment (index 0)
@@ -470,3 +486,212 @@ pub fn get_supported_languages() -> Vec<String> {
 	let ss = get_syntax_set();
 	ss.syntaxes().iter().map(|s| s.name.clone()).collect()
 }
+
+/// Options for `highlightCodeWithTheme`.
+#[napi(object)]
+pub struct HighlightOptions {
+	/// Language name or extension (default: plain text).
+	pub language: Option<String>,
+	/// Theme from `getHighlightThemes` (default: "base16-ocean.dark").
+	pub theme:    Option<String>,
+	/// "ansi" (default) for 24-bit terminal colors, or "html" for a `<pre>`
+	/// block with inline styles.
+	pub output:   Option<String>,
+}
+
+enum Output {
+	Ansi,
+	Html,
+}
+
+impl Output {
+	fn parse(output: Option<&str>) -> Result<Self> {
+		match output.unwrap_or("ansi") {
+			"ansi" => Ok(Self::Ansi),
+			"html" => Ok(Self::Html),
+			other => Err(Error::from_reason(format!(
+				"Unknown highlight output: {other} (expected ansi or html)"
+			))),
+		}
+	}
+
+	fn start(&self, theme: &Theme, out: &mut String) {
+		if matches!(self, Self::Html) {
+			out.push_str("<pre style=\"");
+			if let Some(background) = theme.settings.background {
+				let _ = write!(out, "background-color:{};", css_color(background));
+			}
+			if let Some(foreground) = theme.settings.foreground {
+				let _ = write!(out, "color:{};", css_color(foreground));
+			}
+			out.push_str("\">");
+		}
+	}
+
+	fn finish(&self, out: &mut String) {
+		if matches!(self, Self::Html) {
+			out.push_str("</pre>");
+		}
+	}
+
+	/// Append a run of text in `style`, keeping line breaks out of the
+	/// styling so lines can be rendered on their own.
+	fn push(&self, style: Style, text: &str, out: &mut String) {
+		let body = text.trim_end_matches(['\n', '\r']);
+		if !body.is_empty() {
+			match self {
+				Self::Ansi => push_ansi(style, body, out),
+				Self::Html => push_html(style, body, out),
+			}
+		}
+		out.push_str(&text[body.len()..]);
+	}
+
+	fn push_plain(&self, text: &str, out: &mut String) {
+		match self {
+			Self::Ansi => out.push_str(text),
+			Self::Html => push_escaped(text, out),
+		}
+	}
+}
+
+fn css_color(color: Color) -> String {
+	if color.a == 0xff {
+		format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+	} else {
+		format!("#{:02x}{:02x}{:02x}{:02x}", color.r, color.g, color.b, color.a)
+	}
+}
+
+fn push_ansi(style: Style, text: &str, out: &mut String) {
+	let Color { r, g, b, .. } = style.foreground;
+	let _ = write!(out, "\x1b[38;2;{r};{g};{b}m");
+	if style.font_style.contains(FontStyle::BOLD) {
+		out.push_str("\x1b[1m");
+	}
+	if style.font_style.contains(FontStyle::ITALIC) {
+		out.push_str("\x1b[3m");
+	}
+	if style.font_style.contains(FontStyle::UNDERLINE) {
+		out.push_str("\x1b[4m");
+	}
+	out.push_str(text);
+	if style.font_style.is_empty() {
+		out.push_str("\x1b[39m");
+	} else {
+		out.push_str("\x1b[0m");
+	}
+}
+
+fn push_html(style: Style, text: &str, out: &mut String) {
+	let _ = write!(out, "<span style=\"color:{};", css_color(style.foreground));
+	if style.font_style.contains(FontStyle::BOLD) {
+		out.push_str("font-weight:bold;");
+	}
+	if style.font_style.contains(FontStyle::ITALIC) {
+		out.push_str("font-style:italic;");
+	}
+	if style.font_style.contains(FontStyle::UNDERLINE) {
+		out.push_str("text-decoration:underline;");
+	}
+	out.push_str("\">");
+	push_escaped(text, out);
+	out.push_str("</span>");
+}
+
+fn push_escaped(text: &str, out: &mut String) {
+	for c in text.chars() {
+		match c {
+			'&' => out.push_str("&amp;"),
+			'<' => out.push_str("&lt;"),
+			'>' => out.push_str("&gt;"),
+			'"' => out.push_str("&quot;"),
+			_ => out.push(c),
+		}
+	}
+}
+
+/// Highlight code with one of syntect's bundled themes.
+///
+/// Unlike `highlightCode`, colors come from the theme rather than a semantic
+/// palette, and output can be ANSI or HTML.
+///
+/// # Errors
+/// Returns an error for an unknown theme or output format.
+#[napi(js_name = "highlightCodeWithTheme")]
+pub fn highlight_code_with_theme(
+	code: String,
+	options: Option<HighlightOptions>,
+) -> Result<String> {
+	let (language, theme, output) = options
+		.map_or((None, None, None), |options| (options.language, options.theme, options.output));
+	let output = Output::parse(output.as_deref())?;
+	let theme = find_theme(theme.as_deref())?;
+	Ok(highlight_with_theme(&code, language.as_deref(), theme, &output))
+}
+
+/// Look up a bundled theme by name, or the default theme.
+pub(crate) fn find_theme(name: Option<&str>) -> Result<&'static Theme> {
+	let name = name.unwrap_or(DEFAULT_THEME);
+	get_theme_set().themes.get(name).ok_or_else(|| {
+		Error::from_reason(format!(
+			"Unknown highlight theme: {name} (expected one of {})",
+			get_highlight_themes().join(", ")
+		))
+	})
+}
+
+/// Highlight `code` as 24-bit ANSI text with `theme`, as plain text for an
+/// unknown language.
+pub(crate) fn highlight_ansi(code: &str, language: Option<&str>, theme: &Theme) -> String {
+	highlight_with_theme(code, language, theme, &Output::Ansi)
+}
+
+fn highlight_with_theme(
+	code: &str,
+	language: Option<&str>,
+	theme: &Theme,
+	output: &Output,
+) -> String {
+	let ss = get_syntax_set();
+	let syntax = language
+		.and_then(|language| find_syntax(ss, language))
+		.unwrap_or_else(|| ss.find_syntax_plain_text());
+
+	let highlighter = Highlighter::new(theme);
+	let mut highlight_state = HighlightState::new(&highlighter, ScopeStack::new());
+	let mut parse_state = ParseState::new(syntax);
+	let mut result = String::with_capacity(code.len() * 2);
+	// Adjacent tokens sharing a style are written as one run.
+	let mut run: Option<(Style, String)> = None;
+	output.start(theme, &mut result);
+	for line in syntect::util::LinesWithEndings::from(code) {
+		let Ok(ops) = parse_state.parse_line(line, ss) else {
+			output.push_plain(line, &mut result);
+			continue;
+		};
+		for (style, text) in HighlightIterator::new(&mut highlight_state, &ops, line, &highlighter) {
+			match &mut run {
+				Some((run_style, run_text)) if *run_style == style => run_text.push_str(text),
+				_ => {
+					if let Some((run_style, run_text)) = run.replace((style, text.to_string())) {
+						output.push(run_style, &run_text, &mut result);
+					}
+				},
+			}
+		}
+		if let Some((run_style, run_text)) = run.take() {
+			output.push(run_style, &run_text, &mut result);
+		}
+	}
+	output.finish(&mut result);
+	result
+}
+
+/// Get the names of the bundled themes for `highlightCodeWithTheme`.
+#[napi(js_name = "getHighlightThemes")]
+pub fn get_highlight_themes() -> Vec<String> {
+	let mut themes: Vec<String> = get_theme_set().themes.keys().cloned().collect();
+	themes.sort();
+	themes
+}
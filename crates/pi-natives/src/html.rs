@@ -1,11 +1,20 @@
 //! HTML to Markdown conversion.
 
-use html_to_markdown_rs::{ConversionOptions, PreprocessingOptions, PreprocessingPreset, convert};
+use std::{cell::RefCell, rc::Rc};
+
+use html_to_markdown_rs::{
+	ConversionOptions, PreprocessingOptions, PreprocessingPreset, convert_with_visitor,
+	visitor::{HtmlVisitor, NodeContext, VisitResult},
+};
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
+use url::Url;
 
 use crate::task;
 
+/// Appended to Markdown cut off at `maxLength`.
+const TRUNCATION_MARKER: &str = "\n\n[content truncated]";
+
 /// Options for HTML to Markdown conversion.
 #[napi(object)]
 #[derive(Debug, Default)]
@@ -16,22 +25,158 @@ pub struct HtmlToMarkdownOptions {
 	/// Skip images during conversion.
 	#[napi(js_name = "skipImages")]
 	pub skip_images:   Option<bool>,
+	/// Resolve relative link and image URLs against this URL.
+	#[napi(js_name = "baseUrl")]
+	pub base_url:      Option<String>,
+	/// Keep links as Markdown links (default: true). When false only their
+	/// text is kept.
+	#[napi(js_name = "includeLinks")]
+	pub include_links: Option<bool>,
+	/// Maximum length of the Markdown in characters. Longer output is cut at
+	/// a paragraph or line break where possible and ends with a
+	/// `[content truncated]` marker.
+	#[napi(js_name = "maxLength")]
+	pub max_length:    Option<u32>,
+}
+
+/// Rewrites link and image targets during conversion.
+#[derive(Debug)]
+struct LinkRewriter {
+	base:          Option<Url>,
+	include_links: bool,
+}
+
+impl LinkRewriter {
+	/// `href` resolved against the base URL, if that changes it.
+	fn resolve(&self, href: &str) -> Option<String> {
+		let resolved = self.base.as_ref()?.join(href).ok()?.to_string();
+		(resolved != href).then_some(resolved)
+	}
+}
+
+impl HtmlVisitor for LinkRewriter {
+	fn visit_link(
+		&mut self,
+		_ctx: &NodeContext,
+		href: &str,
+		text: &str,
+		title: Option<&str>,
+	) -> VisitResult {
+		if !self.include_links {
+			return VisitResult::Custom(text.to_string());
+		}
+		match self.resolve(href) {
+			Some(url) => {
+				VisitResult::Custom(format!("[{}]{}", escape_label(text), target(&url, title)))
+			},
+			None => VisitResult::Continue,
+		}
+	}
+
+	fn visit_image(
+		&mut self,
+		_ctx: &NodeContext,
+		src: &str,
+		alt: &str,
+		title: Option<&str>,
+	) -> VisitResult {
+		match self.resolve(src) {
+			Some(url) => {
+				VisitResult::Custom(format!("![{}]{}", escape_label(alt), target(&url, title)))
+			},
+			None => VisitResult::Continue,
+		}
+	}
+}
+
+/// Escape brackets in a link label that would end it early.
+fn escape_label(label: &str) -> String {
+	let mut escaped = String::with_capacity(label.len());
+	let mut depth = 0usize;
+	let mut backslashes = 0usize;
+	for ch in label.chars() {
+		let is_escaped = backslashes % 2 == 1;
+		backslashes = if ch == '\\' { backslashes + 1 } else { 0 };
+		match ch {
+			'[' if !is_escaped => depth += 1,
+			']' if !is_escaped && depth > 0 => depth -= 1,
+			']' if !is_escaped => escaped.push('\\'),
+			_ => {},
+		}
+		escaped.push(ch);
+	}
+	escaped
+}
+
+/// `(url "title")` part of a link or image.
+fn target(url: &str, title: Option<&str>) -> String {
+	let url = if url.contains(['(', ')']) {
+		format!("<{url}>")
+	} else {
+		url.to_string()
+	};
+	match title {
+		Some(title) if !title.is_empty() => format!("({url} \"{}\")", title.replace('"', "\\\"")),
+		_ => format!("({url})"),
+	}
+}
+
+/// Cut `markdown` to at most `max_len` characters including the truncation
+/// marker, preferring a paragraph break, then a line break, in the second
+/// half of the kept text.
+fn truncate(mut markdown: String, max_len: usize) -> String {
+	if markdown.chars().count() <= max_len {
+		return markdown;
+	}
+	let marker_len = TRUNCATION_MARKER.chars().count();
+	let Some(keep) = max_len.checked_sub(marker_len) else {
+		let end = markdown
+			.char_indices()
+			.nth(max_len)
+			.map_or(markdown.len(), |(idx, _)| idx);
+		markdown.truncate(end);
+		return markdown;
+	};
+	let end = markdown
+		.char_indices()
+		.nth(keep)
+		.map_or(markdown.len(), |(idx, _)| idx);
+	let kept = &markdown[..end];
+	let end = kept
+		.rfind("\n\n")
+		.filter(|&idx| idx >= end / 2)
+		.or_else(|| kept.rfind('\n').filter(|&idx| idx >= end / 2))
+		.unwrap_or(end);
+	markdown.truncate(end);
+	markdown.truncate(markdown.trim_end().len());
+	markdown.push_str(TRUNCATION_MARKER);
+	markdown
 }
 
 /// Convert HTML source to Markdown with optional preprocessing.
 ///
 /// # Errors
-/// Returns an error if the conversion fails or the worker task aborts.
+/// Returns an error if `baseUrl` is not a valid URL, the conversion fails or
+/// the worker task aborts.
 #[napi(js_name = "htmlToMarkdown")]
 pub fn html_to_markdown(
 	html: String,
 	options: Option<HtmlToMarkdownOptions>,
-) -> task::Async<String> {
+) -> Result<task::Async<String>> {
 	let options = options.unwrap_or_default();
 	let clean_content = options.clean_content.unwrap_or(false);
 	let skip_images = options.skip_images.unwrap_or(false);
+	let include_links = options.include_links.unwrap_or(true);
+	let max_length = options.max_length.map(|len| len as usize);
+	let base = options
+		.base_url
+		.map(|base| {
+			Url::parse(&base)
+				.map_err(|err| Error::from_reason(format!("Invalid base URL {base}: {err}")))
+		})
+		.transpose()?;
 
-	task::blocking("html_to_markdown", (), move |_| {
+	Ok(task::blocking("html_to_markdown", (), move |_| {
 		let conversion_opts = ConversionOptions {
 			skip_images,
 			preprocessing: PreprocessingOptions {
@@ -43,7 +188,13 @@ pub fn html_to_markdown(
 			..Default::default()
 		};
 
-		convert(html.as_str(), Some(conversion_opts))
-			.map_err(|err| Error::from_reason(format!("Conversion error: {err}")))
-	})
+		let visitor = (base.is_some() || !include_links)
+			.then(|| Rc::new(RefCell::new(LinkRewriter { base, include_links })) as _);
+		let markdown = convert_with_visitor(html.as_str(), Some(conversion_opts), visitor)
+			.map_err(|err| Error::from_reason(format!("Conversion error: {err}")))?;
+		Ok(match max_length {
+			Some(max_len) => truncate(markdown, max_len),
+			None => markdown,
+		})
+	}))
 }
@@ -0,0 +1,190 @@
+//! `.gitignore`-style matching outside of directory walks.
+//!
+//! # Overview
+//! `compileIgnoreRules` builds a matcher from gitignore patterns and ignore
+//! files, optionally together with the `.gitignore`, `.ignore` and
+//! `.git/info/exclude` files under the root, read the way `fuzzyFind`,
+//! `grep` and `glob` read them: rules of deeper directories take precedence,
+//! `!` re-includes, and a path inside an ignored directory is ignored.
+//! The symbol index checks changed paths with the same rules.
+//!
+//! Rule files found under the root are read on first use and cached for
+//! the life of the matcher; compile a new one after they change.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const matcher = native.compileIgnoreRules({ root, patterns: ["*.log", "!keep.log"], gitignore: true });
+//! // JS: matcher.matches("dist/app.js");
+//! ```
+
+use std::{
+	collections::HashMap,
+	env, fs,
+	path::{Path, PathBuf},
+};
+
+use ignore::{
+	Match,
+	gitignore::{Gitignore, GitignoreBuilder, Glob},
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use parking_lot::Mutex;
+
+/// Options for `compileIgnoreRules`.
+#[napi(object)]
+#[derive(Default)]
+pub struct IgnoreRulesOptions {
+	/// Directory patterns and paths are relative to (default: the working
+	/// directory).
+	pub root:      Option<String>,
+	/// Gitignore lines, applied after every rule file.
+	pub patterns:  Option<Vec<String>>,
+	/// Ignore files whose lines apply relative to the root.
+	pub files:     Option<Vec<String>>,
+	/// Also apply the `.gitignore`, `.ignore` and `.git/info/exclude` files
+	/// under the root, as directory walks do (default: false).
+	pub gitignore: Option<bool>,
+}
+
+/// Ignore rules under a root.
+pub struct Rules {
+	root:     PathBuf,
+	/// Rules given explicitly, which take precedence over discovered ones.
+	explicit: Gitignore,
+	discover: bool,
+	/// Rule files of each directory, read on first use.
+	found:    Mutex<HashMap<PathBuf, Gitignore>>,
+}
+
+impl Rules {
+	/// Rules from the ignore files under `root` alone.
+	pub fn discover(root: &Path) -> Self {
+		Self {
+			root:     root.to_path_buf(),
+			explicit: Gitignore::empty(),
+			discover: true,
+			found:    Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Rules of `dir`'s own ignore files.
+	fn read_dir_rules(&self, dir: &Path) -> Gitignore {
+		let mut builder = GitignoreBuilder::new(dir);
+		for name in [".gitignore", ".ignore"] {
+			builder.add(dir.join(name));
+		}
+		if dir == self.root {
+			builder.add(dir.join(".git/info/exclude"));
+		}
+		builder.build().unwrap_or_else(|_| Gitignore::empty())
+	}
+
+	/// Whether `path`, below the root, is ignored. It is checked against
+	/// the rules of every directory from the root down, so a path inside an
+	/// ignored directory is ignored too.
+	pub fn matched(&self, path: &Path, is_dir: bool) -> bool {
+		let Ok(relative) = path.strip_prefix(&self.root) else {
+			return false;
+		};
+		let mut ignored = false;
+		if self.discover {
+			let mut dirs = vec![self.root.clone()];
+			for component in relative.parent().into_iter().flat_map(Path::components) {
+				let next = dirs[dirs.len() - 1].join(component);
+				dirs.push(next);
+			}
+			let mut found = self.found.lock();
+			for dir in dirs {
+				let rules = found
+					.entry(dir)
+					.or_insert_with_key(|dir| self.read_dir_rules(dir));
+				ignored = verdict(rules.matched_path_or_any_parents(path, is_dir), ignored);
+			}
+		}
+		verdict(self.explicit.matched_path_or_any_parents(path, is_dir), ignored)
+	}
+}
+
+/// Whether a path is ignored after a rule set's `matched`, given whether it
+/// was before.
+const fn verdict(matched: Match<&Glob>, ignored: bool) -> bool {
+	match matched {
+		Match::Ignore(_) => true,
+		Match::Whitelist(_) => false,
+		Match::None => ignored,
+	}
+}
+
+/// Compiled ignore rules, from `compileIgnoreRules`.
+#[napi]
+pub struct IgnoreMatcher {
+	rules: Rules,
+}
+
+#[napi]
+impl IgnoreMatcher {
+	/// Whether `path` is ignored. Relative paths are relative to the root;
+	/// paths outside it are never ignored. `isDir` defaults to whether the
+	/// path is a directory on disk.
+	#[napi]
+	pub fn matches(&self, path: String, is_dir: Option<bool>) -> bool {
+		let path = self.rules.root.join(path);
+		let is_dir = is_dir.unwrap_or_else(|| path.is_dir());
+		self.rules.matched(&path, is_dir)
+	}
+
+	/// The paths of `paths` that are not ignored, in order.
+	#[napi]
+	pub fn filter(&self, paths: Vec<String>) -> Vec<String> {
+		paths
+			.into_iter()
+			.filter(|path| !self.matches(path.clone(), None))
+			.collect()
+	}
+
+	/// Absolute root the rules apply under.
+	#[napi(getter)]
+	pub fn root(&self) -> String {
+		self.rules.root.to_string_lossy().into_owned()
+	}
+}
+
+/// Compile gitignore patterns and ignore files into a matcher, with the
+/// semantics directory walks use.
+///
+/// # Errors
+/// Returns an error for an invalid pattern or an unreadable ignore file.
+#[napi(js_name = "compileIgnoreRules")]
+pub fn compile_ignore_rules(options: Option<IgnoreRulesOptions>) -> Result<IgnoreMatcher> {
+	let options = options.unwrap_or_default();
+	let cwd = env::current_dir()
+		.map_err(|err| Error::from_reason(format!("Failed to resolve cwd: {err}")))?;
+	let root = options
+		.root
+		.map_or_else(|| cwd.clone(), |root| cwd.join(root));
+	let root = fs::canonicalize(&root).unwrap_or(root);
+
+	let mut builder = GitignoreBuilder::new(&root);
+	for file in options.files.unwrap_or_default() {
+		if let Some(err) = builder.add(root.join(&file)) {
+			return Err(Error::from_reason(format!("Failed to read ignore file {file}: {err}")));
+		}
+	}
+	for pattern in options.patterns.unwrap_or_default() {
+		builder
+			.add_line(None, &pattern)
+			.map_err(|err| Error::from_reason(format!("Invalid ignore pattern {pattern}: {err}")))?;
+	}
+	let explicit = builder
+		.build()
+		.map_err(|err| Error::from_reason(format!("Failed to compile ignore rules: {err}")))?;
+	Ok(IgnoreMatcher {
+		rules: Rules {
+			root,
+			explicit,
+			discover: options.gitignore.unwrap_or(false),
+			found: Mutex::new(HashMap::new()),
+		},
+	})
+}
@@ -5,18 +5,25 @@
 //! - Get dimensions
 //! - Resize with configurable filter
 //! - Export as PNG, JPEG, WebP, or GIF
+//! - Read dimensions, format and EXIF orientation from a file header
+//! - Downscale a file to fit a bounding box, upright, in one call
 
-use std::{io::Cursor, sync::Arc};
+use std::{
+	fs::File,
+	io::{BufReader, Cursor},
+	sync::Arc,
+};
 
 use image::{
-	DynamicImage, ImageFormat, ImageReader,
+	DynamicImage, ImageDecoder, ImageFormat, ImageReader,
 	codecs::{jpeg::JpegEncoder, webp::WebPEncoder},
 	imageops::FilterType,
+	metadata::Orientation,
 };
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::task;
+use crate::{task, workspace};
 
 /// Sampling filter for resize operations.
 #[napi]
@@ -129,6 +136,14 @@ fn encode_image(img: &DynamicImage, format: u8, quality: u8) -> Result<Vec<u8>>
 		1 => {
 			let mut buffer = Vec::with_capacity((w * h * 3) as usize);
 			let encoder = JpegEncoder::new_with_quality(&mut buffer, quality);
+			// JPEG has no alpha channel.
+			let rgb;
+			let img = if img.color().has_alpha() {
+				rgb = DynamicImage::ImageRgb8(img.to_rgb8());
+				&rgb
+			} else {
+				img
+			};
 			img.write_with_encoder(encoder)
 				.map_err(|e| Error::from_reason(format!("Failed to encode JPEG: {e}")))?;
 			Ok(buffer)
@@ -149,3 +164,226 @@ fn encode_image(img: &DynamicImage, format: u8, quality: u8) -> Result<Vec<u8>>
 		_ => Err(Error::from_reason(format!("Invalid image format: {format}"))),
 	}
 }
+
+/// Image formats readable by `imageInfo` and writable by `resizeImage`, with
+/// their `encode` format codes.
+const FORMATS: [(&str, ImageFormat, u8); 4] = [
+	("png", ImageFormat::Png, 0),
+	("jpeg", ImageFormat::Jpeg, 1),
+	("webp", ImageFormat::WebP, 2),
+	("gif", ImageFormat::Gif, 3),
+];
+
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// Header information of an image file.
+#[napi(object)]
+pub struct ImageInfo {
+	/// Stored width in pixels.
+	pub width:          u32,
+	/// Stored height in pixels.
+	pub height:         u32,
+	/// Width once the EXIF orientation is applied.
+	#[napi(js_name = "displayWidth")]
+	pub display_width:  u32,
+	/// Height once the EXIF orientation is applied.
+	#[napi(js_name = "displayHeight")]
+	pub display_height: u32,
+	/// Image format: `png`, `jpeg`, `webp` or `gif`.
+	pub format:         String,
+	/// MIME type of the format.
+	#[napi(js_name = "mimeType")]
+	pub mime_type:      String,
+	/// EXIF orientation (1-8; 1 when the image is stored upright or has no
+	/// EXIF data).
+	pub orientation:    u32,
+	/// Whether the image has an alpha channel.
+	#[napi(js_name = "hasAlpha")]
+	pub has_alpha:      bool,
+	/// File size in bytes.
+	pub bytes:          f64,
+}
+
+/// Options for `resizeImage`.
+#[napi(object)]
+pub struct ResizeImageOptions {
+	/// Maximum output width in pixels (default: no limit).
+	#[napi(js_name = "maxWidth")]
+	pub max_width:               Option<u32>,
+	/// Maximum output height in pixels (default: no limit).
+	#[napi(js_name = "maxHeight")]
+	pub max_height:              Option<u32>,
+	/// Output format (default: the input format).
+	#[napi(js_name = "formatOut", ts_type = "\"png\" | \"jpeg\" | \"webp\" | \"gif\"")]
+	pub format_out:              Option<String>,
+	/// JPEG quality, 1-100 (default: 85). Ignored for other formats.
+	pub quality:                 Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Encoded output of `resizeImage`.
+#[napi(object)]
+pub struct ResizedImage {
+	/// Encoded image bytes.
+	pub data:      Buffer,
+	/// Output width in pixels.
+	pub width:     u32,
+	/// Output height in pixels.
+	pub height:    u32,
+	/// Output format: `png`, `jpeg`, `webp` or `gif`.
+	pub format:    String,
+	/// MIME type of the output format.
+	#[napi(js_name = "mimeType")]
+	pub mime_type: String,
+	/// Whether the image was scaled down to fit the limits.
+	pub resized:   bool,
+}
+
+fn format_name(format: ImageFormat) -> Result<&'static str> {
+	FORMATS
+		.iter()
+		.find(|&&(_, candidate, _)| candidate == format)
+		.map(|&(name, ..)| name)
+		.ok_or_else(|| Error::from_reason(format!("Unsupported image format: {format:?}")))
+}
+
+fn open_image(path: &str) -> Result<ImageReader<BufReader<File>>> {
+	ImageReader::open(path)
+		.and_then(ImageReader::with_guessed_format)
+		.map_err(|e| Error::from_reason(format!("Failed to read image {path}: {e}")))
+}
+
+const fn is_transposed(orientation: Orientation) -> bool {
+	matches!(
+		orientation,
+		Orientation::Rotate90
+			| Orientation::Rotate270
+			| Orientation::Rotate90FlipH
+			| Orientation::Rotate270FlipH
+	)
+}
+
+fn image_info_sync(path: &str) -> Result<ImageInfo> {
+	let reader = open_image(path)?;
+	let format = reader
+		.format()
+		.ok_or_else(|| Error::from_reason(format!("Unrecognized image format: {path}")))?;
+	let name = format_name(format)?;
+	let mut decoder = reader
+		.into_decoder()
+		.map_err(|e| Error::from_reason(format!("Failed to read image {path}: {e}")))?;
+	let (width, height) = decoder.dimensions();
+	let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+	let (display_width, display_height) = if is_transposed(orientation) {
+		(height, width)
+	} else {
+		(width, height)
+	};
+	let bytes = std::fs::metadata(path)
+		.map_err(|e| Error::from_reason(format!("Failed to read image {path}: {e}")))?
+		.len();
+	Ok(ImageInfo {
+		width,
+		height,
+		display_width,
+		display_height,
+		format: name.to_string(),
+		mime_type: format.to_mime_type().to_string(),
+		orientation: u32::from(orientation.to_exif()),
+		has_alpha: decoder.color_type().has_alpha(),
+		bytes: bytes as f64,
+	})
+}
+
+fn resize_image_sync(path: &str, options: ResizeImageOptions) -> Result<ResizedImage> {
+	let reader = open_image(path)?;
+	let input_format = reader
+		.format()
+		.ok_or_else(|| Error::from_reason(format!("Unrecognized image format: {path}")))?;
+	let (name, format, code) = match options.format_out.as_deref() {
+		None => FORMATS
+			.into_iter()
+			.find(|&(_, candidate, _)| candidate == input_format)
+			.ok_or_else(|| {
+				Error::from_reason(format!("Unsupported image format: {input_format:?}"))
+			})?,
+		Some(out) => FORMATS
+			.into_iter()
+			.find(|&(name, ..)| name == out)
+			.ok_or_else(|| {
+				Error::from_reason(format!(
+					"Invalid output format: {out} (expected \"png\", \"jpeg\", \"webp\" or \"gif\")"
+				))
+			})?,
+	};
+	let quality = options
+		.quality
+		.map_or(DEFAULT_JPEG_QUALITY, |quality| quality.clamp(1, 100) as u8);
+
+	let mut decoder = reader
+		.into_decoder()
+		.map_err(|e| Error::from_reason(format!("Failed to read image {path}: {e}")))?;
+	let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+	let mut img = DynamicImage::from_decoder(decoder)
+		.map_err(|e| Error::from_reason(format!("Failed to decode image {path}: {e}")))?;
+	img.apply_orientation(orientation);
+
+	let max_width = options.max_width.unwrap_or(u32::MAX).max(1);
+	let max_height = options.max_height.unwrap_or(u32::MAX).max(1);
+	let resized = img.width() > max_width || img.height() > max_height;
+	if resized {
+		img = img.resize(max_width, max_height, FilterType::Lanczos3);
+	}
+
+	let data = encode_image(&img, code, quality)?;
+	Ok(ResizedImage {
+		data: data.into(),
+		width: img.width(),
+		height: img.height(),
+		format: name.to_string(),
+		mime_type: format.to_mime_type().to_string(),
+		resized,
+	})
+}
+
+/// Read an image file's dimensions, format and EXIF orientation without
+/// decoding its pixels.
+///
+/// # Errors
+/// Returns an error if the file cannot be read, is outside the workspace
+/// (unless `allowOutsideWorkspace`), or is not a PNG, JPEG, WebP or GIF
+/// image.
+#[napi(js_name = "imageInfo")]
+pub fn image_info(path: String, allow_outside_workspace: Option<bool>) -> task::Async<ImageInfo> {
+	task::blocking("image.info", (), move |_| {
+		workspace::check(&path, allow_outside_workspace)?;
+		image_info_sync(&path)
+	})
+}
+
+/// Decode an image file, rotate it upright according to its EXIF
+/// orientation, scale it down to fit `maxWidth` x `maxHeight` keeping its
+/// aspect ratio, and encode it. Images are never scaled up.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or decoded, is outside the
+/// workspace, the output format is invalid, or encoding fails.
+#[napi(js_name = "resizeImage")]
+pub fn resize_image(
+	path: String,
+	options: Option<ResizeImageOptions>,
+) -> task::Async<ResizedImage> {
+	let options = options.unwrap_or(ResizeImageOptions {
+		max_width:               None,
+		max_height:              None,
+		format_out:              None,
+		quality:                 None,
+		allow_outside_workspace: None,
+	});
+	task::blocking("image.resize_file", (), move |_| {
+		workspace::check(&path, options.allow_outside_workspace)?;
+		resize_image_sync(&path, options)
+	})
+}
@@ -0,0 +1,425 @@
+//! Undo journal for file edits.
+//!
+//! # Overview
+//! Before `writeFileAtomic` or `applyUnifiedDiff` changes a file, its prior
+//! content is put in the artifact store and the change is recorded in the
+//! session store under an operation id, together with the SHA-256 of what
+//! was written. `beginOperation` opens an operation that several calls can
+//! share through their `operationId` option, e.g. everything one tool call
+//! does; a call without one gets an operation of its own.
+//!
+//! `undoOperation` puts every file of an operation back the way it was
+//! before the operation, recreating deleted files and removing created ones.
+//! It first checks that no file changed since the operation wrote it, and
+//! changes nothing otherwise unless `force` is set.
+//!
+//! Prior contents are ordinary artifacts: `gcArtifacts` may evict them
+//! unless their ids (listed by `listRecentOperations`) are kept, after which
+//! the operation can no longer be undone.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const operationId = await native.beginOperation({ label: "edit tool" });
+//! // JS: await native.writeFileAtomic("src/lib.rs", text, { operationId });
+//! // JS: await native.undoOperation(operationId);
+//! ```
+
+use std::{
+	collections::HashMap,
+	fs, io,
+	path::{Path, PathBuf},
+	sync::LazyLock,
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use parking_lot::RwLock;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::{artifacts, store, task, write};
+
+const DEFAULT_LIST_LIMIT: u32 = 20;
+
+static CONFIG: LazyLock<RwLock<Config>> = LazyLock::new(|| {
+	RwLock::new(Config { enabled: true, db_path: None, artifacts_dir: None })
+});
+
+#[derive(Clone)]
+struct Config {
+	enabled:       bool,
+	db_path:       Option<String>,
+	artifacts_dir: Option<String>,
+}
+
+/// Options for `setEditJournal`.
+#[napi(object)]
+#[derive(Default)]
+pub struct EditJournalOptions {
+	/// Journal file edits (default: true).
+	pub enabled:       Option<bool>,
+	/// Database file (default: the session store, as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:       Option<String>,
+	/// Artifact store for prior contents (default: `~/.omp/agent/artifacts`).
+	#[napi(js_name = "artifactsDir")]
+	pub artifacts_dir: Option<String>,
+}
+
+/// Options for `beginOperation`.
+#[napi(object)]
+#[derive(Default)]
+pub struct BeginOperationOptions {
+	/// What the operation is, e.g. the tool call it groups.
+	pub label: Option<String>,
+}
+
+/// Options for `undoOperation`.
+#[napi(object)]
+#[derive(Default)]
+pub struct UndoOperationOptions {
+	/// Restore files even if they changed after the operation (default:
+	/// false).
+	pub force: Option<bool>,
+}
+
+/// Options for `listRecentOperations`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ListOperationsOptions {
+	/// Maximum number of operations (default: 20).
+	pub limit:          Option<u32>,
+	/// Leave out operations that were undone (default: false).
+	#[napi(js_name = "excludeUndone")]
+	pub exclude_undone: Option<bool>,
+}
+
+/// A file changed by an operation.
+#[napi(object)]
+pub struct OperationFile {
+	/// Absolute path of the file.
+	pub path:   String,
+	/// Artifact id of the content before the operation; absent when the file
+	/// did not exist.
+	pub before: Option<String>,
+}
+
+/// A journaled operation.
+#[napi(object)]
+pub struct JournalOperation {
+	/// Id to pass to `undoOperation`.
+	pub id:         i64,
+	/// Label given to `beginOperation`, or the function that wrote the files.
+	pub label:      Option<String>,
+	/// Files changed, in the order they were first changed.
+	pub files:      Vec<OperationFile>,
+	/// Creation time in milliseconds since the Unix epoch.
+	#[napi(js_name = "createdAt")]
+	pub created_at: f64,
+	/// When the operation was undone, in milliseconds since the Unix epoch.
+	#[napi(js_name = "undoneAt")]
+	pub undone_at:  Option<f64>,
+}
+
+/// Changes being made by one call, recorded by [`Self::finish`].
+pub struct Pending {
+	config:       Config,
+	operation_id: Option<i64>,
+	label:        &'static str,
+	/// Files with the artifact id of their prior content.
+	changes:      Vec<(PathBuf, Option<String>)>,
+}
+
+fn journal_error(path: &Path, err: impl std::fmt::Display) -> Error {
+	Error::from_reason(format!("Failed to journal {}: {err}", path.display()))
+}
+
+/// Hex SHA-256 of the file at `path`, or `None` if it does not exist.
+fn file_hash(path: &Path) -> io::Result<Option<String>> {
+	match fs::read(path) {
+		Ok(bytes) => Ok(Some(write::content_hash(&bytes))),
+		Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(err) => Err(err),
+	}
+}
+
+/// Fail unless operation `id` exists and was not undone.
+fn check_open(conn: &Connection, id: i64) -> Result<()> {
+	let undone_at: Option<Option<i64>> = conn
+		.query_row("SELECT undone_at FROM operations WHERE id = ?1", params![id], |row| row.get(0))
+		.optional()
+		.map_err(store::sql_error)?;
+	match undone_at {
+		None => Err(Error::from_reason(format!("No operation with id {id}"))),
+		Some(Some(_)) => Err(Error::from_reason(format!("Operation {id} was already undone"))),
+		Some(None) => Ok(()),
+	}
+}
+
+impl Pending {
+	/// Start journaling a call, unless the journal is off. `label` names the
+	/// operation created when `operation_id` is not given.
+	///
+	/// # Errors
+	/// Returns an error if `operation_id` is unknown or was undone.
+	pub fn begin(operation_id: Option<i64>, label: &'static str) -> Result<Option<Self>> {
+		let config = CONFIG.read().clone();
+		if !config.enabled {
+			return Ok(None);
+		}
+		if let Some(id) = operation_id {
+			check_open(&store::open_store(config.db_path.clone())?.lock(), id)?;
+		}
+		Ok(Some(Self { config, operation_id, label, changes: Vec::new() }))
+	}
+
+	/// Save the current content of the file at absolute `path` before it is
+	/// changed.
+	///
+	/// # Errors
+	/// Returns an error if the file cannot be read or stored.
+	pub fn capture(&mut self, path: &Path) -> Result<()> {
+		let before = match fs::read(path) {
+			Ok(bytes) => Some(artifacts::store_bytes(self.config.artifacts_dir.clone(), bytes)?.id),
+			Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+			Err(err) => return Err(journal_error(path, err)),
+		};
+		self.changes.push((path.to_path_buf(), before));
+		Ok(())
+	}
+
+	/// Record the captured files as they are now, returning the operation id.
+	///
+	/// # Errors
+	/// Returns an error if the files cannot be hashed or the store written;
+	/// the changes themselves are already made.
+	pub fn finish(self) -> Result<i64> {
+		let mut afters = Vec::with_capacity(self.changes.len());
+		for (path, _) in &self.changes {
+			afters.push(file_hash(path).map_err(|err| journal_error(path, err))?);
+		}
+		let store = store::open_store(self.config.db_path)?;
+		let mut conn = store.lock();
+		let tx = conn.transaction().map_err(store::sql_error)?;
+		let now = store::now_ms();
+		let id = if let Some(id) = self.operation_id {
+			id
+		} else {
+			tx.execute("INSERT INTO operations (label, created_at) VALUES (?1, ?2)", params![
+				self.label, now
+			])
+			.map_err(store::sql_error)?;
+			tx.last_insert_rowid()
+		};
+		for ((path, before), after) in self.changes.iter().zip(afters) {
+			tx.execute(
+				"INSERT INTO operation_files (operation_id, path, before, after_hash, recorded_at)
+				 VALUES (?1, ?2, ?3, ?4, ?5)",
+				params![id, path.to_string_lossy(), before, after, now],
+			)
+			.map_err(store::sql_error)?;
+		}
+		tx.commit().map_err(store::sql_error)?;
+		Ok(id)
+	}
+}
+
+/// A file to put back: its content before the operation and the hash of
+/// what the operation last wrote.
+struct Restore {
+	path:   PathBuf,
+	before: Option<String>,
+	after:  Option<String>,
+}
+
+/// Files of operation `id`, each once, in the order they were first changed.
+fn restores(conn: &Connection, id: i64) -> rusqlite::Result<Vec<Restore>> {
+	let mut stmt = conn.prepare(
+		"SELECT path, before, after_hash FROM operation_files WHERE operation_id = ?1 ORDER BY id",
+	)?;
+	let rows = stmt.query_map(params![id], |row| {
+		Ok(Restore {
+			path:   PathBuf::from(row.get::<_, String>(0)?),
+			before: row.get(1)?,
+			after:  row.get(2)?,
+		})
+	})?;
+	let mut restores: Vec<Restore> = Vec::new();
+	let mut index: HashMap<PathBuf, usize> = HashMap::new();
+	for row in rows {
+		let row = row?;
+		// The first prior content and the last written one count.
+		if let Some(&at) = index.get(&row.path) {
+			restores[at].after = row.after;
+		} else {
+			index.insert(row.path.clone(), restores.len());
+			restores.push(row);
+		}
+	}
+	Ok(restores)
+}
+
+fn undo_sync(id: i64, force: bool) -> Result<JournalOperation> {
+	let config = CONFIG.read().clone();
+	let store = store::open_store(config.db_path)?;
+	let conn = store.lock();
+	check_open(&conn, id)?;
+	let restores = restores(&conn, id).map_err(store::sql_error)?;
+
+	// Check and load everything first, so a conflict changes nothing.
+	let mut contents = Vec::with_capacity(restores.len());
+	for restore in &restores {
+		if !force {
+			let current = file_hash(&restore.path).map_err(|err| {
+				Error::from_reason(format!("Failed to read {}: {err}", restore.path.display()))
+			})?;
+			if current != restore.after {
+				return Err(Error::from_reason(format!(
+					"Conflict: {} changed after operation {id}",
+					restore.path.display()
+				)));
+			}
+		}
+		let content = match &restore.before {
+			Some(artifact) => {
+				let bytes =
+					artifacts::load_bytes(config.artifacts_dir.clone(), artifact)?.ok_or_else(|| {
+						Error::from_reason(format!(
+							"Cannot undo operation {id}: the prior content of {} is no longer stored",
+							restore.path.display()
+						))
+					})?;
+				Some(bytes)
+			},
+			None => None,
+		};
+		contents.push(content);
+	}
+
+	for (restore, content) in restores.iter().zip(contents).rev() {
+		let path = &restore.path;
+		let restored = match content {
+			Some(bytes) => path
+				.parent()
+				.map_or(Ok(()), fs::create_dir_all)
+				.and_then(|()| write::replace_file(path, &bytes)),
+			None => match fs::remove_file(path) {
+				Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+				removed => removed,
+			},
+		};
+		restored.map_err(|err| {
+			Error::from_reason(format!("Failed to restore {}: {err}", path.display()))
+		})?;
+	}
+
+	conn
+		.execute("UPDATE operations SET undone_at = ?1 WHERE id = ?2", params![store::now_ms(), id])
+		.map_err(store::sql_error)?;
+	load_operations(&conn, Some(id), 1, false)
+		.map_err(store::sql_error)?
+		.pop()
+		.ok_or_else(|| Error::from_reason(format!("No operation with id {id}")))
+}
+
+/// Operations newest first, or only operation `id`.
+fn load_operations(
+	conn: &Connection,
+	id: Option<i64>,
+	limit: u32,
+	exclude_undone: bool,
+) -> rusqlite::Result<Vec<JournalOperation>> {
+	let mut stmt = conn.prepare(
+		"SELECT id, label, created_at, undone_at FROM operations
+		 WHERE (?1 IS NULL OR id = ?1) AND (NOT ?2 OR undone_at IS NULL)
+		 ORDER BY id DESC LIMIT ?3",
+	)?;
+	let mut operations = stmt
+		.query_map(params![id, exclude_undone, limit], |row| {
+			Ok(JournalOperation {
+				id:         row.get(0)?,
+				label:      row.get(1)?,
+				files:      Vec::new(),
+				created_at: row.get::<_, i64>(2)? as f64,
+				undone_at:  row.get::<_, Option<i64>>(3)?.map(|at| at as f64),
+			})
+		})?
+		.collect::<rusqlite::Result<Vec<_>>>()?;
+	for operation in &mut operations {
+		operation.files = restores(conn, operation.id)?
+			.into_iter()
+			.map(|restore| OperationFile {
+				path:   restore.path.to_string_lossy().into_owned(),
+				before: restore.before,
+			})
+			.collect();
+	}
+	Ok(operations)
+}
+
+/// Turn the edit journal on or off, or move it to another database or
+/// artifact store. Operations recorded elsewhere are no longer listed or
+/// undone.
+#[napi(js_name = "setEditJournal")]
+pub fn set_edit_journal(options: EditJournalOptions) {
+	*CONFIG.write() = Config {
+		enabled:       options.enabled.unwrap_or(true),
+		db_path:       options.db_path,
+		artifacts_dir: options.artifacts_dir,
+	};
+}
+
+/// Open an operation for `writeFileAtomic` and `applyUnifiedDiff` calls to
+/// share through `operationId`, so they are undone together.
+///
+/// # Errors
+/// Returns an error if the session store cannot be written.
+#[napi(js_name = "beginOperation")]
+pub fn begin_operation(options: Option<BeginOperationOptions>) -> task::Async<i64> {
+	let label = options.unwrap_or_default().label;
+	let db_path = CONFIG.read().db_path.clone();
+	task::blocking("journal.begin", (), move |_| {
+		let store = store::open_store(db_path)?;
+		let conn = store.lock();
+		conn
+			.execute("INSERT INTO operations (label, created_at) VALUES (?1, ?2)", params![
+				label,
+				store::now_ms()
+			])
+			.map_err(store::sql_error)?;
+		Ok(conn.last_insert_rowid())
+	})
+}
+
+/// Put every file changed by operation `id` back the way it was before it.
+///
+/// # Errors
+/// Returns an error starting with `Conflict:` when a file changed after the
+/// operation and `force` is not set, and an error for unknown or already
+/// undone operations or evicted prior contents. Nothing is restored then.
+#[napi(js_name = "undoOperation")]
+pub fn undo_operation(
+	id: i64,
+	options: Option<UndoOperationOptions>,
+) -> task::Async<JournalOperation> {
+	let force = options.unwrap_or_default().force.unwrap_or(false);
+	task::blocking("journal.undo", (), move |_| undo_sync(id, force))
+}
+
+/// Journaled operations, newest first.
+///
+/// # Errors
+/// Returns an error if the session store cannot be read.
+#[napi(js_name = "listRecentOperations")]
+pub fn list_recent_operations(
+	options: Option<ListOperationsOptions>,
+) -> task::Async<Vec<JournalOperation>> {
+	let options = options.unwrap_or_default();
+	let limit = options.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+	let exclude_undone = options.exclude_undone.unwrap_or(false);
+	let db_path = CONFIG.read().db_path.clone();
+	task::blocking("journal.list", (), move |_| {
+		let store = store::open_store(db_path)?;
+		let conn = store.lock();
+		load_operations(&conn, None, limit, exclude_undone).map_err(store::sql_error)
+	})
+}
@@ -21,19 +21,60 @@
 #![allow(clippy::trailing_empty_array, reason = "generated by napi macro")]
 #![allow(clippy::trivially_copy_pass_by_ref, reason = "napi env idiom")]
 
+pub mod archive;
+pub mod artifacts;
+pub mod checkpoint;
 pub mod clipboard;
+pub mod diagnostics;
+pub mod diff;
+pub mod dir_tree;
 pub mod fd;
+pub mod fetch;
+pub mod format;
 pub mod fs_cache;
+pub mod fuzzy;
+pub mod git;
 pub mod glob;
+pub mod glob_expand;
 pub mod grep;
+pub mod hash;
 pub mod highlight;
 pub mod html;
+pub mod ignore_rules;
 pub mod image;
+pub mod journal;
 pub mod keys;
+pub mod lsp;
+pub mod markdown;
+pub mod merge;
+pub mod notify;
+pub mod package_manager;
+pub mod patch;
+pub mod pdf;
 pub mod prof;
+pub mod project;
 pub mod ps;
 pub mod pty;
+pub mod read;
+pub mod ready;
+pub mod remove;
+pub mod secret_scan;
+pub mod secrets;
 pub mod shell;
+pub mod store;
+pub mod symbols;
+pub mod syntax;
 pub mod system_info;
+pub mod tail;
 pub mod task;
+pub mod tempdir;
+pub mod term;
 pub mod text;
+pub mod text_profile;
+pub mod tokens;
+pub mod toolchain;
+pub mod trash;
+pub mod watch;
+pub mod which;
+pub mod workspace;
+pub mod write;
@@ -0,0 +1,1155 @@
+//! Language server client.
+//!
+//! # Overview
+//! Each `lspStart` call spawns a language server in its own process group,
+//! performs the `initialize` handshake over stdio and keeps the server until
+//! `lspStop`, which asks it to shut down and kills its process tree if it
+//! does not exit in time.
+//!
+//! Documents are synced in full: a file is opened from disk on first use and
+//! re-sent with a new version when its content changes, either through
+//! `lspOpenFile` or, with `watch`, when it is saved to disk. Published
+//! diagnostics are cached per file, and `lspDiagnostics` can wait for the
+//! server to publish them for the latest content.
+//!
+//! Positions are LSP positions: 0-based lines and UTF-16 code unit offsets.
+//! Requests from the server are answered with `settings` for
+//! `workspace/configuration` and a refusal for `workspace/applyEdit`; edits
+//! such as renames are returned to the caller instead of applied.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { id } = await native.lspStart({ command: "rust-analyzer", rootPath: "/repo" })
+//! // JS: await native.lspDefinition(id, "src/main.rs", { line: 9, character: 4 })
+//! // JS: await native.lspDiagnostics(id, { path: "src/main.rs", waitMs: 5000 })
+//! ```
+
+use std::{
+	collections::{HashMap, VecDeque},
+	io,
+	path::{Path, PathBuf},
+	process::Stdio,
+	sync::{
+		Arc, LazyLock, Weak,
+		atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering},
+	},
+	time::Duration,
+};
+
+use napi::{
+	bindgen_prelude::*,
+	tokio::{
+		self,
+		io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader},
+		process::{Child, ChildStderr, ChildStdin, ChildStdout, Command},
+		sync::{Notify, mpsc, oneshot},
+		time,
+	},
+};
+use napi_derive::napi;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::{ps, task};
+
+const DEFAULT_START_TIMEOUT_MS: u32 = 30_000;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+const KILL_GRACE: Duration = Duration::from_secs(1);
+/// Server stderr lines kept for error messages.
+const STDERR_LINES: usize = 20;
+const STDERR_DRAIN: Duration = Duration::from_millis(200);
+
+static SERVERS: LazyLock<Mutex<HashMap<u32, Arc<Server>>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_SERVER_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Options for `lspStart`.
+#[napi(object)]
+pub struct LspStartOptions {
+	/// Server executable.
+	pub command:                String,
+	/// Server arguments.
+	pub args:                   Option<Vec<String>>,
+	/// Workspace root, also the server's working directory.
+	#[napi(js_name = "rootPath")]
+	pub root_path:              String,
+	/// Environment variables to add for the server.
+	pub env:                    Option<HashMap<String, String>>,
+	/// Server-specific `initializationOptions`.
+	#[napi(js_name = "initializationOptions", ts_type = "unknown")]
+	pub initialization_options: Option<Value>,
+	/// Settings sent with `workspace/didChangeConfiguration` and used to
+	/// answer `workspace/configuration`, keyed by section.
+	#[napi(ts_type = "Record<string, unknown>")]
+	pub settings:               Option<Value>,
+	/// Timeout in milliseconds for the server to answer `initialize`
+	/// (default: 30000).
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:             Option<u32>,
+	/// Re-sync open files when they are saved to disk (default: true).
+	pub watch:                  Option<bool>,
+}
+
+/// A started language server.
+#[napi(object)]
+pub struct LspServerInfo {
+	/// Id for the other `lsp*` calls.
+	pub id:           u32,
+	/// Server process id.
+	pub pid:          Option<u32>,
+	/// Name the server reported.
+	pub name:         Option<String>,
+	/// Version the server reported.
+	pub version:      Option<String>,
+	/// Capabilities the server reported.
+	#[napi(ts_type = "Record<string, unknown>")]
+	pub capabilities: Value,
+}
+
+/// Position in a document.
+#[napi(object)]
+#[derive(Clone, Copy)]
+pub struct LspPosition {
+	/// 0-based line.
+	pub line:      u32,
+	/// 0-based offset in UTF-16 code units.
+	pub character: u32,
+}
+
+/// Range in a document; `end` is exclusive.
+#[napi(object)]
+pub struct LspRange {
+	pub start: LspPosition,
+	pub end:   LspPosition,
+}
+
+/// Range in a file.
+#[napi(object)]
+pub struct LspLocation {
+	/// Absolute path of the file.
+	pub path:  String,
+	pub range: LspRange,
+}
+
+/// Result of `lspHover`.
+#[napi(object)]
+pub struct LspHover {
+	/// Hover text, usually Markdown.
+	pub contents: String,
+	/// Range the hover applies to.
+	pub range:    Option<LspRange>,
+}
+
+/// Replacement of a range of text.
+#[napi(object)]
+pub struct LspTextEdit {
+	pub range:    LspRange,
+	#[napi(js_name = "newText")]
+	pub new_text: String,
+}
+
+/// Edits to one file.
+#[napi(object)]
+pub struct LspFileEdit {
+	/// Absolute path of the file.
+	pub path:  String,
+	/// Edits, all relative to the current content of the file.
+	pub edits: Vec<LspTextEdit>,
+}
+
+/// File created, renamed or deleted by a workspace edit.
+#[napi(object)]
+pub struct LspFileOperation {
+	#[napi(ts_type = "\"create\" | \"rename\" | \"delete\"")]
+	pub kind:     String,
+	/// Absolute path of the file (the old path for renames).
+	pub path:     String,
+	/// New path, for renames.
+	#[napi(js_name = "newPath")]
+	pub new_path: Option<String>,
+}
+
+/// Changes computed by the server; not applied.
+#[napi(object)]
+pub struct LspWorkspaceEdit {
+	pub files:      Vec<LspFileEdit>,
+	/// File operations, in the order the server listed them.
+	pub operations: Vec<LspFileOperation>,
+}
+
+/// Diagnostic published by the server.
+#[napi(object)]
+#[derive(Clone)]
+pub struct LspDiagnostic {
+	/// Absolute path of the file.
+	pub path:     String,
+	pub range:    LspRange,
+	#[napi(ts_type = "\"error\" | \"warning\" | \"information\" | \"hint\"")]
+	pub severity: String,
+	pub message:  String,
+	/// Tool that produced the diagnostic, e.g. `rustc` or `ts`.
+	pub source:   Option<String>,
+	pub code:     Option<String>,
+}
+
+/// Options for `lspOpenFile`.
+#[napi(object)]
+#[derive(Default)]
+pub struct LspOpenOptions {
+	/// Content to sync instead of the file on disk.
+	pub text:        Option<String>,
+	/// Language id (default: derived from the file extension).
+	#[napi(js_name = "languageId")]
+	pub language_id: Option<String>,
+}
+
+/// Options for `lspDiagnostics`.
+#[napi(object)]
+#[derive(Default)]
+pub struct LspDiagnosticsOptions {
+	/// Only this file's diagnostics, opening it if needed (default: all
+	/// files).
+	pub path:    Option<String>,
+	/// Wait up to this many milliseconds for the server to publish
+	/// diagnostics for the latest content of `path`.
+	#[napi(js_name = "waitMs")]
+	pub wait_ms: Option<u32>,
+}
+
+impl Clone for LspRange {
+	fn clone(&self) -> Self {
+		Self { start: self.start, end: self.end }
+	}
+}
+
+/// An open document.
+struct Document {
+	version: i32,
+	text:    String,
+	/// Sync sequence number of the last change sent.
+	synced:  u64,
+}
+
+/// Diagnostics last published for a file.
+struct Published {
+	diagnostics: Vec<LspDiagnostic>,
+	/// Document version they were computed for, when the server says.
+	version:     Option<i64>,
+	/// Sync sequence number when they arrived.
+	seq:         u64,
+}
+
+/// Directories of open documents being watched.
+struct Watch {
+	watcher: RecommendedWatcher,
+	dirs:    HashMap<PathBuf, usize>,
+}
+
+type Reply = std::result::Result<Value, String>;
+
+struct Server {
+	pid:          Option<u32>,
+	root:         PathBuf,
+	settings:     Option<Value>,
+	stdin:        tokio::sync::Mutex<ChildStdin>,
+	child:        tokio::sync::Mutex<Child>,
+	next_request: AtomicI64,
+	pending:      Mutex<HashMap<i64, oneshot::Sender<Reply>>>,
+	documents:    Mutex<HashMap<PathBuf, Document>>,
+	sync_seq:     AtomicU64,
+	diagnostics:  Mutex<HashMap<PathBuf, Published>>,
+	published:    Notify,
+	stderr:       Mutex<VecDeque<String>>,
+	exited:       AtomicBool,
+	watch:        Mutex<Option<Watch>>,
+}
+
+fn io_error(err: io::Error) -> Error {
+	Error::from_reason(format!("Language server I/O failed: {err}"))
+}
+
+fn file_uri(path: &Path) -> Result<String> {
+	Url::from_file_path(path)
+		.map(String::from)
+		.map_err(|()| Error::from_reason(format!("Invalid file path: {}", path.display())))
+}
+
+/// Path of a `file:` URI, or the URI itself for other schemes.
+fn uri_path(uri: &str) -> String {
+	Url::parse(uri)
+		.ok()
+		.and_then(|url| url.to_file_path().ok())
+		.map_or_else(|| uri.to_string(), |path| path.to_string_lossy().into_owned())
+}
+
+/// LSP language id for a file extension.
+fn language_id(path: &Path) -> String {
+	let ext = path
+		.extension()
+		.and_then(|ext| ext.to_str())
+		.unwrap_or_default()
+		.to_ascii_lowercase();
+	let id = match ext.as_str() {
+		"rs" => "rust",
+		"ts" | "mts" | "cts" => "typescript",
+		"tsx" => "typescriptreact",
+		"js" | "mjs" | "cjs" => "javascript",
+		"jsx" => "javascriptreact",
+		"py" | "pyi" => "python",
+		"c" | "h" => "c",
+		"cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => "cpp",
+		"cs" => "csharp",
+		"rb" => "ruby",
+		"sh" | "bash" | "zsh" => "shellscript",
+		"md" => "markdown",
+		"yml" => "yaml",
+		"kt" | "kts" => "kotlin",
+		"ex" | "exs" => "elixir",
+		"hs" => "haskell",
+		"ml" => "ocaml",
+		_ => return ext,
+	};
+	id.to_string()
+}
+
+fn position(value: &Value) -> Option<LspPosition> {
+	Some(LspPosition {
+		line:      u32::try_from(value["line"].as_u64()?).ok()?,
+		character: u32::try_from(value["character"].as_u64()?).ok()?,
+	})
+}
+
+fn range(value: &Value) -> Option<LspRange> {
+	Some(LspRange { start: position(&value["start"])?, end: position(&value["end"])? })
+}
+
+fn position_params(uri: &str, at: LspPosition) -> Value {
+	json!({
+		"textDocument": { "uri": uri },
+		"position": { "line": at.line, "character": at.character },
+	})
+}
+
+/// `Location`, `Location[]` or `LocationLink[]`.
+fn locations(value: &Value) -> Vec<LspLocation> {
+	let items = match value {
+		Value::Array(items) => items.as_slice(),
+		Value::Null => &[],
+		single => std::slice::from_ref(single),
+	};
+	items
+		.iter()
+		.filter_map(|item| {
+			let uri = item
+				.get("targetUri")
+				.or_else(|| item.get("uri"))?
+				.as_str()?;
+			let range = range(
+				item
+					.get("targetSelectionRange")
+					.or_else(|| item.get("range"))?,
+			)?;
+			Some(LspLocation { path: uri_path(uri), range })
+		})
+		.collect()
+}
+
+/// Hover contents as text: `MarkupContent`, `MarkedString` or a list of
+/// them.
+fn hover_text(contents: &Value) -> String {
+	match contents {
+		Value::String(text) => text.clone(),
+		Value::Array(items) => items
+			.iter()
+			.map(hover_text)
+			.filter(|text| !text.is_empty())
+			.collect::<Vec<_>>()
+			.join("\n\n"),
+		Value::Object(object) => match (object.get("language"), object.get("value")) {
+			(Some(Value::String(language)), Some(Value::String(value))) => {
+				format!("```{language}\n{value}\n```")
+			},
+			(_, Some(Value::String(value))) => value.clone(),
+			_ => String::new(),
+		},
+		_ => String::new(),
+	}
+}
+
+fn text_edits(edits: &Value) -> Vec<LspTextEdit> {
+	edits
+		.as_array()
+		.into_iter()
+		.flatten()
+		.filter_map(|edit| {
+			Some(LspTextEdit {
+				range:    range(&edit["range"])?,
+				new_text: edit["newText"].as_str()?.to_string(),
+			})
+		})
+		.collect()
+}
+
+fn workspace_edit(value: &Value) -> LspWorkspaceEdit {
+	let mut edit = LspWorkspaceEdit { files: Vec::new(), operations: Vec::new() };
+	if let Some(changes) = value["documentChanges"].as_array() {
+		for change in changes {
+			let operation = |kind: &str, uri: &Value, new_uri: Option<&Value>| LspFileOperation {
+				kind:     kind.to_string(),
+				path:     uri_path(uri.as_str().unwrap_or_default()),
+				new_path: new_uri.and_then(Value::as_str).map(uri_path),
+			};
+			match change["kind"].as_str() {
+				Some(kind @ ("create" | "delete")) => {
+					edit.operations.push(operation(kind, &change["uri"], None));
+				},
+				Some("rename") => {
+					edit.operations.push(operation(
+						"rename",
+						&change["oldUri"],
+						Some(&change["newUri"]),
+					));
+				},
+				_ => {
+					if let Some(uri) = change["textDocument"]["uri"].as_str() {
+						edit.files.push(LspFileEdit {
+							path:  uri_path(uri),
+							edits: text_edits(&change["edits"]),
+						});
+					}
+				},
+			}
+		}
+	} else if let Some(changes) = value["changes"].as_object() {
+		for (uri, edits) in changes {
+			edit
+				.files
+				.push(LspFileEdit { path: uri_path(uri), edits: text_edits(edits) });
+		}
+	}
+	edit
+}
+
+fn diagnostic(path: &str, value: &Value) -> Option<LspDiagnostic> {
+	let severity = match value["severity"].as_u64() {
+		Some(2) => "warning",
+		Some(3) => "information",
+		Some(4) => "hint",
+		_ => "error",
+	};
+	Some(LspDiagnostic {
+		path:     path.to_string(),
+		range:    range(&value["range"])?,
+		severity: severity.to_string(),
+		message:  value["message"].as_str()?.to_string(),
+		source:   value["source"].as_str().map(str::to_string),
+		code:     match &value["code"] {
+			Value::String(code) => Some(code.clone()),
+			Value::Number(code) => Some(code.to_string()),
+			_ => None,
+		},
+	})
+}
+
+/// Read one `Content-Length` framed message; `None` at end of output.
+async fn read_message(reader: &mut BufReader<ChildStdout>) -> io::Result<Option<Vec<u8>>> {
+	let mut length = None;
+	let mut header = String::new();
+	loop {
+		header.clear();
+		if reader.read_line(&mut header).await? == 0 {
+			return Ok(None);
+		}
+		let header = header.trim_end();
+		if header.is_empty() {
+			if let Some(length) = length {
+				let mut body = vec![0; length];
+				reader.read_exact(&mut body).await?;
+				return Ok(Some(body));
+			}
+			continue;
+		}
+		if let Some((name, value)) = header.split_once(':')
+			&& name.eq_ignore_ascii_case("content-length")
+		{
+			length = value.trim().parse().ok();
+		}
+	}
+}
+
+async fn read_messages(
+	server: Arc<Server>,
+	stdout: ChildStdout,
+	stderr: tokio::task::JoinHandle<()>,
+) {
+	let mut reader = BufReader::new(stdout);
+	while let Ok(Some(body)) = read_message(&mut reader).await {
+		if let Ok(message) = serde_json::from_slice::<Value>(&body) {
+			server.dispatch(message).await;
+		}
+	}
+	// Let the last stderr lines in for the exit error.
+	let _ = time::timeout(STDERR_DRAIN, stderr).await;
+	server.exited.store(true, Ordering::SeqCst);
+	// Dropping the senders fails the requests still waiting.
+	server.pending.lock().clear();
+	server.published.notify_waiters();
+}
+
+async fn read_stderr(server: Arc<Server>, stderr: ChildStderr) {
+	let mut lines = BufReader::new(stderr).lines();
+	while let Ok(Some(line)) = lines.next_line().await {
+		let mut tail = server.stderr.lock();
+		if tail.len() == STDERR_LINES {
+			tail.pop_front();
+		}
+		tail.push_back(line);
+	}
+}
+
+/// Re-sync open documents saved to disk.
+async fn resync_saved(server: Weak<Server>, mut saved: mpsc::UnboundedReceiver<PathBuf>) {
+	while let Some(path) = saved.recv().await {
+		let Some(server) = server.upgrade() else {
+			break;
+		};
+		if !server.documents.lock().contains_key(&path) {
+			continue;
+		}
+		let Ok(text) = tokio::fs::read_to_string(&path).await else {
+			continue;
+		};
+		if matches!(server.sync(&path, text, None).await, Ok(true))
+			&& let Ok(uri) = file_uri(&path)
+		{
+			let _ = server
+				.notify("textDocument/didSave", json!({ "textDocument": { "uri": uri } }))
+				.await;
+		}
+	}
+}
+
+impl Server {
+	fn resolve(&self, path: &str) -> PathBuf {
+		let path = Path::new(path);
+		if path.is_absolute() {
+			path.to_path_buf()
+		} else {
+			self.root.join(path)
+		}
+	}
+
+	fn exit_error(&self) -> Error {
+		let stderr = self.stderr.lock();
+		if stderr.is_empty() {
+			Error::from_reason("Language server exited")
+		} else {
+			let tail = stderr.iter().cloned().collect::<Vec<_>>().join("\n");
+			Error::from_reason(format!("Language server exited:\n{tail}"))
+		}
+	}
+
+	async fn send(&self, message: &Value) -> Result<()> {
+		let body = message.to_string();
+		let mut stdin = self.stdin.lock().await;
+		stdin
+			.write_all(format!("Content-Length: {}\r\n\r\n{body}", body.len()).as_bytes())
+			.await
+			.map_err(io_error)?;
+		stdin.flush().await.map_err(io_error)
+	}
+
+	async fn notify(&self, method: &str, params: Value) -> Result<()> {
+		self
+			.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+			.await
+	}
+
+	async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+		if self.exited.load(Ordering::SeqCst) {
+			return Err(self.exit_error());
+		}
+		let id = self.next_request.fetch_add(1, Ordering::Relaxed);
+		let (reply, response) = oneshot::channel();
+		self.pending.lock().insert(id, reply);
+		let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+		if let Err(err) = self.send(&message).await {
+			self.pending.lock().remove(&id);
+			return Err(err);
+		}
+		match time::timeout(timeout, response).await {
+			Ok(Ok(Ok(result))) => Ok(result),
+			Ok(Ok(Err(message))) => Err(Error::from_reason(format!("{method} failed: {message}"))),
+			Ok(Err(_)) => Err(self.exit_error()),
+			Err(_) => {
+				self.pending.lock().remove(&id);
+				let _ = self.notify("$/cancelRequest", json!({ "id": id })).await;
+				Err(Error::from_reason(format!("{method} timed out after {}ms", timeout.as_millis())))
+			},
+		}
+	}
+
+	async fn dispatch(&self, message: Value) {
+		let method = message["method"].as_str();
+		match (message.get("id"), method) {
+			(Some(id), None) => {
+				let Some(reply) = id.as_i64().and_then(|id| self.pending.lock().remove(&id)) else {
+					return;
+				};
+				let result = match message.get("error") {
+					Some(error) => Err(
+						error["message"]
+							.as_str()
+							.unwrap_or("unknown error")
+							.to_string(),
+					),
+					None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+				};
+				let _ = reply.send(result);
+			},
+			(Some(id), Some(method)) => {
+				let response = match self.answer(method, &message["params"]) {
+					Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+					None => json!({
+						"jsonrpc": "2.0",
+						"id": id,
+						"error": { "code": -32601, "message": format!("Unsupported request: {method}") },
+					}),
+				};
+				let _ = self.send(&response).await;
+			},
+			(None, Some("textDocument/publishDiagnostics")) => self.publish(&message["params"]),
+			_ => {},
+		}
+	}
+
+	/// Result for a request from the server, or `None` if unsupported.
+	fn answer(&self, method: &str, params: &Value) -> Option<Value> {
+		match method {
+			"workspace/configuration" => {
+				let items = params["items"].as_array()?;
+				Some(Value::Array(
+					items
+						.iter()
+						.map(|item| self.setting(item["section"].as_str()))
+						.collect(),
+				))
+			},
+			"workspace/workspaceFolders" => Some(self.workspace_folders()),
+			"workspace/applyEdit" => Some(json!({ "applied": false })),
+			"window/workDoneProgress/create"
+			| "window/showMessageRequest"
+			| "client/registerCapability"
+			| "client/unregisterCapability" => Some(Value::Null),
+			_ => None,
+		}
+	}
+
+	/// Setting at a dotted `section` path.
+	fn setting(&self, section: Option<&str>) -> Value {
+		let Some(settings) = &self.settings else {
+			return Value::Null;
+		};
+		section
+			.into_iter()
+			.flat_map(|section| section.split('.'))
+			.try_fold(settings, |value, key| value.get(key))
+			.cloned()
+			.unwrap_or(Value::Null)
+	}
+
+	fn workspace_folders(&self) -> Value {
+		let name = self
+			.root
+			.file_name()
+			.map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+		json!([{ "uri": file_uri(&self.root).unwrap_or_default(), "name": name }])
+	}
+
+	fn publish(&self, params: &Value) {
+		let Some(uri) = params["uri"].as_str() else {
+			return;
+		};
+		let path = uri_path(uri);
+		let diagnostics = params["diagnostics"]
+			.as_array()
+			.into_iter()
+			.flatten()
+			.filter_map(|value| diagnostic(&path, value))
+			.collect();
+		let published = Published {
+			diagnostics,
+			version: params["version"].as_i64(),
+			seq: self.sync_seq.load(Ordering::SeqCst),
+		};
+		self
+			.diagnostics
+			.lock()
+			.insert(PathBuf::from(path), published);
+		self.published.notify_waiters();
+	}
+
+	fn watch_dir(&self, path: &Path, add: bool) {
+		let mut watch = self.watch.lock();
+		let (Some(dir), Some(watch)) = (path.parent(), watch.as_mut()) else {
+			return;
+		};
+		let count = watch.dirs.entry(dir.to_path_buf()).or_default();
+		if add {
+			if *count == 0 {
+				let _ = watch.watcher.watch(dir, RecursiveMode::NonRecursive);
+			}
+			*count += 1;
+		} else if *count <= 1 {
+			watch.dirs.remove(dir);
+			let _ = watch.watcher.unwatch(dir);
+		} else {
+			*count -= 1;
+		}
+	}
+
+	/// Open `path` with `text`, or send it as a change if already open.
+	/// Returns whether anything was sent.
+	async fn sync(&self, path: &Path, text: String, language: Option<String>) -> Result<bool> {
+		let uri = file_uri(path)?;
+		let seq = self.sync_seq.fetch_add(1, Ordering::SeqCst) + 1;
+		let version = {
+			let mut documents = self.documents.lock();
+			match documents.get_mut(path) {
+				Some(document) if document.text == text => return Ok(false),
+				Some(document) => {
+					document.version += 1;
+					document.text.clone_from(&text);
+					document.synced = seq;
+					Some(document.version)
+				},
+				None => {
+					documents.insert(path.to_path_buf(), Document {
+						version: 1,
+						text:    text.clone(),
+						synced:  seq,
+					});
+					None
+				},
+			}
+		};
+		if let Some(version) = version {
+			self
+				.notify(
+					"textDocument/didChange",
+					json!({
+						"textDocument": { "uri": uri, "version": version },
+						"contentChanges": [{ "text": text }],
+					}),
+				)
+				.await?;
+		} else {
+			self.watch_dir(path, true);
+			let language = language.unwrap_or_else(|| language_id(path));
+			self
+				.notify(
+					"textDocument/didOpen",
+					json!({
+						"textDocument": { "uri": uri, "languageId": language, "version": 1, "text": text },
+					}),
+				)
+				.await?;
+		}
+		Ok(true)
+	}
+
+	/// Open `path` from disk unless already open; returns its URI.
+	async fn ensure_open(&self, path: &Path) -> Result<String> {
+		if !self.documents.lock().contains_key(path) {
+			let text = tokio::fs::read_to_string(path).await.map_err(|err| {
+				Error::from_reason(format!("Failed to read {}: {err}", path.display()))
+			})?;
+			self.sync(path, text, None).await?;
+		}
+		file_uri(path)
+	}
+
+	async fn close(&self, path: &Path) -> Result<()> {
+		if self.documents.lock().remove(path).is_none() {
+			return Ok(());
+		}
+		self.watch_dir(path, false);
+		let uri = file_uri(path)?;
+		self
+			.notify("textDocument/didClose", json!({ "textDocument": { "uri": uri } }))
+			.await
+	}
+
+	/// Whether diagnostics published for `path` cover its latest content.
+	fn fresh(&self, path: &Path) -> bool {
+		let synced = self
+			.documents
+			.lock()
+			.get(path)
+			.map(|document| (document.version, document.synced));
+		let diagnostics = self.diagnostics.lock();
+		match (diagnostics.get(path), synced) {
+			(None, _) => false,
+			(Some(_), None) => true,
+			(Some(published), Some((version, seq))) => published
+				.version
+				.map_or(published.seq >= seq, |published| published >= i64::from(version)),
+		}
+	}
+
+	async fn wait_for_diagnostics(&self, path: &Path, wait: Duration) {
+		let deadline = time::Instant::now() + wait;
+		loop {
+			let notified = self.published.notified();
+			tokio::pin!(notified);
+			notified.as_mut().enable();
+			if self.exited.load(Ordering::SeqCst) || self.fresh(path) {
+				return;
+			}
+			if time::timeout_at(deadline, notified).await.is_err() {
+				return;
+			}
+		}
+	}
+
+	async fn stop(&self) {
+		self.watch.lock().take();
+		if !self.exited.load(Ordering::SeqCst) {
+			let _ = self
+				.request("shutdown", Value::Null, SHUTDOWN_TIMEOUT)
+				.await;
+			let _ = self.notify("exit", Value::Null).await;
+		}
+		let mut child = self.child.lock().await;
+		if time::timeout(SHUTDOWN_TIMEOUT, child.wait()).await.is_err() {
+			if let Some(pid) = self.pid {
+				ps::terminate_tree(pid as i32, KILL_GRACE).await;
+			}
+			let _ = child.kill().await;
+		}
+		// Helpers the server started in its group outlive it otherwise.
+		#[cfg(unix)]
+		if let Some(pid) = self.pid {
+			ps::kill_process_group(pid as i32, libc::SIGKILL);
+		}
+	}
+}
+
+fn server(id: u32) -> Result<Arc<Server>> {
+	SERVERS
+		.lock()
+		.get(&id)
+		.cloned()
+		.ok_or_else(|| Error::from_reason(format!("Unknown language server: {id}")))
+}
+
+async fn start_server(options: LspStartOptions) -> Result<LspServerInfo> {
+	let root = PathBuf::from(&options.root_path);
+	let mut cmd = Command::new(&options.command);
+	cmd.args(options.args.iter().flatten())
+		.current_dir(&root)
+		.envs(options.env.iter().flatten())
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.kill_on_drop(true);
+	#[cfg(unix)]
+	cmd.process_group(0);
+	let mut child = cmd
+		.spawn()
+		.map_err(|err| Error::from_reason(format!("Failed to start {}: {err}", options.command)))?;
+	let (Some(stdin), Some(stdout), Some(stderr)) =
+		(child.stdin.take(), child.stdout.take(), child.stderr.take())
+	else {
+		return Err(Error::from_reason("Failed to open language server pipes"));
+	};
+
+	let (saved_tx, saved_rx) = mpsc::unbounded_channel();
+	let watch = if options.watch.unwrap_or(true) {
+		notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+			if let Ok(event) = event
+				&& matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+			{
+				for path in event.paths {
+					let _ = saved_tx.send(path);
+				}
+			}
+		})
+		.ok()
+		.map(|watcher| Watch { watcher, dirs: HashMap::new() })
+	} else {
+		None
+	};
+
+	let server = Arc::new(Server {
+		pid: child.id(),
+		root,
+		settings: options.settings,
+		stdin: tokio::sync::Mutex::new(stdin),
+		child: tokio::sync::Mutex::new(child),
+		next_request: AtomicI64::new(1),
+		pending: Mutex::default(),
+		documents: Mutex::default(),
+		sync_seq: AtomicU64::new(0),
+		diagnostics: Mutex::default(),
+		published: Notify::new(),
+		stderr: Mutex::default(),
+		exited: AtomicBool::new(false),
+		watch: Mutex::new(watch),
+	});
+	let stderr = tokio::spawn(read_stderr(server.clone(), stderr));
+	tokio::spawn(read_messages(server.clone(), stdout, stderr));
+	tokio::spawn(resync_saved(Arc::downgrade(&server), saved_rx));
+
+	let params = json!({
+		"processId": std::process::id(),
+		"clientInfo": { "name": "omp" },
+		"rootPath": server.root.to_string_lossy(),
+		"rootUri": file_uri(&server.root)?,
+		"workspaceFolders": server.workspace_folders(),
+		"initializationOptions": options.initialization_options,
+		"capabilities": {
+			"general": { "positionEncodings": ["utf-16"] },
+			"textDocument": {
+				"synchronization": { "didSave": true, "dynamicRegistration": false },
+				"hover": { "contentFormat": ["markdown", "plaintext"] },
+				"definition": { "linkSupport": true },
+				"references": {},
+				"rename": { "prepareSupport": false },
+				"publishDiagnostics": { "versionSupport": true },
+			},
+			"workspace": {
+				"configuration": true,
+				"workspaceFolders": true,
+				"workspaceEdit": {
+					"documentChanges": true,
+					"resourceOperations": ["create", "rename", "delete"],
+				},
+			},
+		},
+	});
+	let timeout =
+		Duration::from_millis(u64::from(options.timeout_ms.unwrap_or(DEFAULT_START_TIMEOUT_MS)));
+	let init = match server.request("initialize", params, timeout).await {
+		Ok(init) => init,
+		Err(err) => {
+			server.stop().await;
+			return Err(err);
+		},
+	};
+	server.notify("initialized", json!({})).await?;
+	if let Some(settings) = &server.settings {
+		server
+			.notify("workspace/didChangeConfiguration", json!({ "settings": settings }))
+			.await?;
+	}
+
+	let id = NEXT_SERVER_ID.fetch_add(1, Ordering::Relaxed);
+	let info = LspServerInfo {
+		id,
+		pid: server.pid,
+		name: init["serverInfo"]["name"].as_str().map(str::to_string),
+		version: init["serverInfo"]["version"].as_str().map(str::to_string),
+		capabilities: init["capabilities"].clone(),
+	};
+	SERVERS.lock().insert(id, server);
+	Ok(info)
+}
+
+/// Start a language server and complete the `initialize` handshake.
+///
+/// # Errors
+/// Returns an error if the server cannot be spawned, exits, or does not
+/// answer `initialize` within `timeoutMs`.
+#[napi(js_name = "lspStart")]
+pub fn lsp_start(env: &Env, options: LspStartOptions) -> Result<PromiseRaw<'_, LspServerInfo>> {
+	task::future(env, "lsp.start", start_server(options))
+}
+
+/// Shut a language server down, killing its processes if it does not exit
+/// in time. Unknown ids are ignored.
+#[napi(js_name = "lspStop")]
+pub fn lsp_stop(env: &Env, id: u32) -> Result<PromiseRaw<'_, ()>> {
+	let server = SERVERS.lock().remove(&id);
+	task::future(env, "lsp.stop", async move {
+		if let Some(server) = server {
+			server.stop().await;
+		}
+		Ok(())
+	})
+}
+
+/// Open a file on the server, or sync its new content if already open.
+///
+/// # Errors
+/// Returns an error for unknown ids, unreadable files, or if the server
+/// exited.
+#[napi(js_name = "lspOpenFile")]
+pub fn lsp_open_file(
+	env: &Env,
+	id: u32,
+	path: String,
+	options: Option<LspOpenOptions>,
+) -> Result<PromiseRaw<'_, ()>> {
+	let server = server(id)?;
+	let options = options.unwrap_or_default();
+	task::future(env, "lsp.open_file", async move {
+		let path = server.resolve(&path);
+		let text = match options.text {
+			Some(text) => text,
+			None => tokio::fs::read_to_string(&path).await.map_err(|err| {
+				Error::from_reason(format!("Failed to read {}: {err}", path.display()))
+			})?,
+		};
+		server.sync(&path, text, options.language_id).await?;
+		Ok(())
+	})
+}
+
+/// Close a file on the server.
+///
+/// # Errors
+/// Returns an error for unknown ids or if the server exited.
+#[napi(js_name = "lspCloseFile")]
+pub fn lsp_close_file(env: &Env, id: u32, path: String) -> Result<PromiseRaw<'_, ()>> {
+	let server = server(id)?;
+	task::future(env, "lsp.close_file", async move { server.close(&server.resolve(&path)).await })
+}
+
+/// Locations where the symbol at `position` is defined.
+///
+/// # Errors
+/// Returns an error for unknown ids, unreadable files, and failed or timed
+/// out requests.
+#[napi(js_name = "lspDefinition")]
+pub fn lsp_definition(
+	env: &Env,
+	id: u32,
+	path: String,
+	position: LspPosition,
+) -> Result<PromiseRaw<'_, Vec<LspLocation>>> {
+	let server = server(id)?;
+	task::future(env, "lsp.definition", async move {
+		let uri = server.ensure_open(&server.resolve(&path)).await?;
+		let result = server
+			.request("textDocument/definition", position_params(&uri, position), REQUEST_TIMEOUT)
+			.await?;
+		Ok(locations(&result))
+	})
+}
+
+/// Locations referencing the symbol at `position`.
+///
+/// # Errors
+/// Returns an error for unknown ids, unreadable files, and failed or timed
+/// out requests.
+#[napi(js_name = "lspReferences")]
+pub fn lsp_references(
+	env: &Env,
+	id: u32,
+	path: String,
+	position: LspPosition,
+	include_declaration: Option<bool>,
+) -> Result<PromiseRaw<'_, Vec<LspLocation>>> {
+	let server = server(id)?;
+	task::future(env, "lsp.references", async move {
+		let uri = server.ensure_open(&server.resolve(&path)).await?;
+		let mut params = position_params(&uri, position);
+		params["context"] = json!({ "includeDeclaration": include_declaration.unwrap_or(true) });
+		let result = server
+			.request("textDocument/references", params, REQUEST_TIMEOUT)
+			.await?;
+		Ok(locations(&result))
+	})
+}
+
+/// Hover information for the symbol at `position`, or `null` if there is
+/// none.
+///
+/// # Errors
+/// Returns an error for unknown ids, unreadable files, and failed or timed
+/// out requests.
+#[napi(js_name = "lspHover")]
+pub fn lsp_hover(
+	env: &Env,
+	id: u32,
+	path: String,
+	position: LspPosition,
+) -> Result<PromiseRaw<'_, Option<LspHover>>> {
+	let server = server(id)?;
+	task::future(env, "lsp.hover", async move {
+		let uri = server.ensure_open(&server.resolve(&path)).await?;
+		let result = server
+			.request("textDocument/hover", position_params(&uri, position), REQUEST_TIMEOUT)
+			.await?;
+		let contents = hover_text(&result["contents"]);
+		Ok((!contents.is_empty()).then(|| LspHover { contents, range: range(&result["range"]) }))
+	})
+}
+
+/// Edits renaming the symbol at `position` across the workspace. The edits
+/// are returned, not applied.
+///
+/// # Errors
+/// Returns an error for unknown ids, unreadable files, and failed or timed
+/// out requests, including symbols the server cannot rename.
+#[napi(js_name = "lspRename")]
+pub fn lsp_rename(
+	env: &Env,
+	id: u32,
+	path: String,
+	position: LspPosition,
+	new_name: String,
+) -> Result<PromiseRaw<'_, LspWorkspaceEdit>> {
+	let server = server(id)?;
+	task::future(env, "lsp.rename", async move {
+		let uri = server.ensure_open(&server.resolve(&path)).await?;
+		let mut params = position_params(&uri, position);
+		params["newName"] = Value::String(new_name);
+		let result = server
+			.request("textDocument/rename", params, REQUEST_TIMEOUT)
+			.await?;
+		Ok(workspace_edit(&result))
+	})
+}
+
+/// Diagnostics the server published, for one file or all files, sorted by
+/// path.
+///
+/// # Errors
+/// Returns an error for unknown ids, or if `path` cannot be read or opened.
+#[napi(js_name = "lspDiagnostics")]
+pub fn lsp_diagnostics(
+	env: &Env,
+	id: u32,
+	options: Option<LspDiagnosticsOptions>,
+) -> Result<PromiseRaw<'_, Vec<LspDiagnostic>>> {
+	let server = server(id)?;
+	let options = options.unwrap_or_default();
+	task::future(env, "lsp.diagnostics", async move {
+		let Some(path) = options.path else {
+			let diagnostics = server.diagnostics.lock();
+			let mut paths = diagnostics.keys().collect::<Vec<_>>();
+			paths.sort();
+			return Ok(paths
+				.into_iter()
+				.flat_map(|path| diagnostics[path].diagnostics.iter().cloned())
+				.collect());
+		};
+		let path = server.resolve(&path);
+		server.ensure_open(&path).await?;
+		if let Some(wait_ms) = options.wait_ms {
+			server
+				.wait_for_diagnostics(&path, Duration::from_millis(u64::from(wait_ms)))
+				.await;
+		}
+		Ok(server
+			.diagnostics
+			.lock()
+			.get(&path)
+			.map_or_else(Vec::new, |published| published.diagnostics.clone()))
+	})
+}
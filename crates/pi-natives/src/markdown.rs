@@ -0,0 +1,1113 @@
+//! Markdown rendering for the terminal.
+//!
+//! # Overview
+//! `renderMarkdown` renders CommonMark, with the GitHub extensions assistant
+//! replies use, as ANSI text wrapped to `width`, laid out like the TUI's
+//! `Markdown` component:
+//! - headings, paragraphs, block quotes and thematic breaks
+//! - bullet, ordered and task lists, nested by indentation
+//! - tables, with columns shrunk and cells wrapped to fit
+//! - fenced and indented code, highlighted with a syntect theme
+//! - emphasis, strong, strikethrough, code spans, links and images
+//!
+//! Links become OSC 8 hyperlinks with `hyperlinks`, and are otherwise
+//! followed by their URL. Raw HTML and reference links are kept as text.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const text = native.renderMarkdown(reply, { width: process.stdout.columns, hyperlinks: true })
+//! ```
+
+use std::fmt::Write as _;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use syntect::highlighting::Theme;
+
+use crate::{
+	highlight::{find_theme, highlight_ansi},
+	text::{visible_width_str, wrap_str_with_ansi},
+};
+
+const DEFAULT_WIDTH: u32 = 80;
+/// Rules are never drawn wider than this.
+const MAX_RULE_WIDTH: usize = 80;
+/// Code block lines are indented by this.
+const CODE_INDENT: &str = "  ";
+/// Longest word kept whole when shrinking table columns.
+const MAX_UNBROKEN_WORD: usize = 30;
+
+const RESET: &str = "\x1b[0m";
+const HEADING: &str = "\x1b[36m";
+const LINK: &str = "\x1b[34m";
+const CODE: &str = "\x1b[33m";
+const BULLET: &str = "\x1b[36m";
+const TASK_DONE: &str = "\x1b[32m";
+const MUTED: &str = "\x1b[90m";
+
+/// Options for `renderMarkdown`.
+#[napi(object)]
+#[derive(Default)]
+pub struct RenderMarkdownOptions {
+	/// Width to wrap to, in cells (default: 80).
+	pub width:      Option<u32>,
+	/// Theme for code blocks, from `getHighlightThemes` (default:
+	/// "base16-ocean.dark").
+	pub theme:      Option<String>,
+	/// Write links as OSC 8 hyperlinks instead of following them with their
+	/// URL (default: false).
+	pub hyperlinks: Option<bool>,
+}
+
+// ============================================================================
+// Blocks
+// ============================================================================
+
+#[derive(Clone, Copy)]
+enum Align {
+	Left,
+	Center,
+	Right,
+}
+
+struct ListItem {
+	/// Checkbox state, for task list items.
+	task:   Option<bool>,
+	blocks: Vec<Block>,
+}
+
+enum Block {
+	Heading {
+		level: usize,
+		text:  String,
+	},
+	/// Inline text, with `\n` for hard line breaks.
+	Paragraph(String),
+	Code {
+		lang: Option<String>,
+		text: String,
+	},
+	Quote(Vec<Self>),
+	List {
+		start: Option<u64>,
+		items: Vec<ListItem>,
+	},
+	Table {
+		aligns: Vec<Align>,
+		header: Vec<String>,
+		rows:   Vec<Vec<String>>,
+		raw:    Vec<String>,
+	},
+	Rule,
+}
+
+/// Leading spaces of a line.
+fn indent_of(line: &str) -> usize {
+	line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_blank(line: &str) -> bool {
+	line.trim().is_empty()
+}
+
+/// Drop up to `n` leading spaces.
+fn strip_indent(line: &str, n: usize) -> &str {
+	&line[indent_of(line).min(n)..]
+}
+
+/// Expand tabs in the indentation to four spaces.
+fn expand_indent(line: &str) -> String {
+	let body = line.trim_start_matches([' ', '\t']);
+	let mut out = String::with_capacity(line.len());
+	for c in line[..line.len() - body.len()].chars() {
+		if c == '\t' {
+			out.push_str(&" ".repeat(4 - out.len() % 4));
+		} else {
+			out.push(c);
+		}
+	}
+	out.push_str(body);
+	out
+}
+
+/// Opening code fence: fence character, fence length and info string.
+fn fence_open(line: &str) -> Option<(char, usize, &str)> {
+	if indent_of(line) > 3 {
+		return None;
+	}
+	let line = line.trim_start();
+	let c = line.chars().next().filter(|&c| c == '`' || c == '~')?;
+	let len = line.len() - line.trim_start_matches(c).len();
+	let info = line[len..].trim();
+	(len >= 3 && !(c == '`' && info.contains('`'))).then_some((c, len, info))
+}
+
+fn is_fence_close(line: &str, c: char, len: usize) -> bool {
+	if indent_of(line) > 3 {
+		return false;
+	}
+	let line = line.trim();
+	let run = line.len() - line.trim_start_matches(c).len();
+	run >= len && run == line.len()
+}
+
+fn heading_of(line: &str) -> Option<(usize, &str)> {
+	if indent_of(line) > 3 {
+		return None;
+	}
+	let line = line.trim_start();
+	let level = line.len() - line.trim_start_matches('#').len();
+	let rest = &line[level..];
+	if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+		return None;
+	}
+	let text = rest.trim();
+	// A closing sequence of `#`s must follow a space.
+	let without_closing = text.trim_end_matches('#');
+	let text = if without_closing.is_empty() || without_closing.ends_with(' ') {
+		without_closing.trim_end()
+	} else {
+		text
+	};
+	Some((level, text))
+}
+
+fn is_rule(line: &str) -> bool {
+	if indent_of(line) > 3 {
+		return false;
+	}
+	let mut chars = line.chars().filter(|c| !c.is_whitespace());
+	let Some(c) = chars.next().filter(|c| matches!(c, '-' | '*' | '_')) else {
+		return false;
+	};
+	let mut count = 1;
+	for other in chars {
+		if other != c {
+			return false;
+		}
+		count += 1;
+	}
+	count >= 3
+}
+
+fn is_quote(line: &str) -> bool {
+	indent_of(line) <= 3 && line.trim_start().starts_with('>')
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MarkerKind {
+	Bullet(char),
+	Ordered(char),
+}
+
+struct ListMarker {
+	kind:    MarkerKind,
+	indent:  usize,
+	/// Number of an ordered item.
+	number:  u64,
+	/// Column where the item's content starts.
+	content: usize,
+}
+
+fn list_marker(line: &str) -> Option<ListMarker> {
+	let indent = indent_of(line);
+	let rest = &line[indent..];
+	let first = rest.chars().next()?;
+	let (kind, number, marker_len) = if matches!(first, '-' | '*' | '+') {
+		(MarkerKind::Bullet(first), 0, 1)
+	} else {
+		let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+		let delimiter = rest[digits..].chars().next()?;
+		if !(1..=9).contains(&digits) || !matches!(delimiter, '.' | ')') {
+			return None;
+		}
+		(MarkerKind::Ordered(delimiter), rest[..digits].parse().ok()?, digits + 1)
+	};
+	let after = &rest[marker_len..];
+	let spaces = indent_of(after);
+	let content = if after.trim().is_empty() {
+		indent + marker_len + 1
+	} else if spaces == 0 {
+		return None;
+	} else if spaces > 4 {
+		// Content indented further is an indented code block.
+		indent + marker_len + 1
+	} else {
+		indent + marker_len + spaces
+	};
+	Some(ListMarker { kind, indent, number, content })
+}
+
+/// Cells of a table row, split on unescaped pipes outside code spans.
+fn split_row(line: &str) -> Vec<String> {
+	let line = line.trim();
+	let line = line.strip_prefix('|').unwrap_or(line);
+	let line = if line.ends_with('|') && !line.ends_with("\\|") {
+		&line[..line.len() - 1]
+	} else {
+		line
+	};
+	let mut cells = Vec::new();
+	let mut cell = String::new();
+	let mut in_code = false;
+	let mut chars = line.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' if chars.peek() == Some(&'|') => {
+				cell.push('|');
+				chars.next();
+			},
+			'`' => {
+				in_code = !in_code;
+				cell.push(c);
+			},
+			'|' if !in_code => cells.push(std::mem::take(&mut cell).trim().to_string()),
+			_ => cell.push(c),
+		}
+	}
+	cells.push(cell.trim().to_string());
+	cells
+}
+
+fn delimiter_row(line: &str) -> Option<Vec<Align>> {
+	if !line.contains('-') {
+		return None;
+	}
+	split_row(line)
+		.iter()
+		.map(|cell| {
+			let left = cell.starts_with(':');
+			let right = cell.ends_with(':');
+			let dashes = cell.trim_matches(':');
+			if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+				return None;
+			}
+			Some(match (left, right) {
+				(true, true) => Align::Center,
+				(false, true) => Align::Right,
+				_ => Align::Left,
+			})
+		})
+		.collect()
+}
+
+/// Column alignments, when `lines[i]` starts a table.
+fn table_start(lines: &[String], i: usize) -> Option<Vec<Align>> {
+	let header = &lines[i];
+	if !header.contains('|') || indent_of(header) > 3 {
+		return None;
+	}
+	let aligns = delimiter_row(lines.get(i + 1)?)?;
+	(split_row(header).len() == aligns.len()).then_some(aligns)
+}
+
+/// Whether `line` starts a block that ends a paragraph.
+fn interrupts_paragraph(line: &str) -> bool {
+	heading_of(line).is_some()
+		|| fence_open(line).is_some()
+		|| is_rule(line)
+		|| is_quote(line)
+		|| list_marker(line).is_some_and(|marker| {
+			marker.indent <= 3 && !line[marker.content.min(line.len())..].trim().is_empty()
+		})
+}
+
+fn parse_blocks(lines: &[String]) -> Vec<Block> {
+	let mut blocks = Vec::new();
+	let mut i = 0;
+	while i < lines.len() {
+		let line = lines[i].as_str();
+		if is_blank(line) {
+			i += 1;
+		} else if indent_of(line) >= 4 {
+			let start = i;
+			while i < lines.len() && (is_blank(&lines[i]) || indent_of(&lines[i]) >= 4) {
+				i += 1;
+			}
+			let mut end = i;
+			while is_blank(&lines[end - 1]) {
+				end -= 1;
+			}
+			let code: Vec<&str> = lines[start..end]
+				.iter()
+				.map(|line| strip_indent(line, 4))
+				.collect();
+			blocks.push(Block::Code { lang: None, text: code.join("\n") });
+		} else if let Some((c, len, info)) = fence_open(line) {
+			let indent = indent_of(line);
+			let lang = info.split_whitespace().next().map(str::to_string);
+			i += 1;
+			let mut code = Vec::new();
+			while i < lines.len() && !is_fence_close(&lines[i], c, len) {
+				code.push(strip_indent(&lines[i], indent));
+				i += 1;
+			}
+			i += 1;
+			blocks.push(Block::Code { lang, text: code.join("\n") });
+		} else if let Some((level, text)) = heading_of(line) {
+			blocks.push(Block::Heading { level, text: text.to_string() });
+			i += 1;
+		} else if is_rule(line) {
+			blocks.push(Block::Rule);
+			i += 1;
+		} else if is_quote(line) {
+			let mut inner = Vec::new();
+			while i < lines.len() && !is_blank(&lines[i]) {
+				let line = &lines[i];
+				if is_quote(line) {
+					let body = &line.trim_start()[1..];
+					inner.push(body.strip_prefix(' ').unwrap_or(body).to_string());
+				} else if inner.last().is_some_and(|last| !is_blank(last))
+					&& !interrupts_paragraph(line)
+				{
+					// Lazy continuation of a quoted paragraph.
+					inner.push(line.trim_start().to_string());
+				} else {
+					break;
+				}
+				i += 1;
+			}
+			blocks.push(Block::Quote(parse_blocks(&inner)));
+		} else if let Some(marker) = list_marker(line).filter(|marker| marker.indent <= 3) {
+			let (block, next) = parse_list(lines, i, &marker);
+			blocks.push(block);
+			i = next;
+		} else if let Some(aligns) = table_start(lines, i) {
+			let header = split_row(line);
+			let mut raw = vec![line.to_string(), lines[i + 1].clone()];
+			let mut rows = Vec::new();
+			i += 2;
+			while i < lines.len() && !is_blank(&lines[i]) && !interrupts_paragraph(&lines[i]) {
+				let mut row = split_row(&lines[i]);
+				row.resize(aligns.len(), String::new());
+				rows.push(row);
+				raw.push(lines[i].clone());
+				i += 1;
+			}
+			blocks.push(Block::Table { aligns, header, rows, raw });
+		} else {
+			let mut text = String::new();
+			let mut level = None;
+			while i < lines.len() && !is_blank(&lines[i]) {
+				let line = lines[i].as_str();
+				if !text.is_empty() {
+					let underline = line.trim();
+					if indent_of(line) <= 3 && !underline.is_empty() {
+						if underline.chars().all(|c| c == '=') {
+							level = Some(1);
+						} else if underline.chars().all(|c| c == '-') {
+							level = Some(2);
+						}
+					}
+					if level.is_some() {
+						i += 1;
+						break;
+					}
+					if interrupts_paragraph(line) || table_start(lines, i).is_some() {
+						break;
+					}
+				}
+				push_paragraph_line(&mut text, line);
+				i += 1;
+			}
+			let text = text.trim_end_matches('\n').to_string();
+			blocks.push(match level {
+				Some(level) => Block::Heading { level, text },
+				None => Block::Paragraph(text),
+			});
+		}
+	}
+	blocks
+}
+
+/// Append a paragraph line, joined to the previous one by a space, or by
+/// `\n` after a hard break (two trailing spaces or a backslash).
+fn push_paragraph_line(text: &mut String, line: &str) {
+	if !text.is_empty() && !text.ends_with('\n') {
+		text.push(' ');
+	}
+	let line = line.trim_start();
+	let body = line.trim_end();
+	if let Some(body) = body.strip_suffix('\\').filter(|body| !body.ends_with('\\')) {
+		text.push_str(body);
+		text.push('\n');
+	} else {
+		text.push_str(body);
+		if line.len() - body.len() >= 2 && line[body.len()..].chars().all(|c| c == ' ') {
+			text.push('\n');
+		}
+	}
+}
+
+/// Parse the list starting at `lines[start]`, returning it and the index of
+/// the line after it.
+fn parse_list(lines: &[String], start: usize, first: &ListMarker) -> (Block, usize) {
+	// Markers indented to the content of the first item start a nested list.
+	let max_indent = first.content;
+	let mut items = Vec::new();
+	let mut i = start;
+	while let Some(marker) = lines.get(i).and_then(|line| list_marker(line)) {
+		if marker.kind != first.kind || marker.indent >= max_indent {
+			break;
+		}
+		let line = &lines[i];
+		let mut item_lines = vec![line.get(marker.content..).unwrap_or_default().to_string()];
+		i += 1;
+		while i < lines.len() {
+			let line = &lines[i];
+			if is_blank(line) {
+				let next = (i..lines.len()).find(|&j| !is_blank(&lines[j]));
+				if next.is_none_or(|j| indent_of(&lines[j]) < marker.content) {
+					break;
+				}
+				item_lines.push(String::new());
+			} else if indent_of(line) >= marker.content {
+				item_lines.push(line[marker.content..].to_string());
+			} else if list_marker(line).is_none()
+				&& item_lines.last().is_some_and(|last| !is_blank(last))
+				&& !interrupts_paragraph(line)
+			{
+				// Lazy continuation of the item's paragraph.
+				item_lines.push(line.trim_start().to_string());
+			} else {
+				break;
+			}
+			i += 1;
+		}
+
+		let first_line = &mut item_lines[0];
+		let task = ["[ ]", "[x]", "[X]"]
+			.into_iter()
+			.find(|box_| {
+				first_line.starts_with(box_)
+					&& first_line[box_.len()..]
+						.chars()
+						.next()
+						.is_none_or(char::is_whitespace)
+			})
+			.map(|box_| {
+				let checked = box_ != "[ ]";
+				*first_line = first_line[box_.len()..].trim_start().to_string();
+				checked
+			});
+		items.push(ListItem { task, blocks: parse_blocks(&item_lines) });
+
+		// Blank lines may separate items.
+		let next = (i..lines.len()).find(|&j| !is_blank(&lines[j]));
+		match next.and_then(|j| list_marker(&lines[j]).map(|marker| (j, marker))) {
+			Some((j, marker)) if marker.kind == first.kind && marker.indent < max_indent => i = j,
+			_ => break,
+		}
+	}
+	let start = matches!(first.kind, MarkerKind::Ordered(_)).then_some(first.number);
+	(Block::List { start, items }, i)
+}
+
+// ============================================================================
+// Inlines
+// ============================================================================
+
+/// SGR attributes of a run of text.
+#[derive(Clone, Copy, Default)]
+struct Style {
+	bold:      bool,
+	italic:    bool,
+	underline: bool,
+	strike:    bool,
+	fg:        Option<&'static str>,
+}
+
+impl Style {
+	fn write(self, out: &mut String) {
+		if self.bold {
+			out.push_str("\x1b[1m");
+		}
+		if self.italic {
+			out.push_str("\x1b[3m");
+		}
+		if self.underline {
+			out.push_str("\x1b[4m");
+		}
+		if self.strike {
+			out.push_str("\x1b[9m");
+		}
+		if let Some(fg) = self.fg {
+			out.push_str(fg);
+		}
+	}
+
+	const fn is_plain(self) -> bool {
+		!self.bold && !self.italic && !self.underline && !self.strike && self.fg.is_none()
+	}
+}
+
+/// Length of the run of `c` at `text[i..]`.
+fn run_len(text: &str, i: usize, c: u8) -> usize {
+	text.as_bytes()[i..].iter().take_while(|&&b| b == c).count()
+}
+
+/// End of the code span opening at `text[i..]`: the index after its closing
+/// backticks.
+fn code_span_end(text: &str, i: usize) -> Option<usize> {
+	let ticks = run_len(text, i, b'`');
+	let mut j = i + ticks;
+	while let Some(offset) = text[j..].find('`') {
+		let start = j + offset;
+		let run = run_len(text, start, b'`');
+		if run == ticks {
+			return Some(start + run);
+		}
+		j = start + run;
+	}
+	None
+}
+
+fn prev_char(text: &str, i: usize) -> Option<char> {
+	text[..i].chars().next_back()
+}
+
+fn next_char(text: &str, i: usize) -> Option<char> {
+	text[i..].chars().next()
+}
+
+/// Start of the closing run of exactly `len` × `c` for emphasis opened
+/// before `from`.
+fn find_closing(text: &str, from: usize, c: u8, len: usize) -> Option<usize> {
+	let bytes = text.as_bytes();
+	let mut i = from;
+	while i < bytes.len() {
+		match bytes[i] {
+			b'\\' => i += 2,
+			b'`' => i = code_span_end(text, i).unwrap_or_else(|| i + run_len(text, i, b'`')),
+			b if b == c => {
+				let run = run_len(text, i, c);
+				let after = next_char(text, i + run);
+				let closes = run == len
+					&& i > from
+					&& !prev_char(text, i).is_some_and(char::is_whitespace)
+					&& (c != b'_' || !after.is_some_and(char::is_alphanumeric));
+				if closes {
+					return Some(i);
+				}
+				i += run;
+			},
+			_ => i += 1,
+		}
+	}
+	None
+}
+
+struct Link<'a> {
+	label: &'a str,
+	url:   &'a str,
+	/// Index after the link.
+	end:   usize,
+}
+
+/// Inline link `[label](url "title")` at `text[i..]`.
+fn parse_link(text: &str, i: usize) -> Option<Link<'_>> {
+	let bytes = text.as_bytes();
+	let mut j = i + 1;
+	let mut depth = 0;
+	loop {
+		match *bytes.get(j)? {
+			b'\\' => j += 1,
+			b'`' => j = code_span_end(text, j)?.saturating_sub(1),
+			b'[' => depth += 1,
+			b']' if depth == 0 => break,
+			b']' => depth -= 1,
+			_ => {},
+		}
+		j += 1;
+	}
+	let label = &text[i + 1..j];
+	let rest = text[j + 1..].strip_prefix('(')?;
+	let rest_start = text.len() - rest.len();
+	let trimmed = rest.trim_start();
+	let url_start = rest_start + rest.len() - trimmed.len();
+	let (url, after_url) = if let Some(inner) = trimmed.strip_prefix('<') {
+		let close = inner.find('>')?;
+		(&inner[..close], url_start + 1 + close + 1)
+	} else {
+		let mut parens = 0;
+		let len = trimmed
+			.char_indices()
+			.find(|&(_, c)| match c {
+				'(' => {
+					parens += 1;
+					false
+				},
+				')' if parens == 0 => true,
+				')' => {
+					parens -= 1;
+					false
+				},
+				c => c.is_whitespace(),
+			})
+			.map_or(trimmed.len(), |(k, _)| k);
+		(&trimmed[..len], url_start + len)
+	};
+	// Skip an optional title.
+	let tail = text[after_url..].trim_start();
+	let tail = match tail.chars().next() {
+		Some(quote @ ('"' | '\'')) => {
+			let close = tail[1..].find(quote)?;
+			tail[close + 2..].trim_start()
+		},
+		_ => tail,
+	};
+	tail
+		.starts_with(')')
+		.then(|| Link { label, url, end: text.len() - tail.len() + 1 })
+}
+
+/// Autolink `<scheme:...>` or `<user@host>` at `text[i..]`: its URL and end.
+fn parse_autolink(text: &str, i: usize) -> Option<(String, usize)> {
+	let close = text[i..].find('>')?;
+	let inner = &text[i + 1..i + close];
+	if inner.is_empty() || inner.contains(|c: char| c.is_whitespace() || c == '<') {
+		return None;
+	}
+	let url = match inner.split_once(':') {
+		Some((scheme, _))
+			if scheme.len() >= 2
+				&& scheme
+					.chars()
+					.all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c)) =>
+		{
+			inner.to_string()
+		},
+		_ if inner.contains('@') && !inner.contains(':') => format!("mailto:{inner}"),
+		_ => return None,
+	};
+	Some((url, i + close + 1))
+}
+
+struct Renderer<'a> {
+	width:      usize,
+	theme:      &'a Theme,
+	hyperlinks: bool,
+}
+
+impl Renderer<'_> {
+	/// Render `text` in `style`, restoring `parent` after it.
+	fn styled(&self, text: &str, style: Style, parent: Style, out: &mut String) {
+		style.write(out);
+		self.inline(text, style, out);
+		out.push_str(RESET);
+		parent.write(out);
+	}
+
+	fn link(&self, label: &str, url: &str, style: Style, out: &mut String) {
+		let link_style = Style { underline: true, fg: Some(LINK), ..style };
+		if self.hyperlinks {
+			out.push_str("\x1b]8;;");
+			out.push_str(url);
+			out.push_str("\x1b\\");
+			self.styled(label, link_style, style, out);
+			out.push_str("\x1b]8;;\x1b\\");
+		} else {
+			self.styled(label, link_style, style, out);
+			if label != url && Some(label) != url.strip_prefix("mailto:") {
+				let url_style = Style { fg: Some(MUTED), ..style };
+				url_style.write(out);
+				out.push_str(" (");
+				out.push_str(url);
+				out.push(')');
+				out.push_str(RESET);
+				style.write(out);
+			}
+		}
+	}
+
+	/// Render inline markdown in `style`.
+	fn inline(&self, text: &str, style: Style, out: &mut String) {
+		let bytes = text.as_bytes();
+		let mut i = 0;
+		let mut plain = 0;
+		while i < bytes.len() {
+			let start = i;
+			match bytes[i] {
+				b'\\' if bytes.get(i + 1).is_some_and(u8::is_ascii_punctuation) => {
+					out.push_str(&text[plain..i]);
+					plain = i + 1;
+					i += 2;
+				},
+				b'`' => {
+					let ticks = run_len(text, i, b'`');
+					let Some(end) = code_span_end(text, i) else {
+						i += ticks;
+						continue;
+					};
+					let code = &text[i + ticks..end - ticks];
+					let code = if code.len() >= 2
+						&& code.starts_with(' ')
+						&& code.ends_with(' ')
+						&& !code.trim().is_empty()
+					{
+						&code[1..code.len() - 1]
+					} else {
+						code
+					};
+					out.push_str(&text[plain..start]);
+					Style { fg: Some(CODE), ..style }.write(out);
+					out.push_str(&code.replace('\n', " "));
+					out.push_str(RESET);
+					style.write(out);
+					i = end;
+					plain = i;
+				},
+				c @ (b'*' | b'_' | b'~') => {
+					let run = run_len(text, i, c);
+					let after = next_char(text, i + run);
+					let opens = (1..=3).contains(&run)
+						&& !(c == b'~' && run == 3)
+						&& after.is_some_and(|after| !after.is_whitespace())
+						&& (c != b'_' || !prev_char(text, i).is_some_and(char::is_alphanumeric));
+					let Some(close) = opens.then(|| find_closing(text, i + run, c, run)).flatten()
+					else {
+						i += run;
+						continue;
+					};
+					let inner = match (c, run) {
+						(b'~', _) => Style { strike: true, ..style },
+						(_, 1) => Style { italic: true, ..style },
+						(_, 2) => Style { bold: true, ..style },
+						_ => Style { bold: true, italic: true, ..style },
+					};
+					out.push_str(&text[plain..start]);
+					self.styled(&text[i + run..close], inner, style, out);
+					i = close + run;
+					plain = i;
+				},
+				b'!' if bytes.get(i + 1) == Some(&b'[') => {
+					let Some(link) = parse_link(text, i + 1) else {
+						i += 1;
+						continue;
+					};
+					out.push_str(&text[plain..start]);
+					let label = if link.label.is_empty() {
+						"image"
+					} else {
+						link.label
+					};
+					self.link(label, link.url, style, out);
+					i = link.end;
+					plain = i;
+				},
+				b'[' => {
+					let Some(link) = parse_link(text, i) else {
+						i += 1;
+						continue;
+					};
+					out.push_str(&text[plain..start]);
+					self.link(link.label, link.url, style, out);
+					i = link.end;
+					plain = i;
+				},
+				b'<' => {
+					let Some((url, end)) = parse_autolink(text, i) else {
+						i += 1;
+						continue;
+					};
+					out.push_str(&text[plain..start]);
+					self.link(&text[i + 1..end - 1], &url, style, out);
+					i = end;
+					plain = i;
+				},
+				_ => i += 1,
+			}
+		}
+		out.push_str(&text[plain..]);
+	}
+
+	/// Render inline markdown in `style` as a self-contained string.
+	fn inline_text(&self, text: &str, style: Style) -> String {
+		let mut out = String::new();
+		style.write(&mut out);
+		self.inline(text, style, &mut out);
+		if !style.is_plain() {
+			out.push_str(RESET);
+		}
+		out
+	}
+
+	// ========================================================================
+	// Blocks
+	// ========================================================================
+
+	fn blocks(&self, blocks: &[Block], width: usize, style: Style) -> Vec<String> {
+		let mut lines = Vec::new();
+		for (index, block) in blocks.iter().enumerate() {
+			// Lists hug the paragraph introducing them.
+			let tight = index > 0
+				&& matches!(block, Block::List { .. })
+				&& matches!(blocks[index - 1], Block::Paragraph(_));
+			if index > 0 && !tight {
+				lines.push(String::new());
+			}
+			self.block(block, width, style, &mut lines);
+		}
+		lines
+	}
+
+	fn block(&self, block: &Block, width: usize, style: Style, lines: &mut Vec<String>) {
+		match block {
+			Block::Heading { level, text } => {
+				let heading_style =
+					Style { bold: true, underline: *level == 1, fg: Some(HEADING), ..style };
+				let text = if *level >= 3 {
+					format!("{} {text}", "#".repeat(*level))
+				} else {
+					text.clone()
+				};
+				lines.extend(wrap_str_with_ansi(&self.inline_text(&text, heading_style), width));
+			},
+			Block::Paragraph(text) => {
+				lines.extend(wrap_str_with_ansi(&self.inline_text(text, style), width));
+			},
+			Block::Code { lang, text } => {
+				let lang = lang.as_deref().unwrap_or_default();
+				lines.push(format!("{MUTED}```{lang}{RESET}"));
+				if !text.is_empty() {
+					let code = highlight_ansi(text, (!lang.is_empty()).then_some(lang), self.theme);
+					lines.extend(code.split('\n').map(|line| format!("{CODE_INDENT}{line}")));
+				}
+				lines.push(format!("{MUTED}```{RESET}"));
+			},
+			Block::Quote(blocks) => {
+				let quote_style = Style { italic: true, ..style };
+				for line in self.blocks(blocks, width.saturating_sub(2).max(1), quote_style) {
+					if line.is_empty() {
+						lines.push(format!("{MUTED}│{RESET}"));
+					} else {
+						lines.push(format!("{MUTED}│{RESET} {line}"));
+					}
+				}
+			},
+			Block::List { start, items } => {
+				for (index, item) in items.iter().enumerate() {
+					let mut bullet = match start {
+						Some(start) => format!("{}. ", start + index as u64),
+						None => "- ".to_string(),
+					};
+					let marker_width = bullet.len();
+					bullet = format!("{BULLET}{bullet}{RESET}");
+					match item.task {
+						Some(true) => {
+							let _ = write!(bullet, "{TASK_DONE}[x]{RESET} ");
+						},
+						Some(false) => bullet.push_str("[ ] "),
+						None => {},
+					}
+					let indent = " ".repeat(marker_width);
+					let item_lines =
+						self.blocks(&item.blocks, width.saturating_sub(marker_width).max(1), style);
+					let mut item_lines = item_lines.into_iter();
+					lines.push(format!("{bullet}{}", item_lines.next().unwrap_or_default()));
+					lines.extend(item_lines.map(|line| {
+						if line.is_empty() {
+							line
+						} else {
+							format!("{indent}{line}")
+						}
+					}));
+				}
+			},
+			Block::Table { aligns, header, rows, raw } => {
+				self.table(aligns, header, rows, raw, width, style, lines);
+			},
+			Block::Rule => {
+				lines.push(format!("{MUTED}{}{RESET}", "─".repeat(width.min(MAX_RULE_WIDTH))));
+			},
+		}
+	}
+
+	#[allow(clippy::too_many_arguments, reason = "table parts are passed destructured")]
+	fn table(
+		&self,
+		aligns: &[Align],
+		header: &[String],
+		rows: &[Vec<String>],
+		raw: &[String],
+		width: usize,
+		style: Style,
+		lines: &mut Vec<String>,
+	) {
+		let columns = aligns.len();
+		// "│ " + " │ " between columns + " │"
+		let overhead = 3 * columns + 1;
+		if width < overhead + columns {
+			// Too narrow for a table: show the source.
+			for line in raw {
+				lines.extend(wrap_str_with_ansi(line, width));
+			}
+			return;
+		}
+		let available = width - overhead;
+
+		let header_style = Style { bold: true, ..style };
+		let header: Vec<String> = header
+			.iter()
+			.map(|cell| self.inline_text(cell, header_style))
+			.collect();
+		let rows: Vec<Vec<String>> = rows
+			.iter()
+			.map(|row| {
+				row.iter()
+					.map(|cell| self.inline_text(cell, style))
+					.collect()
+			})
+			.collect();
+
+		let mut natural = vec![0; columns];
+		let mut min_word = vec![1; columns];
+		for row in std::iter::once(&header).chain(&rows) {
+			for (column, cell) in row.iter().enumerate() {
+				natural[column] = natural[column].max(visible_width_str(cell));
+				let longest = cell
+					.split_whitespace()
+					.map(visible_width_str)
+					.max()
+					.unwrap_or(0);
+				min_word[column] = min_word[column].max(longest.min(MAX_UNBROKEN_WORD));
+			}
+		}
+		let widths = column_widths(&natural, &min_word, available);
+
+		let border = |left: &str, middle: &str, right: &str| {
+			let cells: Vec<String> = widths.iter().map(|&width| "─".repeat(width)).collect();
+			format!("{left}─{}─{right}", cells.join(&format!("─{middle}─")))
+		};
+		let push_row = |lines: &mut Vec<String>, row: &[String]| {
+			let cells: Vec<Vec<String>> = row
+				.iter()
+				.zip(&widths)
+				.map(|(cell, &width)| wrap_str_with_ansi(cell, width))
+				.collect();
+			let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+			for line in 0..height {
+				let parts: Vec<String> = cells
+					.iter()
+					.zip(&widths)
+					.zip(aligns)
+					.map(|((cell, &width), &align)| {
+						pad(cell.get(line).map_or("", String::as_str), width, align)
+					})
+					.collect();
+				lines.push(format!("│ {} │", parts.join(" │ ")));
+			}
+		};
+
+		lines.push(border("┌", "┬", "┐"));
+		push_row(lines, &header);
+		let separator = border("├", "┼", "┤");
+		lines.push(separator.clone());
+		for (index, row) in rows.iter().enumerate() {
+			if index > 0 {
+				lines.push(separator.clone());
+			}
+			push_row(lines, row);
+		}
+		lines.push(border("└", "┴", "┘"));
+	}
+}
+
+/// Pad `text` to `width` cells.
+fn pad(text: &str, width: usize, align: Align) -> String {
+	let space = width.saturating_sub(visible_width_str(text));
+	let (left, right) = match align {
+		Align::Left => (0, space),
+		Align::Center => (space / 2, space - space / 2),
+		Align::Right => (space, 0),
+	};
+	format!("{}{text}{}", " ".repeat(left), " ".repeat(right))
+}
+
+/// Fit table columns into `available` cells: natural widths when they fit,
+/// otherwise at least the longest word of each column, with the space left
+/// shared by how much each column wants.
+fn column_widths(natural: &[usize], min_word: &[usize], available: usize) -> Vec<usize> {
+	let columns = natural.len();
+	let mut min_widths = min_word.to_vec();
+	if min_widths.iter().sum::<usize>() > available {
+		// Even whole words do not fit: share the space by word length.
+		let remaining = available - columns;
+		let total_weight: usize = min_word.iter().map(|width| width - 1).sum();
+		min_widths = min_word
+			.iter()
+			.map(|&width| {
+				1 + ((width - 1) * remaining)
+					.checked_div(total_weight)
+					.unwrap_or(0)
+			})
+			.collect();
+		let mut leftover = available - min_widths.iter().sum::<usize>();
+		for width in &mut min_widths {
+			if leftover == 0 {
+				break;
+			}
+			*width += 1;
+			leftover -= 1;
+		}
+	}
+
+	if natural.iter().sum::<usize>() <= available {
+		return natural
+			.iter()
+			.zip(&min_widths)
+			.map(|(&natural, &min)| natural.max(min))
+			.collect();
+	}
+	let min_total: usize = min_widths.iter().sum();
+	let grow_potential: usize = natural
+		.iter()
+		.zip(&min_widths)
+		.map(|(&natural, &min)| natural.saturating_sub(min))
+		.sum();
+	let extra = available.saturating_sub(min_total);
+	let mut widths: Vec<usize> = natural
+		.iter()
+		.zip(&min_widths)
+		.map(|(&natural, &min)| {
+			min + (natural.saturating_sub(min) * extra)
+				.checked_div(grow_potential)
+				.unwrap_or(0)
+		})
+		.collect();
+	// Hand out what rounding left over.
+	let mut remaining = available.saturating_sub(widths.iter().sum());
+	while remaining > 0 {
+		let mut grew = false;
+		for (width, &natural) in widths.iter_mut().zip(natural) {
+			if remaining > 0 && *width < natural {
+				*width += 1;
+				remaining -= 1;
+				grew = true;
+			}
+		}
+		if !grew {
+			break;
+		}
+	}
+	widths
+}
+
+/// Render markdown as ANSI text wrapped to `width`.
+///
+/// # Errors
+/// Returns an error for an unknown code block theme.
+#[napi(js_name = "renderMarkdown")]
+pub fn render_markdown(markdown: String, options: Option<RenderMarkdownOptions>) -> Result<String> {
+	let options = options.unwrap_or_default();
+	let renderer = Renderer {
+		width:      options.width.unwrap_or(DEFAULT_WIDTH).max(1) as usize,
+		theme:      find_theme(options.theme.as_deref())?,
+		hyperlinks: options.hyperlinks.unwrap_or(false),
+	};
+	let lines: Vec<String> = markdown.lines().map(expand_indent).collect();
+	let blocks = parse_blocks(&lines);
+	Ok(renderer
+		.blocks(&blocks, renderer.width, Style::default())
+		.join("\n"))
+}
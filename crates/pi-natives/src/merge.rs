@@ -0,0 +1,410 @@
+//! Line-based three-way merge.
+//!
+//! # Overview
+//! `threeWayMerge` diffs `ours` and `theirs` against their common `base`
+//! (histogram diff) and walks the changes in base order. Changes that touch
+//! the same or adjacent base lines form one region; a region changed on one
+//! side only, or identically on both, merges cleanly, anything else is a
+//! conflict, as with `git merge-file`.
+//!
+//! Conflicts are written with git's markers in one of three styles:
+//! - `merge` (default): ours and theirs, with lines common to both moved out of
+//!   the conflict
+//! - `diff3`: also the base lines, untrimmed
+//! - `zdiff3`: also the base lines, with common lines moved out
+//!
+//! `favor` resolves conflicts instead, taking ours, theirs, or both.
+//! Markers use the dominant line ending of `ours`.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { text, clean } = await native.threeWayMerge(readText, agentText, diskText, { style: "diff3" })
+//! ```
+
+use std::ops::Range;
+
+use encoding_rs::UTF_8;
+use gix::diff::blob::{Algorithm, intern::InternedInput, sources::lines_with_terminator};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{task, text_profile};
+
+const MARKER_SIZE: usize = 7;
+
+/// Conflict marker labels.
+#[napi(object)]
+#[derive(Default)]
+pub struct MergeLabels {
+	/// Label after `<<<<<<<` (default: "ours").
+	pub ours:   Option<String>,
+	/// Label after `|||||||` (default: "base").
+	pub base:   Option<String>,
+	/// Label after `>>>>>>>` (default: "theirs").
+	pub theirs: Option<String>,
+}
+
+/// Options for `threeWayMerge`.
+#[napi(object)]
+#[derive(Default)]
+pub struct MergeOptions {
+	/// Conflict style (default: "merge").
+	#[napi(ts_type = "\"merge\" | \"diff3\" | \"zdiff3\"")]
+	pub style:  Option<String>,
+	/// Resolve conflicts by taking this side, or both (ours first), instead
+	/// of writing markers.
+	#[napi(ts_type = "\"ours\" | \"theirs\" | \"union\"")]
+	pub favor:  Option<String>,
+	/// Conflict marker labels.
+	pub labels: Option<MergeLabels>,
+}
+
+/// A conflict in the merged text.
+#[napi(object)]
+pub struct MergeConflict {
+	/// Line of the `<<<<<<<` marker, 1-indexed.
+	pub start: u32,
+	/// Line of the `>>>>>>>` marker, 1-indexed.
+	pub end:   u32,
+}
+
+/// Result of `threeWayMerge`.
+#[napi(object)]
+pub struct MergeResult {
+	/// Merged text, with conflict markers unless `clean`.
+	pub text:      String,
+	/// Whether every change merged without conflict.
+	pub clean:     bool,
+	/// Conflicts left in `text`, in order.
+	pub conflicts: Vec<MergeConflict>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Style {
+	Merge,
+	Diff3,
+	Zdiff3,
+}
+
+#[derive(Clone, Copy)]
+enum Favor {
+	Ours,
+	Theirs,
+	Union,
+}
+
+/// A change from base lines `base` to side lines `side`.
+struct Hunk {
+	base: Range<usize>,
+	side: Range<usize>,
+}
+
+fn hunks(base: &str, side: &str) -> Vec<Hunk> {
+	let input = InternedInput::new(lines_with_terminator(base), lines_with_terminator(side));
+	let mut hunks = Vec::new();
+	gix::diff::blob::diff(Algorithm::Histogram, &input, |base: Range<u32>, side: Range<u32>| {
+		hunks.push(Hunk {
+			base: base.start as usize..base.end as usize,
+			side: side.start as usize..side.end as usize,
+		});
+	});
+	hunks
+}
+
+/// One side of the merge: its lines and changes against base.
+struct Side<'a> {
+	lines: Vec<&'a str>,
+	hunks: Vec<Hunk>,
+	next:  usize,
+}
+
+impl<'a> Side<'a> {
+	fn new(base: &str, text: &'a str) -> Self {
+		Self { lines: lines_with_terminator(text).collect(), hunks: hunks(base, text), next: 0 }
+	}
+
+	fn next_start(&self) -> Option<usize> {
+		self.hunks.get(self.next).map(|hunk| hunk.base.start)
+	}
+
+	/// Take the next hunk if it starts at or before base line `end`,
+	/// returning its end.
+	fn take_until(&mut self, end: usize) -> Option<usize> {
+		let hunk = self.hunks.get(self.next)?;
+		(hunk.base.start <= end).then(|| {
+			self.next += 1;
+			hunk.base.end
+		})
+	}
+
+	/// This side's lines for base lines `region`, given the hunks it took
+	/// for the region; `None` if it did not change them.
+	fn region(&self, taken: Range<usize>, region: &Range<usize>) -> Option<&[&'a str]> {
+		let first = self.hunks.get(taken.start).filter(|_| !taken.is_empty())?;
+		let last = &self.hunks[taken.end - 1];
+		let start = first.side.start - (first.base.start - region.start);
+		let end = last.side.end + (region.end - last.base.end);
+		Some(&self.lines[start..end])
+	}
+}
+
+struct Output<'a> {
+	text:      String,
+	lines:     u32,
+	eol:       &'static str,
+	conflicts: Vec<MergeConflict>,
+	labels:    [&'a str; 3],
+}
+
+impl Output<'_> {
+	fn push(&mut self, lines: &[&str]) {
+		for line in lines {
+			self.text.push_str(line);
+		}
+		self.lines += lines.len() as u32;
+	}
+
+	/// Push `lines`, terminating the last one so a marker can follow.
+	fn push_section(&mut self, lines: &[&str]) {
+		self.push(lines);
+		if !lines.is_empty() && !self.text.ends_with('\n') {
+			self.text.push_str(self.eol);
+		}
+	}
+
+	fn marker(&mut self, marker: char, label: &str) {
+		self.text.extend(std::iter::repeat_n(marker, MARKER_SIZE));
+		if !label.is_empty() {
+			self.text.push(' ');
+			self.text.push_str(label);
+		}
+		self.text.push_str(self.eol);
+		self.lines += 1;
+	}
+
+	fn conflict(&mut self, ours: &[&str], base: Option<&[&str]>, theirs: &[&str]) {
+		let [ours_label, base_label, theirs_label] = self.labels;
+		let start = self.lines + 1;
+		self.marker('<', ours_label);
+		self.push_section(ours);
+		if let Some(base) = base {
+			self.marker('|', base_label);
+			self.push_section(base);
+		}
+		self.marker('=', "");
+		self.push_section(theirs);
+		self.marker('>', theirs_label);
+		self
+			.conflicts
+			.push(MergeConflict { start, end: self.lines });
+	}
+}
+
+/// Lengths of the common prefix and, in the rest, common suffix of `a` and
+/// `b`.
+fn common_ends(a: &[&str], b: &[&str]) -> (usize, usize) {
+	let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+	let suffix = a[prefix..]
+		.iter()
+		.rev()
+		.zip(b[prefix..].iter().rev())
+		.take_while(|(a, b)| a == b)
+		.count();
+	(prefix, suffix)
+}
+
+fn merge(
+	base: &str,
+	ours: &str,
+	theirs: &str,
+	style: Style,
+	favor: Option<Favor>,
+	labels: [&str; 3],
+) -> MergeResult {
+	let eol = if text_profile::detect(ours, UTF_8, false).eol == "crlf" {
+		"\r\n"
+	} else {
+		"\n"
+	};
+	let base_lines = lines_with_terminator(base).collect::<Vec<_>>();
+	let mut ours = Side::new(base, ours);
+	let mut theirs = Side::new(base, theirs);
+	let mut out = Output {
+		text: String::with_capacity(base.len()),
+		lines: 0,
+		eol,
+		conflicts: Vec::new(),
+		labels,
+	};
+
+	let mut pos = 0;
+	while let Some(start) = ours
+		.next_start()
+		.into_iter()
+		.chain(theirs.next_start())
+		.min()
+	{
+		out.push(&base_lines[pos..start]);
+
+		// Grow the region while either side has a change touching it.
+		let (ours_first, theirs_first) = (ours.next, theirs.next);
+		let mut end = start;
+		while let Some(hunk_end) = ours.take_until(end).or_else(|| theirs.take_until(end)) {
+			end = end.max(hunk_end);
+		}
+		let region = start..end;
+		let base_region = &base_lines[region.clone()];
+		let ours_region = ours.region(ours_first..ours.next, &region);
+		let theirs_region = theirs.region(theirs_first..theirs.next, &region);
+		match (ours_region, theirs_region) {
+			(Some(lines), None) | (None, Some(lines)) => out.push(lines),
+			(Some(a), Some(b)) if a == b => out.push(a),
+			(Some(a), Some(b)) if style == Style::Diff3 && favor.is_none() => {
+				out.conflict(a, Some(base_region), b);
+			},
+			(Some(a), Some(b)) => {
+				let (prefix, suffix) = common_ends(a, b);
+				let (ours_mid, theirs_mid) =
+					(&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+				out.push(&a[..prefix]);
+				match favor {
+					Some(Favor::Ours) => out.push(ours_mid),
+					Some(Favor::Theirs) => out.push(theirs_mid),
+					Some(Favor::Union) => {
+						out.push_section(ours_mid);
+						out.push(theirs_mid);
+					},
+					None => {
+						let base = (style == Style::Zdiff3).then_some(base_region);
+						out.conflict(ours_mid, base, theirs_mid);
+					},
+				}
+				out.push(&a[a.len() - suffix..]);
+			},
+			(None, None) => out.push(base_region),
+		}
+		pos = end;
+	}
+	out.push(&base_lines[pos..]);
+
+	MergeResult {
+		text:      out.text,
+		clean:     out.conflicts.is_empty(),
+		conflicts: out.conflicts,
+	}
+}
+
+fn parse_options(options: Option<MergeOptions>) -> Result<(Style, Option<Favor>, [String; 3])> {
+	let options = options.unwrap_or_default();
+	let style = match options.style.as_deref() {
+		None | Some("merge") => Style::Merge,
+		Some("diff3") => Style::Diff3,
+		Some("zdiff3") => Style::Zdiff3,
+		Some(other) => return Err(Error::from_reason(format!("Unknown merge style: {other}"))),
+	};
+	let favor = match options.favor.as_deref() {
+		None => None,
+		Some("ours") => Some(Favor::Ours),
+		Some("theirs") => Some(Favor::Theirs),
+		Some("union") => Some(Favor::Union),
+		Some(other) => return Err(Error::from_reason(format!("Unknown merge favor: {other}"))),
+	};
+	let labels = options.labels.unwrap_or_default();
+	let labels = [
+		labels.ours.unwrap_or_else(|| "ours".to_string()),
+		labels.base.unwrap_or_else(|| "base".to_string()),
+		labels.theirs.unwrap_or_else(|| "theirs".to_string()),
+	];
+	Ok((style, favor, labels))
+}
+
+/// Merge the changes `ours` and `theirs` made to `base`.
+///
+/// # Errors
+/// Returns an error for an unknown `style` or `favor`.
+#[napi(js_name = "threeWayMerge")]
+pub fn three_way_merge(
+	base: String,
+	ours: String,
+	theirs: String,
+	options: Option<MergeOptions>,
+) -> task::Async<MergeResult> {
+	task::blocking("three_way_merge", (), move |_| {
+		let (style, favor, labels) = parse_options(options)?;
+		let [ours_label, base_label, theirs_label] = &labels;
+		Ok(merge(&base, &ours, &theirs, style, favor, [ours_label, base_label, theirs_label]))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const LABELS: [&str; 3] = ["ours", "base", "theirs"];
+
+	fn merge_with(base: &str, ours: &str, theirs: &str, style: Style) -> MergeResult {
+		merge(base, ours, theirs, style, None, LABELS)
+	}
+
+	#[test]
+	fn test_clean_merge() {
+		let result = merge_with("a\nb\nc\nd\n", "A\nb\nc\nd\n", "a\nb\nc\nD\n", Style::Merge);
+		assert!(result.clean);
+		assert_eq!(result.text, "A\nb\nc\nD\n");
+	}
+
+	#[test]
+	fn test_identical_changes_merge_cleanly() {
+		let result = merge_with("a\nb\nc\n", "a\nB\nc\n", "a\nB\nc\n", Style::Merge);
+		assert!(result.clean);
+		assert_eq!(result.text, "a\nB\nc\n");
+	}
+
+	#[test]
+	fn test_conflict_markers() {
+		let result = merge_with("a\nb\nc\n", "a\nB1\nc\n", "a\nB2\nc\n", Style::Merge);
+		assert!(!result.clean);
+		assert_eq!(result.text, "a\n<<<<<<< ours\nB1\n=======\nB2\n>>>>>>> theirs\nc\n");
+		assert_eq!((result.conflicts[0].start, result.conflicts[0].end), (2, 6));
+	}
+
+	#[test]
+	fn test_conflict_styles() {
+		let (base, ours, theirs) = ("a\nb\nc\n", "a\nX\nY1\nc\n", "a\nX\nY2\nc\n");
+		assert_eq!(
+			merge_with(base, ours, theirs, Style::Merge).text,
+			"a\nX\n<<<<<<< ours\nY1\n=======\nY2\n>>>>>>> theirs\nc\n"
+		);
+		assert_eq!(
+			merge_with(base, ours, theirs, Style::Diff3).text,
+			"a\n<<<<<<< ours\nX\nY1\n||||||| base\nb\n=======\nX\nY2\n>>>>>>> theirs\nc\n"
+		);
+		assert_eq!(
+			merge_with(base, ours, theirs, Style::Zdiff3).text,
+			"a\nX\n<<<<<<< ours\nY1\n||||||| base\nb\n=======\nY2\n>>>>>>> theirs\nc\n"
+		);
+	}
+
+	#[test]
+	fn test_favor() {
+		let (base, ours, theirs) = ("a\nb\nc\n", "a\nB1\nc\n", "a\nB2\nc\n");
+		let favored = |favor| merge(base, ours, theirs, Style::Merge, Some(favor), LABELS);
+		assert_eq!(favored(Favor::Ours).text, "a\nB1\nc\n");
+		assert_eq!(favored(Favor::Theirs).text, "a\nB2\nc\n");
+		let union = favored(Favor::Union);
+		assert!(union.clean);
+		assert_eq!(union.text, "a\nB1\nB2\nc\n");
+	}
+
+	#[test]
+	fn test_markers_follow_line_endings() {
+		let result = merge_with("a\r\nb\r\n", "a\r\nB1\r\n", "a\r\nB2\r\n", Style::Merge);
+		assert_eq!(result.text, "a\r\n<<<<<<< ours\r\nB1\r\n=======\r\nB2\r\n>>>>>>> theirs\r\n");
+	}
+
+	#[test]
+	fn test_missing_final_newline() {
+		let result = merge_with("a\nb", "a\nB1", "a\nB2", Style::Merge);
+		assert_eq!(result.text, "a\n<<<<<<< ours\nB1\n=======\nB2\n>>>>>>> theirs\n");
+	}
+}
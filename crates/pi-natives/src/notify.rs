@@ -0,0 +1,109 @@
+//! Desktop notifications.
+//!
+//! # Overview
+//! Sends a notification through the platform's notification service: the
+//! freedesktop notification D-Bus service on Linux and BSD, Notification
+//! Center on macOS, and toast notifications on Windows.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.sendNotification({ title: "omp", body: "Waiting for approval", urgency: "critical" })
+//! ```
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use notify_rust::{Notification, Timeout};
+
+use crate::task;
+
+const DEFAULT_APP_NAME: &str = "omp";
+
+/// Notification urgency.
+#[derive(Clone, Copy)]
+enum Urgency {
+	Low,
+	Normal,
+	Critical,
+}
+
+impl Urgency {
+	fn parse(option: Option<&str>) -> Result<Self> {
+		match option {
+			Some("low") => Ok(Self::Low),
+			None | Some("normal") => Ok(Self::Normal),
+			Some("critical") => Ok(Self::Critical),
+			Some(other) => Err(Error::from_reason(format!(
+				"Invalid urgency: {other} (expected \"low\", \"normal\" or \"critical\")"
+			))),
+		}
+	}
+}
+
+#[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"))))]
+impl From<Urgency> for notify_rust::Urgency {
+	fn from(urgency: Urgency) -> Self {
+		match urgency {
+			Urgency::Low => Self::Low,
+			Urgency::Normal => Self::Normal,
+			Urgency::Critical => Self::Critical,
+		}
+	}
+}
+
+/// Options for `sendNotification`.
+#[napi(object)]
+pub struct NotificationOptions {
+	/// Notification title.
+	pub title:      String,
+	/// Notification text.
+	pub body:       Option<String>,
+	/// Urgency (default: normal). Critical notifications stay visible until
+	/// dismissed on Linux; ignored on macOS.
+	#[napi(ts_type = "\"low\" | \"normal\" | \"critical\"")]
+	pub urgency:    Option<String>,
+	/// Application name shown with the notification (default: `omp`).
+	#[napi(js_name = "appName")]
+	pub app_name:   Option<String>,
+	/// How long the notification stays visible, in milliseconds (default: the
+	/// notification service's choice). Only honored on Linux.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+}
+
+fn build(options: NotificationOptions, urgency: Urgency) -> Notification {
+	let mut notification = Notification::new();
+	notification
+		.summary(&options.title)
+		.appname(options.app_name.as_deref().unwrap_or(DEFAULT_APP_NAME));
+	if let Some(body) = &options.body {
+		notification.body(body);
+	}
+	if let Some(timeout_ms) = options.timeout_ms {
+		notification.timeout(Timeout::Milliseconds(timeout_ms));
+	}
+	#[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"))))]
+	notification.urgency(urgency.into());
+	#[cfg(target_os = "macos")]
+	let _ = urgency;
+	notification
+}
+
+/// Show a desktop notification.
+///
+/// Resolves once the notification service accepted it, without waiting for
+/// the user to see or dismiss it.
+///
+/// # Errors
+/// Returns an error if the urgency is invalid or no notification service is
+/// available (e.g. no D-Bus session on a headless Linux machine).
+#[napi(js_name = "sendNotification")]
+pub fn send_notification(options: NotificationOptions) -> Result<task::Async<()>> {
+	let urgency = Urgency::parse(options.urgency.as_deref())?;
+	let notification = build(options, urgency);
+	Ok(task::blocking("notify.send", (), move |_| {
+		notification
+			.show()
+			.map(drop)
+			.map_err(|err| Error::from_reason(format!("Failed to send notification: {err}")))
+	}))
+}
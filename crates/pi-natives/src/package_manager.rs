@@ -0,0 +1,396 @@
+//! Package manager detection with canonical commands.
+//!
+//! # Overview
+//! `detectPackageManager` finds the package managers that govern a directory
+//! and the commands to install, add, remove and run with each, so a pnpm
+//! repository gets `pnpm add` rather than `npm install`.
+//!
+//! Lockfiles usually live at the workspace root, so the search walks up from
+//! the directory to the repository root (the first directory holding `.git`)
+//! and, per ecosystem, takes the nearest directory with a lockfile or other
+//! decisive marker (`packageManager` in `package.json`, `pnpm-workspace.yaml`,
+//! `[tool.poetry]` in `pyproject.toml`, ...). An ecosystem with manifests but
+//! no marker falls back to its default manager at the nearest manifest.
+//!
+//! Only known files are read; nothing is executed.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const [primary] = await native.detectPackageManager("packages/app");
+//! // JS: primary // { name: "pnpm", root: "/repo", lockfile: "pnpm-lock.yaml", add: "pnpm add <package>", ... }
+//! ```
+
+use std::{fs, path::Path};
+
+use napi_derive::napi;
+use serde_json::Value;
+use toml_edit::DocumentMut;
+
+use crate::task;
+
+/// A package manager governing a directory.
+#[napi(object)]
+pub struct PackageManager {
+	/// Manager name: "npm", "pnpm", "yarn", "bun", "deno", "uv", "poetry",
+	/// "pdm", "pipenv", "pip", "cargo", "go", "bundler" or "composer".
+	pub name:           String,
+	/// Ecosystem: "javascript", "python", "rust", "go", "ruby" or "php".
+	pub ecosystem:      String,
+	/// Directory the manager works from, holding its lockfile or manifest.
+	pub root:           String,
+	/// Lockfile name in `root`, when there is one.
+	pub lockfile:       Option<String>,
+	/// Install the declared dependencies.
+	pub install:        String,
+	/// Install exactly what the lockfile records, failing if it is out of
+	/// date (for CI); absent without a lockfile.
+	#[napi(js_name = "frozenInstall")]
+	pub frozen_install: Option<String>,
+	/// Add a dependency; `<package>` stands for its name.
+	pub add:            String,
+	/// Add a development dependency.
+	#[napi(js_name = "addDev")]
+	pub add_dev:        Option<String>,
+	/// Remove a dependency.
+	pub remove:         String,
+	/// Run a script defined by the project; `<script>` stands for its name.
+	pub run:            Option<String>,
+	/// Run a command with the project's dependencies available; `<command>`
+	/// stands for it.
+	pub exec:           Option<String>,
+}
+
+/// Ecosystems in reporting order, with the default manager used when only a
+/// manifest is found.
+const ECOSYSTEMS: [(&str, &str); 6] = [
+	("javascript", "npm"),
+	("python", "pip"),
+	("rust", "cargo"),
+	("go", "go"),
+	("ruby", "bundler"),
+	("php", "composer"),
+];
+
+/// Lockfiles and the manager they belong to, by ecosystem, most specific
+/// first.
+const LOCKFILES: &[(&str, &str, &str)] = &[
+	("javascript", "bun.lock", "bun"),
+	("javascript", "bun.lockb", "bun"),
+	("javascript", "pnpm-lock.yaml", "pnpm"),
+	("javascript", "yarn.lock", "yarn"),
+	("javascript", "package-lock.json", "npm"),
+	("javascript", "npm-shrinkwrap.json", "npm"),
+	("javascript", "deno.lock", "deno"),
+	("python", "uv.lock", "uv"),
+	("python", "poetry.lock", "poetry"),
+	("python", "pdm.lock", "pdm"),
+	("python", "Pipfile.lock", "pipenv"),
+	("rust", "Cargo.lock", "cargo"),
+	("go", "go.work.sum", "go"),
+	("go", "go.sum", "go"),
+	("ruby", "Gemfile.lock", "bundler"),
+	("php", "composer.lock", "composer"),
+];
+
+/// Manifests that place a directory in an ecosystem.
+const MANIFESTS: &[(&str, &str)] = &[
+	("javascript", "package.json"),
+	("javascript", "deno.json"),
+	("javascript", "deno.jsonc"),
+	("python", "pyproject.toml"),
+	("python", "requirements.txt"),
+	("python", "setup.py"),
+	("python", "Pipfile"),
+	("rust", "Cargo.toml"),
+	("go", "go.work"),
+	("go", "go.mod"),
+	("ruby", "Gemfile"),
+	("php", "composer.json"),
+];
+
+/// What was found for one ecosystem.
+#[derive(Clone)]
+struct Found {
+	name:         String,
+	dir:          String,
+	lockfile:     Option<String>,
+	/// Distance from the starting directory, for ordering.
+	depth:        usize,
+	/// Yarn 2 or later.
+	berry:        bool,
+	/// `requirements.txt` in the directory, for pip.
+	requirements: bool,
+}
+
+/// Manager named by the `packageManager` field of `package.json` (corepack).
+fn declared_js_manager(dir: &Path) -> Option<(String, bool)> {
+	let text = fs::read_to_string(dir.join("package.json")).ok()?;
+	let manifest = serde_json::from_str::<Value>(&text).ok()?;
+	let spec = manifest.get("packageManager")?.as_str()?;
+	let (name, version) = spec.split_once('@').unwrap_or((spec, ""));
+	let berry = name == "yarn" && !version.starts_with('1');
+	(!name.is_empty()).then(|| (name.to_string(), berry))
+}
+
+/// Python manager configured in `pyproject.toml` without a lockfile yet.
+fn configured_python_manager(dir: &Path) -> Option<&'static str> {
+	let doc = fs::read_to_string(dir.join("pyproject.toml"))
+		.ok()?
+		.parse::<DocumentMut>()
+		.ok()?;
+	let tool = doc.get("tool")?;
+	["uv", "poetry", "pdm"]
+		.into_iter()
+		.find(|name| tool.get(name).is_some())
+}
+
+/// The decisive marker for `ecosystem` in `dir`, if any.
+fn marker(dir: &Path, ecosystem: &str) -> Option<(String, Option<String>, bool)> {
+	let exists = |name: &str| dir.join(name).exists();
+	if ecosystem == "javascript"
+		&& let Some((name, berry)) = declared_js_manager(dir)
+	{
+		let lockfile = LOCKFILES
+			.iter()
+			.find(|(eco, file, manager)| *eco == ecosystem && *manager == name && exists(file))
+			.map(|(_, file, _)| (*file).to_string());
+		return Some((name, lockfile, berry || exists(".yarnrc.yml")));
+	}
+	if let Some((_, file, name)) = LOCKFILES
+		.iter()
+		.find(|(eco, file, _)| *eco == ecosystem && exists(file))
+	{
+		return Some(((*name).to_string(), Some((*file).to_string()), exists(".yarnrc.yml")));
+	}
+	match ecosystem {
+		"javascript" if exists("pnpm-workspace.yaml") => Some(("pnpm".to_string(), None, false)),
+		"python" => configured_python_manager(dir)
+			.map(str::to_string)
+			.or_else(|| exists("Pipfile").then(|| "pipenv".to_string()))
+			.map(|name| (name, None, false)),
+		"go" if exists("go.work") => Some(("go".to_string(), None, false)),
+		_ => None,
+	}
+}
+
+fn detect(start: &Path) -> Vec<PackageManager> {
+	let start = std::path::absolute(start).unwrap_or_else(|_| start.to_path_buf());
+	let mut decided: Vec<Option<Found>> = vec![None; ECOSYSTEMS.len()];
+	let mut fallback: Vec<Option<Found>> = vec![None; ECOSYSTEMS.len()];
+	for (depth, dir) in start.ancestors().enumerate() {
+		let dir_str = dir.to_string_lossy().into_owned();
+		let requirements = dir.join("requirements.txt").is_file();
+		for (index, (ecosystem, default)) in ECOSYSTEMS.iter().enumerate() {
+			if decided[index].is_some() {
+				continue;
+			}
+			if let Some((name, lockfile, berry)) = marker(dir, ecosystem) {
+				decided[index] =
+					Some(Found { name, dir: dir_str.clone(), lockfile, depth, berry, requirements });
+			} else if fallback[index].is_none()
+				&& MANIFESTS
+					.iter()
+					.any(|(eco, file)| eco == ecosystem && dir.join(file).exists())
+			{
+				let deno = dir.join("deno.json").exists() || dir.join("deno.jsonc").exists();
+				let name = if *ecosystem == "javascript" && deno {
+					"deno"
+				} else {
+					default
+				};
+				fallback[index] = Some(Found {
+					name: name.to_string(),
+					dir: dir_str.clone(),
+					lockfile: None,
+					depth,
+					berry: false,
+					requirements,
+				});
+			}
+		}
+		// Nothing above the repository belongs to it.
+		if dir.join(".git").exists() {
+			break;
+		}
+	}
+	let mut found: Vec<(usize, Found)> = decided
+		.into_iter()
+		.zip(fallback)
+		.enumerate()
+		.filter_map(|(index, (decided, fallback))| Some((index, decided.or(fallback)?)))
+		.collect();
+	found.sort_by_key(|(index, found)| (found.depth, *index));
+	found
+		.into_iter()
+		.map(|(index, found)| manager(ECOSYSTEMS[index].0, found))
+		.collect()
+}
+
+fn manager(ecosystem: &str, found: Found) -> PackageManager {
+	let owned = |text: &str| text.to_string();
+	let (install, frozen, add, add_dev, remove, run, exec) = match found.name.as_str() {
+		"pnpm" => (
+			owned("pnpm install"),
+			Some("pnpm install --frozen-lockfile"),
+			"pnpm add <package>",
+			Some("pnpm add -D <package>"),
+			"pnpm remove <package>",
+			Some("pnpm run <script>"),
+			Some("pnpm exec <command>"),
+		),
+		"yarn" if found.berry => (
+			owned("yarn install"),
+			Some("yarn install --immutable"),
+			"yarn add <package>",
+			Some("yarn add -D <package>"),
+			"yarn remove <package>",
+			Some("yarn run <script>"),
+			Some("yarn exec <command>"),
+		),
+		"yarn" => (
+			owned("yarn install"),
+			Some("yarn install --frozen-lockfile"),
+			"yarn add <package>",
+			Some("yarn add -D <package>"),
+			"yarn remove <package>",
+			Some("yarn run <script>"),
+			Some("yarn <command>"),
+		),
+		"bun" => (
+			owned("bun install"),
+			Some("bun install --frozen-lockfile"),
+			"bun add <package>",
+			Some("bun add -d <package>"),
+			"bun remove <package>",
+			Some("bun run <script>"),
+			Some("bunx <command>"),
+		),
+		"deno" => (
+			owned("deno install"),
+			Some("deno install --frozen"),
+			"deno add <package>",
+			Some("deno add --dev <package>"),
+			"deno remove <package>",
+			Some("deno task <script>"),
+			Some("deno run <command>"),
+		),
+		"uv" => (
+			owned("uv sync"),
+			Some("uv sync --locked"),
+			"uv add <package>",
+			Some("uv add --dev <package>"),
+			"uv remove <package>",
+			Some("uv run <script>"),
+			Some("uv run <command>"),
+		),
+		"poetry" => (
+			owned("poetry install"),
+			Some("poetry sync"),
+			"poetry add <package>",
+			Some("poetry add --group dev <package>"),
+			"poetry remove <package>",
+			Some("poetry run <script>"),
+			Some("poetry run <command>"),
+		),
+		"pdm" => (
+			owned("pdm install"),
+			Some("pdm sync"),
+			"pdm add <package>",
+			Some("pdm add --dev <package>"),
+			"pdm remove <package>",
+			Some("pdm run <script>"),
+			Some("pdm run <command>"),
+		),
+		"pipenv" => (
+			owned("pipenv install"),
+			Some("pipenv sync"),
+			"pipenv install <package>",
+			Some("pipenv install --dev <package>"),
+			"pipenv uninstall <package>",
+			Some("pipenv run <script>"),
+			Some("pipenv run <command>"),
+		),
+		"pip" => (
+			owned(if found.requirements {
+				"pip install -r requirements.txt"
+			} else {
+				"pip install -e ."
+			}),
+			None,
+			"pip install <package>",
+			None,
+			"pip uninstall <package>",
+			None,
+			None,
+		),
+		"cargo" => (
+			owned("cargo fetch"),
+			Some("cargo fetch --locked"),
+			"cargo add <package>",
+			Some("cargo add --dev <package>"),
+			"cargo remove <package>",
+			None,
+			None,
+		),
+		"go" => (
+			owned("go mod download"),
+			None,
+			"go get <package>",
+			None,
+			"go get <package>@none",
+			None,
+			Some("go run <command>"),
+		),
+		"bundler" => (
+			owned("bundle install"),
+			Some("bundle install --frozen"),
+			"bundle add <package>",
+			Some("bundle add <package> --group development"),
+			"bundle remove <package>",
+			None,
+			Some("bundle exec <command>"),
+		),
+		"composer" => (
+			owned("composer install"),
+			None,
+			"composer require <package>",
+			Some("composer require --dev <package>"),
+			"composer remove <package>",
+			Some("composer run-script <script>"),
+			Some("composer exec <command>"),
+		),
+		_ => (
+			owned("npm install"),
+			Some("npm ci"),
+			"npm install <package>",
+			Some("npm install --save-dev <package>"),
+			"npm uninstall <package>",
+			Some("npm run <script>"),
+			Some("npx <command>"),
+		),
+	};
+	let locked = found.lockfile.is_some();
+	PackageManager {
+		name: found.name,
+		ecosystem: ecosystem.to_string(),
+		root: found.dir,
+		lockfile: found.lockfile,
+		install,
+		frozen_install: frozen.filter(|_| locked).map(str::to_string),
+		add: add.to_string(),
+		add_dev: add_dev.map(str::to_string),
+		remove: remove.to_string(),
+		run: run.map(str::to_string),
+		exec: exec.map(str::to_string),
+	}
+}
+
+/// Detect the package managers governing `root` and their commands.
+///
+/// Managers are ordered nearest first, so the first entry is the one to use
+/// for a mixed project; an empty list means no known manifest was found up to
+/// the repository root.
+#[napi(js_name = "detectPackageManager")]
+pub fn detect_package_manager(root: String) -> task::Async<Vec<PackageManager>> {
+	task::blocking("detect_package_manager", (), move |_| Ok(detect(Path::new(&root))))
+}
@@ -0,0 +1,865 @@
+//! Unified diff application.
+//!
+//! # Overview
+//! Parses unified diffs (plain or git-style, including create, delete and
+//! rename headers) and applies each hunk to files under a root directory.
+//!
+//! Hunks are located by searching outward from the line their header names,
+//! so hunks still apply when earlier edits shifted the file. With `fuzz`,
+//! up to that many leading and trailing context lines may be ignored, as in
+//! GNU `patch`.
+//!
+//! Application is all-or-nothing: files are only written when every hunk of
+//! every file applies. Encoding and BOM of existing files are kept, and so
+//! is the ending (LF/CRLF) of every line not changed, so files mixing both
+//! only differ in the edited lines; added lines take the ending of the line
+//! they replace, or the dominant one.
+//! The files written are recorded in the edit journal, so `undoOperation`
+//! can put them back.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.applyUnifiedDiff({ diff, root: "/repo", fuzz: 2, dryRun: true })
+//! ```
+
+use std::{
+	collections::{HashMap, VecDeque},
+	fs,
+	path::{Component, Path, PathBuf},
+};
+
+use encoding_rs::{Encoding, UTF_8};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{journal, read, task, text_profile, workspace};
+
+const DEV_NULL: &str = "/dev/null";
+
+/// Options for `applyUnifiedDiff`.
+#[napi(object)]
+pub struct ApplyDiffOptions {
+	/// Unified diff text, possibly covering several files.
+	pub diff:                    String,
+	/// Directory that paths in the diff are relative to.
+	pub root:                    String,
+	/// Maximum number of leading/trailing context lines that may be ignored
+	/// when locating a hunk (default: 0).
+	pub fuzz:                    Option<u32>,
+	/// Compute results without writing anything (default: false).
+	#[napi(js_name = "dryRun")]
+	pub dry_run:                 Option<bool>,
+	/// Journal the changes under this operation from `beginOperation`
+	/// (default: an operation of their own).
+	#[napi(js_name = "operationId")]
+	pub operation_id:            Option<i64>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// What a file patch does to its file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[napi]
+pub enum FilePatchKind {
+	/// Existing file is edited in place.
+	Modify = 1,
+	/// File is created.
+	Create = 2,
+	/// File is deleted.
+	Delete = 3,
+	/// File is moved from `oldPath`, possibly with edits.
+	Rename = 4,
+}
+
+/// Outcome of a single hunk.
+#[napi(object)]
+pub struct HunkResult {
+	/// Starting line of the hunk in the original file, from its header.
+	#[napi(js_name = "oldStart")]
+	pub old_start: u32,
+	/// Starting line of the hunk in the new file, from its header.
+	#[napi(js_name = "newStart")]
+	pub new_start: u32,
+	/// Whether the hunk applied.
+	pub applied:   bool,
+	/// 1-indexed line in the original file where the hunk matched.
+	pub line:      Option<u32>,
+	/// Lines between the header position and where the hunk matched.
+	pub offset:    i32,
+	/// Context lines ignored to make the hunk match.
+	pub fuzz:      u32,
+	/// Why the hunk failed to apply.
+	pub error:     Option<String>,
+}
+
+/// Outcome of the patch for one file.
+#[napi(object)]
+pub struct FilePatchResult {
+	/// Target path relative to the root (the deleted path for deletions).
+	pub path:     String,
+	/// Source path for renames.
+	#[napi(js_name = "oldPath")]
+	pub old_path: Option<String>,
+	/// What the patch does to the file.
+	pub kind:     FilePatchKind,
+	/// Whether every hunk applied and the file-level checks passed.
+	pub applied:  bool,
+	/// Per-hunk results, in diff order.
+	pub hunks:    Vec<HunkResult>,
+	/// Resulting file contents with every applicable hunk applied; absent for
+	/// deletions and file-level errors.
+	pub content:  Option<String>,
+	/// File-level error (missing file, unsafe path, binary patch, ...).
+	pub error:    Option<String>,
+}
+
+/// Result of `applyUnifiedDiff`.
+#[napi(object)]
+pub struct ApplyDiffResult {
+	/// Whether every file applied cleanly. Files are written only when true
+	/// and `dryRun` is not set.
+	pub applied:      bool,
+	/// Per-file results, in diff order.
+	pub files:        Vec<FilePatchResult>,
+	/// Operation the changes were journaled under; absent when nothing was
+	/// written or the journal is off.
+	#[napi(js_name = "operationId")]
+	pub operation_id: Option<i64>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Parsing
+// ═══════════════════════════════════════════════════════════════════════════
+
+enum HunkLine {
+	Context(String),
+	Remove(String),
+	Add(String),
+}
+
+struct Hunk {
+	old_start:  u32,
+	old_len:    u32,
+	new_start:  u32,
+	lines:      Vec<HunkLine>,
+	/// The new side's last line has no trailing newline.
+	new_no_eol: bool,
+}
+
+#[derive(Default)]
+struct FilePatch {
+	/// `None` when the file is created.
+	old_path: Option<String>,
+	/// `None` when the file is deleted.
+	new_path: Option<String>,
+	hunks:    Vec<Hunk>,
+	binary:   bool,
+	/// Started by a `diff --git` line, so `---`/`+++` headers belong to it.
+	git:      bool,
+}
+
+impl FilePatch {
+	fn kind(&self) -> FilePatchKind {
+		match (&self.old_path, &self.new_path) {
+			(None, _) => FilePatchKind::Create,
+			(_, None) => FilePatchKind::Delete,
+			(Some(old), Some(new)) if old != new => FilePatchKind::Rename,
+			_ => FilePatchKind::Modify,
+		}
+	}
+}
+
+/// Parse a `---`/`+++` header path, dropping timestamps and the git `a/`/`b/`
+/// prefix. Returns `None` for `/dev/null`.
+fn parse_header_path(raw: &str) -> Option<String> {
+	let path = raw.split('\t').next().unwrap_or(raw).trim_end();
+	let path = path
+		.strip_prefix('"')
+		.and_then(|p| p.strip_suffix('"'))
+		.unwrap_or(path);
+	if path == DEV_NULL {
+		return None;
+	}
+	Some(strip_git_prefix(path).to_string())
+}
+
+fn strip_git_prefix(path: &str) -> &str {
+	path
+		.strip_prefix("a/")
+		.or_else(|| path.strip_prefix("b/"))
+		.unwrap_or(path)
+}
+
+/// Parse `@@ -l[,s] +l[,s] @@` into `(old_start, old_len, new_start, new_len)`.
+fn parse_hunk_header(line: &str) -> Option<(u32, u32, u32, u32)> {
+	let rest = line.strip_prefix("@@ -")?;
+	let (ranges, _) = rest.split_once(" @@")?;
+	let (old, new) = ranges.split_once(" +")?;
+	let range = |spec: &str| -> Option<(u32, u32)> {
+		match spec.split_once(',') {
+			Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+			None => Some((spec.parse().ok()?, 1)),
+		}
+	};
+	let (old_start, old_len) = range(old)?;
+	let (new_start, new_len) = range(new)?;
+	Some((old_start, old_len, new_start, new_len))
+}
+
+fn parse_diff(diff: &str) -> Result<Vec<FilePatch>> {
+	let mut patches: Vec<FilePatch> = Vec::new();
+	let mut lines = diff.lines().enumerate().peekable();
+
+	while let Some((index, line)) = lines.next() {
+		let line_no = index + 1;
+		if let Some(rest) = line.strip_prefix("diff --git ") {
+			let (old, new) = rest.rsplit_once(" b/").unwrap_or((rest, rest));
+			patches.push(FilePatch {
+				old_path: Some(strip_git_prefix(old).to_string()),
+				new_path: Some(new.to_string()),
+				git: true,
+				..FilePatch::default()
+			});
+		} else if let Some(old) = line.strip_prefix("--- ")
+			&& let Some(new) = lines.peek().and_then(|(_, next)| next.strip_prefix("+++ "))
+		{
+			let (old, new) = (parse_header_path(old), parse_header_path(new));
+			lines.next();
+			match patches.last_mut() {
+				Some(patch) if patch.git && patch.hunks.is_empty() => {
+					patch.old_path = old;
+					patch.new_path = new;
+					patch.git = false;
+				},
+				_ => patches.push(FilePatch { old_path: old, new_path: new, ..FilePatch::default() }),
+			}
+		} else if line.starts_with("@@ ") {
+			let Some(patch) = patches.last_mut() else {
+				return Err(Error::from_reason(format!(
+					"Failed to parse diff: line {line_no}: hunk without a file header"
+				)));
+			};
+			let (old_start, old_len, new_start, new_len) =
+				parse_hunk_header(line).ok_or_else(|| {
+					Error::from_reason(format!(
+						"Failed to parse diff: line {line_no}: invalid hunk header"
+					))
+				})?;
+			let mut hunk =
+				Hunk { old_start, old_len, new_start, lines: Vec::new(), new_no_eol: false };
+			let (mut old_left, mut new_left) = (old_len, new_len);
+			while let Some(&(_, body)) = lines.peek() {
+				if body.starts_with('\\') {
+					// "\ No newline at end of file" applies to the line before it.
+					if matches!(hunk.lines.last(), Some(HunkLine::Context(_) | HunkLine::Add(_))) {
+						hunk.new_no_eol = true;
+					}
+					lines.next();
+					continue;
+				}
+				if old_left == 0 && new_left == 0 {
+					break;
+				}
+				let (tag, text) = body.split_at(body.len().min(1));
+				let hunk_line = match tag {
+					" " | "" if old_left > 0 && new_left > 0 => {
+						old_left -= 1;
+						new_left -= 1;
+						HunkLine::Context(text.to_string())
+					},
+					"-" if old_left > 0 => {
+						old_left -= 1;
+						HunkLine::Remove(text.to_string())
+					},
+					"+" if new_left > 0 => {
+						new_left -= 1;
+						HunkLine::Add(text.to_string())
+					},
+					_ => break,
+				};
+				hunk.lines.push(hunk_line);
+				lines.next();
+			}
+			if old_left > 0 || new_left > 0 {
+				return Err(Error::from_reason(format!(
+					"Failed to parse diff: line {line_no}: hunk is shorter than its header"
+				)));
+			}
+			patch.hunks.push(hunk);
+		} else if let Some(patch) = patches.last_mut() {
+			if let Some(path) = line.strip_prefix("rename from ") {
+				patch.old_path = Some(path.to_string());
+			} else if let Some(path) = line.strip_prefix("rename to ") {
+				patch.new_path = Some(path.to_string());
+			} else if line.starts_with("new file mode ") {
+				patch.old_path = None;
+			} else if line.starts_with("deleted file mode ") {
+				patch.new_path = None;
+			} else if line.starts_with("GIT binary patch") || line.starts_with("Binary files ") {
+				patch.binary = true;
+			}
+		}
+	}
+	Ok(patches)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Application
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// File contents split into lines, each with its own line ending.
+struct Text {
+	lines:   Vec<String>,
+	/// Ending of each line; empty for a last line without one.
+	endings: Vec<&'static str>,
+	/// Dominant line ending, for added lines.
+	eol:     &'static str,
+}
+
+impl Text {
+	fn parse(content: &str) -> Self {
+		let eol = if text_profile::detect(content, UTF_8, false).eol == "crlf" {
+			"\r\n"
+		} else {
+			"\n"
+		};
+		let (lines, endings) = content
+			.split_inclusive('\n')
+			.map(|line| {
+				if let Some(line) = line.strip_suffix("\r\n") {
+					(line.to_string(), "\r\n")
+				} else if let Some(line) = line.strip_suffix('\n') {
+					(line.to_string(), "\n")
+				} else {
+					(line.to_string(), "")
+				}
+			})
+			.unzip();
+		Self { lines, endings, eol }
+	}
+
+	/// End the last line with a line ending, or with none.
+	fn set_final_newline(&mut self, final_newline: bool) {
+		if let Some(ending) = self.endings.last_mut() {
+			if !final_newline {
+				*ending = "";
+			} else if ending.is_empty() {
+				*ending = self.eol;
+			}
+		}
+	}
+
+	fn render(&self) -> String {
+		let mut out = String::new();
+		for (index, (line, ending)) in self.lines.iter().zip(&self.endings).enumerate() {
+			out.push_str(line);
+			// A former last line followed by added lines needs an ending.
+			out.push_str(if ending.is_empty() && index + 1 < self.lines.len() {
+				self.eol
+			} else {
+				ending
+			});
+		}
+		out
+	}
+}
+
+fn lines_match(file: &[String], expected: &[&str]) -> bool {
+	file.len() == expected.len()
+		&& file.iter().zip(expected).all(|(have, want)| {
+			have == want || have.as_str() == want.strip_suffix('\r').unwrap_or(want)
+		})
+}
+
+/// Find `needle` in `lines` at or after `min_start`, nearest to `expected`.
+fn locate(lines: &[String], needle: &[&str], expected: usize, min_start: usize) -> Option<usize> {
+	let last = lines.len().checked_sub(needle.len())?;
+	if min_start > last {
+		return None;
+	}
+	let expected = expected.clamp(min_start, last);
+	let fits = |pos: usize| lines_match(&lines[pos..pos + needle.len()], needle);
+	for distance in 0..=(last - min_start) {
+		let after = expected + distance;
+		if after <= last && fits(after) {
+			return Some(after);
+		}
+		if let Some(before) = expected.checked_sub(distance)
+			&& distance > 0
+			&& before >= min_start
+			&& fits(before)
+		{
+			return Some(before);
+		}
+	}
+	None
+}
+
+/// Tracks positions while hunks are applied to a [`Text`] in order.
+struct HunkApplier {
+	fuzz:        u32,
+	/// Net lines added by the hunks applied so far.
+	delta:       isize,
+	/// First line a later hunk may touch, in current coordinates.
+	min_start:   usize,
+	/// Whether the file should end with a newline, once a hunk has
+	/// rewritten its last line.
+	touched_end: Option<bool>,
+}
+
+impl HunkApplier {
+	fn apply(&mut self, text: &mut Text, hunk: &Hunk) -> HunkResult {
+		let mut result = HunkResult {
+			old_start: hunk.old_start,
+			new_start: hunk.new_start,
+			applied:   false,
+			line:      None,
+			offset:    0,
+			fuzz:      0,
+			error:     None,
+		};
+		let old: Vec<&str> = hunk
+			.lines
+			.iter()
+			.filter_map(|line| match line {
+				HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+				HunkLine::Add(_) => None,
+			})
+			.collect();
+		let leading = hunk
+			.lines
+			.iter()
+			.take_while(|line| matches!(line, HunkLine::Context(_)))
+			.count();
+		let trailing = hunk
+			.lines
+			.iter()
+			.rev()
+			.take_while(|line| matches!(line, HunkLine::Context(_)))
+			.count();
+
+		// A zero-length old range names the line *after which* to insert.
+		let header_pos = if hunk.old_len == 0 {
+			hunk.old_start
+		} else {
+			hunk.old_start.saturating_sub(1)
+		};
+		let header_pos = header_pos as usize;
+
+		let mut prev_trim = None;
+		for fuzz in 0..=self.fuzz as usize {
+			let trim = (fuzz.min(leading), fuzz.min(trailing));
+			if prev_trim == Some(trim) {
+				break;
+			}
+			prev_trim = Some(trim);
+			let (head, tail) = trim;
+			if head + tail > old.len() || (head + tail == old.len() && !old.is_empty()) {
+				break;
+			}
+			let needle = &old[head..old.len() - tail];
+			let expected = (header_pos + head).saturating_add_signed(self.delta);
+			let Some(pos) = locate(&text.lines, needle, expected, self.min_start) else {
+				continue;
+			};
+
+			// Context lines keep the file's text and ending; added lines take
+			// the ending of the line they replace, or else the dominant one.
+			let mut old_index = pos;
+			let mut removed = VecDeque::new();
+			let (replacement, endings): (Vec<String>, Vec<&str>) = hunk.lines
+				[head..hunk.lines.len() - tail]
+				.iter()
+				.filter_map(|line| match line {
+					HunkLine::Context(_) => {
+						let kept = (text.lines[old_index].clone(), text.endings[old_index]);
+						old_index += 1;
+						Some(kept)
+					},
+					HunkLine::Remove(_) => {
+						removed.push_back(text.endings[old_index]);
+						old_index += 1;
+						None
+					},
+					HunkLine::Add(line) => {
+						let ending = removed
+							.pop_front()
+							.filter(|ending: &&str| !ending.is_empty())
+							.unwrap_or(text.eol);
+						Some((line.strip_suffix('\r').unwrap_or(line).to_string(), ending))
+					},
+				})
+				.unzip();
+			let reaches_end = pos + needle.len() == text.lines.len();
+			let added = replacement.len();
+			text.lines.splice(pos..pos + needle.len(), replacement);
+			text.endings.splice(pos..pos + needle.len(), endings);
+			// A context line keeps its ending; only a changed last line takes
+			// the hunk's.
+			let changes_end = !matches!(hunk.lines.last(), Some(HunkLine::Context(_)));
+			if reaches_end && tail == 0 && changes_end {
+				self.touched_end = Some(!hunk.new_no_eol);
+			}
+
+			let original_pos = pos.saturating_add_signed(-self.delta);
+			result.applied = true;
+			// Trimmed context may lie before the start of the file.
+			result.line = Some((original_pos + 1).saturating_sub(head).max(1) as u32);
+			result.offset = (original_pos as i64 - (header_pos + head) as i64) as i32;
+			result.fuzz = fuzz as u32;
+			self.delta += added as isize - needle.len() as isize;
+			self.min_start = pos + added;
+			return result;
+		}
+		result.error = Some(if old.is_empty() {
+			"insertion point is past the end of the file".to_string()
+		} else {
+			"context not found".to_string()
+		});
+		result
+	}
+}
+
+/// Resolve `relative` under `root`, rejecting absolute paths, `..` and
+/// symlinks leading out of `root` or the workspace.
+fn resolve_path(
+	root: &Path,
+	relative: &str,
+	allow_outside: Option<bool>,
+) -> std::result::Result<PathBuf, String> {
+	let outside = || format!("refusing to patch path outside the root: {relative}");
+	let path = Path::new(relative);
+	if path
+		.components()
+		.any(|part| !matches!(part, Component::Normal(_) | Component::CurDir))
+	{
+		return Err(outside());
+	}
+	let path = root.join(path);
+	let resolved = workspace::resolve(&path).map_err(|err| err.reason.clone())?;
+	let resolved_root = workspace::resolve(root).map_err(|err| err.reason.clone())?;
+	if !resolved.starts_with(resolved_root) {
+		return Err(outside());
+	}
+	workspace::check(&resolved, allow_outside).map_err(|err| err.reason.clone())?;
+	Ok(path)
+}
+
+/// Contents of files as the patch sees them, including earlier file patches.
+/// `None` marks a file deleted by the patch.
+struct Staged {
+	root:          PathBuf,
+	allow_outside: Option<bool>,
+	files:         HashMap<PathBuf, Option<String>>,
+	order:         Vec<PathBuf>,
+	/// Encoding and BOM of files read from disk, to write them back with.
+	encodings:     HashMap<PathBuf, (&'static Encoding, bool)>,
+}
+
+impl Staged {
+	fn read(&mut self, path: &Path) -> std::result::Result<Option<String>, String> {
+		if let Some(content) = self.files.get(path) {
+			return Ok(content.clone());
+		}
+		if !path.exists() {
+			return Ok(None);
+		}
+		let bytes =
+			fs::read(path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+		let (text, encoding, bom) = read::decode(&bytes).ok_or("file is binary")?;
+		self.encodings.insert(path.to_path_buf(), (encoding, bom));
+		Ok(Some(text))
+	}
+
+	fn stage(&mut self, path: PathBuf, content: Option<String>) {
+		if self.files.insert(path.clone(), content).is_none() {
+			self.order.push(path);
+		}
+	}
+
+	/// Stage a rename, carrying the encoding over to `target`.
+	fn stage_rename(&mut self, source: PathBuf, target: PathBuf, content: Option<String>) {
+		if let Some(&encoding) = self.encodings.get(&source) {
+			self.encodings.insert(target.clone(), encoding);
+		}
+		self.stage(source, None);
+		self.stage(target, content);
+	}
+
+	fn commit(self) -> Result<()> {
+		let Self { mut files, order, encodings, .. } = self;
+		// Encode everything first, so an unencodable file writes nothing.
+		let mut writes = Vec::with_capacity(order.len());
+		for path in order {
+			let bytes = match files.remove(&path).flatten() {
+				Some(content) => {
+					let (encoding, bom) = encodings.get(&path).copied().unwrap_or((UTF_8, false));
+					let bytes = text_profile::encode(&content, encoding, bom).map_err(|err| {
+						Error::from_reason(format!("Failed to encode {}: {err}", path.display()))
+					})?;
+					Some(bytes)
+				},
+				None => None,
+			};
+			writes.push((path, bytes));
+		}
+		for (path, bytes) in writes {
+			match bytes {
+				Some(bytes) => {
+					if let Some(parent) = path.parent() {
+						fs::create_dir_all(parent).map_err(|err| {
+							Error::from_reason(format!("Failed to create {}: {err}", parent.display()))
+						})?;
+					}
+					fs::write(&path, bytes).map_err(|err| {
+						Error::from_reason(format!("Failed to write {}: {err}", path.display()))
+					})?;
+				},
+				None if path.exists() => fs::remove_file(&path).map_err(|err| {
+					Error::from_reason(format!("Failed to delete {}: {err}", path.display()))
+				})?,
+				None => {},
+			}
+		}
+		Ok(())
+	}
+}
+
+fn apply_file(staged: &mut Staged, patch: &FilePatch, fuzz: u32) -> FilePatchResult {
+	let kind = patch.kind();
+	let path = patch
+		.new_path
+		.clone()
+		.or_else(|| patch.old_path.clone())
+		.unwrap_or_default();
+	let mut result = FilePatchResult {
+		path,
+		old_path: (kind == FilePatchKind::Rename)
+			.then(|| patch.old_path.clone())
+			.flatten(),
+		kind,
+		applied: false,
+		hunks: Vec::new(),
+		content: None,
+		error: None,
+	};
+	if let Err(err) = apply_file_inner(staged, patch, fuzz, &mut result) {
+		result.error = Some(err);
+	}
+	result
+}
+
+fn apply_file_inner(
+	staged: &mut Staged,
+	patch: &FilePatch,
+	fuzz: u32,
+	result: &mut FilePatchResult,
+) -> std::result::Result<(), String> {
+	if patch.binary {
+		return Err("binary patches are not supported".to_string());
+	}
+	let target = resolve_path(&staged.root, &result.path, staged.allow_outside)?;
+	let source = match &patch.old_path {
+		Some(old) => resolve_path(&staged.root, old, staged.allow_outside)?,
+		None => target.clone(),
+	};
+
+	let original = staged.read(&source)?;
+	let original = match (result.kind, original) {
+		(FilePatchKind::Create, Some(_)) => return Err("file already exists".to_string()),
+		(FilePatchKind::Create, None) => String::new(),
+		(_, None) => return Err("file not found".to_string()),
+		(_, Some(content)) => content,
+	};
+	if result.kind == FilePatchKind::Rename && staged.read(&target)?.is_some() {
+		return Err("rename target already exists".to_string());
+	}
+
+	let mut text = Text::parse(&original);
+	let mut applier = HunkApplier { fuzz, delta: 0, min_start: 0, touched_end: None };
+	result.hunks = patch
+		.hunks
+		.iter()
+		.map(|hunk| applier.apply(&mut text, hunk))
+		.collect();
+	if let Some(final_newline) = applier.touched_end {
+		text.set_final_newline(final_newline);
+	}
+	let all_applied = result.hunks.iter().all(|hunk| hunk.applied);
+
+	if result.kind == FilePatchKind::Delete {
+		if all_applied && !text.lines.is_empty() {
+			return Err("file is not empty after applying the deletion".to_string());
+		}
+	} else {
+		result.content = Some(text.render());
+	}
+	if !all_applied {
+		return Ok(());
+	}
+
+	match result.kind {
+		FilePatchKind::Delete => staged.stage(source, None),
+		FilePatchKind::Rename => staged.stage_rename(source, target, result.content.clone()),
+		FilePatchKind::Create | FilePatchKind::Modify => staged.stage(target, result.content.clone()),
+	}
+	result.applied = true;
+	Ok(())
+}
+
+/// Apply a unified diff to files under `root`.
+///
+/// Every file and hunk gets a result; files are only written when all of
+/// them apply and `dryRun` is not set.
+///
+/// # Errors
+/// Returns an error if the diff cannot be parsed or a file cannot be written.
+#[napi(js_name = "applyUnifiedDiff")]
+pub fn apply_unified_diff(options: ApplyDiffOptions) -> task::Async<ApplyDiffResult> {
+	let ApplyDiffOptions { diff, root, fuzz, dry_run, operation_id, allow_outside_workspace } =
+		options;
+	task::blocking("apply_unified_diff", (), move |_| {
+		workspace::check(&root, allow_outside_workspace)?;
+		let patches = parse_diff(&diff)?;
+		let mut staged = Staged {
+			root:          PathBuf::from(root),
+			allow_outside: allow_outside_workspace,
+			files:         HashMap::new(),
+			order:         Vec::new(),
+			encodings:     HashMap::new(),
+		};
+		let files: Vec<FilePatchResult> = patches
+			.iter()
+			.map(|patch| apply_file(&mut staged, patch, fuzz.unwrap_or(0)))
+			.collect();
+		let applied = files.iter().all(|file| file.applied);
+		if !applied || dry_run.unwrap_or(false) {
+			return Ok(ApplyDiffResult { applied, files, operation_id: None });
+		}
+		let mut journal = journal::Pending::begin(operation_id, "applyUnifiedDiff")?;
+		if let Some(journal) = &mut journal {
+			for path in &staged.order {
+				journal.capture(&workspace::resolve(path)?)?;
+			}
+		}
+		staged.commit()?;
+		let operation_id = journal.map(journal::Pending::finish).transpose()?;
+		Ok(ApplyDiffResult { applied, files, operation_id })
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Apply the hunks of a single-file `diff` to `content`.
+	fn apply(content: &str, diff: &str, fuzz: u32) -> (String, Vec<HunkResult>) {
+		let patches = parse_diff(diff).expect("diff parses");
+		assert_eq!(patches.len(), 1);
+		let mut text = Text::parse(content);
+		let mut applier = HunkApplier { fuzz, delta: 0, min_start: 0, touched_end: None };
+		let results = patches[0]
+			.hunks
+			.iter()
+			.map(|hunk| applier.apply(&mut text, hunk))
+			.collect();
+		if let Some(final_newline) = applier.touched_end {
+			text.set_final_newline(final_newline);
+		}
+		(text.render(), results)
+	}
+
+	const DIFF: &str = "--- a/f\n+++ b/f\n@@ -2,3 +2,3 @@\n b\n-c\n+C\n d\n";
+
+	#[test]
+	fn test_exact_match() {
+		let (out, results) = apply("a\nb\nc\nd\ne\n", DIFF, 0);
+		assert_eq!(out, "a\nb\nC\nd\ne\n");
+		assert!(results[0].applied);
+		assert_eq!(results[0].line, Some(2));
+		assert_eq!(results[0].offset, 0);
+		assert_eq!(results[0].fuzz, 0);
+	}
+
+	#[test]
+	fn test_offset() {
+		let (out, results) = apply("x\ny\nz\na\nb\nc\nd\ne\n", DIFF, 0);
+		assert_eq!(out, "x\ny\nz\na\nb\nC\nd\ne\n");
+		assert_eq!(results[0].line, Some(5));
+		assert_eq!(results[0].offset, 3);
+	}
+
+	#[test]
+	fn test_offset_prefers_nearest_match() {
+		let (out, results) =
+			apply("b\nc\nd\nb\nc\nd\n", "--- a/f\n+++ b/f\n@@ -4,3 +4,3 @@\n b\n-c\n+C\n d\n", 0);
+		assert_eq!(out, "b\nc\nd\nb\nC\nd\n");
+		assert_eq!(results[0].offset, 0);
+	}
+
+	#[test]
+	fn test_fuzz() {
+		let content = "a\nB\nc\nD\ne\n";
+		let (out, results) = apply(content, DIFF, 0);
+		assert_eq!(out, content);
+		assert!(!results[0].applied);
+		assert_eq!(results[0].error.as_deref(), Some("context not found"));
+
+		let (out, results) = apply(content, DIFF, 1);
+		assert_eq!(out, "a\nB\nC\nD\ne\n");
+		assert!(results[0].applied);
+		assert_eq!(results[0].fuzz, 1);
+	}
+
+	#[test]
+	fn test_fuzz_at_start_of_file() {
+		let diff = "--- a/f\n+++ b/f\n@@ -1,4 +1,4 @@\n p\n q\n-a\n+A\n b\n";
+		let (out, results) = apply("a\nb\n", diff, 2);
+		assert_eq!(out, "A\nb\n");
+		assert!(results[0].applied);
+		assert_eq!(results[0].line, Some(1));
+		assert_eq!(results[0].fuzz, 2);
+	}
+
+	#[test]
+	fn test_later_hunks_follow_earlier_ones() {
+		let diff = "--- a/f\n+++ b/f\n@@ -1,2 +1,3 @@\n a\n+a2\n b\n@@ -4,2 +5,2 @@\n d\n-e\n+E\n";
+		let (out, results) = apply("a\nb\nc\nd\ne\n", diff, 0);
+		assert_eq!(out, "a\na2\nb\nc\nd\nE\n");
+		assert!(
+			results
+				.iter()
+				.all(|result| result.applied && result.offset == 0)
+		);
+	}
+
+	#[test]
+	fn test_line_endings_preserved() {
+		let (out, _) = apply("a\r\nb\r\nc\r\nd\r\n", DIFF, 0);
+		assert_eq!(out, "a\r\nb\r\nC\r\nd\r\n");
+		let (out, _) = apply("a\nb\nc\nd", DIFF, 0);
+		assert_eq!(out, "a\nb\nC\nd");
+	}
+
+	#[test]
+	fn test_mixed_line_endings_kept() {
+		let (out, _) = apply("a\r\nb\nc\r\nd\r\ne\n", DIFF, 0);
+		assert_eq!(out, "a\r\nb\nC\r\nd\r\ne\n");
+		let diff = "--- a/f\n+++ b/f\n@@ -2,2 +2,3 @@\n b\n+b2\n c\n";
+		let (out, _) = apply("a\nb\r\nc\r\nd\n", diff, 0);
+		assert_eq!(out, "a\nb\r\nb2\nc\r\nd\n");
+		let (out, _) = apply("a\nb\nc\r\nd\n", DIFF, 0);
+		assert_eq!(out, "a\nb\nC\r\nd\n");
+	}
+
+	#[test]
+	fn test_final_newline() {
+		let append = "--- a/f\n+++ b/f\n@@ -2 +2,2 @@\n b\n+c\n";
+		let (out, _) = apply("a\r\nb", append, 0);
+		assert_eq!(out, "a\r\nb\r\nc\r\n");
+		let strip = "--- a/f\n+++ b/f\n@@ -2 +2 @@\n-b\n+B\n\\ No newline at end of file\n";
+		let (out, _) = apply("a\nb\n", strip, 0);
+		assert_eq!(out, "a\nB");
+	}
+}
@@ -0,0 +1,310 @@
+//! Text extraction from PDF files without `pdftotext`.
+//!
+//! # Overview
+//! Pages are parsed with `pdf-extract` and their glyphs laid out into lines
+//! by position: a vertical jump larger than the font size starts a new line,
+//! a larger one a new paragraph, and horizontal gaps become spaces. Each
+//! page also reports layout hints: its size, the font size of its body text
+//! and the lines set in a larger font, which are usually headings.
+//!
+//! Pages that fail to parse are reported with an `error` instead of failing
+//! the whole document.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.extractPdfText("docs/design.pdf", { pages: [1, 2], maxPages: 20 })
+//! ```
+
+use std::{
+	collections::HashMap,
+	panic::{self, AssertUnwindSafe},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use pdf_extract::{Document, MediaBox, OutputDev, OutputError, Transform};
+
+use crate::{task, workspace};
+
+/// Lines whose font is at least this much larger than the body text are
+/// reported as headings.
+const HEADING_SCALE: f64 = 1.15;
+
+/// Longer lines are body text set large, not headings.
+const MAX_HEADING_CHARS: usize = 200;
+
+/// Options for `extractPdfText`.
+#[napi(object)]
+pub struct PdfTextOptions<'env> {
+	/// 1-based page numbers to extract (default: all pages).
+	pub pages:                   Option<Vec<u32>>,
+	/// Extract at most this many pages; the rest are reported as `truncated`.
+	#[napi(js_name = "maxPages")]
+	pub max_pages:               Option<u32>,
+	/// Abort signal for cancelling the operation.
+	pub signal:                  Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the operation.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:              Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Text and layout hints of one PDF page.
+#[napi(object)]
+pub struct PdfPage {
+	/// 1-based page number.
+	pub page:      u32,
+	/// Page text, with lines separated by `\n` and paragraphs by a blank line.
+	pub text:      String,
+	/// Page width in points.
+	pub width:     f64,
+	/// Page height in points.
+	pub height:    f64,
+	/// Most common font size on the page, in points (0 for pages without
+	/// text).
+	#[napi(js_name = "fontSize")]
+	pub font_size: f64,
+	/// Lines set in a noticeably larger font than the body text, in page
+	/// order.
+	pub headings:  Vec<String>,
+	/// Why the page could not be parsed; `text` is empty when set.
+	pub error:     Option<String>,
+}
+
+/// Result of `extractPdfText`.
+#[napi(object)]
+pub struct PdfTextResult {
+	/// Total pages in the document.
+	#[napi(js_name = "pageCount")]
+	pub page_count: u32,
+	/// Extracted pages, in page order.
+	pub pages:      Vec<PdfPage>,
+	/// Whether requested pages were left out because of `maxPages`.
+	pub truncated:  bool,
+}
+
+/// A laid-out line of text.
+struct Line {
+	text:      String,
+	font_size: f64,
+	/// Whether a paragraph gap precedes the line.
+	gap:       bool,
+}
+
+/// Collects the glyphs of a page into lines.
+#[derive(Default)]
+struct PageCollector {
+	width:      f64,
+	height:     f64,
+	lines:      Vec<Line>,
+	current:    String,
+	size:       f64,
+	gap:        bool,
+	last_end:   f64,
+	last_y:     f64,
+	word_start: bool,
+	/// Characters per font size, in tenths of a point.
+	sizes:      HashMap<u32, usize>,
+}
+
+impl PageCollector {
+	fn end_line(&mut self, gap: bool) {
+		let text = self.current.trim_end();
+		if text.trim_start().is_empty() {
+			self.gap |= gap;
+		} else {
+			self.lines.push(Line {
+				text:      text.to_string(),
+				font_size: self.size,
+				gap:       self.gap,
+			});
+			self.gap = gap;
+		}
+		self.current.clear();
+		self.size = 0.0;
+	}
+
+	fn into_page(mut self, page: u32) -> PdfPage {
+		self.end_line(false);
+		let font_size = self
+			.sizes
+			.iter()
+			.max_by_key(|&(size, count)| (count, size))
+			.map_or(0.0, |(&size, _)| f64::from(size) / 10.0);
+
+		let mut text = String::new();
+		let mut headings = Vec::new();
+		for line in self.lines {
+			if !text.is_empty() {
+				text.push_str(if line.gap { "\n\n" } else { "\n" });
+			}
+			text.push_str(&line.text);
+			let heading = font_size > 0.0
+				&& line.font_size >= font_size * HEADING_SCALE
+				&& line.text.chars().count() <= MAX_HEADING_CHARS
+				&& line.text.chars().any(char::is_alphabetic);
+			if heading {
+				headings.push(line.text.trim().to_string());
+			}
+		}
+		PdfPage {
+			page,
+			text,
+			width: self.width,
+			height: self.height,
+			font_size,
+			headings,
+			error: None,
+		}
+	}
+}
+
+impl OutputDev for PageCollector {
+	fn begin_page(
+		&mut self,
+		_page_num: u32,
+		media_box: &MediaBox,
+		_art_box: Option<(f64, f64, f64, f64)>,
+	) -> std::result::Result<(), OutputError> {
+		self.width = media_box.urx - media_box.llx;
+		self.height = media_box.ury - media_box.lly;
+		self.last_end = f64::INFINITY;
+		Ok(())
+	}
+
+	fn end_page(&mut self) -> std::result::Result<(), OutputError> {
+		Ok(())
+	}
+
+	fn output_character(
+		&mut self,
+		trm: &Transform,
+		width: f64,
+		_spacing: f64,
+		font_size: f64,
+		char: &str,
+	) -> std::result::Result<(), OutputError> {
+		// Flip to top-down coordinates and scale the font size by the text
+		// matrix.
+		let (x, y) = (trm.m31, self.height - trm.m32);
+		let size = (font_size * (trm.m11 + trm.m21) * font_size * (trm.m12 + trm.m22))
+			.abs()
+			.sqrt();
+		if self.word_start {
+			let dy = (y - self.last_y).abs();
+			if dy > size * 1.5 {
+				self.end_line(true);
+			} else if x < self.last_end && dy > size * 0.5 {
+				self.end_line(false);
+			} else if x > size.mul_add(0.1, self.last_end) && !self.current.is_empty() {
+				self.current.push(' ');
+			}
+		}
+		self.current.push_str(char);
+		self.size = self.size.max(size);
+		let tenths = (size * 10.0).round() as u32;
+		*self.sizes.entry(tenths).or_default() += char.chars().count();
+		self.word_start = false;
+		self.last_y = y;
+		self.last_end = width.mul_add(size, x);
+		Ok(())
+	}
+
+	fn begin_word(&mut self) -> std::result::Result<(), OutputError> {
+		self.word_start = true;
+		Ok(())
+	}
+
+	fn end_word(&mut self) -> std::result::Result<(), OutputError> {
+		Ok(())
+	}
+
+	fn end_line(&mut self) -> std::result::Result<(), OutputError> {
+		Ok(())
+	}
+}
+
+fn extract_page(doc: &Document, page: u32) -> PdfPage {
+	let mut collector = PageCollector::default();
+	// The parser panics on some malformed content; keep the other pages.
+	let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+		pdf_extract::output_doc_page(doc, &mut collector, page)
+	}));
+	let error = match outcome {
+		Ok(Ok(())) => return collector.into_page(page),
+		Ok(Err(err)) => err.to_string(),
+		Err(_) => "malformed page content".to_string(),
+	};
+	PdfPage {
+		page,
+		text: String::new(),
+		width: collector.width,
+		height: collector.height,
+		font_size: 0.0,
+		headings: Vec::new(),
+		error: Some(error),
+	}
+}
+
+fn extract_pdf_text_sync(
+	path: &str,
+	pages: Option<Vec<u32>>,
+	max_pages: Option<u32>,
+	ct: &task::CancelToken,
+) -> Result<PdfTextResult> {
+	let mut doc = Document::load(path)
+		.map_err(|err| Error::from_reason(format!("Failed to read PDF {path}: {err}")))?;
+	if doc.is_encrypted() && doc.decrypt("").is_err() {
+		return Err(Error::from_reason(format!("PDF is password-protected: {path}")));
+	}
+	let page_count = u32::try_from(doc.get_pages().len()).unwrap_or(u32::MAX);
+
+	let mut selected = match pages {
+		Some(mut pages) => {
+			pages.sort_unstable();
+			pages.dedup();
+			if let Some(&page) = pages.iter().find(|&&page| page == 0 || page > page_count) {
+				return Err(Error::from_reason(format!(
+					"Page {page} out of range (document has {page_count} pages)"
+				)));
+			}
+			pages
+		},
+		None => (1..=page_count).collect(),
+	};
+	let max_pages = max_pages.map_or(usize::MAX, |max| max as usize);
+	let truncated = selected.len() > max_pages;
+	selected.truncate(max_pages);
+
+	let mut extracted = Vec::with_capacity(selected.len());
+	for page in selected {
+		ct.heartbeat()?;
+		extracted.push(extract_page(&doc, page));
+	}
+	Ok(PdfTextResult { page_count, pages: extracted, truncated })
+}
+
+/// Extract the text of a PDF file page by page, with layout hints.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or is not a PDF, it is outside
+/// the workspace, it is password-protected, a requested page does not exist, or
+/// on abort or timeout. Pages that fail to parse carry an `error` instead.
+#[napi(js_name = "extractPdfText")]
+pub fn extract_pdf_text(
+	path: String,
+	options: Option<PdfTextOptions>,
+) -> task::Async<PdfTextResult> {
+	let (pages, max_pages, allow_outside, ct) = match options {
+		Some(PdfTextOptions { pages, max_pages, signal, timeout_ms, allow_outside_workspace }) => {
+			(pages, max_pages, allow_outside_workspace, task::CancelToken::new(timeout_ms, signal))
+		},
+		None => (None, None, None, task::CancelToken::default()),
+	};
+	task::blocking("extract_pdf_text", ct, move |ct| {
+		workspace::check(&path, allow_outside)?;
+		extract_pdf_text_sync(&path, pages, max_pages, &ct)
+	})
+}
@@ -0,0 +1,847 @@
+//! Workspace language, framework and tooling detection.
+//!
+//! # Overview
+//! `detectProject` reads the manifests, lockfiles and CI configuration at a
+//! project root, and the manifests of its workspace members, to report:
+//! - languages, frameworks and package managers in use
+//! - build, test, lint, format and typecheck commands, from package scripts,
+//!   ecosystem conventions and Makefile targets
+//! - CI systems and the monorepo layout
+//!
+//! Only known files are read; nothing is executed.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const project = await native.detectProject(".")
+//! // JS: project.commands // [{ kind: "test", command: "cargo test --workspace", source: "Cargo.toml" }, ...]
+//! ```
+
+use std::path::Path;
+
+use globset::{Glob, GlobSetBuilder};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item};
+
+use crate::{fs_cache, task};
+
+/// Deepest directory level searched for workspace members.
+const MAX_MEMBER_DEPTH: usize = 4;
+
+/// Dependencies that identify a framework, by ecosystem.
+const JS_FRAMEWORKS: &[(&str, &str)] = &[
+	("next", "next"),
+	("nuxt", "nuxt"),
+	("@remix-run/react", "remix"),
+	("@sveltejs/kit", "sveltekit"),
+	("astro", "astro"),
+	("react", "react"),
+	("vue", "vue"),
+	("svelte", "svelte"),
+	("solid-js", "solid"),
+	("@angular/core", "angular"),
+	("express", "express"),
+	("fastify", "fastify"),
+	("hono", "hono"),
+	("@nestjs/core", "nestjs"),
+	("electron", "electron"),
+	("vite", "vite"),
+	("vitest", "vitest"),
+	("jest", "jest"),
+	("mocha", "mocha"),
+	("@playwright/test", "playwright"),
+	("tailwindcss", "tailwind"),
+];
+const RUST_FRAMEWORKS: &[(&str, &str)] = &[
+	("tokio", "tokio"),
+	("axum", "axum"),
+	("actix-web", "actix-web"),
+	("rocket", "rocket"),
+	("warp", "warp"),
+	("tauri", "tauri"),
+	("bevy", "bevy"),
+	("leptos", "leptos"),
+	("napi", "napi-rs"),
+	("wasm-bindgen", "wasm-bindgen"),
+	("clap", "clap"),
+];
+const PYTHON_FRAMEWORKS: &[(&str, &str)] = &[
+	("django", "django"),
+	("flask", "flask"),
+	("fastapi", "fastapi"),
+	("starlette", "starlette"),
+	("pytest", "pytest"),
+	("numpy", "numpy"),
+	("pandas", "pandas"),
+	("torch", "pytorch"),
+	("tensorflow", "tensorflow"),
+];
+const GO_FRAMEWORKS: &[(&str, &str)] = &[
+	("github.com/gin-gonic/gin", "gin"),
+	("github.com/labstack/echo", "echo"),
+	("github.com/gofiber/fiber", "fiber"),
+	("github.com/go-chi/chi", "chi"),
+	("github.com/spf13/cobra", "cobra"),
+];
+
+/// Package script names and the command kind they provide.
+const SCRIPT_KINDS: &[(&str, &str)] = &[
+	("build", "build"),
+	("test", "test"),
+	("lint", "lint"),
+	("format", "format"),
+	("fmt", "format"),
+	("typecheck", "typecheck"),
+	("type-check", "typecheck"),
+	("dev", "dev"),
+];
+
+/// CI configuration paths and the system they belong to.
+const CI_FILES: &[(&str, &str)] = &[
+	(".github/workflows", "github-actions"),
+	(".gitlab-ci.yml", "gitlab-ci"),
+	(".circleci/config.yml", "circleci"),
+	("azure-pipelines.yml", "azure-pipelines"),
+	("Jenkinsfile", "jenkins"),
+	(".travis.yml", "travis"),
+	(".buildkite", "buildkite"),
+	("bitbucket-pipelines.yml", "bitbucket-pipelines"),
+];
+
+/// A command for working on the project.
+#[napi(object)]
+pub struct ProjectCommand {
+	/// What the command does: "build", "test", "lint", "format",
+	/// "typecheck" or "dev".
+	pub kind:    String,
+	/// Shell command, run from the project root.
+	pub command: String,
+	/// Manifest the command comes from, relative to the root.
+	pub source:  String,
+}
+
+/// A package in a monorepo.
+#[napi(object)]
+pub struct WorkspacePackage {
+	/// Package name from its manifest, when declared.
+	pub name:     Option<String>,
+	/// Directory relative to the root, using forward slashes.
+	pub path:     String,
+	/// Manifest file name (e.g. "Cargo.toml", "package.json").
+	pub manifest: String,
+}
+
+/// Monorepo layout.
+#[napi(object)]
+pub struct MonorepoInfo {
+	/// Workspace mechanisms in use (e.g. "cargo", "pnpm", "turbo", "nx").
+	pub tools:    Vec<String>,
+	/// Member packages, sorted by path.
+	pub packages: Vec<WorkspacePackage>,
+}
+
+/// What `detectProject` found.
+#[napi(object)]
+#[derive(Default)]
+pub struct ProjectInfo {
+	/// Languages, those of root manifests first (e.g. "rust", "typescript").
+	pub languages:        Vec<String>,
+	/// Frameworks and notable libraries (e.g. "react", "tokio", "pytest").
+	pub frameworks:       Vec<String>,
+	/// Package managers and build tools (e.g. "cargo", "pnpm", "uv").
+	#[napi(js_name = "packageManagers")]
+	pub package_managers: Vec<String>,
+	/// Commands for working on the project, root manifests first.
+	pub commands:         Vec<ProjectCommand>,
+	/// CI systems configured (e.g. "github-actions").
+	pub ci:               Vec<String>,
+	/// Workspace layout, when the root is a monorepo.
+	pub monorepo:         Option<MonorepoInfo>,
+	/// Manifests and lockfiles found at the root.
+	pub manifests:        Vec<String>,
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+	if !list.iter().any(|existing| existing == value) {
+		list.push(value.to_string());
+	}
+}
+
+struct Detector<'a> {
+	root:         &'a Path,
+	info:         ProjectInfo,
+	/// Workspace member patterns, relative to the root.
+	members:      Vec<String>,
+	member_tools: Vec<String>,
+	/// Package manager of the root `package.json`.
+	js_manager:   Option<String>,
+}
+
+impl Detector<'_> {
+	fn read(&self, dir: &str, name: &str) -> Option<String> {
+		std::fs::read_to_string(self.root.join(dir).join(name)).ok()
+	}
+
+	fn exists(&self, dir: &str, name: &str) -> bool {
+		self.root.join(dir).join(name).exists()
+	}
+
+	fn manifest(&mut self, dir: &str, name: &str) -> bool {
+		let found = self.exists(dir, name);
+		if found && dir.is_empty() {
+			push_unique(&mut self.info.manifests, name);
+		}
+		found
+	}
+
+	fn language(&mut self, language: &str) {
+		push_unique(&mut self.info.languages, language);
+	}
+
+	fn package_manager(&mut self, manager: &str) {
+		push_unique(&mut self.info.package_managers, manager);
+	}
+
+	fn frameworks<'d>(&mut self, known: &[(&str, &str)], deps: impl IntoIterator<Item = &'d str>) {
+		let deps = deps.into_iter().collect::<Vec<_>>();
+		for (dep, framework) in known {
+			if deps
+				.iter()
+				.any(|name| name == dep || name.starts_with(&format!("{dep}/")))
+			{
+				push_unique(&mut self.info.frameworks, framework);
+			}
+		}
+	}
+
+	fn command(&mut self, kind: &str, command: String, source: &str) {
+		if !self
+			.info
+			.commands
+			.iter()
+			.any(|existing| existing.command == command)
+		{
+			self.info.commands.push(ProjectCommand {
+				kind: kind.to_string(),
+				command,
+				source: source.to_string(),
+			});
+		}
+	}
+
+	/// Detect everything declared in `dir`; commands only for the root.
+	fn scan(&mut self, dir: &str) {
+		let root = dir.is_empty();
+		self.rust(dir, root);
+		self.javascript(dir, root);
+		self.python(dir, root);
+		self.go(dir, root);
+		self.others(dir, root);
+	}
+
+	fn rust(&mut self, dir: &str, root: bool) {
+		if !self.manifest(dir, "Cargo.toml") {
+			return;
+		}
+		self.manifest(dir, "Cargo.lock");
+		self.language("rust");
+		self.package_manager("cargo");
+		let Some(doc) = self
+			.read(dir, "Cargo.toml")
+			.and_then(|text| text.parse::<DocumentMut>().ok())
+		else {
+			return;
+		};
+		let workspace = doc.get("workspace");
+		let mut deps = Vec::new();
+		for table in [
+			doc.get("dependencies"),
+			doc.get("dev-dependencies"),
+			workspace.and_then(|workspace| workspace.get("dependencies")),
+		] {
+			if let Some(table) = table.and_then(Item::as_table_like) {
+				deps.extend(table.iter().map(|(name, _)| name.to_string()));
+			}
+		}
+		self.frameworks(RUST_FRAMEWORKS, deps.iter().map(String::as_str));
+
+		if !root {
+			return;
+		}
+		let members = workspace
+			.and_then(|workspace| workspace.get("members"))
+			.and_then(Item::as_array)
+			.map_or_else(Vec::new, |members| {
+				members
+					.iter()
+					.filter_map(|member| member.as_str())
+					.collect::<Vec<_>>()
+			});
+		let flag = if members.is_empty() {
+			""
+		} else {
+			push_unique(&mut self.member_tools, "cargo");
+			self.members.extend(members.iter().map(ToString::to_string));
+			" --workspace"
+		};
+		self.command("build", format!("cargo build{flag}"), "Cargo.toml");
+		self.command("test", format!("cargo test{flag}"), "Cargo.toml");
+		self.command("lint", format!("cargo clippy{flag} --all-targets"), "Cargo.toml");
+		self.command("format", "cargo fmt --all".to_string(), "Cargo.toml");
+	}
+
+	fn javascript(&mut self, dir: &str, root: bool) {
+		if self.manifest(dir, "deno.json") || self.manifest(dir, "deno.jsonc") {
+			self.language("typescript");
+			self.package_manager("deno");
+		}
+		if !self.manifest(dir, "package.json") {
+			return;
+		}
+		let Some(manifest) = self
+			.read(dir, "package.json")
+			.and_then(|text| serde_json::from_str::<Value>(&text).ok())
+		else {
+			return;
+		};
+		let deps = ["dependencies", "devDependencies", "peerDependencies"]
+			.iter()
+			.filter_map(|key| manifest.get(key)?.as_object())
+			.flat_map(|deps| deps.keys().cloned())
+			.collect::<Vec<_>>();
+		// Members of a TypeScript workspace often rely on the root's compiler.
+		let typescript = self.manifest(dir, "tsconfig.json")
+			|| deps.iter().any(|dep| dep == "typescript")
+			|| (!root
+				&& self
+					.info
+					.languages
+					.iter()
+					.any(|language| language == "typescript"));
+		self.language(if typescript {
+			"typescript"
+		} else {
+			"javascript"
+		});
+		self.frameworks(JS_FRAMEWORKS, deps.iter().map(String::as_str));
+
+		// Lockfiles live at the workspace root; members inherit its manager.
+		let declared = manifest
+			.get("packageManager")
+			.and_then(Value::as_str)
+			.and_then(|spec| spec.split('@').next())
+			.filter(|name| !name.is_empty())
+			.map(ToString::to_string);
+		let mut manager = declared;
+		for (lockfile, name) in [
+			("bun.lock", "bun"),
+			("bun.lockb", "bun"),
+			("pnpm-lock.yaml", "pnpm"),
+			("yarn.lock", "yarn"),
+			("package-lock.json", "npm"),
+		] {
+			if self.manifest(dir, lockfile) {
+				manager.get_or_insert_with(|| name.to_string());
+			}
+		}
+		let manager = manager
+			.or_else(|| self.js_manager.clone())
+			.unwrap_or_else(|| "npm".to_string());
+		self.package_manager(&manager);
+
+		if !root {
+			return;
+		}
+		self.js_manager = Some(manager.clone());
+		let workspaces = match manifest.get("workspaces") {
+			Some(Value::Array(patterns)) => Some(patterns),
+			Some(Value::Object(config)) => config.get("packages").and_then(Value::as_array),
+			_ => None,
+		};
+		if let Some(patterns) = workspaces {
+			push_unique(&mut self.member_tools, &manager);
+			self.members.extend(
+				patterns
+					.iter()
+					.filter_map(Value::as_str)
+					.map(ToString::to_string),
+			);
+		}
+		if self.manifest(dir, "pnpm-workspace.yaml")
+			&& let Some(patterns) = self
+				.read(dir, "pnpm-workspace.yaml")
+				.map(|text| yaml_list(&text, "packages"))
+		{
+			push_unique(&mut self.member_tools, "pnpm");
+			self.members.extend(patterns);
+		}
+		for (config, tool) in [("turbo.json", "turbo"), ("nx.json", "nx"), ("lerna.json", "lerna")] {
+			if self.manifest(dir, config) {
+				push_unique(&mut self.member_tools, tool);
+			}
+		}
+
+		if let Some(scripts) = manifest.get("scripts").and_then(Value::as_object) {
+			for (script, kind) in SCRIPT_KINDS {
+				if scripts.contains_key(*script) {
+					self.command(kind, format!("{manager} run {script}"), "package.json");
+				}
+			}
+		}
+	}
+
+	fn python(&mut self, dir: &str, root: bool) {
+		let pyproject = self.manifest(dir, "pyproject.toml");
+		let requirements = self.manifest(dir, "requirements.txt");
+		let setup = self.manifest(dir, "setup.py");
+		let pipfile = self.manifest(dir, "Pipfile");
+		if !(pyproject || requirements || setup || pipfile) {
+			return;
+		}
+		self.language("python");
+		let doc = self
+			.read(dir, "pyproject.toml")
+			.and_then(|text| text.parse::<DocumentMut>().ok());
+		let tool = |name: &str| {
+			doc.as_ref()
+				.and_then(|doc| doc.get("tool")?.get(name))
+				.is_some()
+		};
+
+		let manager = if self.manifest(dir, "uv.lock") || tool("uv") {
+			"uv"
+		} else if self.manifest(dir, "poetry.lock") || tool("poetry") {
+			"poetry"
+		} else if self.manifest(dir, "pdm.lock") || tool("pdm") {
+			"pdm"
+		} else if pipfile {
+			self.manifest(dir, "Pipfile.lock");
+			"pipenv"
+		} else {
+			"pip"
+		};
+		self.package_manager(manager);
+
+		let mut deps = Vec::new();
+		if let Some(doc) = &doc {
+			let project_deps = doc
+				.get("project")
+				.and_then(|project| project.get("dependencies"))
+				.and_then(Item::as_array);
+			deps.extend(
+				project_deps
+					.into_iter()
+					.flat_map(|deps| deps.iter().filter_map(|dep| dep.as_str()))
+					.map(requirement_name),
+			);
+			for groups in [
+				doc.get("project")
+					.and_then(|project| project.get("optional-dependencies")),
+				doc.get("dependency-groups").map(|groups| groups as &Item),
+			] {
+				for (_, group) in groups
+					.and_then(Item::as_table_like)
+					.into_iter()
+					.flat_map(|t| t.iter())
+				{
+					deps.extend(
+						group
+							.as_array()
+							.into_iter()
+							.flat_map(|deps| deps.iter().filter_map(|dep| dep.as_str()))
+							.map(requirement_name),
+					);
+				}
+			}
+			let poetry_deps = doc
+				.get("tool")
+				.and_then(|tool| tool.get("poetry"))
+				.and_then(|poetry| poetry.get("dependencies"))
+				.and_then(Item::as_table_like);
+			deps.extend(
+				poetry_deps
+					.into_iter()
+					.flat_map(|t| t.iter().map(|(name, _)| name.to_lowercase())),
+			);
+		}
+		if let Some(text) = self.read(dir, "requirements.txt") {
+			deps.extend(
+				text
+					.lines()
+					.map(str::trim)
+					.filter(|line| !line.is_empty() && !line.starts_with(['#', '-']))
+					.map(requirement_name),
+			);
+		}
+		self.frameworks(PYTHON_FRAMEWORKS, deps.iter().map(String::as_str));
+
+		if !root {
+			return;
+		}
+		let run = match manager {
+			"pip" => String::new(),
+			manager => format!("{manager} run "),
+		};
+		let source = if pyproject {
+			"pyproject.toml"
+		} else if requirements {
+			"requirements.txt"
+		} else if setup {
+			"setup.py"
+		} else {
+			"Pipfile"
+		};
+		let has = |dep: &str| deps.iter().any(|name| name == dep);
+		if has("pytest")
+			|| tool("pytest")
+			|| self.exists(dir, "pytest.ini")
+			|| self.exists(dir, "tests")
+		{
+			self.command("test", format!("{run}pytest"), source);
+		}
+		if has("ruff") || tool("ruff") || self.exists(dir, "ruff.toml") {
+			self.command("lint", format!("{run}ruff check ."), source);
+			self.command("format", format!("{run}ruff format ."), source);
+		} else if has("black") || tool("black") {
+			self.command("format", format!("{run}black ."), source);
+		}
+		if has("mypy") || tool("mypy") {
+			self.command("typecheck", format!("{run}mypy ."), source);
+		} else if has("pyright") || tool("pyright") {
+			self.command("typecheck", format!("{run}pyright"), source);
+		}
+	}
+
+	fn go(&mut self, dir: &str, root: bool) {
+		if root && self.manifest(dir, "go.work") {
+			push_unique(&mut self.member_tools, "go");
+			if let Some(text) = self.read(dir, "go.work") {
+				self.members.extend(go_work_uses(&text));
+			}
+		}
+		if !self.manifest(dir, "go.mod") {
+			return;
+		}
+		self.manifest(dir, "go.sum");
+		self.language("go");
+		self.package_manager("go");
+		if let Some(text) = self.read(dir, "go.mod") {
+			let requires = text
+				.lines()
+				.map(|line| line.trim().trim_start_matches("require").trim())
+				.filter_map(|line| line.split_whitespace().next())
+				.map(ToString::to_string)
+				.collect::<Vec<_>>();
+			self.frameworks(GO_FRAMEWORKS, requires.iter().map(String::as_str));
+		}
+		if root {
+			self.command("build", "go build ./...".to_string(), "go.mod");
+			self.command("test", "go test ./...".to_string(), "go.mod");
+			self.command("lint", "go vet ./...".to_string(), "go.mod");
+			self.command("format", "gofmt -w .".to_string(), "go.mod");
+		}
+	}
+
+	fn others(&mut self, dir: &str, root: bool) {
+		if self.manifest(dir, "Gemfile") {
+			self.manifest(dir, "Gemfile.lock");
+			self.language("ruby");
+			self.package_manager("bundler");
+			if self
+				.read(dir, "Gemfile")
+				.is_some_and(|text| text.contains("\"rails\"") || text.contains("'rails'"))
+			{
+				push_unique(&mut self.info.frameworks, "rails");
+			}
+			if root && self.exists(dir, "spec") {
+				self.command("test", "bundle exec rspec".to_string(), "Gemfile");
+			}
+		}
+		if self.manifest(dir, "composer.json") {
+			self.manifest(dir, "composer.lock");
+			self.language("php");
+			self.package_manager("composer");
+			if let Some(manifest) = self
+				.read(dir, "composer.json")
+				.and_then(|text| serde_json::from_str::<Value>(&text).ok())
+			{
+				let deps = ["require", "require-dev"]
+					.iter()
+					.filter_map(|key| manifest.get(key)?.as_object())
+					.flat_map(|deps| deps.keys().cloned())
+					.collect::<Vec<_>>();
+				self.frameworks(
+					&[
+						("laravel/framework", "laravel"),
+						("symfony/framework-bundle", "symfony"),
+						("phpunit/phpunit", "phpunit"),
+					],
+					deps.iter().map(String::as_str),
+				);
+			}
+		}
+		if self.manifest(dir, "pom.xml") {
+			self.language("java");
+			self.package_manager("maven");
+			if root {
+				self.command("build", "mvn package".to_string(), "pom.xml");
+				self.command("test", "mvn test".to_string(), "pom.xml");
+			}
+		}
+		for (gradle, language) in [("build.gradle.kts", "kotlin"), ("build.gradle", "java")] {
+			if self.manifest(dir, gradle) {
+				self.language(language);
+				self.package_manager("gradle");
+				if root {
+					let gradlew = if self.exists(dir, "gradlew") {
+						"./gradlew"
+					} else {
+						"gradle"
+					};
+					self.command("build", format!("{gradlew} build"), gradle);
+					self.command("test", format!("{gradlew} test"), gradle);
+				}
+			}
+		}
+		if self.manifest(dir, "mix.exs") {
+			self.language("elixir");
+			self.package_manager("mix");
+			if root {
+				self.command("test", "mix test".to_string(), "mix.exs");
+				self.command("format", "mix format".to_string(), "mix.exs");
+			}
+		}
+		if self.manifest(dir, "Package.swift") {
+			self.language("swift");
+			self.package_manager("swiftpm");
+			if root {
+				self.command("build", "swift build".to_string(), "Package.swift");
+				self.command("test", "swift test".to_string(), "Package.swift");
+			}
+		}
+		let dotnet = std::fs::read_dir(self.root.join(dir))
+			.ok()
+			.and_then(|entries| {
+				entries.flatten().find_map(|entry| {
+					let name = entry.file_name().to_string_lossy().into_owned();
+					let extension = Path::new(&name).extension()?.to_str()?.to_ascii_lowercase();
+					["sln", "csproj", "fsproj"]
+						.contains(&extension.as_str())
+						.then_some((name, extension))
+				})
+			});
+		if let Some((project, extension)) = dotnet {
+			self.manifest(dir, &project);
+			self.language(if extension == "fsproj" {
+				"fsharp"
+			} else {
+				"csharp"
+			});
+			self.package_manager("dotnet");
+			if root {
+				self.command("build", "dotnet build".to_string(), &project);
+				self.command("test", "dotnet test".to_string(), &project);
+			}
+		}
+		if self.manifest(dir, "CMakeLists.txt") {
+			self.language("c++");
+			self.package_manager("cmake");
+		}
+		if root {
+			for makefile in ["Makefile", "justfile"] {
+				let Some(text) = self.read(dir, makefile) else {
+					continue;
+				};
+				push_unique(&mut self.info.manifests, makefile);
+				let runner = if makefile == "Makefile" {
+					"make"
+				} else {
+					"just"
+				};
+				for (target, kind) in SCRIPT_KINDS {
+					let declared = text.lines().any(|line| {
+						line
+							.strip_prefix(target)
+							.is_some_and(|rest| rest.trim_start().starts_with(':') && !rest.contains(":="))
+					});
+					if declared {
+						self.command(kind, format!("{runner} {target}"), makefile);
+					}
+				}
+			}
+		}
+	}
+
+	fn ci(&mut self) {
+		for (path, system) in CI_FILES {
+			if self.root.join(path).exists() {
+				push_unique(&mut self.info.ci, system);
+			}
+		}
+	}
+
+	/// Resolve member patterns to package directories and scan them.
+	fn workspace(&mut self, ct: &task::CancelToken) -> Result<()> {
+		if self.members.is_empty() && self.member_tools.is_empty() {
+			return Ok(());
+		}
+		let mut include = GlobSetBuilder::new();
+		let mut exclude = GlobSetBuilder::new();
+		for pattern in &self.members {
+			let (set, pattern) = match pattern.strip_prefix('!') {
+				Some(negated) => (&mut exclude, negated),
+				None => (&mut include, pattern.as_str()),
+			};
+			let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+			if let Ok(glob) = Glob::new(pattern) {
+				set.add(glob);
+			}
+		}
+		let (Ok(include), Ok(exclude)) = (include.build(), exclude.build()) else {
+			return Ok(());
+		};
+
+		let mut packages = Vec::new();
+		let mut builder = fs_cache::build_walker(self.root, false, true);
+		builder
+			.max_depth(Some(MAX_MEMBER_DEPTH))
+			.filter_entry(|entry| {
+				entry.file_name() != "node_modules" && entry.file_name() != "target"
+			});
+		for entry in builder.build() {
+			ct.heartbeat()?;
+			let Ok(entry) = entry else { continue };
+			if entry.depth() == 0 || !entry.file_type().is_some_and(|kind| kind.is_dir()) {
+				continue;
+			}
+			let path = fs_cache::normalize_relative_path(self.root, entry.path()).into_owned();
+			if !include.is_match(&path) || exclude.is_match(&path) {
+				continue;
+			}
+			let Some(manifest) = ["Cargo.toml", "package.json", "go.mod", "pyproject.toml"]
+				.into_iter()
+				.find(|manifest| entry.path().join(manifest).is_file())
+			else {
+				continue;
+			};
+			packages.push(WorkspacePackage {
+				name: self.package_name(&path, manifest),
+				path,
+				manifest: manifest.to_string(),
+			});
+		}
+		for package in &packages {
+			self.scan(&package.path);
+		}
+		self.info.monorepo =
+			Some(MonorepoInfo { tools: std::mem::take(&mut self.member_tools), packages });
+		Ok(())
+	}
+
+	fn package_name(&self, dir: &str, manifest: &str) -> Option<String> {
+		let text = self.read(dir, manifest)?;
+		match manifest {
+			"package.json" => serde_json::from_str::<Value>(&text)
+				.ok()?
+				.get("name")?
+				.as_str()
+				.map(ToString::to_string),
+			"go.mod" => text
+				.lines()
+				.find_map(|line| line.trim().strip_prefix("module "))
+				.map(|module| module.trim().to_string()),
+			_ => {
+				let doc = text.parse::<DocumentMut>().ok()?;
+				let table = doc.get("package").or_else(|| doc.get("project"))?;
+				table.get("name")?.as_str().map(ToString::to_string)
+			},
+		}
+	}
+}
+
+/// Package name of a PEP 508 requirement, lowercased.
+fn requirement_name(requirement: &str) -> String {
+	requirement
+		.split(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+		.next()
+		.unwrap_or_default()
+		.to_lowercase()
+		.replace('_', "-")
+}
+
+/// Items of the top-level YAML list `key`, enough for `pnpm-workspace.yaml`.
+fn yaml_list(text: &str, key: &str) -> Vec<String> {
+	let mut items = Vec::new();
+	let mut in_list = false;
+	for line in text.lines() {
+		let trimmed = line.trim();
+		if trimmed.is_empty() || trimmed.starts_with('#') {
+			continue;
+		}
+		if !line.starts_with([' ', '\t', '-']) {
+			in_list = line
+				.strip_prefix(key)
+				.is_some_and(|rest| rest.trim() == ":");
+			continue;
+		}
+		if in_list && let Some(item) = trimmed.strip_prefix('-') {
+			items.push(item.trim().trim_matches(['"', '\'']).to_string());
+		}
+	}
+	items
+}
+
+/// Module directories listed by `use` in a `go.work` file.
+fn go_work_uses(text: &str) -> Vec<String> {
+	let mut uses = Vec::new();
+	let mut in_block = false;
+	for line in text.lines() {
+		let line = line.split("//").next().unwrap_or_default().trim();
+		if in_block {
+			if line == ")" {
+				in_block = false;
+			} else if !line.is_empty() {
+				uses.push(line.to_string());
+			}
+		} else if let Some(rest) = line.strip_prefix("use") {
+			let rest = rest.trim();
+			if rest == "(" {
+				in_block = true;
+			} else if !rest.is_empty() {
+				uses.push(rest.to_string());
+			}
+		}
+	}
+	uses
+		.into_iter()
+		.map(|path| path.trim_start_matches("./").to_string())
+		.filter(|path| !path.is_empty() && path != ".")
+		.collect()
+}
+
+fn detect(root: &Path, ct: &task::CancelToken) -> Result<ProjectInfo> {
+	let mut detector = Detector {
+		root,
+		info: ProjectInfo::default(),
+		members: Vec::new(),
+		member_tools: Vec::new(),
+		js_manager: None,
+	};
+	detector.scan("");
+	detector.ci();
+	detector.workspace(ct)?;
+	Ok(detector.info)
+}
+
+/// Detect the languages, frameworks, tooling and layout of the project at
+/// `root`.
+///
+/// # Errors
+/// Returns an error when `root` is not a directory.
+#[napi(js_name = "detectProject")]
+pub fn detect_project(root: String) -> task::Async<ProjectInfo> {
+	task::blocking("detect_project", (), move |ct| {
+		detect(&fs_cache::resolve_search_path(&root)?, &ct)
+	})
+}
@@ -7,7 +7,7 @@
 //! - **Linux**: Reads `/proc/{pid}/children` recursively
 //! - **macOS**: Uses `libproc` (`proc_listchildpids`)
 //! - **Windows**: Uses `CreateToolhelp32Snapshot` to build parent-child
-//!   relationships
+//!   relationships; process groups are emulated with Job Objects
 //!
 //! # Example
 //! ```ignore
@@ -15,13 +15,29 @@
 //!
 //! // Kill process 1234 and all its descendants
 //! let killed = kill_tree(1234, 9); // SIGKILL
+//!
+//! // SIGTERM the tree, SIGKILL anything still alive after 2 seconds
+//! let report = terminate_tree(1234, Duration::from_secs(2)).await;
+//!
+//! // Inspect what the tree is doing
+//! let tree = snapshot_tree(1234).await;
 //! ```
 
+use std::{
+	collections::HashMap,
+	sync::atomic::{AtomicBool, Ordering},
+	time::{Duration, Instant},
+};
+
+use napi::{Env, Result, bindgen_prelude::PromiseRaw, tokio::time};
 use napi_derive::napi;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::task;
 
 #[cfg(target_os = "linux")]
 mod platform {
-	use std::fs;
+	use std::{collections::HashMap, fs, io};
 
 	/// Collect all descendant PIDs of `pid` into `pids`.
 	/// Skips branches when `/proc/{pid}/children` cannot be read.
@@ -39,6 +55,44 @@ mod platform {
 		}
 	}
 
+	/// Processes holding each open socket, keyed by socket inode. Processes
+	/// whose descriptors we may not read are left out.
+	pub fn socket_owners() -> HashMap<u64, Vec<i32>> {
+		let mut owners: HashMap<u64, Vec<i32>> = HashMap::new();
+		let Ok(procs) = fs::read_dir("/proc") else {
+			return owners;
+		};
+		for entry in procs.flatten() {
+			let Some(pid) = entry
+				.file_name()
+				.to_str()
+				.and_then(|name| name.parse::<i32>().ok())
+			else {
+				continue;
+			};
+			let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+				continue;
+			};
+			for fd in fds.flatten() {
+				let Ok(target) = fs::read_link(fd.path()) else {
+					continue;
+				};
+				let inode = target
+					.to_str()
+					.and_then(|target| target.strip_prefix("socket:["))
+					.and_then(|rest| rest.strip_suffix(']'))
+					.and_then(|inode| inode.parse().ok());
+				if let Some(inode) = inode {
+					let pids = owners.entry(inode).or_default();
+					if !pids.contains(&pid) {
+						pids.push(pid);
+					}
+				}
+			}
+		}
+		owners
+	}
+
 	/// Send `signal` to `pid`.
 	/// Returns true when the signal is delivered successfully.
 	pub fn kill_pid(pid: i32, signal: i32) -> bool {
@@ -60,6 +114,112 @@ mod platform {
 		// SAFETY: libc::kill is safe to call with any pid/signal combination
 		unsafe { libc::kill(-pgid, signal) == 0 }
 	}
+
+	/// Groups are chosen at spawn time on unix; report the current one.
+	pub fn create_process_group(pid: i32) -> Option<i32> {
+		process_group_id(pid)
+	}
+
+	/// Nothing to release on unix.
+	pub const fn release_process_group(_pgid: i32) {}
+
+	/// Check whether `pid` is still running.
+	/// Zombies (exited but not yet reaped) count as gone.
+	pub fn is_alive(pid: i32) -> bool {
+		// SAFETY: signal 0 performs only the existence/permission check
+		let exists = unsafe { libc::kill(pid, 0) == 0 }
+			|| io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+		if !exists {
+			return false;
+		}
+		let Ok(stat) = fs::read_to_string(format!("/proc/{pid}/stat")) else {
+			return true;
+		};
+		// The state field follows the parenthesized command name.
+		stat
+			.rsplit_once(')')
+			.and_then(|(_, rest)| rest.trim_start().chars().next())
+			.is_none_or(|state| state != 'Z')
+	}
+
+	/// Mark this process as a child subreaper so orphaned descendants are
+	/// re-parented to it instead of init.
+	pub fn enable_subreaper() -> bool {
+		// SAFETY: PR_SET_CHILD_SUBREAPER takes a single integer flag.
+		unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) == 0 }
+	}
+
+	/// State and process group of `pid` from `/proc/{pid}/stat`.
+	fn state_and_pgid(pid: i32) -> Option<(char, i32)> {
+		let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+		// Fields after the parenthesized command name: state, ppid, pgrp.
+		let mut fields = stat.rsplit_once(')')?.1.split_whitespace();
+		let state = fields.next()?.chars().next()?;
+		let pgid = fields.nth(1)?.parse().ok()?;
+		Some((state, pgid))
+	}
+
+	/// Reap zombie children that were adopted as orphans.
+	///
+	/// Only zombies that are not process group leaders and not in our own
+	/// group are reaped: commands we spawn lead their own groups and are
+	/// waited on by their spawner, as are children sharing our group.
+	pub fn reap_orphans() -> Vec<super::ReapedProcess> {
+		let own_pgid = process_group_id(std::process::id() as i32);
+		let Ok(tasks) = fs::read_dir("/proc/self/task") else {
+			return Vec::new();
+		};
+		// Adopted orphans can belong to any of our threads.
+		let children: Vec<i32> = tasks
+			.flatten()
+			.filter_map(|task| fs::read_to_string(task.path().join("children")).ok())
+			.flat_map(|content| {
+				content
+					.split_whitespace()
+					.filter_map(|pid| pid.parse().ok())
+					.collect::<Vec<i32>>()
+			})
+			.collect();
+
+		let mut reaped = Vec::new();
+		for pid in children {
+			let Some((state, pgid)) = state_and_pgid(pid) else {
+				continue;
+			};
+			if state != 'Z' || pgid == pid || Some(pgid) == own_pgid {
+				continue;
+			}
+			let mut status = 0;
+			// SAFETY: `status` is a valid out-pointer; WNOHANG never blocks.
+			if unsafe { libc::waitpid(pid, &raw mut status, libc::WNOHANG) } != pid {
+				continue;
+			}
+			reaped.push(super::ReapedProcess {
+				pid,
+				exit_code: libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status)),
+				signal: libc::WIFSIGNALED(status).then(|| libc::WTERMSIG(status)),
+			});
+		}
+		reaped
+	}
+
+	/// CPU time of terminated children that have been waited for.
+	pub fn children_cpu_time() -> Option<super::CpuTime> {
+		// SAFETY: `rusage` is plain old data; getrusage fills it in.
+		let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+		// SAFETY: `usage` is a valid, writable rusage.
+		if unsafe { libc::getrusage(libc::RUSAGE_CHILDREN, &raw mut usage) } != 0 {
+			return None;
+		}
+		let duration =
+			|tv: libc::timeval| std::time::Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000);
+		Some(super::CpuTime { user: duration(usage.ru_utime), system: duration(usage.ru_stime) })
+	}
+
+	/// Process groups keep no accounting on unix; see [`children_cpu_time`].
+	pub const fn process_group_cpu_time(_pgid: i32) -> Option<super::CpuTime> {
+		None
+	}
 }
 
 #[cfg(target_os = "macos")]
@@ -122,12 +282,56 @@ mod platform {
 		// SAFETY: libc::kill is safe to call with any pid/signal combination
 		unsafe { libc::kill(-pgid, signal) == 0 }
 	}
+
+	/// Groups are chosen at spawn time on unix; report the current one.
+	pub fn create_process_group(pid: i32) -> Option<i32> {
+		process_group_id(pid)
+	}
+
+	/// Nothing to release on unix.
+	pub const fn release_process_group(_pgid: i32) {}
+
+	/// Check whether `pid` is still running.
+	pub fn is_alive(pid: i32) -> bool {
+		// SAFETY: signal 0 performs only the existence/permission check
+		let delivered = unsafe { libc::kill(pid, 0) == 0 };
+		delivered || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+	}
+
+	/// macOS has no subreapers; orphans always go to launchd.
+	pub const fn enable_subreaper() -> bool {
+		false
+	}
+
+	/// Orphans are never adopted, so there is nothing to reap.
+	pub const fn reap_orphans() -> Vec<super::ReapedProcess> {
+		Vec::new()
+	}
+
+	/// CPU time of terminated children that have been waited for.
+	pub fn children_cpu_time() -> Option<super::CpuTime> {
+		// SAFETY: `rusage` is plain old data; getrusage fills it in.
+		let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+		// SAFETY: `usage` is a valid, writable rusage.
+		if unsafe { libc::getrusage(libc::RUSAGE_CHILDREN, &raw mut usage) } != 0 {
+			return None;
+		}
+		let duration =
+			|tv: libc::timeval| std::time::Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000);
+		Some(super::CpuTime { user: duration(usage.ru_utime), system: duration(usage.ru_stime) })
+	}
+
+	/// Process groups keep no accounting on unix; see [`children_cpu_time`].
+	pub const fn process_group_cpu_time(_pgid: i32) -> Option<super::CpuTime> {
+		None
+	}
 }
 
 #[cfg(target_os = "windows")]
 mod platform {
-	use std::{collections::HashMap, mem};
+	use std::{collections::HashMap, mem, sync::LazyLock, time::Duration};
 
+	use parking_lot::Mutex;
 	use smallvec::SmallVec;
 
 	#[repr(C)]
@@ -145,10 +349,71 @@ mod platform {
 		szExeFile:           [u16; 260],
 	}
 
+	#[repr(C)]
+	#[derive(Default)]
+	#[allow(non_snake_case, reason = "mirrors the Win32 struct layout")]
+	struct JOBOBJECT_BASIC_LIMIT_INFORMATION {
+		PerProcessUserTimeLimit: i64,
+		PerJobUserTimeLimit:     i64,
+		LimitFlags:              u32,
+		MinimumWorkingSetSize:   usize,
+		MaximumWorkingSetSize:   usize,
+		ActiveProcessLimit:      u32,
+		Affinity:                usize,
+		PriorityClass:           u32,
+		SchedulingClass:         u32,
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	#[allow(non_snake_case, reason = "mirrors the Win32 struct layout")]
+	struct IO_COUNTERS {
+		ReadOperationCount:  u64,
+		WriteOperationCount: u64,
+		OtherOperationCount: u64,
+		ReadTransferCount:   u64,
+		WriteTransferCount:  u64,
+		OtherTransferCount:  u64,
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	#[allow(non_snake_case, reason = "mirrors the Win32 struct layout")]
+	struct JOBOBJECT_BASIC_ACCOUNTING_INFORMATION {
+		TotalUserTime:             i64,
+		TotalKernelTime:           i64,
+		ThisPeriodTotalUserTime:   i64,
+		ThisPeriodTotalKernelTime: i64,
+		TotalPageFaultCount:       u32,
+		TotalProcesses:            u32,
+		ActiveProcesses:           u32,
+		TotalTerminatedProcesses:  u32,
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	#[allow(non_snake_case, reason = "mirrors the Win32 struct layout")]
+	struct JOBOBJECT_EXTENDED_LIMIT_INFORMATION {
+		BasicLimitInformation: JOBOBJECT_BASIC_LIMIT_INFORMATION,
+		IoInfo:                IO_COUNTERS,
+		ProcessMemoryLimit:    usize,
+		JobMemoryLimit:        usize,
+		PeakProcessMemoryUsed: usize,
+		PeakJobMemoryUsed:     usize,
+	}
+
 	type HANDLE = *mut std::ffi::c_void;
 	const INVALID_HANDLE_VALUE: HANDLE = -1isize as HANDLE;
 	const TH32CS_SNAPPROCESS: u32 = 0x00000002;
 	const PROCESS_TERMINATE: u32 = 0x0001;
+	const PROCESS_SET_QUOTA: u32 = 0x0100;
+	const PROCESS_SET_INFORMATION: u32 = 0x0200;
+	const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+	const STILL_ACTIVE: u32 = 259;
+	const JOB_OBJECT_BASIC_ACCOUNTING_INFORMATION_CLASS: i32 = 1;
+	const JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS: i32 = 9;
+	const JOB_OBJECT_LIMIT_PROCESS_TIME: u32 = 0x0000_0002;
+	const JOB_OBJECT_LIMIT_PROCESS_MEMORY: u32 = 0x0000_0100;
 
 	#[link(name = "kernel32")]
 	unsafe extern "system" {
@@ -158,6 +423,158 @@ mod platform {
 		fn CloseHandle(hObject: HANDLE) -> i32;
 		fn OpenProcess(dwDesiredAccess: u32, bInheritHandle: i32, dwProcessId: u32) -> HANDLE;
 		fn TerminateProcess(hProcess: HANDLE, uExitCode: u32) -> i32;
+		fn CreateJobObjectW(lpJobAttributes: *mut std::ffi::c_void, lpName: *const u16) -> HANDLE;
+		fn SetInformationJobObject(
+			hJob: HANDLE,
+			JobObjectInformationClass: i32,
+			lpJobObjectInformation: *mut std::ffi::c_void,
+			cbJobObjectInformationLength: u32,
+		) -> i32;
+		fn QueryInformationJobObject(
+			hJob: HANDLE,
+			JobObjectInformationClass: i32,
+			lpJobObjectInformation: *mut std::ffi::c_void,
+			cbJobObjectInformationLength: u32,
+			lpReturnLength: *mut u32,
+		) -> i32;
+		fn AssignProcessToJobObject(hJob: HANDLE, hProcess: HANDLE) -> i32;
+		fn GetExitCodeProcess(hProcess: HANDLE, lpExitCode: *mut u32) -> i32;
+		fn TerminateJobObject(hJob: HANDLE, uExitCode: u32) -> i32;
+		fn IsProcessInJob(ProcessHandle: HANDLE, JobHandle: HANDLE, Result: *mut i32) -> i32;
+		fn SetPriorityClass(hProcess: HANDLE, dwPriorityClass: u32) -> i32;
+	}
+
+	/// Jobs standing in for process groups, keyed by the leader pid.
+	static GROUPS: LazyLock<Mutex<HashMap<i32, Job>>> = LazyLock::new(Default::default);
+
+	/// Owned Windows Job Object handle, closed on drop.
+	pub struct Job {
+		handle: HANDLE,
+	}
+
+	// SAFETY: Job object handles may be used from any thread; the kernel
+	// serializes operations on them.
+	unsafe impl Send for Job {}
+	// SAFETY: See `Send` above; all methods take `&self` and only pass the
+	// handle to thread-safe Win32 APIs.
+	unsafe impl Sync for Job {}
+
+	impl Job {
+		/// Create an anonymous job object.
+		/// Returns `None` when the kernel refuses to create one.
+		pub fn new() -> Option<Self> {
+			// SAFETY: null attributes and name create an anonymous job with
+			// default security.
+			let handle = unsafe { CreateJobObjectW(std::ptr::null_mut(), std::ptr::null()) };
+			if handle.is_null() {
+				None
+			} else {
+				Some(Self { handle })
+			}
+		}
+
+		/// Apply per-process memory (bytes) and user CPU time (seconds) limits.
+		/// Returns true when the limits are accepted.
+		pub fn set_limits(
+			&self,
+			max_memory_bytes: Option<u64>,
+			max_cpu_seconds: Option<u32>,
+		) -> bool {
+			let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+			if let Some(bytes) = max_memory_bytes {
+				info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+				info.ProcessMemoryLimit = usize::try_from(bytes).unwrap_or(usize::MAX);
+			}
+			if let Some(seconds) = max_cpu_seconds {
+				// Job time limits are expressed in 100-nanosecond ticks.
+				info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+				info.BasicLimitInformation.PerProcessUserTimeLimit = seconds as i64 * 10_000_000;
+			}
+			// SAFETY: `info` is a correctly sized
+			// JOBOBJECT_EXTENDED_LIMIT_INFORMATION that outlives the
+			// call.
+			unsafe {
+				SetInformationJobObject(
+					self.handle,
+					JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS,
+					(&raw mut info).cast(),
+					mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+				) != 0
+			}
+		}
+
+		/// Assign `pid` to this job.
+		/// Returns true when the process is now governed by the job.
+		pub fn assign(&self, pid: i32) -> bool {
+			// SAFETY: OpenProcess/AssignProcessToJobObject/CloseHandle are called
+			// with a handle we own and close before returning.
+			unsafe {
+				let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid as u32);
+				if process.is_null() || process == INVALID_HANDLE_VALUE {
+					return false;
+				}
+				let assigned = AssignProcessToJobObject(self.handle, process) != 0;
+				CloseHandle(process);
+				assigned
+			}
+		}
+
+		/// Total user and kernel CPU time of every process that ran in the job.
+		pub fn cpu_time(&self) -> Option<super::CpuTime> {
+			let mut info = JOBOBJECT_BASIC_ACCOUNTING_INFORMATION::default();
+			// SAFETY: `info` is a correctly sized
+			// JOBOBJECT_BASIC_ACCOUNTING_INFORMATION that outlives the
+			// call.
+			let ok = unsafe {
+				QueryInformationJobObject(
+					self.handle,
+					JOB_OBJECT_BASIC_ACCOUNTING_INFORMATION_CLASS,
+					(&raw mut info).cast(),
+					mem::size_of::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>() as u32,
+					std::ptr::null_mut(),
+				) != 0
+			};
+			// Job times are expressed in 100-nanosecond ticks.
+			let duration = |ticks: i64| Duration::from_nanos(ticks.max(0) as u64 * 100);
+			ok.then(|| super::CpuTime {
+				user:   duration(info.TotalUserTime),
+				system: duration(info.TotalKernelTime),
+			})
+		}
+
+		/// Check whether `pid` is governed by this job.
+		pub fn contains(&self, pid: i32) -> bool {
+			// SAFETY: the process handle is checked for validity and closed before
+			// returning; `result` is a valid out pointer.
+			unsafe {
+				let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid as u32);
+				if process.is_null() || process == INVALID_HANDLE_VALUE {
+					return false;
+				}
+				let mut result = 0i32;
+				let ok = IsProcessInJob(process, self.handle, &raw mut result) != 0;
+				CloseHandle(process);
+				ok && result != 0
+			}
+		}
+
+		/// Terminate every process in the job, including descendants that
+		/// inherited it.
+		/// Returns true when the job is terminated.
+		pub fn kill(&self) -> bool {
+			// SAFETY: `handle` is a live job handle owned by `self`.
+			unsafe { TerminateJobObject(self.handle, 1) != 0 }
+		}
+	}
+
+	impl Drop for Job {
+		fn drop(&mut self) {
+			// SAFETY: `handle` was returned by CreateJobObjectW and is closed
+			// once.
+			unsafe {
+				CloseHandle(self.handle);
+			}
+		}
 	}
 
 	/// Build a map of parent_pid -> [child_pids] for all processes.
@@ -226,19 +643,94 @@ mod platform {
 		}
 	}
 
-	/// Process groups are not exposed on Windows.
-	/// Always returns `None`.
-	pub fn process_group_id(_pid: i32) -> Option<i32> {
-		None
+	/// Get the emulated process group of `pid`.
+	/// Returns `None` unless `pid` belongs to a group from
+	/// [`create_process_group`].
+	pub fn process_group_id(pid: i32) -> Option<i32> {
+		let groups = GROUPS.lock();
+		if groups.contains_key(&pid) {
+			return Some(pid);
+		}
+		groups
+			.iter()
+			.find_map(|(&pgid, job)| job.contains(pid).then_some(pgid))
+	}
+
+	/// Terminate every process in the emulated group `pgid` (Windows ignores
+	/// `signal`) and forget the group.
+	/// Returns false when `pgid` is not a known group.
+	pub fn kill_process_group(pgid: i32, _signal: i32) -> bool {
+		GROUPS.lock().remove(&pgid).is_some_and(|job| job.kill())
+	}
+
+	/// Assign `pid` to a new job that stands in for its process group.
+	/// Processes it spawns later join the job automatically.
+	pub fn create_process_group(pid: i32) -> Option<i32> {
+		let job = Job::new().filter(|job| job.assign(pid))?;
+		GROUPS.lock().insert(pid, job);
+		Some(pid)
+	}
+
+	/// Close the job backing `pgid` without terminating its processes.
+	pub fn release_process_group(pgid: i32) {
+		GROUPS.lock().remove(&pgid);
+	}
+
+	/// Check whether `pid` is still running.
+	pub fn is_alive(pid: i32) -> bool {
+		// SAFETY: the handle is checked for validity and closed before returning.
+		unsafe {
+			let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid as u32);
+			if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+				return false;
+			}
+			let mut code = 0u32;
+			let ok = GetExitCodeProcess(handle, &raw mut code) != 0;
+			CloseHandle(handle);
+			ok && code == STILL_ACTIVE
+		}
 	}
 
-	/// Process groups are not exposed on Windows.
-	/// Always returns `false`.
-	pub fn kill_process_group(_pgid: i32, _signal: i32) -> bool {
+	/// Set the priority class of `pid`.
+	pub fn set_priority_class(pid: i32, class: u32) -> bool {
+		// SAFETY: the handle is checked for validity and closed before returning.
+		unsafe {
+			let handle = OpenProcess(PROCESS_SET_INFORMATION, 0, pid as u32);
+			if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+				return false;
+			}
+			let ok = SetPriorityClass(handle, class) != 0;
+			CloseHandle(handle);
+			ok
+		}
+	}
+
+	/// Windows does not re-parent processes; Job Objects already track them.
+	pub const fn enable_subreaper() -> bool {
 		false
 	}
+
+	/// Windows has no zombies to reap.
+	pub const fn reap_orphans() -> Vec<super::ReapedProcess> {
+		Vec::new()
+	}
+
+	/// Child accounting is per Job Object; see [`process_group_cpu_time`].
+	pub const fn children_cpu_time() -> Option<super::CpuTime> {
+		None
+	}
+
+	/// CPU time of every process that ran in the emulated group `pgid`.
+	pub fn process_group_cpu_time(pgid: i32) -> Option<super::CpuTime> {
+		GROUPS.lock().get(&pgid)?.cpu_time()
+	}
 }
 
+#[cfg(target_os = "linux")]
+pub use platform::socket_owners;
+#[cfg(target_os = "windows")]
+pub use platform::{Job, set_priority_class};
+
 /// Kill a process tree (the process and all its descendants).
 ///
 /// Arguments: `pid` is the root process and `signal` is the kill signal.
@@ -272,12 +764,207 @@ pub fn process_group_id(pid: i32) -> Option<i32> {
 	platform::process_group_id(pid)
 }
 
+/// Check whether `pid` is still running.
+pub fn is_alive(pid: i32) -> bool {
+	platform::is_alive(pid)
+}
+
 /// Send `signal` to the process group `pgid`.
-/// Returns false when process groups are unsupported on the platform.
+/// Returns false when the group does not exist.
 pub fn kill_process_group(pgid: i32, signal: i32) -> bool {
 	platform::kill_process_group(pgid, signal)
 }
 
+/// Make `pid` the leader of a process group and return the group id.
+///
+/// On unix, groups are chosen at spawn time, so this only reports the group
+/// `pid` already belongs to. On Windows, `pid` is assigned to a new Job Object
+/// that its future children inherit, and the group must be released with
+/// [`release_process_group`] once it is no longer needed.
+pub fn create_process_group(pid: i32) -> Option<i32> {
+	platform::create_process_group(pid)
+}
+
+/// User and system CPU time consumed by processes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuTime {
+	pub user:   Duration,
+	pub system: Duration,
+}
+
+impl CpuTime {
+	/// CPU time accrued since `earlier`.
+	#[must_use]
+	pub const fn since(self, earlier: Self) -> Self {
+		Self {
+			user:   self.user.saturating_sub(earlier.user),
+			system: self.system.saturating_sub(earlier.system),
+		}
+	}
+}
+
+impl std::ops::Add for CpuTime {
+	type Output = Self;
+
+	fn add(self, other: Self) -> Self {
+		Self { user: self.user + other.user, system: self.system + other.system }
+	}
+}
+
+/// CPU time of every terminated child of this process that has been waited
+/// for, including their waited-for descendants (unix only).
+#[allow(clippy::missing_const_for_fn, reason = "calls getrusage on unix")]
+pub fn children_cpu_time() -> Option<CpuTime> {
+	platform::children_cpu_time()
+}
+
+/// CPU time of every process that ran in the group `pgid` (Windows only,
+/// where groups are Job Objects that keep accounting).
+#[allow(clippy::missing_const_for_fn, reason = "queries a job object on Windows")]
+pub fn process_group_cpu_time(pgid: i32) -> Option<CpuTime> {
+	platform::process_group_cpu_time(pgid)
+}
+
+/// Release a group returned by [`create_process_group`] without killing it.
+#[allow(clippy::missing_const_for_fn, reason = "closes a job object on Windows")]
+pub fn release_process_group(pgid: i32) {
+	platform::release_process_group(pgid);
+}
+
+/// Outcome of a graceful tree kill for a single process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[napi]
+pub enum KillOutcome {
+	/// The process was already gone before it could be signalled.
+	NotFound   = 1,
+	/// The process exited within the grace period after `SIGTERM`.
+	Terminated = 2,
+	/// The process outlived the grace period and was killed with `SIGKILL`.
+	Killed     = 3,
+	/// The process is still alive after `SIGKILL` (e.g. permission denied).
+	Survived   = 4,
+}
+
+/// Per-process result of [`kill_tree_graceful`].
+#[napi(object)]
+pub struct KillReport {
+	/// Process id.
+	pub pid:     i32,
+	/// What happened to the process.
+	pub outcome: KillOutcome,
+}
+
+const SIGTERM: i32 = 15;
+pub(crate) const SIGKILL: i32 = 9;
+const LIVENESS_POLL: Duration = Duration::from_millis(20);
+const KILL_CONFIRM: Duration = Duration::from_millis(200);
+
+/// Poll until every pid in `pids` has exited or `budget` elapses.
+async fn wait_for_exit(pids: &[i32], budget: Duration) {
+	let deadline = Instant::now() + budget;
+	while pids.iter().any(|&pid| platform::is_alive(pid)) && Instant::now() < deadline {
+		time::sleep(LIVENESS_POLL).await;
+	}
+}
+
+/// SIGTERM a process tree, wait up to `grace`, then SIGKILL stragglers.
+///
+/// Descendants are re-collected before escalating so children spawned during
+/// the grace period are killed too; the original snapshot is kept because
+/// orphans get re-parented away from the root once it exits.
+pub async fn terminate_tree(pid: i32, grace: Duration) -> Vec<KillReport> {
+	let mut pids = Vec::new();
+	platform::collect_descendants(pid, &mut pids);
+	pids.reverse();
+	pids.push(pid);
+
+	let mut outcomes = HashMap::with_capacity(pids.len());
+	for &target in &pids {
+		if !platform::is_alive(target) || !platform::kill_pid(target, SIGTERM) {
+			outcomes.insert(target, KillOutcome::NotFound);
+		}
+	}
+
+	wait_for_exit(&pids, grace).await;
+
+	let mut late = Vec::new();
+	platform::collect_descendants(pid, &mut late);
+	for target in late.into_iter().rev() {
+		if !pids.contains(&target) {
+			pids.insert(0, target);
+		}
+	}
+
+	// Snapshot survivors before escalating so a parent that exits because its
+	// children were just killed is not mistaken for a clean exit.
+	let mut escalated = Vec::new();
+	for &target in &pids {
+		if outcomes.contains_key(&target) {
+			continue;
+		}
+		if platform::is_alive(target) {
+			escalated.push(target);
+		} else {
+			outcomes.insert(target, KillOutcome::Terminated);
+		}
+	}
+	for &target in &escalated {
+		platform::kill_pid(target, SIGKILL);
+	}
+
+	wait_for_exit(&escalated, KILL_CONFIRM).await;
+	for target in escalated {
+		let outcome = if platform::is_alive(target) {
+			KillOutcome::Survived
+		} else {
+			KillOutcome::Killed
+		};
+		outcomes.insert(target, outcome);
+	}
+
+	pids
+		.into_iter()
+		.map(|pid| KillReport { pid, outcome: outcomes[&pid] })
+		.collect()
+}
+
+/// Gracefully kill a process tree: SIGTERM, wait, then SIGKILL.
+///
+/// Sends SIGTERM to `pid` and all its descendants (children first), polls
+/// liveness for up to `grace_ms`, then SIGKILLs anything still running,
+/// including descendants spawned during the grace period. Windows has no
+/// graceful signal, so the first phase already terminates processes there.
+///
+/// Returns one report per process that was part of the tree.
+#[napi(js_name = "killTreeGraceful")]
+pub fn kill_tree_graceful(
+	env: &Env,
+	pid: i32,
+	grace_ms: u32,
+) -> Result<PromiseRaw<'_, Vec<KillReport>>> {
+	task::future(env, "ps.kill_tree_graceful", async move {
+		Ok(terminate_tree(pid, Duration::from_millis(grace_ms as u64)).await)
+	})
+}
+
+/// Executable names of the running processes among `pids`.
+pub fn process_names(pids: &[i32]) -> HashMap<i32, String> {
+	let targets: Vec<Pid> = pids.iter().map(|&pid| Pid::from_u32(pid as u32)).collect();
+	let mut system = System::new();
+	system.refresh_processes_specifics(
+		ProcessesToUpdate::Some(&targets),
+		true,
+		ProcessRefreshKind::nothing(),
+	);
+	pids
+		.iter()
+		.filter_map(|&pid| {
+			let process = system.process(Pid::from_u32(pid as u32))?;
+			Some((pid, process.name().to_string_lossy().into_owned()))
+		})
+		.collect()
+}
+
 /// List all descendant PIDs of `pid`.
 ///
 /// Returns an empty array if the process has no children or doesn't exist.
@@ -287,3 +974,228 @@ pub fn list_descendants(pid: i32) -> Vec<i32> {
 	platform::collect_descendants(pid, &mut descendants);
 	descendants
 }
+
+static SUBREAPER: AtomicBool = AtomicBool::new(false);
+
+/// An adopted orphan reaped by `reapZombies`.
+#[napi(object)]
+pub struct ReapedProcess {
+	/// Process id.
+	pub pid:       i32,
+	/// Exit code, when the process exited normally.
+	#[napi(js_name = "exitCode")]
+	pub exit_code: Option<i32>,
+	/// Terminating signal, when the process was killed by one.
+	pub signal:    Option<i32>,
+}
+
+/// Make this process a child subreaper (Linux only).
+///
+/// Processes double-forked by shell commands are then re-parented to us
+/// instead of init, so they stay in our process tree and in the process
+/// group of the command that started them, where cancellation reaches
+/// them. Their zombies must be collected with [`reap_zombies`], which runs
+/// automatically after every shell execution.
+///
+/// Returns false when the platform has no subreaper support.
+#[napi(js_name = "enableSubreaper")]
+pub fn enable_subreaper() -> bool {
+	if SUBREAPER.load(Ordering::Relaxed) {
+		return true;
+	}
+	let enabled = platform::enable_subreaper();
+	SUBREAPER.store(enabled, Ordering::Relaxed);
+	enabled
+}
+
+/// Whether [`enable_subreaper`] succeeded.
+pub fn is_subreaper() -> bool {
+	SUBREAPER.load(Ordering::Relaxed)
+}
+
+/// Reap exited orphans that were re-parented to this process.
+///
+/// Children we spawned ourselves (process group leaders, or members of our
+/// own group) are left for their spawner to wait on.
+#[napi(js_name = "reapZombies")]
+#[allow(clippy::missing_const_for_fn, reason = "waits on zombies on Linux")]
+pub fn reap_zombies() -> Vec<ReapedProcess> {
+	platform::reap_orphans()
+}
+
+/// Snapshot of a single process and its descendants.
+#[napi(object)]
+pub struct ProcessNode {
+	/// Process id.
+	pub pid:        i32,
+	/// Parent process id, if known.
+	pub ppid:       Option<i32>,
+	/// Executable name.
+	pub name:       String,
+	/// Full command line (argv).
+	pub cmdline:    Vec<String>,
+	/// Resident set size in bytes.
+	#[napi(js_name = "rssBytes")]
+	pub rss_bytes:  i64,
+	/// CPU usage in percent, sampled over a short interval. Can exceed 100 on
+	/// multi-core machines.
+	pub cpu:        f64,
+	/// Start time in milliseconds since the Unix epoch.
+	#[napi(js_name = "startTime")]
+	pub start_time: i64,
+	/// Direct children.
+	pub children:   Vec<Self>,
+}
+
+/// Capture `pid` and all its descendants with name, command line, memory and
+/// CPU usage.
+///
+/// Processes are refreshed twice, `MINIMUM_CPU_UPDATE_INTERVAL` apart, so the
+/// CPU figures reflect current activity. Returns `None` when `pid` is gone.
+pub async fn snapshot_tree(pid: i32) -> Option<ProcessNode> {
+	let mut pids = vec![pid];
+	platform::collect_descendants(pid, &mut pids);
+	let targets: Vec<Pid> = pids.iter().map(|&pid| Pid::from_u32(pid as u32)).collect();
+
+	let refresh = ProcessRefreshKind::nothing()
+		.with_memory()
+		.with_cpu()
+		.with_cmd(UpdateKind::OnlyIfNotSet);
+	let mut system = System::new();
+	system.refresh_processes_specifics(ProcessesToUpdate::Some(&targets), true, refresh);
+	time::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).await;
+	system.refresh_processes_specifics(ProcessesToUpdate::Some(&targets), true, refresh);
+
+	let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+	for &child in &pids[1..] {
+		let parent = system
+			.process(Pid::from_u32(child as u32))
+			.and_then(|process| process.parent());
+		if let Some(parent) = parent {
+			children
+				.entry(parent.as_u32() as i32)
+				.or_default()
+				.push(child);
+		}
+	}
+	build_node(&system, pid, &children)
+}
+
+fn build_node(system: &System, pid: i32, children: &HashMap<i32, Vec<i32>>) -> Option<ProcessNode> {
+	let process = system.process(Pid::from_u32(pid as u32))?;
+	let nodes = children
+		.get(&pid)
+		.into_iter()
+		.flatten()
+		.filter_map(|&child| build_node(system, child, children))
+		.collect();
+	Some(ProcessNode {
+		pid,
+		ppid: process.parent().map(|parent| parent.as_u32() as i32),
+		name: process.name().to_string_lossy().into_owned(),
+		cmdline: process
+			.cmd()
+			.iter()
+			.map(|arg| arg.to_string_lossy().into_owned())
+			.collect(),
+		rss_bytes: process.memory() as i64,
+		cpu: f64::from(process.cpu_usage()),
+		start_time: process.start_time() as i64 * 1000,
+		children: nodes,
+	})
+}
+
+/// Snapshot a process tree with per-process metadata.
+///
+/// Returns the root process with nested children, each carrying executable
+/// name, command line, RSS, CPU% and start time. Resolves to `null` when the
+/// process doesn't exist.
+#[napi(js_name = "processTree")]
+pub fn process_tree(env: &Env, pid: i32) -> Result<PromiseRaw<'_, Option<ProcessNode>>> {
+	task::future(env, "ps.process_tree", async move { Ok(snapshot_tree(pid).await) })
+}
+
+/// Resource usage of a process, or the sum over a process tree.
+#[napi(object)]
+pub struct ProcessStats {
+	/// Root process id.
+	pub pid:           i32,
+	/// Number of processes included (1 for a single process).
+	#[napi(js_name = "processCount")]
+	pub process_count: u32,
+	/// User plus system CPU time in milliseconds.
+	#[napi(js_name = "cpuTimeMs")]
+	pub cpu_time_ms:   f64,
+	/// Resident set size in bytes.
+	#[napi(js_name = "rssBytes")]
+	pub rss_bytes:     i64,
+	/// Open file descriptors (handles on Windows), when the platform reports
+	/// them.
+	#[napi(js_name = "openFiles")]
+	pub open_files:    Option<u32>,
+	/// Bytes read from storage since the process started.
+	#[napi(js_name = "readBytes")]
+	pub read_bytes:    f64,
+	/// Bytes written to storage since the process started.
+	#[napi(js_name = "writtenBytes")]
+	pub written_bytes: f64,
+}
+
+/// Sum resource usage over `pids`, the first of which is the root.
+///
+/// Processes that exit before they are sampled are skipped. Returns `None`
+/// when the root is gone.
+fn sample_stats(pids: &[i32]) -> Option<ProcessStats> {
+	let targets: Vec<Pid> = pids.iter().map(|&pid| Pid::from_u32(pid as u32)).collect();
+	let mut system = System::new();
+	system.refresh_processes_specifics(
+		ProcessesToUpdate::Some(&targets),
+		true,
+		ProcessRefreshKind::nothing()
+			.with_memory()
+			.with_cpu()
+			.with_disk_usage(),
+	);
+	system.process(targets[0])?;
+
+	let mut stats = ProcessStats {
+		pid:           pids[0],
+		process_count: 0,
+		cpu_time_ms:   0.0,
+		rss_bytes:     0,
+		open_files:    None,
+		read_bytes:    0.0,
+		written_bytes: 0.0,
+	};
+	for process in targets.iter().filter_map(|pid| system.process(*pid)) {
+		let disk = process.disk_usage();
+		stats.process_count += 1;
+		stats.cpu_time_ms += process.accumulated_cpu_time() as f64;
+		stats.rss_bytes += process.memory() as i64;
+		stats.read_bytes += disk.total_read_bytes as f64;
+		stats.written_bytes += disk.total_written_bytes as f64;
+		if let Some(open) = process.open_files() {
+			*stats.open_files.get_or_insert(0) += open as u32;
+		}
+	}
+	Some(stats)
+}
+
+/// Sample CPU time, RSS, open files and IO bytes of a single process.
+///
+/// Returns `null` if the process doesn't exist.
+#[napi(js_name = "processStats")]
+pub fn process_stats(pid: i32) -> Option<ProcessStats> {
+	sample_stats(&[pid])
+}
+
+/// Sample CPU time, RSS, open files and IO bytes summed over a process and
+/// all its descendants.
+///
+/// Returns `null` if the process doesn't exist.
+#[napi(js_name = "processTreeStats")]
+pub fn process_tree_stats(pid: i32) -> Option<ProcessStats> {
+	let mut pids = vec![pid];
+	platform::collect_descendants(pid, &mut pids);
+	sample_stats(&pids)
+}
@@ -3,13 +3,18 @@
 //! # Overview
 //! Provides a stateful PTY session that supports streaming output and stdin
 //! passthrough while a command is running.
+//!
+//! With `record`, the session's output and resizes are recorded as an
+//! asciinema v2 cast and stored in the artifact store, for replay with
+//! `asciinema play`.
 
 use std::{
 	collections::HashMap,
+	fmt::Write as _,
 	io::{Read, Write},
 	str,
 	sync::{Arc, Mutex, mpsc},
-	time::Duration,
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use napi::{
@@ -18,8 +23,9 @@ use napi::{
 };
 use napi_derive::napi;
 use portable_pty::{CommandBuilder, PtySize, native_pty_system};
+use serde_json::json;
 
-use crate::task;
+use crate::{artifacts, task};
 
 /// Options for running a command in a PTY session.
 #[napi(object)]
@@ -39,6 +45,12 @@ pub struct PtyStartOptions<'env> {
 	pub cols:       Option<u16>,
 	/// PTY row count.
 	pub rows:       Option<u16>,
+	/// Record the session as an asciinema v2 cast in the artifact store.
+	pub record:     Option<bool>,
+	/// Artifact store directory for the recording (default:
+	/// `~/.omp/agent/artifacts`).
+	#[napi(js_name = "recordDir")]
+	pub record_dir: Option<String>,
 }
 
 /// Result of a PTY command run.
@@ -50,15 +62,19 @@ pub struct PtyRunResult {
 	pub cancelled: bool,
 	/// Whether command timed out.
 	pub timed_out: bool,
+	/// `agent://` id of the cast recording, with `record`.
+	pub recording: Option<String>,
 }
 
 #[derive(Clone)]
 struct PtyRunConfig {
-	command: String,
-	cwd:     Option<String>,
-	env:     Option<HashMap<String, String>>,
-	cols:    u16,
-	rows:    u16,
+	command:    String,
+	cwd:        Option<String>,
+	env:        Option<HashMap<String, String>>,
+	cols:       u16,
+	rows:       u16,
+	record:     bool,
+	record_dir: Option<String>,
 }
 
 enum ReaderEvent {
@@ -106,11 +122,13 @@ impl PtySession {
 		>,
 	) -> Result<PromiseRaw<'env, PtyRunResult>> {
 		let run_config = PtyRunConfig {
-			command: options.command,
-			cwd:     options.cwd,
-			env:     options.env,
-			cols:    options.cols.unwrap_or(120).clamp(20, 400),
-			rows:    options.rows.unwrap_or(40).clamp(5, 200),
+			command:    options.command,
+			cwd:        options.cwd,
+			env:        options.env,
+			cols:       options.cols.unwrap_or(120).clamp(20, 400),
+			rows:       options.rows.unwrap_or(40).clamp(5, 200),
+			record:     options.record.unwrap_or(false),
+			record_dir: options.record_dir,
 		};
 		let ct = task::CancelToken::new(options.timeout_ms, options.signal);
 		let core = Arc::clone(&self.core);
@@ -183,6 +201,47 @@ impl PtySession {
 	}
 }
 
+/// asciinema v2 cast of a PTY run: a header line, then one JSON event per
+/// line with its time in seconds since the start.
+struct CastRecorder {
+	started: Instant,
+	cast:    String,
+}
+
+impl CastRecorder {
+	fn new(config: &PtyRunConfig) -> Self {
+		let timestamp = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_or(0, |since| since.as_secs());
+		let header = json!({
+			"version": 2,
+			"width": config.cols,
+			"height": config.rows,
+			"timestamp": timestamp,
+			"command": config.command,
+		});
+		Self { started: Instant::now(), cast: format!("{header}\n") }
+	}
+
+	fn event(&mut self, code: &str, data: &str) {
+		let time = (self.started.elapsed().as_secs_f64() * 1e6).round() / 1e6;
+		let _ = writeln!(self.cast, "{}", json!([time, code, data]));
+	}
+
+	fn output(&mut self, text: &str) {
+		self.event("o", text);
+	}
+
+	fn resize(&mut self, cols: u16, rows: u16) {
+		self.event("r", &format!("{cols}x{rows}"));
+	}
+
+	/// Store the cast, returning its artifact id.
+	fn finish(self, dir: Option<String>) -> Result<String> {
+		Ok(artifacts::store_bytes(dir, self.cast.into_bytes())?.id)
+	}
+}
+
 fn run_pty_sync(
 	config: PtyRunConfig,
 	on_chunk: Option<ThreadsafeFunction<String>>,
@@ -211,6 +270,7 @@ fn run_pty_sync(
 		}
 	}
 
+	let mut recorder = config.record.then(|| CastRecorder::new(&config));
 	let mut child = pair
 		.slave
 		.spawn_command(cmd)
@@ -307,6 +367,9 @@ fn run_pty_sync(
 				},
 				Ok(ControlMessage::Resize { cols, rows }) => {
 					let _ = master.resize(PtySize { rows, cols, pixel_width: 0, pixel_height: 0 });
+					if let Some(recorder) = recorder.as_mut() {
+						recorder.resize(cols, rows);
+					}
 				},
 				Ok(ControlMessage::Kill) => {
 					cancelled = true;
@@ -319,7 +382,12 @@ fn run_pty_sync(
 
 		loop {
 			match reader_rx.try_recv() {
-				Ok(ReaderEvent::Chunk(chunk)) => emit_chunk(&chunk, on_chunk.as_ref()),
+				Ok(ReaderEvent::Chunk(chunk)) => {
+					if let Some(recorder) = recorder.as_mut() {
+						recorder.output(&chunk);
+					}
+					emit_chunk(&chunk, on_chunk.as_ref());
+				},
 				Ok(ReaderEvent::Done) => {
 					reader_done = true;
 					break;
@@ -354,7 +422,10 @@ fn run_pty_sync(
 
 	let _ = reader_thread.join();
 
-	Ok(PtyRunResult { exit_code, cancelled, timed_out })
+	let recording = recorder
+		.map(|recorder| recorder.finish(config.record_dir))
+		.transpose()?;
+	Ok(PtyRunResult { exit_code, cancelled, timed_out, recording })
 }
 
 fn emit_chunk(text: &str, callback: Option<&ThreadsafeFunction<String>>) {
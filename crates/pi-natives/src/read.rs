@@ -0,0 +1,223 @@
+//! Encoding-aware, size-guarded file reading.
+//!
+//! # Overview
+//! Reads at most `maxBytes` of a file, detects its encoding, decodes it to
+//! UTF-8 and returns a window of lines addressed by 1-indexed line numbers.
+//!
+//! Encoding detection, in order:
+//! 1. Byte order mark (UTF-8, UTF-16LE/BE)
+//! 2. UTF-16 without BOM, recognized by its NUL byte pattern
+//! 3. Valid UTF-8
+//! 4. [`chardetng`] guess among legacy encodings (e.g. windows-1252)
+//!
+//! Files containing NUL bytes that are not UTF-16 are reported as binary and
+//! not decoded.
+//!
+//! Text files also get a [`TextProfile`] (line ending, final newline, BOM)
+//! to pass back to `writeFileAtomic`, so edits keep the file's format.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.readFileSmart("src/main.rs", { offset: 100, limit: 50 })
+//! ```
+
+use std::{fs::File, io::Read};
+
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+	task,
+	text_profile::{self, TextProfile},
+	workspace, write,
+};
+
+const DEFAULT_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Bytes inspected for binary and UTF-16 detection.
+const SNIFF_BYTES: usize = 8 * 1024;
+
+/// Options for `readFileSmart`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ReadFileOptions {
+	/// First line to return, 1-indexed (default: 1).
+	pub offset:                  Option<u32>,
+	/// Maximum number of lines to return.
+	pub limit:                   Option<u32>,
+	/// Maximum number of bytes to read from the file (default: 16 MiB).
+	#[napi(js_name = "maxBytes")]
+	pub max_bytes:               Option<i64>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Result of `readFileSmart`.
+#[napi(object)]
+pub struct ReadFileResult {
+	/// Requested lines, decoded to UTF-8 without line terminators.
+	/// Empty for binary files.
+	pub lines:       Vec<String>,
+	/// Line number of the first entry in `lines`, 1-indexed.
+	#[napi(js_name = "startLine")]
+	pub start_line:  u32,
+	/// Number of lines in the portion of the file that was read.
+	#[napi(js_name = "totalLines")]
+	pub total_lines: u32,
+	/// Detected encoding label (e.g. "UTF-8", "UTF-16LE", "windows-1252"),
+	/// or "binary".
+	pub encoding:    String,
+	/// Whether the file looks binary.
+	pub binary:      bool,
+	/// File size in bytes.
+	pub size:        f64,
+	/// Whether the file was larger than `maxBytes` and only its head was read.
+	pub truncated:   bool,
+	/// Whether lines exist past the returned window.
+	#[napi(js_name = "hasMore")]
+	pub has_more:    bool,
+	/// Hex SHA-256 of the file's bytes, usable as `expectedHash` for
+	/// `writeFileAtomic`. Absent when the file was truncated.
+	pub hash:        Option<String>,
+	/// Line ending, final newline, BOM and encoding, for writing edits back
+	/// the same way. Absent for binary files; `finalNewline` is assumed when
+	/// the file was truncated.
+	pub profile:     Option<TextProfile>,
+}
+
+/// Guess UTF-16 byte order from the NUL pattern of mostly-ASCII text.
+fn sniff_utf16(sample: &[u8]) -> Option<&'static Encoding> {
+	let pairs = sample.len() / 2;
+	if pairs < 2 {
+		return None;
+	}
+	let (mut even_nul, mut odd_nul) = (0, 0);
+	for [even, odd] in sample.as_chunks::<2>().0 {
+		even_nul += usize::from(*even == 0);
+		odd_nul += usize::from(*odd == 0);
+	}
+	// ASCII in UTF-16 puts a NUL in every other byte and almost never in both.
+	if odd_nul * 10 >= pairs * 4 && even_nul * 10 < pairs {
+		Some(UTF_16LE)
+	} else if even_nul * 10 >= pairs * 4 && odd_nul * 10 < pairs {
+		Some(UTF_16BE)
+	} else {
+		None
+	}
+}
+
+/// Detect the encoding of `bytes`. Returns the encoding and BOM length, or
+/// `None` for binary content.
+fn detect_encoding(bytes: &[u8], truncated: bool) -> Option<(&'static Encoding, usize)> {
+	if let Some(bom) = Encoding::for_bom(bytes) {
+		return Some(bom);
+	}
+	let sample = &bytes[..bytes.len().min(SNIFF_BYTES)];
+	if let Some(encoding) = sniff_utf16(sample) {
+		return Some((encoding, 0));
+	}
+	if sample.contains(&0) {
+		return None;
+	}
+	match std::str::from_utf8(bytes) {
+		Ok(_) => return Some((UTF_8, 0)),
+		// A multi-byte character cut off by `maxBytes` is still UTF-8.
+		Err(err) if truncated && err.error_len().is_none() => return Some((UTF_8, 0)),
+		Err(_) => {},
+	}
+	let mut detector = EncodingDetector::new(Iso2022JpDetection::Deny);
+	detector.feed(bytes, !truncated);
+	Some((detector.guess(None, Utf8Detection::Allow), 0))
+}
+
+/// Decode a whole file. Returns the text without BOM, its encoding and
+/// whether it had a BOM, or `None` for binary content.
+pub(crate) fn decode(bytes: &[u8]) -> Option<(String, &'static Encoding, bool)> {
+	let (encoding, bom_len) = detect_encoding(bytes, false)?;
+	let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+	Some((text.into_owned(), encoding, bom_len > 0))
+}
+
+fn read_file_sync(path: &str, options: ReadFileOptions) -> Result<ReadFileResult> {
+	let file = File::open(path)
+		.map_err(|err| Error::from_reason(format!("Failed to open {path}: {err}")))?;
+	let size = file
+		.metadata()
+		.map_err(|err| Error::from_reason(format!("Failed to stat {path}: {err}")))?
+		.len();
+	let max_bytes = options
+		.max_bytes
+		.map_or(DEFAULT_MAX_BYTES, |bytes| bytes.max(0) as u64);
+	let truncated = size > max_bytes;
+
+	let mut bytes = Vec::with_capacity(size.min(max_bytes) as usize);
+	file
+		.take(max_bytes)
+		.read_to_end(&mut bytes)
+		.map_err(|err| Error::from_reason(format!("Failed to read {path}: {err}")))?;
+
+	let mut result = ReadFileResult {
+		lines: Vec::new(),
+		start_line: options.offset.unwrap_or(1).max(1),
+		total_lines: 0,
+		encoding: "binary".to_string(),
+		binary: true,
+		size: size as f64,
+		truncated,
+		has_more: false,
+		hash: (!truncated).then(|| write::content_hash(&bytes)),
+		profile: None,
+	};
+	let Some((encoding, bom_len)) = detect_encoding(&bytes, truncated) else {
+		return Ok(result);
+	};
+	let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+
+	let mut text: &str = &text;
+	if truncated {
+		// Drop the partial last line (and any partially decoded character).
+		text = text.rfind('\n').map_or("", |end| &text[..=end]);
+	}
+	let mut profile = text_profile::detect(text, encoding, bom_len > 0);
+	profile.final_newline |= truncated;
+	result.profile = Some(profile);
+	let text = text.strip_suffix('\n').unwrap_or(text);
+	let all_lines: Vec<&str> = if text.is_empty() {
+		Vec::new()
+	} else {
+		text.split('\n').collect()
+	};
+
+	let start = result.start_line as usize - 1;
+	let limit = options.limit.map_or(usize::MAX, |limit| limit as usize);
+	result.lines = all_lines
+		.iter()
+		.skip(start)
+		.take(limit)
+		.map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+		.collect();
+	result.total_lines = all_lines.len() as u32;
+	result.has_more = start.saturating_add(result.lines.len()) < all_lines.len();
+	result.encoding = encoding.name().to_string();
+	result.binary = false;
+	Ok(result)
+}
+
+/// Read a file as UTF-8 lines with encoding detection and a size guard.
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or read.
+#[napi(js_name = "readFileSmart")]
+pub fn read_file_smart(
+	path: String,
+	options: Option<ReadFileOptions>,
+) -> task::Async<ReadFileResult> {
+	task::blocking("read_file_smart", (), move |_| {
+		let options = options.unwrap_or_default();
+		workspace::check(&path, options.allow_outside_workspace)?;
+		read_file_sync(&path, options)
+	})
+}
@@ -0,0 +1,194 @@
+//! Readiness probes for services started in the background.
+//!
+//! # Overview
+//! `waitForReady` polls a single target every `intervalMs` until it is
+//! ready or `timeoutMs` passes:
+//! - **port**: a TCP connection to `host` (default: localhost) succeeds
+//! - **url**: a GET request answers with a 2xx status, after redirects
+//! - **path**: the file or directory exists
+//!
+//! Each attempt is cut off after [`ATTEMPT_TIMEOUT`] (or the time left), so a
+//! server that accepts connections but never answers is retried.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.waitForReady({ url: "http://localhost:5173", timeoutMs: 60_000 })
+//! ```
+
+use std::{
+	path::PathBuf,
+	time::{Duration, Instant},
+};
+
+use napi::{bindgen_prelude::*, tokio};
+use napi_derive::napi;
+
+use crate::task;
+
+const DEFAULT_TIMEOUT_MS: u32 = 30_000;
+const DEFAULT_INTERVAL_MS: u32 = 250;
+const DEFAULT_HOST: &str = "localhost";
+/// Longest single attempt.
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options for `waitForReady`. Exactly one of `port`, `url` and `path` must
+/// be given.
+#[napi(object)]
+pub struct WaitForReadyOptions<'env> {
+	/// TCP port that must accept connections.
+	pub port:        Option<u32>,
+	/// Host to connect to with `port` (default: "localhost", trying each of
+	/// its addresses).
+	pub host:        Option<String>,
+	/// http or https URL that must answer a GET with a 2xx status.
+	pub url:         Option<String>,
+	/// File or directory that must exist.
+	pub path:        Option<String>,
+	/// Give up after this many milliseconds (default: 30000).
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:  Option<u32>,
+	/// Delay between attempts in milliseconds (default: 250).
+	#[napi(js_name = "intervalMs")]
+	pub interval_ms: Option<u32>,
+	/// Abort signal for cancelling the wait.
+	pub signal:      Option<Unknown<'env>>,
+}
+
+/// Result of `waitForReady`.
+#[napi(object)]
+pub struct ReadyResult {
+	/// Number of attempts, including the successful one.
+	pub attempts:   u32,
+	/// Time until the target was ready, in milliseconds.
+	#[napi(js_name = "elapsedMs")]
+	pub elapsed_ms: f64,
+	/// HTTP status of the successful response, for `url`.
+	pub status:     Option<u32>,
+}
+
+enum Target {
+	Port { host: String, port: u16 },
+	Url { client: reqwest::Client, url: reqwest::Url },
+	Path(PathBuf),
+}
+
+impl Target {
+	fn parse(options: &WaitForReadyOptions) -> Result<Self> {
+		match (options.port, &options.url, &options.path) {
+			(Some(port), None, None) => {
+				let port = u16::try_from(port)
+					.ok()
+					.filter(|&port| port != 0)
+					.ok_or_else(|| Error::from_reason(format!("Invalid port: {port}")))?;
+				let host = options.host.as_deref().unwrap_or(DEFAULT_HOST).to_string();
+				Ok(Self::Port { host, port })
+			},
+			(None, Some(url), None) => {
+				let url = reqwest::Url::parse(url)
+					.map_err(|err| Error::from_reason(format!("Invalid URL {url}: {err}")))?;
+				if !matches!(url.scheme(), "http" | "https") {
+					return Err(Error::from_reason(format!(
+						"Unsupported URL scheme: {} (expected http or https)",
+						url.scheme()
+					)));
+				}
+				let client = reqwest::Client::builder()
+					.build()
+					.map_err(|err| Error::from_reason(format!("Failed to create HTTP client: {err}")))?;
+				Ok(Self::Url { client, url })
+			},
+			(None, None, Some(path)) => Ok(Self::Path(PathBuf::from(path))),
+			_ => Err(Error::from_reason("Expected exactly one of port, url and path")),
+		}
+	}
+
+	fn describe(&self) -> String {
+		match self {
+			Self::Port { host, port } => format!("{host}:{port}"),
+			Self::Url { url, .. } => url.to_string(),
+			Self::Path(path) => path.display().to_string(),
+		}
+	}
+
+	/// One attempt: `Ok` with the HTTP status (if any) once ready, or the
+	/// reason it is not.
+	async fn probe(&self) -> std::result::Result<Option<u32>, String> {
+		match self {
+			Self::Port { host, port } => tokio::net::TcpStream::connect((host.as_str(), *port))
+				.await
+				.map(|_| None)
+				.map_err(|err| err.to_string()),
+			Self::Url { client, url } => {
+				let response = client
+					.get(url.clone())
+					.send()
+					.await
+					.map_err(|err| err.to_string())?;
+				let status = response.status();
+				if status.is_success() {
+					Ok(Some(u32::from(status.as_u16())))
+				} else {
+					Err(format!("HTTP {status}"))
+				}
+			},
+			Self::Path(path) => match tokio::fs::try_exists(path).await {
+				Ok(true) => Ok(None),
+				Ok(false) => Err("does not exist".to_string()),
+				Err(err) => Err(err.to_string()),
+			},
+		}
+	}
+}
+
+async fn wait(target: Target, timeout: Duration, interval: Duration) -> Result<ReadyResult> {
+	let started = Instant::now();
+	let deadline = started + timeout;
+	let mut attempts = 0;
+	loop {
+		attempts += 1;
+		let left = deadline.saturating_duration_since(Instant::now());
+		let error = match tokio::time::timeout(left.min(ATTEMPT_TIMEOUT), target.probe()).await {
+			Ok(Ok(status)) => {
+				return Ok(ReadyResult {
+					attempts,
+					elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+					status,
+				});
+			},
+			Ok(Err(error)) => error,
+			Err(_) => "attempt timed out".to_string(),
+		};
+		if Instant::now() + interval >= deadline {
+			return Err(Error::from_reason(format!(
+				"Timed out after {}ms waiting for {} ({attempts} attempts, last: {error})",
+				timeout.as_millis(),
+				target.describe()
+			)));
+		}
+		tokio::time::sleep(interval).await;
+	}
+}
+
+/// Wait until a TCP port accepts connections, a URL answers with a 2xx
+/// status, or a path exists.
+///
+/// # Errors
+/// Returns an error for invalid options, when the target is not ready within
+/// `timeoutMs` (with the last failure), and on abort (`Aborted: ...`).
+#[napi(js_name = "waitForReady")]
+pub fn wait_for_ready<'env>(
+	env: &'env Env,
+	options: WaitForReadyOptions<'env>,
+) -> Result<PromiseRaw<'env, ReadyResult>> {
+	let target = Target::parse(&options)?;
+	let timeout = Duration::from_millis(options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS).into());
+	let interval = Duration::from_millis(options.interval_ms.unwrap_or(DEFAULT_INTERVAL_MS).into());
+	// The timeout is handled by `wait`, to report the last failure.
+	let ct = task::CancelToken::new(None, options.signal);
+	task::future(env, "wait_for_ready", async move {
+		tokio::select! {
+			result = wait(target, timeout, interval) => result,
+			reason = ct.wait() => Err(Error::from_reason(format!("Aborted: {reason:?}"))),
+		}
+	})
+}
@@ -0,0 +1,288 @@
+//! Recursive delete that stays inside the workspace.
+//!
+//! # Overview
+//! `removePathSafe` resolves the parent of the path it is given, so a
+//! symlinked directory on the way cannot redirect it, and refuses targets
+//! outside `root` or the `setWorkspaceRoots` roots, the roots themselves, the
+//! home directory, and the working directory or its parents. The tree is then
+//! listed without following symlinks, which are removed as links, and without
+//! crossing into other filesystems. Nothing is removed when the listing
+//! exceeds `maxEntries`, or at all with `dryRun`.
+//!
+//! Each entry is checked again right before it is removed; if it was
+//! replaced meanwhile (e.g. a directory swapped for a symlink), removal stops.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { entries } = await native.removePathSafe("build", { dryRun: true });
+//! ```
+
+use std::{
+	env, fs, io,
+	path::{Path, PathBuf},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{task, workspace};
+
+const DEFAULT_MAX_ENTRIES: u32 = 10_000;
+
+/// Options for `removePathSafe`.
+#[napi(object)]
+#[derive(Default)]
+pub struct RemovePathOptions {
+	/// Directory the path must be inside, in addition to the roots set with
+	/// `setWorkspaceRoots`.
+	pub root:                    Option<String>,
+	/// Refuse to remove more entries than this (default: 10000).
+	#[napi(js_name = "maxEntries")]
+	pub max_entries:             Option<u32>,
+	/// Report what would be removed without removing it (default: false).
+	#[napi(js_name = "dryRun")]
+	pub dry_run:                 Option<bool>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Result of `removePathSafe`.
+#[napi(object)]
+pub struct RemovePathResult {
+	/// Paths removed, or that would be with `dryRun`, each directory after
+	/// its contents. Empty when the path does not exist.
+	pub entries:     Vec<String>,
+	/// Regular files and other non-directories among `entries`.
+	pub files:       u32,
+	/// Directories among `entries`.
+	pub directories: u32,
+	/// Symlinks among `entries`, removed without touching their targets.
+	pub symlinks:    u32,
+	/// Total size of the files, in bytes.
+	pub bytes:       f64,
+	/// Whether the entries were removed.
+	pub removed:     bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+	File,
+	Directory,
+	Symlink,
+}
+
+/// An entry to remove, with the identity it had when listed.
+struct Entry {
+	path:     PathBuf,
+	kind:     Kind,
+	identity: (u64, u64),
+	len:      u64,
+}
+
+impl Kind {
+	fn of(meta: &fs::Metadata) -> Self {
+		if meta.is_symlink() {
+			Self::Symlink
+		} else if meta.is_dir() {
+			Self::Directory
+		} else {
+			Self::File
+		}
+	}
+}
+
+/// Device and inode of `meta`.
+#[cfg(unix)]
+fn identity(meta: &fs::Metadata) -> (u64, u64) {
+	use std::os::unix::fs::MetadataExt;
+	(meta.dev(), meta.ino())
+}
+
+#[cfg(not(unix))]
+const fn identity(_meta: &fs::Metadata) -> (u64, u64) {
+	(0, 0)
+}
+
+/// Refuse targets outside `root` or the workspace, `root` or a workspace root
+/// itself, the home directory, and the working directory or its parents.
+fn check_target(target: &Path, root: Option<&Path>, allow_outside: Option<bool>) -> Result<()> {
+	let refuse = |reason: &str| {
+		Err(Error::from_reason(format!("Refusing to remove {}: {reason}", target.display())))
+	};
+	if root.is_some_and(|root| !target.starts_with(root)) {
+		return refuse("it is outside the root");
+	}
+	if root == Some(target) {
+		return refuse("it is the root");
+	}
+	if workspace::roots().iter().any(|root| root == target) {
+		return refuse("it is a workspace root");
+	}
+	// The parent is resolved already; the target itself may be a symlink, which
+	// is removed rather than followed.
+	if target
+		.parent()
+		.is_some_and(|parent| workspace::check(parent, allow_outside).is_err())
+	{
+		return refuse("it is outside the workspace");
+	}
+	if env::home_dir().is_some_and(|home| home.starts_with(target)) {
+		return refuse("it is the home directory or contains it");
+	}
+	if env::current_dir().is_ok_and(|cwd| cwd.starts_with(target)) {
+		return refuse("it is the working directory or contains it");
+	}
+	Ok(())
+}
+
+/// List `target` and everything below it, each directory after its
+/// contents, failing once there are more than `max_entries`.
+fn list(target: &Path, meta: fs::Metadata, max_entries: usize) -> Result<Vec<Entry>> {
+	let too_many = || {
+		Error::from_reason(format!(
+			"Refusing to remove {}: more than {max_entries} entries",
+			target.display()
+		))
+	};
+	let device = identity(&meta).0;
+	let mut seen = 1;
+	let mut entries = Vec::new();
+	let mut stack = vec![(target.to_path_buf(), meta, false)];
+	while let Some((path, meta, listed)) = stack.pop() {
+		let kind = Kind::of(&meta);
+		if kind == Kind::Directory && !listed {
+			if identity(&meta).0 != device {
+				return Err(Error::from_reason(format!(
+					"Refusing to remove {}: {} is a mount point",
+					target.display(),
+					path.display()
+				)));
+			}
+			let mut children = Vec::new();
+			for child in fs::read_dir(&path).map_err(|err| io_error(&path, &err))? {
+				let child = child.map_err(|err| io_error(&path, &err))?;
+				let child_meta =
+					fs::symlink_metadata(child.path()).map_err(|err| io_error(&child.path(), &err))?;
+				seen += 1;
+				if seen > max_entries {
+					return Err(too_many());
+				}
+				children.push((child.path(), child_meta, false));
+			}
+			children.sort_by(|left, right| right.0.cmp(&left.0));
+			stack.push((path, meta, true));
+			stack.extend(children);
+			continue;
+		}
+		entries.push(Entry { path, kind, identity: identity(&meta), len: meta.len() });
+	}
+	if entries.len() > max_entries {
+		return Err(too_many());
+	}
+	Ok(entries)
+}
+
+fn io_error(path: &Path, err: &io::Error) -> Error {
+	Error::from_reason(format!("Failed to read {}: {err}", path.display()))
+}
+
+/// Remove a symlink without following it.
+fn remove_link(path: &Path) -> io::Result<()> {
+	#[cfg(windows)]
+	{
+		use std::os::windows::fs::FileTypeExt;
+		if fs::symlink_metadata(path)?.file_type().is_symlink_dir() {
+			return fs::remove_dir(path);
+		}
+	}
+	fs::remove_file(path)
+}
+
+fn remove(entries: &[Entry]) -> Result<()> {
+	for (removed, entry) in entries.iter().enumerate() {
+		let unchanged = fs::symlink_metadata(&entry.path)
+			.is_ok_and(|meta| Kind::of(&meta) == entry.kind && identity(&meta) == entry.identity);
+		if !unchanged {
+			return Err(Error::from_reason(format!(
+				"Stopped removing after {removed} entries: {} changed while being removed",
+				entry.path.display()
+			)));
+		}
+		let result = match entry.kind {
+			Kind::Directory => fs::remove_dir(&entry.path),
+			Kind::Symlink => remove_link(&entry.path),
+			Kind::File => fs::remove_file(&entry.path),
+		};
+		result.map_err(|err| {
+			Error::from_reason(format!(
+				"Stopped removing after {removed} entries: failed to remove {}: {err}",
+				entry.path.display()
+			))
+		})?;
+	}
+	Ok(())
+}
+
+fn remove_path_sync(path: &str, options: RemovePathOptions) -> Result<RemovePathResult> {
+	let target = workspace::resolve_entry(Path::new(path))
+		.ok_or_else(|| Error::from_reason(format!("Refusing to remove {path}")))??;
+	let root = match &options.root {
+		Some(root) => Some(workspace::resolve(Path::new(root))?),
+		None => None,
+	};
+	check_target(&target, root.as_deref(), options.allow_outside_workspace)?;
+
+	let mut result = RemovePathResult {
+		entries:     Vec::new(),
+		files:       0,
+		directories: 0,
+		symlinks:    0,
+		bytes:       0.0,
+		removed:     false,
+	};
+	let meta = match fs::symlink_metadata(&target) {
+		Ok(meta) => meta,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(result),
+		Err(err) => return Err(io_error(&target, &err)),
+	};
+	let max_entries = options.max_entries.unwrap_or(DEFAULT_MAX_ENTRIES) as usize;
+	let entries = list(&target, meta, max_entries)?;
+
+	for entry in &entries {
+		match entry.kind {
+			Kind::File => {
+				result.files += 1;
+				result.bytes += entry.len as f64;
+			},
+			Kind::Directory => result.directories += 1,
+			Kind::Symlink => result.symlinks += 1,
+		}
+	}
+	if !options.dry_run.unwrap_or(false) {
+		remove(&entries)?;
+		result.removed = true;
+	}
+	result.entries = entries
+		.into_iter()
+		.map(|entry| entry.path.to_string_lossy().into_owned())
+		.collect();
+	Ok(result)
+}
+
+/// Recursively remove `path` without following symlinks out of the
+/// workspace, within an entry-count cap.
+///
+/// # Errors
+/// Returns an error when the target is refused, exceeds `maxEntries`, spans
+/// a mount point, or cannot be listed or removed; entries removed before a
+/// failure stay removed.
+#[napi(js_name = "removePathSafe")]
+pub fn remove_path_safe(
+	path: String,
+	options: Option<RemovePathOptions>,
+) -> task::Async<RemovePathResult> {
+	task::blocking("remove_path_safe", (), move |_| {
+		remove_path_sync(&path, options.unwrap_or_default())
+	})
+}
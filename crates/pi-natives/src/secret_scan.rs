@@ -0,0 +1,446 @@
+//! Secret detection for redacting tool output and finding credential files.
+//!
+//! # Overview
+//! `scanSecrets` finds likely secrets in text with two kinds of rule:
+//! - token formats with a recognizable prefix or shape (AWS access keys,
+//!   GitHub, GitLab, Slack, Stripe, npm and LLM provider tokens, JWTs, private
+//!   key blocks, passwords in URLs)
+//! - values assigned to secret-looking names (`API_KEY=…`, `"password": …`)
+//!   whose Shannon entropy is high enough to rule out placeholders
+//!
+//! Rules are prefiltered with a [`RegexSet`], so text without secrets costs
+//! one pass. `redactSecrets` replaces the spans with a placeholder.
+//!
+//! `scanSecretFiles` walks a directory, ignoring `.gitignore` (credential
+//! files are usually ignored), and scans `.env` files, package manager and
+//! cloud credential files, and private keys.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const spans = native.scanSecrets(chunk) // [{ rule: "github-token", start: 6, end: 46, line: 1 }]
+//! // JS: const safe = native.redactSecrets(chunk) // "token=[REDACTED:github-token]"
+//! ```
+
+use std::{path::Path, sync::LazyLock};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use regex::{Regex, RegexSet};
+
+use crate::{fs_cache, task};
+
+const DEFAULT_MIN_ENTROPY: f64 = 3.0;
+const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// A secret-matching rule; `group` is the capture holding the secret.
+struct Rule {
+	id:      &'static str,
+	pattern: &'static str,
+	group:   usize,
+	/// Whether the match must pass the entropy check.
+	entropy: bool,
+}
+
+const RULES: &[Rule] = &[
+	Rule {
+		id:      "private-key",
+		pattern: r"-----BEGIN[ A-Z0-9_-]{0,100}PRIVATE KEY(?: BLOCK)?-----[\s\S]*?(?:-----END[ A-Z0-9_-]{0,100}PRIVATE KEY(?: BLOCK)?-----|\z)",
+		group:   0,
+		entropy: false,
+	},
+	Rule {
+		id:      "aws-access-key",
+		pattern: r"\b((?:AKIA|ASIA|ABIA|ACCA)[0-9A-Z]{16})\b",
+		group:   1,
+		entropy: false,
+	},
+	Rule {
+		id:      "github-token",
+		pattern: r"\b(gh[pousr]_[A-Za-z0-9]{36,255}|github_pat_[A-Za-z0-9_]{82})\b",
+		group:   1,
+		entropy: false,
+	},
+	Rule {
+		id:      "gitlab-token",
+		pattern: r"\b(glpat-[A-Za-z0-9_-]{20,})",
+		group:   1,
+		entropy: false,
+	},
+	Rule {
+		id:      "slack-token",
+		pattern: r"\b(xox[abposr]-[A-Za-z0-9-]{10,}|https://hooks\.slack\.com/services/[A-Za-z0-9/_]+)",
+		group:   1,
+		entropy: false,
+	},
+	Rule {
+		id:      "anthropic-key",
+		pattern: r"\b(sk-ant-[A-Za-z0-9_-]{20,})",
+		group:   1,
+		entropy: false,
+	},
+	Rule {
+		id:      "openai-key",
+		pattern: r"\b(sk-(?:proj-|svcacct-)?[A-Za-z0-9_-]{20,})",
+		group:   1,
+		entropy: true,
+	},
+	Rule {
+		id:      "google-api-key",
+		pattern: r"\b(AIza[0-9A-Za-z_-]{35})",
+		group:   1,
+		entropy: false,
+	},
+	Rule {
+		id:      "stripe-key",
+		pattern: r"\b([rs]k_(?:live|test)_[0-9a-zA-Z]{16,})",
+		group:   1,
+		entropy: false,
+	},
+	Rule { id: "npm-token", pattern: r"\b(npm_[A-Za-z0-9]{36})\b", group: 1, entropy: false },
+	Rule {
+		id:      "jwt",
+		pattern: r"\b(eyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,})",
+		group:   1,
+		entropy: false,
+	},
+	Rule {
+		id:      "url-password",
+		pattern: r"\b[a-zA-Z][a-zA-Z0-9+.-]*://[^/\s:@]+:([^/\s:@]{3,})@",
+		group:   1,
+		entropy: false,
+	},
+	Rule {
+		id:      "secret-assignment",
+		pattern: r#"(?i)[\w.-]*(?:secret|token|passw(?:or)?d|pwd|api[_-]?key|access[_-]?key|auth[_-]?key|credentials?|private[_-]?key)[\w.-]*["']?\s*(?::=|=>|:|=)\s*["']?([^\s"'`,;]{8,})"#,
+		group:   1,
+		entropy: true,
+	},
+];
+
+struct Rules {
+	set:     RegexSet,
+	regexes: Vec<Regex>,
+}
+
+static COMPILED: LazyLock<Rules> = LazyLock::new(|| Rules {
+	set:     RegexSet::new(RULES.iter().map(|rule| rule.pattern)).expect("valid secret rules"),
+	regexes: RULES
+		.iter()
+		.map(|rule| Regex::new(rule.pattern).expect("valid secret rule"))
+		.collect(),
+});
+
+/// Options for `scanSecrets` and `redactSecrets`.
+#[napi(object)]
+#[derive(Default)]
+pub struct SecretScanOptions {
+	/// Minimum Shannon entropy, in bits per character, for values of
+	/// secret-looking assignments and generic tokens (default: 3.0).
+	#[napi(js_name = "minEntropy")]
+	pub min_entropy:    Option<f64>,
+	/// Rule ids to skip (e.g. "jwt").
+	#[napi(js_name = "disabledRules")]
+	pub disabled_rules: Option<Vec<String>>,
+}
+
+/// A likely secret in scanned text.
+#[napi(object)]
+pub struct SecretMatch {
+	/// Id of the rule that matched (e.g. "aws-access-key").
+	pub rule:  String,
+	/// UTF-16 offset of the secret.
+	pub start: u32,
+	/// UTF-16 offset just past the secret.
+	pub end:   u32,
+	/// Line of the secret's start, 1-indexed.
+	pub line:  u32,
+}
+
+/// Options for `scanSecretFiles`.
+#[napi(object)]
+pub struct SecretFileScanOptions<'env> {
+	/// Scan every text file instead of only known credential files
+	/// (default: false).
+	#[napi(js_name = "allFiles")]
+	pub all_files:      Option<bool>,
+	/// Skip files larger than this many bytes (default: 1 MiB).
+	#[napi(js_name = "maxFileSize")]
+	pub max_file_size:  Option<u32>,
+	/// Minimum entropy, as for `scanSecrets`.
+	#[napi(js_name = "minEntropy")]
+	pub min_entropy:    Option<f64>,
+	/// Rule ids to skip.
+	#[napi(js_name = "disabledRules")]
+	pub disabled_rules: Option<Vec<String>>,
+	/// Abort signal for cancelling the scan.
+	pub signal:         Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the scan.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms:     Option<u32>,
+}
+
+/// Secrets found in a file.
+#[napi(object)]
+pub struct SecretFile {
+	/// Path relative to the scanned root, using forward slashes.
+	pub path:    String,
+	/// Whether the file is a known credential file (e.g. `.env`, `.npmrc`).
+	pub known:   bool,
+	/// Secrets in the file, in order.
+	pub matches: Vec<SecretMatch>,
+}
+
+/// Shannon entropy of `value` in bits per character.
+fn entropy(value: &str) -> f64 {
+	let mut counts = [0u32; 256];
+	for byte in value.bytes() {
+		counts[byte as usize] += 1;
+	}
+	let len = value.len() as f64;
+	counts
+		.iter()
+		.filter(|&&count| count > 0)
+		.map(|&count| {
+			let p = f64::from(count) / len;
+			-p * p.log2()
+		})
+		.sum()
+}
+
+/// Whether an assigned value is a reference or placeholder rather than a
+/// secret.
+fn is_placeholder(value: &str) -> bool {
+	value.starts_with(['$', '<', '{', '%'])
+		|| value.contains("${")
+		|| value.contains("{{")
+		|| value
+			.chars()
+			.all(|c| c == value.chars().next().unwrap_or_default())
+		|| ["example", "changeme", "placeholder", "redacted", "your_", "your-", "xxxx"]
+			.iter()
+			.any(|word| value.to_ascii_lowercase().contains(word))
+}
+
+struct Config {
+	min_entropy: f64,
+	disabled:    Vec<String>,
+}
+
+impl Config {
+	fn new(min_entropy: Option<f64>, disabled: Option<Vec<String>>) -> Self {
+		Self {
+			min_entropy: min_entropy.unwrap_or(DEFAULT_MIN_ENTROPY),
+			disabled:    disabled.unwrap_or_default(),
+		}
+	}
+}
+
+/// A secret's byte range in the scanned text.
+pub(crate) struct Span {
+	pub(crate) rule:  &'static str,
+	pub(crate) start: usize,
+	pub(crate) end:   usize,
+}
+
+/// Secret spans in `text`, sorted and non-overlapping.
+fn find(text: &str, config: &Config) -> Vec<Span> {
+	let mut spans = Vec::new();
+	for index in &COMPILED.set.matches(text) {
+		let rule = &RULES[index];
+		if config.disabled.iter().any(|id| id == rule.id) {
+			continue;
+		}
+		for captures in COMPILED.regexes[index].captures_iter(text) {
+			let Some(secret) = captures.get(rule.group) else {
+				continue;
+			};
+			if rule.entropy
+				&& (is_placeholder(secret.as_str()) || entropy(secret.as_str()) < config.min_entropy)
+			{
+				continue;
+			}
+			spans.push(Span { rule: rule.id, start: secret.start(), end: secret.end() });
+		}
+	}
+	// Earlier rules are more specific; keep the first of overlapping spans.
+	spans.sort_by_key(|span| span.start);
+	let mut kept: Vec<Span> = Vec::with_capacity(spans.len());
+	for span in spans {
+		match kept.last_mut() {
+			Some(last) if span.start < last.end => {
+				let specific = |rule| RULES.iter().position(|candidate| candidate.id == rule);
+				if specific(span.rule) < specific(last.rule) && span.end >= last.end {
+					*last = span;
+				} else {
+					last.end = last.end.max(span.end);
+				}
+			},
+			_ => kept.push(span),
+		}
+	}
+	kept
+}
+
+/// Secret spans in `text` with the default entropy threshold and all rules.
+pub(crate) fn find_default(text: &str) -> Vec<Span> {
+	find(text, &Config::new(None, None))
+}
+
+/// Convert sorted byte spans to UTF-16 offsets and lines.
+fn to_matches(text: &str, spans: &[Span]) -> Vec<SecretMatch> {
+	let mut matches = Vec::with_capacity(spans.len());
+	let (mut byte, mut utf16, mut line) = (0, 0u32, 1u32);
+	let mut advance = |to: usize| {
+		for c in text[byte..to].chars() {
+			utf16 += c.len_utf16() as u32;
+			line += u32::from(c == '\n');
+		}
+		byte = to;
+		(utf16, line)
+	};
+	for span in spans {
+		let (start, start_line) = advance(span.start);
+		let (end, _) = advance(span.end);
+		matches.push(SecretMatch { rule: span.rule.to_string(), start, end, line: start_line });
+	}
+	matches
+}
+
+/// `text` with each span replaced by `[REDACTED:<rule>]`.
+fn redact(text: &str, spans: &[Span]) -> String {
+	let mut out = String::with_capacity(text.len());
+	let mut pos = 0;
+	for span in spans {
+		out.push_str(&text[pos..span.start]);
+		out.push_str("[REDACTED:");
+		out.push_str(span.rule);
+		out.push(']');
+		pos = span.end;
+	}
+	out.push_str(&text[pos..]);
+	out
+}
+
+/// Find likely secrets in `text`.
+#[napi(js_name = "scanSecrets")]
+pub fn scan_secrets(text: String, options: Option<SecretScanOptions>) -> Vec<SecretMatch> {
+	let options = options.unwrap_or_default();
+	let config = Config::new(options.min_entropy, options.disabled_rules);
+	to_matches(&text, &find(&text, &config))
+}
+
+/// Replace likely secrets in `text` with `[REDACTED:<rule>]`.
+#[napi(js_name = "redactSecrets")]
+pub fn redact_secrets(text: String, options: Option<SecretScanOptions>) -> String {
+	let options = options.unwrap_or_default();
+	let config = Config::new(options.min_entropy, options.disabled_rules);
+	redact(&text, &find(&text, &config))
+}
+
+/// Whether `name` is a file that conventionally holds credentials.
+fn is_credential_file(path: &Path) -> bool {
+	let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+		return false;
+	};
+	let extension = path
+		.extension()
+		.and_then(|extension| extension.to_str())
+		.map(str::to_ascii_lowercase);
+	name == ".env"
+		|| name.starts_with(".env.")
+		|| matches!(
+			name,
+			".npmrc"
+				| ".pypirc"
+				| ".netrc"
+				| ".git-credentials"
+				| ".dockercfg"
+				| "credentials"
+				| "credentials.json"
+				| "secrets.json"
+				| "secrets.yaml"
+				| "secrets.yml"
+				| "id_rsa"
+				| "id_dsa"
+				| "id_ecdsa"
+				| "id_ed25519"
+				| ".htpasswd"
+		)
+		|| matches!(extension.as_deref(), Some("env" | "pem" | "key" | "tfvars"))
+		|| (name == "config.json" && path.parent().is_some_and(|dir| dir.ends_with(".docker")))
+}
+
+fn scan_files(
+	root: &Path,
+	all_files: bool,
+	max_file_size: u64,
+	config: &Config,
+	ct: &task::CancelToken,
+) -> Result<Vec<SecretFile>> {
+	let mut files = Vec::new();
+	let mut builder = fs_cache::build_walker(root, true, false);
+	builder.filter_entry(|entry| {
+		!matches!(entry.file_name().to_str(), Some(".git" | "node_modules" | "target"))
+	});
+	for entry in builder.build() {
+		ct.heartbeat()?;
+		let Ok(entry) = entry else { continue };
+		if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+			continue;
+		}
+		let path = entry.path();
+		let known = is_credential_file(path);
+		if !(known || all_files)
+			|| entry
+				.metadata()
+				.map_or(true, |metadata| metadata.len() > max_file_size)
+		{
+			continue;
+		}
+		let Ok(bytes) = std::fs::read(path) else {
+			continue;
+		};
+		if bytes.contains(&0) {
+			continue;
+		}
+		let text = String::from_utf8_lossy(&bytes);
+		let spans = find(&text, config);
+		if known || !spans.is_empty() {
+			files.push(SecretFile {
+				path: fs_cache::normalize_relative_path(root, path).into_owned(),
+				known,
+				matches: to_matches(&text, &spans),
+			});
+		}
+	}
+	Ok(files)
+}
+
+/// Find credential files under `root` and the secrets in them.
+///
+/// Known credential files are always listed, even without matches; with
+/// `allFiles`, other text files are listed when they contain secrets.
+///
+/// # Errors
+/// Returns an error when `root` is not a directory, or on cancellation or
+/// timeout.
+#[napi(js_name = "scanSecretFiles")]
+pub fn scan_secret_files(
+	root: String,
+	options: Option<SecretFileScanOptions<'_>>,
+) -> task::Async<Vec<SecretFile>> {
+	let (all_files, max_file_size, config, ct) = match options {
+		Some(options) => (
+			options.all_files.unwrap_or(false),
+			options
+				.max_file_size
+				.map_or(DEFAULT_MAX_FILE_SIZE, u64::from),
+			Config::new(options.min_entropy, options.disabled_rules),
+			task::CancelToken::new(options.timeout_ms, options.signal),
+		),
+		None => (false, DEFAULT_MAX_FILE_SIZE, Config::new(None, None), task::CancelToken::default()),
+	};
+	task::blocking("scan_secret_files", ct, move |ct| {
+		scan_files(&fs_cache::resolve_search_path(&root)?, all_files, max_file_size, &config, &ct)
+	})
+}
@@ -0,0 +1,72 @@
+//! Credential storage in the OS keychain.
+//!
+//! # Overview
+//! Secrets are stored per `service` and `key` in the platform credential
+//! store: the D-Bus Secret Service (GNOME Keyring and others) on Linux and
+//! BSD, the login Keychain on macOS, and Credential Manager on Windows.
+//! Values never touch the filesystem in plaintext.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.storeSecret("omp", "anthropic-api-key", key)
+//! // JS: const key = await native.getSecret("omp", "anthropic-api-key")
+//! ```
+
+use keyring::{Entry, Error as KeyringError};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::task;
+
+fn entry(service: &str, key: &str) -> Result<Entry> {
+	Entry::new(service, key)
+		.map_err(|err| Error::from_reason(format!("Invalid keychain entry {service}/{key}: {err}")))
+}
+
+/// Store `value` under `service` and `key`, replacing any existing value.
+///
+/// # Errors
+/// Returns an error if no credential store is available (e.g. no Secret
+/// Service on a headless Linux machine) or it rejects the write.
+#[napi(js_name = "storeSecret")]
+pub fn store_secret(service: String, key: String, value: String) -> task::Async<()> {
+	task::blocking("secrets.store", (), move |_| {
+		entry(&service, &key)?.set_password(&value).map_err(|err| {
+			Error::from_reason(format!("Failed to store secret {service}/{key}: {err}"))
+		})
+	})
+}
+
+/// Read the value stored under `service` and `key`.
+///
+/// Returns `None` if no value is stored.
+///
+/// # Errors
+/// Returns an error if no credential store is available or the stored value
+/// cannot be read.
+#[napi(js_name = "getSecret")]
+pub fn get_secret(service: String, key: String) -> task::Async<Option<String>> {
+	task::blocking("secrets.get", (), move |_| match entry(&service, &key)?.get_password() {
+		Ok(value) => Ok(Some(value)),
+		Err(KeyringError::NoEntry) => Ok(None),
+		Err(err) => Err(Error::from_reason(format!("Failed to read secret {service}/{key}: {err}"))),
+	})
+}
+
+/// Delete the value stored under `service` and `key`.
+///
+/// Returns false if no value was stored.
+///
+/// # Errors
+/// Returns an error if no credential store is available or it rejects the
+/// deletion.
+#[napi(js_name = "deleteSecret")]
+pub fn delete_secret(service: String, key: String) -> task::Async<bool> {
+	task::blocking("secrets.delete", (), move |_| match entry(&service, &key)?.delete_credential() {
+		Ok(()) => Ok(true),
+		Err(KeyringError::NoEntry) => Ok(false),
+		Err(err) => {
+			Err(Error::from_reason(format!("Failed to delete secret {service}/{key}: {err}")))
+		},
+	})
+}
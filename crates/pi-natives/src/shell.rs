@@ -18,29 +18,59 @@ use std::{
 	collections::HashMap,
 	fs,
 	io::{self, Write},
+	path::{Path, PathBuf},
+	process::{ExitStatus, Stdio},
 	str,
-	sync::Arc,
-	time::Duration,
+	sync::{
+		Arc, OnceLock,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+mod analyze;
+mod ansi;
+mod audit;
+mod builtin_policy;
+mod cgroup;
+mod complete;
+mod detached;
+mod executions;
+mod exit;
+mod home;
+mod interpreter;
+mod json_lines;
+mod limits;
+mod network;
+mod policy;
+mod priority;
+mod prompt;
+mod queue;
+mod quote;
+mod redact;
+mod sandbox;
+mod syntax;
+mod tracker;
 #[cfg(windows)]
 mod windows;
+mod words;
 
 use brush_builtins::{BuiltinSet, default_builtins};
 use brush_core::{
 	CreateOptions, ExecutionContext, ExecutionControlFlow, ExecutionExitCode, ExecutionResult,
-	ProcessGroupPolicy, Shell as BrushShell, ShellValue, ShellVariable, builtins,
+	ProcessGroupPolicy, Shell as BrushShell, ShellValue, ShellVariable, SpawnHooks as _, builtins,
 	env::EnvironmentScope,
 	openfiles::{self, OpenFile, OpenFiles},
 	sys, traps,
 };
+use bstr::ByteSlice as _;
 use clap::Parser;
 use napi::{
 	bindgen_prelude::*,
 	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
 	tokio::{
 		self,
-		sync::{Mutex as TokioMutex, mpsc},
+		sync::{Mutex as TokioMutex, Notify, mpsc},
 		time,
 	},
 };
@@ -50,7 +80,75 @@ use tokio_util::sync::CancellationToken;
 #[cfg(windows)]
 use windows::configure_windows_path;
 
-use crate::task;
+pub use self::{
+	analyze::{
+		ShellAnalyzedAssignment, ShellAnalyzedCommand, ShellAnalyzedRedirect, ShellCommandAnalysis,
+		ShellRedirectKind, ShellRisk, ShellRiskKind, analyze_shell_command,
+	},
+	audit::{AuditLogEntry, AuditLogFilter, AuditLogOptions, query_audit_log, set_audit_log},
+	builtin_policy::ShellBuiltinPolicy,
+	cgroup::ShellResourceUsage,
+	complete::{CommandLineCompletions, CompleteCommandLineOptions, complete_command_line},
+	detached::{
+		AttachExecutionOptions, DetachedExecution, DetachedShellOptions, StopExecutionOptions,
+		attach_execution, execute_shell_detached, list_detached_executions, stop_execution,
+	},
+	executions::{
+		ExecutionOutputOptions, ShellExecutionInfo, ShellExecutionOutput, cancel_shell_execution,
+		get_execution_output, list_shell_executions,
+	},
+	exit::ShellTermination,
+	home::ShellIsolatedHome,
+	json_lines::ShellJsonLine,
+	limits::ShellResourceLimits,
+	network::ShellNetworkAllowlist,
+	policy::{
+		CommandPolicy, CommandPolicyDecision, CommandPolicyOptions, CommandPolicyRule,
+		evaluate_command_policy, set_command_policy,
+	},
+	prompt::ShellInputPrompt,
+	queue::set_max_concurrent_executions,
+	quote::{BuildCommandOptions, ShellDialect, build_command, shell_quote},
+	redact::{RedactionOptions, RedactionRule, set_redaction_rules},
+	sandbox::ShellSandbox,
+	syntax::{ShellSyntaxError, check_shell_syntax},
+	words::{ExpandShellWordsOptions, expand_shell_words},
+};
+use self::{
+	ansi::AnsiStripper, executions::ExecutionProgress, home::IsolatedHome, interpreter::Interpreter,
+	json_lines::JsonLines, priority::Priority, prompt::PromptWatch, redact::Redactor,
+	tracker::SpawnTracker,
+};
+use crate::{ps, task};
+
+const REPLACEMENT: &str = "\u{FFFD}";
+
+/// Bash builtins disabled in every session.
+const DISABLED_BUILTINS: [&str; 2] = ["exec", "suspend"];
+
+/// Builtins implemented here and registered in every session.
+const NATIVE_BUILTINS: [&str; 2] = ["sleep", "timeout"];
+
+/// Whether sessions run `name` as a builtin instead of looking it up on
+/// `PATH`.
+pub(crate) fn is_builtin(name: &str) -> bool {
+	static NAMES: OnceLock<Vec<String>> = OnceLock::new();
+	NAMES
+		.get_or_init(|| {
+			default_builtins(BuiltinSet::BashMode)
+				.into_keys()
+				.filter(|name| !DISABLED_BUILTINS.contains(&name.as_str()))
+				.chain(NATIVE_BUILTINS.map(String::from))
+				.collect()
+		})
+		.iter()
+		.any(|builtin| builtin == name)
+}
+
+/// Streamed output callback: text chunks, or raw chunks in binary mode,
+/// input prompt reports and parsed JSON lines.
+type OutputCallback =
+	ThreadsafeFunction<Either4<String, ShellOutputChunk, ShellInputPrompt, ShellJsonLine>>;
 
 struct ShellSessionCore {
 	shell:         BrushShell,
@@ -59,54 +157,244 @@ struct ShellSessionCore {
 
 #[derive(Clone)]
 struct ShellConfig {
-	session_env:   Option<HashMap<String, String>>,
-	snapshot_path: Option<String>,
+	session_env:    Option<HashMap<String, String>>,
+	snapshot_path:  Option<String>,
+	builtin_policy: Option<ShellBuiltinPolicy>,
 }
 
 /// Options for configuring a persistent shell session.
 #[napi(object)]
 pub struct ShellOptions {
 	/// Environment variables to apply once per session.
-	pub session_env:   Option<HashMap<String, String>>,
+	pub session_env:    Option<HashMap<String, String>>,
 	/// Optional snapshot file to source on session creation.
-	pub snapshot_path: Option<String>,
+	pub snapshot_path:  Option<String>,
+	/// Builtins the session may use.
+	pub builtin_policy: Option<ShellBuiltinPolicy>,
 }
 
 /// Options for running a shell command (internal, lifetime-free).
 struct ShellRunConfig {
 	/// Command string to execute in the shell.
-	command: String,
+	command:              String,
 	/// Working directory for the command.
-	cwd:     Option<String>,
+	cwd:                  Option<String>,
 	/// Environment variables to apply for this command only.
-	env:     Option<HashMap<String, String>>,
+	env:                  Option<HashMap<String, String>>,
+	/// Timeout in milliseconds, counted from when the command leaves the
+	/// execution queue.
+	timeout_ms:           Option<u32>,
+	/// Resource limits for spawned processes.
+	limits:               Option<ShellResourceLimits>,
+	/// Filesystem and network confinement for spawned processes.
+	sandbox:              Option<ShellSandbox>,
+	/// Network egress policy for spawned processes.
+	network:              Option<Either<String, ShellNetworkAllowlist>>,
+	/// Program that runs the command.
+	interpreter:          Interpreter,
+	/// Scheduling priority of spawned processes.
+	priority:             Priority,
+	/// Stop streaming after this many output bytes.
+	max_output_bytes:     Option<u64>,
+	/// Stop streaming after this many output lines.
+	max_output_lines:     Option<u64>,
+	/// Kill the command once an output limit is hit.
+	kill_on_output_limit: bool,
+	/// Report a prompt after this long without output.
+	prompt_idle:          Option<Duration>,
+	/// Kill the command after this long without output.
+	idle_timeout:         Option<Duration>,
+	/// Record the working directory and environment the command starts with.
+	capture_environment:  bool,
+	/// Remove ANSI escape sequences from streamed output.
+	strip_ansi:           bool,
+	/// Stream raw bytes instead of decoded text.
+	binary_output:        bool,
+	/// Deliver lines holding JSON as parsed objects.
+	json_lines:           bool,
+	/// Progress reported by `listShellExecutions`.
+	progress:             Arc<ExecutionProgress>,
+}
+
+/// Outcome of a single command run within a session.
+struct CommandOutcome {
+	/// Exit code of the command.
+	exit_code:        i32,
+	/// Whether the session can run further commands.
+	keepalive:        bool,
+	/// Whether streamed output was cut off by an output limit.
+	output_truncated: bool,
+	/// Whether the command was killed because of an output limit.
+	output_killed:    bool,
+	/// Whether the command was killed for producing no output for too long.
+	idle_timed_out:   bool,
+	/// When the first output byte arrived.
+	first_output:     Option<Instant>,
+	/// Time from starting the command until it exited.
+	command_time:     Duration,
+	/// Time spent draining trailing output after the command exited.
+	drain_time:       Duration,
+	/// CPU time of the spawned command tree, when available.
+	cpu_time:         Option<ps::CpuTime>,
+	/// The signal that ended the command, if one did.
+	termination:      Option<ShellTermination>,
+	/// Whether the OOM killer killed one of the command's processes.
+	oom_killed:       bool,
+	/// Peak usage of the command's cgroup, if it ran in one.
+	usage:            Option<ShellResourceUsage>,
+	/// Working directory and environment the command started with.
+	environment:      Option<ShellEnvironment>,
+}
+
+/// Where emitted output goes: the callback, and the execution's scrollback
+/// for `getExecutionOutput`.
+struct OutputSink {
+	callback: Option<Arc<OutputCallback>>,
+	progress: Arc<ExecutionProgress>,
+}
+
+impl OutputSink {
+	fn callback(&self) -> Option<&OutputCallback> {
+		self.callback.as_deref()
+	}
+
+	fn record(&self, bytes: &[u8]) {
+		self.progress.record_emitted(bytes);
+	}
+}
+
+/// Reports output to the post-exit drain loop and the idle timeout, and
+/// records when output first arrived.
+struct OutputActivity {
+	notify:   mpsc::Sender<()>,
+	first:    Arc<OnceLock<Instant>>,
+	idle:     Option<Arc<Notify>>,
+	prompt:   Option<Arc<PromptWatch>>,
+	progress: Arc<ExecutionProgress>,
+}
+
+impl OutputActivity {
+	fn record(&self, bytes: &[u8]) {
+		self.first.get_or_init(Instant::now);
+		self.progress.record_output(bytes.len());
+		let _ = self.notify.try_send(());
+		if let Some(idle) = &self.idle {
+			idle.notify_one();
+		}
+		if let Some(prompt) = &self.prompt {
+			prompt.record(bytes);
+		}
+	}
+}
+
+/// Byte and line budget for streamed output.
+struct OutputLimit {
+	max_bytes:     Option<u64>,
+	max_lines:     Option<u64>,
+	bytes:         u64,
+	lines:         u64,
+	at_line_start: bool,
+	/// Cancelled once the budget is exhausted, if the command should be killed.
+	kill:          Option<CancellationToken>,
+	truncated:     Arc<AtomicBool>,
+}
+
+impl OutputLimit {
+	/// Account for `chunk` and return how many of its bytes fit the budget,
+	/// plus the truncation marker once the budget is exhausted.
+	fn admit(&mut self, chunk: &[u8]) -> (usize, Option<String>) {
+		let mut take = chunk.len();
+		let mut reason = None;
+		if let Some(max) = self.max_bytes {
+			let remaining = max.saturating_sub(self.bytes);
+			if take as u64 > remaining {
+				take = remaining as usize;
+				reason = Some(format!("{max} bytes"));
+			}
+		}
+		if let Some(max) = self.max_lines {
+			let remaining = max.saturating_sub(self.lines) as usize;
+			let cut = if remaining == 0 {
+				Some(0)
+			} else {
+				chunk[..take]
+					.find_iter(b"\n")
+					.nth(remaining - 1)
+					.map(|idx| idx + 1)
+			};
+			if let Some(cut) = cut
+				&& cut < take
+			{
+				take = cut;
+				reason = Some(format!("{max} lines"));
+			}
+		}
+
+		let admitted = &chunk[..take];
+		self.bytes += take as u64;
+		self.lines += admitted.find_iter(b"\n").count() as u64;
+		if let Some(&last) = admitted.last() {
+			self.at_line_start = last == b'\n';
+		}
+
+		let marker = reason.map(|reason| {
+			let sep = if self.at_line_start { "" } else { "\n" };
+			format!("{sep}[output truncated after {reason}]\n")
+		});
+		(take, marker)
+	}
+
+	/// Mark the output as truncated and kill the command if requested.
+	/// Returns true when the command is being killed.
+	fn trip(&self) -> bool {
+		self.truncated.store(true, Ordering::Relaxed);
+		self.kill.as_ref().inspect(|kill| kill.cancel()).is_some()
+	}
 }
 
 /// Options for running a shell command.
-#[napi(object)]
+#[napi(object, object_to_js = false)]
 pub struct ShellRunOptions<'env> {
 	/// Command string to execute in the shell.
-	pub command:    String,
+	pub command:         String,
 	/// Working directory for the command.
-	pub cwd:        Option<String>,
+	pub cwd:             Option<String>,
 	/// Environment variables to apply for this command only.
-	pub env:        Option<HashMap<String, String>>,
-	/// Timeout in milliseconds before cancelling the command.
+	pub env:             Option<HashMap<String, String>>,
+	/// Timeout in milliseconds before cancelling the command, not counting
+	/// time spent queued under `setMaxConcurrentExecutions`.
 	#[napi(js_name = "timeoutMs")]
-	pub timeout_ms: Option<u32>,
+	pub timeout_ms:      Option<u32>,
+	/// Kill the command once it has produced no output for this many
+	/// milliseconds.
+	#[napi(js_name = "idleTimeoutMs")]
+	pub idle_timeout_ms: Option<u32>,
+	/// Run the command with `HOME` and the XDG directories in a home of its
+	/// own: `true` for a temporary one, or where to put it and what to seed
+	/// it with.
+	#[napi(js_name = "isolatedHome", ts_type = "boolean | ShellIsolatedHome")]
+	pub isolated_home:   Option<Either<bool, ShellIsolatedHome>>,
+	/// Called with the execution id as soon as the command is registered,
+	/// before it waits for a slot, for `cancelShellExecution` and
+	/// `getExecutionOutput`.
+	#[napi(js_name = "onStart", ts_type = "TsFunc<number>")]
+	pub on_start:        Option<ThreadsafeFunction<u32>>,
 	/// Abort signal for cancelling the operation.
-	pub signal:     Option<Unknown<'env>>,
+	pub signal:          Option<Unknown<'env>>,
 }
 
 /// Result of running a shell command.
 #[napi(object)]
 pub struct ShellRunResult {
 	/// Exit code when the command completes normally.
-	pub exit_code: Option<i32>,
+	pub exit_code:      Option<i32>,
 	/// Whether the command was cancelled via abort.
-	pub cancelled: bool,
+	pub cancelled:      bool,
 	/// Whether the command timed out before completion.
-	pub timed_out: bool,
+	pub timed_out:      bool,
+	/// Whether the timeout was the idle timeout.
+	#[napi(js_name = "idleTimedOut")]
+	pub idle_timed_out: bool,
 }
 
 /// Persistent brush-core shell session.
@@ -124,8 +412,12 @@ impl Shell {
 	/// The options set session-scoped environment variables and a snapshot path.
 	pub fn new(options: Option<ShellOptions>) -> Self {
 		let config = options.map_or_else(
-			|| ShellConfig { session_env: None, snapshot_path: None },
-			|opt| ShellConfig { session_env: opt.session_env, snapshot_path: opt.snapshot_path },
+			|| ShellConfig { session_env: None, snapshot_path: None, builtin_policy: None },
+			|opt| ShellConfig {
+				session_env:    opt.session_env,
+				snapshot_path:  opt.snapshot_path,
+				builtin_policy: opt.builtin_policy,
+			},
 		);
 		Self { session: Arc::new(TokioMutex::new(None)), config }
 	}
@@ -140,19 +432,62 @@ impl Shell {
 		&self,
 		env: &'e Env,
 		options: ShellRunOptions<'e>,
-		#[napi(ts_arg_type = "((chunk: string) => void) | undefined | null")] on_chunk: Option<
-			ThreadsafeFunction<String>,
-		>,
+		#[napi(ts_arg_type = "((chunk: string | ShellOutputChunk | ShellInputPrompt | \
+		                      ShellJsonLine) => void) | undefined | null")]
+		on_chunk: Option<OutputCallback>,
 	) -> Result<PromiseRaw<'e, ShellRunResult>> {
-		let ct = task::CancelToken::new(options.timeout_ms, options.signal);
+		let ct = task::CancelToken::new(None, options.signal);
 		let session = self.session.clone();
 		let config = self.config.clone();
 
-		let run_config =
-			ShellRunConfig { command: options.command, cwd: options.cwd, env: options.env };
+		let mut run_config = ShellRunConfig {
+			command:              options.command,
+			cwd:                  options.cwd,
+			env:                  options.env,
+			timeout_ms:           options.timeout_ms,
+			limits:               None,
+			sandbox:              None,
+			network:              None,
+			interpreter:          Interpreter::Brush,
+			priority:             Priority::Normal,
+			max_output_bytes:     None,
+			max_output_lines:     None,
+			kill_on_output_limit: false,
+			prompt_idle:          None,
+			idle_timeout:         options
+				.idle_timeout_ms
+				.map(|ms| Duration::from_millis(u64::from(ms))),
+			capture_environment:  false,
+			strip_ansi:           false,
+			binary_output:        false,
+			json_lines:           false,
+			progress:             Arc::default(),
+		};
 
+		let audit = audit::AuditRecord::start(&run_config.command, run_config.cwd.clone(), [
+			config.session_env.as_ref(),
+			run_config.env.as_ref(),
+		]);
+		let progress = run_config.progress.clone();
+		let isolated_home = options.isolated_home;
+		let on_start = options.on_start;
 		task::future(env, "shell.run", async move {
-			run_shell_session(session, config, run_config, on_chunk, ct).await
+			let result = async {
+				let home = IsolatedHome::create(isolated_home)?;
+				if let Some(home) = &home {
+					home.apply(&mut run_config.env, None);
+				}
+				run_shell_session(session, config, run_config, on_start, on_chunk, ct).await
+			}
+			.await;
+			if let Some(audit) = audit {
+				let ending = result.as_ref().map_or_else(
+					|_| audit::Ending::error(),
+					|res| audit::Ending::of(res.exit_code, res.cancelled, res.timed_out),
+				);
+				audit.finish(ending, progress.output_bytes()).await;
+			}
+			result
 		})
 	}
 
@@ -175,9 +510,28 @@ async fn run_shell_session(
 	session: Arc<TokioMutex<Option<ShellSessionCore>>>,
 	config: ShellConfig,
 	run_config: ShellRunConfig,
-	on_chunk: Option<ThreadsafeFunction<String>>,
+	on_start: Option<ThreadsafeFunction<u32>>,
+	on_chunk: Option<OutputCallback>,
 	mut ct: task::CancelToken,
 ) -> Result<ShellRunResult> {
+	let registration = executions::register(
+		&run_config.command,
+		SystemTime::now(),
+		ct.emplace_abort_token(),
+		run_config.progress.clone(),
+	);
+	report_start(on_start, &registration);
+	let _slot = match wait_for_slot(&mut ct, run_config.timeout_ms, &run_config.progress).await {
+		Ok(slot) => slot,
+		Err(reason) => {
+			return Ok(ShellRunResult {
+				exit_code:      None,
+				cancelled:      matches!(reason, task::AbortReason::Signal),
+				timed_out:      false,
+				idle_timed_out: false,
+			});
+		},
+	};
 	let tokio_cancel = CancellationToken::new();
 
 	let mut run_task = tokio::spawn({
@@ -207,16 +561,19 @@ async fn run_shell_session(
 			}
 			*session.lock().await = None;
 			return Ok(ShellRunResult {
-				exit_code: None,
-				cancelled: matches!(reason, task::AbortReason::Signal),
-				timed_out: matches!(reason, task::AbortReason::Timeout),
+				exit_code:      None,
+				cancelled:      matches!(reason, task::AbortReason::Signal),
+				timed_out:      matches!(reason, task::AbortReason::Timeout),
+				idle_timed_out: false,
 			});
 		}
 	};
 	let res =
 		res.unwrap_or_else(|e| Err(Error::from_reason(format!("Shell execution task failed: {e}"))));
 
-	let keepalive = res.as_ref().is_ok_and(session_keepalive);
+	let keepalive = res
+		.as_ref()
+		.is_ok_and(|outcome| outcome.keepalive && !outcome.idle_timed_out);
 	if keepalive {
 		// Clear abort token when command completes
 		if let Some(session_core) = session.lock().await.as_mut() {
@@ -225,39 +582,276 @@ async fn run_shell_session(
 	} else {
 		*session.lock().await = None;
 	}
-	Ok(ShellRunResult { exit_code: Some(exit_code(&res?)), cancelled: false, timed_out: false })
+	let outcome = res?;
+	Ok(ShellRunResult {
+		exit_code:      (!outcome.output_killed && !outcome.idle_timed_out)
+			.then_some(outcome.exit_code),
+		cancelled:      false,
+		timed_out:      outcome.idle_timed_out,
+		idle_timed_out: outcome.idle_timed_out,
+	})
 }
 
 /// Options for executing a shell command via brush-core.
-#[napi(object)]
+#[napi(object, object_to_js = false)]
 pub struct ShellExecuteOptions<'env> {
 	/// Command string to execute in the shell.
-	pub command:       String,
+	pub command:              String,
 	/// Working directory for the command.
-	pub cwd:           Option<String>,
+	pub cwd:                  Option<String>,
 	/// Environment variables to apply for this command only.
-	pub env:           Option<HashMap<String, String>>,
+	pub env:                  Option<HashMap<String, String>>,
 	/// Environment variables to apply once per session.
-	pub session_env:   Option<HashMap<String, String>>,
-	/// Timeout in milliseconds before cancelling the command.
+	pub session_env:          Option<HashMap<String, String>>,
+	/// Timeout in milliseconds before cancelling the command, not counting
+	/// time spent queued under `setMaxConcurrentExecutions`.
 	#[napi(js_name = "timeoutMs")]
-	pub timeout_ms:    Option<u32>,
+	pub timeout_ms:           Option<u32>,
+	/// Kill the command once it has produced no output for this many
+	/// milliseconds, regardless of `timeoutMs`.
+	#[napi(js_name = "idleTimeoutMs")]
+	pub idle_timeout_ms:      Option<u32>,
 	/// Optional snapshot file to source on session creation.
 	#[napi(js_name = "snapshotPath")]
-	pub snapshot_path: Option<String>,
+	pub snapshot_path:        Option<String>,
+	/// Builtins the session may use.
+	#[napi(js_name = "builtinPolicy")]
+	pub builtin_policy:       Option<ShellBuiltinPolicy>,
+	/// Resource limits for spawned processes.
+	pub limits:               Option<ShellResourceLimits>,
+	/// Confine spawned processes to allowlisted paths, without network access
+	/// unless allowed.
+	pub sandbox:              Option<ShellSandbox>,
+	/// Network egress policy for spawned processes: `"allow"` (default),
+	/// `"deny"` (loopback only) or `{ allowHosts }`.
+	#[napi(ts_type = "\"allow\" | \"deny\" | ShellNetworkAllowlist")]
+	pub network:              Option<Either<String, ShellNetworkAllowlist>>,
+	/// Run the command with `HOME` and the XDG directories in a home of its
+	/// own: `true` for a temporary one, or where to put it and what to seed
+	/// it with. A sandbox may write to it.
+	#[napi(js_name = "isolatedHome", ts_type = "boolean | ShellIsolatedHome")]
+	pub isolated_home:        Option<Either<bool, ShellIsolatedHome>>,
+	/// Program that runs the command: `"brush"` (default), `"powershell"`
+	/// (`pwsh`, or Windows PowerShell when `pwsh` is missing) or `"cmd"`
+	/// (Windows only). `snapshotPath` and `captureState` only apply to brush.
+	#[napi(ts_type = "\"brush\" | \"powershell\" | \"cmd\"")]
+	pub interpreter:          Option<String>,
+	/// CPU and I/O scheduling priority of spawned processes: `"background"`
+	/// yields to interactive work, `"high"` runs ahead of it where permitted
+	/// (default: `"normal"`).
+	#[napi(ts_type = "\"background\" | \"normal\" | \"high\"")]
+	pub priority:             Option<String>,
+	/// Stop streaming after this many combined stdout/stderr bytes.
+	#[napi(js_name = "maxOutputBytes")]
+	pub max_output_bytes:     Option<i64>,
+	/// Stop streaming after this many output lines.
+	#[napi(js_name = "maxOutputLines")]
+	pub max_output_lines:     Option<u32>,
+	/// Kill the command once an output limit is hit instead of discarding the
+	/// rest of its output.
+	#[napi(js_name = "killOnOutputLimit")]
+	pub kill_on_output_limit: Option<bool>,
+	/// Report a [`ShellInputPrompt`] through the output callback when the
+	/// command has been silent this long while still running and its last
+	/// output line looks like a prompt.
+	#[napi(js_name = "promptIdleMs")]
+	pub prompt_idle_ms:       Option<u32>,
+	/// Remove ANSI escape sequences from output before it is streamed.
+	#[napi(js_name = "stripAnsi")]
+	pub strip_ansi:           Option<bool>,
+	/// Report exported environment changes and the final working directory.
+	#[napi(js_name = "captureState")]
+	pub capture_state:        Option<bool>,
+	/// Report the working directory and environment the command started
+	/// with.
+	#[napi(js_name = "captureEnvironment")]
+	pub capture_environment:  Option<bool>,
+	/// Stream output as raw [`ShellOutputChunk`]s instead of decoded strings.
+	#[napi(js_name = "binaryOutput")]
+	pub binary_output:        Option<bool>,
+	/// Deliver output lines holding a JSON object or array as parsed
+	/// [`ShellJsonLine`]s, and other output as text. Cannot be combined with
+	/// `binaryOutput`.
+	#[napi(js_name = "jsonLines")]
+	pub json_lines:           Option<bool>,
+	/// Keep this many bytes of streamed output for `getExecutionOutput`
+	/// while the command runs (default: 65536, 0 disables).
+	#[napi(js_name = "scrollbackBytes")]
+	pub scrollback_bytes:     Option<u32>,
+	/// Called with the execution id as soon as the command is registered,
+	/// before it waits for a slot, for `cancelShellExecution` and
+	/// `getExecutionOutput`.
+	#[napi(js_name = "onStart", ts_type = "TsFunc<number>")]
+	pub on_start:             Option<ThreadsafeFunction<u32>>,
 	/// Abort signal for cancelling the operation.
-	pub signal:        Option<Unknown<'env>>,
+	pub signal:               Option<Unknown<'env>>,
+}
+
+/// Raw output chunk streamed in `binaryOutput` mode.
+#[napi(object)]
+pub struct ShellOutputChunk {
+	/// Output bytes, exactly as the command wrote them (after ANSI stripping
+	/// and output limits).
+	pub data: Buffer,
+	/// Whether `data` is valid UTF-8 on its own. Characters split across reads
+	/// are held back so text output always arrives in whole characters.
+	pub utf8: bool,
+}
+
+/// Working directory and environment a command started with, reported with
+/// `captureEnvironment`.
+#[napi(object)]
+pub struct ShellEnvironment {
+	/// Resolved working directory.
+	pub cwd: String,
+	/// Exported environment variables, after inheritance (minus the shell's
+	/// own variables for brush), `sessionEnv`, the snapshot and `env`.
+	pub env: HashMap<String, String>,
+}
+
+/// Shell state left behind by a command, reported with `captureState`.
+#[napi(object)]
+pub struct ShellStateDelta {
+	/// Working directory after the command finished.
+	pub cwd:         String,
+	/// Exported variables the command added or changed, with their new
+	/// values.
+	pub env:         HashMap<String, String>,
+	/// Exported variables the command unset or stopped exporting.
+	#[napi(js_name = "removedEnv")]
+	pub removed_env: Vec<String>,
 }
 
 /// Result of executing a shell command via brush-core.
 #[napi(object)]
 pub struct ShellExecuteResult {
 	/// Exit code when the command completes normally.
-	pub exit_code: Option<i32>,
+	pub exit_code:             Option<i32>,
 	/// Whether the command was cancelled via abort.
-	pub cancelled: bool,
+	pub cancelled:             bool,
 	/// Whether the command timed out before completion.
-	pub timed_out: bool,
+	pub timed_out:             bool,
+	/// Whether the timeout was `idleTimeoutMs` rather than `timeoutMs`.
+	#[napi(js_name = "idleTimedOut")]
+	pub idle_timed_out:        bool,
+	/// Whether streamed output was cut off by `maxOutputBytes` or
+	/// `maxOutputLines`.
+	#[napi(js_name = "outputTruncated")]
+	pub output_truncated:      bool,
+	/// Whether the command was killed for hitting an output limit.
+	#[napi(js_name = "outputLimitExceeded")]
+	pub output_limit_exceeded: bool,
+	/// The signal that ended the command, when its exit code reports one
+	/// (unix).
+	pub termination:           Option<ShellTermination>,
+	/// Whether the Linux OOM killer killed one of the command's processes,
+	/// not necessarily the one that ended it.
+	#[napi(js_name = "oomKilled")]
+	pub oom_killed:            bool,
+	/// Peak resource usage, when the command ran in a cgroup of its own
+	/// (see [`ShellResourceLimits`]).
+	pub usage:                 Option<ShellResourceUsage>,
+	/// Environment and cwd changes, when `captureState` was set and the
+	/// command ran to completion.
+	pub state:                 Option<ShellStateDelta>,
+	/// Working directory and environment the command started with, when
+	/// `captureEnvironment` was set and the command ran to completion.
+	pub environment:           Option<ShellEnvironment>,
+	/// Where the time went.
+	pub timing:                ShellTiming,
+}
+
+/// Wall-clock and CPU timing of a shell execution.
+///
+/// Phase durations and CPU times are absent when the command was cancelled
+/// or timed out.
+#[napi(object)]
+pub struct ShellTiming {
+	/// Start of the execution, in milliseconds since the Unix epoch.
+	#[napi(js_name = "startedAt")]
+	pub started_at:      f64,
+	/// Arrival of the first output byte, in milliseconds since the Unix epoch.
+	#[napi(js_name = "firstOutputAt")]
+	pub first_output_at: Option<f64>,
+	/// End of the execution, in milliseconds since the Unix epoch.
+	#[napi(js_name = "endedAt")]
+	pub ended_at:        f64,
+	/// Total wall-clock duration in milliseconds.
+	#[napi(js_name = "durationMs")]
+	pub duration_ms:     f64,
+	/// Time spent waiting for a slot under `setMaxConcurrentExecutions`, in
+	/// milliseconds.
+	#[napi(js_name = "queuedMs")]
+	pub queued_ms:       f64,
+	/// Session startup (environment import, snapshot sourcing) in
+	/// milliseconds.
+	#[napi(js_name = "setupMs")]
+	pub setup_ms:        Option<f64>,
+	/// Time from starting the command until it exited, in milliseconds.
+	#[napi(js_name = "commandMs")]
+	pub command_ms:      Option<f64>,
+	/// Time spent draining output after the command exited, in milliseconds.
+	#[napi(js_name = "drainMs")]
+	pub drain_ms:        Option<f64>,
+	/// User CPU time of the command's processes in milliseconds. On unix
+	/// this counts children reaped during the execution, so concurrent
+	/// executions inflate each other's figures.
+	#[napi(js_name = "userCpuMs")]
+	pub user_cpu_ms:     Option<f64>,
+	/// System CPU time of the command's processes in milliseconds.
+	#[napi(js_name = "systemCpuMs")]
+	pub system_cpu_ms:   Option<f64>,
+}
+
+impl ShellTiming {
+	/// Wall-clock timing from `started` (observed at `started_at`) until now,
+	/// of which `queued` was spent waiting for an execution slot.
+	fn wall(started_at: SystemTime, started: Instant, queued: Duration) -> Self {
+		let start_ms = millis(started_at.duration_since(UNIX_EPOCH).unwrap_or_default());
+		let duration_ms = millis(started.elapsed());
+		Self {
+			started_at: start_ms,
+			first_output_at: None,
+			ended_at: start_ms + duration_ms,
+			duration_ms,
+			queued_ms: millis(queued),
+			setup_ms: None,
+			command_ms: None,
+			drain_ms: None,
+			user_cpu_ms: None,
+			system_cpu_ms: None,
+		}
+	}
+}
+
+/// Pass the id of a newly registered execution to its `onStart` callback.
+fn report_start(
+	on_start: Option<ThreadsafeFunction<u32>>,
+	registration: &executions::Registration,
+) {
+	if let Some(on_start) = on_start {
+		on_start.call(Ok(registration.id()), ThreadsafeFunctionCallMode::NonBlocking);
+	}
+}
+
+/// Wait for an execution slot, then start the timeout so that it does not
+/// count time spent queued. Returns the abort reason when cancelled while
+/// queued.
+async fn wait_for_slot(
+	ct: &mut task::CancelToken,
+	timeout_ms: Option<u32>,
+	progress: &ExecutionProgress,
+) -> std::result::Result<queue::Slot, task::AbortReason> {
+	let slot = tokio::select! {
+		slot = queue::acquire(progress) => slot,
+		reason = ct.wait() => return Err(reason),
+	};
+	ct.set_timeout(timeout_ms);
+	Ok(slot)
+}
+
+fn millis(duration: Duration) -> f64 {
+	duration.as_secs_f64() * 1000.0
 }
 
 /// Execute a brush shell command.
@@ -269,18 +863,96 @@ pub struct ShellExecuteResult {
 pub fn execute_shell<'env>(
 	env: &'env Env,
 	options: ShellExecuteOptions<'env>,
-	#[napi(ts_arg_type = "((chunk: string) => void) | undefined | null")] on_chunk: Option<
-		ThreadsafeFunction<String>,
-	>,
+	#[napi(ts_arg_type = "((chunk: string | ShellOutputChunk | ShellInputPrompt | ShellJsonLine) \
+	                      => void) | undefined | null")]
+	on_chunk: Option<OutputCallback>,
 ) -> Result<PromiseRaw<'env, ShellExecuteResult>> {
-	let config =
-		ShellConfig { session_env: options.session_env, snapshot_path: options.snapshot_path };
-	let run_config =
-		ShellRunConfig { command: options.command, cwd: options.cwd, env: options.env };
+	if options.binary_output == Some(true) && options.json_lines == Some(true) {
+		return Err(Error::from_reason("jsonLines cannot be combined with binaryOutput"));
+	}
+	let config = ShellConfig {
+		session_env:    options.session_env,
+		snapshot_path:  options.snapshot_path,
+		builtin_policy: options.builtin_policy,
+	};
+	// `limits.maxOutputBytes` kills the command, as it did before the
+	// top-level options existed; `maxOutputBytes` wins when both are set.
+	let legacy_max_output = options
+		.limits
+		.as_ref()
+		.and_then(ShellResourceLimits::max_output)
+		.filter(|_| options.max_output_bytes.is_none());
+	let mut run_config = ShellRunConfig {
+		command:              options.command,
+		cwd:                  options.cwd,
+		env:                  options.env,
+		timeout_ms:           options.timeout_ms,
+		limits:               options.limits,
+		sandbox:              options.sandbox,
+		network:              options.network,
+		interpreter:          Interpreter::parse(options.interpreter.as_deref())?,
+		priority:             Priority::parse(options.priority.as_deref())?,
+		max_output_bytes:     options
+			.max_output_bytes
+			.map(|bytes| bytes.max(0) as u64)
+			.or(legacy_max_output),
+		max_output_lines:     options.max_output_lines.map(u64::from),
+		kill_on_output_limit: options
+			.kill_on_output_limit
+			.unwrap_or_else(|| legacy_max_output.is_some()),
+		prompt_idle:          options
+			.prompt_idle_ms
+			.map(|ms| Duration::from_millis(u64::from(ms))),
+		idle_timeout:         options
+			.idle_timeout_ms
+			.map(|ms| Duration::from_millis(u64::from(ms))),
+		capture_environment:  options.capture_environment.unwrap_or(false),
+		strip_ansi:           options.strip_ansi.unwrap_or(false),
+		binary_output:        options.binary_output.unwrap_or(false),
+		json_lines:           options.json_lines.unwrap_or(false),
+		progress:             Arc::new(ExecutionProgress::with_scrollback(
+			options
+				.scrollback_bytes
+				.map_or(executions::DEFAULT_SCROLLBACK_BYTES, |bytes| bytes as usize),
+		)),
+	};
 
-	let ct = task::CancelToken::new(options.timeout_ms, options.signal);
+	let capture_state = options.capture_state.unwrap_or(false);
+
+	let ct = task::CancelToken::new(None, options.signal);
+	let audit = audit::AuditRecord::start(
+		&run_config.command,
+		run_config.cwd.clone().or_else(|| {
+			std::env::current_dir()
+				.ok()
+				.map(|dir| dir.to_string_lossy().into_owned())
+		}),
+		[config.session_env.as_ref(), run_config.env.as_ref()],
+	);
+	let progress = run_config.progress.clone();
+	let isolated_home = options.isolated_home;
+	let on_start = options.on_start;
 	task::future(env, "shell.execute", async move {
-		run_shell_oneshot(config, run_config, on_chunk, ct).await
+		let result = async {
+			// Dropping the home removes it if temporary.
+			let home = IsolatedHome::create(isolated_home)?;
+			if let Some(home) = &home {
+				home.apply(&mut run_config.env, run_config.sandbox.as_mut());
+			}
+			let result =
+				run_shell_oneshot(config, run_config, capture_state, on_start, on_chunk, ct).await;
+			drop(home);
+			result
+		}
+		.await;
+		if let Some(audit) = audit {
+			let ending = result.as_ref().map_or_else(
+				|_| audit::Ending::error(),
+				|res| audit::Ending::of(res.exit_code, res.cancelled, res.timed_out),
+			);
+			audit.finish(ending, progress.output_bytes()).await;
+		}
+		result
 	})
 }
 
@@ -288,16 +960,57 @@ pub fn execute_shell<'env>(
 async fn run_shell_oneshot(
 	config: ShellConfig,
 	run_config: ShellRunConfig,
-	on_chunk: Option<ThreadsafeFunction<String>>,
-	ct: task::CancelToken,
+	capture_state: bool,
+	on_start: Option<ThreadsafeFunction<u32>>,
+	on_chunk: Option<OutputCallback>,
+	mut ct: task::CancelToken,
 ) -> Result<ShellExecuteResult> {
+	let started_at = SystemTime::now();
+	let started = Instant::now();
+	let registration = executions::register(
+		&run_config.command,
+		started_at,
+		ct.emplace_abort_token(),
+		run_config.progress.clone(),
+	);
+	report_start(on_start, &registration);
+	let slot = wait_for_slot(&mut ct, run_config.timeout_ms, &run_config.progress).await;
+	let admitted = Instant::now();
+	let queued_time = admitted.duration_since(started);
+	let _slot = match slot {
+		Ok(slot) => slot,
+		Err(reason) => {
+			return Ok(ShellExecuteResult {
+				exit_code:             None,
+				cancelled:             matches!(reason, task::AbortReason::Signal),
+				timed_out:             false,
+				idle_timed_out:        false,
+				output_truncated:      false,
+				output_limit_exceeded: false,
+				termination:           None,
+				oom_killed:            false,
+				usage:                 None,
+				state:                 None,
+				environment:           None,
+				timing:                ShellTiming::wall(started_at, started, queued_time),
+			});
+		},
+	};
 	let tokio_cancel = CancellationToken::new();
 
 	let mut task = tokio::spawn({
 		let tokio_cancel = tokio_cancel.clone();
 		async move {
+			if run_config.interpreter != Interpreter::Brush {
+				let outcome = run_native_command(&config, &run_config, on_chunk, tokio_cancel).await?;
+				return Ok((outcome, None, Duration::ZERO));
+			}
 			let mut session = create_session(&config).await?;
-			run_shell_command(&mut session, &run_config, on_chunk, tokio_cancel).await
+			let setup_time = admitted.elapsed();
+			let before = capture_state.then(|| exported_env(&session.shell));
+			let outcome = run_shell_command(&mut session, &run_config, on_chunk, tokio_cancel).await?;
+			let state = before.map(|before| state_delta(&session.shell, &before));
+			Ok((outcome, state, setup_time))
 		}
 	});
 
@@ -311,17 +1024,81 @@ async fn run_shell_oneshot(
 				let _ = task.await;
 			}
 			return Ok(ShellExecuteResult {
-				exit_code: None,
-				cancelled: matches!(reason, task::AbortReason::Signal),
-				timed_out: matches!(reason, task::AbortReason::Timeout),
+				exit_code:             None,
+				cancelled:             matches!(reason, task::AbortReason::Signal),
+				timed_out:             matches!(reason, task::AbortReason::Timeout),
+				idle_timed_out:        false,
+				output_truncated:      false,
+				output_limit_exceeded: false,
+				termination:           None,
+				oom_killed:            false,
+				usage:                 None,
+				state:                 None,
+				environment:           None,
+				timing:                ShellTiming::wall(started_at, started, queued_time),
 			})
 		},
 	};
 
-	let res = run_result
-		.unwrap_or_else(|e| Err(Error::from_reason(format!("Shell execution task failed: {e}"))));
+	let (outcome, delta, setup_time) = run_result
+		.unwrap_or_else(|e| Err(Error::from_reason(format!("Shell execution task failed: {e}"))))?;
+
+	let mut timing = ShellTiming::wall(started_at, started, queued_time);
+	timing.first_output_at = outcome
+		.first_output
+		.map(|at| timing.started_at + millis(at.duration_since(started)));
+	timing.setup_ms = Some(millis(setup_time));
+	timing.command_ms = Some(millis(outcome.command_time));
+	timing.drain_ms = Some(millis(outcome.drain_time));
+	timing.user_cpu_ms = outcome.cpu_time.map(|cpu| millis(cpu.user));
+	timing.system_cpu_ms = outcome.cpu_time.map(|cpu| millis(cpu.system));
+
+	Ok(ShellExecuteResult {
+		exit_code: (!outcome.output_killed && !outcome.idle_timed_out).then_some(outcome.exit_code),
+		cancelled: false,
+		timed_out: outcome.idle_timed_out,
+		idle_timed_out: outcome.idle_timed_out,
+		output_truncated: outcome.output_truncated,
+		output_limit_exceeded: outcome.output_killed,
+		termination: outcome.termination,
+		oom_killed: outcome.oom_killed,
+		usage: outcome.usage,
+		state: delta,
+		environment: outcome.environment,
+		timing,
+	})
+}
+
+/// Exported variables with a value, as a child process would inherit them.
+fn exported_env(shell: &BrushShell) -> HashMap<String, String> {
+	shell
+		.env
+		.iter_exported()
+		.filter_map(|(name, var)| {
+			let value = var.value().try_get_cow_str(shell)?;
+			Some((name.clone(), value.into_owned()))
+		})
+		.collect()
+}
 
-	Ok(ShellExecuteResult { exit_code: Some(exit_code(&res?)), cancelled: false, timed_out: false })
+/// Compare the shell's exported variables against `before` and report them
+/// with the current working directory.
+fn state_delta(shell: &BrushShell, before: &HashMap<String, String>) -> ShellStateDelta {
+	let after = exported_env(shell);
+	let mut removed_env: Vec<String> = before
+		.keys()
+		.filter(|name| !after.contains_key(*name))
+		.cloned()
+		.collect();
+	removed_env.sort();
+	ShellStateDelta {
+		cwd: shell.working_dir().to_string_lossy().into_owned(),
+		env: after
+			.into_iter()
+			.filter(|(name, value)| before.get(name) != Some(value))
+			.collect(),
+		removed_env,
+	}
 }
 
 fn null_file() -> Result<OpenFile> {
@@ -416,11 +1193,10 @@ async fn create_session(config: &ShellConfig) -> Result<ShellSessionCore> {
 		.await
 		.map_err(|err| Error::from_reason(format!("Failed to initialize shell: {err}")))?;
 
-	if let Some(exec_builtin) = shell.builtin_mut("exec") {
-		exec_builtin.disabled = true;
-	}
-	if let Some(suspend_builtin) = shell.builtin_mut("suspend") {
-		suspend_builtin.disabled = true;
+	for name in DISABLED_BUILTINS {
+		if let Some(builtin) = shell.builtin_mut(name) {
+			builtin.disabled = true;
+		}
 	}
 	shell.register_builtin("sleep", builtins::builtin::<SleepCommand>());
 	shell.register_builtin("timeout", builtins::builtin::<TimeoutCommand>());
@@ -484,6 +1260,10 @@ async fn create_session(config: &ShellConfig) -> Result<ShellSessionCore> {
 		source_snapshot(&mut shell, snapshot_path).await?;
 	}
 
+	if let Some(policy) = config.builtin_policy.as_ref() {
+		policy.apply(&mut shell)?;
+	}
+
 	Ok(ShellSessionCore { shell, current_abort: None })
 }
 
@@ -505,9 +1285,9 @@ async fn source_snapshot(shell: &mut BrushShell, snapshot_path: &str) -> Result<
 async fn run_shell_command(
 	session: &mut ShellSessionCore,
 	options: &ShellRunConfig,
-	on_chunk: Option<ThreadsafeFunction<String>>,
+	on_chunk: Option<OutputCallback>,
 	cancel_token: CancellationToken,
-) -> Result<ExecutionResult> {
+) -> Result<CommandOutcome> {
 	if let Some(cwd) = options.cwd.as_deref() {
 		session
 			.shell
@@ -515,7 +1295,8 @@ async fn run_shell_command(
 			.map_err(|err| Error::from_reason(format!("Failed to set cwd: {err}")))?;
 	}
 
-	let (reader_file, writer_file) = pipe_to_files("output")?;
+	let tracker = spawn_tracker(options, session.shell.working_dir())?;
+	let (writer_file, mut stream) = OutputStream::start(options, on_chunk, &cancel_token, &tracker)?;
 
 	let stdout_file = OpenFile::from(
 		writer_file
@@ -530,6 +1311,7 @@ async fn run_shell_command(
 	params.set_fd(OpenFiles::STDERR_FD, stderr_file);
 	params.process_group_policy = ProcessGroupPolicy::NewProcessGroup;
 	params.set_cancel_token(cancel_token.clone());
+	params.set_spawn_hooks(tracker.clone());
 
 	let mut env_scope_pushed = false;
 	if let Some(env) = options.env.as_ref() {
@@ -553,29 +1335,22 @@ async fn run_shell_command(
 		}
 	}
 
-	let reader_cancel = CancellationToken::new();
-	let (activity_tx, mut activity_rx) = mpsc::channel::<()>(1);
-	let mut reader_handle = tokio::spawn({
-		let reader_cancel = reader_cancel.clone();
-		async move {
-			read_output(reader_file, on_chunk, reader_cancel, activity_tx).await;
-			Result::<()>::Ok(())
-		}
-	});
-	let cancel_bridge = tokio::spawn({
-		let cancel_token = cancel_token.clone();
-		let reader_cancel = reader_cancel.clone();
-		async move {
-			cancel_token.cancelled().await;
-			reader_cancel.cancel();
-		}
+	let environment = options.capture_environment.then(|| ShellEnvironment {
+		cwd: session.shell.working_dir().to_string_lossy().into_owned(),
+		env: exported_env(&session.shell),
 	});
+
+	let cancel_bridge = stream.cancel_bridge(&cancel_token, &tracker);
+	let command_start = Instant::now();
 	let result = session
 		.shell
 		.run_string(options.command.clone(), &params)
 		.await;
+	let command_time = command_start.elapsed();
+	stream.command_exited();
 
 	if cancel_token.is_cancelled() {
+		tracker.kill_all();
 		terminate_background_jobs(&session.shell);
 	}
 
@@ -589,42 +1364,340 @@ async fn run_shell_command(
 
 	drop(params);
 
-	// The foreground command can complete while background jobs keep the
-	// stdout/stderr pipe open. Don't hang forever waiting for EOF; drain output
-	// for a short period, then cancel.
-	const POST_EXIT_IDLE: Duration = Duration::from_millis(250);
-	const POST_EXIT_MAX: Duration = Duration::from_secs(2);
+	let drain_time = stream.finish().await;
+	cancel_bridge.abort();
+	let _ = cancel_bridge.await;
 
-	let mut reader_finished = false;
-	let mut idle_timer = Box::pin(time::sleep(POST_EXIT_IDLE));
-	let mut max_timer = Box::pin(time::sleep(POST_EXIT_MAX));
+	let output_truncated = stream.truncated.load(Ordering::Relaxed);
+	let output_killed = output_truncated && options.kill_on_output_limit;
+	let idle_timed_out = stream.idle_expired.load(Ordering::Relaxed);
+	let result = match result {
+		Ok(result) => result,
+		// The command was killed on purpose; its interruption error is expected.
+		Err(_) if output_killed || idle_timed_out => ExecutionExitCode::Interrupted.into(),
+		Err(err) => return Err(Error::from_reason(format!("Shell execution failed: {err}"))),
+	};
+	let exit_code = exit_code(&result);
+	Ok(CommandOutcome {
+		exit_code,
+		keepalive: session_keepalive(&result),
+		output_truncated,
+		output_killed,
+		idle_timed_out,
+		first_output: stream.first.get().copied(),
+		command_time,
+		drain_time,
+		cpu_time: tracker.cpu_time(),
+		termination: tracker.termination(exit_code),
+		oom_killed: tracker.oom_killed(),
+		usage: tracker.usage(),
+		environment,
+	})
+}
 
-	loop {
-		tokio::select! {
-			res = &mut reader_handle => {
-				let _ = res;
-				reader_finished = true;
-				break;
-			}
-			msg = activity_rx.recv() => {
-				if msg.is_none() {
+/// Run a command with a native interpreter instead of brush.
+///
+/// The interpreter is spawned directly, with the session environment and
+/// `env` layered over the inherited environment.
+async fn run_native_command(
+	config: &ShellConfig,
+	options: &ShellRunConfig,
+	on_chunk: Option<OutputCallback>,
+	cancel_token: CancellationToken,
+) -> Result<CommandOutcome> {
+	let cwd = match options.cwd.as_deref() {
+		Some(cwd) => PathBuf::from(cwd),
+		None => std::env::current_dir()
+			.map_err(|err| Error::from_reason(format!("Failed to get cwd: {err}")))?,
+	};
+	let tracker = spawn_tracker(options, &cwd)?;
+	let mut cmd =
+		interpreter::command(options.interpreter, &options.command, tracker.command_wrapper())?;
+	cmd.current_dir(&cwd)
+		.envs(config.session_env.iter().flatten())
+		.envs(options.env.iter().flatten())
+		.stdin(Stdio::null());
+	let environment = options
+		.capture_environment
+		.then(|| ShellEnvironment { cwd: cwd.to_string_lossy().into_owned(), env: native_env(&cmd) });
+
+	let (writer_file, mut stream) = OutputStream::start(options, on_chunk, &cancel_token, &tracker)?;
+	let stdout_file = writer_file
+		.try_clone()
+		.map_err(|err| Error::from_reason(format!("Failed to clone pipe: {err}")))?;
+	cmd.stdout(stdout_file).stderr(writer_file);
+	tracker.before_spawn(&mut cmd);
+
+	let mut cmd = tokio::process::Command::from(cmd);
+	cmd.kill_on_drop(true);
+	let command_start = Instant::now();
+	let child = cmd.spawn();
+	// The command still holds the pipe's write end; the reader only sees EOF
+	// once it is gone.
+	drop(cmd);
+	let mut child = child.map_err(|err| {
+		Error::from_reason(format!("Failed to start {}: {err}", options.interpreter.name()))
+	})?;
+	let pid = child.id().map(|pid| pid as i32);
+	if let Some(pid) = pid {
+		tracker.after_spawn(pid);
+	}
+
+	let cancel_bridge = stream.cancel_bridge(&cancel_token, &tracker);
+	let status = child.wait().await;
+	let command_time = command_start.elapsed();
+	stream.command_exited();
+
+	let drain_time = stream.finish().await;
+	cancel_bridge.abort();
+	let _ = cancel_bridge.await;
+
+	let status = status.map_err(|err| {
+		Error::from_reason(format!("Failed to wait for {}: {err}", options.interpreter.name()))
+	})?;
+	if let Some(pid) = pid {
+		tracker.after_exit(pid, &status);
+	}
+	let exit_code = status_code(status);
+	let output_truncated = stream.truncated.load(Ordering::Relaxed);
+	Ok(CommandOutcome {
+		exit_code,
+		keepalive: true,
+		output_truncated,
+		output_killed: output_truncated && options.kill_on_output_limit,
+		idle_timed_out: stream.idle_expired.load(Ordering::Relaxed),
+		first_output: stream.first.get().copied(),
+		command_time,
+		drain_time,
+		cpu_time: tracker.cpu_time(),
+		termination: tracker.termination(exit_code),
+		oom_killed: tracker.oom_killed(),
+		usage: tracker.usage(),
+		environment,
+	})
+}
+
+/// Environment `cmd` passes to its process: ours, with its overrides
+/// applied.
+fn native_env(cmd: &std::process::Command) -> HashMap<String, String> {
+	let mut env: HashMap<String, String> = std::env::vars_os()
+		.map(|(key, value)| {
+			(key.to_string_lossy().into_owned(), value.to_string_lossy().into_owned())
+		})
+		.collect();
+	for (key, value) in cmd.get_envs() {
+		let key = key.to_string_lossy().into_owned();
+		match value {
+			Some(value) => env.insert(key, value.to_string_lossy().into_owned()),
+			None => env.remove(&key),
+		};
+	}
+	env
+}
+
+/// Spawn hooks applying the run's resource limits, priority, sandbox and
+/// network policy, with the sandbox rooted at `cwd`.
+fn spawn_tracker(options: &ShellRunConfig, cwd: &Path) -> Result<Arc<SpawnTracker>> {
+	let sandbox = options
+		.sandbox
+		.as_ref()
+		.map(|sandbox| sandbox::SandboxHooks::new(sandbox, cwd))
+		.transpose()?;
+	let network =
+		network::NetworkHooks::new(network::NetworkPolicy::parse(options.network.as_ref())?)?;
+	let tracker = SpawnTracker::new(
+		options.limits.as_ref().and_then(limits::LimitHooks::new),
+		priority::PriorityHooks::new(options.priority),
+		cgroup::create(options.limits.as_ref())?,
+		sandbox,
+		network,
+	);
+	options.progress.track(&tracker);
+	Ok(tracker)
+}
+
+/// Kill the command through `cancel_token` once `output` has not been
+/// notified for `timeout`.
+async fn watch_idle(
+	timeout: Duration,
+	output: Arc<Notify>,
+	expired: Arc<AtomicBool>,
+	cancel_token: CancellationToken,
+) {
+	while time::timeout(timeout, output.notified()).await.is_ok() {}
+	expired.store(true, Ordering::Relaxed);
+	cancel_token.cancel();
+}
+
+/// Exit code of a process, with signals reported as `128 + signal` like a
+/// shell does.
+fn status_code(status: ExitStatus) -> i32 {
+	#[cfg(unix)]
+	if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+		return 128 + signal;
+	}
+	status.code().unwrap_or(1)
+}
+
+/// How output is delivered to the callback.
+enum OutputMode {
+	/// Decoded strings.
+	Text,
+	/// Parsed JSON lines, with other output as decoded strings.
+	JsonLines(JsonLines),
+	/// Raw [`ShellOutputChunk`]s.
+	Binary,
+}
+
+/// The output pipe of a running command, streamed to the callback by a
+/// reader task.
+struct OutputStream {
+	reader:       tokio::task::JoinHandle<()>,
+	/// Idle timeout and prompt detection, running until the command exits.
+	watchers:     Vec<tokio::task::JoinHandle<()>>,
+	/// Stops the reader.
+	cancel:       CancellationToken,
+	activity:     mpsc::Receiver<()>,
+	first:        Arc<OnceLock<Instant>>,
+	truncated:    Arc<AtomicBool>,
+	/// Set when the idle timeout killed the command.
+	idle_expired: Arc<AtomicBool>,
+}
+
+impl OutputStream {
+	/// Create the output pipe and start streaming it. Returns the write end,
+	/// for the command's stdout and stderr.
+	fn start(
+		options: &ShellRunConfig,
+		on_chunk: Option<OutputCallback>,
+		cancel_token: &CancellationToken,
+		tracker: &Arc<SpawnTracker>,
+	) -> Result<(fs::File, Self)> {
+		let (reader_file, writer_file) = pipe_to_files("output")?;
+
+		let truncated = Arc::new(AtomicBool::new(false));
+		let output_limit = (options.max_output_bytes.is_some() || options.max_output_lines.is_some())
+			.then(|| OutputLimit {
+				max_bytes:     options.max_output_bytes,
+				max_lines:     options.max_output_lines,
+				bytes:         0,
+				lines:         0,
+				at_line_start: true,
+				kill:          options.kill_on_output_limit.then(|| cancel_token.clone()),
+				truncated:     truncated.clone(),
+			});
+
+		let ansi = options.strip_ansi.then(AnsiStripper::default);
+		let cancel = CancellationToken::new();
+		let (notify, activity) = mpsc::channel::<()>(1);
+		let first = Arc::new(OnceLock::new());
+		let on_chunk = on_chunk.map(Arc::new);
+		let mut watchers = Vec::new();
+		let idle_expired = Arc::new(AtomicBool::new(false));
+		let idle = options.idle_timeout.map(|timeout| {
+			let idle = Arc::new(Notify::new());
+			watchers.push(tokio::spawn(watch_idle(
+				timeout,
+				idle.clone(),
+				idle_expired.clone(),
+				cancel_token.clone(),
+			)));
+			idle
+		});
+		let prompt = match (options.prompt_idle, &on_chunk) {
+			(Some(idle), Some(callback)) => {
+				let watch = PromptWatch::new();
+				watchers.push(tokio::spawn(watch.clone().run(idle, tracker.clone(), callback.clone())));
+				Some(watch)
+			},
+			_ => None,
+		};
+		let reader = tokio::spawn(read_output(
+			reader_file,
+			on_chunk,
+			cancel.clone(),
+			OutputActivity {
+				notify,
+				first: first.clone(),
+				idle,
+				prompt,
+				progress: options.progress.clone(),
+			},
+			output_limit,
+			ansi,
+			if options.binary_output {
+				OutputMode::Binary
+			} else if options.json_lines {
+				OutputMode::JsonLines(JsonLines::default())
+			} else {
+				OutputMode::Text
+			},
+		));
+		Ok((writer_file, Self { reader, watchers, cancel, activity, first, truncated, idle_expired }))
+	}
+
+	/// Kill the command's processes and stop streaming once `cancel_token`
+	/// fires.
+	fn cancel_bridge(
+		&self,
+		cancel_token: &CancellationToken,
+		tracker: &Arc<SpawnTracker>,
+	) -> tokio::task::JoinHandle<()> {
+		let cancel_token = cancel_token.clone();
+		let reader_cancel = self.cancel.clone();
+		let tracker = tracker.clone();
+		tokio::spawn(async move {
+			cancel_token.cancelled().await;
+			tracker.kill_all();
+			reader_cancel.cancel();
+		})
+	}
+
+	/// Stop the idle timeout and prompt detection once the command exited.
+	fn command_exited(&mut self) {
+		for watcher in self.watchers.drain(..) {
+			watcher.abort();
+		}
+	}
+
+	/// Drain trailing output after the command exited, then stop the reader.
+	/// Returns the time spent draining.
+	async fn finish(&mut self) -> Duration {
+		// The foreground command can complete while background jobs keep the
+		// stdout/stderr pipe open. Don't hang forever waiting for EOF; drain
+		// output for a short period, then cancel.
+		const POST_EXIT_IDLE: Duration = Duration::from_millis(250);
+		const POST_EXIT_MAX: Duration = Duration::from_secs(2);
+
+		let drain_start = Instant::now();
+		let mut reader_finished = false;
+		let mut idle_timer = Box::pin(time::sleep(POST_EXIT_IDLE));
+		let mut max_timer = Box::pin(time::sleep(POST_EXIT_MAX));
+
+		loop {
+			tokio::select! {
+				res = &mut self.reader => {
+					let _ = res;
+					reader_finished = true;
 					break;
 				}
-				idle_timer.as_mut().reset(time::Instant::now() + POST_EXIT_IDLE);
+				msg = self.activity.recv() => {
+					if msg.is_none() {
+						break;
+					}
+					idle_timer.as_mut().reset(time::Instant::now() + POST_EXIT_IDLE);
+				}
+				() = &mut idle_timer => break,
+				() = &mut max_timer => break,
 			}
-			() = &mut idle_timer => break,
-			() = &mut max_timer => break,
 		}
-	}
 
-	if !reader_finished {
-		reader_cancel.cancel();
-		let _ = reader_handle.await;
+		if !reader_finished {
+			self.cancel.cancel();
+			let _ = (&mut self.reader).await;
+		}
+		drain_start.elapsed()
 	}
-	cancel_bridge.abort();
-	let _ = cancel_bridge.await;
-
-	result.map_err(|err| Error::from_reason(format!("Shell execution failed: {err}")))
 }
 
 #[cfg(unix)]
@@ -755,14 +1828,24 @@ const fn session_keepalive(result: &ExecutionResult) -> bool {
 
 async fn read_output(
 	reader: fs::File,
-	on_chunk: Option<ThreadsafeFunction<String>>,
+	on_chunk: Option<Arc<OutputCallback>>,
 	cancel_token: CancellationToken,
-	activity: mpsc::Sender<()>,
+	activity: OutputActivity,
+	mut output_limit: Option<OutputLimit>,
+	mut ansi: Option<AnsiStripper>,
+	mode: OutputMode,
 ) {
-	const REPLACEMENT: &str = "\u{FFFD}";
 	const BUF: usize = 4096;
 	let mut buf = [0u8; BUF + 4]; // +4 for max UTF-8 char
 	let mut it = 0;
+	let mut draining = false;
+	let binary = matches!(mode, OutputMode::Binary);
+	let mut json = match mode {
+		OutputMode::JsonLines(json) => Some(json),
+		OutputMode::Text | OutputMode::Binary => None,
+	};
+	let mut redactor = Redactor::current();
+	let sink = OutputSink { callback: on_chunk, progress: activity.progress.clone() };
 
 	let reader = tokio::fs::File::from_std(reader);
 	tokio::pin!(reader);
@@ -770,8 +1853,15 @@ async fn read_output(
 	loop {
 		let read_future = reader.read(&mut buf[it..BUF]);
 		tokio::pin!(read_future);
-		let n = match tokio::select! {
+		let mut n = match tokio::select! {
 			res = &mut read_future => res,
+			() = time::sleep(redact::FLUSH_DELAY),
+				if redactor.as_ref().is_some_and(Redactor::has_pending) =>
+			{
+				// Output went quiet mid-line: release the held-back partial line.
+				emit_pending(&[], binary, redactor.as_mut(), json.as_mut(), &sink);
+				continue;
+			},
 			() = cancel_token.cancelled() => break,
 		} {
 			Ok(0) => break, // EOF
@@ -780,16 +1870,30 @@ async fn read_output(
 			Err(_) => break,
 		};
 		if n > 0 {
-			let _ = activity.try_send(());
+			activity.record(&buf[it..it + n]);
+		}
+		if draining {
+			// Over the output limit: keep the pipe flowing but drop the bytes.
+			continue;
+		}
+		if let Some(ansi) = &mut ansi {
+			n = ansi.strip(&mut buf[it..it + n]);
+		}
+		let mut marker = None;
+		if let Some(limit) = &mut output_limit {
+			(n, marker) = limit.admit(&buf[it..it + n]);
 		}
 		it += n;
 
+		if binary {
+			it = emit_raw(&mut buf, it, redactor.as_mut(), &sink);
+		}
 		// Consume as much of `pending` as is decodable *right now*.
-		while it > 0 {
+		while !binary && it > 0 {
 			let pending = &buf[..it];
 			match str::from_utf8(pending) {
 				Ok(text) => {
-					emit_chunk(text, on_chunk.as_ref());
+					emit_redacted(text, redactor.as_mut(), json.as_mut(), &sink);
 					it = 0;
 					break;
 				},
@@ -798,7 +1902,7 @@ async fn read_output(
 					if p > 0 {
 						// SAFETY: [..p] is guaranteed valid UTF-8 by valid_up_to().
 						let text = unsafe { str::from_utf8_unchecked(&pending[..p]) };
-						emit_chunk(text, on_chunk.as_ref());
+						emit_redacted(text, redactor.as_mut(), json.as_mut(), &sink);
 						// copy p..it to the beginning of the buffer
 						buf.copy_within(p..it, 0);
 						it -= p;
@@ -807,7 +1911,7 @@ async fn read_output(
 					match err.error_len() {
 						Some(p) => {
 							// Invalid byte sequence: emit replacement and drop those bytes.
-							emit_chunk(REPLACEMENT, on_chunk.as_ref());
+							emit_redacted(REPLACEMENT, redactor.as_mut(), json.as_mut(), &sink);
 							// copy p..it to the beginning of the buffer
 							buf.copy_within(p..it, 0);
 							it -= p;
@@ -822,23 +1926,138 @@ async fn read_output(
 				},
 			}
 		}
+
+		if let Some(marker) = marker {
+			emit_pending(&buf[..it], binary, redactor.as_mut(), json.as_mut(), &sink);
+			it = 0;
+			if binary {
+				emit_bytes(marker.as_bytes(), true, &sink);
+			} else {
+				emit_text(&marker, json.as_mut(), &sink);
+			}
+			if output_limit.as_ref().is_some_and(OutputLimit::trip) {
+				break;
+			}
+			draining = true;
+		}
 	}
 
 	// Flush whatever is left at EOF (including an incomplete final sequence).
-	for chunk in buf[..it].utf8_chunks() {
+	emit_pending(&buf[..it], binary, redactor.as_mut(), json.as_mut(), &sink);
+	if let Some(json) = &mut json {
+		json.finish(&sink);
+	}
+}
+
+/// Emit `buf[..len]` as one raw chunk, holding back an incomplete trailing
+/// UTF-8 sequence. Returns the number of bytes held at the start of `buf`.
+fn emit_raw(
+	buf: &mut [u8],
+	len: usize,
+	redactor: Option<&mut Redactor>,
+	sink: &OutputSink,
+) -> usize {
+	let (end, utf8) = match str::from_utf8(&buf[..len]) {
+		Ok(_) => (len, true),
+		// A character split across reads: keep its bytes for the next one.
+		Err(err) if err.error_len().is_none() => (err.valid_up_to(), true),
+		Err(_) => (len, false),
+	};
+	match redactor {
+		Some(redactor) => {
+			let out = redactor.push(&buf[..end]);
+			emit_bytes(&out, str::from_utf8(&out).is_ok(), sink);
+		},
+		None => emit_bytes(&buf[..end], utf8, sink),
+	}
+	buf.copy_within(end..len, 0);
+	len - end
+}
+
+/// Flush leftover bytes, and anything the redactor holds back: raw in binary
+/// mode, lossily decoded otherwise.
+fn emit_pending(
+	bytes: &[u8],
+	binary: bool,
+	redactor: Option<&mut Redactor>,
+	json: Option<&mut JsonLines>,
+	sink: &OutputSink,
+) {
+	let redacted;
+	let bytes = match redactor {
+		Some(redactor) => {
+			let mut out = if binary {
+				redactor.push(bytes)
+			} else {
+				redactor.push(String::from_utf8_lossy(bytes).as_bytes())
+			};
+			out.extend(redactor.flush());
+			redacted = out;
+			&redacted[..]
+		},
+		None => bytes,
+	};
+	if binary {
+		emit_bytes(bytes, str::from_utf8(bytes).is_ok(), sink);
+	} else if let Some(json) = json {
+		json.push(&String::from_utf8_lossy(bytes), sink);
+	} else {
+		emit_lossy(bytes, sink);
+	}
+}
+
+fn emit_bytes(bytes: &[u8], utf8: bool, sink: &OutputSink) {
+	if bytes.is_empty() {
+		return;
+	}
+	sink.record(bytes);
+	if let Some(callback) = sink.callback() {
+		let chunk = ShellOutputChunk { data: bytes.to_vec().into(), utf8 };
+		callback.call(Ok(Either4::B(chunk)), ThreadsafeFunctionCallMode::NonBlocking);
+	}
+}
+
+/// Emit `bytes`, replacing invalid UTF-8 sequences with U+FFFD.
+fn emit_lossy(bytes: &[u8], sink: &OutputSink) {
+	for chunk in bytes.utf8_chunks() {
 		let valid = chunk.valid();
 		if !valid.is_empty() {
-			emit_chunk(valid, on_chunk.as_ref());
+			emit_chunk(valid, sink);
 		}
 		if !chunk.invalid().is_empty() {
-			emit_chunk(REPLACEMENT, on_chunk.as_ref());
+			emit_chunk(REPLACEMENT, sink);
 		}
 	}
 }
 
-fn emit_chunk(text: &str, callback: Option<&ThreadsafeFunction<String>>) {
-	if let Some(callback) = callback {
-		callback.call(Ok(text.to_string()), ThreadsafeFunctionCallMode::NonBlocking);
+/// Emit decoded text, through the redactor when redaction rules are set.
+fn emit_redacted(
+	text: &str,
+	redactor: Option<&mut Redactor>,
+	json: Option<&mut JsonLines>,
+	sink: &OutputSink,
+) {
+	let Some(redactor) = redactor else {
+		return emit_text(text, json, sink);
+	};
+	let out = redactor.push(text.as_bytes());
+	if !out.is_empty() {
+		emit_text(&String::from_utf8_lossy(&out), json, sink);
+	}
+}
+
+/// Emit decoded text, through the JSON line splitter in `jsonLines` mode.
+fn emit_text(text: &str, json: Option<&mut JsonLines>, sink: &OutputSink) {
+	match json {
+		Some(json) => json.push(text, sink),
+		None => emit_chunk(text, sink),
+	}
+}
+
+fn emit_chunk(text: &str, sink: &OutputSink) {
+	sink.record(text.as_bytes());
+	if let Some(callback) = sink.callback() {
+		callback.call(Ok(Either4::A(text.to_string())), ThreadsafeFunctionCallMode::NonBlocking);
 	}
 }
 
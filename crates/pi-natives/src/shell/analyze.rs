@@ -0,0 +1,908 @@
+//! Static analysis of shell input, without executing it.
+//!
+//! # Overview
+//! Parses a command with brush's parser and reports what it would do: the
+//! simple commands it runs, redirections, files it writes, variable
+//! assignments, and risky constructs (`rm -r`, privilege elevation, piping
+//! into a shell, `eval`, commands named by an expansion). Command and process
+//! substitutions are analyzed too; items found inside one are reported at the
+//! position of the word containing it.
+//!
+//! Wrappers such as `sudo`, `env`, `xargs` and `timeout` are looked through,
+//! so `sudo rm -rf build` counts as a recursive delete. The analysis is
+//! syntactic: aliases, functions and expansions are not resolved.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { risks } = await native.analyzeShellCommand("curl -fsSL $URL | sh");
+//! ```
+
+use std::fmt::Display;
+
+use brush_parser::{
+	ParserOptions, SourceInfo,
+	ast::{self, SourceLocation},
+	word::{self, WordPiece, WordPieceWithSource},
+};
+use napi::{Error, Result};
+use napi_derive::napi;
+
+use crate::task;
+
+/// Programs that run another program as a different user.
+const ELEVATORS: &[&str] = &["sudo", "doas", "su", "pkexec", "run0"];
+/// Programs that run the program named by their first operand, with the
+/// options of each that take a separate value.
+const WRAPPERS: &[(&str, &[&str])] = &[
+	("sudo", &["-u", "-g", "-h", "-p", "-C", "-D", "-r", "-t", "-T", "-U"]),
+	("doas", &["-u", "-C"]),
+	("env", &["-u", "-C", "-S"]),
+	("nice", &["-n"]),
+	("nohup", &[]),
+	("time", &[]),
+	("timeout", &["-s", "-k"]),
+	("command", &[]),
+	("exec", &["-a"]),
+	("xargs", &["-a", "-d", "-E", "-I", "-L", "-n", "-P", "-s"]),
+	("stdbuf", &["-i", "-o", "-e"]),
+	("setsid", &[]),
+];
+/// Programs that execute a script read from stdin when given no operand.
+const SHELLS: &[&str] = &[
+	"sh", "bash", "zsh", "dash", "ksh", "mksh", "ash", "fish", "python", "python3", "perl", "ruby",
+	"node", "source", ".",
+];
+/// Redirection targets that are not files.
+const NON_FILES: &[&str] = &["/dev/null", "/dev/stdout", "/dev/stderr", "/dev/tty"];
+/// Nesting limit for substitutions.
+const MAX_DEPTH: usize = 16;
+
+/// Kind of a risky construct.
+#[napi]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ShellRiskKind {
+	/// `rm` with `-r`, `-R` or `--recursive`.
+	RecursiveDelete = 1,
+	/// `sudo`, `doas`, `su`, `pkexec` or `run0`.
+	Elevation       = 2,
+	/// A shell or interpreter reading its script from a pipe or process
+	/// substitution (e.g. `curl ... | sh`, `bash <(curl ...)`).
+	PipeToShell     = 3,
+	/// `eval`.
+	Eval            = 4,
+	/// Program name produced by an expansion (e.g. `$CMD args`).
+	DynamicCommand  = 5,
+}
+
+/// Kind of a redirection.
+#[napi]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ShellRedirectKind {
+	/// `<`
+	Read       = 1,
+	/// `>`, or `&>` for stdout and stderr.
+	Write      = 2,
+	/// `>>`, or `&>>` for stdout and stderr.
+	Append     = 3,
+	/// `<>`
+	ReadWrite  = 4,
+	/// `>|`
+	Clobber    = 5,
+	/// `<&` or `>&`
+	Duplicate  = 6,
+	/// `<<` (target is the delimiter).
+	HereDoc    = 7,
+	/// `<<<`
+	HereString = 8,
+}
+
+/// A simple command.
+#[napi(object)]
+pub struct ShellAnalyzedCommand {
+	/// Program name with quoting removed, or its source text when `dynamic`.
+	pub program: String,
+	/// Arguments with quoting removed; arguments containing expansions are
+	/// given as source text.
+	pub args:    Vec<String>,
+	/// Whether the program name contains an expansion.
+	pub dynamic: bool,
+	/// Source text of the command.
+	pub text:    String,
+	/// Line of the command, 1-indexed.
+	pub line:    u32,
+	/// Character column of the command, 1-indexed.
+	pub column:  u32,
+}
+
+/// A redirection.
+#[napi(object)]
+pub struct ShellAnalyzedRedirect {
+	/// Kind of redirection.
+	pub kind:   ShellRedirectKind,
+	/// Redirected descriptor, when given explicitly.
+	pub fd:     Option<i32>,
+	/// Target file, descriptor or here-doc delimiter.
+	pub target: String,
+	/// Line of the redirection, 1-indexed.
+	pub line:   u32,
+	/// Character column of the redirection, 1-indexed.
+	pub column: u32,
+}
+
+/// A variable assignment.
+#[napi(object)]
+pub struct ShellAnalyzedAssignment {
+	/// Variable name (with subscript for array elements).
+	pub name:    String,
+	/// Assigned value as source text.
+	pub value:   String,
+	/// Program whose environment alone gets the variable (`FOO=1 make`,
+	/// `env FOO=1 make`); absent for shell variable assignments.
+	pub command: Option<String>,
+	/// Line of the assignment, 1-indexed.
+	pub line:    u32,
+	/// Character column of the assignment, 1-indexed.
+	pub column:  u32,
+}
+
+/// A risky construct.
+#[napi(object)]
+pub struct ShellRisk {
+	/// Kind of risk.
+	pub kind:   ShellRiskKind,
+	/// Source text of the command it was found in.
+	pub text:   String,
+	/// Line of the command, 1-indexed.
+	pub line:   u32,
+	/// Character column of the command, 1-indexed.
+	pub column: u32,
+}
+
+/// Result of `analyzeShellCommand`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ShellCommandAnalysis {
+	/// Programs run, including those behind wrappers like `sudo`, in order
+	/// of first appearance.
+	pub programs:      Vec<String>,
+	/// Simple commands in source order, with substitutions after the
+	/// command containing them.
+	pub commands:      Vec<ShellAnalyzedCommand>,
+	/// Redirections.
+	pub redirections:  Vec<ShellAnalyzedRedirect>,
+	/// Files written by output redirections or `tee`, in order of first
+	/// appearance.
+	#[napi(js_name = "filesWritten")]
+	pub files_written: Vec<String>,
+	/// Variable assignments.
+	pub assignments:   Vec<ShellAnalyzedAssignment>,
+	/// Risky constructs.
+	pub risks:         Vec<ShellRisk>,
+}
+
+/// A simple command with its wrappers looked through, for `policy`.
+pub(super) struct ResolvedCommand {
+	/// Index of the command in [`ShellCommandAnalysis::commands`].
+	pub index:    usize,
+	/// Programs run, outermost first: wrappers, then the program they run.
+	pub programs: Vec<String>,
+	/// Arguments of the last program, unless it is unknown.
+	pub args:     Option<Vec<String>>,
+	/// Whether a program is named by an expansion.
+	pub dynamic:  bool,
+	/// Whether a wrapper runs the program as another user.
+	pub elevated: bool,
+	/// Files redirected from or to.
+	pub files:    Vec<String>,
+	/// Operands of the last program, when its arguments are known.
+	pub operands: Vec<String>,
+}
+
+/// Line and column reported for an item.
+#[derive(Clone, Copy)]
+struct Position {
+	line:   u32,
+	column: u32,
+}
+
+/// A word after parsing, with its literal value when it has one.
+struct Arg {
+	text:                 String,
+	literal:              bool,
+	process_substitution: bool,
+}
+
+struct Analyzer {
+	options:  ParserOptions,
+	analysis: ShellCommandAnalysis,
+	resolved: Vec<ResolvedCommand>,
+	/// Position of the word whose substitution is being analyzed.
+	origin:   Option<Position>,
+	/// Position of the enclosing command, for items without a location.
+	current:  Position,
+	depth:    usize,
+}
+
+impl Analyzer {
+	fn position(&self, location: Option<&impl SourceLocation>) -> Position {
+		self.origin.unwrap_or_else(|| {
+			location
+				.and_then(SourceLocation::location)
+				.map_or(self.current, |location| Position {
+					line:   to_u32(location.start.line),
+					column: to_u32(location.start.column),
+				})
+		})
+	}
+
+	fn program(&mut self, program: &ast::Program) {
+		for list in &program.complete_commands {
+			self.compound_list(list);
+		}
+	}
+
+	fn compound_list(&mut self, list: &ast::CompoundList) {
+		for ast::CompoundListItem(and_or, _) in &list.0 {
+			self.pipeline(&and_or.first);
+			for next in &and_or.additional {
+				let (ast::AndOr::And(pipeline) | ast::AndOr::Or(pipeline)) = next;
+				self.pipeline(pipeline);
+			}
+		}
+	}
+
+	fn pipeline(&mut self, pipeline: &ast::Pipeline) {
+		for (index, command) in pipeline.seq.iter().enumerate() {
+			self.command(command, index > 0);
+		}
+	}
+
+	fn command(&mut self, command: &ast::Command, piped: bool) {
+		match command {
+			ast::Command::Simple(simple) => self.simple(simple, piped),
+			ast::Command::Compound(compound, redirects) => {
+				self.compound(compound);
+				self.current = self.position(Some(compound));
+				self.redirects(redirects.as_ref());
+			},
+			ast::Command::Function(function) => {
+				self.compound(&function.body.0);
+				self.current = self.position(Some(&function.body.0));
+				self.redirects(function.body.1.as_ref());
+			},
+			ast::Command::ExtendedTest(test) => self.test(&test.expr),
+		}
+	}
+
+	fn compound(&mut self, compound: &ast::CompoundCommand) {
+		match compound {
+			ast::CompoundCommand::Arithmetic(_) => {},
+			ast::CompoundCommand::ArithmeticForClause(clause) => self.compound_list(&clause.body.list),
+			ast::CompoundCommand::BraceGroup(group) => self.compound_list(&group.list),
+			ast::CompoundCommand::Subshell(subshell) => self.compound_list(&subshell.list),
+			ast::CompoundCommand::ForClause(clause) => {
+				for value in clause.values.iter().flatten() {
+					self.word(value);
+				}
+				self.compound_list(&clause.body.list);
+			},
+			ast::CompoundCommand::CaseClause(clause) => {
+				self.word(&clause.value);
+				for case in &clause.cases {
+					if let Some(list) = &case.cmd {
+						self.compound_list(list);
+					}
+				}
+			},
+			ast::CompoundCommand::IfClause(clause) => {
+				self.compound_list(&clause.condition);
+				self.compound_list(&clause.then);
+				for branch in clause.elses.iter().flatten() {
+					if let Some(condition) = &branch.condition {
+						self.compound_list(condition);
+					}
+					self.compound_list(&branch.body);
+				}
+			},
+			ast::CompoundCommand::WhileClause(clause) | ast::CompoundCommand::UntilClause(clause) => {
+				self.compound_list(&clause.0);
+				self.compound_list(&clause.1.list);
+			},
+		}
+	}
+
+	fn test(&mut self, expr: &ast::ExtendedTestExpr) {
+		match expr {
+			ast::ExtendedTestExpr::And(left, right) | ast::ExtendedTestExpr::Or(left, right) => {
+				self.test(left);
+				self.test(right);
+			},
+			ast::ExtendedTestExpr::Not(inner) | ast::ExtendedTestExpr::Parenthesized(inner) => {
+				self.test(inner);
+			},
+			ast::ExtendedTestExpr::UnaryTest(_, word) => self.word(word),
+			ast::ExtendedTestExpr::BinaryTest(_, left, right) => {
+				self.word(left);
+				self.word(right);
+			},
+		}
+	}
+
+	fn simple(&mut self, command: &ast::SimpleCommand, piped: bool) {
+		// Leading assignments have no location of their own in the command's.
+		let position = match command.prefix.as_ref().and_then(|prefix| prefix.0.first()) {
+			Some(ast::CommandPrefixOrSuffixItem::AssignmentWord(assignment, _)) => {
+				self.position(Some(assignment))
+			},
+			_ => self.position(Some(command)),
+		};
+		self.current = position;
+		// Take the command's place before its substitutions are analyzed, so
+		// the commands and programs in them follow it.
+		let index = self.analysis.commands.len();
+		let first_program = self.analysis.programs.len();
+		if command.word_or_name.is_some() {
+			self.analysis.commands.push(ShellAnalyzedCommand {
+				program: String::new(),
+				args:    Vec::new(),
+				dynamic: false,
+				text:    String::new(),
+				line:    position.line,
+				column:  position.column,
+			});
+		}
+		let prefix = command.prefix.iter().flat_map(|prefix| &prefix.0);
+		let suffix = command.suffix.iter().flat_map(|suffix| &suffix.0);
+		let program = command.word_or_name.as_ref().map(|word| self.arg(word));
+
+		let mut args = Vec::new();
+		let mut assignments = Vec::new();
+		let mut redirects = Vec::new();
+		let items = prefix
+			.map(|item| (item, true))
+			.chain(suffix.map(|item| (item, false)));
+		for (item, in_prefix) in items {
+			match item {
+				ast::CommandPrefixOrSuffixItem::IoRedirect(redirect) => {
+					redirects.extend(self.redirect(redirect));
+				},
+				ast::CommandPrefixOrSuffixItem::Word(word) => args.push(self.arg(word)),
+				ast::CommandPrefixOrSuffixItem::AssignmentWord(assignment, word) => {
+					let at = self.position(Some(assignment));
+					self.current = at;
+					self.assignment_value(&assignment.value);
+					self.current = position;
+					if in_prefix {
+						assignments.push((assignment, at));
+					} else {
+						// Builtins like `export` and `local` take assignments as
+						// arguments.
+						args.push(Arg {
+							text:                 word.value.clone(),
+							literal:              false,
+							process_substitution: false,
+						});
+					}
+				},
+				ast::CommandPrefixOrSuffixItem::ProcessSubstitution(_, subshell) => {
+					args.push(Arg {
+						text:                 item.to_string(),
+						literal:              false,
+						process_substitution: true,
+					});
+					self.nested(position, |analyzer| analyzer.compound_list(&subshell.list));
+				},
+			}
+		}
+
+		let Some(program) = program else {
+			for (assignment, at) in assignments {
+				self.push_assignment(assignment, None, at);
+			}
+			return;
+		};
+		let text = command.to_string();
+		let args_text: Vec<String> = args.iter().map(|arg| arg.text.clone()).collect();
+		self.analysis.commands[index] = ShellAnalyzedCommand {
+			program: program.text.clone(),
+			args:    args_text,
+			dynamic: !program.literal,
+			text:    text.clone(),
+			line:    position.line,
+			column:  position.column,
+		};
+		if !program.literal {
+			self.risk(ShellRiskKind::DynamicCommand, &text, position);
+			self.resolved.push(ResolvedCommand {
+				index,
+				programs: vec![program.text],
+				args: None,
+				dynamic: true,
+				elevated: false,
+				files: redirects,
+				operands: Vec::new(),
+			});
+			return;
+		}
+		let resolved = ResolvedCommand {
+			index,
+			programs: Vec::new(),
+			args: None,
+			dynamic: false,
+			elevated: false,
+			files: redirects,
+			operands: Vec::new(),
+		};
+		let nested_programs = self.analysis.programs.len() - first_program;
+		let program = self.classify(&program.text, &args, piped, &text, position, resolved);
+		self.analysis.programs[first_program..].rotate_left(nested_programs);
+		for (assignment, at) in assignments {
+			self.push_assignment(assignment, Some(program.clone()), at);
+		}
+	}
+
+	/// Record the risks of running `program` with `args`, looking through
+	/// wrappers, and complete `resolved`. Returns the program finally run.
+	fn classify(
+		&mut self,
+		program: &str,
+		args: &[Arg],
+		piped: bool,
+		text: &str,
+		position: Position,
+		mut resolved_command: ResolvedCommand,
+	) -> String {
+		let mut program = program.to_string();
+		let mut args = args;
+		let mut env = Vec::new();
+		// The arguments of the program finally run, unless it is unknown.
+		let resolved = loop {
+			self.push_program(&program);
+			resolved_command.programs.push(program.clone());
+			if ELEVATORS.contains(&program.as_str()) {
+				self.risk(ShellRiskKind::Elevation, text, position);
+				resolved_command.elevated = true;
+			}
+			let Some((_, value_options)) = WRAPPERS.iter().find(|(name, _)| *name == program) else {
+				break Some(args);
+			};
+			let Some(index) = wrapped_program(&program, value_options, args) else {
+				break None;
+			};
+			if program == "env" {
+				let assignments = args[..index]
+					.iter()
+					.filter(|arg| !arg.text.starts_with('-'));
+				env.extend(assignments.filter_map(|arg| arg.text.split_once('=')));
+			}
+			program.clone_from(&args[index].text);
+			if !args[index].literal {
+				self.risk(ShellRiskKind::DynamicCommand, text, position);
+				resolved_command.programs.push(program.clone());
+				resolved_command.dynamic = true;
+				break None;
+			}
+			args = &args[index + 1..];
+		};
+		for (name, value) in env {
+			self.analysis.assignments.push(ShellAnalyzedAssignment {
+				name:    name.to_string(),
+				value:   value.to_string(),
+				command: Some(program.clone()),
+				line:    position.line,
+				column:  position.column,
+			});
+		}
+		let Some(args) = resolved else {
+			self.resolved.push(resolved_command);
+			return program;
+		};
+
+		let operands = operands(args);
+		resolved_command.args = Some(args.iter().map(|arg| arg.text.clone()).collect());
+		resolved_command.operands = operands.iter().map(|arg| arg.text.clone()).collect();
+		self.resolved.push(resolved_command);
+		match program.as_str() {
+			"rm" => {
+				let recursive = args
+					.iter()
+					.take_while(|arg| arg.text != "--")
+					.any(|arg| is_recursive_flag(&arg.text));
+				if recursive {
+					self.risk(ShellRiskKind::RecursiveDelete, text, position);
+				}
+			},
+			"eval" => self.risk(ShellRiskKind::Eval, text, position),
+			"tee" => {
+				for arg in operands {
+					self.push_file(&arg.text);
+				}
+			},
+			shell if SHELLS.contains(&shell) => {
+				let reads_stdin = match operands.first() {
+					None => true,
+					Some(first) => first.text == "-",
+				} || args.iter().any(|arg| arg.text == "-s");
+				let substituted = operands
+					.first()
+					.is_some_and(|first| first.process_substitution);
+				if (piped && reads_stdin) || substituted {
+					self.risk(ShellRiskKind::PipeToShell, text, position);
+				}
+			},
+			_ => {},
+		}
+		program
+	}
+
+	fn redirects(&mut self, redirects: Option<&ast::RedirectList>) {
+		for redirect in redirects.iter().flat_map(|list| &list.0) {
+			self.redirect(redirect);
+		}
+	}
+
+	/// Record `redirect`. Returns the file it reads or writes, if any.
+	fn redirect(&mut self, redirect: &ast::IoRedirect) -> Option<String> {
+		let position = self.position(Some(redirect));
+		let (kind, fd, target) = match redirect {
+			ast::IoRedirect::File(fd, kind, target) => {
+				let kind = match kind {
+					ast::IoFileRedirectKind::Read => ShellRedirectKind::Read,
+					ast::IoFileRedirectKind::Write => ShellRedirectKind::Write,
+					ast::IoFileRedirectKind::Append => ShellRedirectKind::Append,
+					ast::IoFileRedirectKind::ReadAndWrite => ShellRedirectKind::ReadWrite,
+					ast::IoFileRedirectKind::Clobber => ShellRedirectKind::Clobber,
+					ast::IoFileRedirectKind::DuplicateInput
+					| ast::IoFileRedirectKind::DuplicateOutput => ShellRedirectKind::Duplicate,
+				};
+				let target = match target {
+					ast::IoFileRedirectTarget::Filename(word) => {
+						let target = self.arg(word).text;
+						if !matches!(kind, ShellRedirectKind::Read | ShellRedirectKind::Duplicate) {
+							self.push_file(&target);
+						}
+						target
+					},
+					ast::IoFileRedirectTarget::Fd(fd) => fd.to_string(),
+					ast::IoFileRedirectTarget::Duplicate(word) => self.arg(word).text,
+					ast::IoFileRedirectTarget::ProcessSubstitution(_, subshell) => {
+						self.nested(position, |analyzer| analyzer.compound_list(&subshell.list));
+						target.to_string()
+					},
+				};
+				(kind, *fd, target)
+			},
+			ast::IoRedirect::HereDocument(fd, doc) => {
+				if doc.requires_expansion {
+					self.word(&doc.doc);
+				}
+				(ShellRedirectKind::HereDoc, *fd, doc.here_end.value.clone())
+			},
+			ast::IoRedirect::HereString(fd, word) => {
+				(ShellRedirectKind::HereString, *fd, self.arg(word).text)
+			},
+			ast::IoRedirect::OutputAndError(word, append) => {
+				let target = self.arg(word).text;
+				self.push_file(&target);
+				let kind = if *append {
+					ShellRedirectKind::Append
+				} else {
+					ShellRedirectKind::Write
+				};
+				(kind, None, target)
+			},
+		};
+		let file = match redirect {
+			ast::IoRedirect::File(_, _, ast::IoFileRedirectTarget::Filename(_))
+			| ast::IoRedirect::OutputAndError(..) => Some(target.clone()),
+			_ => None,
+		};
+		self.analysis.redirections.push(ShellAnalyzedRedirect {
+			kind,
+			fd,
+			target,
+			line: position.line,
+			column: position.column,
+		});
+		file
+	}
+
+	fn assignment_value(&mut self, value: &ast::AssignmentValue) {
+		match value {
+			ast::AssignmentValue::Scalar(word) => self.word(word),
+			ast::AssignmentValue::Array(elements) => {
+				for (key, value) in elements {
+					if let Some(key) = key {
+						self.word(key);
+					}
+					self.word(value);
+				}
+			},
+		}
+	}
+
+	fn push_assignment(
+		&mut self,
+		assignment: &ast::Assignment,
+		command: Option<String>,
+		position: Position,
+	) {
+		let name = match &assignment.name {
+			ast::AssignmentName::VariableName(name) => name.clone(),
+			ast::AssignmentName::ArrayElementName(name, index) => format!("{name}[{index}]"),
+		};
+		self.analysis.assignments.push(ShellAnalyzedAssignment {
+			name,
+			value: assignment.value.to_string(),
+			command,
+			line: position.line,
+			column: position.column,
+		});
+	}
+
+	/// Analyze the substitutions in `word`.
+	fn word(&mut self, word: &ast::Word) {
+		self.arg(word);
+	}
+
+	/// Analyze the substitutions in `word` and unquote it.
+	fn arg(&mut self, word: &ast::Word) -> Arg {
+		let Ok(pieces) = word::parse(&word.value, &self.options) else {
+			return Arg {
+				text:                 word.value.clone(),
+				literal:              false,
+				process_substitution: false,
+			};
+		};
+		let position = self.position(Some(word));
+		self.substitutions(&pieces, position);
+		let literal = unquote(&pieces);
+		Arg {
+			literal:              literal.is_some(),
+			text:                 literal.unwrap_or_else(|| word.value.clone()),
+			process_substitution: false,
+		}
+	}
+
+	fn substitutions(&mut self, pieces: &[WordPieceWithSource], position: Position) {
+		for piece in pieces {
+			match &piece.piece {
+				WordPiece::CommandSubstitution(source)
+				| WordPiece::BackquotedCommandSubstitution(source) => {
+					let Ok(program) = parse(source, &self.options) else {
+						continue;
+					};
+					self.nested(position, |analyzer| analyzer.program(&program));
+				},
+				WordPiece::DoubleQuotedSequence(inner)
+				| WordPiece::GettextDoubleQuotedSequence(inner) => {
+					self.substitutions(inner, position);
+				},
+				_ => {},
+			}
+		}
+	}
+
+	/// Analyze a substitution, reporting its items at `position`.
+	fn nested(&mut self, position: Position, analyze: impl FnOnce(&mut Self)) {
+		if self.depth >= MAX_DEPTH {
+			return;
+		}
+		let origin = self.origin.replace(position);
+		self.depth += 1;
+		analyze(self);
+		self.depth -= 1;
+		self.origin = origin;
+	}
+
+	fn push_program(&mut self, program: &str) {
+		if !self.analysis.programs.iter().any(|known| known == program) {
+			self.analysis.programs.push(program.to_string());
+		}
+	}
+
+	fn push_file(&mut self, path: &str) {
+		let non_file = NON_FILES.contains(&path) || path.starts_with("/dev/fd/");
+		if !non_file
+			&& !self
+				.analysis
+				.files_written
+				.iter()
+				.any(|known| known == path)
+		{
+			self.analysis.files_written.push(path.to_string());
+		}
+	}
+
+	fn risk(&mut self, kind: ShellRiskKind, text: &str, position: Position) {
+		self.analysis.risks.push(ShellRisk {
+			kind,
+			text: text.to_string(),
+			line: position.line,
+			column: position.column,
+		});
+	}
+}
+
+fn to_u32(count: usize) -> u32 {
+	u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+fn parse(source: &str, options: &ParserOptions) -> std::result::Result<ast::Program, impl Display> {
+	let source_info = SourceInfo { source: String::from("main") };
+	brush_parser::Parser::new(source.as_bytes(), options, &source_info).parse_program()
+}
+
+/// Value of a word without expansions, with quoting removed.
+fn unquote(pieces: &[WordPieceWithSource]) -> Option<String> {
+	let mut out = String::new();
+	for piece in pieces {
+		match &piece.piece {
+			WordPiece::Text(text)
+			| WordPiece::SingleQuotedText(text)
+			| WordPiece::AnsiCQuotedText(text) => out.push_str(text),
+			// The prefix holds the user name after the tilde, if any.
+			WordPiece::TildePrefix(user) => {
+				out.push('~');
+				out.push_str(user);
+			},
+			WordPiece::EscapeSequence(escape) => {
+				out.push_str(escape.strip_prefix('\\').unwrap_or(escape));
+			},
+			WordPiece::DoubleQuotedSequence(inner) | WordPiece::GettextDoubleQuotedSequence(inner) => {
+				out.push_str(&unquote(inner)?);
+			},
+			WordPiece::ParameterExpansion(_)
+			| WordPiece::CommandSubstitution(_)
+			| WordPiece::BackquotedCommandSubstitution(_)
+			| WordPiece::ArithmeticExpression(_) => return None,
+		}
+	}
+	Some(out)
+}
+
+/// Index in `args` of the program run by `wrapper`, skipping its options and
+/// (for `env`) assignments and (for `timeout`) duration.
+fn wrapped_program(wrapper: &str, value_options: &[&str], args: &[Arg]) -> Option<usize> {
+	let mut index = 0;
+	let mut skip_operand = wrapper == "timeout";
+	while let Some(arg) = args.get(index) {
+		if arg.text == "--" {
+			return (index + 1 < args.len()).then_some(index + 1);
+		}
+		if arg.text.starts_with('-') && arg.text.len() > 1 {
+			index += if value_options.contains(&arg.text.as_str()) {
+				2
+			} else {
+				1
+			};
+		} else if wrapper == "env" && arg.text.contains('=') {
+			index += 1;
+		} else if skip_operand {
+			skip_operand = false;
+			index += 1;
+		} else {
+			return Some(index);
+		}
+	}
+	None
+}
+
+/// Arguments that are not options.
+fn operands(args: &[Arg]) -> Vec<&Arg> {
+	let mut operands = Vec::new();
+	let mut options_done = false;
+	for arg in args {
+		if !options_done && arg.text == "--" {
+			options_done = true;
+		} else if options_done || !arg.text.starts_with('-') || arg.text == "-" {
+			operands.push(arg);
+		}
+	}
+	operands
+}
+
+fn is_recursive_flag(arg: &str) -> bool {
+	arg == "--recursive"
+		|| (arg.starts_with('-') && !arg.starts_with("--") && arg.contains(['r', 'R']))
+}
+
+/// Analyze `command`, with its simple commands resolved through wrappers in
+/// source order.
+pub(super) fn analyze_resolved(
+	command: &str,
+) -> Result<(ShellCommandAnalysis, Vec<ResolvedCommand>)> {
+	let options = ParserOptions::default();
+	let program = parse(command, &options)
+		.map_err(|err| Error::from_reason(format!("Failed to parse shell command: {err}")))?;
+	let mut analyzer = Analyzer {
+		options,
+		analysis: ShellCommandAnalysis::default(),
+		resolved: Vec::new(),
+		origin: None,
+		current: Position { line: 1, column: 1 },
+		depth: 0,
+	};
+	analyzer.program(&program);
+	analyzer.resolved.sort_by_key(|resolved| resolved.index);
+	Ok((analyzer.analysis, analyzer.resolved))
+}
+
+fn analyze(command: &str) -> Result<ShellCommandAnalysis> {
+	analyze_resolved(command).map(|(analysis, _)| analysis)
+}
+
+/// Parse `command` and report the commands, redirections, written files,
+/// assignments and risky constructs in it, without running anything.
+#[napi(js_name = "analyzeShellCommand")]
+pub fn analyze_shell_command(command: String) -> task::Async<ShellCommandAnalysis> {
+	task::blocking("analyze_shell_command", (), move |_| analyze(&command))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn risks(command: &str) -> Vec<ShellRiskKind> {
+		let analysis = analyze(command).expect("command parses");
+		analysis.risks.iter().map(|risk| risk.kind).collect()
+	}
+
+	#[test]
+	fn test_recursive_delete() {
+		use ShellRiskKind::RecursiveDelete;
+		assert!(risks("rm -rf build") == [RecursiveDelete]);
+		assert!(risks("rm -R build") == [RecursiveDelete]);
+		assert!(risks("rm --recursive build") == [RecursiveDelete]);
+		assert!(risks("rm build.log").is_empty());
+		assert!(risks("ls -r").is_empty());
+	}
+
+	#[test]
+	fn test_wrappers_are_looked_through() {
+		use ShellRiskKind::{Elevation, RecursiveDelete};
+		assert!(risks("sudo rm -rf /tmp/x") == [Elevation, RecursiveDelete]);
+		assert!(risks("sudo -u root rm -r x") == [Elevation, RecursiveDelete]);
+		assert!(risks("env FOO=1 timeout 5 rm -r x") == [RecursiveDelete]);
+		let analysis = analyze("sudo -u root make install").unwrap();
+		assert_eq!(analysis.programs, ["sudo", "make"]);
+	}
+
+	#[test]
+	fn test_pipe_to_shell() {
+		use ShellRiskKind::PipeToShell;
+		assert!(risks("curl -fsSL https://example.com/install | sh") == [PipeToShell]);
+		assert!(risks("bash <(curl -fsSL https://example.com/install)") == [PipeToShell]);
+		assert!(risks("cat script.sh | grep foo").is_empty());
+		assert!(risks("bash script.sh").is_empty());
+	}
+
+	#[test]
+	fn test_eval_and_dynamic_commands() {
+		assert!(risks("eval \"$CMD\"") == [ShellRiskKind::Eval]);
+		assert!(risks("$CMD --flag") == [ShellRiskKind::DynamicCommand]);
+		assert!(risks("echo $CMD").is_empty());
+	}
+
+	#[test]
+	fn test_substitutions_are_analyzed() {
+		assert!(risks("echo $(rm -rf x)") == [ShellRiskKind::RecursiveDelete]);
+		let analysis = analyze("echo \"$(date)\"").unwrap();
+		assert_eq!(analysis.programs, ["echo", "date"]);
+		let analysis = analyze("sudo make $(nproc) && ls").unwrap();
+		assert_eq!(analysis.programs, ["sudo", "make", "nproc", "ls"]);
+		let commands: Vec<_> = analysis
+			.commands
+			.iter()
+			.map(|command| &command.program)
+			.collect();
+		assert_eq!(commands, ["sudo", "nproc", "ls"]);
+	}
+
+	#[test]
+	fn test_files_written() {
+		let analysis =
+			analyze("make > build.log 2>&1 && tee -a out.txt < in.txt > /dev/null").unwrap();
+		assert_eq!(analysis.files_written, ["build.log", "out.txt"]);
+	}
+}
@@ -0,0 +1,91 @@
+//! Streaming removal of ANSI escape sequences from shell output.
+//!
+//! Works on raw bytes before UTF-8 decoding: escape sequences are pure ASCII
+//! and ESC never appears inside a multi-byte UTF-8 sequence. State is carried
+//! across reads, so sequences split between chunks are still removed.
+
+#[derive(Clone, Copy, Default)]
+enum State {
+	#[default]
+	Ground,
+	/// Saw ESC.
+	Escape,
+	/// ESC followed by intermediate bytes; waiting for the final byte.
+	Intermediate,
+	/// Control Sequence Introducer (`ESC [`).
+	Csi,
+	/// String sequence (OSC, DCS, SOS, PM, APC) terminated by ST; OSC also
+	/// accepts BEL.
+	Str { bel: bool },
+	/// Saw ESC inside a string sequence; `\` completes the ST terminator.
+	StrEscape { bel: bool },
+}
+
+/// Strips ANSI escape sequences from a byte stream.
+#[derive(Default)]
+pub struct AnsiStripper {
+	state: State,
+}
+
+impl AnsiStripper {
+	/// Remove escape sequences from `buf` in place.
+	/// Returns the length of the retained prefix.
+	pub fn strip(&mut self, buf: &mut [u8]) -> usize {
+		let mut out = 0;
+		for i in 0..buf.len() {
+			let byte = buf[i];
+			if self.advance(byte) {
+				buf[out] = byte;
+				out += 1;
+			}
+		}
+		out
+	}
+
+	/// Feed one byte through the state machine; returns true to keep it.
+	const fn advance(&mut self, byte: u8) -> bool {
+		const ESC: u8 = 0x1b;
+		const BEL: u8 = 0x07;
+
+		self.state = match self.state {
+			State::Ground => {
+				if byte != ESC {
+					return true;
+				}
+				State::Escape
+			},
+			State::Escape => match byte {
+				b'[' => State::Csi,
+				b']' => State::Str { bel: true },
+				b'P' | b'X' | b'^' | b'_' => State::Str { bel: false },
+				0x20..=0x2f => State::Intermediate,
+				0x40..=0x7e => State::Ground,
+				ESC => State::Escape,
+				// Not a valid sequence: drop the ESC and keep the byte.
+				_ => {
+					self.state = State::Ground;
+					return true;
+				},
+			},
+			State::Intermediate => match byte {
+				0x30..=0x7e => State::Ground,
+				_ => State::Intermediate,
+			},
+			State::Csi => match byte {
+				0x40..=0x7e => State::Ground,
+				_ => State::Csi,
+			},
+			State::Str { bel } => match byte {
+				ESC => State::StrEscape { bel },
+				BEL if bel => State::Ground,
+				_ => State::Str { bel },
+			},
+			State::StrEscape { bel } => match byte {
+				b'\\' => State::Ground,
+				ESC => State::StrEscape { bel },
+				_ => State::Str { bel },
+			},
+		};
+		false
+	}
+}
@@ -0,0 +1,295 @@
+//! Append-only audit log of shell executions.
+//!
+//! Every `executeShell` call and `Shell.run` command is appended to the
+//! `audit_log` table of the session store once it ends, however it ends:
+//! the command with the `setRedactionRules` patterns applied, its working
+//! directory, a BLAKE3 hash of the environment it was given, exit code,
+//! outcome, duration and bytes of output. Triggers reject updates and
+//! deletes, so entries can only be added.
+//!
+//! Writing an entry is best effort: a store that cannot be written does not
+//! fail the command, which has already run. `setAuditLog` turns the log off
+//! or moves it to another database, and `queryAuditLog` reads it back.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const failed = await native.queryAuditLog({ since: Date.now() - 86_400_000, outcome: "exited" })
+//! ```
+
+use std::{
+	collections::{BTreeMap, HashMap},
+	env,
+	sync::LazyLock,
+	time::Instant,
+};
+
+use napi::tokio;
+use napi_derive::napi;
+use parking_lot::RwLock;
+use rusqlite::{Connection, params, params_from_iter, types::Value};
+
+use super::redact;
+use crate::{store, task};
+
+const DEFAULT_QUERY_LIMIT: u32 = 100;
+
+static CONFIG: LazyLock<RwLock<Config>> =
+	LazyLock::new(|| RwLock::new(Config { enabled: true, db_path: None }));
+
+struct Config {
+	enabled: bool,
+	db_path: Option<String>,
+}
+
+/// Options for `setAuditLog`.
+#[napi(object)]
+#[derive(Default)]
+pub struct AuditLogOptions {
+	/// Record executions (default: true).
+	pub enabled: Option<bool>,
+	/// Database file (default: the session store, as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path: Option<String>,
+}
+
+/// Filter for `queryAuditLog`; every field given must match.
+#[napi(object)]
+#[derive(Default)]
+pub struct AuditLogFilter {
+	/// Database file (default: the one set with `setAuditLog`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:   Option<String>,
+	/// Earliest start time in milliseconds since the epoch, inclusive.
+	pub since:     Option<f64>,
+	/// Latest start time in milliseconds since the epoch, inclusive.
+	pub until:     Option<f64>,
+	/// Text the command contains.
+	pub command:   Option<String>,
+	/// Working directory.
+	pub cwd:       Option<String>,
+	/// How the execution ended.
+	#[napi(ts_type = "\"exited\" | \"killed\" | \"cancelled\" | \"timeout\" | \"error\"")]
+	pub outcome:   Option<String>,
+	/// Exit code.
+	#[napi(js_name = "exitCode")]
+	pub exit_code: Option<i32>,
+	/// Maximum number of entries (default: 100).
+	pub limit:     Option<u32>,
+}
+
+/// An execution recorded in the audit log.
+#[napi(object)]
+pub struct AuditLogEntry {
+	/// Position in the log.
+	pub id:           i64,
+	/// Command, with the redaction rules applied.
+	pub command:      String,
+	/// Working directory, when known; `Shell.run` commands without `cwd` run
+	/// wherever the session is.
+	pub cwd:          Option<String>,
+	/// BLAKE3 hash of the environment the command was given, lowercase hex.
+	#[napi(js_name = "envHash")]
+	pub env_hash:     String,
+	/// Exit code, when the command exited.
+	#[napi(js_name = "exitCode")]
+	pub exit_code:    Option<i32>,
+	/// "exited", "killed" (for output or idle limits), "cancelled",
+	/// "timeout", or "error" when the command could not be run.
+	#[napi(ts_type = "\"exited\" | \"killed\" | \"cancelled\" | \"timeout\" | \"error\"")]
+	pub outcome:      String,
+	/// Wall-clock duration in milliseconds, including time queued.
+	#[napi(js_name = "durationMs")]
+	pub duration_ms:  f64,
+	/// Bytes of output the command produced.
+	#[napi(js_name = "outputBytes")]
+	pub output_bytes: f64,
+	/// Start time in milliseconds since the epoch.
+	#[napi(js_name = "startedAt")]
+	pub started_at:   f64,
+}
+
+/// How an execution ended.
+pub struct Ending {
+	exit_code: Option<i32>,
+	outcome:   &'static str,
+}
+
+impl Ending {
+	pub const fn of(exit_code: Option<i32>, cancelled: bool, timed_out: bool) -> Self {
+		let outcome = if cancelled {
+			"cancelled"
+		} else if timed_out {
+			"timeout"
+		} else if exit_code.is_some() {
+			"exited"
+		} else {
+			"killed"
+		};
+		Self { exit_code, outcome }
+	}
+
+	pub const fn error() -> Self {
+		Self { exit_code: None, outcome: "error" }
+	}
+}
+
+/// An execution being audited, written to the log by [`Self::finish`].
+pub struct AuditRecord {
+	db_path:    Option<String>,
+	command:    String,
+	cwd:        Option<String>,
+	env_hash:   String,
+	started_at: i64,
+	started:    Instant,
+}
+
+impl AuditRecord {
+	/// Start auditing an execution, unless the log is off. `env` are the
+	/// variables set over the process environment, in order.
+	pub fn start(
+		command: &str,
+		cwd: Option<String>,
+		env: [Option<&HashMap<String, String>>; 2],
+	) -> Option<Self> {
+		let config = CONFIG.read();
+		config.enabled.then(|| Self {
+			db_path: config.db_path.clone(),
+			command: redact::redact_text(command.to_string()),
+			cwd,
+			env_hash: env_hash(env),
+			started_at: store::now_ms(),
+			started: Instant::now(),
+		})
+	}
+
+	/// Append the entry, ignoring a store that cannot be written.
+	pub async fn finish(self, ending: Ending, output_bytes: u64) {
+		let duration_ms = self.started.elapsed().as_secs_f64() * 1000.0;
+		let _ = tokio::task::spawn_blocking(move || {
+			let store = store::open_store(self.db_path)?;
+			store
+				.lock()
+				.execute(
+					"INSERT INTO audit_log
+					 (command, cwd, env_hash, exit_code, outcome, duration_ms, output_bytes, started_at)
+					 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+					params![
+						self.command,
+						self.cwd,
+						self.env_hash,
+						ending.exit_code,
+						ending.outcome,
+						duration_ms,
+						i64::try_from(output_bytes).unwrap_or(i64::MAX),
+						self.started_at
+					],
+				)
+				.map_err(store::sql_error)
+		})
+		.await;
+	}
+}
+
+/// Hash of the process environment with `overlays` applied, independent of
+/// variable order.
+fn env_hash(overlays: [Option<&HashMap<String, String>>; 2]) -> String {
+	let mut vars: BTreeMap<String, String> = env::vars_os()
+		.map(|(key, value)| {
+			(key.to_string_lossy().into_owned(), value.to_string_lossy().into_owned())
+		})
+		.collect();
+	for overlay in overlays.into_iter().flatten() {
+		vars.extend(
+			overlay
+				.iter()
+				.map(|(key, value)| (key.clone(), value.clone())),
+		);
+	}
+	let mut hasher = blake3::Hasher::new();
+	for (key, value) in &vars {
+		hasher.update(key.as_bytes());
+		hasher.update(b"=");
+		hasher.update(value.as_bytes());
+		hasher.update(b"\0");
+	}
+	hasher.finalize().to_hex().to_string()
+}
+
+fn query_audit_log_sync(
+	conn: &Connection,
+	filter: AuditLogFilter,
+) -> rusqlite::Result<Vec<AuditLogEntry>> {
+	let mut sql = String::from(
+		"SELECT id, command, cwd, env_hash, exit_code, outcome, duration_ms, output_bytes, \
+		 started_at
+		 FROM audit_log WHERE 1",
+	);
+	let mut args = Vec::new();
+	if let Some(since) = filter.since {
+		sql.push_str(" AND started_at >= ?");
+		args.push(Value::Integer(since as i64));
+	}
+	if let Some(until) = filter.until {
+		sql.push_str(" AND started_at <= ?");
+		args.push(Value::Integer(until as i64));
+	}
+	if let Some(command) = filter.command {
+		sql.push_str(" AND instr(command, ?) > 0");
+		args.push(Value::Text(command));
+	}
+	if let Some(cwd) = filter.cwd {
+		sql.push_str(" AND cwd = ?");
+		args.push(Value::Text(cwd));
+	}
+	if let Some(outcome) = filter.outcome {
+		sql.push_str(" AND outcome = ?");
+		args.push(Value::Text(outcome));
+	}
+	if let Some(exit_code) = filter.exit_code {
+		sql.push_str(" AND exit_code = ?");
+		args.push(Value::Integer(exit_code.into()));
+	}
+	sql.push_str(" ORDER BY id DESC LIMIT ?");
+	args.push(Value::Integer(filter.limit.unwrap_or(DEFAULT_QUERY_LIMIT).into()));
+
+	let mut stmt = conn.prepare(&sql)?;
+	let rows = stmt.query_map(params_from_iter(args), |row| {
+		Ok(AuditLogEntry {
+			id:           row.get(0)?,
+			command:      row.get(1)?,
+			cwd:          row.get(2)?,
+			env_hash:     row.get(3)?,
+			exit_code:    row.get(4)?,
+			outcome:      row.get(5)?,
+			duration_ms:  row.get(6)?,
+			output_bytes: row.get::<_, i64>(7)? as f64,
+			started_at:   row.get::<_, i64>(8)? as f64,
+		})
+	})?;
+	rows.collect()
+}
+
+/// Turn the audit log of shell executions on or off, or move it to another
+/// database. Executions already running are logged where they started.
+#[napi(js_name = "setAuditLog")]
+pub fn set_audit_log(options: AuditLogOptions) {
+	*CONFIG.write() = Config { enabled: options.enabled.unwrap_or(true), db_path: options.db_path };
+}
+
+/// Read audited shell executions matching `filter`, newest first.
+///
+/// # Errors
+/// Returns an error if the database cannot be opened or read.
+#[napi(js_name = "queryAuditLog")]
+pub fn query_audit_log(filter: Option<AuditLogFilter>) -> task::Async<Vec<AuditLogEntry>> {
+	let mut filter = filter.unwrap_or_default();
+	let db_path = filter
+		.db_path
+		.take()
+		.or_else(|| CONFIG.read().db_path.clone());
+	task::blocking("shell.audit_query", (), move |_| {
+		let store = store::open_store(db_path)?;
+		let conn = store.lock();
+		query_audit_log_sync(&conn, filter).map_err(store::sql_error)
+	})
+}
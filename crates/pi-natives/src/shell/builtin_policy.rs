@@ -0,0 +1,118 @@
+//! Restricting the builtins of a session.
+//!
+//! # Overview
+//! `builtinPolicy` trims the builtins a session offers: `allow` keeps only
+//! the named ones, `deny` disables the named ones, and `confineCd` keeps
+//! `cd`, `pushd` and `popd` inside the roots set with `setWorkspaceRoots`.
+//! A disabled builtin is gone as far as the shell is concerned, so a program
+//! of the same name on `PATH` (`kill`, `printf`) runs in its place. Any
+//! restriction also disables `enable`, which could turn builtins back on.
+//!
+//! A confined directory change runs as usual and is then undone, with an
+//! error, if it left the workspace; `CDPATH`, `cd -` and the directory stack
+//! are resolved exactly as the builtins resolve them.
+
+use std::{collections::HashMap, future::Future, io::Write as _, pin::Pin, sync::LazyLock};
+
+use brush_builtins::{BuiltinSet, default_builtins};
+use brush_core::{
+	CommandArg, ExecutionContext, ExecutionResult, Shell as BrushShell, builtins::CommandExecuteFunc,
+};
+use napi::{Error, Result};
+use napi_derive::napi;
+
+use crate::workspace;
+
+/// Builtins that change the working directory.
+const DIR_CHANGERS: [&str; 3] = ["cd", "pushd", "popd"];
+
+/// The stock implementations of `DIR_CHANGERS`.
+static STOCK: LazyLock<HashMap<String, CommandExecuteFunc>> = LazyLock::new(|| {
+	default_builtins(BuiltinSet::BashMode)
+		.into_iter()
+		.filter(|(name, _)| DIR_CHANGERS.contains(&name.as_str()))
+		.map(|(name, registration)| (name, registration.execute_func))
+		.collect()
+});
+
+/// Builtins a session may use.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct ShellBuiltinPolicy {
+	/// Builtins to keep; all others are disabled (default: all).
+	pub allow:      Option<Vec<String>>,
+	/// Builtins to disable, such as `trap` or `ulimit`.
+	pub deny:       Option<Vec<String>>,
+	/// Refuse `cd`, `pushd` and `popd` into directories outside the
+	/// workspace roots (default: false).
+	#[napi(js_name = "confineCd")]
+	pub confine_cd: Option<bool>,
+}
+
+impl ShellBuiltinPolicy {
+	/// Apply the policy to a newly created session.
+	pub fn apply(&self, shell: &mut BrushShell) -> Result<()> {
+		for name in self.allow.iter().chain(&self.deny).flatten() {
+			if !shell.builtins().contains_key(name) {
+				return Err(Error::from_reason(format!("Unknown builtin in builtinPolicy: {name}")));
+			}
+		}
+		let mut disabled: Vec<String> = self.deny.clone().unwrap_or_default();
+		if let Some(allow) = &self.allow {
+			disabled.extend(
+				shell
+					.builtins()
+					.keys()
+					.filter(|name| !allow.contains(name))
+					.cloned(),
+			);
+		}
+		if !disabled.is_empty() {
+			disabled.push(String::from("enable"));
+		}
+		for name in &disabled {
+			if let Some(registration) = shell.builtin_mut(name) {
+				registration.disabled = true;
+			}
+		}
+		if self.confine_cd == Some(true) {
+			for name in DIR_CHANGERS {
+				if let Some(registration) = shell.builtin_mut(name) {
+					registration.execute_func = confined;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// A directory change undone if it leaves the workspace.
+fn confined(
+	context: ExecutionContext<'_>,
+	args: Vec<CommandArg>,
+) -> Pin<
+	Box<dyn Future<Output = std::result::Result<ExecutionResult, brush_core::Error>> + Send + '_>,
+> {
+	Box::pin(async move {
+		let mut stderr = context.stderr();
+		let ExecutionContext { shell, command_name, params } = context;
+		let Some(&stock) = STOCK.get(&command_name) else {
+			return Ok(ExecutionResult::general_error());
+		};
+		let before = shell.working_dir().to_path_buf();
+		let stack = shell.directory_stack.clone();
+		let result = stock(
+			ExecutionContext { shell: &mut *shell, command_name: command_name.clone(), params },
+			args,
+		)
+		.await?;
+		let after = shell.working_dir().to_path_buf();
+		if after == before || workspace::check(&after, None).is_ok() {
+			return Ok(result);
+		}
+		shell.set_working_dir(&before)?;
+		shell.directory_stack = stack;
+		let _ = writeln!(stderr, "{command_name}: {}: outside the workspace", after.display());
+		Ok(ExecutionResult::general_error())
+	})
+}
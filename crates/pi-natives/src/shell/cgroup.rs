@@ -0,0 +1,326 @@
+//! Per-execution cgroups, limiting and measuring a command's whole process
+//! tree.
+//!
+//! Rlimits apply to each process on its own, so a build running many
+//! compilers can still exhaust memory; a cgroup limits and counts all of
+//! them together, including descendants that escape their process group.
+//!
+//! # Platform Implementation
+//! - **Linux**: with cgroup v2 and a delegated cgroup, an execution that sets
+//!   `maxTotalMemoryBytes`, `maxCpus`, `maxProcesses` or `cgroup` gets a child
+//!   cgroup of the one this process started in. Every spawned command moves
+//!   itself into it between fork and exec; cancelling kills the whole cgroup
+//!   through `cgroup.kill`, and it is removed once the execution ends (unless
+//!   background processes still run in it). The controllers a limit needs are
+//!   enabled for child cgroups on first use; if this process shares its cgroup,
+//!   it first moves itself into a `pi-host` leaf, as cgroup v2 only enables
+//!   controllers below cgroups without processes of their own
+//! - **Elsewhere**: cgroup limits fail the execution rather than running
+//!   commands unconstrained; `cgroup` alone is ignored
+
+use napi::{Error, Result};
+use napi_derive::napi;
+
+pub use self::platform::Cgroup;
+use super::limits::ShellResourceLimits;
+
+/// Resource usage of a command that ran in its own cgroup.
+#[napi(object)]
+pub struct ShellResourceUsage {
+	/// Highest memory use of all of the command's processes together, in
+	/// bytes (needs the memory controller and Linux 5.19).
+	#[napi(js_name = "peakMemoryBytes")]
+	pub peak_memory_bytes: Option<f64>,
+	/// Most processes and threads running at once (needs the pids controller
+	/// and Linux 6.1).
+	#[napi(js_name = "peakProcesses")]
+	pub peak_processes:    Option<u32>,
+	/// Time the command was held back by `maxCpus`, in milliseconds.
+	#[napi(js_name = "throttledMs")]
+	pub throttled_ms:      Option<f64>,
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+	use std::{
+		fs, io,
+		os::fd::AsRawFd as _,
+		path::{Path, PathBuf},
+		process::Command,
+		sync::{
+			LazyLock,
+			atomic::{AtomicU64, Ordering},
+		},
+		time::Duration,
+	};
+
+	use parking_lot::Mutex;
+
+	use super::{ShellResourceLimits, ShellResourceUsage};
+	use crate::ps;
+
+	/// `cpu.max` period in microseconds (the kernel default).
+	const CPU_PERIOD_US: u64 = 100_000;
+	/// Controllers enabled for execution cgroups when available.
+	const CONTROLLERS: [&str; 3] = ["cpu", "memory", "pids"];
+	const HOST_LEAF: &str = "pi-host";
+
+	/// The cgroup this process started in, below which execution cgroups are
+	/// created.
+	static PARENT: LazyLock<Option<PathBuf>> = LazyLock::new(own_cgroup);
+	/// Serializes enabling controllers (and moving this process).
+	static SETUP: Mutex<()> = Mutex::new(());
+	static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+	/// A cgroup v2 directory holding one execution's processes.
+	pub struct Cgroup {
+		path:  PathBuf,
+		/// `cgroup.procs`, written by each command before exec.
+		procs: fs::File,
+	}
+
+	impl Cgroup {
+		/// Create a cgroup with `limits` applied, needing `required`
+		/// controllers.
+		pub fn create(limits: &ShellResourceLimits, required: &[&str]) -> io::Result<Self> {
+			let parent = PARENT.as_deref().ok_or_else(|| {
+				io::Error::new(io::ErrorKind::Unsupported, "cgroup v2 is not available")
+			})?;
+			enable_controllers(parent, required)?;
+			let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+			let path = parent.join(format!("pi-{}-{id}", std::process::id()));
+			fs::create_dir(&path)?;
+			let configure = || {
+				if let Some(bytes) = limits.max_total_memory_bytes {
+					write(&path, "memory.max", &bytes.max(0).to_string())?;
+				}
+				if let Some(cpus) = limits.max_cpus {
+					// The kernel refuses quotas below 1ms.
+					let quota = ((cpus * CPU_PERIOD_US as f64).ceil() as u64).max(1000);
+					write(&path, "cpu.max", &format!("{quota} {CPU_PERIOD_US}"))?;
+				}
+				if let Some(count) = limits.max_processes {
+					write(&path, "pids.max", &count.to_string())?;
+				}
+				fs::OpenOptions::new()
+					.write(true)
+					.open(path.join("cgroup.procs"))
+			};
+			match configure() {
+				Ok(procs) => Ok(Self { path, procs }),
+				Err(err) => {
+					let _ = fs::remove_dir(&path);
+					Err(err)
+				},
+			}
+		}
+
+		/// Move each spawned command into the cgroup before it runs.
+		pub fn before_spawn(&self, cmd: &mut Command) {
+			use std::os::unix::process::CommandExt as _;
+
+			let fd = self.procs.as_raw_fd();
+			// SAFETY: the closure only calls write(2), which is
+			// async-signal-safe, and allocates nothing; the file outlives every
+			// spawn since the tracker owns the cgroup.
+			unsafe {
+				cmd.pre_exec(move || {
+					// Writing 0 moves the writing process.
+					if libc::write(fd, b"0".as_ptr().cast(), 1) < 0 {
+						return Err(io::Error::last_os_error());
+					}
+					Ok(())
+				});
+			}
+		}
+
+		/// CPU time of every process that ran in the cgroup.
+		pub fn cpu_time(&self) -> Option<ps::CpuTime> {
+			let stat = self.read("cpu.stat")?;
+			Some(ps::CpuTime {
+				user:   Duration::from_micros(stat_value(&stat, "user_usec")?),
+				system: Duration::from_micros(stat_value(&stat, "system_usec")?),
+			})
+		}
+
+		/// OOM kills in the cgroup, when the memory controller is enabled.
+		pub fn oom_kills(&self) -> Option<u64> {
+			stat_value(&self.read("memory.events")?, "oom_kill")
+		}
+
+		/// Peak usage figures the kernel keeps for the cgroup.
+		pub fn usage(&self) -> ShellResourceUsage {
+			let peak = |file| self.read(file)?.trim().parse::<u64>().ok();
+			ShellResourceUsage {
+				peak_memory_bytes: peak("memory.peak").map(|bytes| bytes as f64),
+				peak_processes:    peak("pids.peak").map(|count| count as u32),
+				throttled_ms:      self
+					.read("cpu.stat")
+					.and_then(|stat| stat_value(&stat, "throttled_usec"))
+					.map(|usec| usec as f64 / 1000.0),
+			}
+		}
+
+		/// Kill every process in the cgroup (Linux 5.14+).
+		pub fn kill(&self) {
+			let _ = write(&self.path, "cgroup.kill", "1");
+		}
+
+		fn read(&self, file: &str) -> Option<String> {
+			fs::read_to_string(self.path.join(file)).ok()
+		}
+	}
+
+	impl Drop for Cgroup {
+		fn drop(&mut self) {
+			// Fails while background processes still run in it.
+			let _ = fs::remove_dir(&self.path);
+		}
+	}
+
+	fn write(dir: &Path, file: &str, value: &str) -> io::Result<()> {
+		fs::write(dir.join(file), value)
+			.map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", dir.join(file).display())))
+	}
+
+	fn stat_value(stat: &str, key: &str) -> Option<u64> {
+		stat.lines().find_map(|line| {
+			let (name, value) = line.split_once(' ')?;
+			(name == key).then(|| value.trim().parse().ok()).flatten()
+		})
+	}
+
+	/// Path of this process's cgroup v2 cgroup, from `/proc/self/cgroup` and
+	/// the cgroup2 mount in `/proc/self/mountinfo`.
+	fn own_cgroup() -> Option<PathBuf> {
+		let cgroups = fs::read_to_string("/proc/self/cgroup").ok()?;
+		let path = cgroups.lines().find_map(|line| line.strip_prefix("0::"))?;
+		let mountinfo = fs::read_to_string("/proc/self/mountinfo").ok()?;
+		mountinfo.lines().find_map(|line| {
+			let (fields, source) = line.split_once(" - ")?;
+			if !source.starts_with("cgroup2 ") {
+				return None;
+			}
+			// Fields: mount id, parent id, device, root, mount point, ...
+			let mut fields = fields.split(' ').skip(3);
+			let root = fields.next()?;
+			let mount = fields.next()?;
+			let relative = path.strip_prefix(root)?.trim_start_matches('/');
+			let mount = Path::new(mount);
+			Some(if relative.is_empty() {
+				mount.to_path_buf()
+			} else {
+				mount.join(relative)
+			})
+		})
+	}
+
+	/// Enable controllers for the children of `parent`: the `required` ones,
+	/// failing when that is not possible, and the others when available.
+	fn enable_controllers(parent: &Path, required: &[&str]) -> io::Result<()> {
+		let _setup = SETUP.lock();
+		let read = |file| fs::read_to_string(parent.join(file)).unwrap_or_default();
+		let available = read("cgroup.controllers");
+		let enabled = read("cgroup.subtree_control");
+		let mut moved = false;
+		for controller in CONTROLLERS {
+			if enabled.split_whitespace().any(|name| name == controller) {
+				continue;
+			}
+			let needed = required.contains(&controller);
+			if !available.split_whitespace().any(|name| name == controller) {
+				if needed {
+					return Err(io::Error::new(
+						io::ErrorKind::Unsupported,
+						format!("the {controller} controller is not delegated to {}", parent.display()),
+					));
+				}
+				continue;
+			}
+			let mut result = write(parent, "cgroup.subtree_control", &format!("+{controller}"));
+			if needed
+				&& !moved
+				&& result
+					.as_ref()
+					.is_err_and(|err| err.raw_os_error() == Some(libc::EBUSY))
+			{
+				// Our own process is in the way; move it to a leaf and retry.
+				moved = true;
+				move_to_leaf(parent)?;
+				result = write(parent, "cgroup.subtree_control", &format!("+{controller}"));
+			}
+			if needed {
+				result?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Move this process into the `pi-host` leaf below `parent`.
+	fn move_to_leaf(parent: &Path) -> io::Result<()> {
+		let leaf = parent.join(HOST_LEAF);
+		match fs::create_dir(&leaf) {
+			Ok(()) => {},
+			Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {},
+			Err(err) => return Err(err),
+		}
+		write(&leaf, "cgroup.procs", &std::process::id().to_string())
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+	use std::{io, process::Command};
+
+	use super::{ShellResourceLimits, ShellResourceUsage};
+	use crate::ps;
+
+	pub enum Cgroup {}
+
+	impl Cgroup {
+		pub fn create(_limits: &ShellResourceLimits, _required: &[&str]) -> io::Result<Self> {
+			Err(io::Error::new(io::ErrorKind::Unsupported, "cgroups are only supported on Linux"))
+		}
+
+		pub const fn before_spawn(&self, _cmd: &mut Command) {
+			match *self {}
+		}
+
+		pub const fn cpu_time(&self) -> Option<ps::CpuTime> {
+			match *self {}
+		}
+
+		pub const fn oom_kills(&self) -> Option<u64> {
+			match *self {}
+		}
+
+		pub const fn usage(&self) -> ShellResourceUsage {
+			match *self {}
+		}
+
+		pub const fn kill(&self) {
+			match *self {}
+		}
+	}
+}
+
+/// Create a cgroup for an execution with `limits`, or `None` when it needs
+/// none.
+///
+/// # Errors
+/// Returns an error when cgroup limits are set but cannot be enforced.
+pub fn create(limits: Option<&ShellResourceLimits>) -> Result<Option<Cgroup>> {
+	let Some(limits) = limits else {
+		return Ok(None);
+	};
+	let required = limits.cgroup_controllers();
+	if required.is_empty() && !limits.cgroup.unwrap_or(false) {
+		return Ok(None);
+	}
+	match Cgroup::create(limits, &required) {
+		Ok(cgroup) => Ok(Some(cgroup)),
+		// Without limits the cgroup only improves accounting.
+		Err(_) if required.is_empty() => Ok(None),
+		Err(err) => Err(Error::from_reason(format!("Failed to create cgroup: {err}"))),
+	}
+}
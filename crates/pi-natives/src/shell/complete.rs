@@ -0,0 +1,147 @@
+//! Command-line completion.
+//!
+//! # Overview
+//! `completeCommandLine` completes the word under the cursor with brush's
+//! completion engine in a fresh session: commands on `PATH`, builtins,
+//! functions, aliases and keywords in command position, and paths elsewhere.
+//! Specs registered with `complete`, for instance by a sourced snapshot, add
+//! flags and other candidates for their commands. Command substitutions in
+//! the word being completed are not run.
+//!
+//! Offsets are in UTF-16 code units, as JavaScript indexes strings.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { start, end, candidates } = await native.completeCommandLine("git st", undefined, { cwd });
+//! ```
+
+use std::collections::HashMap;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use super::{ShellConfig, create_session};
+use crate::task;
+
+/// Characters escaped with a backslash in completed file names.
+const SPECIAL: &str = " \t\n\"'\\$`&|;()<>*?[]{}!#";
+
+/// Options for `completeCommandLine`.
+#[napi(object)]
+#[derive(Default)]
+pub struct CompleteCommandLineOptions {
+	/// Directory relative paths complete in (default: the working directory).
+	pub cwd:           Option<String>,
+	/// Variables to set on top of the process environment.
+	pub env:           Option<HashMap<String, String>>,
+	/// Shell snapshot to source first, for its functions, aliases and
+	/// completion specs.
+	#[napi(js_name = "snapshotPath")]
+	pub snapshot_path: Option<String>,
+}
+
+/// Completions for the word under the cursor.
+#[napi(object)]
+pub struct CommandLineCompletions {
+	/// Offset of the text the candidates replace.
+	pub start:      u32,
+	/// Offset where the replaced text ends: the cursor.
+	pub end:        u32,
+	/// Replacements; file names are escaped for the shell and directories
+	/// end in `/`.
+	pub candidates: Vec<String>,
+}
+
+/// Byte offset in `line` of the UTF-16 offset `offset`, clamped to the line.
+fn byte_offset(line: &str, offset: u32) -> usize {
+	let mut units = 0;
+	for (index, char) in line.char_indices() {
+		if units >= offset as usize {
+			return index;
+		}
+		units += char.len_utf16();
+	}
+	line.len()
+}
+
+fn utf16_offset(line: &str, byte: usize) -> u32 {
+	line
+		.get(..byte)
+		.map_or(0, |prefix| prefix.encode_utf16().count() as u32)
+}
+
+fn escape(name: &str) -> String {
+	let mut out = String::with_capacity(name.len());
+	for char in name.chars() {
+		if SPECIAL.contains(char) {
+			out.push('\\');
+		}
+		out.push(char);
+	}
+	out
+}
+
+/// Complete the word before `cursor` (default: the end of `line`).
+///
+/// # Errors
+/// Returns an error if the session cannot start, `cwd` does not exist, or
+/// completion fails.
+#[napi(js_name = "completeCommandLine")]
+pub fn complete_command_line(
+	env: &Env,
+	line: String,
+	cursor: Option<u32>,
+	options: Option<CompleteCommandLineOptions>,
+) -> Result<PromiseRaw<'_, CommandLineCompletions>> {
+	let options = options.unwrap_or_default();
+	task::future(env, "shell.complete", async move {
+		let config = ShellConfig {
+			session_env:    options.env,
+			snapshot_path:  options.snapshot_path,
+			builtin_policy: None,
+		};
+		let mut session = create_session(&config).await?;
+		let shell = &mut session.shell;
+		if let Some(cwd) = options.cwd.as_deref() {
+			shell
+				.set_working_dir(cwd)
+				.map_err(|err| Error::from_reason(format!("Failed to set cwd: {err}")))?;
+		}
+		let position = cursor.map_or(line.len(), |cursor| byte_offset(&line, cursor));
+		let completions = shell
+			.complete(&line, position)
+			.await
+			.map_err(|err| Error::from_reason(format!("Failed to complete: {err}")))?;
+
+		let start = completions.insertion_index.min(position);
+		// Names typed inside an open quote are completed as they are.
+		let quoted = ['\'', '"']
+			.iter()
+			.any(|&quote| line[..position].matches(quote).count() % 2 == 1);
+		let filenames = completions.options.treat_as_filenames;
+		let candidates = completions
+			.candidates
+			.into_iter()
+			.map(|candidate| {
+				if !filenames {
+					return candidate;
+				}
+				let is_dir = !candidate.ends_with('/') && shell.absolute_path(&candidate).is_dir();
+				let mut candidate = if quoted || completions.options.no_autoquote_filenames {
+					candidate
+				} else {
+					escape(&candidate)
+				};
+				if is_dir {
+					candidate.push('/');
+				}
+				candidate
+			})
+			.collect();
+		Ok(CommandLineCompletions {
+			start: utf16_offset(&line, start),
+			end: utf16_offset(&line, position),
+			candidates,
+		})
+	})
+}
@@ -0,0 +1,903 @@
+//! Detached executions that keep running after the call, and the process,
+//! that started them.
+//!
+//! `executeShellDetached` starts a command under `/bin/sh` (not the embedded
+//! shell, which lives in this process) in a session of its own, records it in
+//! the session store and returns at once. A small supervisor process writes
+//! the command's output to a log file keeping the last `maxLogBytes`, and the
+//! exit status once the command and every process holding its output have
+//! exited. `attachExecution` replays and follows that log and `stopExecution`
+//! terminates the command, from any later call or after a restart.
+//!
+//! # Log Format
+//! A header of six little-endian 64-bit fields: magic, ring capacity, bytes
+//! written so far, bytes being written (set before a write, so readers can
+//! tell which old bytes it may have overwritten), the command and supervisor
+//! pids (32 bits each), and the raw wait status (`i64::MIN` while running).
+//! The ring follows, with byte `n` of the output at `n % capacity`.
+//!
+//! # Platform Implementation
+//! - **Unix**: the command is double-forked into a new session. The supervisor
+//!   only makes async-signal-safe calls, as it runs in a fork of this
+//!   multi-threaded process without exec, and closes every descriptor it
+//!   inherited but its pipes and the log
+//! - **Windows**: not supported yet; `executeShellDetached` fails
+
+use std::{
+	collections::HashMap,
+	ffi::OsString,
+	fs::{self, File},
+	io::{self, Read as _, Seek as _, SeekFrom, Write as _},
+	path::{Path, PathBuf},
+	sync::atomic::{AtomicU32, Ordering},
+	time::Duration,
+};
+
+use napi::{
+	bindgen_prelude::*,
+	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+	tokio::{self, time},
+};
+use napi_derive::napi;
+use rusqlite::{Connection, OptionalExtension as _, params};
+
+use super::{exit::ShellTermination, redact::Redactor};
+use crate::{ps, store, task};
+
+const DEFAULT_MAX_LOG_BYTES: u32 = 1024 * 1024;
+const MIN_LOG_BYTES: u32 = 4096;
+const DEFAULT_STOP_GRACE_MS: u32 = 2000;
+/// How often an attached reader checks the log.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long `stopExecution` waits for the supervisor to record the status.
+const STATUS_WAIT: Duration = Duration::from_secs(1);
+
+const MAGIC: u64 = u64::from_le_bytes(*b"PIRING01");
+const HEADER_LEN: u64 = 48;
+const CAPACITY_AT: u64 = 8;
+const WRITTEN_AT: u64 = 16;
+const WRITING_AT: u64 = 24;
+const PIDS_AT: u64 = 32;
+const STATUS_AT: u64 = 40;
+const RUNNING: i64 = i64::MIN;
+
+static NEXT_LOG_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Options for `executeShellDetached`.
+#[napi(object)]
+pub struct DetachedShellOptions {
+	/// Command run with `/bin/sh -c`.
+	pub command:       String,
+	/// Working directory (default: this process's).
+	pub cwd:           Option<String>,
+	/// Variables added to this process's environment.
+	pub env:           Option<HashMap<String, String>>,
+	/// Output kept in the log; older output is dropped (default: 1 MiB).
+	#[napi(js_name = "maxLogBytes")]
+	pub max_log_bytes: Option<u32>,
+	/// Log file (default: `detached/` next to the session database).
+	#[napi(js_name = "logPath")]
+	pub log_path:      Option<String>,
+	/// Session database recording the execution (default: the session store,
+	/// as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:       Option<String>,
+}
+
+/// A detached execution and its state.
+#[napi(object)]
+pub struct DetachedExecution {
+	/// Id to pass to `attachExecution` and `stopExecution`.
+	pub id:           i64,
+	/// Command being executed.
+	pub command:      String,
+	/// Working directory, when one was given.
+	pub cwd:          Option<String>,
+	/// Process id of the shell running the command.
+	pub pid:          i32,
+	/// Log file holding the output.
+	#[napi(js_name = "logPath")]
+	pub log_path:     String,
+	/// Start of the execution, in milliseconds since the Unix epoch.
+	#[napi(js_name = "startedAt")]
+	pub started_at:   f64,
+	/// Whether the command (or a process holding its output) still runs.
+	pub running:      bool,
+	/// Exit code, once the command exited normally.
+	#[napi(js_name = "exitCode")]
+	pub exit_code:    Option<i32>,
+	/// The signal that ended the command.
+	pub termination:  Option<ShellTermination>,
+	/// Output bytes written so far, including those dropped from the log.
+	#[napi(js_name = "outputBytes")]
+	pub output_bytes: f64,
+}
+
+/// Options for `attachExecution`.
+#[napi(object)]
+pub struct AttachExecutionOptions<'env> {
+	/// Keep streaming new output until the command ends (default: true);
+	/// when false, only the logged output is replayed.
+	pub follow:  Option<bool>,
+	/// Abort signal for detaching; the command keeps running.
+	pub signal:  Option<Unknown<'env>>,
+	/// Session database (default: the session store, as in
+	/// `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path: Option<String>,
+}
+
+/// Options for `stopExecution`.
+#[napi(object)]
+#[derive(Default)]
+pub struct StopExecutionOptions {
+	/// Milliseconds between SIGTERM and SIGKILL (default: 2000).
+	#[napi(js_name = "graceMs")]
+	pub grace_ms: Option<u32>,
+	/// Also delete the record and the log (default: false).
+	pub remove:   Option<bool>,
+	/// Session database (default: the session store, as in
+	/// `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:  Option<String>,
+}
+
+/// A row of `detached_executions`.
+struct Record {
+	id:         i64,
+	command:    String,
+	cwd:        Option<String>,
+	pid:        i32,
+	log_path:   String,
+	started_at: i64,
+}
+
+/// The log header fields readers need.
+struct Header {
+	capacity:       u64,
+	written:        u64,
+	writing:        u64,
+	supervisor_pid: i32,
+	status:         i64,
+}
+
+impl Header {
+	fn read(file: &mut File) -> io::Result<Self> {
+		let mut buf = [0; HEADER_LEN as usize];
+		file.seek(SeekFrom::Start(0))?;
+		file.read_exact(&mut buf)?;
+		let field = |at: u64| {
+			let at = at as usize;
+			u64::from_le_bytes(buf[at..at + 8].try_into().expect("8-byte field"))
+		};
+		if field(0) != MAGIC || field(CAPACITY_AT) == 0 {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "not a detached execution log"));
+		}
+		let pids = field(PIDS_AT);
+		Ok(Self {
+			capacity:       field(CAPACITY_AT),
+			written:        field(WRITTEN_AT),
+			writing:        field(WRITING_AT),
+			supervisor_pid: (pids >> 32) as i32,
+			status:         field(STATUS_AT) as i64,
+		})
+	}
+
+	/// Whether the supervisor still waits for the command. A supervisor that
+	/// died without recording the status counts as ended.
+	fn running(&self) -> bool {
+		self.status == RUNNING && platform::alive(self.supervisor_pid)
+	}
+}
+
+/// Follows the output in a log, from the oldest byte still kept.
+struct LogReader {
+	file: File,
+	pos:  u64,
+}
+
+impl LogReader {
+	/// Output written since the last call, and the header it was read with.
+	fn read_new(&mut self) -> io::Result<(Vec<u8>, Header)> {
+		let header = Header::read(&mut self.file)?;
+		let capacity = header.capacity;
+		let start = self.pos.max(header.written.saturating_sub(capacity));
+		let mut out = vec![0; (header.written - start.min(header.written)) as usize];
+		let split = ((capacity - start % capacity) as usize).min(out.len());
+		let (first, second) = out.split_at_mut(split);
+		self
+			.file
+			.seek(SeekFrom::Start(HEADER_LEN + start % capacity))?;
+		self.file.read_exact(first)?;
+		if !second.is_empty() {
+			self.file.seek(SeekFrom::Start(HEADER_LEN))?;
+			self.file.read_exact(second)?;
+		}
+		// Drop what a write in progress may have overwritten meanwhile.
+		let writing = Header::read(&mut self.file)?.writing;
+		let overwritten = writing.saturating_sub(capacity).saturating_sub(start);
+		out.drain(..(overwritten as usize).min(out.len()));
+		self.pos = header.written;
+		Ok((out, header))
+	}
+}
+
+fn log_error(path: &str, err: &io::Error) -> Error {
+	Error::from_reason(format!("Failed to read detached execution log {path}: {err}"))
+}
+
+/// `record` with the state its log reports.
+fn describe(record: Record) -> DetachedExecution {
+	let header = File::open(&record.log_path)
+		.and_then(|mut file| Header::read(&mut file))
+		.ok();
+	let status = header
+		.as_ref()
+		.filter(|header| header.status != RUNNING)
+		.map(|header| header.status as i32);
+	let (exit_code, termination) = platform::exit_status(record.pid, status);
+	DetachedExecution {
+		id: record.id,
+		command: record.command,
+		cwd: record.cwd,
+		pid: record.pid,
+		log_path: record.log_path,
+		started_at: record.started_at as f64,
+		running: header.as_ref().is_some_and(Header::running),
+		exit_code,
+		termination,
+		output_bytes: header.map_or(0.0, |header| header.written as f64),
+	}
+}
+
+fn record_from_row(row: &rusqlite::Row) -> rusqlite::Result<Record> {
+	Ok(Record {
+		id:         row.get(0)?,
+		command:    row.get(1)?,
+		cwd:        row.get(2)?,
+		pid:        row.get(3)?,
+		log_path:   row.get(4)?,
+		started_at: row.get(5)?,
+	})
+}
+
+const SELECT_RECORD: &str =
+	"SELECT id, command, cwd, pid, log_path, started_at FROM detached_executions";
+
+fn find_record(conn: &Connection, id: i64) -> rusqlite::Result<Option<Record>> {
+	conn
+		.query_row(&format!("{SELECT_RECORD} WHERE id = ?1"), [id], record_from_row)
+		.optional()
+}
+
+fn load_record(db_path: Option<String>, id: i64) -> Result<Option<Record>> {
+	let store = store::open_store(db_path)?;
+	let conn = store.lock();
+	find_record(&conn, id).map_err(store::sql_error)
+}
+
+async fn load_record_async(db_path: Option<String>, id: i64) -> Result<Option<Record>> {
+	tokio::task::spawn_blocking(move || load_record(db_path, id))
+		.await
+		.map_err(|err| Error::from_reason(format!("Store lookup failed: {err}")))?
+}
+
+/// Default log path: `detached/` next to the session database.
+fn default_log_path(db_path: Option<String>, started_at: i64) -> Result<PathBuf> {
+	let db_path = store::resolve_db_path(db_path)?;
+	let dir = db_path
+		.parent()
+		.map_or_else(|| PathBuf::from("detached"), |parent| parent.join("detached"));
+	let id = NEXT_LOG_ID.fetch_add(1, Ordering::Relaxed);
+	Ok(dir.join(format!("{started_at}-{}-{id}.log", std::process::id())))
+}
+
+/// Create the log at `path` with an empty ring of `capacity` bytes.
+fn create_log(path: &Path, capacity: u64) -> io::Result<File> {
+	if let Some(parent) = path
+		.parent()
+		.filter(|parent| !parent.as_os_str().is_empty())
+	{
+		fs::create_dir_all(parent)?;
+	}
+	let mut file = File::options()
+		.read(true)
+		.write(true)
+		.create(true)
+		.truncate(true)
+		.open(path)?;
+	let mut header = Vec::with_capacity(HEADER_LEN as usize);
+	for field in [MAGIC, capacity, 0, 0, 0, RUNNING as u64] {
+		header.extend_from_slice(&field.to_le_bytes());
+	}
+	file.write_all(&header)?;
+	Ok(file)
+}
+
+fn execute_detached_sync(options: DetachedShellOptions) -> Result<DetachedExecution> {
+	let started_at = store::now_ms();
+	let capacity = u64::from(
+		options
+			.max_log_bytes
+			.unwrap_or(DEFAULT_MAX_LOG_BYTES)
+			.max(MIN_LOG_BYTES),
+	);
+	if let Some(cwd) = &options.cwd
+		&& !Path::new(cwd).is_dir()
+	{
+		return Err(Error::from_reason(format!("Working directory does not exist: {cwd}")));
+	}
+	let log_path = match options.log_path {
+		Some(path) => PathBuf::from(path),
+		None => default_log_path(options.db_path.clone(), started_at)?,
+	};
+	let store = store::open_store(options.db_path)?;
+
+	let mut env: HashMap<OsString, OsString> = std::env::vars_os().collect();
+	for (key, value) in options.env.unwrap_or_default() {
+		env.insert(key.into(), value.into());
+	}
+	let log = create_log(&log_path, capacity).map_err(|err| {
+		Error::from_reason(format!("Failed to create {}: {err}", log_path.display()))
+	})?;
+	let pid = match platform::spawn(&options.command, options.cwd.as_deref(), &env, &log) {
+		Ok(pid) => pid,
+		Err(err) => {
+			let _ = fs::remove_file(&log_path);
+			return Err(Error::from_reason(format!("Failed to start detached command: {err}")));
+		},
+	};
+
+	let record = Record {
+		id: 0,
+		command: options.command,
+		cwd: options.cwd,
+		pid,
+		log_path: log_path.to_string_lossy().into_owned(),
+		started_at,
+	};
+	let inserted = {
+		let conn = store.lock();
+		conn
+			.execute(
+				"INSERT INTO detached_executions (command, cwd, pid, log_path, started_at)
+				 VALUES (?1, ?2, ?3, ?4, ?5)",
+				params![record.command, record.cwd, record.pid, record.log_path, record.started_at],
+			)
+			.map(|_| conn.last_insert_rowid())
+	};
+	match inserted {
+		Ok(id) => Ok(describe(Record { id, ..record })),
+		Err(err) => {
+			// Nothing could find the command again; do not leave it running.
+			ps::kill_tree(pid, ps::SIGKILL);
+			Err(store::sql_error(err))
+		},
+	}
+}
+
+/// Start a command that keeps running after this call and this process,
+/// writing its output to a log file; see `attachExecution` and
+/// `stopExecution`.
+///
+/// # Errors
+/// Returns an error for a missing working directory, when the log or the
+/// store cannot be written, when the command cannot be started, and on
+/// Windows.
+#[napi(js_name = "executeShellDetached")]
+pub fn execute_shell_detached(options: DetachedShellOptions) -> task::Async<DetachedExecution> {
+	task::blocking("shell.detached", (), move |_| execute_detached_sync(options))
+}
+
+/// List detached executions, oldest first, with their state.
+///
+/// # Errors
+/// Returns an error if the database cannot be opened or read.
+#[napi(js_name = "listDetachedExecutions")]
+pub fn list_detached_executions(
+	options: Option<store::SessionStoreOptions>,
+) -> task::Async<Vec<DetachedExecution>> {
+	let db_path = options.unwrap_or_default().db_path;
+	task::blocking("shell.detached_list", (), move |_| {
+		let store = store::open_store(db_path)?;
+		let records = {
+			let conn = store.lock();
+			let mut stmt = conn
+				.prepare(&format!("{SELECT_RECORD} ORDER BY id"))
+				.map_err(store::sql_error)?;
+			stmt
+				.query_map([], record_from_row)
+				.and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>)
+				.map_err(store::sql_error)?
+		};
+		Ok(records.into_iter().map(describe).collect())
+	})
+}
+
+/// Decodes log bytes for the callback, keeping characters split across
+/// reads together and applying the redaction rules.
+struct Emitter {
+	callback: ThreadsafeFunction<String>,
+	redactor: Option<Redactor>,
+	partial:  Vec<u8>,
+}
+
+impl Emitter {
+	fn push(&mut self, bytes: &[u8]) {
+		self.partial.extend_from_slice(bytes);
+		let end = match std::str::from_utf8(&self.partial) {
+			Ok(_) => self.partial.len(),
+			Err(err) if err.error_len().is_none() => err.valid_up_to(),
+			Err(_) => self.partial.len(),
+		};
+		let rest = self.partial.split_off(end);
+		let ready = std::mem::replace(&mut self.partial, rest);
+		self.emit(&ready);
+	}
+
+	/// Emit the partial line held back for redaction.
+	fn release(&mut self) {
+		if let Some(redactor) = &mut self.redactor {
+			let out = redactor.flush();
+			self.send(&out);
+		}
+	}
+
+	/// Emit everything held back.
+	fn finish(&mut self) {
+		let partial = std::mem::take(&mut self.partial);
+		self.emit(&partial);
+		self.release();
+	}
+
+	fn emit(&mut self, bytes: &[u8]) {
+		match &mut self.redactor {
+			Some(redactor) => {
+				let out = redactor.push(bytes);
+				self.send(&out);
+			},
+			None => self.send(bytes),
+		}
+	}
+
+	fn send(&self, bytes: &[u8]) {
+		if !bytes.is_empty() {
+			let text = String::from_utf8_lossy(bytes).into_owned();
+			self
+				.callback
+				.call(Ok(text), ThreadsafeFunctionCallMode::NonBlocking);
+		}
+	}
+}
+
+/// Replay the logged output of a detached execution to `onChunk` and, with
+/// `follow`, stream new output until the command ends. Output dropped from
+/// the log while following is skipped.
+///
+/// Resolves with the execution's state once streaming ends.
+///
+/// # Errors
+/// Returns an error for an unknown id, an unreadable log, and on abort
+/// (`Aborted: ...`), which leaves the command running.
+#[napi(js_name = "attachExecution")]
+pub fn attach_execution<'env>(
+	env: &'env Env,
+	id: i64,
+	#[napi(ts_arg_type = "(error: Error | null, chunk: string) => void")]
+	on_chunk: ThreadsafeFunction<String>,
+	options: Option<AttachExecutionOptions<'env>>,
+) -> Result<PromiseRaw<'env, DetachedExecution>> {
+	let (follow, signal, db_path) = match options {
+		Some(options) => (options.follow.unwrap_or(true), options.signal, options.db_path),
+		None => (true, None, None),
+	};
+	let ct = task::CancelToken::new(None, signal);
+	task::future(env, "shell.attach", async move {
+		let record = load_record_async(db_path, id)
+			.await?
+			.ok_or_else(|| Error::from_reason(format!("Unknown detached execution: {id}")))?;
+		let file = File::open(&record.log_path).map_err(|err| log_error(&record.log_path, &err))?;
+		let mut reader = LogReader { file, pos: 0 };
+		let mut emitter =
+			Emitter { callback: on_chunk, redactor: Redactor::current(), partial: Vec::new() };
+		loop {
+			let (bytes, header) = reader
+				.read_new()
+				.map_err(|err| log_error(&record.log_path, &err))?;
+			if bytes.is_empty() {
+				emitter.release();
+			}
+			emitter.push(&bytes);
+			if !follow || !header.running() {
+				break;
+			}
+			tokio::select! {
+				() = time::sleep(POLL_INTERVAL) => {},
+				reason = ct.wait() => {
+					emitter.finish();
+					return Err(Error::from_reason(format!("Aborted: {reason:?}")));
+				},
+			}
+		}
+		emitter.finish();
+		Ok(describe(record))
+	})
+}
+
+/// Terminate a detached execution's process tree: SIGTERM, then SIGKILL for
+/// processes still running after `graceMs`. With `remove`, its record and log
+/// are deleted as well.
+///
+/// Resolves with the final state, or `null` for an unknown id.
+///
+/// # Errors
+/// Returns an error if the database cannot be opened or written.
+#[napi(js_name = "stopExecution")]
+pub fn stop_execution(
+	env: &Env,
+	id: i64,
+	options: Option<StopExecutionOptions>,
+) -> Result<PromiseRaw<'_, Option<DetachedExecution>>> {
+	let options = options.unwrap_or_default();
+	let grace = Duration::from_millis(options.grace_ms.unwrap_or(DEFAULT_STOP_GRACE_MS).into());
+	task::future(env, "shell.stop_detached", async move {
+		let Some(record) = load_record_async(options.db_path.clone(), id).await? else {
+			return Ok(None);
+		};
+		if is_running(&record) {
+			ps::terminate_tree(record.pid, grace).await;
+			// Give the supervisor time to record the exit status.
+			let waited = time::Instant::now();
+			while is_running(&record) && waited.elapsed() < STATUS_WAIT {
+				time::sleep(POLL_INTERVAL / 4).await;
+			}
+		}
+		let result = describe(record);
+		if options.remove.unwrap_or(false) {
+			let db_path = options.db_path;
+			let log_path = result.log_path.clone();
+			tokio::task::spawn_blocking(move || {
+				let store = store::open_store(db_path)?;
+				store
+					.lock()
+					.execute("DELETE FROM detached_executions WHERE id = ?1", [id])
+					.map_err(store::sql_error)?;
+				let _ = fs::remove_file(log_path);
+				Ok::<_, Error>(())
+			})
+			.await
+			.map_err(|err| Error::from_reason(format!("Store update failed: {err}")))??;
+		}
+		Ok(Some(result))
+	})
+}
+
+fn is_running(record: &Record) -> bool {
+	File::open(&record.log_path)
+		.and_then(|mut file| Header::read(&mut file))
+		.is_ok_and(|header| header.running())
+}
+
+#[cfg(unix)]
+mod platform {
+	use std::{
+		collections::HashMap,
+		ffi::{CString, OsString},
+		fs::File,
+		io::{self, Read as _},
+		os::{
+			fd::AsRawFd as _,
+			unix::{ffi::OsStrExt as _, process::ExitStatusExt as _},
+		},
+		process::ExitStatus,
+		ptr,
+	};
+
+	use super::{
+		CAPACITY_AT, HEADER_LEN, PIDS_AT, STATUS_AT, ShellTermination, WRITING_AT, WRITTEN_AT,
+	};
+
+	/// Highest descriptor closed one by one where `close_range` is missing.
+	const MAX_CLOSE_FD: i32 = 65_536;
+
+	fn cstring(bytes: &[u8]) -> io::Result<CString> {
+		CString::new(bytes)
+			.map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "argument contains a NUL byte"))
+	}
+
+	/// Start `command` detached, with its output going through a supervisor
+	/// into `log`, and return the pid of its shell.
+	pub fn spawn(
+		command: &str,
+		cwd: Option<&str>,
+		env: &HashMap<OsString, OsString>,
+		log: &File,
+	) -> io::Result<i32> {
+		let args = [cstring(b"sh")?, cstring(b"-c")?, cstring(command.as_bytes())?];
+		let argv: Vec<*const libc::c_char> = args
+			.iter()
+			.map(|arg| arg.as_ptr())
+			.chain([ptr::null()])
+			.collect();
+		let vars = env
+			.iter()
+			.map(|(key, value)| {
+				let mut var = key.as_bytes().to_vec();
+				var.push(b'=');
+				var.extend_from_slice(value.as_bytes());
+				cstring(&var)
+			})
+			.collect::<io::Result<Vec<_>>>()?;
+		let envp: Vec<*const libc::c_char> = vars
+			.iter()
+			.map(|var| var.as_ptr())
+			.chain([ptr::null()])
+			.collect();
+		let cwd = cwd.map(|cwd| cstring(cwd.as_bytes())).transpose()?;
+		let (out_read, out_write) = os_pipe::pipe()?;
+		let (mut ready_read, ready_write) = os_pipe::pipe()?;
+		let null = File::options().read(true).open("/dev/null")?;
+		let child = Child {
+			out_read:  out_read.as_raw_fd(),
+			out_write: out_write.as_raw_fd(),
+			log:       log.as_raw_fd(),
+			ready:     ready_write.as_raw_fd(),
+			null:      null.as_raw_fd(),
+			shell:     c"/bin/sh".as_ptr(),
+			argv:      argv.as_ptr(),
+			envp:      envp.as_ptr(),
+			cwd:       cwd.as_ref().map_or(ptr::null(), |cwd| cwd.as_ptr()),
+		};
+
+		// SAFETY: the forked child only calls async-signal-safe functions (see
+		// `Child::detach`) on memory prepared above, which stays alive in the
+		// child's copy of the address space.
+		let pid = unsafe { libc::fork() };
+		if pid < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		if pid == 0 {
+			// SAFETY: in the forked child.
+			unsafe { child.detach() }
+		}
+		drop(ready_write);
+		drop(out_write);
+		let mut status = 0;
+		// SAFETY: reaps the intermediate child, which exits right away.
+		while unsafe { libc::waitpid(pid, &raw mut status, 0) } < 0 {
+			if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+				break;
+			}
+		}
+		let mut reply = [0; 4];
+		ready_read
+			.read_exact(&mut reply)
+			.map_err(|_| io::Error::other("the supervisor exited before starting the command"))?;
+		match i32::from_ne_bytes(reply) {
+			pid if pid > 0 => Ok(pid),
+			errno => Err(io::Error::from_raw_os_error(-errno)),
+		}
+	}
+
+	/// Everything the forked processes need, prepared before forking.
+	///
+	/// The descriptors keep the numbers they have here: fork handlers of the
+	/// host (libuv's among them) reopen descriptors of their own at the lowest
+	/// free numbers in every forked child, so fixed low numbers are not safe.
+	struct Child {
+		out_read:  i32,
+		out_write: i32,
+		log:       i32,
+		/// Reports the command pid, or a negated errno, to the parent.
+		ready:     i32,
+		null:      i32,
+		shell:     *const libc::c_char,
+		argv:      *const *const libc::c_char,
+		envp:      *const *const libc::c_char,
+		cwd:       *const libc::c_char,
+	}
+
+	impl Child {
+		/// Start a new session and fork the supervisor from it, so that it
+		/// is not a session leader and not our child.
+		unsafe fn detach(&self) -> ! {
+			// SAFETY: setsid, fork, write and _exit are async-signal-safe.
+			unsafe {
+				libc::setsid();
+				let pid = libc::fork();
+				if pid == 0 {
+					self.supervise();
+				}
+				if pid < 0 {
+					self.report(-errno());
+				}
+				libc::_exit(0)
+			}
+		}
+
+		/// Run the command and copy its output into the log until every
+		/// writer is gone, then record its wait status.
+		unsafe fn supervise(&self) -> ! {
+			// SAFETY: only async-signal-safe calls on descriptors and memory
+			// this process owns.
+			unsafe {
+				// Descriptors of the host (sockets above all) must not stay
+				// open for as long as the command runs.
+				let mut keep = [self.out_read, self.out_write, self.log, self.ready, self.null];
+				keep.sort_unstable();
+				let mut first = 3;
+				for fd in keep {
+					close_range(first, fd - 1);
+					first = fd + 1;
+				}
+				close_range(first, MAX_CLOSE_FD);
+				for fd in 0..3 {
+					libc::dup2(self.null, fd);
+				}
+				libc::signal(libc::SIGPIPE, libc::SIG_IGN);
+				libc::signal(libc::SIGHUP, libc::SIG_IGN);
+
+				let command = libc::fork();
+				if command == 0 {
+					self.exec();
+				}
+				libc::close(self.out_write);
+				libc::close(self.null);
+				if command < 0 {
+					self.report(-errno());
+					libc::_exit(1);
+				}
+				let pids = (u64::from(libc::getpid() as u32) << 32) | u64::from(command as u32);
+				write_at(self.log, &pids.to_le_bytes(), PIDS_AT);
+				self.report(command);
+				libc::close(self.ready);
+
+				let mut capacity = [0; 8];
+				libc::pread(self.log, capacity.as_mut_ptr().cast(), 8, CAPACITY_AT as libc::off_t);
+				let capacity = u64::from_le_bytes(capacity).max(1);
+				let mut written = 0;
+				let mut buf = [0u8; 16 * 1024];
+				loop {
+					let len = libc::read(self.out_read, buf.as_mut_ptr().cast(), buf.len());
+					if len < 0 && errno() == libc::EINTR {
+						continue;
+					}
+					if len <= 0 {
+						break;
+					}
+					append(self.log, capacity, &mut written, &buf[..len as usize]);
+				}
+
+				let mut status = 0;
+				while libc::waitpid(command, &raw mut status, 0) < 0 && errno() == libc::EINTR {}
+				write_at(self.log, &i64::from(status).to_le_bytes(), STATUS_AT);
+				libc::_exit(0)
+			}
+		}
+
+		/// Replace the forked process with the command.
+		unsafe fn exec(&self) -> ! {
+			// SAFETY: only async-signal-safe calls before execve.
+			unsafe {
+				libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+				libc::signal(libc::SIGHUP, libc::SIG_DFL);
+				let mut set = std::mem::zeroed();
+				libc::sigemptyset(&raw mut set);
+				libc::sigprocmask(libc::SIG_SETMASK, &raw const set, ptr::null_mut());
+				libc::dup2(self.out_write, 1);
+				libc::dup2(self.out_write, 2);
+				close_range(3, MAX_CLOSE_FD);
+				if !self.cwd.is_null() && libc::chdir(self.cwd) < 0 {
+					libc::_exit(127);
+				}
+				libc::execve(self.shell, self.argv, self.envp);
+				libc::_exit(127)
+			}
+		}
+
+		/// Send the command pid, or a negated errno, to the waiting parent.
+		unsafe fn report(&self, value: i32) {
+			// SAFETY: writes from a local buffer.
+			unsafe {
+				libc::write(self.ready, value.to_ne_bytes().as_ptr().cast(), 4);
+			}
+		}
+	}
+
+	fn errno() -> i32 {
+		io::Error::last_os_error().raw_os_error().unwrap_or(0)
+	}
+
+	unsafe fn write_at(fd: i32, bytes: &[u8], offset: u64) {
+		// SAFETY: writes from a borrowed buffer.
+		unsafe {
+			libc::pwrite(fd, bytes.as_ptr().cast(), bytes.len(), offset as libc::off_t);
+		}
+	}
+
+	/// Append `bytes` to the ring of `capacity` bytes in the log `fd`.
+	unsafe fn append(fd: i32, capacity: u64, written: &mut u64, mut bytes: &[u8]) {
+		// SAFETY: writes to the log only.
+		unsafe {
+			if bytes.len() as u64 > capacity {
+				let skip = bytes.len() - capacity as usize;
+				*written += skip as u64;
+				bytes = &bytes[skip..];
+			}
+			let end = *written + bytes.len() as u64;
+			write_at(fd, &end.to_le_bytes(), WRITING_AT);
+			let pos = *written % capacity;
+			let first = ((capacity - pos) as usize).min(bytes.len());
+			write_at(fd, &bytes[..first], HEADER_LEN + pos);
+			if first < bytes.len() {
+				write_at(fd, &bytes[first..], HEADER_LEN);
+			}
+			*written = end;
+			write_at(fd, &end.to_le_bytes(), WRITTEN_AT);
+		}
+	}
+
+	/// Close the descriptors from `first` through `last`.
+	unsafe fn close_range(first: i32, last: i32) {
+		if first > last {
+			return;
+		}
+		// SAFETY: closing descriptors has no memory effects.
+		unsafe {
+			#[cfg(target_os = "linux")]
+			if libc::syscall(libc::SYS_close_range, first as libc::c_uint, last as libc::c_uint, 0)
+				== 0
+			{
+				return;
+			}
+			for fd in first..=last {
+				libc::close(fd);
+			}
+		}
+	}
+
+	/// Whether the supervisor `pid` still runs.
+	pub fn alive(pid: i32) -> bool {
+		// SAFETY: signal 0 only checks for existence.
+		pid > 0 && (unsafe { libc::kill(pid, 0) } == 0 || errno() == libc::EPERM)
+	}
+
+	/// Exit code and termination for a raw wait `status`.
+	pub fn exit_status(pid: i32, status: Option<i32>) -> (Option<i32>, Option<ShellTermination>) {
+		let Some(status) = status else {
+			return (None, None);
+		};
+		let status = ExitStatus::from_raw(status);
+		(status.code(), ShellTermination::from_status(pid, &status))
+	}
+}
+
+#[cfg(not(unix))]
+mod platform {
+	use std::{collections::HashMap, ffi::OsString, fs::File, io};
+
+	use super::ShellTermination;
+
+	pub fn spawn(
+		_command: &str,
+		_cwd: Option<&str>,
+		_env: &HashMap<OsString, OsString>,
+		_log: &File,
+	) -> io::Result<i32> {
+		Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"detached executions are not supported on this platform",
+		))
+	}
+
+	pub const fn alive(_pid: i32) -> bool {
+		false
+	}
+
+	pub const fn exit_status(
+		_pid: i32,
+		_status: Option<i32>,
+	) -> (Option<i32>, Option<ShellTermination>) {
+		(None, None)
+	}
+}
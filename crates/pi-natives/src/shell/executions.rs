@@ -0,0 +1,298 @@
+//! Registry of running shell executions, for listing and cancelling them
+//! from outside the call that started them.
+//!
+//! Every `executeShell` call and `Shell.run` command is registered for as
+//! long as it runs, with the token that cancels it and the progress its
+//! output reader and spawn hooks report, including while it waits in the
+//! execution queue. The last `scrollbackBytes` of each execution's output
+//! are kept so `getExecutionOutput` can replay them, e.g. after the UI
+//! reconnects to a command that is still running.
+
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{
+		Arc, LazyLock, Weak,
+		atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+	},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use napi_derive::napi;
+use parking_lot::Mutex;
+
+use super::tracker::SpawnTracker;
+use crate::task;
+
+static EXECUTIONS: LazyLock<Mutex<HashMap<u32, Execution>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_EXECUTION_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Output kept per execution unless `scrollbackBytes` says otherwise.
+pub const DEFAULT_SCROLLBACK_BYTES: usize = 64 * 1024;
+
+/// A running shell execution, as reported by `listShellExecutions`.
+#[napi(object)]
+pub struct ShellExecutionInfo {
+	/// Id to pass to `cancelShellExecution` and `getExecutionOutput`.
+	pub id:           u32,
+	/// Command string being executed.
+	pub command:      String,
+	/// Start of the execution, in milliseconds since the Unix epoch.
+	#[napi(js_name = "startedAt")]
+	pub started_at:   f64,
+	/// Processes of the command that are still running.
+	pub pids:         Vec<i32>,
+	/// Output bytes the command has written so far, before ANSI stripping
+	/// and output limits.
+	#[napi(js_name = "outputBytes")]
+	pub output_bytes: i64,
+	/// Whether the execution is waiting for a slot under
+	/// `setMaxConcurrentExecutions` rather than running.
+	pub queued:       bool,
+}
+
+/// Options for `getExecutionOutput`.
+#[napi(object)]
+pub struct ExecutionOutputOptions {
+	/// Offset in the execution's output to start at (default: the oldest
+	/// byte still kept).
+	#[napi(js_name = "fromByte")]
+	pub from_byte: Option<i64>,
+	/// Return at most this many bytes (default: everything kept).
+	#[napi(js_name = "maxBytes")]
+	pub max_bytes: Option<u32>,
+}
+
+/// Output replayed by `getExecutionOutput`.
+#[napi(object)]
+pub struct ShellExecutionOutput {
+	/// Output from `start` to `end`, as streamed (after redaction, ANSI
+	/// stripping and output limits). Invalid UTF-8 is replaced.
+	pub text:        String,
+	/// Offset of the first returned byte. Greater than the requested
+	/// `fromByte` when that output no longer fits in the scrollback.
+	pub start:       i64,
+	/// Offset just past the last returned byte; pass it as `fromByte` to
+	/// continue.
+	pub end:         i64,
+	/// Bytes streamed so far.
+	#[napi(js_name = "totalBytes")]
+	pub total_bytes: i64,
+}
+
+/// The last `capacity` bytes of streamed output.
+struct Scrollback {
+	buf:      VecDeque<u8>,
+	capacity: usize,
+	/// Bytes recorded so far, including those dropped from `buf`.
+	total:    u64,
+}
+
+impl Default for Scrollback {
+	fn default() -> Self {
+		Self::new(DEFAULT_SCROLLBACK_BYTES)
+	}
+}
+
+impl Scrollback {
+	const fn new(capacity: usize) -> Self {
+		Self { buf: VecDeque::new(), capacity, total: 0 }
+	}
+
+	fn push(&mut self, bytes: &[u8]) {
+		self.total += bytes.len() as u64;
+		let bytes = &bytes[bytes.len().saturating_sub(self.capacity)..];
+		let overflow = (self.buf.len() + bytes.len()).saturating_sub(self.capacity);
+		self.buf.drain(..overflow);
+		self.buf.extend(bytes);
+	}
+
+	fn read(&self, from: Option<u64>, max: Option<usize>) -> ShellExecutionOutput {
+		let first = self.total - self.buf.len() as u64;
+		let mut start = from.unwrap_or(first).clamp(first, self.total) as usize - first as usize;
+		// Whole characters only: skip the rest of one cut off at the start,
+		// and hold back one cut off by `maxBytes`.
+		while start < self.buf.len() && is_continuation(self.buf[start]) {
+			start += 1;
+		}
+		let mut end = max.map_or(self.buf.len(), |max| self.buf.len().min(start + max));
+		if end < self.buf.len() {
+			end = char_boundary_before(&self.buf, start, end);
+		}
+		let bytes: Vec<u8> = self.buf.range(start..end).copied().collect();
+		ShellExecutionOutput {
+			text:        String::from_utf8_lossy(&bytes).into_owned(),
+			start:       (first + start as u64) as i64,
+			end:         (first + end as u64) as i64,
+			total_bytes: self.total as i64,
+		}
+	}
+}
+
+const fn is_continuation(byte: u8) -> bool {
+	byte & 0xc0 == 0x80
+}
+
+/// `end`, moved back to the start of a UTF-8 sequence it would cut.
+fn char_boundary_before(buf: &VecDeque<u8>, start: usize, end: usize) -> usize {
+	let mut lead = end;
+	while lead > start && end - lead < 4 && is_continuation(buf[lead - 1]) {
+		lead -= 1;
+	}
+	if lead == start {
+		return end;
+	}
+	let lead = lead - 1;
+	let len = match buf[lead] {
+		byte if byte >= 0xf0 => 4,
+		byte if byte >= 0xe0 => 3,
+		byte if byte >= 0xc0 => 2,
+		_ => 1,
+	};
+	if end - lead < len { lead } else { end }
+}
+
+/// Progress of an execution, updated while it runs.
+#[derive(Default)]
+pub struct ExecutionProgress {
+	/// Spawn hooks of the command currently running.
+	tracker:      Mutex<Weak<SpawnTracker>>,
+	output_bytes: AtomicU64,
+	queued:       AtomicBool,
+	scrollback:   Mutex<Scrollback>,
+}
+
+impl ExecutionProgress {
+	/// Progress keeping the last `bytes` of streamed output (none for 0).
+	pub fn with_scrollback(bytes: usize) -> Self {
+		Self { scrollback: Mutex::new(Scrollback::new(bytes)), ..Self::default() }
+	}
+
+	/// Report the processes spawned through `tracker`.
+	pub fn track(&self, tracker: &Arc<SpawnTracker>) {
+		*self.tracker.lock() = Arc::downgrade(tracker);
+	}
+
+	/// Record output written by the command.
+	pub fn record_output(&self, len: usize) {
+		self.output_bytes.fetch_add(len as u64, Ordering::Relaxed);
+	}
+
+	/// Output bytes read so far.
+	pub fn output_bytes(&self) -> u64 {
+		self.output_bytes.load(Ordering::Relaxed)
+	}
+
+	/// Record output as it was streamed, for `getExecutionOutput`.
+	pub fn record_emitted(&self, bytes: &[u8]) {
+		self.scrollback.lock().push(bytes);
+	}
+
+	/// Record whether the execution is waiting for a slot.
+	pub fn set_queued(&self, queued: bool) {
+		self.queued.store(queued, Ordering::Relaxed);
+	}
+}
+
+struct Execution {
+	command:    String,
+	started_at: f64,
+	abort:      task::AbortToken,
+	progress:   Arc<ExecutionProgress>,
+}
+
+/// Keeps an execution listed until dropped.
+pub struct Registration(u32);
+
+impl Registration {
+	/// Id of the execution, as listed by `listShellExecutions`.
+	pub const fn id(&self) -> u32 {
+		self.0
+	}
+}
+
+impl Drop for Registration {
+	fn drop(&mut self) {
+		EXECUTIONS.lock().remove(&self.0);
+	}
+}
+
+/// List `command` as running until the returned registration is dropped.
+pub fn register(
+	command: &str,
+	started_at: SystemTime,
+	abort: task::AbortToken,
+	progress: Arc<ExecutionProgress>,
+) -> Registration {
+	let id = NEXT_EXECUTION_ID.fetch_add(1, Ordering::Relaxed);
+	let started_at = started_at
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs_f64()
+		* 1000.0;
+	EXECUTIONS.lock().insert(id, Execution {
+		command: command.to_string(),
+		started_at,
+		abort,
+		progress,
+	});
+	Registration(id)
+}
+
+/// List the shell executions currently running, oldest first.
+#[napi(js_name = "listShellExecutions")]
+pub fn list_shell_executions() -> Vec<ShellExecutionInfo> {
+	let executions = EXECUTIONS.lock();
+	let mut list: Vec<ShellExecutionInfo> = executions
+		.iter()
+		.map(|(&id, execution)| ShellExecutionInfo {
+			id,
+			command: execution.command.clone(),
+			started_at: execution.started_at,
+			pids: execution
+				.progress
+				.tracker
+				.lock()
+				.upgrade()
+				.map_or_else(Vec::new, |tracker| tracker.live_pids()),
+			output_bytes: execution.progress.output_bytes.load(Ordering::Relaxed) as i64,
+			queued: execution.progress.queued.load(Ordering::Relaxed),
+		})
+		.collect();
+	list.sort_by_key(|execution| execution.id);
+	list
+}
+
+/// Cancel a running shell execution, as if its abort signal fired.
+///
+/// Returns false when no execution with `id` is running.
+#[napi(js_name = "cancelShellExecution")]
+pub fn cancel_shell_execution(id: u32) -> bool {
+	let Some(abort) = EXECUTIONS
+		.lock()
+		.get(&id)
+		.map(|execution| execution.abort.clone())
+	else {
+		return false;
+	};
+	abort.abort(task::AbortReason::Signal);
+	true
+}
+
+/// Read the scrollback of a running shell execution: the last
+/// `scrollbackBytes` of the output it streamed, with byte offsets for
+/// resuming.
+///
+/// Returns null when no execution with `id` is running.
+#[napi(js_name = "getExecutionOutput")]
+pub fn get_execution_output(
+	id: u32,
+	options: Option<ExecutionOutputOptions>,
+) -> Option<ShellExecutionOutput> {
+	let progress = EXECUTIONS.lock().get(&id)?.progress.clone();
+	let (from, max) = options.map_or((None, None), |options| {
+		(options.from_byte.map(|from| from.max(0) as u64), options.max_bytes.map(|max| max as usize))
+	});
+	let output = progress.scrollback.lock().read(from, max);
+	Some(output)
+}
@@ -0,0 +1,150 @@
+//! How a command's processes ended: the signal that killed them, core dumps,
+//! and kills by the Linux OOM killer.
+//!
+//! # Platform Implementation
+//! - **Unix**: signals and core dumps come from the wait status of each process
+//!   brush (or a native interpreter) spawned
+//! - **Linux**: OOM kills are counted from `oom_kill` in this process's memory
+//!   cgroup (`memory.events` on cgroup v2, `memory.oom_control` on v1), which
+//!   also counts kills in concurrent executions and other processes sharing the
+//!   cgroup; without a memory cgroup, the kernel log (`/dev/kmsg`, when
+//!   readable) is searched for the processes that died of `SIGKILL`
+//! - **Windows**: processes do not end by signals; nothing is reported
+
+use std::process::ExitStatus;
+
+use napi_derive::napi;
+
+/// The signal that ended a command.
+#[napi(object)]
+pub struct ShellTermination {
+	/// Process that the signal killed.
+	pub pid:         i32,
+	/// Signal number.
+	pub signal:      i32,
+	/// Signal name, like `SIGKILL` (`SIG<n>` for unknown signals).
+	#[napi(js_name = "signalName")]
+	pub signal_name: String,
+	/// Whether the process dumped core.
+	#[napi(js_name = "coreDumped")]
+	pub core_dumped: bool,
+}
+
+impl ShellTermination {
+	/// The termination `status` reports, if a signal ended the process.
+	#[cfg(unix)]
+	pub fn from_status(pid: i32, status: &ExitStatus) -> Option<Self> {
+		use std::os::unix::process::ExitStatusExt as _;
+		let signal = status.signal()?;
+		Some(Self {
+			pid,
+			signal,
+			signal_name: signal_name(signal),
+			core_dumped: status.core_dumped(),
+		})
+	}
+
+	#[cfg(not(unix))]
+	pub const fn from_status(_pid: i32, _status: &ExitStatus) -> Option<Self> {
+		None
+	}
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> String {
+	let name = match signal {
+		libc::SIGHUP => "SIGHUP",
+		libc::SIGINT => "SIGINT",
+		libc::SIGQUIT => "SIGQUIT",
+		libc::SIGILL => "SIGILL",
+		libc::SIGTRAP => "SIGTRAP",
+		libc::SIGABRT => "SIGABRT",
+		libc::SIGBUS => "SIGBUS",
+		libc::SIGFPE => "SIGFPE",
+		libc::SIGKILL => "SIGKILL",
+		libc::SIGUSR1 => "SIGUSR1",
+		libc::SIGSEGV => "SIGSEGV",
+		libc::SIGUSR2 => "SIGUSR2",
+		libc::SIGPIPE => "SIGPIPE",
+		libc::SIGALRM => "SIGALRM",
+		libc::SIGTERM => "SIGTERM",
+		libc::SIGXCPU => "SIGXCPU",
+		libc::SIGXFSZ => "SIGXFSZ",
+		libc::SIGVTALRM => "SIGVTALRM",
+		libc::SIGPROF => "SIGPROF",
+		libc::SIGSYS => "SIGSYS",
+		_ => return format!("SIG{signal}"),
+	};
+	name.to_string()
+}
+
+/// OOM kills recorded for this process's memory cgroup so far.
+#[cfg(target_os = "linux")]
+pub fn oom_kill_count() -> Option<u64> {
+	let cgroups = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+	// cgroup v1 lists the memory controller's hierarchy as `<id>:memory:<path>`;
+	// v2 has a single hierarchy, listed as `0::<path>`.
+	let v1 = cgroups.lines().find_map(|line| {
+		let (controllers, path) = line.split_once(':')?.1.split_once(':')?;
+		controllers
+			.split(',')
+			.any(|controller| controller == "memory")
+			.then(|| format!("/sys/fs/cgroup/memory{path}/memory.oom_control"))
+	});
+	let v2 = || {
+		let path = cgroups.lines().find_map(|line| line.strip_prefix("0::"))?;
+		Some(format!("/sys/fs/cgroup{path}/memory.events"))
+	};
+	let events = std::fs::read_to_string(v1.or_else(v2)?).ok()?;
+	events
+		.lines()
+		.find_map(|line| line.strip_prefix("oom_kill "))
+		.and_then(|count| count.trim().parse().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub const fn oom_kill_count() -> Option<u64> {
+	None
+}
+
+/// Whether the kernel log reports any of `pids` killed by the OOM killer.
+#[cfg(target_os = "linux")]
+pub fn oom_killed_any(pids: &[i32]) -> bool {
+	use std::{io::Read as _, os::unix::fs::OpenOptionsExt as _};
+
+	if pids.is_empty() {
+		return false;
+	}
+	let Ok(mut kmsg) = std::fs::OpenOptions::new()
+		.read(true)
+		.custom_flags(libc::O_NONBLOCK)
+		.open("/dev/kmsg")
+	else {
+		return false;
+	};
+	let needles: Vec<String> = pids
+		.iter()
+		.map(|pid| format!("Killed process {pid} ("))
+		.collect();
+	// Each read returns one record; EAGAIN marks the end of the buffer.
+	let mut record = vec![0u8; 8192];
+	loop {
+		match kmsg.read(&mut record) {
+			Ok(0) => return false,
+			Ok(len) => {
+				let text = String::from_utf8_lossy(&record[..len]);
+				if needles.iter().any(|needle| text.contains(needle.as_str())) {
+					return true;
+				}
+			},
+			// Records overwritten while reading.
+			Err(err) if err.raw_os_error() == Some(libc::EPIPE) => {},
+			Err(_) => return false,
+		}
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+pub const fn oom_killed_any(_pids: &[i32]) -> bool {
+	false
+}
@@ -0,0 +1,178 @@
+//! Isolated home directories for executions.
+//!
+//! # Overview
+//! With `isolatedHome`, a command sees a home directory of its own: `HOME`
+//! and the XDG base directories (on Windows also `USERPROFILE`, `APPDATA` and
+//! `LOCALAPPDATA`) point into it, so tools that write `~/.config` or
+//! `~/.cache` leave the user's dotfiles alone. Variables set explicitly in
+//! `env` win.
+//!
+//! The home is a fresh temporary directory removed after the command, or
+//! `path`, kept for later executions to reuse. `template` seeds it with a copy
+//! of another directory, keeping files already there. Sandboxed commands may
+//! write to it.
+
+use std::{
+	collections::HashMap,
+	env, fs, io,
+	path::{Path, PathBuf},
+	process,
+	sync::atomic::{AtomicU64, Ordering},
+};
+
+use napi::{Either, Error, Result};
+use napi_derive::napi;
+
+use super::ShellSandbox;
+
+/// Variables pointing into the home, with their path inside it.
+#[cfg(not(windows))]
+const VARS: &[(&str, &str)] = &[
+	("HOME", ""),
+	("XDG_CONFIG_HOME", ".config"),
+	("XDG_CACHE_HOME", ".cache"),
+	("XDG_DATA_HOME", ".local/share"),
+	("XDG_STATE_HOME", ".local/state"),
+];
+#[cfg(windows)]
+const VARS: &[(&str, &str)] = &[
+	("HOME", ""),
+	("USERPROFILE", ""),
+	("APPDATA", "AppData/Roaming"),
+	("LOCALAPPDATA", "AppData/Local"),
+	("XDG_CONFIG_HOME", ".config"),
+	("XDG_CACHE_HOME", ".cache"),
+	("XDG_DATA_HOME", ".local/share"),
+	("XDG_STATE_HOME", ".local/state"),
+];
+
+/// Suffix keeping temporary homes of this process apart.
+static NEXT: AtomicU64 = AtomicU64::new(0);
+
+/// Home directory for `isolatedHome`.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct ShellIsolatedHome {
+	/// Directory to use, created if missing and kept afterwards (default: a
+	/// temporary directory removed after the command).
+	pub path:     Option<String>,
+	/// Directory copied into the home first; files already there are kept.
+	pub template: Option<String>,
+}
+
+/// A home directory set up for one execution.
+pub struct IsolatedHome {
+	path:      PathBuf,
+	/// Removed when dropped.
+	temporary: bool,
+}
+
+impl IsolatedHome {
+	/// Set up the home `option` asks for, if any.
+	pub fn create(option: Option<Either<bool, ShellIsolatedHome>>) -> Result<Option<Self>> {
+		let options = match option {
+			None | Some(Either::A(false)) => return Ok(None),
+			Some(Either::A(true)) => ShellIsolatedHome::default(),
+			Some(Either::B(options)) => options,
+		};
+		let home = match options.path {
+			Some(path) => {
+				fs::create_dir_all(&path).map_err(|err| {
+					Error::from_reason(format!("Failed to create home directory {path}: {err}"))
+				})?;
+				Self {
+					path:      fs::canonicalize(&path).unwrap_or_else(|_| path.into()),
+					temporary: false,
+				}
+			},
+			None => Self { path: temporary_dir()?, temporary: true },
+		};
+		if let Some(template) = options.template {
+			copy_tree(Path::new(&template), &home.path).map_err(|err| {
+				Error::from_reason(format!("Failed to copy home template {template}: {err}"))
+			})?;
+		}
+		for (_, relative) in VARS {
+			fs::create_dir_all(home.path.join(relative))
+				.map_err(|err| Error::from_reason(format!("Failed to create home directory: {err}")))?;
+		}
+		Ok(Some(home))
+	}
+
+	/// Point `env` into the home, and let `sandbox` write to it.
+	pub fn apply(
+		&self,
+		env: &mut Option<HashMap<String, String>>,
+		sandbox: Option<&mut ShellSandbox>,
+	) {
+		let env = env.get_or_insert_default();
+		for (key, relative) in VARS {
+			let path = if relative.is_empty() {
+				self.path.clone()
+			} else {
+				self.path.join(relative)
+			};
+			env.entry((*key).to_string())
+				.or_insert_with(|| path.to_string_lossy().into_owned());
+		}
+		if let Some(sandbox) = sandbox {
+			sandbox
+				.write
+				.get_or_insert_default()
+				.push(self.path.to_string_lossy().into_owned());
+		}
+	}
+}
+
+impl Drop for IsolatedHome {
+	fn drop(&mut self) {
+		if self.temporary {
+			let _ = fs::remove_dir_all(&self.path);
+		}
+	}
+}
+
+/// A new directory under the system temporary directory, private to the user.
+fn temporary_dir() -> Result<PathBuf> {
+	let mut builder = fs::DirBuilder::new();
+	#[cfg(unix)]
+	std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+	loop {
+		let path = env::temp_dir().join(format!(
+			"pi-home-{}-{}",
+			process::id(),
+			NEXT.fetch_add(1, Ordering::Relaxed)
+		));
+		match builder.create(&path) {
+			Ok(()) => return Ok(fs::canonicalize(&path).unwrap_or(path)),
+			Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {},
+			Err(err) => {
+				return Err(Error::from_reason(format!("Failed to create home directory: {err}")));
+			},
+		}
+	}
+}
+
+/// Copy the contents of `from` into `to`, keeping entries already in `to`
+/// and symlinks as symlinks.
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+	for entry in fs::read_dir(from)? {
+		let entry = entry?;
+		let target = to.join(entry.file_name());
+		let file_type = entry.file_type()?;
+		if file_type.is_dir() {
+			fs::create_dir_all(&target)?;
+			copy_tree(&entry.path(), &target)?;
+		} else if fs::symlink_metadata(&target).is_ok() {
+			// Already in the home.
+		} else if file_type.is_symlink() {
+			#[cfg(unix)]
+			std::os::unix::fs::symlink(fs::read_link(entry.path())?, &target)?;
+			#[cfg(windows)]
+			fs::copy(entry.path(), &target)?;
+		} else {
+			fs::copy(entry.path(), &target)?;
+		}
+	}
+	Ok(())
+}
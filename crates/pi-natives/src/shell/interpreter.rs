@@ -0,0 +1,120 @@
+//! Native interpreters that run a command in place of brush.
+//!
+//! # Overview
+//! The command string is handed to the interpreter as-is and the interpreter
+//! runs as a single spawned process, so it goes through the same spawn hooks
+//! (limits, sandbox, network policy, process tracking) as a command brush
+//! spawns.
+//!
+//! - **PowerShell**: `pwsh`, falling back to Windows PowerShell
+//!   (`powershell.exe`) on Windows; the script is passed with `-EncodedCommand`
+//!   so no quoting is involved, and progress bars are disabled so they don't
+//!   end up in the output
+//! - **cmd**: `cmd.exe /d /s /c`, Windows only
+
+use std::{ffi::OsString, process::Command};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use napi::{Error, Result};
+
+/// Statements run before a PowerShell script: no progress records, and UTF-8
+/// output (Windows PowerShell writes redirected output in the OEM code page).
+const POWERSHELL_PRELUDE: &str = "$ProgressPreference = 'SilentlyContinue'\ntry { \
+                                  [Console]::OutputEncoding = [Text.UTF8Encoding]::new($false) } \
+                                  catch {}\n";
+
+/// Program that runs a command string.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpreter {
+	/// The embedded brush shell.
+	#[default]
+	Brush,
+	/// PowerShell 7 (`pwsh`) or Windows PowerShell.
+	PowerShell,
+	/// The Windows command processor.
+	Cmd,
+}
+
+impl Interpreter {
+	/// Parse the `interpreter` option.
+	pub fn parse(option: Option<&str>) -> Result<Self> {
+		match option {
+			None | Some("brush") => Ok(Self::Brush),
+			Some("powershell") => Ok(Self::PowerShell),
+			Some("cmd") => Ok(Self::Cmd),
+			Some(other) => Err(Error::from_reason(format!(
+				"Invalid interpreter: {other} (expected \"brush\", \"powershell\" or \"cmd\")"
+			))),
+		}
+	}
+
+	/// Name used in error messages.
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Brush => "brush",
+			Self::PowerShell => "PowerShell",
+			Self::Cmd => "cmd",
+		}
+	}
+}
+
+/// Start a command for `program`, launched through `wrapper` if given.
+fn wrapped(program: &str, wrapper: Option<Vec<OsString>>) -> Command {
+	if let Some((wrapper, args)) = wrapper.as_deref().and_then(<[OsString]>::split_first) {
+		let mut cmd = Command::new(wrapper);
+		cmd.args(args).arg(program);
+		return cmd;
+	}
+	Command::new(program)
+}
+
+/// The PowerShell executable: `pwsh` when it is on `PATH`, otherwise Windows
+/// PowerShell.
+#[cfg(windows)]
+fn powershell_program() -> &'static str {
+	let has_pwsh = std::env::var_os("PATH")
+		.is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join("pwsh.exe").is_file()));
+	if has_pwsh {
+		"pwsh.exe"
+	} else {
+		"powershell.exe"
+	}
+}
+
+#[cfg(not(windows))]
+const fn powershell_program() -> &'static str {
+	"pwsh"
+}
+
+/// Build the command running `script` with `interpreter`.
+///
+/// `wrapper` is the spawn hooks' command wrapper (`sandbox-exec` on macOS).
+pub fn command(
+	interpreter: Interpreter,
+	script: &str,
+	wrapper: Option<Vec<OsString>>,
+) -> Result<Command> {
+	match interpreter {
+		Interpreter::Brush => Err(Error::from_reason("brush commands run in a shell session")),
+		Interpreter::PowerShell => {
+			let script = format!("{POWERSHELL_PRELUDE}{script}");
+			let utf16: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+			let mut cmd = wrapped(powershell_program(), wrapper);
+			cmd.args(["-NoLogo", "-NoProfile", "-NonInteractive"]);
+			#[cfg(windows)]
+			cmd.args(["-ExecutionPolicy", "Bypass"]);
+			cmd.arg("-EncodedCommand").arg(STANDARD.encode(utf16));
+			Ok(cmd)
+		},
+		#[cfg(windows)]
+		Interpreter::Cmd => {
+			use std::os::windows::process::CommandExt as _;
+			let mut cmd = wrapped("cmd.exe", wrapper);
+			// `/s` strips the outer quotes and leaves the rest untouched.
+			cmd.raw_arg(format!("/d /s /c \"{script}\""));
+			Ok(cmd)
+		},
+		#[cfg(not(windows))]
+		Interpreter::Cmd => Err(Error::from_reason("The cmd interpreter is only available on Windows")),
+	}
+}
@@ -0,0 +1,114 @@
+//! Line-delimited JSON detection in streamed shell output.
+//!
+//! # Overview
+//! With `jsonLines`, decoded output is split into lines. A line holding a
+//! JSON object or array (`cargo build --message-format=json`,
+//! `jest --json`, `eslint -f json`) is parsed and delivered as a
+//! [`ShellJsonLine`]; everything else is delivered as text, batched per
+//! read. Lines split across reads are reassembled here, so JS never sees
+//! half an object.
+//!
+//! Lines longer than [`MAX_LINE`] are streamed as text instead of buffered.
+
+use napi::{bindgen_prelude::Either4, threadsafe_function::ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use serde_json::Value;
+
+use super::{OutputSink, emit_chunk};
+
+/// Longest line buffered for parsing.
+const MAX_LINE: usize = 16 * 1024 * 1024;
+
+/// A line of output holding JSON, streamed with `jsonLines`.
+#[napi(object)]
+pub struct ShellJsonLine {
+	/// The parsed object or array.
+	#[napi(ts_type = "unknown")]
+	pub json: Value,
+	/// The line as written, without its line terminator.
+	pub line: String,
+}
+
+/// Splits decoded output into JSON lines and text.
+#[derive(Default)]
+pub struct JsonLines {
+	/// Start of a line not yet terminated.
+	line:     String,
+	/// The current line outgrew [`MAX_LINE`] and is streamed as text.
+	overflow: bool,
+}
+
+impl JsonLines {
+	/// Feed decoded output, emitting every completed line.
+	pub fn push(&mut self, text: &str, sink: &OutputSink) {
+		let mut plain = String::new();
+		let mut rest = text;
+		while let Some(end) = rest.find('\n') {
+			let (head, tail) = rest.split_at(end + 1);
+			rest = tail;
+			if self.overflow {
+				plain.push_str(head);
+				self.overflow = false;
+				continue;
+			}
+			self.line.push_str(head);
+			let line = std::mem::take(&mut self.line);
+			match parse(&line) {
+				Some(json) => {
+					emit_plain(&mut plain, sink);
+					emit_json(json, &line, sink);
+				},
+				None => plain.push_str(&line),
+			}
+		}
+		if self.overflow {
+			plain.push_str(rest);
+		} else {
+			self.line.push_str(rest);
+			if self.line.len() > MAX_LINE {
+				plain.push_str(&std::mem::take(&mut self.line));
+				self.overflow = true;
+			}
+		}
+		emit_plain(&mut plain, sink);
+	}
+
+	/// Emit an unterminated last line at the end of output.
+	pub fn finish(&mut self, sink: &OutputSink) {
+		let line = std::mem::take(&mut self.line);
+		if line.is_empty() {
+			return;
+		}
+		match parse(&line) {
+			Some(json) => emit_json(json, &line, sink),
+			None => emit_chunk(&line, sink),
+		}
+	}
+}
+
+/// JSON object or array on `line`; scalars stay text.
+fn parse(line: &str) -> Option<Value> {
+	let trimmed = line.trim();
+	if !(trimmed.starts_with('{') || trimmed.starts_with('[')) {
+		return None;
+	}
+	serde_json::from_str(trimmed).ok()
+}
+
+fn emit_plain(plain: &mut String, sink: &OutputSink) {
+	if !plain.is_empty() {
+		emit_chunk(plain, sink);
+		plain.clear();
+	}
+}
+
+fn emit_json(json: Value, line: &str, sink: &OutputSink) {
+	sink.record(line.as_bytes());
+	if let Some(callback) = sink.callback() {
+		let line = line.trim_end_matches(['\n', '\r']).to_string();
+		callback.call(
+			Ok(Either4::D(ShellJsonLine { json, line })),
+			ThreadsafeFunctionCallMode::NonBlocking,
+		);
+	}
+}
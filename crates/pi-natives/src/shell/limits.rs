@@ -0,0 +1,173 @@
+//! Resource limits for external commands spawned by brush.
+//!
+//! # Platform Implementation
+//! - **Unix**: `setrlimit` in the child between fork and exec
+//! - **Windows**: a per-execution Job Object with memory and CPU-time limits;
+//!   each spawned process is assigned to the job right after creation
+//!
+//! Limits only apply to external processes. Builtins run inside the host
+//! process and are not constrained. Limits on a command's whole process tree
+//! are enforced through a cgroup (see [`super::cgroup`]).
+
+use std::process::Command;
+
+use brush_core::SpawnHooks;
+use napi_derive::napi;
+
+/// Resource limits for commands spawned by a shell execution.
+#[napi(object)]
+#[derive(Clone, Copy, Default)]
+pub struct ShellResourceLimits {
+	/// Maximum address space per process, in bytes (`RLIMIT_AS` / job process
+	/// memory).
+	#[napi(js_name = "maxMemoryBytes")]
+	pub max_memory_bytes:       Option<i64>,
+	/// Maximum CPU time per process, in seconds.
+	#[napi(js_name = "maxCpuSeconds")]
+	pub max_cpu_seconds:        Option<u32>,
+	/// Maximum open file descriptors per process (unix only).
+	#[napi(js_name = "maxOpenFiles")]
+	pub max_open_files:         Option<u32>,
+	/// Maximum memory of all of a command's processes together, in bytes
+	/// (cgroup `memory.max`; Linux with cgroup v2 only).
+	#[napi(js_name = "maxTotalMemoryBytes")]
+	pub max_total_memory_bytes: Option<i64>,
+	/// Maximum CPU bandwidth of all of a command's processes together, in
+	/// CPUs; `1.5` allows one and a half CPUs (cgroup `cpu.max`; Linux with
+	/// cgroup v2 only).
+	#[napi(js_name = "maxCpus")]
+	pub max_cpus:               Option<f64>,
+	/// Maximum number of processes and threads a command may run at once
+	/// (cgroup `pids.max`; Linux with cgroup v2 only).
+	#[napi(js_name = "maxProcesses")]
+	pub max_processes:          Option<u32>,
+	/// Run the command in a cgroup of its own even without cgroup limits, so
+	/// its resource usage is measured exactly. Ignored where cgroups are not
+	/// available.
+	pub cgroup:                 Option<bool>,
+	/// Maximum combined stdout/stderr bytes before the command is killed.
+	/// Deprecated: same as `maxOutputBytes` with `killOnOutputLimit`, which
+	/// take precedence.
+	#[napi(js_name = "maxOutputBytes")]
+	pub max_output_bytes:       Option<i64>,
+}
+
+impl ShellResourceLimits {
+	fn max_memory(&self) -> Option<u64> {
+		self.max_memory_bytes.map(|bytes| bytes.max(0) as u64)
+	}
+
+	/// Output cap in bytes, if configured.
+	pub fn max_output(&self) -> Option<u64> {
+		self.max_output_bytes.map(|bytes| bytes.max(0) as u64)
+	}
+
+	/// Cgroup controllers needed to enforce the configured limits.
+	pub fn cgroup_controllers(&self) -> Vec<&'static str> {
+		let mut controllers = Vec::new();
+		if self.max_cpus.is_some() {
+			controllers.push("cpu");
+		}
+		if self.max_total_memory_bytes.is_some() {
+			controllers.push("memory");
+		}
+		if self.max_processes.is_some() {
+			controllers.push("pids");
+		}
+		controllers
+	}
+
+	const fn has_process_limits(&self) -> bool {
+		self.max_memory_bytes.is_some()
+			|| self.max_cpu_seconds.is_some()
+			|| self.max_open_files.is_some()
+	}
+}
+
+/// Spawn hooks that apply [`ShellResourceLimits`] to every external command.
+pub struct LimitHooks {
+	#[cfg_attr(windows, allow(dead_code, reason = "rlimits are unix-only"))]
+	limits: ShellResourceLimits,
+	#[cfg(windows)]
+	job:    Option<crate::ps::Job>,
+}
+
+impl LimitHooks {
+	/// Build hooks for `limits`.
+	/// Returns `None` when no per-process limit is configured.
+	#[allow(clippy::missing_const_for_fn, reason = "creates a job object on Windows")]
+	pub fn new(limits: &ShellResourceLimits) -> Option<Self> {
+		if !limits.has_process_limits() {
+			return None;
+		}
+		#[cfg(windows)]
+		let job = crate::ps::Job::new()
+			.filter(|job| job.set_limits(limits.max_memory(), limits.max_cpu_seconds));
+		Some(Self {
+			limits: *limits,
+			#[cfg(windows)]
+			job,
+		})
+	}
+}
+
+impl SpawnHooks for LimitHooks {
+	#[cfg(unix)]
+	fn before_spawn(&self, cmd: &mut Command) {
+		use std::os::unix::process::CommandExt;
+
+		let limits = self.limits;
+		// SAFETY: the closure only calls getrlimit/setrlimit, which are
+		// async-signal-safe, and allocates nothing.
+		unsafe {
+			cmd.pre_exec(move || apply_rlimits(&limits));
+		}
+	}
+
+	#[cfg(windows)]
+	fn before_spawn(&self, _cmd: &mut Command) {}
+
+	#[cfg(windows)]
+	fn after_spawn(&self, pid: i32) {
+		if let Some(job) = &self.job {
+			job.assign(pid);
+		}
+	}
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type Resource = libc::c_int;
+
+#[cfg(unix)]
+fn apply_rlimits(limits: &ShellResourceLimits) -> std::io::Result<()> {
+	if let Some(bytes) = limits.max_memory() {
+		set_rlimit(libc::RLIMIT_AS, bytes)?;
+	}
+	if let Some(seconds) = limits.max_cpu_seconds {
+		set_rlimit(libc::RLIMIT_CPU, seconds as u64)?;
+	}
+	if let Some(files) = limits.max_open_files {
+		set_rlimit(libc::RLIMIT_NOFILE, files as u64)?;
+	}
+	Ok(())
+}
+
+/// Lower both soft and hard limits for `resource` to `value`, never raising
+/// the current hard limit (which would fail without privileges).
+#[cfg(unix)]
+fn set_rlimit(resource: Resource, value: u64) -> std::io::Result<()> {
+	let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+	// SAFETY: `current` is a valid, writable rlimit struct.
+	if unsafe { libc::getrlimit(resource, &raw mut current) } != 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+	let value = (value as libc::rlim_t).min(current.rlim_max);
+	let limit = libc::rlimit { rlim_cur: value, rlim_max: value };
+	// SAFETY: `limit` is a valid rlimit struct that outlives the call.
+	if unsafe { libc::setrlimit(resource, &raw const limit) } != 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+	Ok(())
+}
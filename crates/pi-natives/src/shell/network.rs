@@ -0,0 +1,486 @@
+//! Network egress policy for commands run by a shell execution.
+//!
+//! `"deny"` blocks connections to anything but loopback; `{ allowHosts }`
+//! additionally allows the listed hosts (and DNS to the system resolvers).
+//! Unix sockets are always allowed.
+//!
+//! # Platform Implementation
+//! - **Linux**: each spawned command installs a seccomp filter that refers
+//!   `connect`, `sendto`, `sendmsg` and `sendmmsg` with a destination to a
+//!   supervisor thread, which checks the address and lets the call continue or
+//!   fails it with `EACCES`; packet sockets and `io_uring` are refused
+//! - **macOS**: `"deny"` becomes seatbelt rules for `sandbox-exec`; host
+//!   allowlists are not supported (seatbelt only matches `localhost` or `*`)
+//! - **Windows**: not supported (WFP filters need administrator rights)
+//!
+//! This is a policy for well-behaved build and test commands, not a security
+//! boundary: the supervisor reads addresses from the caller's memory, which a
+//! hostile multithreaded program can change after the check. Use `sandbox`
+//! with network disabled to cut off network access entirely.
+
+use napi::{Either, Error, Result};
+use napi_derive::napi;
+
+/// Hosts that commands may connect to, beyond loopback.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct ShellNetworkAllowlist {
+	/// Host names or IP addresses. Names are resolved when the execution
+	/// starts, and again when a command connects to an unknown address.
+	#[napi(js_name = "allowHosts")]
+	pub allow_hosts: Vec<String>,
+}
+
+/// Parsed `network` execution option.
+#[cfg_attr(
+	not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))),
+	allow(dead_code, reason = "host allowlists are only enforced on Linux")
+)]
+pub enum NetworkPolicy {
+	Allow,
+	Deny,
+	AllowHosts(Vec<String>),
+}
+
+impl NetworkPolicy {
+	/// Parse the `"allow" | "deny" | { allowHosts }` option.
+	pub fn parse(option: Option<&Either<String, ShellNetworkAllowlist>>) -> Result<Self> {
+		match option {
+			None => Ok(Self::Allow),
+			Some(Either::A(mode)) if mode == "allow" => Ok(Self::Allow),
+			Some(Either::A(mode)) if mode == "deny" => Ok(Self::Deny),
+			Some(Either::A(mode)) => {
+				Err(Error::from_reason(format!("Invalid network policy: {mode}")))
+			},
+			Some(Either::B(allowlist)) => Ok(Self::AllowHosts(allowlist.allow_hosts.clone())),
+		}
+	}
+}
+
+/// Spawn hooks that enforce a [`NetworkPolicy`] on every external command.
+pub struct NetworkHooks {
+	#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+	filter:       Vec<libc::sock_filter>,
+	#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+	destinations: std::sync::Arc<linux::Destinations>,
+}
+
+impl NetworkHooks {
+	/// Build hooks for `policy`.
+	/// Returns `None` when the policy allows all traffic.
+	#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+	#[allow(clippy::unnecessary_wraps, reason = "other platforms reject some policies")]
+	pub fn new(policy: NetworkPolicy) -> Result<Option<Self>> {
+		let hosts = match policy {
+			NetworkPolicy::Allow => return Ok(None),
+			NetworkPolicy::Deny => Vec::new(),
+			NetworkPolicy::AllowHosts(hosts) => hosts,
+		};
+		Ok(Some(Self {
+			filter:       linux::filter(),
+			destinations: std::sync::Arc::new(linux::Destinations::new(hosts)),
+		}))
+	}
+
+	/// Build hooks for `policy`.
+	/// Returns `None` when the policy allows all traffic.
+	#[cfg(target_os = "macos")]
+	pub fn new(policy: NetworkPolicy) -> Result<Option<Self>> {
+		match policy {
+			NetworkPolicy::Allow => Ok(None),
+			NetworkPolicy::Deny => Ok(Some(Self {})),
+			NetworkPolicy::AllowHosts(_) => {
+				Err(Error::from_reason("Network host allowlists are not supported on macOS"))
+			},
+		}
+	}
+
+	/// Build hooks for `policy`.
+	/// Returns `None` when the policy allows all traffic.
+	#[cfg(not(any(
+		all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")),
+		target_os = "macos"
+	)))]
+	pub fn new(policy: NetworkPolicy) -> Result<Option<Self>> {
+		match policy {
+			NetworkPolicy::Allow => Ok(None),
+			_ => Err(Error::from_reason("Network policies are not supported on this platform")),
+		}
+	}
+
+	/// Seatbelt rules denying non-local outbound traffic.
+	#[cfg(target_os = "macos")]
+	#[allow(clippy::unused_self, reason = "only the deny policy exists on macOS")]
+	pub const fn seatbelt_rules(&self) -> &'static str {
+		"(deny network-outbound)\n(allow network-outbound (remote unix-socket))\n(allow \
+		 network-outbound (remote ip \"localhost:*\"))\n"
+	}
+}
+
+impl brush_core::SpawnHooks for NetworkHooks {
+	#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+	fn before_spawn(&self, cmd: &mut std::process::Command) {
+		use std::os::unix::process::CommandExt;
+
+		let channel = linux::Channel::open(self.destinations.clone());
+		let filter = self.filter.clone();
+		// SAFETY: the closure only issues prctl, seccomp, read, write and close
+		// syscalls on data prepared before the fork, and allocates nothing.
+		unsafe {
+			cmd.pre_exec(move || linux::install(&filter, channel.as_ref()));
+		}
+	}
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod linux {
+	use std::{
+		collections::HashSet,
+		ffi::c_void,
+		fs, io,
+		mem::{self, MaybeUninit},
+		net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs},
+		os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+		sync::Arc,
+		time::{Duration, Instant},
+	};
+
+	use libc::{BPF_ABS, BPF_JEQ, BPF_JGE, BPF_JMP, BPF_K, BPF_LD, BPF_RET, BPF_W, sock_filter};
+	use parking_lot::Mutex;
+
+	#[cfg(target_arch = "x86_64")]
+	const AUDIT_ARCH: u32 = 0xc000_003e;
+	#[cfg(target_arch = "aarch64")]
+	const AUDIT_ARCH: u32 = 0xc000_00b7;
+	/// Syscalls of the x32 ABI, which shares the x86-64 audit arch.
+	const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+	/// Offsets into `struct seccomp_data` (low words, little-endian).
+	const NR: u32 = 0;
+	const ARCH: u32 = 4;
+	const ARG0: u32 = 16;
+	const ARG4_LOW: u32 = 16 + 4 * 8;
+	const ARG4_HIGH: u32 = ARG4_LOW + 4;
+	/// Re-resolve allowed host names at most this often.
+	const REFRESH: Duration = Duration::from_secs(1);
+	/// `sendmmsg` never sends more messages than this.
+	const UIO_MAXIOV: usize = 1024;
+
+	const fn stmt(code: u32, k: u32) -> sock_filter {
+		sock_filter { code: code as u16, jt: 0, jf: 0, k }
+	}
+
+	const fn jump(code: u32, k: u32, jt: usize, jf: usize) -> sock_filter {
+		sock_filter { code: code as u16, jt: jt as u8, jf: jf as u8, k }
+	}
+
+	/// Seccomp program referring destination-carrying sends to the listener.
+	pub fn filter() -> Vec<sock_filter> {
+		const NOTIFY: usize = 20;
+		const DENY: usize = 21;
+		const SENDTO: usize = 12;
+		const SOCKET: usize = 17;
+		// Relative jump from instruction `from` to `to`.
+		let to = |from: usize, to: usize| to - from - 1;
+		let eq = BPF_JMP | BPF_JEQ | BPF_K;
+		let load = BPF_LD | BPF_W | BPF_ABS;
+		let ret = |action| stmt(BPF_RET | BPF_K, action);
+		vec![
+			/* 0 */ stmt(load, ARCH),
+			/* 1 */ jump(eq, AUDIT_ARCH, 1, 0),
+			/* 2 */ ret(libc::SECCOMP_RET_KILL_PROCESS),
+			/* 3 */ stmt(load, NR),
+			/* 4 */ jump(BPF_JMP | BPF_JGE | BPF_K, X32_SYSCALL_BIT, to(4, DENY), 0),
+			/* 5 */ jump(eq, libc::SYS_connect as u32, to(5, NOTIFY), 0),
+			/* 6 */ jump(eq, libc::SYS_sendmsg as u32, to(6, NOTIFY), 0),
+			/* 7 */ jump(eq, libc::SYS_sendmmsg as u32, to(7, NOTIFY), 0),
+			/* 8 */ jump(eq, libc::SYS_sendto as u32, to(8, SENDTO), 0),
+			/* 9 */ jump(eq, libc::SYS_io_uring_setup as u32, to(9, DENY), 0),
+			/* 10 */ jump(eq, libc::SYS_socket as u32, to(10, SOCKET), 0),
+			/* 11 */ ret(libc::SECCOMP_RET_ALLOW),
+			// sendto: only with a destination address.
+			/* 12 */ stmt(load, ARG4_LOW),
+			/* 13 */ jump(eq, 0, 0, to(13, NOTIFY)),
+			/* 14 */ stmt(load, ARG4_HIGH),
+			/* 15 */ jump(eq, 0, 0, to(15, NOTIFY)),
+			/* 16 */ ret(libc::SECCOMP_RET_ALLOW),
+			// socket: no packet sockets.
+			/* 17 */ stmt(load, ARG0),
+			/* 18 */ jump(eq, libc::AF_PACKET as u32, to(18, DENY), 0),
+			/* 19 */ ret(libc::SECCOMP_RET_ALLOW),
+			/* 20 */ ret(libc::SECCOMP_RET_USER_NOTIF),
+			/* 21 */ ret(libc::SECCOMP_RET_ERRNO | libc::EACCES as u32),
+		]
+	}
+
+	/// Addresses commands may send to.
+	pub struct Destinations {
+		hosts:       Vec<String>,
+		nameservers: Vec<IpAddr>,
+		resolved:    Mutex<(Instant, HashSet<IpAddr>)>,
+	}
+
+	impl Destinations {
+		pub fn new(hosts: Vec<String>) -> Self {
+			// Allowed names must be resolvable by the command itself.
+			let nameservers = if hosts.is_empty() {
+				Vec::new()
+			} else {
+				nameservers()
+			};
+			let resolved = resolve(&hosts);
+			Self { hosts, nameservers, resolved: Mutex::new((Instant::now(), resolved)) }
+		}
+
+		fn allows(&self, ip: IpAddr, port: u16) -> bool {
+			let ip = ip.to_canonical();
+			if ip.is_loopback() || ip.is_unspecified() {
+				return true;
+			}
+			if port == 53 && self.nameservers.contains(&ip) {
+				return true;
+			}
+			let mut resolved = self.resolved.lock();
+			if resolved.1.contains(&ip) {
+				return true;
+			}
+			// The command may have been given another address for a name.
+			if self.hosts.is_empty() || resolved.0.elapsed() < REFRESH {
+				return false;
+			}
+			*resolved = (Instant::now(), resolve(&self.hosts));
+			resolved.1.contains(&ip)
+		}
+	}
+
+	fn resolve(hosts: &[String]) -> HashSet<IpAddr> {
+		hosts
+			.iter()
+			.filter_map(|host| (host.as_str(), 0).to_socket_addrs().ok())
+			.flatten()
+			.map(|addr| addr.ip().to_canonical())
+			.collect()
+	}
+
+	fn nameservers() -> Vec<IpAddr> {
+		let config = fs::read_to_string("/etc/resolv.conf").unwrap_or_default();
+		config
+			.lines()
+			.filter_map(|line| line.trim().strip_prefix("nameserver"))
+			.filter_map(|server| server.trim().parse::<IpAddr>().ok())
+			.map(|ip| ip.to_canonical())
+			.collect()
+	}
+
+	/// Socket through which a child hands its seccomp listener to us. The
+	/// child end lives in the spawn's `pre_exec` closure; the parent end is
+	/// served by a thread that fetches the listener and supervises it.
+	pub struct Channel(OwnedFd);
+
+	impl Channel {
+		/// Create the channel and start its thread. Returns `None` (and the
+		/// child then fails to start) if either fails.
+		pub fn open(destinations: Arc<Destinations>) -> Option<Self> {
+			let mut fds = [0; 2];
+			// SAFETY: `fds` has room for the two descriptors.
+			let rc = unsafe {
+				libc::socketpair(
+					libc::AF_UNIX,
+					libc::SOCK_STREAM | libc::SOCK_CLOEXEC,
+					0,
+					fds.as_mut_ptr(),
+				)
+			};
+			if rc != 0 {
+				return None;
+			}
+			// SAFETY: socketpair returned two new descriptors that we own.
+			let (parent, child) =
+				unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+			std::thread::Builder::new()
+				.name("pi-net-policy".into())
+				.spawn(move || {
+					if let Some(listener) = receive_listener(&parent) {
+						supervise(&listener, &destinations);
+					}
+				})
+				.ok()?;
+			Some(Self(child))
+		}
+	}
+
+	/// Install the filter in the calling (child) process and hand its listener
+	/// to the parent, waiting until the parent holds it.
+	pub fn install(filter: &[sock_filter], channel: Option<&Channel>) -> io::Result<()> {
+		let channel = channel.ok_or_else(|| io::Error::from_raw_os_error(libc::EMFILE))?;
+		let program =
+			libc::sock_fprog { len: filter.len() as u16, filter: filter.as_ptr().cast_mut() };
+		// SAFETY: plain syscalls; `program` points at a valid filter.
+		unsafe {
+			if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+				return Err(io::Error::last_os_error());
+			}
+			let listener = libc::syscall(
+				libc::SYS_seccomp,
+				libc::SECCOMP_SET_MODE_FILTER,
+				libc::SECCOMP_FILTER_FLAG_NEW_LISTENER,
+				&raw const program,
+			);
+			if listener < 0 {
+				return Err(io::Error::last_os_error());
+			}
+			let message = [libc::getpid(), listener as RawFd];
+			let sent =
+				libc::write(channel.0.as_raw_fd(), message.as_ptr().cast(), mem::size_of_val(&message));
+			let mut ack = 1u8;
+			let received = libc::read(channel.0.as_raw_fd(), (&raw mut ack).cast(), 1);
+			libc::close(listener as RawFd);
+			if sent != mem::size_of_val(&message) as isize || received != 1 || ack != 0 {
+				return Err(io::Error::from_raw_os_error(libc::EACCES));
+			}
+		}
+		Ok(())
+	}
+
+	/// Fetch the listener the child announces on `channel` with
+	/// `pidfd_getfd`, and tell the child whether that worked.
+	fn receive_listener(channel: &OwnedFd) -> Option<OwnedFd> {
+		let mut message = [0 as RawFd; 2];
+		let size = mem::size_of_val(&message);
+		// SAFETY: `message` is a writable buffer of `size` bytes.
+		let read = unsafe { libc::read(channel.as_raw_fd(), message.as_mut_ptr().cast(), size) };
+		if read != size as isize {
+			return None;
+		}
+		let [pid, fd] = message;
+		// SAFETY: plain syscalls; each returned descriptor is owned exactly once.
+		let listener = unsafe {
+			let pidfd = libc::syscall(libc::SYS_pidfd_open, pid, 0);
+			if pidfd < 0 {
+				None
+			} else {
+				let pidfd = OwnedFd::from_raw_fd(pidfd as RawFd);
+				let listener = libc::syscall(libc::SYS_pidfd_getfd, pidfd.as_raw_fd(), fd, 0);
+				(listener >= 0).then(|| OwnedFd::from_raw_fd(listener as RawFd))
+			}
+		};
+		let ack = u8::from(listener.is_none());
+		// SAFETY: `ack` is a readable one-byte buffer.
+		unsafe { libc::write(channel.as_raw_fd(), (&raw const ack).cast(), 1) };
+		listener
+	}
+
+	/// Answer the listener's notifications until every process using the
+	/// filter has exited.
+	fn supervise(listener: &OwnedFd, destinations: &Destinations) {
+		let fd = listener.as_raw_fd();
+		loop {
+			let mut poll = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+			// SAFETY: `poll` is a valid pollfd.
+			if unsafe { libc::poll(&raw mut poll, 1, -1) } < 0 {
+				if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+					continue;
+				}
+				return;
+			}
+			if poll.revents & libc::POLLIN == 0 {
+				return;
+			}
+			// SAFETY: all-zero is a valid `seccomp_notif`, as RECV requires.
+			let mut notif: libc::seccomp_notif = unsafe { mem::zeroed() };
+			// SAFETY: `notif` is a valid, writable notification buffer.
+			if unsafe { libc::ioctl(fd, libc::SECCOMP_IOCTL_NOTIF_RECV, &raw mut notif) } != 0 {
+				// The caller was interrupted or died before we received it.
+				continue;
+			}
+			let allowed = allows(&notif, destinations);
+			// The target may have died and its pid been reused while we read.
+			// SAFETY: `notif.id` is a valid u64.
+			if unsafe { libc::ioctl(fd, libc::SECCOMP_IOCTL_NOTIF_ID_VALID, &raw const notif.id) } != 0
+			{
+				continue;
+			}
+			let response = libc::seccomp_notif_resp {
+				id:    notif.id,
+				val:   0,
+				error: if allowed { 0 } else { -libc::EACCES },
+				flags: if allowed {
+					libc::SECCOMP_USER_NOTIF_FLAG_CONTINUE as u32
+				} else {
+					0
+				},
+			};
+			// SAFETY: `response` is a valid response struct. Failure means the
+			// caller is gone.
+			unsafe { libc::ioctl(fd, libc::SECCOMP_IOCTL_NOTIF_SEND, &raw const response) };
+		}
+	}
+
+	/// Whether the notified call may proceed.
+	fn allows(notif: &libc::seccomp_notif, destinations: &Destinations) -> bool {
+		let pid = notif.pid as libc::pid_t;
+		let args = notif.data.args;
+		let allows_message = |header: &libc::msghdr| {
+			header.msg_name.is_null()
+				|| allows_address(pid, header.msg_name as u64, header.msg_namelen as u64, destinations)
+		};
+		match i64::from(notif.data.nr) {
+			libc::SYS_connect => allows_address(pid, args[1], args[2], destinations),
+			libc::SYS_sendto => allows_address(pid, args[4], args[5], destinations),
+			libc::SYS_sendmsg => {
+				read::<libc::msghdr>(pid, args[1]).is_some_and(|header| allows_message(&header))
+			},
+			libc::SYS_sendmmsg => {
+				let count = (args[2] as usize).min(UIO_MAXIOV);
+				(0..count).all(|index| {
+					let at = args[1] + (index * mem::size_of::<libc::mmsghdr>()) as u64;
+					read::<libc::mmsghdr>(pid, at)
+						.is_some_and(|message| allows_message(&message.msg_hdr))
+				})
+			},
+			_ => false,
+		}
+	}
+
+	/// Whether the `sockaddr` of `len` bytes at `addr` in `pid` is allowed.
+	fn allows_address(pid: libc::pid_t, addr: u64, len: u64, destinations: &Destinations) -> bool {
+		let Some(storage) = read::<libc::sockaddr_storage>(pid, addr) else {
+			return false;
+		};
+		let len = len as usize;
+		match i32::from(storage.ss_family) {
+			libc::AF_INET if len >= mem::size_of::<libc::sockaddr_in>() => {
+				// SAFETY: the family says the storage holds a sockaddr_in.
+				let addr: libc::sockaddr_in = unsafe { mem::transmute_copy(&storage) };
+				let ip = Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr));
+				destinations.allows(IpAddr::V4(ip), u16::from_be(addr.sin_port))
+			},
+			libc::AF_INET6 if len >= mem::size_of::<libc::sockaddr_in6>() => {
+				// SAFETY: the family says the storage holds a sockaddr_in6.
+				let addr: libc::sockaddr_in6 = unsafe { mem::transmute_copy(&storage) };
+				let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+				destinations.allows(IpAddr::V6(ip), u16::from_be(addr.sin6_port))
+			},
+			// Too short for the kernel to accept either.
+			libc::AF_INET | libc::AF_INET6 => true,
+			// Unix, netlink and the like are local.
+			_ => true,
+		}
+	}
+
+	/// Copy a `T` out of `pid`'s memory at `addr`, zero-filling the part of
+	/// it that is unmapped.
+	fn read<T>(pid: libc::pid_t, addr: u64) -> Option<T> {
+		let mut value = MaybeUninit::<T>::zeroed();
+		let local = libc::iovec {
+			iov_base: value.as_mut_ptr().cast::<c_void>(),
+			iov_len:  mem::size_of::<T>(),
+		};
+		let remote = libc::iovec { iov_base: addr as *mut c_void, iov_len: mem::size_of::<T>() };
+		// SAFETY: `local` covers exactly `value`; the kernel validates `remote`.
+		let read =
+			unsafe { libc::process_vm_readv(pid, &raw const local, 1, &raw const remote, 1, 0) };
+		// SAFETY: zero-initialized and partially overwritten with raw bytes;
+		// only used for plain-data libc structs.
+		(read > 0).then(|| unsafe { value.assume_init() })
+	}
+}
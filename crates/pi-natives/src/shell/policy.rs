@@ -0,0 +1,391 @@
+//! Approval rules for shell commands.
+//!
+//! `setCommandPolicy` installs an ordered list of rules, each deciding
+//! `allow`, `deny` or `ask` for the simple commands it matches by program,
+//! path, network use or privilege elevation. `evaluateCommandPolicy` parses a
+//! command with the analyzer, looks through wrappers like `sudo` and `env`,
+//! and checks every simple command, including those in substitutions,
+//! against the rules: the first matching rule decides for a command, and the
+//! input as a whole gets the strictest decision (`deny` over `ask` over
+//! `allow`).
+//!
+//! Keeping the rules here lets every tool that runs commands (the shell,
+//! Python, sub-agents) enforce the same policy.
+//!
+//! # Example
+//! ```ignore
+//! // JS: native.setCommandPolicy({ rules: [{ action: "deny", sudo: true }], default: "ask" });
+//! // JS: const { action, rule } = await native.evaluateCommandPolicy("sudo rm -rf /");
+//! ```
+
+use std::{
+	path::{Component, Path, PathBuf},
+	sync::{Arc, LazyLock},
+};
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use parking_lot::RwLock;
+
+use super::analyze::{self, ResolvedCommand};
+use crate::task;
+
+/// Programs that always use the network.
+const NETWORK_PROGRAMS: &[&str] = &[
+	"curl", "wget", "ssh", "scp", "sftp", "rsync", "nc", "ncat", "netcat", "telnet", "ftp", "ping",
+	"dig", "nslookup", "host", "http", "https", "xh", "gh", "aria2c",
+];
+/// Programs whose listed subcommands use the network.
+const NETWORK_SUBCOMMANDS: &[(&str, &[&str])] = &[
+	("git", &["clone", "fetch", "pull", "push", "ls-remote", "submodule"]),
+	("npm", &["install", "i", "ci", "add", "update", "publish", "exec"]),
+	("npx", &[]),
+	("pnpm", &["install", "i", "add", "update", "publish", "dlx"]),
+	("yarn", &["install", "add", "upgrade", "publish", "dlx"]),
+	("bun", &["install", "i", "add", "update", "publish", "x"]),
+	("bunx", &[]),
+	("pip", &["install", "download"]),
+	("pip3", &["install", "download"]),
+	("uv", &["pip", "add", "sync", "lock", "run", "tool"]),
+	("cargo", &["install", "publish", "fetch", "update", "search", "add"]),
+	("go", &["get", "install", "mod"]),
+	("docker", &["pull", "push", "login", "build", "run"]),
+	("podman", &["pull", "push", "login", "build", "run"]),
+	("brew", &["install", "update", "upgrade", "fetch"]),
+	("apt", &["install", "update", "upgrade"]),
+	("apt-get", &["install", "update", "upgrade"]),
+];
+/// Programs whose operands are all paths.
+const FILE_PROGRAMS: &[&str] = &[
+	"rm", "rmdir", "cp", "mv", "ln", "touch", "mkdir", "chmod", "chown", "chgrp", "cat", "less",
+	"head", "tail", "tee", "truncate", "shred", "stat", "ls", "find", "source", ".",
+];
+
+static POLICY: LazyLock<RwLock<Arc<Policy>>> =
+	LazyLock::new(|| RwLock::new(Arc::new(Policy { rules: Vec::new(), default: Action::Ask })));
+
+/// A rule deciding the commands it matches. Conditions left out match any
+/// command; a rule without conditions matches every command.
+#[napi(object)]
+#[derive(Clone)]
+pub struct CommandPolicyRule {
+	/// Name reported with the decision.
+	pub name:     Option<String>,
+	/// Decision for matching commands.
+	#[napi(ts_type = "\"allow\" | \"deny\" | \"ask\"")]
+	pub action:   String,
+	/// Program names or globs (`git`, `py*`), matched against the program
+	/// and any wrappers it runs under, without their directory.
+	pub programs: Option<Vec<String>>,
+	/// Path globs, matched against the files a command redirects from or to
+	/// and the operands that look like paths, after resolving them against
+	/// `cwd` and `~`. Allow rules match when every path matches; deny and ask
+	/// rules when any path does.
+	pub paths:    Option<Vec<String>>,
+	/// Match commands that use the network (`curl`, `git push`, `npm
+	/// install`, ...) when true, and those that do not when false.
+	pub network:  Option<bool>,
+	/// Match commands run through `sudo`, `doas`, `su`, `pkexec` or `run0`
+	/// when true, and those that are not when false.
+	pub sudo:     Option<bool>,
+}
+
+/// Rules for `setCommandPolicy`.
+#[napi(object)]
+pub struct CommandPolicy {
+	/// Rules in priority order.
+	pub rules:   Vec<CommandPolicyRule>,
+	/// Decision for commands no rule matches (default: "ask").
+	#[napi(ts_type = "\"allow\" | \"deny\" | \"ask\"")]
+	pub default: Option<String>,
+}
+
+/// Options for `evaluateCommandPolicy`.
+#[napi(object)]
+#[derive(Default)]
+pub struct CommandPolicyOptions {
+	/// Directory relative paths are resolved against (default: this
+	/// process's working directory).
+	pub cwd: Option<String>,
+}
+
+/// Result of `evaluateCommandPolicy`.
+#[napi(object)]
+pub struct CommandPolicyDecision {
+	/// Strictest decision for the commands in the input.
+	#[napi(ts_type = "\"allow\" | \"deny\" | \"ask\"")]
+	pub action:     String,
+	/// The rule that decided, absent when the default applied.
+	pub rule:       Option<CommandPolicyRule>,
+	/// Position of `rule` in the policy.
+	#[napi(js_name = "ruleIndex")]
+	pub rule_index: Option<u32>,
+	/// Source text of the simple command that decided, absent for input
+	/// without commands.
+	pub command:    Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Action {
+	Allow,
+	Ask,
+	Deny,
+}
+
+impl Action {
+	fn parse(action: &str) -> Result<Self> {
+		match action {
+			"allow" => Ok(Self::Allow),
+			"ask" => Ok(Self::Ask),
+			"deny" => Ok(Self::Deny),
+			other => Err(Error::from_reason(format!(
+				"Invalid policy action {other:?}: expected \"allow\", \"deny\" or \"ask\""
+			))),
+		}
+	}
+
+	const fn as_str(self) -> &'static str {
+		match self {
+			Self::Allow => "allow",
+			Self::Ask => "ask",
+			Self::Deny => "deny",
+		}
+	}
+}
+
+struct Rule {
+	source:   CommandPolicyRule,
+	action:   Action,
+	programs: Option<GlobSet>,
+	paths:    Option<GlobSet>,
+}
+
+struct Policy {
+	rules:   Vec<Rule>,
+	default: Action,
+}
+
+/// A simple command as the rules see it.
+struct Subject<'a> {
+	command: &'a ResolvedCommand,
+	network: bool,
+	/// Paths resolved against the working directory.
+	paths:   Vec<PathBuf>,
+}
+
+impl Rule {
+	fn compile(source: CommandPolicyRule) -> Result<Self> {
+		let action = Action::parse(&source.action)?;
+		let programs = source
+			.programs
+			.as_deref()
+			.map(|globs| glob_set(globs, false))
+			.transpose()?;
+		let paths = source
+			.paths
+			.as_deref()
+			.map(|globs| glob_set(globs, true))
+			.transpose()?;
+		Ok(Self { source, action, programs, paths })
+	}
+
+	fn matches(&self, subject: &Subject<'_>) -> bool {
+		if let Some(sudo) = self.source.sudo
+			&& sudo != subject.command.elevated
+		{
+			return false;
+		}
+		if let Some(network) = self.source.network
+			&& network != subject.network
+		{
+			return false;
+		}
+		if let Some(programs) = &self.programs {
+			// A program named by an expansion could be anything.
+			if subject.command.dynamic
+				|| !subject
+					.command
+					.programs
+					.iter()
+					.any(|program| programs.is_match(program_name(program)))
+			{
+				return false;
+			}
+		}
+		if let Some(paths) = &self.paths {
+			let mut matched = subject.paths.iter().map(|path| paths.is_match(path));
+			let matches = if self.action == Action::Allow {
+				// Arguments of an unknown program could name any path.
+				subject.command.args.is_some() && matched.all(|matched| matched)
+			} else {
+				matched.any(|matched| matched)
+			};
+			if !matches {
+				return false;
+			}
+		}
+		true
+	}
+}
+
+fn glob_set(globs: &[String], literal_separator: bool) -> Result<GlobSet> {
+	let mut builder = GlobSetBuilder::new();
+	for glob in globs {
+		let compiled = if literal_separator {
+			GlobBuilder::new(&expand_home(glob))
+				.literal_separator(true)
+				.build()
+		} else {
+			Glob::new(glob)
+		};
+		builder.add(
+			compiled
+				.map_err(|err| Error::from_reason(format!("Invalid policy glob {glob:?}: {err}")))?,
+		);
+	}
+	builder
+		.build()
+		.map_err(|err| Error::from_reason(format!("Invalid policy globs: {err}")))
+}
+
+/// `path` with a leading `~` replaced by the home directory.
+fn expand_home(path: &str) -> String {
+	let rest = match path.strip_prefix('~') {
+		Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+		_ => return path.to_string(),
+	};
+	std::env::home_dir()
+		.map_or_else(|| path.to_string(), |home| format!("{}{rest}", home.to_string_lossy()))
+}
+
+/// Program name without its directory.
+fn program_name(program: &str) -> &str {
+	program.rsplit('/').next().unwrap_or(program)
+}
+
+fn uses_network(command: &ResolvedCommand) -> bool {
+	let Some(program) = command.programs.last().map(|program| program_name(program)) else {
+		return false;
+	};
+	if NETWORK_PROGRAMS.contains(&program) {
+		return true;
+	}
+	let Some((_, subcommands)) = NETWORK_SUBCOMMANDS
+		.iter()
+		.find(|(name, _)| *name == program)
+	else {
+		return false;
+	};
+	if subcommands.is_empty() {
+		return true;
+	}
+	command
+		.args
+		.iter()
+		.flatten()
+		.find(|arg| !arg.starts_with('-'))
+		.is_some_and(|subcommand| subcommands.contains(&subcommand.as_str()))
+}
+
+/// Paths `command` touches, resolved against `cwd`.
+fn paths(command: &ResolvedCommand, cwd: &Path) -> Vec<PathBuf> {
+	let program = command
+		.programs
+		.last()
+		.map_or("", |program| program_name(program));
+	let all_paths = FILE_PROGRAMS.contains(&program);
+	let operands = command.operands.iter().filter(|operand| {
+		all_paths || operand.contains('/') || operand.starts_with('~') || operand.starts_with('.')
+	});
+	command
+		.files
+		.iter()
+		.chain(operands)
+		.map(|path| normalize(&cwd.join(expand_home(path))))
+		.collect()
+}
+
+/// `path` with `.` and `..` components resolved lexically.
+fn normalize(path: &Path) -> PathBuf {
+	let mut out = PathBuf::new();
+	for component in path.components() {
+		match component {
+			Component::CurDir => {},
+			Component::ParentDir => {
+				out.pop();
+			},
+			other => out.push(other),
+		}
+	}
+	out
+}
+
+fn evaluate(policy: &Policy, command: &str, cwd: &Path) -> Result<CommandPolicyDecision> {
+	let (analysis, resolved) = analyze::analyze_resolved(command)?;
+	let mut decision: Option<(Action, Option<usize>, usize)> = None;
+	for command in &resolved {
+		let subject = Subject { command, network: uses_network(command), paths: paths(command, cwd) };
+		let rule = policy.rules.iter().position(|rule| rule.matches(&subject));
+		let action = rule.map_or(policy.default, |index| policy.rules[index].action);
+		if decision.is_none_or(|(strictest, ..)| action > strictest) {
+			decision = Some((action, rule, command.index));
+		}
+	}
+	let Some((action, rule, index)) = decision else {
+		return Ok(CommandPolicyDecision {
+			action:     policy.default.as_str().to_string(),
+			rule:       None,
+			rule_index: None,
+			command:    None,
+		});
+	};
+	Ok(CommandPolicyDecision {
+		action:     action.as_str().to_string(),
+		rule:       rule.map(|rule| policy.rules[rule].source.clone()),
+		rule_index: rule.map(|rule| u32::try_from(rule).unwrap_or(u32::MAX)),
+		command:    Some(analysis.commands[index].text.clone()),
+	})
+}
+
+/// Set the rules `evaluateCommandPolicy` applies, replacing earlier ones.
+///
+/// # Errors
+/// Returns an error for an unknown action or an invalid glob; the previous
+/// rules stay in effect.
+#[napi(js_name = "setCommandPolicy")]
+pub fn set_command_policy(policy: CommandPolicy) -> Result<()> {
+	let default = policy
+		.default
+		.as_deref()
+		.map_or(Ok(Action::Ask), Action::parse)?;
+	let rules = policy
+		.rules
+		.into_iter()
+		.map(Rule::compile)
+		.collect::<Result<Vec<_>>>()?;
+	*POLICY.write() = Arc::new(Policy { rules, default });
+	Ok(())
+}
+
+/// Decide whether `command` may run under the rules set with
+/// `setCommandPolicy`.
+///
+/// # Errors
+/// Returns an error if the command does not parse.
+#[napi(js_name = "evaluateCommandPolicy")]
+pub fn evaluate_command_policy(
+	command: String,
+	options: Option<CommandPolicyOptions>,
+) -> task::Async<CommandPolicyDecision> {
+	let policy = POLICY.read().clone();
+	let cwd = options.unwrap_or_default().cwd;
+	task::blocking("evaluate_command_policy", (), move |_| {
+		let cwd = match cwd {
+			Some(cwd) => PathBuf::from(expand_home(&cwd)),
+			None => std::env::current_dir()
+				.map_err(|err| Error::from_reason(format!("Failed to get cwd: {err}")))?,
+		};
+		evaluate(&policy, &command, &cwd)
+	})
+}
@@ -0,0 +1,124 @@
+//! Scheduling priority for commands spawned by a shell execution.
+//!
+//! # Platform Implementation
+//! - **Unix**: `setpriority` in the child between fork and exec, to nice 10 for
+//!   `background` and -5 for `high`; on Linux `ioprio_set` also moves the
+//!   command to the lowest or highest best-effort I/O priority. Raising the
+//!   priority needs privileges; without them the command keeps ours
+//! - **Windows**: each spawned process gets the below-normal or above-normal
+//!   priority class right after creation
+//!
+//! Commands pass their priority on to the processes they start, except that
+//! Windows only passes on lowered priority classes. Builtins run inside the
+//! host process and keep its priority.
+
+use std::process::Command;
+
+use brush_core::SpawnHooks;
+use napi::{Error, Result};
+
+/// Scheduling priority of spawned commands.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+	/// Yield to interactive work: indexing, sub-agent builds.
+	Background,
+	/// The host's own priority.
+	#[default]
+	Normal,
+	/// Ahead of other work, where permitted.
+	High,
+}
+
+impl Priority {
+	/// Parse the `priority` option.
+	pub fn parse(option: Option<&str>) -> Result<Self> {
+		match option {
+			None | Some("normal") => Ok(Self::Normal),
+			Some("background") => Ok(Self::Background),
+			Some("high") => Ok(Self::High),
+			Some(other) => Err(Error::from_reason(format!(
+				"Invalid priority: {other} (expected \"background\", \"normal\" or \"high\")"
+			))),
+		}
+	}
+}
+
+/// Spawn hooks that apply a [`Priority`] to every external command.
+pub struct PriorityHooks {
+	#[cfg_attr(windows, allow(dead_code, reason = "applied after spawn on Windows"))]
+	priority: Priority,
+}
+
+impl PriorityHooks {
+	/// Build hooks for `priority`.
+	/// Returns `None` for [`Priority::Normal`], which needs no changes.
+	pub fn new(priority: Priority) -> Option<Self> {
+		(priority != Priority::Normal).then_some(Self { priority })
+	}
+}
+
+impl SpawnHooks for PriorityHooks {
+	#[cfg(unix)]
+	fn before_spawn(&self, cmd: &mut Command) {
+		use std::os::unix::process::CommandExt;
+
+		let priority = self.priority;
+		// SAFETY: the closure only calls setpriority/ioprio_set, which are
+		// async-signal-safe, and allocates nothing.
+		unsafe {
+			cmd.pre_exec(move || {
+				apply_priority(priority);
+				Ok(())
+			});
+		}
+	}
+
+	#[cfg(windows)]
+	fn before_spawn(&self, _cmd: &mut Command) {}
+
+	#[cfg(windows)]
+	fn after_spawn(&self, pid: i32) {
+		const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x4000;
+		const ABOVE_NORMAL_PRIORITY_CLASS: u32 = 0x8000;
+		let class = match self.priority {
+			Priority::Background => BELOW_NORMAL_PRIORITY_CLASS,
+			Priority::High => ABOVE_NORMAL_PRIORITY_CLASS,
+			Priority::Normal => return,
+		};
+		crate::ps::set_priority_class(pid, class);
+	}
+}
+
+/// Set the calling process's CPU and I/O priority, ignoring refusals.
+#[cfg(unix)]
+fn apply_priority(priority: Priority) {
+	let nice = match priority {
+		Priority::Background => 10,
+		Priority::High => -5,
+		Priority::Normal => return,
+	};
+	// SAFETY: setpriority only reads its integer arguments.
+	unsafe {
+		libc::setpriority(libc::PRIO_PROCESS, 0, nice);
+	}
+	#[cfg(target_os = "linux")]
+	{
+		const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+		const IOPRIO_CLASS_BE: libc::c_long = 2;
+		const IOPRIO_CLASS_SHIFT: u32 = 13;
+		let level = if priority == Priority::Background {
+			7
+		} else {
+			0
+		};
+		// SAFETY: ioprio_set only reads its integer arguments.
+		unsafe {
+			libc::syscall(
+				libc::SYS_ioprio_set,
+				IOPRIO_WHO_PROCESS,
+				0,
+				(IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | level,
+			);
+		}
+	}
+}
@@ -0,0 +1,191 @@
+//! Detection of commands that appear to be waiting for input.
+//!
+//! # Overview
+//! Commands run with stdin closed, so a command that still prompts reads from
+//! the terminal directly (sudo, ssh, credential helpers) and hangs until the
+//! timeout. A command is reported once it has been silent for the idle
+//! period while one of its processes is still running and its last output
+//! line looks like a prompt (`Password:`, `[y/N]`, `Press any key`, ...).
+//! It is reported again only after it writes more output.
+
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use napi::{
+	bindgen_prelude::Either4,
+	threadsafe_function::ThreadsafeFunctionCallMode,
+	tokio::{self, sync::Notify, time},
+};
+use napi_derive::napi;
+use parking_lot::Mutex;
+
+use super::{OutputCallback, ansi::AnsiStripper, redact, tracker::SpawnTracker};
+
+/// Longest line tail kept for matching.
+const MAX_LINE: usize = 512;
+/// Words that make a line ending in `:` or `?` a prompt.
+const PROMPT_WORDS: &[&str] = &[
+	"password",
+	"passphrase",
+	"username",
+	"login",
+	"token",
+	"verification code",
+	"one-time",
+	"continue",
+	"proceed",
+	"overwrite",
+	"are you sure",
+];
+/// Choice lists offered by confirmation prompts (whitespace removed).
+const CHOICES: &[&str] = &["[y/n", "(y/n", "[yes/no", "(yes/no"];
+/// Phrases asking for a key press.
+const KEY_PRESS: &[&str] = &["press enter", "press return", "press any key"];
+
+/// Reported through the output callback when a command appears to be waiting
+/// for input, with `promptIdleMs`.
+#[napi(object)]
+pub struct ShellInputPrompt {
+	/// The last output line, which looks like a prompt.
+	pub prompt:  String,
+	/// How long the command had been silent, in milliseconds.
+	#[napi(js_name = "idleMs")]
+	pub idle_ms: f64,
+}
+
+/// Last output lines of a command.
+struct Tail {
+	/// Line being written.
+	line:            Vec<u8>,
+	/// Last complete non-blank line.
+	last_line:       Vec<u8>,
+	/// A carriage return was seen; the next character rewrites the line.
+	carriage_return: bool,
+	ansi:            AnsiStripper,
+	last_output:     Instant,
+}
+
+impl Tail {
+	fn push(&mut self, bytes: &[u8]) {
+		let mut bytes = bytes.to_vec();
+		let len = self.ansi.strip(&mut bytes);
+		for &byte in &bytes[..len] {
+			match byte {
+				b'\n' => {
+					self.carriage_return = false;
+					if self.line.trim_ascii().is_empty() {
+						self.line.clear();
+					} else {
+						self.last_line = std::mem::take(&mut self.line);
+					}
+				},
+				b'\r' => self.carriage_return = true,
+				_ => {
+					if std::mem::take(&mut self.carriage_return) {
+						self.line.clear();
+					}
+					if self.line.len() == MAX_LINE {
+						self.line.drain(..MAX_LINE / 2);
+					}
+					self.line.push(byte);
+				},
+			}
+		}
+	}
+
+	/// The last line, if it looks like a prompt.
+	fn prompt(&self) -> Option<String> {
+		let (line, partial) = if self.line.trim_ascii().is_empty() {
+			(&self.last_line, false)
+		} else {
+			(&self.line, true)
+		};
+		let line = String::from_utf8_lossy(line).trim().to_string();
+		looks_like_prompt(&line, partial).then_some(line)
+	}
+}
+
+/// Whether `line` asks for input. A `partial` line (not ended by a newline)
+/// ending in `:` or `?` is taken as a prompt on its own.
+fn looks_like_prompt(line: &str, partial: bool) -> bool {
+	let lower = line.to_lowercase();
+	let compact: String = lower.chars().filter(|c| !c.is_whitespace()).collect();
+	if CHOICES.iter().any(|choice| compact.contains(choice))
+		|| KEY_PRESS.iter().any(|phrase| lower.contains(phrase))
+	{
+		return true;
+	}
+	(lower.ends_with(':') || lower.ends_with('?'))
+		&& (partial || PROMPT_WORDS.iter().any(|word| lower.contains(word)))
+}
+
+/// Watches a command's output for prompts.
+pub struct PromptWatch {
+	tail:   Mutex<Tail>,
+	output: Notify,
+}
+
+impl PromptWatch {
+	pub fn new() -> Arc<Self> {
+		Arc::new(Self {
+			tail:   Mutex::new(Tail {
+				line:            Vec::new(),
+				last_line:       Vec::new(),
+				carriage_return: false,
+				ansi:            AnsiStripper::default(),
+				last_output:     Instant::now(),
+			}),
+			output: Notify::new(),
+		})
+	}
+
+	/// Record output written by the command.
+	pub fn record(&self, bytes: &[u8]) {
+		let mut tail = self.tail.lock();
+		tail.push(bytes);
+		tail.last_output = Instant::now();
+		self.output.notify_one();
+	}
+
+	/// Report prompts to `callback` until aborted.
+	pub async fn run(
+		self: Arc<Self>,
+		idle: Duration,
+		tracker: Arc<SpawnTracker>,
+		callback: Arc<OutputCallback>,
+	) {
+		// Output time of the silence already reported.
+		let mut reported = None;
+		loop {
+			let (last_output, prompt) = {
+				let tail = self.tail.lock();
+				(tail.last_output, tail.prompt())
+			};
+			let silent = last_output.elapsed();
+			if let Some(remaining) = idle.checked_sub(silent)
+				&& !remaining.is_zero()
+			{
+				time::sleep(remaining).await;
+				continue;
+			}
+			if reported != Some(last_output)
+				&& let Some(prompt) = prompt
+				&& tracker.has_live_process()
+			{
+				let event = ShellInputPrompt {
+					prompt:  redact::redact_text(prompt),
+					idle_ms: silent.as_secs_f64() * 1000.0,
+				};
+				callback.call(Ok(Either4::C(event)), ThreadsafeFunctionCallMode::NonBlocking);
+				reported = Some(last_output);
+			}
+			// Wait for more output, or look again later in case a process starts.
+			tokio::select! {
+				() = self.output.notified() => {},
+				() = time::sleep(idle) => {},
+			}
+		}
+	}
+}
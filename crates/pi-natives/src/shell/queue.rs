@@ -0,0 +1,89 @@
+//! Gate limiting how many shell executions run at once.
+//!
+//! Every `executeShell` call and `Shell.run` command takes a slot before it
+//! starts; once `setMaxConcurrentExecutions` slots are taken, further
+//! executions wait in first-come order until one is released.
+
+use std::{collections::VecDeque, mem, sync::LazyLock};
+
+use napi::tokio::sync::oneshot;
+use napi_derive::napi;
+use parking_lot::Mutex;
+
+use super::executions::ExecutionProgress;
+
+static GATE: LazyLock<Mutex<Gate>> = LazyLock::new(|| Mutex::new(Gate::default()));
+
+#[derive(Default)]
+struct Gate {
+	/// Maximum running executions, or 0 for no limit.
+	limit:   usize,
+	running: usize,
+	waiting: VecDeque<oneshot::Sender<Slot>>,
+}
+
+impl Gate {
+	const fn has_room(&self) -> bool {
+		self.limit == 0 || self.running < self.limit
+	}
+
+	/// Hand free slots to waiting executions, skipping ones that gave up.
+	fn dispatch(&mut self) {
+		while self.has_room()
+			&& let Some(waiter) = self.waiting.pop_front()
+		{
+			self.running += 1;
+			if let Err(slot) = waiter.send(Slot(())) {
+				self.running -= 1;
+				// Dropping it here would re-enter the gate lock.
+				mem::forget(slot);
+			}
+		}
+	}
+}
+
+/// A taken execution slot, released when dropped.
+pub struct Slot(());
+
+impl Drop for Slot {
+	fn drop(&mut self) {
+		let mut gate = GATE.lock();
+		gate.running -= 1;
+		gate.dispatch();
+	}
+}
+
+/// Take a slot, waiting behind earlier executions when none is free.
+///
+/// `progress` reports the execution as queued while it waits.
+pub async fn acquire(progress: &ExecutionProgress) -> Slot {
+	loop {
+		let waiter = {
+			let mut gate = GATE.lock();
+			if gate.waiting.is_empty() && gate.has_room() {
+				gate.running += 1;
+				return Slot(());
+			}
+			let (tx, rx) = oneshot::channel();
+			gate.waiting.push_back(tx);
+			rx
+		};
+		progress.set_queued(true);
+		if let Ok(slot) = waiter.await {
+			progress.set_queued(false);
+			return slot;
+		}
+	}
+}
+
+/// Limit how many `executeShell` calls and `Shell.run` commands run at once.
+///
+/// Executions beyond the limit wait in first-come order and are reported as
+/// `queued` by `listShellExecutions`. Lowering the limit lets running
+/// executions finish; `0` or no value removes the limit.
+#[napi(js_name = "setMaxConcurrentExecutions")]
+pub fn set_max_concurrent_executions(max: Option<u32>) {
+	let mut gate = GATE.lock();
+	gate.limit = max.unwrap_or(0) as usize;
+	gate.dispatch();
+}
@@ -0,0 +1,304 @@
+//! Quoting of arguments and construction of command lines.
+//!
+//! # Overview
+//! Produces command strings that a shell of the dialect reads back as the
+//! original arguments.
+//!
+//! - **POSIX**: words made only of safe characters are left bare; all others
+//!   are single-quoted, which every POSIX shell reads literally, so control
+//!   characters and newlines are kept as they are
+//! - **PowerShell**: single-quoted (typographic quotes are doubled like `'`),
+//!   or double-quoted with backtick escapes when they contain control
+//!   characters, so the command stays on one line; programs are invoked with
+//!   the call operator `&`
+//!
+//! # Example
+//! ```ignore
+//! // JS: shellQuote(["git", "commit", "-m", "it's done"]) === "git commit -m 'it'\\''s done'"
+//! ```
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use napi::{Error, Result};
+use napi_derive::napi;
+
+/// Characters that never need quoting in a POSIX shell word.
+const POSIX_SAFE: &str = "_@%+=:,./-";
+/// Characters that never need quoting in a PowerShell argument.
+const POWERSHELL_SAFE: &str = "_./:\\-";
+/// Words a POSIX shell treats specially in command position.
+const RESERVED: &[&str] = &[
+	"!", "[[", "]]", "{", "}", "case", "coproc", "do", "done", "elif", "else", "esac", "fi", "for",
+	"function", "if", "in", "select", "then", "time", "until", "while",
+];
+
+/// Command language to quote for.
+#[napi]
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShellDialect {
+	/// POSIX shells (bash, brush, zsh, ...).
+	#[default]
+	Posix      = 1,
+	/// Windows PowerShell and PowerShell 7.
+	PowerShell = 2,
+}
+
+/// Options for `buildCommand`.
+#[napi(object)]
+pub struct BuildCommandOptions {
+	/// Program to run.
+	pub program: String,
+	/// Arguments to pass.
+	pub args:    Option<Vec<String>>,
+	/// Environment variables to set for the program (for PowerShell, for
+	/// the rest of the session).
+	pub env:     Option<BTreeMap<String, String>>,
+	/// Directory to change to first; the command does not run if that fails.
+	pub cwd:     Option<String>,
+	/// Command language (default: POSIX).
+	pub dialect: Option<ShellDialect>,
+}
+
+fn is_posix_bare(arg: &str) -> bool {
+	!arg.is_empty()
+		&& arg
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || POSIX_SAFE.contains(c))
+}
+
+fn posix_quote(arg: &str, out: &mut String) -> Result<()> {
+	if arg.contains('\0') {
+		return Err(Error::from_reason("Failed to quote argument: POSIX shells cannot pass NUL"));
+	}
+	if is_posix_bare(arg) {
+		out.push_str(arg);
+	} else {
+		out.push('\'');
+		out.push_str(&arg.replace('\'', r"'\''"));
+		out.push('\'');
+	}
+	Ok(())
+}
+
+fn powershell_quote(arg: &str, out: &mut String) {
+	if !arg.is_empty()
+		&& !arg.starts_with('-')
+		&& arg
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || POWERSHELL_SAFE.contains(c))
+	{
+		out.push_str(arg);
+	} else if arg.chars().any(char::is_control) {
+		out.push('"');
+		for c in arg.chars() {
+			match c {
+				'\n' => out.push_str("`n"),
+				'\r' => out.push_str("`r"),
+				'\t' => out.push_str("`t"),
+				'\0' => out.push_str("`0"),
+				'`' | '$' | '"' | '\u{201C}' | '\u{201D}' | '\u{201E}' => {
+					out.push('`');
+					out.push(c);
+				},
+				c if c.is_control() => {
+					let _ = write!(out, "$([char]0x{:04x})", u32::from(c));
+				},
+				c => out.push(c),
+			}
+		}
+		out.push('"');
+	} else {
+		out.push('\'');
+		for c in arg.chars() {
+			if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+				out.push(c);
+			}
+			out.push(c);
+		}
+		out.push('\'');
+	}
+}
+
+fn quote(arg: &str, dialect: ShellDialect, out: &mut String) -> Result<()> {
+	match dialect {
+		ShellDialect::Posix => posix_quote(arg, out)?,
+		ShellDialect::PowerShell => powershell_quote(arg, out),
+	}
+	Ok(())
+}
+
+fn quote_all(args: &[String], dialect: ShellDialect, out: &mut String) -> Result<()> {
+	for arg in args {
+		if !out.is_empty() {
+			out.push(' ');
+		}
+		quote(arg, dialect, out)?;
+	}
+	Ok(())
+}
+
+fn is_env_name(name: &str) -> bool {
+	name
+		.chars()
+		.next()
+		.is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+		&& name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn build(options: &BuildCommandOptions) -> Result<String> {
+	let dialect = options.dialect.unwrap_or_default();
+	let mut out = String::new();
+	if let Some(cwd) = &options.cwd {
+		match dialect {
+			ShellDialect::Posix => out.push_str("cd -- "),
+			ShellDialect::PowerShell => out.push_str("Set-Location -ErrorAction Stop -LiteralPath "),
+		}
+		quote(cwd, dialect, &mut out)?;
+		out.push_str(if dialect == ShellDialect::Posix {
+			" && "
+		} else {
+			"; "
+		});
+	}
+	for (name, value) in options.env.iter().flatten() {
+		if !is_env_name(name) {
+			return Err(Error::from_reason(format!("Invalid environment variable name: {name}")));
+		}
+		match dialect {
+			ShellDialect::Posix => {
+				let _ = write!(out, "{name}=");
+				quote(value, dialect, &mut out)?;
+				out.push(' ');
+			},
+			ShellDialect::PowerShell => {
+				let _ = write!(out, "$env:{name} = ");
+				quote(value, dialect, &mut out)?;
+				out.push_str("; ");
+			},
+		}
+	}
+	match dialect {
+		// Quoted, a reserved word or assignment-like name is run as a program.
+		ShellDialect::Posix
+			if is_posix_bare(&options.program)
+				&& (RESERVED.contains(&options.program.as_str()) || options.program.contains('=')) =>
+		{
+			let _ = write!(out, "'{}'", options.program);
+		},
+		ShellDialect::Posix => quote(&options.program, dialect, &mut out)?,
+		ShellDialect::PowerShell => {
+			out.push_str("& ");
+			quote(&options.program, dialect, &mut out)?;
+		},
+	}
+	quote_all(options.args.as_deref().unwrap_or_default(), dialect, &mut out)?;
+	Ok(out)
+}
+
+/// Quote `args` as separate words and join them with spaces.
+#[napi(js_name = "shellQuote")]
+pub fn shell_quote(args: Vec<String>, dialect: Option<ShellDialect>) -> Result<String> {
+	let mut out = String::new();
+	quote_all(&args, dialect.unwrap_or_default(), &mut out)?;
+	Ok(out)
+}
+
+/// Build a command line running `program` with `args`, after setting `env`
+/// and changing to `cwd`.
+#[napi(js_name = "buildCommand")]
+pub fn build_command(options: BuildCommandOptions) -> Result<String> {
+	build(&options)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TRICKY: &[&str] = &[
+		"plain",
+		"",
+		"two words",
+		"it's",
+		"'",
+		"\"double\"",
+		"$HOME `id` $(id)",
+		"back\\slash",
+		"glob*?[a]",
+		"line1\nline2",
+		"tab\tand\rreturn",
+		"\x1b[31mred\x1b[0m",
+		"-n",
+		"~user",
+		"#comment",
+		"a;b|c&d",
+		"ünïcødé",
+	];
+
+	fn posix(args: &[&str]) -> String {
+		let args: Vec<String> = args.iter().map(|arg| (*arg).to_string()).collect();
+		shell_quote(args, None).unwrap()
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_posix_round_trip() {
+		let quoted = posix(TRICKY);
+		let output = std::process::Command::new("sh")
+			.arg("-c")
+			.arg(format!("printf '%s\\0' {quoted}"))
+			.output()
+			.unwrap();
+		assert!(output.status.success());
+		let words: Vec<&str> = std::str::from_utf8(&output.stdout)
+			.unwrap()
+			.split_terminator('\0')
+			.collect();
+		assert_eq!(words, TRICKY);
+	}
+
+	#[test]
+	fn test_posix_quoting() {
+		assert_eq!(posix(&["git", "commit", "-m", "it's done"]), "git commit -m 'it'\\''s done'");
+		assert_eq!(posix(&["a\nb"]), "'a\nb'");
+		assert_eq!(posix(&[""]), "''");
+		assert!(shell_quote(vec!["a\0b".to_string()], None).is_err());
+	}
+
+	#[test]
+	fn test_powershell_quoting() {
+		let quote = |arg: &str| {
+			let mut out = String::new();
+			powershell_quote(arg, &mut out);
+			out
+		};
+		assert_eq!(quote("C:\\path\\file.txt"), "C:\\path\\file.txt");
+		assert_eq!(quote("-flag"), "'-flag'");
+		assert_eq!(quote("it's"), "'it''s'");
+		assert_eq!(quote("it\u{2019}s"), "'it\u{2019}\u{2019}s'");
+		assert_eq!(quote("$env:HOME"), "'$env:HOME'");
+		assert_eq!(quote("a\nb$c"), "\"a`nb`$c\"");
+		assert_eq!(quote("\x07"), "\"$([char]0x0007)\"");
+	}
+
+	#[test]
+	fn test_build_command() {
+		let options = BuildCommandOptions {
+			program: "if".to_string(),
+			args:    Some(vec!["a b".to_string()]),
+			env:     Some(BTreeMap::from([("LANG".to_string(), "C".to_string())])),
+			cwd:     Some("/tmp/dir x".to_string()),
+			dialect: None,
+		};
+		assert_eq!(build(&options).unwrap(), "cd -- '/tmp/dir x' && LANG=C 'if' 'a b'");
+		let options = BuildCommandOptions { dialect: Some(ShellDialect::PowerShell), ..options };
+		assert_eq!(
+			build(&options).unwrap(),
+			"Set-Location -ErrorAction Stop -LiteralPath '/tmp/dir x'; $env:LANG = C; & if 'a b'"
+		);
+		let options = BuildCommandOptions {
+			env: Some(BTreeMap::from([("BAD-NAME".to_string(), String::new())])),
+			..options
+		};
+		assert!(build(&options).is_err());
+	}
+}
@@ -0,0 +1,237 @@
+//! Redaction of streamed command output.
+//!
+//! `setRedactionRules` registers literal and regex patterns, and optionally
+//! the secret detectors behind `scanSecrets`, that are applied to the output
+//! of every command before it is passed to JS, including prompts reported
+//! with `promptIdleMs`.
+//!
+//! Output is redacted a line at a time so a secret split across reads is
+//! still matched: a partial line is held back until its newline arrives, the
+//! command goes quiet for [`FLUSH_DELAY`], or it grows past [`MAX_HOLD`]
+//! bytes. A private key block split across lines is redacted through its END
+//! line.
+
+use std::{
+	borrow::Cow,
+	sync::{Arc, LazyLock},
+	time::Duration,
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use parking_lot::RwLock;
+use regex::bytes::{Regex, RegexBuilder};
+
+use crate::secret_scan;
+
+/// How long a partial line is held back once output stops.
+pub const FLUSH_DELAY: Duration = Duration::from_millis(100);
+/// Longest partial line held back.
+const MAX_HOLD: usize = 8 * 1024;
+const DEFAULT_REPLACEMENT: &str = "[REDACTED]";
+const PRIVATE_KEY_RULE: &str = "private-key";
+
+static RULES: LazyLock<RwLock<Option<Arc<Rules>>>> = LazyLock::new(|| RwLock::new(None));
+static KEY_END: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"-----END[ A-Z0-9_-]{0,100}PRIVATE KEY(?: BLOCK)?-----")
+		.expect("valid key end pattern")
+});
+
+/// A pattern to redact from command output.
+#[napi(object)]
+pub struct RedactionRule {
+	/// Text to redact, or a regular expression with `regex`.
+	pub pattern:     String,
+	/// Treat `pattern` as a regular expression (default: false).
+	pub regex:       Option<bool>,
+	/// Match case-insensitively (default: false).
+	#[napi(js_name = "ignoreCase")]
+	pub ignore_case: Option<bool>,
+	/// Text replacing each match (default: "[REDACTED]").
+	pub replacement: Option<String>,
+}
+
+/// Options for `setRedactionRules`.
+#[napi(object)]
+#[derive(Default)]
+pub struct RedactionOptions {
+	/// Also redact secrets found by the `scanSecrets` detectors, as
+	/// `[REDACTED:<rule>]` (default: false).
+	#[napi(js_name = "detectSecrets")]
+	pub detect_secrets: Option<bool>,
+}
+
+struct Rules {
+	patterns:       Vec<(Regex, Vec<u8>)>,
+	detect_secrets: bool,
+}
+
+struct Span<'a> {
+	start:       usize,
+	end:         usize,
+	replacement: Cow<'a, [u8]>,
+}
+
+impl Rules {
+	/// `bytes` with every match replaced, and whether it ends inside a private
+	/// key block.
+	fn redact(&self, bytes: &[u8]) -> (Vec<u8>, bool) {
+		let mut spans = Vec::new();
+		for (regex, replacement) in &self.patterns {
+			spans.extend(
+				regex
+					.find_iter(bytes)
+					.filter(|found| !found.is_empty())
+					.map(|found| Span {
+						start:       found.start(),
+						end:         found.end(),
+						replacement: Cow::Borrowed(replacement.as_slice()),
+					}),
+			);
+		}
+		let mut open_key = false;
+		if self.detect_secrets
+			&& let Ok(text) = std::str::from_utf8(bytes)
+		{
+			for span in secret_scan::find_default(text) {
+				if span.rule == PRIVATE_KEY_RULE && !KEY_END.is_match(&bytes[span.start..span.end]) {
+					open_key = true;
+				}
+				spans.push(Span {
+					start:       span.start,
+					end:         span.end,
+					replacement: Cow::Owned(format!("[REDACTED:{}]", span.rule).into_bytes()),
+				});
+			}
+		}
+		if spans.is_empty() {
+			return (bytes.to_vec(), open_key);
+		}
+
+		// Overlapping matches are merged into the earliest one.
+		spans.sort_by_key(|span| (span.start, std::cmp::Reverse(span.end)));
+		let mut out = Vec::with_capacity(bytes.len());
+		let mut pos = 0;
+		for span in spans {
+			if span.start < pos {
+				pos = pos.max(span.end);
+				continue;
+			}
+			out.extend_from_slice(&bytes[pos..span.start]);
+			out.extend_from_slice(&span.replacement);
+			pos = span.end;
+		}
+		out.extend_from_slice(&bytes[pos..]);
+		(out, open_key)
+	}
+}
+
+/// Redacts one command's output stream.
+pub struct Redactor {
+	rules:   Arc<Rules>,
+	/// Partial line held back.
+	pending: Vec<u8>,
+	/// Inside a private key block whose start was already redacted.
+	in_key:  bool,
+}
+
+impl Redactor {
+	/// A redactor for the registered rules, if any are set.
+	pub fn current() -> Option<Self> {
+		let rules = RULES.read().clone()?;
+		Some(Self { rules, pending: Vec::new(), in_key: false })
+	}
+
+	/// Whether a partial line is held back.
+	pub const fn has_pending(&self) -> bool {
+		!self.pending.is_empty()
+	}
+
+	/// Add output and return the redacted complete lines.
+	pub fn push(&mut self, bytes: &[u8]) -> Vec<u8> {
+		self.pending.extend_from_slice(bytes);
+		let cut = match self.pending.iter().rposition(|&byte| byte == b'\n') {
+			Some(newline) => newline + 1,
+			None if self.pending.len() > MAX_HOLD => {
+				// Hold back the last character, which may be incomplete.
+				let mut cut = self.pending.len() - 1;
+				while cut > 0 && self.pending[cut] & 0xc0 == 0x80 {
+					cut -= 1;
+				}
+				cut
+			},
+			None => return Vec::new(),
+		};
+		let rest = self.pending.split_off(cut);
+		let ready = std::mem::replace(&mut self.pending, rest);
+		self.redact(&ready)
+	}
+
+	/// Return the redacted partial line held back.
+	pub fn flush(&mut self) -> Vec<u8> {
+		let ready = std::mem::take(&mut self.pending);
+		self.redact(&ready)
+	}
+
+	fn redact(&mut self, mut bytes: &[u8]) -> Vec<u8> {
+		if self.in_key {
+			let Some(end) = KEY_END.find(bytes) else {
+				return Vec::new();
+			};
+			bytes = &bytes[end.end()..];
+			self.in_key = false;
+		}
+		let (out, open_key) = self.rules.redact(bytes);
+		self.in_key = open_key;
+		out
+	}
+}
+
+/// Redact a single piece of text, such as a reported prompt.
+pub fn redact_text(text: String) -> String {
+	let Some(rules) = RULES.read().clone() else {
+		return text;
+	};
+	let (out, _) = rules.redact(text.as_bytes());
+	String::from_utf8(out).unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
+
+/// Set the patterns redacted from the output of every shell command,
+/// replacing earlier ones. Commands already running keep their rules.
+///
+/// An empty rule list without `detectSecrets` turns redaction off.
+///
+/// # Errors
+/// Returns an error for an empty pattern or an invalid regular expression;
+/// the previous rules stay in effect.
+#[napi(js_name = "setRedactionRules")]
+pub fn set_redaction_rules(
+	rules: Vec<RedactionRule>,
+	options: Option<RedactionOptions>,
+) -> Result<()> {
+	let detect_secrets = options.unwrap_or_default().detect_secrets.unwrap_or(false);
+	let mut patterns = Vec::with_capacity(rules.len());
+	for rule in rules {
+		if rule.pattern.is_empty() {
+			return Err(Error::from_reason("Redaction pattern must not be empty"));
+		}
+		let source = if rule.regex.unwrap_or(false) {
+			rule.pattern
+		} else {
+			regex::escape(&rule.pattern)
+		};
+		let regex = RegexBuilder::new(&source)
+			.case_insensitive(rule.ignore_case.unwrap_or(false))
+			.build()
+			.map_err(|err| {
+				Error::from_reason(format!("Invalid redaction pattern {source:?}: {err}"))
+			})?;
+		let replacement = rule
+			.replacement
+			.unwrap_or_else(|| DEFAULT_REPLACEMENT.to_string());
+		patterns.push((regex, replacement.into_bytes()));
+	}
+	*RULES.write() = (!patterns.is_empty() || detect_secrets)
+		.then(|| Arc::new(Rules { patterns, detect_secrets }));
+	Ok(())
+}
@@ -0,0 +1,331 @@
+//! Filesystem and network confinement for commands run by a shell execution.
+//!
+//! Commands may read system directories, the working directory and the
+//! configured `read` paths, may write only below the configured `write`
+//! paths, and have no network access unless it is allowed.
+//!
+//! # Platform Implementation
+//! - **Linux**: a Landlock ruleset built once per execution is applied in the
+//!   child between fork and exec; without network, a seccomp filter also
+//!   refuses internet and packet sockets (and `io_uring`, which can open them)
+//! - **macOS**: commands are launched through `sandbox-exec` with a generated
+//!   profile
+//! - **Windows**: not supported yet; requesting a sandbox fails the execution
+//!   rather than running commands unconfined (an `AppContainer` needs process
+//!   attributes at creation, which brush's `tokio::process` spawn cannot pass)
+//!
+//! Builtins run inside the host process and are not confined, but the files
+//! the shell itself opens for redirections and `source` are checked against
+//! the same allowlist.
+
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use brush_core::SpawnHooks;
+use napi::{Error, Result};
+use napi_derive::napi;
+
+/// Filesystem and network confinement for commands spawned by a shell
+/// execution.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct ShellSandbox {
+	/// Extra paths commands may read. System directories and the working
+	/// directory are always readable.
+	pub read:    Option<Vec<String>>,
+	/// Paths commands may read and write, including everything below them.
+	pub write:   Option<Vec<String>>,
+	/// Allow network access (default: false).
+	pub network: Option<bool>,
+}
+
+#[cfg(target_os = "linux")]
+const SYSTEM_READ: &[&str] = &[
+	"/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/libx32", "/etc", "/opt", "/nix", "/proc",
+	"/sys", "/dev",
+];
+#[cfg(target_os = "macos")]
+const SYSTEM_READ: &[&str] = &[
+	"/usr",
+	"/bin",
+	"/sbin",
+	"/opt",
+	"/System",
+	"/Library",
+	"/Applications",
+	"/private/etc",
+	"/private/var/db",
+	"/private/var/select",
+	"/dev",
+];
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const SYSTEM_READ: &[&str] = &[];
+
+#[cfg(unix)]
+const SYSTEM_WRITE: &[&str] = &["/dev/null", "/dev/zero", "/dev/full", "/dev/tty"];
+#[cfg(not(unix))]
+const SYSTEM_WRITE: &[&str] = &[];
+
+/// Canonical paths commands may read and write below.
+struct Allowlist {
+	read:  Vec<PathBuf>,
+	write: Vec<PathBuf>,
+}
+
+#[cfg_attr(
+	not(any(target_os = "linux", target_os = "macos")),
+	allow(dead_code, reason = "sandboxing is unsupported on this platform")
+)]
+impl Allowlist {
+	fn new(sandbox: &ShellSandbox, cwd: &Path) -> Result<Self> {
+		let canonical = |path: &str| {
+			fs::canonicalize(Path::new(path)).map_err(|err| {
+				Error::from_reason(format!("Failed to resolve sandbox path {path}: {err}"))
+			})
+		};
+		let mut read = vec![fs::canonicalize(cwd).map_err(|err| {
+			Error::from_reason(format!("Failed to resolve sandbox working directory: {err}"))
+		})?];
+		for path in sandbox.read.iter().flatten() {
+			read.push(canonical(path)?);
+		}
+		let mut write = Vec::new();
+		for path in sandbox.write.iter().flatten() {
+			write.push(canonical(path)?);
+		}
+		Ok(Self { read, write })
+	}
+
+	/// System paths that exist on this machine.
+	fn system(paths: &[&str]) -> impl Iterator<Item = PathBuf> {
+		paths
+			.iter()
+			.filter_map(|path| fs::canonicalize(Path::new(path)).ok())
+	}
+
+	/// Whether `path` may be opened for reading, or writing when `write`.
+	fn allows(&self, path: &Path, write: bool) -> bool {
+		// Files about to be created resolve through their parent.
+		let resolved = fs::canonicalize(path).or_else(|_| {
+			let name = path.file_name().ok_or(io::ErrorKind::NotFound)?;
+			let parent = path.parent().ok_or(io::ErrorKind::NotFound)?;
+			fs::canonicalize(parent).map(|parent| parent.join(name))
+		});
+		let Ok(resolved) = resolved else {
+			return false;
+		};
+		let inside = |root: &PathBuf| resolved.starts_with(root);
+		if self.write.iter().any(inside) || Self::system(SYSTEM_WRITE).any(|root| inside(&root)) {
+			return true;
+		}
+		!write
+			&& (self.read.iter().any(inside) || Self::system(SYSTEM_READ).any(|root| inside(&root)))
+	}
+}
+
+/// Spawn hooks that confine every external command to a [`ShellSandbox`].
+pub struct SandboxHooks {
+	allowlist: Allowlist,
+	#[cfg(target_os = "linux")]
+	ruleset:   std::os::fd::OwnedFd,
+	#[cfg(target_os = "linux")]
+	seccomp:   Option<seccompiler::BpfProgram>,
+	#[cfg(target_os = "macos")]
+	rules:     String,
+}
+
+impl SandboxHooks {
+	/// Prepare the confinement for `sandbox`, with `cwd` as the execution's
+	/// working directory.
+	#[cfg(target_os = "linux")]
+	pub fn new(sandbox: &ShellSandbox, cwd: &Path) -> Result<Self> {
+		let allowlist = Allowlist::new(sandbox, cwd)?;
+		let ruleset = landlock_ruleset(&allowlist)?;
+		let seccomp = (!sandbox.network.unwrap_or(false))
+			.then(network_filter)
+			.transpose()?;
+		Ok(Self { allowlist, ruleset, seccomp })
+	}
+
+	/// Prepare the confinement for `sandbox`, with `cwd` as the execution's
+	/// working directory.
+	#[cfg(target_os = "macos")]
+	pub fn new(sandbox: &ShellSandbox, cwd: &Path) -> Result<Self> {
+		let allowlist = Allowlist::new(sandbox, cwd)?;
+		let rules = seatbelt_rules(&allowlist, sandbox.network.unwrap_or(false));
+		Ok(Self { allowlist, rules })
+	}
+
+	/// Seatbelt rules confining commands to the sandbox.
+	#[cfg(target_os = "macos")]
+	pub fn seatbelt_rules(&self) -> &str {
+		&self.rules
+	}
+
+	/// Prepare the confinement for `sandbox`, with `cwd` as the execution's
+	/// working directory.
+	#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+	pub fn new(_sandbox: &ShellSandbox, _cwd: &Path) -> Result<Self> {
+		Err(Error::from_reason("Sandboxing is not supported on this platform"))
+	}
+}
+
+impl SpawnHooks for SandboxHooks {
+	fn before_open(&self, path: &Path, write: bool) -> io::Result<()> {
+		if self.allowlist.allows(path, write) {
+			Ok(())
+		} else {
+			Err(io::ErrorKind::PermissionDenied.into())
+		}
+	}
+
+	#[cfg(target_os = "linux")]
+	fn before_spawn(&self, cmd: &mut std::process::Command) {
+		use std::os::{fd::AsRawFd, unix::process::CommandExt};
+
+		let ruleset = self.ruleset.as_raw_fd();
+		let seccomp = self.seccomp.clone();
+		// SAFETY: the closure only issues prctl, landlock and seccomp syscalls
+		// on data prepared before the fork, and allocates nothing.
+		unsafe {
+			cmd.pre_exec(move || restrict_self(ruleset, seccomp.as_deref()));
+		}
+	}
+}
+
+/// Landlock ruleset for `allowlist`. The returned descriptor is close-on-exec,
+/// so it reaches each child only until its exec.
+#[cfg(target_os = "linux")]
+fn landlock_ruleset(allowlist: &Allowlist) -> Result<std::os::fd::OwnedFd> {
+	use landlock::{
+		ABI, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, path_beneath_rules,
+	};
+
+	let abi = ABI::V5;
+	let fail = |err: landlock::RulesetError| {
+		Error::from_reason(format!("Failed to create sandbox ruleset: {err}"))
+	};
+	let system_read: Vec<PathBuf> = Allowlist::system(SYSTEM_READ).collect();
+	let system_write: Vec<PathBuf> = Allowlist::system(SYSTEM_WRITE).collect();
+	let ruleset = Ruleset::default()
+		.handle_access(AccessFs::from_all(abi))
+		.and_then(Ruleset::create)
+		.and_then(|ruleset| {
+			ruleset.add_rules(path_beneath_rules(
+				system_read.iter().chain(&allowlist.read),
+				AccessFs::from_read(abi),
+			))
+		})
+		.and_then(|ruleset| {
+			ruleset.add_rules(path_beneath_rules(
+				system_write.iter().chain(&allowlist.write),
+				AccessFs::from_all(abi),
+			))
+		})
+		.map_err(fail)?;
+	Option::from(ruleset).ok_or_else(|| {
+		Error::from_reason("Sandboxing requires Landlock, which this kernel does not support")
+	})
+}
+
+/// Seccomp filter refusing internet and packet sockets with `EACCES`.
+#[cfg(target_os = "linux")]
+fn network_filter() -> Result<seccompiler::BpfProgram> {
+	use std::collections::BTreeMap;
+
+	use seccompiler::{
+		SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter, SeccompRule,
+	};
+
+	let fail = |err: seccompiler::BackendError| {
+		Error::from_reason(format!("Failed to build network filter: {err}"))
+	};
+	let arch = std::env::consts::ARCH.try_into().map_err(fail)?;
+	let socket_rules = [libc::AF_INET, libc::AF_INET6, libc::AF_PACKET]
+		.into_iter()
+		.map(|family| {
+			let condition =
+				SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, family as u64)?;
+			SeccompRule::new(vec![condition])
+		})
+		.collect::<std::result::Result<Vec<_>, _>>()
+		.map_err(fail)?;
+	let rules =
+		BTreeMap::from([(libc::SYS_socket, socket_rules), (libc::SYS_io_uring_setup, vec![])]);
+	let filter = SeccompFilter::new(
+		rules,
+		SeccompAction::Allow,
+		SeccompAction::Errno(libc::EACCES as u32),
+		arch,
+	)
+	.map_err(fail)?;
+	filter.try_into().map_err(fail)
+}
+
+/// Restrict the calling (child) process to the prepared ruleset and filter.
+#[cfg(target_os = "linux")]
+fn restrict_self(ruleset: i32, seccomp: Option<&[seccompiler::sock_filter]>) -> io::Result<()> {
+	// SAFETY: plain prctl/syscall invocations with integer arguments.
+	unsafe {
+		if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0
+			|| libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0) != 0
+		{
+			return Err(io::Error::last_os_error());
+		}
+	}
+	match seccomp.map(seccompiler::apply_filter) {
+		None | Some(Ok(())) => Ok(()),
+		Some(Err(seccompiler::Error::Prctl(err) | seccompiler::Error::Seccomp(err))) => Err(err),
+		Some(Err(_)) => Err(io::Error::from_raw_os_error(libc::EINVAL)),
+	}
+}
+
+/// Wrapper running a command under `sandbox-exec` with a profile that allows
+/// everything `rules` do not deny. Later rules take precedence.
+#[cfg(target_os = "macos")]
+pub fn sandbox_exec(rules: &[&str]) -> Vec<std::ffi::OsString> {
+	let mut profile = String::from("(version 1)\n(allow default)\n");
+	profile.extend(rules.iter().copied());
+	vec!["/usr/bin/sandbox-exec".into(), "-p".into(), profile.into()]
+}
+
+/// Seatbelt rules for `allowlist`: deny reading file contents, writing, and
+/// (unless `network`) non-local networking outside it.
+#[cfg(target_os = "macos")]
+fn seatbelt_rules(allowlist: &Allowlist, network: bool) -> String {
+	use std::fmt::Write as _;
+
+	let subpaths = |paths: &mut dyn Iterator<Item = &Path>| {
+		paths.fold(String::new(), |mut out, path| {
+			let path = path
+				.to_string_lossy()
+				.replace('\\', "\\\\")
+				.replace('"', "\\\"");
+			let _ = write!(out, " (subpath \"{path}\")");
+			out
+		})
+	};
+	let system_read: Vec<PathBuf> = Allowlist::system(SYSTEM_READ).collect();
+	let system_write: Vec<PathBuf> = Allowlist::system(SYSTEM_WRITE).collect();
+	let read = subpaths(
+		&mut system_read
+			.iter()
+			.chain(&allowlist.read)
+			.map(PathBuf::as_path),
+	);
+	let write = subpaths(
+		&mut system_write
+			.iter()
+			.chain(&allowlist.write)
+			.map(PathBuf::as_path),
+	);
+	let mut rules = String::from("(deny file-read* file-write*)\n(allow file-read-metadata)\n");
+	let _ = writeln!(rules, "(allow file-read*{read})");
+	let _ = writeln!(rules, "(allow file-read* file-write*{write})");
+	if !network {
+		rules.push_str("(deny network*)\n(allow network* (remote unix-socket))\n");
+	}
+	rules
+}
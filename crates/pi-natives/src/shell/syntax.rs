@@ -0,0 +1,131 @@
+//! Syntax checking of shell input, without executing it.
+//!
+//! # Overview
+//! Parses a script with brush's parser, the one sessions run it with, and
+//! reports the first syntax error with its span and an excerpt of the line
+//! marked with carets, so a generated script can be fixed before it runs.
+//! Unterminated quotes and here-documents are reported where they open; a
+//! script that ends in the middle of a construct is reported at its end. The
+//! bodies of command substitutions are parsed when they run, not here.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const error = await native.checkShellSyntax("if true; then\n  echo hi\n");
+//! // error?.excerpt === "3 | \n  | ^"
+//! ```
+
+use std::fmt::Write as _;
+
+use brush_parser::{ParseError, ParserOptions, SourceInfo, TokenizerError};
+use napi_derive::napi;
+
+use crate::task;
+
+/// First syntax error in a script, from `checkShellSyntax`.
+#[napi(object)]
+pub struct ShellSyntaxError {
+	/// What is wrong, like ``syntax error near unexpected token `fi'``.
+	pub message:    String,
+	/// 1-based line the error starts on.
+	pub line:       u32,
+	/// 1-based column, in characters, the error starts at.
+	pub column:     u32,
+	/// 1-based line the error ends on.
+	#[napi(js_name = "endLine")]
+	pub end_line:   u32,
+	/// 1-based column just past the error.
+	#[napi(js_name = "endColumn")]
+	pub end_column: u32,
+	/// The line, numbered, with the error marked by carets below it.
+	pub excerpt:    String,
+}
+
+/// Line and column just past the last character of `source`.
+fn end_of(source: &str) -> (usize, usize) {
+	let last = source.rsplit('\n').next().unwrap_or_default();
+	(source.matches('\n').count() + 1, last.chars().count() + 1)
+}
+
+/// First `line L col C` position in a tokenizer error's text.
+fn line_col(text: &str) -> Option<(usize, usize)> {
+	let (line, rest) = text.strip_prefix("line ")?.split_once(" col ")?;
+	let column = rest.split(|char: char| !char.is_ascii_digit()).next()?;
+	Some((line.parse().ok()?, column.parse().ok()?))
+}
+
+/// `line` of `source`, numbered, with carets under `column..end_column`.
+fn excerpt(source: &str, line: usize, column: usize, end_column: usize) -> String {
+	let text = source.split('\n').nth(line - 1).unwrap_or_default();
+	let text = text.strip_suffix('\r').unwrap_or(text);
+	let number = line.to_string();
+	let gutter = " ".repeat(number.len());
+	let mut out = format!("{number} | {text}\n{gutter} | ");
+	// Keep tabs so the carets line up however they are rendered.
+	for char in text.chars().take(column - 1) {
+		out.push(if char == '\t' { '\t' } else { ' ' });
+	}
+	let _ = write!(out, "{}", "^".repeat(end_column.saturating_sub(column).max(1)));
+	out
+}
+
+fn check(source: &str) -> Option<ShellSyntaxError> {
+	let source_info = SourceInfo { source: String::from("main") };
+	let err = brush_parser::Parser::new(source.as_bytes(), &ParserOptions::default(), &source_info)
+		.parse_program()
+		.err()?;
+	let (message, start, end) = match err {
+		ParseError::ParsingNearToken(token) => {
+			let location = token.location();
+			let text = match token.to_str() {
+				"\n" => "newline",
+				text => text,
+			};
+			(
+				format!("syntax error near unexpected token `{text}'"),
+				(location.start.line, location.start.column),
+				(location.end.line, location.end.column),
+			)
+		},
+		ParseError::ParsingAtEndOfInput => {
+			let end = end_of(source);
+			(String::from("syntax error: unexpected end of input"), end, end)
+		},
+		ParseError::Tokenizing { inner, position } => {
+			// Point at what was left open rather than where that was noticed.
+			let opened = match &inner {
+				TokenizerError::UnterminatedSingleQuote(at)
+				| TokenizerError::UnterminatedAnsiCQuote(at)
+				| TokenizerError::UnterminatedDoubleQuote(at)
+				| TokenizerError::UnterminatedBackquote(at)
+				| TokenizerError::UnterminatedExtendedGlob(at) => Some((at.line, at.column)),
+				TokenizerError::UnterminatedHereDocuments(_, found) => line_col(found),
+				_ => None,
+			};
+			let start = opened
+				.or_else(|| position.map(|position| (position.line, position.column)))
+				.unwrap_or_else(|| end_of(source));
+			(format!("syntax error: {inner}"), start, (start.0, start.1 + 1))
+		},
+	};
+	// A span across lines is marked to the end of its first line.
+	let end_column = if end.0 == start.0 { end.1 } else { usize::MAX };
+	let line_len = source
+		.split('\n')
+		.nth(start.0 - 1)
+		.map_or(0, |text| text.trim_end_matches('\r').chars().count());
+	Some(ShellSyntaxError {
+		message,
+		line: start.0 as u32,
+		column: start.1 as u32,
+		end_line: end.0 as u32,
+		end_column: end.1 as u32,
+		excerpt: excerpt(source, start.0, start.1, end_column.min(line_len + 1).max(start.1 + 1)),
+	})
+}
+
+/// Parse `command` and report its first syntax error, or `null` if it
+/// parses, without running anything.
+#[napi(js_name = "checkShellSyntax")]
+pub fn check_shell_syntax(command: String) -> task::Async<Option<ShellSyntaxError>> {
+	task::blocking("check_shell_syntax", (), move |_| Ok(check(&command)))
+}
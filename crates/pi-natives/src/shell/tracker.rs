@@ -0,0 +1,273 @@
+//! Tracks the processes brush spawns for a single execution so cancellation
+//! can reach the whole command, not just its direct child.
+//!
+//! On cancellation brush only kills the process it is waiting on, and a
+//! non-interactive brush shell never creates process groups of its own, so
+//! grandchildren and pipeline members would be left running.
+//!
+//! # Platform Implementation
+//! - **Unix**: every external command leads a new process group; cancelling
+//!   kills the descendant tree of each spawned command, then its whole group
+//!   (catching members already re-parented away)
+//! - **Windows**: every external command is assigned to a Job Object that
+//!   stands in for its process group (see [`ps::create_process_group`]);
+//!   cancelling terminates the jobs
+//!
+//! With [`ps::enable_subreaper`], orphans double-forked by a command are
+//! adopted by us and keep their process group, so the group kill still
+//! reaches them; their zombies are reaped when the execution ends.
+
+#[cfg(target_os = "macos")]
+use std::ffi::OsString;
+use std::{
+	io,
+	path::Path,
+	process::{Command, ExitStatus},
+	sync::Arc,
+};
+
+use brush_core::SpawnHooks;
+use parking_lot::Mutex;
+
+use super::{
+	cgroup::{Cgroup, ShellResourceUsage},
+	exit::{self, ShellTermination},
+	limits::LimitHooks,
+	network::NetworkHooks,
+	priority::PriorityHooks,
+	sandbox::SandboxHooks,
+};
+use crate::ps;
+
+const SIGKILL: i32 = 9;
+
+/// A process spawned by brush, with the group it was placed in.
+struct Spawned {
+	pid:  i32,
+	pgid: Option<i32>,
+}
+
+/// Spawn hooks that record every external command and apply resource limits,
+/// the execution's cgroup, its priority, sandboxing and the network policy.
+pub struct SpawnTracker {
+	limits:   Option<LimitHooks>,
+	priority: Option<PriorityHooks>,
+	cgroup:   Option<Cgroup>,
+	sandbox:  Option<SandboxHooks>,
+	network:  Option<NetworkHooks>,
+	spawned:  Mutex<Vec<Spawned>>,
+	/// Exit statuses of reaped commands, in the order they were reaped.
+	exits:    Mutex<Vec<(i32, ExitStatus)>>,
+	/// CPU time of reaped children when the tracker was created (unix).
+	cpu_base: Option<ps::CpuTime>,
+	/// OOM kills in our memory cgroup when the tracker was created (Linux).
+	oom_base: Option<u64>,
+}
+
+impl SpawnTracker {
+	/// Create a tracker that also applies `limits`, `priority`, `cgroup`,
+	/// `sandbox` and `network` to spawned commands.
+	pub fn new(
+		limits: Option<LimitHooks>,
+		priority: Option<PriorityHooks>,
+		cgroup: Option<Cgroup>,
+		sandbox: Option<SandboxHooks>,
+		network: Option<NetworkHooks>,
+	) -> Arc<Self> {
+		Arc::new(Self {
+			limits,
+			priority,
+			cgroup,
+			sandbox,
+			network,
+			spawned: Mutex::new(Vec::new()),
+			exits: Mutex::new(Vec::new()),
+			cpu_base: ps::children_cpu_time(),
+			oom_base: exit::oom_kill_count(),
+		})
+	}
+
+	/// CPU time used by the tracked commands so far.
+	///
+	/// With a cgroup this is read from it exactly. Otherwise, on unix it is the
+	/// growth in CPU time of reaped children since the tracker was created,
+	/// which includes unrelated children reaped in the meantime; on Windows it
+	/// is read from each command's Job Object.
+	pub fn cpu_time(&self) -> Option<ps::CpuTime> {
+		if let Some(cpu) = self.cgroup.as_ref().and_then(Cgroup::cpu_time) {
+			return Some(cpu);
+		}
+		if let Some(base) = self.cpu_base {
+			return ps::children_cpu_time().map(|now| now.since(base));
+		}
+		if cfg!(unix) {
+			return None;
+		}
+		let spawned = self.spawned.lock();
+		let groups = spawned.iter().filter_map(|process| process.pgid);
+		Some(
+			groups
+				.filter_map(ps::process_group_cpu_time)
+				.fold(ps::CpuTime::default(), |total, cpu| total + cpu),
+		)
+	}
+
+	/// The signal that ended the command: the last tracked process to die of
+	/// a signal, when the command's `exit_code` reports that signal.
+	pub fn termination(&self, exit_code: i32) -> Option<ShellTermination> {
+		let exits = self.exits.lock();
+		exits
+			.iter()
+			.rev()
+			.find_map(|(pid, status)| ShellTermination::from_status(*pid, status))
+			.filter(|termination| exit_code == 128 + termination.signal)
+	}
+
+	/// Whether the OOM killer killed a process of the command.
+	///
+	/// The execution's own cgroup counts exactly the command's kills. With
+	/// this process's memory cgroup this also sees untracked descendants (and
+	/// unrelated processes in the same cgroup); otherwise the kernel log is
+	/// searched for the tracked processes that died of `SIGKILL`.
+	pub fn oom_killed(&self) -> bool {
+		if let Some(kills) = self.cgroup.as_ref().and_then(Cgroup::oom_kills) {
+			return kills > 0;
+		}
+		if let Some(base) = self.oom_base
+			&& let Some(count) = exit::oom_kill_count()
+		{
+			return count > base;
+		}
+		let killed: Vec<i32> = self
+			.exits
+			.lock()
+			.iter()
+			.filter_map(|(pid, status)| ShellTermination::from_status(*pid, status))
+			.filter(|termination| termination.signal == SIGKILL)
+			.map(|termination| termination.pid)
+			.collect();
+		exit::oom_killed_any(&killed)
+	}
+
+	/// Peak usage of the execution's cgroup, if it has one.
+	pub fn usage(&self) -> Option<ShellResourceUsage> {
+		self.cgroup.as_ref().map(Cgroup::usage)
+	}
+
+	/// Whether any tracked process is still running.
+	pub fn has_live_process(&self) -> bool {
+		self
+			.spawned
+			.lock()
+			.iter()
+			.any(|process| ps::is_alive(process.pid))
+	}
+
+	/// Tracked processes that are still running.
+	pub fn live_pids(&self) -> Vec<i32> {
+		let spawned = self.spawned.lock();
+		spawned
+			.iter()
+			.map(|process| process.pid)
+			.filter(|&pid| ps::is_alive(pid))
+			.collect()
+	}
+
+	/// Kill every tracked process with its descendants and process group.
+	///
+	/// Trees are walked first, while the spawned pids still anchor them;
+	/// group kills then reach members that were already orphaned, and the
+	/// cgroup kill those that left their group.
+	pub fn kill_all(&self) {
+		// Never signal our own group, in case a command failed to get its own.
+		let own_pgid = ps::process_group_id(std::process::id() as i32);
+		let spawned = self.spawned.lock();
+		for process in spawned.iter() {
+			// Skip pids that have since been reaped and reused by another process.
+			if process.pgid.is_some() && ps::process_group_id(process.pid) != process.pgid {
+				continue;
+			}
+			ps::kill_tree(process.pid, SIGKILL);
+		}
+		for pgid in spawned.iter().filter_map(|process| process.pgid) {
+			if Some(pgid) != own_pgid {
+				ps::kill_process_group(pgid, SIGKILL);
+			}
+		}
+		if let Some(cgroup) = &self.cgroup {
+			cgroup.kill();
+		}
+	}
+}
+
+impl SpawnHooks for SpawnTracker {
+	/// One `sandbox-exec` profile for both the sandbox and the network policy,
+	/// with the sandbox's rules last so its denials win.
+	#[cfg(target_os = "macos")]
+	fn command_wrapper(&self) -> Option<Vec<OsString>> {
+		let rules = [
+			self.network.as_ref().map(NetworkHooks::seatbelt_rules),
+			self.sandbox.as_ref().map(SandboxHooks::seatbelt_rules),
+		];
+		let rules: Vec<&str> = rules.into_iter().flatten().collect();
+		(!rules.is_empty()).then(|| super::sandbox::sandbox_exec(&rules))
+	}
+
+	fn before_open(&self, path: &Path, write: bool) -> io::Result<()> {
+		self
+			.sandbox
+			.as_ref()
+			.map_or(Ok(()), |sandbox| sandbox.before_open(path, write))
+	}
+
+	fn before_spawn(&self, cmd: &mut Command) {
+		#[cfg(unix)]
+		std::os::unix::process::CommandExt::process_group(cmd, 0);
+		if let Some(cgroup) = &self.cgroup {
+			cgroup.before_spawn(cmd);
+		}
+		if let Some(limits) = &self.limits {
+			limits.before_spawn(cmd);
+		}
+		if let Some(priority) = &self.priority {
+			priority.before_spawn(cmd);
+		}
+		if let Some(sandbox) = &self.sandbox {
+			sandbox.before_spawn(cmd);
+		}
+		if let Some(network) = &self.network {
+			network.before_spawn(cmd);
+		}
+	}
+
+	fn after_spawn(&self, pid: i32) {
+		if let Some(limits) = &self.limits {
+			limits.after_spawn(pid);
+		}
+		if let Some(priority) = &self.priority {
+			priority.after_spawn(pid);
+		}
+		let pgid = ps::create_process_group(pid);
+		self.spawned.lock().push(Spawned { pid, pgid });
+	}
+
+	fn after_exit(&self, pid: i32, status: &ExitStatus) {
+		self.exits.lock().push((pid, *status));
+	}
+}
+
+impl Drop for SpawnTracker {
+	fn drop(&mut self) {
+		for pgid in self
+			.spawned
+			.get_mut()
+			.iter()
+			.filter_map(|process| process.pgid)
+		{
+			ps::release_process_group(pgid);
+		}
+		if ps::is_subreaper() {
+			ps::reap_zombies();
+		}
+	}
+}
@@ -0,0 +1,82 @@
+//! Word expansion without execution.
+//!
+//! # Overview
+//! Expands text the way a fresh session expands a command's arguments: the
+//! text is split into words, and each gets tilde, parameter (`$HOME`,
+//! `${VAR:-default}`), arithmetic and brace expansion, field splitting and
+//! quote removal. Config values that mention variables then resolve exactly
+//! as they would in the shell. Command substitutions fail instead of running,
+//! operators such as `;` are rejected, and pathname expansion is off;
+//! `expandGlob` expands paths.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.expandShellWords("~/.config/{a,b} \"$NAME x\"", { env: { NAME: "pi" } })
+//! ```
+
+use std::collections::HashMap;
+
+use brush_parser::Token;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use super::{ShellConfig, create_session};
+use crate::task;
+
+/// Options for `expandShellWords`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ExpandShellWordsOptions {
+	/// Variables to set on top of the process environment.
+	pub env: Option<HashMap<String, String>>,
+	/// Working directory, for `$PWD` and `~+` (default: the process's).
+	pub cwd: Option<String>,
+}
+
+/// Expand `text` into words as the shell would, without running commands.
+///
+/// # Errors
+/// Returns an error for a command substitution, an operator such as `;` or
+/// `|`, a bad substitution or arithmetic, or a `cwd` that does not exist.
+#[napi(js_name = "expandShellWords")]
+pub fn expand_shell_words(
+	env: &Env,
+	text: String,
+	options: Option<ExpandShellWordsOptions>,
+) -> Result<PromiseRaw<'_, Vec<String>>> {
+	let options = options.unwrap_or_default();
+	task::future(env, "shell.expand_words", async move {
+		let config =
+			ShellConfig { session_env: options.env, snapshot_path: None, builtin_policy: None };
+		let mut session = create_session(&config).await?;
+		let shell = &mut session.shell;
+		if let Some(cwd) = options.cwd.as_deref() {
+			shell
+				.set_working_dir(cwd)
+				.map_err(|err| Error::from_reason(format!("Failed to set cwd: {err}")))?;
+		}
+		shell.options.disable_filename_globbing = true;
+		let mut params = shell.default_exec_params();
+		params.forbid_command_substitution = true;
+		let tokens = brush_parser::tokenize_str(&text)
+			.map_err(|err| Error::from_reason(format!("Failed to expand {text}: {err}")))?;
+		let mut words = Vec::new();
+		for token in tokens {
+			match token {
+				Token::Word(word, _) => words.extend(
+					shell
+						.full_expand_and_split_string(&params, &word)
+						.await
+						.map_err(|err| Error::from_reason(format!("Failed to expand {text}: {err}")))?,
+				),
+				Token::Operator(op, _) if op == "\n" => {},
+				Token::Operator(op, _) => {
+					return Err(Error::from_reason(format!(
+						"Failed to expand {text}: unexpected operator `{op}`"
+					)));
+				},
+			}
+		}
+		Ok(words)
+	})
+}
@@ -0,0 +1,714 @@
+//! Session persistence in SQLite.
+//!
+//! # Overview
+//! Conversation events, tool call records and artifacts are appended to a
+//! single database (`sessions.db` in `$PI_CODING_AGENT_DIR`, falling back to
+//! `~/.omp/agent`) instead of one JSON file per session. Every event gets a
+//! per-session sequence number, so a session can be read back in order or
+//! resumed after a known event.
+//!
+//! Connections are opened once per database path and kept for the process
+//! lifetime. The database runs in WAL mode, so a crash loses at most the
+//! event being written and never corrupts earlier ones.
+//!
+//! Event payloads and tool names are indexed with FTS5 for
+//! `searchSessions`; the index is kept in sync by triggers.
+//!
+//! Detached shell executions (`executeShellDetached`) are recorded in the
+//! same database, so they can be found again after a restart, and so are the
+//! audit log of shell executions (`queryAuditLog`), the items moved to the
+//! trash by `trashPath`, the journal of file edits (`undoOperation`) and
+//! directory checkpoints (`createCheckpoint`).
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.appendEvent({ sessionId, kind: "message", role: "user", content: JSON.stringify(msg) })
+//! // JS: const events = await native.querySession(sessionId, { afterSeq: 120 })
+//! ```
+
+use std::{
+	collections::HashMap,
+	env, fs,
+	path::PathBuf,
+	sync::{Arc, LazyLock},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter, types::Value};
+
+use crate::task;
+
+/// Schema migrations; the database's `PRAGMA user_version` is the number
+/// already applied.
+const MIGRATIONS: [&str; 7] = [
+	"
+CREATE TABLE sessions (
+	id         TEXT PRIMARY KEY,
+	created_at INTEGER NOT NULL,
+	updated_at INTEGER NOT NULL,
+	last_seq   INTEGER NOT NULL
+);
+CREATE TABLE events (
+	id         INTEGER PRIMARY KEY,
+	session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+	seq        INTEGER NOT NULL,
+	kind       TEXT NOT NULL,
+	role       TEXT,
+	tool_name  TEXT,
+	content    TEXT NOT NULL,
+	data       BLOB,
+	created_at INTEGER NOT NULL,
+	UNIQUE (session_id, seq)
+);
+CREATE INDEX sessions_updated_at ON sessions (updated_at);
+",
+	"
+CREATE VIRTUAL TABLE events_fts USING fts5 (
+	content, tool_name,
+	content = 'events', content_rowid = 'id', tokenize = 'unicode61 remove_diacritics 2'
+);
+CREATE TRIGGER events_fts_insert AFTER INSERT ON events BEGIN
+	INSERT INTO events_fts (rowid, content, tool_name) VALUES (new.id, new.content, new.tool_name);
+END;
+CREATE TRIGGER events_fts_delete AFTER DELETE ON events BEGIN
+	INSERT INTO events_fts (events_fts, rowid, content, tool_name)
+	VALUES ('delete', old.id, old.content, old.tool_name);
+END;
+INSERT INTO events_fts (events_fts) VALUES ('rebuild');
+CREATE INDEX events_created_at ON events (created_at);
+",
+	"
+CREATE TABLE detached_executions (
+	id         INTEGER PRIMARY KEY,
+	command    TEXT NOT NULL,
+	cwd        TEXT,
+	pid        INTEGER NOT NULL,
+	log_path   TEXT NOT NULL,
+	started_at INTEGER NOT NULL
+);
+",
+	"
+CREATE TABLE audit_log (
+	id           INTEGER PRIMARY KEY,
+	command      TEXT NOT NULL,
+	cwd          TEXT,
+	env_hash     TEXT NOT NULL,
+	exit_code    INTEGER,
+	outcome      TEXT NOT NULL,
+	duration_ms  REAL NOT NULL,
+	output_bytes INTEGER NOT NULL,
+	started_at   INTEGER NOT NULL
+);
+CREATE INDEX audit_log_started_at ON audit_log (started_at);
+CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log BEGIN
+	SELECT RAISE(ABORT, 'audit log is append-only');
+END;
+CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log BEGIN
+	SELECT RAISE(ABORT, 'audit log is append-only');
+END;
+",
+	"
+CREATE TABLE trash (
+	id            INTEGER PRIMARY KEY,
+	original_path TEXT NOT NULL,
+	trashed_path  TEXT NOT NULL,
+	info_path     TEXT,
+	trashed_at    INTEGER NOT NULL,
+	restored_at   INTEGER
+);
+",
+	"
+CREATE TABLE operations (
+	id         INTEGER PRIMARY KEY,
+	label      TEXT,
+	created_at INTEGER NOT NULL,
+	undone_at  INTEGER
+);
+CREATE TABLE operation_files (
+	id           INTEGER PRIMARY KEY,
+	operation_id INTEGER NOT NULL REFERENCES operations(id) ON DELETE CASCADE,
+	path         TEXT NOT NULL,
+	before       TEXT,
+	after_hash   TEXT,
+	recorded_at  INTEGER NOT NULL
+);
+CREATE INDEX operation_files_operation_id ON operation_files (operation_id);
+",
+	"
+CREATE TABLE checkpoints (
+	id         INTEGER PRIMARY KEY,
+	root       TEXT NOT NULL,
+	label      TEXT,
+	gitignore  INTEGER NOT NULL,
+	created_at INTEGER NOT NULL
+);
+CREATE TABLE checkpoint_files (
+	checkpoint_id INTEGER NOT NULL REFERENCES checkpoints(id) ON DELETE CASCADE,
+	path          TEXT NOT NULL,
+	kind          TEXT NOT NULL,
+	artifact      TEXT,
+	link          TEXT,
+	mode          INTEGER,
+	size          INTEGER NOT NULL,
+	mtime         INTEGER NOT NULL,
+	PRIMARY KEY (checkpoint_id, path)
+);
+CREATE INDEX checkpoints_root ON checkpoints (root);
+",
+];
+
+const DEFAULT_SEARCH_LIMIT: u32 = 20;
+
+/// Words of context around the matches in a search snippet.
+const SNIPPET_TOKENS: u32 = 24;
+
+static STORES: LazyLock<Mutex<HashMap<PathBuf, Arc<Mutex<Connection>>>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// An event to append to a session.
+#[napi(object)]
+pub struct SessionEvent {
+	/// Session the event belongs to; created on its first event.
+	#[napi(js_name = "sessionId")]
+	pub session_id: String,
+	/// Event kind, e.g. `message`, `tool_call` or `artifact`.
+	pub kind:       String,
+	/// Message role, for message events.
+	pub role:       Option<String>,
+	/// Tool name, for tool call events.
+	#[napi(js_name = "toolName")]
+	pub tool_name:  Option<String>,
+	/// Event payload, usually JSON.
+	pub content:    String,
+	/// Binary payload, e.g. artifact bytes.
+	pub data:       Option<Uint8Array>,
+	/// Event time in milliseconds since the epoch (default: now).
+	pub timestamp:  Option<f64>,
+}
+
+/// An event read back from a session.
+#[napi(object)]
+pub struct StoredSessionEvent {
+	/// Database-wide event id.
+	pub id:         i64,
+	/// Session the event belongs to.
+	#[napi(js_name = "sessionId")]
+	pub session_id: String,
+	/// 1-based position of the event in its session.
+	pub seq:        i64,
+	/// Event kind.
+	pub kind:       String,
+	/// Message role, for message events.
+	pub role:       Option<String>,
+	/// Tool name, for tool call events.
+	#[napi(js_name = "toolName")]
+	pub tool_name:  Option<String>,
+	/// Event payload.
+	pub content:    String,
+	/// Binary payload.
+	pub data:       Option<Buffer>,
+	/// Event time in milliseconds since the epoch.
+	pub timestamp:  f64,
+}
+
+/// Position of an appended event.
+#[napi(object)]
+pub struct AppendedEvent {
+	/// Database-wide event id.
+	pub id:  i64,
+	/// 1-based position of the event in its session.
+	pub seq: i64,
+}
+
+/// Options selecting the session database.
+#[napi(object)]
+#[derive(Default)]
+pub struct SessionStoreOptions {
+	/// Database file (default: `$PI_CODING_AGENT_DIR/sessions.db`, falling back
+	/// to `~/.omp/agent`).
+	#[napi(js_name = "dbPath")]
+	pub db_path: Option<String>,
+}
+
+/// Options for `querySession`.
+#[napi(object)]
+#[derive(Default)]
+pub struct QuerySessionOptions {
+	/// Database file (default: the session store, as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:   Option<String>,
+	/// Only return events of these kinds.
+	pub kinds:     Option<Vec<String>>,
+	/// Only return events after this sequence number.
+	#[napi(js_name = "afterSeq")]
+	pub after_seq: Option<i64>,
+	/// Maximum number of events to return (default: all).
+	pub limit:     Option<u32>,
+}
+
+/// Options for `vacuumSessions`.
+#[napi(object)]
+#[derive(Default)]
+pub struct VacuumSessionsOptions {
+	/// Database file (default: the session store, as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:       Option<String>,
+	/// Delete sessions without events for this many milliseconds.
+	#[napi(js_name = "olderThanMs")]
+	pub older_than_ms: Option<f64>,
+	/// Delete these sessions.
+	#[napi(js_name = "sessionIds")]
+	pub session_ids:   Option<Vec<String>>,
+}
+
+/// Result of `vacuumSessions`.
+#[napi(object)]
+pub struct VacuumSessionsResult {
+	/// Sessions deleted.
+	#[napi(js_name = "deletedSessions")]
+	pub deleted_sessions: u32,
+	/// Events deleted with them.
+	#[napi(js_name = "deletedEvents")]
+	pub deleted_events:   u32,
+	/// Database size in bytes before compaction.
+	#[napi(js_name = "bytesBefore")]
+	pub bytes_before:     f64,
+	/// Database size in bytes after compaction.
+	#[napi(js_name = "bytesAfter")]
+	pub bytes_after:      f64,
+}
+
+/// Time window for `searchSessions`, in milliseconds since the epoch.
+#[napi(object)]
+pub struct DateRange {
+	/// Earliest event time, inclusive.
+	pub from: Option<f64>,
+	/// Latest event time, inclusive.
+	pub to:   Option<f64>,
+}
+
+/// Options for `searchSessions`.
+#[napi(object)]
+#[derive(Default)]
+pub struct SearchSessionsOptions {
+	/// Database file (default: the session store, as in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:         Option<String>,
+	/// Maximum number of hits (default: 20).
+	pub limit:           Option<u32>,
+	/// Only match events in this time window.
+	#[napi(js_name = "dateRange")]
+	pub date_range:      Option<DateRange>,
+	/// Only match events of these kinds.
+	pub kinds:           Option<Vec<String>>,
+	/// Only match events of this session.
+	#[napi(js_name = "sessionId")]
+	pub session_id:      Option<String>,
+	/// Treat `query` as an FTS5 query expression (phrases, `OR`, `NOT`,
+	/// prefixes) instead of plain words that must all appear (default: false).
+	pub raw:             Option<bool>,
+	/// Marker placed before matched words in snippets (default: `**`).
+	#[napi(js_name = "highlightStart")]
+	pub highlight_start: Option<String>,
+	/// Marker placed after matched words in snippets (default: `**`).
+	#[napi(js_name = "highlightEnd")]
+	pub highlight_end:   Option<String>,
+}
+
+/// An event matching a `searchSessions` query.
+#[napi(object)]
+pub struct SessionSearchHit {
+	/// Database-wide event id.
+	pub id:         i64,
+	/// Session the event belongs to.
+	#[napi(js_name = "sessionId")]
+	pub session_id: String,
+	/// Position of the event in its session.
+	pub seq:        i64,
+	/// Event kind.
+	pub kind:       String,
+	/// Message role, for message events.
+	pub role:       Option<String>,
+	/// Tool name, for tool call events.
+	#[napi(js_name = "toolName")]
+	pub tool_name:  Option<String>,
+	/// Excerpt of the event payload with the matched words highlighted.
+	pub snippet:    String,
+	/// Event time in milliseconds since the epoch.
+	pub timestamp:  f64,
+	/// BM25 relevance; lower is more relevant.
+	pub rank:       f64,
+}
+
+pub(crate) fn sql_error(err: rusqlite::Error) -> Error {
+	Error::from_reason(format!("Session store error: {err}"))
+}
+
+pub(crate) fn now_ms() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |elapsed| i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX))
+}
+
+/// The agent directory, `PI_CODING_AGENT_DIR` or `~/.omp/agent` (honoring
+/// `PI_CONFIG_DIR`), read at each call so `setAgentDir()` on the JS side,
+/// which sets the variable, takes effect. `None` without a home directory.
+pub(crate) fn agent_dir() -> Option<PathBuf> {
+	if let Some(dir) = env::var_os("PI_CODING_AGENT_DIR").filter(|dir| !dir.is_empty()) {
+		return Some(PathBuf::from(dir));
+	}
+	let home = env::var_os("HOME")
+		.or_else(|| env::var_os("USERPROFILE"))
+		.filter(|home| !home.is_empty())?;
+	let config_dir = env::var_os("PI_CONFIG_DIR")
+		.filter(|dir| !dir.is_empty())
+		.unwrap_or_else(|| ".omp".into());
+	Some(PathBuf::from(home).join(config_dir).join("agent"))
+}
+
+/// `sessions.db` in the agent directory.
+fn default_db_path() -> Result<PathBuf> {
+	agent_dir()
+		.map(|dir| dir.join("sessions.db"))
+		.ok_or_else(|| Error::from_reason("Cannot locate session store: no home directory"))
+}
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+	conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+	conn.pragma_update(None, "synchronous", "NORMAL")?;
+	conn.pragma_update(None, "foreign_keys", true)?;
+	let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+	for (applied, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+		let tx = conn.unchecked_transaction()?;
+		tx.execute_batch(migration)?;
+		tx.pragma_update(None, "user_version", applied + 1)?;
+		tx.commit()?;
+	}
+	Ok(())
+}
+
+/// The database file: `db_path`, or the default one.
+pub(crate) fn resolve_db_path(db_path: Option<String>) -> Result<PathBuf> {
+	db_path.map_or_else(default_db_path, |path| Ok(PathBuf::from(path)))
+}
+
+/// Shared connection for the database at `db_path`, opened and migrated on
+/// first use.
+pub(crate) fn open_store(db_path: Option<String>) -> Result<Arc<Mutex<Connection>>> {
+	let path = resolve_db_path(db_path)?;
+	let mut stores = STORES.lock();
+	if let Some(store) = stores.get(&path) {
+		return Ok(Arc::clone(store));
+	}
+	if let Some(parent) = path
+		.parent()
+		.filter(|parent| !parent.as_os_str().is_empty())
+	{
+		fs::create_dir_all(parent).map_err(|err| {
+			Error::from_reason(format!("Failed to create {}: {err}", parent.display()))
+		})?;
+	}
+	let conn = Connection::open(&path).map_err(|err| {
+		Error::from_reason(format!("Failed to open session store {}: {err}", path.display()))
+	})?;
+	migrate(&conn).map_err(sql_error)?;
+	let store = Arc::new(Mutex::new(conn));
+	stores.insert(path, Arc::clone(&store));
+	Ok(store)
+}
+
+fn database_bytes(conn: &Connection) -> rusqlite::Result<f64> {
+	let pages: i64 = conn.pragma_query_value(None, "page_count", |row| row.get(0))?;
+	let page_size: i64 = conn.pragma_query_value(None, "page_size", |row| row.get(0))?;
+	Ok((pages * page_size) as f64)
+}
+
+/// Event fields moved off the JS thread.
+struct NewEvent {
+	session_id: String,
+	kind:       String,
+	role:       Option<String>,
+	tool_name:  Option<String>,
+	content:    String,
+	data:       Option<Vec<u8>>,
+	timestamp:  i64,
+}
+
+fn append_event_sync(conn: &mut Connection, event: &NewEvent) -> rusqlite::Result<AppendedEvent> {
+	let tx = conn.transaction()?;
+	let seq = tx
+		.query_row("SELECT last_seq FROM sessions WHERE id = ?1", [&event.session_id], |row| {
+			row.get::<_, i64>(0)
+		})
+		.optional()?
+		.unwrap_or(0)
+		+ 1;
+	tx.execute(
+		"INSERT INTO sessions (id, created_at, updated_at, last_seq) VALUES (?1, ?2, ?2, ?3)
+		 ON CONFLICT (id) DO UPDATE SET updated_at = max(updated_at, ?2), last_seq = ?3",
+		params![event.session_id, event.timestamp, seq],
+	)?;
+	tx.execute(
+		"INSERT INTO events (session_id, seq, kind, role, tool_name, content, data, created_at)
+		 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+		params![
+			event.session_id,
+			seq,
+			event.kind,
+			event.role,
+			event.tool_name,
+			event.content,
+			event.data,
+			event.timestamp
+		],
+	)?;
+	let id = tx.last_insert_rowid();
+	tx.commit()?;
+	Ok(AppendedEvent { id, seq })
+}
+
+fn query_session_sync(
+	conn: &Connection,
+	session_id: String,
+	options: QuerySessionOptions,
+) -> rusqlite::Result<Vec<StoredSessionEvent>> {
+	let mut sql = String::from(
+		"SELECT id, session_id, seq, kind, role, tool_name, content, data, created_at
+		 FROM events WHERE session_id = ? AND seq > ?",
+	);
+	let mut args = vec![Value::Text(session_id), Value::Integer(options.after_seq.unwrap_or(0))];
+	if let Some(kinds) = options.kinds {
+		sql.push_str(" AND kind IN (");
+		sql.push_str(&vec!["?"; kinds.len()].join(", "));
+		sql.push(')');
+		args.extend(kinds.into_iter().map(Value::Text));
+	}
+	sql.push_str(" ORDER BY seq LIMIT ?");
+	args.push(Value::Integer(options.limit.map_or(-1, i64::from)));
+
+	let mut stmt = conn.prepare(&sql)?;
+	let rows = stmt.query_map(params_from_iter(args), |row| {
+		Ok(StoredSessionEvent {
+			id:         row.get(0)?,
+			session_id: row.get(1)?,
+			seq:        row.get(2)?,
+			kind:       row.get(3)?,
+			role:       row.get(4)?,
+			tool_name:  row.get(5)?,
+			content:    row.get(6)?,
+			data:       row.get::<_, Option<Vec<u8>>>(7)?.map(Buffer::from),
+			timestamp:  row.get::<_, i64>(8)? as f64,
+		})
+	})?;
+	rows.collect()
+}
+
+fn vacuum_sessions_sync(
+	conn: &mut Connection,
+	older_than_ms: Option<f64>,
+	session_ids: Vec<String>,
+) -> rusqlite::Result<VacuumSessionsResult> {
+	let bytes_before = database_bytes(conn)?;
+	let tx = conn.transaction()?;
+	let mut doomed = session_ids;
+	if let Some(older_than_ms) = older_than_ms {
+		let cutoff = now_ms() - older_than_ms as i64;
+		let mut stmt = tx.prepare("SELECT id FROM sessions WHERE updated_at < ?1")?;
+		let stale = stmt.query_map([cutoff], |row| row.get::<_, String>(0))?;
+		for id in stale {
+			doomed.push(id?);
+		}
+	}
+	doomed.sort_unstable();
+	doomed.dedup();
+
+	let mut deleted_sessions = 0;
+	let mut deleted_events = 0;
+	for id in &doomed {
+		deleted_events += tx.execute("DELETE FROM events WHERE session_id = ?1", [id])?;
+		deleted_sessions += tx.execute("DELETE FROM sessions WHERE id = ?1", [id])?;
+	}
+	tx.commit()?;
+
+	conn.execute_batch("VACUUM")?;
+	conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+	Ok(VacuumSessionsResult {
+		deleted_sessions: u32::try_from(deleted_sessions).unwrap_or(u32::MAX),
+		deleted_events: u32::try_from(deleted_events).unwrap_or(u32::MAX),
+		bytes_before,
+		bytes_after: database_bytes(conn)?,
+	})
+}
+
+/// FTS5 query matching every word of `query`, each quoted so punctuation and
+/// operators in it are taken literally.
+fn plain_query(query: &str) -> String {
+	query
+		.split_whitespace()
+		.map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+fn search_sessions_sync(
+	conn: &Connection,
+	query: &str,
+	options: SearchSessionsOptions,
+) -> rusqlite::Result<Vec<SessionSearchHit>> {
+	let start = options.highlight_start.unwrap_or_else(|| "**".to_string());
+	let end = options.highlight_end.unwrap_or_else(|| "**".to_string());
+	let mut sql = String::from(
+		"SELECT e.id, e.session_id, e.seq, e.kind, e.role, e.tool_name,
+		        snippet(events_fts, 0, ?, ?, '…', ?), e.created_at, events_fts.rank
+		 FROM events_fts JOIN events e ON e.id = events_fts.rowid
+		 WHERE events_fts MATCH ?",
+	);
+	let query = if options.raw.unwrap_or(false) {
+		query.to_string()
+	} else {
+		plain_query(query)
+	};
+	let mut args = vec![
+		Value::Text(start),
+		Value::Text(end),
+		Value::Integer(SNIPPET_TOKENS.into()),
+		Value::Text(query),
+	];
+	if let Some(range) = options.date_range {
+		if let Some(from) = range.from {
+			sql.push_str(" AND e.created_at >= ?");
+			args.push(Value::Integer(from as i64));
+		}
+		if let Some(to) = range.to {
+			sql.push_str(" AND e.created_at <= ?");
+			args.push(Value::Integer(to as i64));
+		}
+	}
+	if let Some(kinds) = options.kinds {
+		sql.push_str(" AND e.kind IN (");
+		sql.push_str(&vec!["?"; kinds.len()].join(", "));
+		sql.push(')');
+		args.extend(kinds.into_iter().map(Value::Text));
+	}
+	if let Some(session_id) = options.session_id {
+		sql.push_str(" AND e.session_id = ?");
+		args.push(Value::Text(session_id));
+	}
+	sql.push_str(" ORDER BY events_fts.rank LIMIT ?");
+	args.push(Value::Integer(options.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).into()));
+
+	let mut stmt = conn.prepare(&sql)?;
+	let rows = stmt.query_map(params_from_iter(args), |row| {
+		Ok(SessionSearchHit {
+			id:         row.get(0)?,
+			session_id: row.get(1)?,
+			seq:        row.get(2)?,
+			kind:       row.get(3)?,
+			role:       row.get(4)?,
+			tool_name:  row.get(5)?,
+			snippet:    row.get(6)?,
+			timestamp:  row.get::<_, i64>(7)? as f64,
+			rank:       row.get(8)?,
+		})
+	})?;
+	rows.collect()
+}
+
+/// Append an event to a session, creating the session on its first event.
+///
+/// # Errors
+/// Returns an error if the database cannot be opened or written.
+#[napi(js_name = "appendEvent")]
+pub fn append_event(
+	event: SessionEvent,
+	options: Option<SessionStoreOptions>,
+) -> task::Async<AppendedEvent> {
+	let db_path = options.unwrap_or_default().db_path;
+	let event = NewEvent {
+		session_id: event.session_id,
+		kind:       event.kind,
+		role:       event.role,
+		tool_name:  event.tool_name,
+		content:    event.content,
+		data:       event.data.map(|data| data.to_vec()),
+		timestamp:  event
+			.timestamp
+			.map_or_else(now_ms, |timestamp| timestamp as i64),
+	};
+	task::blocking("store.append_event", (), move |_| {
+		let store = open_store(db_path)?;
+		let mut conn = store.lock();
+		append_event_sync(&mut conn, &event).map_err(sql_error)
+	})
+}
+
+/// Read a session's events in order.
+///
+/// Returns an empty list for unknown sessions.
+///
+/// # Errors
+/// Returns an error if the database cannot be opened or read.
+#[napi(js_name = "querySession")]
+pub fn query_session(
+	session_id: String,
+	options: Option<QuerySessionOptions>,
+) -> task::Async<Vec<StoredSessionEvent>> {
+	let mut options = options.unwrap_or_default();
+	let db_path = options.db_path.take();
+	task::blocking("store.query_session", (), move |_| {
+		let store = open_store(db_path)?;
+		let conn = store.lock();
+		query_session_sync(&conn, session_id, options).map_err(sql_error)
+	})
+}
+
+/// Delete stale or given sessions with their events, then compact the
+/// database.
+///
+/// # Errors
+/// Returns an error if the database cannot be opened or written.
+#[napi(js_name = "vacuumSessions")]
+pub fn vacuum_sessions(
+	options: Option<VacuumSessionsOptions>,
+) -> task::Async<VacuumSessionsResult> {
+	let options = options.unwrap_or_default();
+	task::blocking("store.vacuum_sessions", (), move |_| {
+		let store = open_store(options.db_path)?;
+		let mut conn = store.lock();
+		vacuum_sessions_sync(
+			&mut conn,
+			options.older_than_ms,
+			options.session_ids.unwrap_or_default(),
+		)
+		.map_err(sql_error)
+	})
+}
+
+/// Full-text search over the payloads and tool names of all stored events,
+/// most relevant first.
+///
+/// Plain queries match events containing every word, ignoring case and
+/// diacritics.
+///
+/// # Errors
+/// Returns an error if the database cannot be opened or read, or a `raw`
+/// query is not valid FTS5 syntax.
+#[napi(js_name = "searchSessions")]
+pub fn search_sessions(
+	query: String,
+	options: Option<SearchSessionsOptions>,
+) -> task::Async<Vec<SessionSearchHit>> {
+	let mut options = options.unwrap_or_default();
+	let db_path = options.db_path.take();
+	task::blocking("store.search_sessions", (), move |_| {
+		if query.trim().is_empty() {
+			return Ok(Vec::new());
+		}
+		let store = open_store(db_path)?;
+		let conn = store.lock();
+		search_sessions_sync(&conn, &query, options).map_err(sql_error)
+	})
+}
@@ -0,0 +1,732 @@
+//! Workspace-wide symbol index built on tree-sitter outlines.
+//!
+//! # Overview
+//! `indexWorkspace` walks a directory (honoring `.gitignore`), outlines every
+//! supported source file with [`crate::syntax`], and keeps the flattened
+//! definitions in memory for `querySymbols`. Each indexed workspace is:
+//! - persisted to `~/.omp/agent/symbols/<root hash>.idx` (or `cachePath`), so
+//!   the next `indexWorkspace` only re-parses files whose size or mtime changed
+//! - kept current by a filesystem watcher that re-outlines changed files until
+//!   `closeWorkspaceIndex` is called
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.indexWorkspace("/repo");
+//! // JS: const [def] = await native.querySymbols("FooService", { kind: SymbolKind.Class });
+//! ```
+
+use std::{
+	collections::{BTreeMap, HashMap, HashSet},
+	fmt::Write as _,
+	fs,
+	path::{Path, PathBuf},
+	sync::{Arc, LazyLock, Weak, mpsc},
+	thread,
+	time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::{Mutex, RwLock};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::{
+	fs_cache::{build_walker, normalize_relative_path, resolve_search_path, should_skip_path},
+	ignore_rules, store,
+	syntax::{self, OutlineSymbol, SymbolKind},
+	task,
+};
+
+const DEFAULT_QUERY_LIMIT: u32 = 100;
+
+/// Larger files are almost always generated or minified; skip them.
+const MAX_INDEXED_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Quiet period before watched changes are re-indexed.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Re-index at least this often while changes keep arriving.
+const MAX_WATCH_BATCH: Duration = Duration::from_secs(2);
+
+/// First line of the on-disk index; bump when the format changes.
+const CACHE_HEADER: &str = "pi-symbols 1";
+
+static WORKSPACES: LazyLock<Mutex<HashMap<PathBuf, Arc<Workspace>>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Options for `indexWorkspace`.
+#[napi(object)]
+pub struct IndexWorkspaceOptions<'env> {
+	/// Keep the index current by watching the workspace (default: true).
+	pub watch:      Option<bool>,
+	/// Read and write the on-disk index (default: true).
+	pub persist:    Option<bool>,
+	/// Location of the on-disk index (default:
+	/// `~/.omp/agent/symbols/<root hash>.idx`).
+	#[napi(js_name = "cachePath")]
+	pub cache_path: Option<String>,
+	/// Include hidden files (default: false).
+	pub hidden:     Option<bool>,
+	/// Abort signal for cancelling the operation.
+	pub signal:     Option<Unknown<'env>>,
+	/// Timeout in milliseconds for the operation.
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+}
+
+/// Result of `indexWorkspace`.
+#[napi(object)]
+pub struct WorkspaceIndexStats {
+	/// Canonical workspace root.
+	pub root:       String,
+	/// Indexed source files.
+	pub files:      u32,
+	/// Indexed definitions.
+	pub symbols:    u32,
+	/// Files parsed by this call (new or changed since the last index).
+	pub parsed:     u32,
+	/// Files whose definitions were reused from the previous index.
+	pub reused:     u32,
+	/// Files dropped because they were deleted or are now ignored.
+	pub removed:    u32,
+	/// Where the index was written, absent when not persisted.
+	#[napi(js_name = "cachePath")]
+	pub cache_path: Option<String>,
+	/// Whether the workspace is being watched for changes.
+	pub watching:   bool,
+}
+
+/// Options for `querySymbols`.
+#[napi(object)]
+#[derive(Default)]
+pub struct QuerySymbolsOptions {
+	/// Only search this indexed workspace (default: all indexed workspaces).
+	pub root:  Option<String>,
+	/// Only return definitions of this kind.
+	pub kind:  Option<SymbolKind>,
+	/// Also match prefixes, substrings and subsequences of names, best first
+	/// (default: false, which matches whole names case-insensitively).
+	pub fuzzy: Option<bool>,
+	/// Maximum number of results (default: 100).
+	pub limit: Option<u32>,
+}
+
+/// A definition found by `querySymbols`.
+#[napi(object)]
+pub struct SymbolMatch {
+	/// Symbol name.
+	pub name:         String,
+	/// Kind of definition.
+	pub kind:         SymbolKind,
+	/// Name of the enclosing definition (e.g. the class of a method).
+	pub container:    Option<String>,
+	/// Declaration up to its body, on one line.
+	pub signature:    String,
+	/// Workspace root the file belongs to.
+	pub root:         String,
+	/// File path relative to `root`, using forward slashes.
+	pub path:         String,
+	/// First line of the definition, 1-indexed.
+	#[napi(js_name = "startLine")]
+	pub start_line:   u32,
+	/// Byte column where the definition starts, 1-indexed.
+	#[napi(js_name = "startColumn")]
+	pub start_column: u32,
+	/// Last line of the definition, 1-indexed.
+	#[napi(js_name = "endLine")]
+	pub end_line:     u32,
+	/// Byte column just past the end of the definition, 1-indexed.
+	#[napi(js_name = "endColumn")]
+	pub end_column:   u32,
+}
+
+#[derive(Clone)]
+struct IndexedSymbol {
+	name:         String,
+	kind:         SymbolKind,
+	container:    Option<String>,
+	signature:    String,
+	start_line:   u32,
+	start_column: u32,
+	end_line:     u32,
+	end_column:   u32,
+}
+
+#[derive(Clone)]
+struct IndexedFile {
+	mtime_ms: u64,
+	size:     u64,
+	symbols:  Vec<IndexedSymbol>,
+}
+
+/// Files keyed by forward-slash path relative to the workspace root.
+type FileMap = BTreeMap<String, IndexedFile>;
+
+struct Workspace {
+	root:       PathBuf,
+	cache_path: Option<PathBuf>,
+	hidden:     bool,
+	files:      RwLock<FileMap>,
+	watcher:    Mutex<Option<RecommendedWatcher>>,
+}
+
+/// A source file found by a walk, with the stamp used to detect changes.
+struct Candidate {
+	relative: String,
+	path:     PathBuf,
+	mtime_ms: u64,
+	size:     u64,
+}
+
+impl Workspace {
+	/// Whether a walked path belongs in the index.
+	fn accepts(&self, path: &Path) -> bool {
+		if should_skip_path(path, false) || !syntax::is_supported(path) {
+			return false;
+		}
+		// A walk rooted below a hidden directory does not filter it out.
+		self.hidden
+			|| !path
+				.strip_prefix(&self.root)
+				.unwrap_or(path)
+				.components()
+				.any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+	}
+
+	/// Index entry for `path`, if it is a file small enough to index.
+	fn candidate(&self, path: &Path, metadata: &fs::Metadata) -> Option<Candidate> {
+		if !metadata.is_file() || metadata.len() > MAX_INDEXED_FILE_BYTES {
+			return None;
+		}
+		Some(Candidate {
+			relative: normalize_relative_path(&self.root, path).into_owned(),
+			path:     path.to_path_buf(),
+			mtime_ms: metadata
+				.modified()
+				.ok()
+				.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+				.map_or(0, |duration| duration.as_millis() as u64),
+			size:     metadata.len(),
+		})
+	}
+
+	/// Supported, non-ignored files under `dir`.
+	fn walk(&self, dir: &Path, ct: &task::CancelToken) -> Result<Vec<Candidate>> {
+		let mut candidates = Vec::new();
+		for entry in build_walker(dir, self.hidden, true).build() {
+			ct.heartbeat()?;
+			let Ok(entry) = entry else { continue };
+			if !entry.file_type().is_some_and(|ty| ty.is_file()) || !self.accepts(entry.path()) {
+				continue;
+			}
+			if let Ok(metadata) = entry.metadata()
+				&& let Some(candidate) = self.candidate(entry.path(), &metadata)
+			{
+				candidates.push(candidate);
+			}
+		}
+		Ok(candidates)
+	}
+
+	/// Write the index to `cache_path`, returning whether it was written.
+	fn persist(&self) -> bool {
+		let Some(cache_path) = &self.cache_path else {
+			return false;
+		};
+		let contents = serialize(&self.files.read());
+		let temp = cache_path.with_extension("idx.tmp");
+		let written = cache_path
+			.parent()
+			.is_none_or(|dir| fs::create_dir_all(dir).is_ok())
+			&& fs::write(&temp, contents).is_ok()
+			&& fs::rename(&temp, cache_path).is_ok();
+		if !written {
+			let _ = fs::remove_file(&temp);
+		}
+		written
+	}
+
+	/// Re-index the files at or below `paths` after they changed on disk.
+	fn refresh(&self, paths: HashSet<PathBuf>) {
+		let ct = task::CancelToken::default();
+		// A walk only applies ignore rules below its start, so changed paths are
+		// checked against every ancestor's rules; paths outside the root are
+		// never indexed.
+		let rules = ignore_rules::Rules::discover(&self.root);
+		let ignored =
+			|path: &Path, is_dir| !path.starts_with(&self.root) || rules.matched(path, is_dir);
+		let mut found = Vec::new();
+		let mut gone = Vec::new();
+		for path in paths {
+			let metadata = fs::metadata(&path).ok();
+			let is_dir = metadata.as_ref().is_some_and(fs::Metadata::is_dir);
+			if ignored(&path, is_dir) {
+				// Deleted, or newly ignored: drop whatever was indexed there.
+				gone.push(normalize_relative_path(&self.root, &path).into_owned());
+			} else if is_dir {
+				found.extend(self.walk(&path, &ct).unwrap_or_default());
+			} else if let Some(candidate) = metadata
+				.filter(|_| self.accepts(&path))
+				.and_then(|metadata| self.candidate(&path, &metadata))
+			{
+				found.push(candidate);
+			} else {
+				gone.push(normalize_relative_path(&self.root, &path).into_owned());
+			}
+		}
+		let parsed = parse_candidates(found, &ct).unwrap_or_default();
+
+		let mut files = self.files.write();
+		for relative in gone {
+			// The path may have been a directory.
+			let prefix = format!("{relative}/");
+			files.retain(|path, _| *path != relative && !path.starts_with(&prefix));
+		}
+		files.extend(parsed);
+		drop(files);
+		self.persist();
+	}
+}
+
+fn to_u32(count: usize) -> u32 {
+	u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+/// Flatten an outline, recording each definition's immediate container.
+fn flatten(symbols: Vec<OutlineSymbol>, container: Option<&str>, out: &mut Vec<IndexedSymbol>) {
+	for symbol in symbols {
+		out.push(IndexedSymbol {
+			name:         symbol.name,
+			kind:         symbol.kind,
+			container:    container.map(str::to_string),
+			signature:    symbol.signature,
+			start_line:   symbol.start_line,
+			start_column: symbol.start_column,
+			end_line:     symbol.end_line,
+			end_column:   symbol.end_column,
+		});
+		let name = out.last().map(|symbol| symbol.name.clone());
+		flatten(symbol.children, name.as_deref(), out);
+	}
+}
+
+/// Outline candidates in parallel. Unreadable files are indexed as empty so
+/// they are not retried until they change.
+fn parse_candidates(
+	candidates: Vec<Candidate>,
+	ct: &task::CancelToken,
+) -> Result<Vec<(String, IndexedFile)>> {
+	let parsed = candidates
+		.into_par_iter()
+		.filter_map(|candidate| {
+			if ct.aborted() {
+				return None;
+			}
+			let mut symbols = Vec::new();
+			if let Ok(source) = fs::read(&candidate.path)
+				&& let Ok(outline) = syntax::outline_source(&candidate.path, source)
+			{
+				flatten(outline, None, &mut symbols);
+			}
+			Some((candidate.relative, IndexedFile {
+				mtime_ms: candidate.mtime_ms,
+				size: candidate.size,
+				symbols,
+			}))
+		})
+		.collect();
+	ct.heartbeat()?;
+	Ok(parsed)
+}
+
+/// Replace separators so a field fits on one tab-separated line.
+fn field(value: &str) -> String {
+	value.replace(['\t', '\n', '\r'], " ")
+}
+
+fn serialize(files: &FileMap) -> String {
+	let mut out = format!("{CACHE_HEADER}\n");
+	for (path, file) in files {
+		let _ = writeln!(out, "F\t{}\t{}\t{}", file.mtime_ms, file.size, field(path));
+		for symbol in &file.symbols {
+			let _ = writeln!(
+				out,
+				"S\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+				symbol.kind.name(),
+				symbol.start_line,
+				symbol.start_column,
+				symbol.end_line,
+				symbol.end_column,
+				field(&symbol.name),
+				field(symbol.container.as_deref().unwrap_or_default()),
+				field(&symbol.signature),
+			);
+		}
+	}
+	out
+}
+
+/// Parse an on-disk index; `None` if it is missing, outdated or corrupt.
+fn deserialize(contents: &str) -> Option<FileMap> {
+	let mut lines = contents.lines();
+	if lines.next()? != CACHE_HEADER {
+		return None;
+	}
+	let mut files = FileMap::new();
+	let mut current: Option<(String, IndexedFile)> = None;
+	for line in lines {
+		let mut fields = line.split('\t');
+		match fields.next()? {
+			"F" => {
+				let mtime_ms = fields.next()?.parse().ok()?;
+				let size = fields.next()?.parse().ok()?;
+				let path = fields.next()?.to_string();
+				if let Some((path, file)) =
+					current.replace((path, IndexedFile { mtime_ms, size, symbols: Vec::new() }))
+				{
+					files.insert(path, file);
+				}
+			},
+			"S" => {
+				let kind = SymbolKind::from_capture(fields.next()?)?;
+				let mut number = || fields.next()?.parse::<u32>().ok();
+				let (start_line, start_column, end_line, end_column) =
+					(number()?, number()?, number()?, number()?);
+				let name = fields.next()?.to_string();
+				let container = Some(fields.next()?)
+					.filter(|container| !container.is_empty())
+					.map(str::to_string);
+				let signature = fields.next()?.to_string();
+				current.as_mut()?.1.symbols.push(IndexedSymbol {
+					name,
+					kind,
+					container,
+					signature,
+					start_line,
+					start_column,
+					end_line,
+					end_column,
+				});
+			},
+			_ => return None,
+		}
+	}
+	if let Some((path, file)) = current {
+		files.insert(path, file);
+	}
+	Some(files)
+}
+
+/// `symbols/<root hash>.idx` in the agent directory.
+fn default_cache_path(root: &Path) -> Option<PathBuf> {
+	let digest = Sha256::digest(root.to_string_lossy().as_bytes());
+	let name = digest[..8].iter().fold(String::new(), |mut name, byte| {
+		let _ = write!(name, "{byte:02x}");
+		name
+	});
+	Some(
+		store::agent_dir()?
+			.join("symbols")
+			.join(format!("{name}.idx")),
+	)
+}
+
+/// Re-index batches of changed paths until the workspace is dropped.
+fn run_watcher(rx: &mpsc::Receiver<notify::Result<Event>>, workspace: &Weak<Workspace>) {
+	let collect = |paths: &mut HashSet<PathBuf>, result: notify::Result<Event>| {
+		if let Ok(event) = result
+			&& !matches!(event.kind, EventKind::Access(_))
+		{
+			paths.extend(event.paths);
+		}
+	};
+	while let Ok(first) = rx.recv() {
+		let started = Instant::now();
+		let mut paths = HashSet::new();
+		collect(&mut paths, first);
+		while started.elapsed() < MAX_WATCH_BATCH {
+			match rx.recv_timeout(WATCH_DEBOUNCE) {
+				Ok(result) => collect(&mut paths, result),
+				Err(mpsc::RecvTimeoutError::Timeout) => break,
+				Err(mpsc::RecvTimeoutError::Disconnected) => return,
+			}
+		}
+		let Some(workspace) = workspace.upgrade() else {
+			return;
+		};
+		if !paths.is_empty() {
+			workspace.refresh(paths);
+		}
+	}
+}
+
+fn start_watcher(workspace: &Arc<Workspace>) -> Result<RecommendedWatcher> {
+	let (tx, rx) = mpsc::channel();
+	let mut watcher = notify::recommended_watcher(move |result| {
+		let _ = tx.send(result);
+	})
+	.map_err(|err| Error::from_reason(format!("Failed to create watcher: {err}")))?;
+	watcher
+		.watch(&workspace.root, RecursiveMode::Recursive)
+		.map_err(|err| {
+			Error::from_reason(format!("Failed to watch {}: {err}", workspace.root.display()))
+		})?;
+	let weak = Arc::downgrade(workspace);
+	thread::Builder::new()
+		.name("pi-symbols".to_string())
+		.spawn(move || run_watcher(&rx, &weak))
+		.map_err(|err| Error::from_reason(format!("Failed to spawn watch thread: {err}")))?;
+	Ok(watcher)
+}
+
+struct IndexConfig {
+	root:       String,
+	watch:      bool,
+	persist:    bool,
+	cache_path: Option<String>,
+	hidden:     bool,
+}
+
+fn index_workspace_sync(
+	config: IndexConfig,
+	ct: &task::CancelToken,
+) -> Result<WorkspaceIndexStats> {
+	let root = resolve_search_path(&config.root)?;
+	let cache_path = if config.persist {
+		config
+			.cache_path
+			.map(PathBuf::from)
+			.or_else(|| default_cache_path(&root))
+	} else {
+		None
+	};
+
+	// Start from the live index when there is one, else from disk.
+	let previous = WORKSPACES
+		.lock()
+		.get(&root)
+		.map(|workspace| workspace.files.read().clone());
+	let mut files = previous
+		.or_else(|| {
+			let contents = fs::read_to_string(cache_path.as_ref()?).ok()?;
+			deserialize(&contents)
+		})
+		.unwrap_or_default();
+
+	let workspace = Arc::new(Workspace {
+		root: root.clone(),
+		cache_path,
+		hidden: config.hidden,
+		files: RwLock::new(FileMap::new()),
+		watcher: Mutex::new(None),
+	});
+	let candidates = workspace.walk(&root, ct)?;
+	let total = candidates.len();
+	let mut index = FileMap::new();
+	let mut stale = Vec::new();
+	for candidate in candidates {
+		match files.remove(&candidate.relative) {
+			Some(file) if file.mtime_ms == candidate.mtime_ms && file.size == candidate.size => {
+				index.insert(candidate.relative, file);
+			},
+			_ => stale.push(candidate),
+		}
+	}
+	let parsed = to_u32(stale.len());
+	index.extend(parse_candidates(stale, ct)?);
+
+	let symbols = index.values().map(|file| file.symbols.len()).sum();
+	*workspace.files.write() = index;
+	let persisted = workspace.persist();
+	if config.watch {
+		*workspace.watcher.lock() = Some(start_watcher(&workspace)?);
+	}
+	WORKSPACES.lock().insert(root.clone(), workspace.clone());
+
+	Ok(WorkspaceIndexStats {
+		root: root.to_string_lossy().into_owned(),
+		files: to_u32(total),
+		symbols: to_u32(symbols),
+		parsed,
+		reused: to_u32(total) - parsed,
+		removed: to_u32(files.len()),
+		cache_path: workspace
+			.cache_path
+			.as_ref()
+			.filter(|_| persisted)
+			.map(|path| path.to_string_lossy().into_owned()),
+		watching: config.watch,
+	})
+}
+
+/// Rank of `name` against the query, lower is better; `None` if it does not
+/// match.
+fn match_rank(query: &str, name: &str, fuzzy: bool) -> Option<u32> {
+	if name == query {
+		return Some(0);
+	}
+	if name.eq_ignore_ascii_case(query) {
+		return Some(1);
+	}
+	if !fuzzy {
+		return None;
+	}
+	let (query, name) = (query.to_lowercase(), name.to_lowercase());
+	if name.starts_with(&query) {
+		return Some(2);
+	}
+	if name.contains(&query) {
+		return Some(3);
+	}
+	// Subsequence match, ranked by how spread out the matched characters are.
+	let mut chars = name.chars().enumerate();
+	let mut first = None;
+	let mut last = 0;
+	for wanted in query.chars() {
+		let (index, _) = chars.find(|(_, ch)| *ch == wanted)?;
+		first.get_or_insert(index);
+		last = index;
+	}
+	Some(4 + to_u32(last - first.unwrap_or(0)))
+}
+
+/// Whether the container name `outer` matches a container query. Rust impl
+/// containers (`fmt::Display for Foo<T>`) also match by the bare type or trait
+/// name.
+fn container_matches(query: &str, outer: &str, fuzzy: bool) -> bool {
+	let bare = |name: &str| {
+		let name = name.split('<').next().unwrap_or(name).trim();
+		name.rsplit("::").next().unwrap_or(name).to_string()
+	};
+	std::iter::once(outer)
+		.chain(outer.split(" for "))
+		.any(|name| {
+			match_rank(query, name, fuzzy).is_some() || match_rank(query, &bare(name), fuzzy).is_some()
+		})
+}
+
+fn query_symbols_sync(query: &str, options: QuerySymbolsOptions) -> Result<Vec<SymbolMatch>> {
+	let workspaces: Vec<Arc<Workspace>> = match &options.root {
+		Some(root) => {
+			let resolved = resolve_search_path(root)?;
+			let workspace = WORKSPACES
+				.lock()
+				.get(&resolved)
+				.cloned()
+				.ok_or_else(|| Error::from_reason(format!("Workspace not indexed: {root}")))?;
+			vec![workspace]
+		},
+		None => WORKSPACES.lock().values().cloned().collect(),
+	};
+	// "Outer.name" and "Outer::name" also match on the container.
+	let (container, name) = query
+		.rsplit_once("::")
+		.or_else(|| query.rsplit_once('.'))
+		.map_or((None, query), |(container, name)| (Some(container), name));
+	let fuzzy = options.fuzzy.unwrap_or(false);
+	let limit = options.limit.unwrap_or(DEFAULT_QUERY_LIMIT) as usize;
+
+	let mut ranked = Vec::new();
+	for workspace in &workspaces {
+		let root = workspace.root.to_string_lossy();
+		for (path, file) in workspace.files.read().iter() {
+			for symbol in &file.symbols {
+				if options.kind.is_some_and(|kind| kind != symbol.kind) {
+					continue;
+				}
+				if let Some(container) = container
+					&& !symbol
+						.container
+						.as_deref()
+						.is_some_and(|outer| container_matches(container, outer, fuzzy))
+				{
+					continue;
+				}
+				let Some(rank) = match_rank(name, &symbol.name, fuzzy) else {
+					continue;
+				};
+				ranked.push((rank, SymbolMatch {
+					name:         symbol.name.clone(),
+					kind:         symbol.kind,
+					container:    symbol.container.clone(),
+					signature:    symbol.signature.clone(),
+					root:         root.to_string(),
+					path:         path.clone(),
+					start_line:   symbol.start_line,
+					start_column: symbol.start_column,
+					end_line:     symbol.end_line,
+					end_column:   symbol.end_column,
+				}));
+			}
+		}
+	}
+	ranked.sort_by(|(a_rank, a), (b_rank, b)| {
+		a_rank
+			.cmp(b_rank)
+			.then(a.name.len().cmp(&b.name.len()))
+			.then_with(|| a.path.cmp(&b.path))
+			.then(a.start_line.cmp(&b.start_line))
+	});
+	Ok(ranked
+		.into_iter()
+		.take(limit)
+		.map(|(_, symbol)| symbol)
+		.collect())
+}
+
+/// Index the definitions in every supported source file under `root`.
+///
+/// Re-indexing a workspace only parses files changed since the previous
+/// index (in memory or on disk).
+///
+/// # Errors
+/// Returns an error if `root` is not a directory, the operation is cancelled,
+/// or the watcher cannot be started.
+#[napi(js_name = "indexWorkspace")]
+pub fn index_workspace(
+	root: String,
+	options: Option<IndexWorkspaceOptions>,
+) -> task::Async<WorkspaceIndexStats> {
+	let (config, ct) = match options {
+		Some(IndexWorkspaceOptions { watch, persist, cache_path, hidden, signal, timeout_ms }) => (
+			IndexConfig {
+				root,
+				watch: watch.unwrap_or(true),
+				persist: persist.unwrap_or(true),
+				cache_path,
+				hidden: hidden.unwrap_or(false),
+			},
+			task::CancelToken::new(timeout_ms, signal),
+		),
+		None => (
+			IndexConfig { root, watch: true, persist: true, cache_path: None, hidden: false },
+			task::CancelToken::default(),
+		),
+	};
+	task::blocking("index_workspace", ct, move |ct| index_workspace_sync(config, &ct))
+}
+
+/// Find definitions named `name` in indexed workspaces. `name` may be
+/// qualified by its container, as in `Server.start` or `Server::start`.
+///
+/// # Errors
+/// Returns an error if `options.root` is given but has not been indexed.
+#[napi(js_name = "querySymbols")]
+pub fn query_symbols(
+	name: String,
+	options: Option<QuerySymbolsOptions>,
+) -> task::Async<Vec<SymbolMatch>> {
+	let options = options.unwrap_or_default();
+	task::blocking("query_symbols", (), move |_| query_symbols_sync(&name, options))
+}
+
+/// Stop watching an indexed workspace and drop its in-memory index; the
+/// on-disk index is kept for the next `indexWorkspace`. Returns false if
+/// `root` is not indexed.
+#[napi(js_name = "closeWorkspaceIndex")]
+pub fn close_workspace_index(root: String) -> bool {
+	let root = fs::canonicalize(&root).unwrap_or_else(|_| PathBuf::from(root));
+	WORKSPACES.lock().remove(&root).is_some()
+}
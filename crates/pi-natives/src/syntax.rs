@@ -0,0 +1,688 @@
+//! Syntax-aware file structure via tree-sitter.
+//!
+//! # Overview
+//! Bundles tree-sitter grammars for common languages and exposes:
+//! - `fileOutline`: the definitions in a file (functions, classes, methods,
+//!   types, ...) as a nested tree with line ranges and one-line signatures.
+//! - `nodeAtPosition`: the innermost syntax node at a line/column, its
+//!   ancestors, and the definitions enclosing it.
+//!
+//! The language is chosen from the file extension. Each language has a small
+//! outline query whose captures name the symbol kind (`@function`, `@class`,
+//! ...) and its `@name`; functions nested directly in a type become methods.
+//!
+//! Lines and columns are 1-indexed; columns count bytes, as in `grep`.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { symbols } = await native.fileOutline("src/main.rs");
+//! // JS: const { node, scope } = await native.nodeAtPosition("src/main.rs", 42, 8);
+//! ```
+
+use std::{fs, path::Path, sync::OnceLock};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use tree_sitter::{Node, Parser, Point, Query, QueryCursor, StreamingIterator, Tree};
+
+use crate::{task, workspace};
+
+/// Longest signature kept for an outline symbol, in bytes.
+const MAX_SIGNATURE_BYTES: usize = 200;
+
+/// Longest node text returned by `nodeAtPosition`, in bytes.
+const MAX_NODE_TEXT_BYTES: usize = 4096;
+
+/// Kind of a definition in a file outline.
+#[napi]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+	/// Free function.
+	Function  = 1,
+	/// Function defined inside a type.
+	Method    = 2,
+	/// Class (or Swift actor).
+	Class     = 3,
+	/// Struct or union.
+	Struct    = 4,
+	/// Enumeration.
+	Enum      = 5,
+	/// Interface or protocol.
+	Interface = 6,
+	/// Trait.
+	Trait     = 7,
+	/// Rust `impl` block or Swift extension.
+	Impl      = 8,
+	/// Module or namespace.
+	Module    = 9,
+	/// Type alias.
+	Type      = 10,
+}
+
+impl SymbolKind {
+	/// Kind named by an outline query capture (e.g. "function").
+	pub fn from_capture(name: &str) -> Option<Self> {
+		Some(match name {
+			"function" => Self::Function,
+			"method" => Self::Method,
+			"class" => Self::Class,
+			"struct" => Self::Struct,
+			"enum" => Self::Enum,
+			"interface" => Self::Interface,
+			"trait" => Self::Trait,
+			"impl" => Self::Impl,
+			"module" => Self::Module,
+			"type" => Self::Type,
+			_ => return None,
+		})
+	}
+
+	/// Capture name of this kind, the inverse of [`Self::from_capture`].
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Function => "function",
+			Self::Method => "method",
+			Self::Class => "class",
+			Self::Struct => "struct",
+			Self::Enum => "enum",
+			Self::Interface => "interface",
+			Self::Trait => "trait",
+			Self::Impl => "impl",
+			Self::Module => "module",
+			Self::Type => "type",
+		}
+	}
+
+	/// Whether functions defined directly inside this symbol are methods.
+	const fn holds_methods(self) -> bool {
+		matches!(
+			self,
+			Self::Class | Self::Struct | Self::Enum | Self::Interface | Self::Trait | Self::Impl
+		)
+	}
+}
+
+/// A definition in a file outline.
+#[napi(object)]
+pub struct OutlineSymbol {
+	/// Symbol name (for Rust impl blocks, e.g. "Display for Foo").
+	pub name:         String,
+	/// Kind of definition.
+	pub kind:         SymbolKind,
+	/// Declaration up to its body, on one line (e.g. "pub fn parse(input:
+	/// &str) -> Result<Ast>").
+	pub signature:    String,
+	/// First line of the definition, 1-indexed.
+	#[napi(js_name = "startLine")]
+	pub start_line:   u32,
+	/// Byte column where the definition starts, 1-indexed.
+	#[napi(js_name = "startColumn")]
+	pub start_column: u32,
+	/// Last line of the definition, 1-indexed.
+	#[napi(js_name = "endLine")]
+	pub end_line:     u32,
+	/// Byte column just past the end of the definition, 1-indexed.
+	#[napi(js_name = "endColumn")]
+	pub end_column:   u32,
+	/// Definitions nested inside this one.
+	pub children:     Vec<Self>,
+}
+
+/// Result of `fileOutline`.
+#[napi(object)]
+pub struct FileOutline {
+	/// Language the file was parsed as (e.g. "rust", "typescript").
+	pub language: String,
+	/// Top-level definitions in file order.
+	pub symbols:  Vec<OutlineSymbol>,
+}
+
+/// A syntax node and its range.
+#[napi(object)]
+pub struct SyntaxNode {
+	/// Grammar node type (e.g. `call_expression`).
+	pub kind:         String,
+	/// First line of the node, 1-indexed.
+	#[napi(js_name = "startLine")]
+	pub start_line:   u32,
+	/// Byte column where the node starts, 1-indexed.
+	#[napi(js_name = "startColumn")]
+	pub start_column: u32,
+	/// Last line of the node, 1-indexed.
+	#[napi(js_name = "endLine")]
+	pub end_line:     u32,
+	/// Byte column just past the end of the node, 1-indexed.
+	#[napi(js_name = "endColumn")]
+	pub end_column:   u32,
+	/// Source text of the node, truncated to 4 KiB; absent for ancestors.
+	pub text:         Option<String>,
+}
+
+/// Result of `nodeAtPosition`.
+#[napi(object)]
+pub struct NodeAtPosition {
+	/// Language the file was parsed as.
+	pub language:  String,
+	/// Innermost named node at the position.
+	pub node:      SyntaxNode,
+	/// Enclosing nodes from the parent outwards, excluding the root.
+	pub ancestors: Vec<SyntaxNode>,
+	/// Definitions enclosing the position, outermost first (without
+	/// children).
+	pub scope:     Vec<OutlineSymbol>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Language {
+	Bash,
+	C,
+	CSharp,
+	Cpp,
+	Go,
+	Java,
+	JavaScript,
+	Php,
+	Python,
+	Ruby,
+	Rust,
+	Swift,
+	Tsx,
+	TypeScript,
+}
+
+const LANGUAGE_COUNT: usize = 14;
+
+const JS_OUTLINE: &str = r"
+(function_declaration name: (identifier) @name) @function
+(generator_function_declaration name: (identifier) @name) @function
+(class_declaration name: (_) @name) @class
+(method_definition name: (_) @name) @method
+(variable_declarator
+  name: (identifier) @name
+  value: [(arrow_function) (function_expression)]) @function
+";
+
+const TS_OUTLINE: &str = r"
+(function_signature name: (identifier) @name) @function
+(abstract_class_declaration name: (_) @name) @class
+(interface_declaration name: (_) @name) @interface
+(type_alias_declaration name: (_) @name) @type
+(enum_declaration name: (_) @name) @enum
+(internal_module name: (_) @name) @module
+(method_signature name: (_) @name) @method
+(abstract_method_signature name: (_) @name) @method
+";
+
+const RUST_OUTLINE: &str = r"
+(function_item name: (identifier) @name) @function
+(function_signature_item name: (identifier) @name) @function
+(struct_item name: (type_identifier) @name) @struct
+(union_item name: (type_identifier) @name) @struct
+(enum_item name: (type_identifier) @name) @enum
+(trait_item name: (type_identifier) @name) @trait
+(impl_item type: (_) @name) @impl
+(mod_item name: (identifier) @name) @module
+(type_item name: (type_identifier) @name) @type
+(macro_definition name: (identifier) @name) @function
+";
+
+const PYTHON_OUTLINE: &str = r"
+(class_definition name: (identifier) @name) @class
+(function_definition name: (identifier) @name) @function
+";
+
+const GO_OUTLINE: &str = r"
+(function_declaration name: (identifier) @name) @function
+(method_declaration name: (field_identifier) @name) @method
+(type_spec name: (type_identifier) @name) @type
+";
+
+const JAVA_OUTLINE: &str = r"
+(class_declaration name: (identifier) @name) @class
+(record_declaration name: (identifier) @name) @class
+(interface_declaration name: (identifier) @name) @interface
+(annotation_type_declaration name: (identifier) @name) @interface
+(enum_declaration name: (identifier) @name) @enum
+(method_declaration name: (identifier) @name) @method
+(constructor_declaration name: (identifier) @name) @method
+";
+
+const C_OUTLINE: &str = r"
+(function_definition declarator: (function_declarator declarator: (_) @name)) @function
+(function_definition
+  declarator: (pointer_declarator declarator: (function_declarator declarator: (_) @name))) @function
+(struct_specifier name: (type_identifier) @name body: (field_declaration_list)) @struct
+(union_specifier name: (type_identifier) @name body: (field_declaration_list)) @struct
+(enum_specifier name: (type_identifier) @name body: (enumerator_list)) @enum
+(type_definition declarator: (type_identifier) @name) @type
+";
+
+const CPP_OUTLINE: &str = r"
+(function_definition declarator: (function_declarator declarator: (_) @name)) @function
+(function_definition
+  declarator: (pointer_declarator declarator: (function_declarator declarator: (_) @name))) @function
+(function_definition
+  declarator: (reference_declarator (function_declarator declarator: (_) @name))) @function
+(field_declaration declarator: (function_declarator declarator: (_) @name)) @function
+(class_specifier name: (_) @name body: (field_declaration_list)) @class
+(struct_specifier name: (_) @name body: (field_declaration_list)) @struct
+(union_specifier name: (_) @name body: (field_declaration_list)) @struct
+(enum_specifier name: (_) @name body: (enumerator_list)) @enum
+(namespace_definition name: (_) @name) @module
+(alias_declaration name: (type_identifier) @name) @type
+(type_definition declarator: (type_identifier) @name) @type
+";
+
+const CSHARP_OUTLINE: &str = r"
+(class_declaration name: (identifier) @name) @class
+(record_declaration name: (identifier) @name) @class
+(struct_declaration name: (identifier) @name) @struct
+(interface_declaration name: (identifier) @name) @interface
+(enum_declaration name: (identifier) @name) @enum
+(method_declaration name: (identifier) @name) @method
+(constructor_declaration name: (identifier) @name) @method
+(local_function_statement name: (identifier) @name) @function
+(namespace_declaration name: (_) @name) @module
+(file_scoped_namespace_declaration name: (_) @name) @module
+";
+
+const RUBY_OUTLINE: &str = r"
+(class name: (_) @name) @class
+(module name: (_) @name) @module
+(method name: (_) @name) @function
+(singleton_method name: (_) @name) @function
+";
+
+const PHP_OUTLINE: &str = r"
+(class_declaration name: (name) @name) @class
+(interface_declaration name: (name) @name) @interface
+(trait_declaration name: (name) @name) @trait
+(enum_declaration name: (name) @name) @enum
+(function_definition name: (name) @name) @function
+(method_declaration name: (name) @name) @method
+(namespace_definition name: (namespace_name) @name) @module
+";
+
+const BASH_OUTLINE: &str = r"
+(function_definition name: (word) @name) @function
+";
+
+const SWIFT_OUTLINE: &str = r"
+(class_declaration name: (_) @name) @class
+(protocol_declaration name: (_) @name) @interface
+(function_declaration name: _ @name) @function
+(protocol_function_declaration name: _ @name) @function
+(init_declaration name: _ @name) @function
+(typealias_declaration name: (_) @name) @type
+";
+
+impl Language {
+	fn from_path(path: &Path) -> Option<Self> {
+		let name = path.file_name()?.to_str()?;
+		if matches!(name, "Rakefile" | "Gemfile" | "Podfile" | "Vagrantfile") {
+			return Some(Self::Ruby);
+		}
+		let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+		Some(match ext.as_str() {
+			"sh" | "bash" | "zsh" | "ksh" => Self::Bash,
+			"c" | "h" => Self::C,
+			"cs" => Self::CSharp,
+			"cc" | "cpp" | "cxx" | "c++" | "hh" | "hpp" | "hxx" | "h++" | "ipp" => Self::Cpp,
+			"go" => Self::Go,
+			"java" => Self::Java,
+			"js" | "jsx" | "mjs" | "cjs" => Self::JavaScript,
+			"php" => Self::Php,
+			"py" | "pyi" | "pyw" => Self::Python,
+			"rb" | "rake" | "gemspec" => Self::Ruby,
+			"rs" => Self::Rust,
+			"swift" => Self::Swift,
+			"tsx" => Self::Tsx,
+			"ts" | "mts" | "cts" => Self::TypeScript,
+			_ => return None,
+		})
+	}
+
+	const fn name(self) -> &'static str {
+		match self {
+			Self::Bash => "bash",
+			Self::C => "c",
+			Self::CSharp => "csharp",
+			Self::Cpp => "cpp",
+			Self::Go => "go",
+			Self::Java => "java",
+			Self::JavaScript => "javascript",
+			Self::Php => "php",
+			Self::Python => "python",
+			Self::Ruby => "ruby",
+			Self::Rust => "rust",
+			Self::Swift => "swift",
+			Self::Tsx => "tsx",
+			Self::TypeScript => "typescript",
+		}
+	}
+
+	fn grammar(self) -> tree_sitter::Language {
+		match self {
+			Self::Bash => tree_sitter_bash::LANGUAGE.into(),
+			Self::C => tree_sitter_c::LANGUAGE.into(),
+			Self::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
+			Self::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+			Self::Go => tree_sitter_go::LANGUAGE.into(),
+			Self::Java => tree_sitter_java::LANGUAGE.into(),
+			Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+			Self::Php => tree_sitter_php::LANGUAGE_PHP.into(),
+			Self::Python => tree_sitter_python::LANGUAGE.into(),
+			Self::Ruby => tree_sitter_ruby::LANGUAGE.into(),
+			Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+			Self::Swift => tree_sitter_swift::LANGUAGE.into(),
+			Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+			Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+		}
+	}
+
+	fn outline_query(self) -> String {
+		match self {
+			Self::Bash => BASH_OUTLINE.to_string(),
+			Self::C => C_OUTLINE.to_string(),
+			Self::CSharp => CSHARP_OUTLINE.to_string(),
+			Self::Cpp => CPP_OUTLINE.to_string(),
+			Self::Go => GO_OUTLINE.to_string(),
+			Self::Java => JAVA_OUTLINE.to_string(),
+			Self::JavaScript => JS_OUTLINE.to_string(),
+			Self::Php => PHP_OUTLINE.to_string(),
+			Self::Python => PYTHON_OUTLINE.to_string(),
+			Self::Ruby => RUBY_OUTLINE.to_string(),
+			Self::Rust => RUST_OUTLINE.to_string(),
+			Self::Swift => SWIFT_OUTLINE.to_string(),
+			Self::Tsx | Self::TypeScript => format!("{JS_OUTLINE}{TS_OUTLINE}"),
+		}
+	}
+}
+
+/// A loaded grammar and its compiled outline query.
+struct Grammar {
+	language: tree_sitter::Language,
+	outline:  Query,
+	/// Symbol kind for each capture index, `None` for `@name`.
+	kinds:    Vec<Option<SymbolKind>>,
+}
+
+fn grammar(language: Language) -> Result<&'static Grammar> {
+	static GRAMMARS: [OnceLock<std::result::Result<Grammar, String>>; LANGUAGE_COUNT] =
+		[const { OnceLock::new() }; LANGUAGE_COUNT];
+	GRAMMARS[language as usize]
+		.get_or_init(|| {
+			let grammar = language.grammar();
+			let outline = Query::new(&grammar, &language.outline_query())
+				.map_err(|err| format!("Failed to load {} outline query: {err}", language.name()))?;
+			let kinds = outline
+				.capture_names()
+				.iter()
+				.map(|name| SymbolKind::from_capture(name))
+				.collect();
+			Ok(Grammar { language: grammar, outline, kinds })
+		})
+		.as_ref()
+		.map_err(|err| Error::from_reason(err.clone()))
+}
+
+/// A file parsed with its language's grammar.
+struct Parsed {
+	language: Language,
+	source:   Vec<u8>,
+	tree:     Tree,
+}
+
+fn parse_file(path: &str) -> Result<Parsed> {
+	let source =
+		fs::read(path).map_err(|err| Error::from_reason(format!("Failed to read {path}: {err}")))?;
+	parse_source(Path::new(path), source)
+}
+
+fn parse_source(path: &Path, source: Vec<u8>) -> Result<Parsed> {
+	let language = Language::from_path(path)
+		.ok_or_else(|| Error::from_reason(format!("Unsupported language: {}", path.display())))?;
+	let mut parser = Parser::new();
+	parser
+		.set_language(&grammar(language)?.language)
+		.map_err(|err| {
+			Error::from_reason(format!("Failed to load {} grammar: {err}", language.name()))
+		})?;
+	let tree = parser
+		.parse(&source, None)
+		.ok_or_else(|| Error::from_reason(format!("Failed to parse {}", path.display())))?;
+	Ok(Parsed { language, source, tree })
+}
+
+/// Whether files at `path` can be outlined (judged by name only).
+pub fn is_supported(path: &Path) -> bool {
+	Language::from_path(path).is_some()
+}
+
+/// Outline `source`, parsed with the grammar chosen by `path`'s extension.
+pub fn outline_source(path: &Path, source: Vec<u8>) -> Result<Vec<OutlineSymbol>> {
+	let parsed = parse_source(path, source)?;
+	let defs = definitions(&parsed)?;
+	Ok(nest(&mut defs.into_iter().peekable(), usize::MAX, None))
+}
+
+/// Cut `text` to at most `max` bytes on a character boundary.
+fn truncate(text: &str, max: usize) -> &str {
+	if text.len() <= max {
+		return text;
+	}
+	let mut end = max;
+	while !text.is_char_boundary(end) {
+		end -= 1;
+	}
+	&text[..end]
+}
+
+/// The declaration part of a definition, whitespace-collapsed onto one line.
+fn signature(node: Node<'_>, source: &[u8]) -> String {
+	let end = node
+		.child_by_field_name("body")
+		.map_or_else(|| node.end_byte(), |body| body.start_byte());
+	let head = String::from_utf8_lossy(&source[node.start_byte()..end]);
+	// Without a body (e.g. `const f = () => ...`), the first line is enough.
+	let head = if end == node.end_byte() {
+		head.lines().next().unwrap_or_default()
+	} else {
+		&head
+	};
+	let collapsed = head.split_whitespace().collect::<Vec<_>>().join(" ");
+	let trimmed = collapsed.trim_end_matches(['{', ':', ';', ' ']);
+	truncate(trimmed, MAX_SIGNATURE_BYTES).to_string()
+}
+
+/// Kind refined from the definition itself where one pattern covers several
+/// kinds.
+fn refine_kind(language: Language, node: Node<'_>, kind: SymbolKind, source: &[u8]) -> SymbolKind {
+	match language {
+		Language::Go => match node.child_by_field_name("type").map(|ty| ty.kind()) {
+			Some("struct_type") => SymbolKind::Struct,
+			Some("interface_type") => SymbolKind::Interface,
+			_ => kind,
+		},
+		Language::Swift => match node
+			.child_by_field_name("declaration_kind")
+			.and_then(|decl| decl.utf8_text(source).ok())
+		{
+			Some("struct") => SymbolKind::Struct,
+			Some("enum") => SymbolKind::Enum,
+			Some("extension") => SymbolKind::Impl,
+			_ => kind,
+		},
+		_ => kind,
+	}
+}
+
+fn symbol_name(node: Node<'_>, name: Option<Node<'_>>, source: &[u8]) -> String {
+	let text = |node: Node<'_>| String::from_utf8_lossy(&source[node.byte_range()]).into_owned();
+	if node.kind() == "impl_item"
+		&& let (Some(trait_name), Some(ty)) =
+			(node.child_by_field_name("trait"), node.child_by_field_name("type"))
+	{
+		return format!("{} for {}", text(trait_name), text(ty));
+	}
+	name.map_or_else(|| node.kind().to_string(), text)
+}
+
+fn new_symbol(node: Node<'_>, name: String, kind: SymbolKind, source: &[u8]) -> OutlineSymbol {
+	let (start, end) = (node.start_position(), node.end_position());
+	OutlineSymbol {
+		name,
+		kind,
+		signature: signature(node, source),
+		start_line: start.row as u32 + 1,
+		start_column: start.column as u32 + 1,
+		end_line: end.row as u32 + 1,
+		end_column: end.column as u32 + 1,
+		children: Vec::new(),
+	}
+}
+
+/// Definitions in `parsed` with their byte ranges, sorted by start and then
+/// by size (outer definitions first).
+fn definitions(parsed: &Parsed) -> Result<Vec<(std::ops::Range<usize>, OutlineSymbol)>> {
+	let grammar = grammar(parsed.language)?;
+	let mut cursor = QueryCursor::new();
+	let mut matches = cursor.matches(&grammar.outline, parsed.tree.root_node(), &parsed.source[..]);
+	let mut defs: Vec<(std::ops::Range<usize>, OutlineSymbol)> = Vec::new();
+	while let Some(found) = matches.next() {
+		let mut definition = None;
+		let mut name = None;
+		for capture in found.captures {
+			match grammar.kinds[capture.index as usize] {
+				Some(kind) => definition = Some((capture.node, kind)),
+				None => name = Some(capture.node),
+			}
+		}
+		let Some((node, kind)) = definition else {
+			continue;
+		};
+		if defs.iter().any(|(range, _)| *range == node.byte_range()) {
+			continue;
+		}
+		let kind = refine_kind(parsed.language, node, kind, &parsed.source);
+		let name = symbol_name(node, name, &parsed.source);
+		defs.push((node.byte_range(), new_symbol(node, name, kind, &parsed.source)));
+	}
+	defs.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+	Ok(defs)
+}
+
+/// Nest the sorted definitions starting at `defs[0]` that end by `end`.
+fn nest(
+	defs: &mut std::iter::Peekable<impl Iterator<Item = (std::ops::Range<usize>, OutlineSymbol)>>,
+	end: usize,
+	parent: Option<SymbolKind>,
+) -> Vec<OutlineSymbol> {
+	let mut symbols = Vec::new();
+	while let Some((range, mut symbol)) = defs.next_if(|(range, _)| range.start < end) {
+		if symbol.kind == SymbolKind::Function && parent.is_some_and(SymbolKind::holds_methods) {
+			symbol.kind = SymbolKind::Method;
+		}
+		symbol.children = nest(defs, range.end, Some(symbol.kind));
+		symbols.push(symbol);
+	}
+	symbols
+}
+
+fn file_outline_sync(path: &str) -> Result<FileOutline> {
+	let parsed = parse_file(path)?;
+	let defs = definitions(&parsed)?;
+	Ok(FileOutline {
+		language: parsed.language.name().to_string(),
+		symbols:  nest(&mut defs.into_iter().peekable(), usize::MAX, None),
+	})
+}
+
+fn syntax_node(node: Node<'_>, text: Option<&[u8]>) -> SyntaxNode {
+	let (start, end) = (node.start_position(), node.end_position());
+	SyntaxNode {
+		kind:         node.kind().to_string(),
+		start_line:   start.row as u32 + 1,
+		start_column: start.column as u32 + 1,
+		end_line:     end.row as u32 + 1,
+		end_column:   end.column as u32 + 1,
+		text:         text.map(|source| {
+			let text = String::from_utf8_lossy(&source[node.byte_range()]);
+			truncate(&text, MAX_NODE_TEXT_BYTES).to_string()
+		}),
+	}
+}
+
+fn node_at_position_sync(path: &str, line: u32, column: u32) -> Result<NodeAtPosition> {
+	let parsed = parse_file(path)?;
+	let root = parsed.tree.root_node();
+	let point = Point::new(line.saturating_sub(1) as usize, column.saturating_sub(1) as usize);
+	let node = root
+		.named_descendant_for_point_range(point, point)
+		.unwrap_or(root);
+
+	let mut ancestors = Vec::new();
+	let mut current = node.parent();
+	while let Some(ancestor) = current
+		&& ancestor.id() != root.id()
+	{
+		ancestors.push(syntax_node(ancestor, None));
+		current = ancestor.parent();
+	}
+
+	let byte = node.start_byte();
+	let defs = definitions(&parsed)?;
+	let mut scope: Vec<OutlineSymbol> = Vec::new();
+	for (range, mut symbol) in defs {
+		if !range.contains(&byte) {
+			continue;
+		}
+		if symbol.kind == SymbolKind::Function
+			&& scope.last().is_some_and(|outer| outer.kind.holds_methods())
+		{
+			symbol.kind = SymbolKind::Method;
+		}
+		scope.push(symbol);
+	}
+
+	Ok(NodeAtPosition {
+		language: parsed.language.name().to_string(),
+		node: syntax_node(node, Some(&parsed.source)),
+		ancestors,
+		scope,
+	})
+}
+
+/// Outline the definitions in a source file.
+///
+/// # Errors
+/// Returns an error if the language is not supported, the file cannot be
+/// read, or it is outside the workspace (unless `allowOutsideWorkspace`).
+#[napi(js_name = "fileOutline")]
+pub fn file_outline(
+	path: String,
+	allow_outside_workspace: Option<bool>,
+) -> task::Async<FileOutline> {
+	task::blocking("file_outline", (), move |_| {
+		workspace::check(&path, allow_outside_workspace)?;
+		file_outline_sync(&path)
+	})
+}
+
+/// Find the innermost syntax node at a 1-indexed line and byte column.
+///
+/// # Errors
+/// Returns an error if the language is not supported, the file cannot be
+/// read, or it is outside the workspace (unless `allowOutsideWorkspace`).
+#[napi(js_name = "nodeAtPosition")]
+pub fn node_at_position(
+	path: String,
+	line: u32,
+	column: u32,
+	allow_outside_workspace: Option<bool>,
+) -> task::Async<NodeAtPosition> {
+	task::blocking("node_at_position", (), move |_| {
+		workspace::check(&path, allow_outside_workspace)?;
+		node_at_position_sync(&path, line, column)
+	})
+}
@@ -2,7 +2,10 @@
 //!
 //! # Overview
 //! Provides cross-platform system info without shelling out, including OS,
-//! distro, kernel, CPU model, architecture, and disk usage.
+//! distro, kernel, CPU model, architecture, core counts, memory, swap, load
+//! averages, GPUs, disk usage, battery and power source, uptime, the current
+//! user and their privileges, locale and time zone, and the container/VM/CI
+//! environment.
 //!
 //! # Example
 //! ```ignore
@@ -10,11 +13,42 @@
 //! console.log(info.os, info.cpu);
 //! ```
 
-use std::{fs, path::Path};
+use std::{fmt::Write as _, fs, path::Path};
 
 use napi_derive::napi;
 use sysinfo::{Disks, System};
 
+pub(crate) mod disk;
+mod environment;
+mod gpu;
+mod locale;
+mod network;
+mod power;
+mod probe;
+mod thermal;
+mod user;
+
+pub use self::{
+	disk::{DiskSpace, check_disk_space},
+	environment::EnvironmentInfo,
+	gpu::GpuInfo,
+	locale::LocaleInfo,
+	network::{
+		ListeningPort, NetworkInfo, NetworkInterface, PortRange, find_free_port, get_network_info,
+		is_port_free, kill_port_owners, list_listening_ports, pids_listening_on,
+	},
+	power::{
+		PowerEvent, PowerEventOptions, PowerInfo, get_power_info, subscribe_power_events,
+		unsubscribe_power_events,
+	},
+	probe::{
+		LatencyAttempt, LatencyProbe, LatencyProbeOptions, ResolveHostOptions, ResolvedHost,
+		probe_latency, resolve_host,
+	},
+	thermal::{ThermalInfo, ThermalSensor, get_thermal_info},
+	user::UserInfo,
+};
+
 /// Basic system info without shelling out.
 #[napi(object)]
 pub struct SystemInfo {
@@ -26,6 +60,48 @@ pub struct SystemInfo {
 	pub cpu:    Option<String>,
 	/// Disk usage summary (used/total) for primary mount.
 	pub disk:   Option<String>,
+	/// System memory summary (used/total).
+	pub memory: Option<String>,
+	/// Primary GPU summary (model and VRAM).
+	pub gpu:    Option<String>,
+
+	/// Total physical memory in bytes.
+	#[napi(js_name = "totalMemory")]
+	pub total_memory:     f64,
+	/// Memory available for new allocations without swapping, in bytes.
+	#[napi(js_name = "availableMemory")]
+	pub available_memory: f64,
+	/// Total swap space in bytes.
+	#[napi(js_name = "totalSwap")]
+	pub total_swap:       f64,
+	/// Swap space in use, in bytes.
+	#[napi(js_name = "usedSwap")]
+	pub used_swap:        f64,
+	/// Load averages over 1, 5 and 15 minutes (not available on Windows).
+	#[napi(js_name = "loadAverage")]
+	pub load_average:     Option<Vec<f64>>,
+	/// Number of logical CPUs.
+	#[napi(js_name = "logicalCores")]
+	pub logical_cores:    u32,
+	/// Number of physical CPU cores, when known.
+	#[napi(js_name = "physicalCores")]
+	pub physical_cores:   Option<u32>,
+	/// Detected graphics adapters, discrete GPUs first.
+	pub gpus:             Vec<GpuInfo>,
+	/// Container, virtual machine, WSL, and CI detection.
+	pub environment:      EnvironmentInfo,
+	/// Battery charge and power source.
+	pub power:            PowerInfo,
+	/// Seconds since the system booted.
+	#[napi(js_name = "uptimeSeconds")]
+	pub uptime_seconds:   f64,
+	/// Boot time in milliseconds since the Unix epoch.
+	#[napi(js_name = "bootTime")]
+	pub boot_time:        f64,
+	/// The user the agent runs as, and their privileges.
+	pub user:             UserInfo,
+	/// Locale, time zone and keyboard layout.
+	pub locale:           LocaleInfo,
 }
 
 /// Collect system info with native APIs (no shell commands).
@@ -43,7 +119,51 @@ pub fn get_system_info() -> SystemInfo {
 		.filter(|v| !v.is_empty());
 	let disk = get_disk_info();
 
-	SystemInfo { distro, kernel, cpu, disk }
+	let total_memory = system.total_memory();
+	let available_memory = system.available_memory();
+	let memory = (total_memory > 0)
+		.then(|| format_usage(total_memory.saturating_sub(available_memory), total_memory));
+	let load_average = (!cfg!(target_os = "windows")).then(|| {
+		let load = System::load_average();
+		vec![load.one, load.five, load.fifteen]
+	});
+	let gpus = gpu::detect_gpus();
+	let gpu = gpus.first().map(format_gpu);
+
+	SystemInfo {
+		distro,
+		kernel,
+		cpu,
+		disk,
+		memory,
+		gpu,
+		total_memory: total_memory as f64,
+		available_memory: available_memory as f64,
+		total_swap: system.total_swap() as f64,
+		used_swap: system.used_swap() as f64,
+		load_average,
+		logical_cores: system.cpus().len() as u32,
+		physical_cores: System::physical_core_count().map(|count| count as u32),
+		gpus,
+		environment: environment::detect_environment(),
+		power: power::read_power(),
+		uptime_seconds: System::uptime() as f64,
+		boot_time: System::boot_time() as f64 * 1000.0,
+		user: user::detect_user(),
+		locale: locale::detect_locale(),
+	}
+}
+
+fn format_gpu(gpu: &GpuInfo) -> String {
+	let mut summary = if gpu.model.starts_with(&gpu.vendor) {
+		gpu.model.clone()
+	} else {
+		format!("{} {}", gpu.vendor, gpu.model)
+	};
+	if let Some(vram) = gpu.vram_bytes {
+		let _ = write!(summary, " ({})", format_bytes(vram as u64));
+	}
+	summary
 }
 
 fn get_os_distro(_system: &System) -> Option<String> {
@@ -157,14 +277,14 @@ fn get_disk_info() -> Option<String> {
 		return None;
 	}
 	let used = total.saturating_sub(disk.available_space());
-	Some(format!("/ {}", format_disk_usage(used, total)))
+	Some(format!("/ {}", format_usage(used, total)))
 }
 
 fn format_disk_entry(label: &str, used: u64, total: u64) -> String {
-	format!("{} {}", label, format_disk_usage(used, total))
+	format!("{} {}", label, format_usage(used, total))
 }
 
-fn format_disk_usage(used: u64, total: u64) -> String {
+fn format_usage(used: u64, total: u64) -> String {
 	let pct = if total == 0 {
 		0
 	} else {
@@ -173,7 +293,7 @@ fn format_disk_usage(used: u64, total: u64) -> String {
 	format!("{}/{} ({}%)", format_bytes(used), format_bytes(total), pct)
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub(crate) fn format_bytes(bytes: u64) -> String {
 	const KB: f64 = 1024.0;
 	const MB: f64 = KB * 1024.0;
 	const GB: f64 = MB * 1024.0;
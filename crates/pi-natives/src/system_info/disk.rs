@@ -0,0 +1,129 @@
+//! Free disk space checks before large writes.
+//!
+//! # Overview
+//! `checkDiskSpace` reports the space available to this user on the
+//! filesystem holding a path, or its nearest existing ancestor when the path
+//! does not exist yet. Archive extraction, checkpoints and artifact writes use
+//! the same check to refuse up front instead of failing halfway through.
+//!
+//! # Platform Implementation
+//! - **Unix**: `statvfs`, counting blocks available to unprivileged users
+//! - **Windows**: the volume with the longest mount point containing the path
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { sufficient } = await native.checkDiskSpace("vendor", 5 * 2 ** 30);
+//! ```
+
+use std::{
+	io,
+	path::{Path, PathBuf},
+};
+
+use napi::{Error, Result};
+use napi_derive::napi;
+
+use super::format_bytes;
+use crate::task;
+
+/// Space on the filesystem holding a path.
+#[napi(object)]
+pub struct DiskSpace {
+	/// Bytes available for writing.
+	#[napi(js_name = "availableBytes")]
+	pub available_bytes: f64,
+	/// Size of the filesystem in bytes.
+	#[napi(js_name = "totalBytes")]
+	pub total_bytes:     f64,
+	/// Whether `requiredBytes` fit in the available space.
+	pub sufficient:      bool,
+}
+
+/// `path`, or its nearest existing ancestor.
+fn existing(path: &Path) -> io::Result<PathBuf> {
+	let path = std::path::absolute(path)?;
+	path
+		.ancestors()
+		.find(|ancestor| ancestor.exists())
+		.map(Path::to_path_buf)
+		.ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+}
+
+/// Available and total bytes on the filesystem holding `path`.
+pub fn space(path: &Path) -> io::Result<(u64, u64)> {
+	platform::space(&existing(path)?)
+}
+
+/// Fail with a clear error if `required` bytes do not fit below `path`.
+pub fn ensure(path: &Path, required: u64, action: &str) -> Result<()> {
+	// Space that cannot be measured is not in the way.
+	let Ok((available, _)) = space(path) else {
+		return Ok(());
+	};
+	if required <= available {
+		return Ok(());
+	}
+	Err(Error::from_reason(format!(
+		"Not enough disk space to {action}: {} needed, {} available on {}",
+		format_bytes(required),
+		format_bytes(available),
+		path.display()
+	)))
+}
+
+#[cfg(unix)]
+mod platform {
+	use std::{ffi::CString, io, mem::MaybeUninit, os::unix::ffi::OsStrExt, path::Path};
+
+	#[allow(clippy::unnecessary_cast, reason = "statvfs field types vary by platform")]
+	pub fn space(path: &Path) -> io::Result<(u64, u64)> {
+		let path = CString::new(path.as_os_str().as_bytes())?;
+		let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+		// SAFETY: `path` is NUL-terminated and `stat` is written on success.
+		if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+			return Err(io::Error::last_os_error());
+		}
+		// SAFETY: statvfs succeeded.
+		let stat = unsafe { stat.assume_init() };
+		let fragment = stat.f_frsize as u64;
+		Ok((stat.f_bavail as u64 * fragment, stat.f_blocks as u64 * fragment))
+	}
+}
+
+#[cfg(windows)]
+mod platform {
+	use std::{io, path::Path};
+
+	use sysinfo::Disks;
+
+	pub fn space(path: &Path) -> io::Result<(u64, u64)> {
+		let path = std::fs::canonicalize(path)?;
+		let disks = Disks::new_with_refreshed_list();
+		disks
+			.list()
+			.iter()
+			.filter(|disk| path.starts_with(disk.mount_point()))
+			.max_by_key(|disk| disk.mount_point().as_os_str().len())
+			.map(|disk| (disk.available_space(), disk.total_space()))
+			.ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+	}
+}
+
+/// Report the space available on the filesystem holding `path`, and whether
+/// `requiredBytes` (default: 0) fit in it.
+///
+/// # Errors
+/// Returns an error if the filesystem cannot be queried.
+#[napi(js_name = "checkDiskSpace")]
+pub fn check_disk_space(path: String, required_bytes: Option<f64>) -> task::Async<DiskSpace> {
+	task::blocking("check_disk_space", (), move |_| {
+		let (available, total) = space(Path::new(&path)).map_err(|err| {
+			Error::from_reason(format!("Failed to check disk space for {path}: {err}"))
+		})?;
+		Ok(DiskSpace {
+			available_bytes: available as f64,
+			total_bytes:     total as f64,
+			sufficient:      required_bytes.unwrap_or(0.0).max(0.0) <= available as f64,
+		})
+	})
+}
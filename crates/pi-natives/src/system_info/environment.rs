@@ -0,0 +1,238 @@
+//! Detection of containers, virtual machines, WSL, and CI runners.
+//!
+//! # Platform Implementation
+//! - **Linux**: container marker files (`/.dockerenv`, `/run/.containerenv`),
+//!   the `container` variable of this process and PID 1, and cgroup paths; WSL
+//!   from the kernel release string; hypervisors from DMI identifiers
+//! - **macOS**: hypervisors via the `kern.hv_vmm_present` sysctl
+//! - **Windows**: hypervisors from the BIOS manufacturer/product in the
+//!   registry
+//! - **All**: CI providers from their well-known environment variables
+
+use std::env;
+
+use napi_derive::napi;
+
+/// Execution environment the agent is running in.
+#[napi(object)]
+pub struct EnvironmentInfo {
+	/// Container runtime: "docker", "podman", "lxc", "systemd-nspawn",
+	/// "kubernetes", or "container" when the runtime is unknown.
+	pub container:       Option<String>,
+	/// Hypervisor: "kvm", "qemu", "vmware", "virtualbox", "hyper-v", "xen",
+	/// "parallels", "amazon", "google", "bhyve", or "vm" when the vendor is
+	/// unknown.
+	#[napi(js_name = "virtualMachine")]
+	pub virtual_machine: Option<String>,
+	/// WSL version (1 or 2) when running under the Windows Subsystem for
+	/// Linux.
+	pub wsl:             Option<u32>,
+	/// CI provider: "github-actions", "gitlab", "circleci", "buildkite",
+	/// "jenkins", "travis", "azure-pipelines", "teamcity", "bitbucket",
+	/// "codebuild", or "ci" for other providers that set `CI`.
+	pub ci:              Option<String>,
+}
+
+/// Detect the current execution environment.
+pub fn detect_environment() -> EnvironmentInfo {
+	let wsl = platform::detect_wsl();
+	EnvironmentInfo {
+		container: platform::detect_container(),
+		// The WSL2 utility VM is an implementation detail, not a guest the user
+		// manages.
+		virtual_machine: if wsl == Some(2) {
+			None
+		} else {
+			platform::detect_vm()
+		},
+		wsl,
+		ci: detect_ci(),
+	}
+}
+
+fn detect_ci() -> Option<String> {
+	const PROVIDERS: [(&str, &str); 10] = [
+		("GITHUB_ACTIONS", "github-actions"),
+		("GITLAB_CI", "gitlab"),
+		("CIRCLECI", "circleci"),
+		("BUILDKITE", "buildkite"),
+		("JENKINS_URL", "jenkins"),
+		("TRAVIS", "travis"),
+		("TF_BUILD", "azure-pipelines"),
+		("TEAMCITY_VERSION", "teamcity"),
+		("BITBUCKET_BUILD_NUMBER", "bitbucket"),
+		("CODEBUILD_BUILD_ID", "codebuild"),
+	];
+	let is_set = |name: &str| env::var_os(name).is_some_and(|value| !value.is_empty());
+	PROVIDERS
+		.iter()
+		.find(|(var, _)| is_set(var))
+		.map(|(_, name)| (*name).to_string())
+		.or_else(|| {
+			env::var("CI")
+				.is_ok_and(|value| !matches!(value.as_str(), "" | "0" | "false"))
+				.then(|| "ci".to_string())
+		})
+}
+
+/// Map DMI/BIOS vendor and product strings to a hypervisor name.
+#[cfg_attr(target_os = "macos", allow(dead_code, reason = "macOS has no DMI strings"))]
+fn classify_vm(vendor: &str, product: &str) -> Option<&'static str> {
+	const VENDORS: [(&str, &str); 11] = [
+		("kvm", "kvm"),
+		("qemu", "qemu"),
+		("vmware", "vmware"),
+		("virtualbox", "virtualbox"),
+		("innotek", "virtualbox"),
+		// Physical Surface devices share the vendor; only VMs say so.
+		("microsoft corporation virtual machine", "hyper-v"),
+		("xen", "xen"),
+		("parallels", "parallels"),
+		("amazon ec2", "amazon"),
+		("google compute engine", "google"),
+		("bhyve", "bhyve"),
+	];
+	let identifier = format!("{vendor} {product}").to_ascii_lowercase();
+	VENDORS
+		.iter()
+		.find(|(needle, _)| identifier.contains(needle))
+		.map(|(_, name)| *name)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+	use std::{env, fs, path::Path};
+
+	use super::classify_vm;
+
+	fn read(path: &str) -> Option<String> {
+		fs::read_to_string(path).ok()
+	}
+
+	/// Normalize a systemd-style `container=` value.
+	fn runtime_name(value: &str) -> String {
+		match value.trim() {
+			"oci" => "container".to_string(),
+			"lxc-libvirt" => "lxc".to_string(),
+			other => other.to_string(),
+		}
+	}
+
+	/// `container=` from PID 1's environment, readable only with privileges
+	/// inside most containers.
+	fn pid1_container_var() -> Option<String> {
+		let environ = fs::read("/proc/1/environ").ok()?;
+		environ
+			.split(|byte| *byte == 0)
+			.find_map(|entry| entry.strip_prefix(b"container="))
+			.map(|value| String::from_utf8_lossy(value).into_owned())
+	}
+
+	pub fn detect_container() -> Option<String> {
+		if Path::new("/run/.containerenv").exists() {
+			return Some("podman".to_string());
+		}
+		if Path::new("/.dockerenv").exists() {
+			return Some("docker".to_string());
+		}
+		if let Some(value) = env::var("container").ok().or_else(pid1_container_var)
+			&& !value.trim().is_empty()
+		{
+			return Some(runtime_name(&value));
+		}
+		let cgroup = read("/proc/1/cgroup").unwrap_or_default();
+		if cgroup.contains("kubepods") || env::var_os("KUBERNETES_SERVICE_HOST").is_some() {
+			return Some("kubernetes".to_string());
+		}
+		if cgroup.contains("libpod") {
+			return Some("podman".to_string());
+		}
+		if cgroup.contains("docker") {
+			return Some("docker".to_string());
+		}
+		if cgroup.contains("/lxc") {
+			return Some("lxc".to_string());
+		}
+		None
+	}
+
+	pub fn detect_wsl() -> Option<u32> {
+		let release = read("/proc/sys/kernel/osrelease")?.to_ascii_lowercase();
+		if !release.contains("microsoft") {
+			return None;
+		}
+		// WSL2 runs a real kernel ("...-microsoft-standard-WSL2"); WSL1 reports
+		// a translated release ending in "-Microsoft".
+		Some(if release.contains("wsl2") || release.contains("microsoft-standard") {
+			2
+		} else {
+			1
+		})
+	}
+
+	pub fn detect_vm() -> Option<String> {
+		let vendor = read("/sys/class/dmi/id/sys_vendor").unwrap_or_default();
+		let product = read("/sys/class/dmi/id/product_name").unwrap_or_default();
+		if let Some(name) = classify_vm(vendor.trim(), product.trim()) {
+			return Some(name.to_string());
+		}
+		let cpuinfo = read("/proc/cpuinfo")?;
+		cpuinfo
+			.lines()
+			.filter(|line| line.starts_with("flags"))
+			.any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"))
+			.then(|| "vm".to_string())
+	}
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+	pub fn detect_vm() -> Option<String> {
+		let mut present: libc::c_int = 0;
+		let mut size = std::mem::size_of::<libc::c_int>();
+		// SAFETY: the name is NUL-terminated and `present`/`size` describe a
+		// valid, writable c_int buffer.
+		let rc = unsafe {
+			libc::sysctlbyname(
+				c"kern.hv_vmm_present".as_ptr(),
+				(&raw mut present).cast(),
+				&raw mut size,
+				std::ptr::null_mut(),
+				0,
+			)
+		};
+		(rc == 0 && present != 0).then(|| "vm".to_string())
+	}
+
+	pub const fn detect_container() -> Option<String> {
+		None
+	}
+
+	pub const fn detect_wsl() -> Option<u32> {
+		None
+	}
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+	use winreg::{RegKey, enums::HKEY_LOCAL_MACHINE};
+
+	use super::classify_vm;
+
+	pub fn detect_vm() -> Option<String> {
+		let bios = RegKey::predef(HKEY_LOCAL_MACHINE)
+			.open_subkey(r"HARDWARE\DESCRIPTION\System\BIOS")
+			.ok()?;
+		let vendor: String = bios.get_value("SystemManufacturer").unwrap_or_default();
+		let product: String = bios.get_value("SystemProductName").unwrap_or_default();
+		classify_vm(&vendor, &product).map(str::to_string)
+	}
+
+	pub const fn detect_container() -> Option<String> {
+		None
+	}
+
+	pub const fn detect_wsl() -> Option<u32> {
+		None
+	}
+}
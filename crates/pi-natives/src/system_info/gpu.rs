@@ -0,0 +1,251 @@
+//! GPU detection without shelling out to `nvidia-smi` or `system_profiler`.
+//!
+//! # Platform Implementation
+//! - **Linux**: display-class PCI devices from `/sys/bus/pci/devices`, named
+//!   via `pci.ids` or the NVIDIA driver's `/proc` entries; VRAM from amdgpu's
+//!   `mem_info_vram_total`. Non-PCI GPUs fall back to their DRM driver name.
+//! - **macOS**: Apple silicon GPUs, named after the `SoC` (memory is unified,
+//!   so no VRAM is reported)
+//! - **Windows**: display adapters from the registry's display device class,
+//!   with VRAM from `HardwareInformation.qwMemorySize`
+
+use napi_derive::napi;
+
+/// A detected graphics adapter.
+#[napi(object)]
+pub struct GpuInfo {
+	/// Vendor name (e.g. "NVIDIA", "AMD", "Intel", "Apple").
+	pub vendor:     String,
+	/// Adapter model name.
+	pub model:      String,
+	/// Dedicated video memory in bytes, when known.
+	#[napi(js_name = "vramBytes")]
+	pub vram_bytes: Option<f64>,
+}
+
+/// Detect graphics adapters, discrete GPUs first.
+pub fn detect_gpus() -> Vec<GpuInfo> {
+	let mut gpus = platform::detect();
+	// Integrated adapters rarely report VRAM; prefer the ones that do.
+	gpus.sort_by_key(|gpu| gpu.vram_bytes.is_none());
+	gpus
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code, reason = "Apple GPUs are not PCI devices"))]
+const fn vendor_name(id: u16) -> Option<&'static str> {
+	Some(match id {
+		0x10de => "NVIDIA",
+		0x1002 | 0x1022 => "AMD",
+		0x8086 => "Intel",
+		0x106b => "Apple",
+		0x13b5 => "ARM",
+		0x5143 => "Qualcomm",
+		0x1a03 => "ASPEED",
+		0x1414 => "Microsoft",
+		0x15ad => "VMware",
+		0x80ee => "VirtualBox",
+		0x1af4 => "Red Hat (virtio)",
+		0x1234 => "QEMU",
+		_ => return None,
+	})
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+	use std::{fs, path::Path};
+
+	use super::{GpuInfo, vendor_name};
+
+	const PCI_IDS: [&str; 3] =
+		["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids", "/usr/share/pci.ids"];
+
+	fn read_trimmed(path: &Path) -> Option<String> {
+		fs::read_to_string(path)
+			.ok()
+			.map(|value| value.trim().to_string())
+	}
+
+	fn read_hex(path: &Path) -> Option<u32> {
+		let value = read_trimmed(path)?;
+		u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+	}
+
+	/// Look up a device name in the first available `pci.ids` database.
+	fn pci_ids_name(vendor: u16, device: u16) -> Option<String> {
+		let db = PCI_IDS
+			.iter()
+			.find_map(|path| fs::read_to_string(path).ok())?;
+		let vendor_prefix = format!("{vendor:04x}  ");
+		let device_prefix = format!("\t{device:04x}  ");
+		let mut in_vendor = false;
+		for line in db.lines() {
+			if !line.starts_with('\t') && !line.starts_with('#') {
+				if in_vendor {
+					return None;
+				}
+				in_vendor = line.starts_with(&vendor_prefix);
+			} else if in_vendor && let Some(name) = line.strip_prefix(&device_prefix) {
+				return Some(name.trim().to_string());
+			}
+		}
+		None
+	}
+
+	/// The NVIDIA driver publishes the marketing name per bus id.
+	fn nvidia_model(bus_id: &str) -> Option<String> {
+		let info =
+			fs::read_to_string(format!("/proc/driver/nvidia/gpus/{bus_id}/information")).ok()?;
+		info
+			.lines()
+			.find_map(|line| line.strip_prefix("Model:"))
+			.map(|model| model.trim().to_string())
+	}
+
+	fn pci_gpus() -> Vec<GpuInfo> {
+		let Ok(entries) = fs::read_dir("/sys/bus/pci/devices") else {
+			return Vec::new();
+		};
+		let mut gpus = Vec::new();
+		for entry in entries.flatten() {
+			let dir = entry.path();
+			// PCI base class 0x03 is "display controller".
+			if read_hex(&dir.join("class")).is_none_or(|class| class >> 16 != 0x03) {
+				continue;
+			}
+			let vendor = read_hex(&dir.join("vendor")).unwrap_or(0) as u16;
+			let device = read_hex(&dir.join("device")).unwrap_or(0) as u16;
+			let bus_id = entry.file_name().to_string_lossy().into_owned();
+			let model = (vendor == 0x10de)
+				.then(|| nvidia_model(&bus_id))
+				.flatten()
+				.or_else(|| pci_ids_name(vendor, device))
+				.unwrap_or_else(|| format!("device 0x{device:04x}"));
+			let vram_bytes = read_trimmed(&dir.join("mem_info_vram_total"))
+				.and_then(|value| value.parse::<u64>().ok())
+				.filter(|bytes| *bytes > 0)
+				.map(|bytes| bytes as f64);
+			gpus.push(GpuInfo {
+				vendor: vendor_name(vendor).map_or_else(|| format!("0x{vendor:04x}"), str::to_string),
+				model,
+				vram_bytes,
+			});
+		}
+		gpus
+	}
+
+	/// `SoC` GPUs (e.g. Raspberry Pi, Jetson) only show up as DRM cards.
+	fn drm_gpus() -> Vec<GpuInfo> {
+		let Ok(entries) = fs::read_dir("/sys/class/drm") else {
+			return Vec::new();
+		};
+		entries
+			.flatten()
+			.filter(|entry| {
+				let name = entry.file_name();
+				let name = name.to_string_lossy();
+				name.starts_with("card") && !name.contains('-')
+			})
+			.filter_map(|entry| {
+				let uevent = fs::read_to_string(entry.path().join("device/uevent")).ok()?;
+				let driver = uevent
+					.lines()
+					.find_map(|line| line.strip_prefix("DRIVER="))?;
+				Some(GpuInfo {
+					vendor:     "unknown".to_string(),
+					model:      driver.to_string(),
+					vram_bytes: None,
+				})
+			})
+			.collect()
+	}
+
+	pub fn detect() -> Vec<GpuInfo> {
+		let gpus = pci_gpus();
+		if gpus.is_empty() { drm_gpus() } else { gpus }
+	}
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+	use sysinfo::System;
+
+	use super::GpuInfo;
+
+	pub fn detect() -> Vec<GpuInfo> {
+		if !cfg!(target_arch = "aarch64") {
+			return Vec::new();
+		}
+		let system = System::new_with_specifics(
+			sysinfo::RefreshKind::nothing().with_cpu(sysinfo::CpuRefreshKind::nothing()),
+		);
+		let Some(brand) = system
+			.cpus()
+			.first()
+			.map(|cpu| cpu.brand().trim().to_string())
+			.filter(|brand| brand.starts_with("Apple"))
+		else {
+			return Vec::new();
+		};
+		vec![GpuInfo {
+			vendor:     "Apple".to_string(),
+			model:      format!("{brand} GPU"),
+			vram_bytes: None,
+		}]
+	}
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+	use winreg::{RegKey, enums::HKEY_LOCAL_MACHINE};
+
+	use super::{GpuInfo, vendor_name};
+
+	/// Device class GUID for display adapters.
+	const DISPLAY_CLASS: &str =
+		r"SYSTEM\CurrentControlSet\Control\Class\{4d36e968-e325-11ce-bfc1-08002be10318}";
+
+	/// Parse the vendor id out of a hardware id like `pci\ven_10de&dev_2684`.
+	fn vendor_id(hardware_id: &str) -> Option<u16> {
+		let lower = hardware_id.to_ascii_lowercase();
+		let start = lower.find("ven_")? + 4;
+		u16::from_str_radix(lower.get(start..start + 4)?, 16).ok()
+	}
+
+	fn vram(key: &RegKey) -> Option<u64> {
+		if let Ok(bytes) = key.get_value::<u64, _>("HardwareInformation.qwMemorySize") {
+			return Some(bytes);
+		}
+		// Older drivers store a 32-bit size, as DWORD or raw bytes.
+		let raw = key.get_raw_value("HardwareInformation.MemorySize").ok()?;
+		let bytes: [u8; 4] = raw.bytes.get(..4)?.try_into().ok()?;
+		Some(u32::from_le_bytes(bytes) as u64)
+	}
+
+	pub fn detect() -> Vec<GpuInfo> {
+		let Ok(class) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(DISPLAY_CLASS) else {
+			return Vec::new();
+		};
+		class
+			.enum_keys()
+			.flatten()
+			// Adapter instances are numbered ("0000", "0001", ...).
+			.filter(|name| name.chars().all(|c| c.is_ascii_digit()))
+			.filter_map(|name| class.open_subkey(name).ok())
+			.filter_map(|key| {
+				let model: String = key.get_value("DriverDesc").ok()?;
+				let vendor = key
+					.get_value::<String, _>("MatchingDeviceId")
+					.ok()
+					.and_then(|id| vendor_id(&id))
+					.and_then(vendor_name)
+					.map(str::to_string)
+					.or_else(|| key.get_value::<String, _>("ProviderName").ok())
+					.unwrap_or_else(|| "unknown".to_string());
+				let vram_bytes = vram(&key)
+					.filter(|bytes| *bytes > 0)
+					.map(|bytes| bytes as f64);
+				Some(GpuInfo { vendor, model, vram_bytes })
+			})
+			.collect()
+	}
+}
@@ -0,0 +1,181 @@
+//! Detection of the locale, time zone and keyboard layout.
+//!
+//! # Platform Implementation
+//! - **All**: locales from `LC_ALL`, the category variable, then `LANG`; the
+//!   time zone from `TZ`, then the system configuration
+//! - **Linux**: default locale from `/etc/locale.conf` or
+//!   `/etc/default/locale`; keyboard layout from `/etc/vconsole.conf`,
+//!   `/etc/default/keyboard` or the X11 keyboard configuration
+//! - **macOS**: default locale from `NSLocale`; no keyboard layout
+//! - **Windows**: default locale from the user settings; keyboard layout as the
+//!   language of the active input locale
+
+use std::env;
+
+use jiff::{Timestamp, tz::TimeZone};
+use napi_derive::napi;
+
+/// Locale, time zone and keyboard layout of the user.
+#[napi(object)]
+pub struct LocaleInfo {
+	/// Locale for messages (e.g. "en_US.UTF-8", "de-DE").
+	pub locale:             Option<String>,
+	/// Locale for dates and times, which `LC_TIME` may set apart from
+	/// `locale`.
+	#[napi(js_name = "timeLocale")]
+	pub time_locale:        Option<String>,
+	/// IANA time zone name (e.g. "Europe/Berlin").
+	pub timezone:           Option<String>,
+	/// Current offset from UTC in minutes, east positive.
+	#[napi(js_name = "utcOffsetMinutes")]
+	pub utc_offset_minutes: i32,
+	/// Keyboard layout (e.g. "us", "de", or on Windows the input language
+	/// such as "de-DE").
+	#[napi(js_name = "keyboardLayout")]
+	pub keyboard_layout:    Option<String>,
+}
+
+/// Detect the locale, time zone and keyboard layout.
+pub fn detect_locale() -> LocaleInfo {
+	let default = platform::default_locale();
+	let time_zone = TimeZone::try_system().ok();
+	LocaleInfo {
+		locale:             env_locale("LC_MESSAGES").or_else(|| default.clone()),
+		time_locale:        env_locale("LC_TIME").or(default),
+		timezone:           time_zone
+			.as_ref()
+			.and_then(|zone| zone.iana_name().map(str::to_string)),
+		utc_offset_minutes: time_zone
+			.map_or(0, |zone| zone.to_offset(Timestamp::now()).seconds() / 60),
+		keyboard_layout:    platform::keyboard_layout(),
+	}
+}
+
+/// Locale for `category` from the environment, as the C library resolves it.
+fn env_locale(category: &str) -> Option<String> {
+	["LC_ALL", category, "LANG"]
+		.into_iter()
+		.filter_map(|name| env::var(name).ok())
+		.find(|value| !value.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+	use std::fs;
+
+	use super::super::{parse_key_value, strip_quotes};
+
+	/// `key` from the first of `files` that sets it.
+	fn setting(files: &[&str], key: &str) -> Option<String> {
+		files.iter().find_map(|file| {
+			let content = fs::read_to_string(file).ok()?;
+			parse_key_value(&content)
+				.get(key)
+				.map(|value| strip_quotes(value))
+				.filter(|value| !value.is_empty())
+		})
+	}
+
+	pub fn default_locale() -> Option<String> {
+		setting(&["/etc/locale.conf", "/etc/default/locale"], "LANG")
+	}
+
+	pub fn keyboard_layout() -> Option<String> {
+		setting(&["/etc/vconsole.conf", "/etc/default/keyboard"], "XKBLAYOUT")
+			.or_else(x11_layout)
+			.or_else(|| setting(&["/etc/vconsole.conf"], "KEYMAP"))
+	}
+
+	/// Layout from `Option "XkbLayout" "us"` in the X11 keyboard configuration.
+	fn x11_layout() -> Option<String> {
+		let content = fs::read_to_string("/etc/X11/xorg.conf.d/00-keyboard.conf").ok()?;
+		content.lines().find_map(|line| {
+			if !line.trim_start().starts_with("Option") {
+				return None;
+			}
+			let mut quoted = line.split('"').skip(1).step_by(2);
+			if quoted.next()? != "XkbLayout" {
+				return None;
+			}
+			quoted.next().map(str::to_string)
+		})
+	}
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+	use std::ffi::{CStr, c_char};
+
+	use objc2::{class, msg_send, rc::autoreleasepool, runtime::AnyObject};
+
+	#[link(name = "Foundation", kind = "framework")]
+	unsafe extern "C" {}
+
+	pub fn default_locale() -> Option<String> {
+		autoreleasepool(|_| {
+			// SAFETY: `currentLocale` returns an autoreleased NSLocale whose
+			// `localeIdentifier` is an NSString; UTF8String returns a
+			// NUL-terminated buffer or null, valid within the pool.
+			unsafe {
+				let locale: *mut AnyObject = msg_send![class!(NSLocale), currentLocale];
+				let identifier: *mut AnyObject = msg_send![locale, localeIdentifier];
+				if identifier.is_null() {
+					return None;
+				}
+				let utf8: *const c_char = msg_send![identifier, UTF8String];
+				(!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned())
+			}
+		})
+	}
+
+	pub const fn keyboard_layout() -> Option<String> {
+		None
+	}
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+	/// `LOCALE_NAME_MAX_LENGTH`.
+	const NAME_LEN: usize = 85;
+	/// `KL_NAMELENGTH`.
+	const KLID_LEN: usize = 9;
+
+	#[link(name = "kernel32")]
+	unsafe extern "system" {
+		fn GetUserDefaultLocaleName(name: *mut u16, len: i32) -> i32;
+		fn LCIDToLocaleName(locale: u32, name: *mut u16, len: i32, flags: u32) -> i32;
+	}
+
+	#[link(name = "user32")]
+	unsafe extern "system" {
+		fn GetKeyboardLayoutNameW(name: *mut u16) -> i32;
+	}
+
+	/// String from a buffer holding `len` characters including the NUL.
+	fn to_string(buf: &[u16], len: i32) -> Option<String> {
+		let len = usize::try_from(len).ok()?.checked_sub(1)?;
+		(len > 0).then(|| String::from_utf16_lossy(&buf[..len]))
+	}
+
+	pub fn default_locale() -> Option<String> {
+		let mut buf = [0u16; NAME_LEN];
+		// SAFETY: `buf` holds `NAME_LEN` characters.
+		let len = unsafe { GetUserDefaultLocaleName(buf.as_mut_ptr(), NAME_LEN as i32) };
+		to_string(&buf, len)
+	}
+
+	pub fn keyboard_layout() -> Option<String> {
+		let mut klid = [0u16; KLID_LEN];
+		// SAFETY: `klid` holds `KL_NAMELENGTH` characters.
+		if unsafe { GetKeyboardLayoutNameW(klid.as_mut_ptr()) } == 0 {
+			return None;
+		}
+		let klid = String::from_utf16_lossy(&klid[..KLID_LEN - 1]);
+		// The low word is the language of the layout.
+		let language = u32::from_str_radix(&klid, 16).ok()? & 0xffff;
+		let mut buf = [0u16; NAME_LEN];
+		// SAFETY: `buf` holds `NAME_LEN` characters.
+		let len = unsafe { LCIDToLocaleName(language, buf.as_mut_ptr(), NAME_LEN as i32, 0) };
+		to_string(&buf, len).or(Some(klid))
+	}
+}
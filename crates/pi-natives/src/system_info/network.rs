@@ -0,0 +1,519 @@
+//! Network interface summary, a cheap connectivity probe, and local port
+//! helpers.
+//!
+//! # Overview
+//! Interfaces and their addresses come from [`sysinfo`]. The primary
+//! addresses are the local ends the OS would route public traffic from, found
+//! by connecting (not sending on) a UDP socket; the same connect tells whether
+//! a default route exists. The online probe opens TCP connections to public
+//! resolvers by IP, so it does not depend on DNS.
+//!
+//! A port counts as free when a TCP listener can bind it on every local
+//! address, IPv4 and IPv6, wildcard and loopback.
+//!
+//! # Platform Implementation
+//! Listening TCP sockets are read from:
+//! - **Linux**: `/proc/net/tcp` and `/proc/net/tcp6`, with owners found by
+//!   matching socket inodes against `/proc/<pid>/fd`
+//! - **Windows**: `GetExtendedTcpTable`, which reports owners directly
+//! - **macOS**: not supported yet
+//!
+//! `killPortOwners` terminates the process trees holding a port, like
+//! `killTreeGraceful`.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const net = await native.getNetworkInfo({ timeoutMs: 1000 });
+//! // JS: if (net.online === false) warn("you appear to be offline");
+//! // JS: const port = native.findFreePort({ start: 3000, end: 3100 });
+//! ```
+
+use std::{
+	collections::HashMap,
+	io,
+	net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, UdpSocket},
+	time::Duration,
+};
+
+use napi::{
+	Env, Error, Result,
+	bindgen_prelude::PromiseRaw,
+	tokio::{net::TcpStream, task::JoinSet, time},
+};
+use napi_derive::napi;
+use sysinfo::Networks;
+
+use crate::{ps, task};
+
+const DEFAULT_PROBE_TIMEOUT_MS: u32 = 1500;
+
+/// Public addresses used to find the routed interface; nothing is sent.
+const ROUTE_TARGET_V4: &str = "8.8.8.8:53";
+const ROUTE_TARGET_V6: &str = "[2001:4860:4860::8888]:53";
+
+/// Endpoints for the online probe; reaching any one counts as online.
+const PROBE_TARGETS: [&str; 3] = ["1.1.1.1:443", "8.8.8.8:443", "[2606:4700:4700::1111]:443"];
+
+/// Local addresses a free port must be bindable on.
+const LOCAL_ADDRS: [IpAddr; 4] = [
+	IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+	IpAddr::V4(Ipv4Addr::LOCALHOST),
+	IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+	IpAddr::V6(Ipv6Addr::LOCALHOST),
+];
+/// Attempts at finding an OS-assigned port that is free everywhere.
+const EPHEMERAL_ATTEMPTS: usize = 16;
+const DEFAULT_KILL_GRACE_MS: u32 = 1000;
+
+/// A network interface and its addresses.
+#[napi(object)]
+pub struct NetworkInterface {
+	/// Interface name (e.g. "eth0", "en0", "Ethernet").
+	pub name:      String,
+	/// Hardware address, when the interface has one.
+	pub mac:       Option<String>,
+	/// Addresses in CIDR notation (e.g. "192.168.1.20/24").
+	pub addresses: Vec<String>,
+	/// Whether every address is a loopback address.
+	pub loopback:  bool,
+}
+
+/// Options for `getNetworkInfo`.
+#[napi(object)]
+#[derive(Default)]
+pub struct NetworkInfoOptions {
+	/// Probe internet connectivity (default: true).
+	pub probe:      Option<bool>,
+	/// Timeout for the connectivity probe in milliseconds (default: 1500).
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+}
+
+/// Network interfaces, routing, and connectivity summary.
+#[napi(object)]
+pub struct NetworkInfo {
+	/// Network interfaces with their addresses.
+	pub interfaces:    Vec<NetworkInterface>,
+	/// Local IPv4 address used for outbound traffic.
+	#[napi(js_name = "primaryIpv4")]
+	pub primary_ipv4:  Option<String>,
+	/// Local IPv6 address used for outbound traffic.
+	#[napi(js_name = "primaryIpv6")]
+	pub primary_ipv6:  Option<String>,
+	/// Whether a default route (gateway) exists for IPv4 or IPv6.
+	#[napi(js_name = "defaultRoute")]
+	pub default_route: bool,
+	/// Whether a public host was reachable; absent when probing is disabled.
+	pub online:        Option<bool>,
+}
+
+fn list_interfaces() -> Vec<NetworkInterface> {
+	let networks = Networks::new_with_refreshed_list();
+	let mut interfaces: Vec<NetworkInterface> = networks
+		.iter()
+		.map(|(name, data)| {
+			let mut ips = data.ip_networks().to_vec();
+			// IPv4 first, then IPv6, each in address order.
+			ips.sort_by_key(|ip| (ip.addr.is_ipv6(), ip.addr));
+			let mac = data.mac_address();
+			NetworkInterface {
+				name:      name.clone(),
+				mac:       (!mac.is_unspecified()).then(|| mac.to_string()),
+				addresses: ips
+					.iter()
+					.map(|ip| format!("{}/{}", ip.addr, ip.prefix))
+					.collect(),
+				loopback:  !ips.is_empty() && ips.iter().all(|ip| ip.addr.is_loopback()),
+			}
+		})
+		.collect();
+	interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+	interfaces
+}
+
+/// Local address the OS picks to reach `target`, if a route exists.
+fn routed_local_addr(bind: &str, target: &str) -> Option<IpAddr> {
+	let socket = UdpSocket::bind(bind).ok()?;
+	socket.connect(target).ok()?;
+	let local = socket.local_addr().ok()?.ip();
+	(!local.is_unspecified()).then_some(local)
+}
+
+async fn probe_online(timeout: Duration) -> bool {
+	let mut attempts = JoinSet::new();
+	for target in PROBE_TARGETS {
+		let Ok(addr) = target.parse::<SocketAddr>() else {
+			continue;
+		};
+		attempts.spawn(async move {
+			matches!(time::timeout(timeout, TcpStream::connect(addr)).await, Ok(Ok(_)))
+		});
+	}
+	while let Some(result) = attempts.join_next().await {
+		if matches!(result, Ok(true)) {
+			return true;
+		}
+	}
+	false
+}
+
+/// Collect network interfaces, primary addresses, default route presence and
+/// (optionally) internet reachability.
+#[napi(js_name = "getNetworkInfo")]
+pub fn get_network_info(
+	env: &Env,
+	options: Option<NetworkInfoOptions>,
+) -> Result<PromiseRaw<'_, NetworkInfo>> {
+	let options = options.unwrap_or_default();
+	let probe = options.probe.unwrap_or(true);
+	let timeout = Duration::from_millis(
+		options
+			.timeout_ms
+			.unwrap_or(DEFAULT_PROBE_TIMEOUT_MS)
+			.into(),
+	);
+
+	task::future(env, "system_info.network", async move {
+		let primary_ipv4 = routed_local_addr("0.0.0.0:0", ROUTE_TARGET_V4);
+		let primary_ipv6 = routed_local_addr("[::]:0", ROUTE_TARGET_V6);
+		let online = if probe {
+			Some(probe_online(timeout).await)
+		} else {
+			None
+		};
+		Ok(NetworkInfo {
+			interfaces: list_interfaces(),
+			primary_ipv4: primary_ipv4.map(|ip| ip.to_string()),
+			primary_ipv6: primary_ipv6.map(|ip| ip.to_string()),
+			default_route: primary_ipv4.is_some() || primary_ipv6.is_some(),
+			online,
+		})
+	})
+}
+
+/// A TCP socket listening for connections.
+#[napi(object)]
+pub struct ListeningPort {
+	/// Port number.
+	pub port:    u32,
+	/// Local address listened on; `0.0.0.0` or `::` for every address.
+	pub address: String,
+	/// Process owning the socket (the lowest pid when several share it), when
+	/// it can be determined.
+	pub pid:     Option<i32>,
+	/// Executable name of the owning process.
+	pub process: Option<String>,
+}
+
+/// Inclusive range of ports for `findFreePort`.
+#[napi(object)]
+pub struct PortRange {
+	/// First port to try.
+	pub start: u32,
+	/// Last port to try.
+	pub end:   u32,
+}
+
+fn to_port(port: u32) -> Result<u16> {
+	u16::try_from(port)
+		.ok()
+		.filter(|&port| port != 0)
+		.ok_or_else(|| Error::from_reason(format!("Invalid port: {port}")))
+}
+
+/// Whether a listener can bind `port` on `ip`. Addresses this machine does
+/// not have (no IPv6) impose no constraint.
+fn bindable(ip: IpAddr, port: u16) -> bool {
+	match TcpListener::bind(SocketAddr::new(ip, port)) {
+		Ok(_) => true,
+		Err(err) => {
+			ip.is_ipv6()
+				&& matches!(err.kind(), io::ErrorKind::AddrNotAvailable | io::ErrorKind::Unsupported)
+		},
+	}
+}
+
+fn port_free(host: Option<IpAddr>, port: u16) -> bool {
+	match host {
+		Some(ip) => bindable(ip, port),
+		None => LOCAL_ADDRS.iter().all(|&ip| bindable(ip, port)),
+	}
+}
+
+fn parse_host(host: Option<String>) -> Result<Option<IpAddr>> {
+	host
+		.map(|host| {
+			host
+				.parse()
+				.map_err(|_| Error::from_reason(format!("Invalid IP address: {host}")))
+		})
+		.transpose()
+}
+
+/// Whether a server could listen on `port`: by default on every local
+/// address, or only on `host` (an IP address) when given.
+///
+/// # Errors
+/// Returns an error for port 0 or above 65535, or an invalid `host`.
+#[napi(js_name = "isPortFree")]
+pub fn is_port_free(port: u32, host: Option<String>) -> Result<bool> {
+	Ok(port_free(parse_host(host)?, to_port(port)?))
+}
+
+/// Find a port a server could listen on on every local address: the first
+/// free one in `range`, or one the OS picks when no range is given. Returns
+/// `null` when none is free.
+///
+/// # Errors
+/// Returns an error when `range` holds an invalid port or is reversed.
+#[napi(js_name = "findFreePort")]
+pub fn find_free_port(range: Option<PortRange>) -> Result<Option<u32>> {
+	let Some(range) = range else {
+		for _ in 0..EPHEMERAL_ATTEMPTS {
+			let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))
+				.map_err(|err| Error::from_reason(format!("Failed to bind a port: {err}")))?;
+			let port = listener.local_addr()?.port();
+			drop(listener);
+			if port_free(None, port) {
+				return Ok(Some(port.into()));
+			}
+		}
+		return Ok(None);
+	};
+	let (start, end) = (to_port(range.start)?, to_port(range.end)?);
+	if start > end {
+		return Err(Error::from_reason(format!("Invalid port range: {start}-{end}")));
+	}
+	Ok((start..=end)
+		.find(|&port| port_free(None, port))
+		.map(u32::from))
+}
+
+/// Listening TCP sockets with the pids holding them.
+#[cfg(target_os = "linux")]
+#[allow(clippy::unnecessary_wraps, reason = "fails on other platforms")]
+fn listening_sockets() -> Result<Vec<(SocketAddr, Vec<i32>)>> {
+	let owners = ps::socket_owners();
+	let mut sockets = Vec::new();
+	for (table, v6) in [("/proc/net/tcp", false), ("/proc/net/tcp6", true)] {
+		let Ok(table) = std::fs::read_to_string(table) else {
+			continue;
+		};
+		for line in table.lines().skip(1) {
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			// Fields: sl, local, remote, state (0A = LISTEN), ..., inode (10th).
+			if fields.len() < 10 || fields[3] != "0A" {
+				continue;
+			}
+			let Some(addr) = parse_proc_addr(fields[1], v6) else {
+				continue;
+			};
+			let pids = fields[9]
+				.parse()
+				.ok()
+				.and_then(|inode: u64| owners.get(&inode).cloned())
+				.unwrap_or_default();
+			sockets.push((addr, pids));
+		}
+	}
+	Ok(sockets)
+}
+
+/// Parse a `/proc/net/tcp` address: hex words in host byte order, then a hex
+/// port.
+#[cfg(target_os = "linux")]
+fn parse_proc_addr(field: &str, v6: bool) -> Option<SocketAddr> {
+	let (ip, port) = field.split_once(':')?;
+	let port = u16::from_str_radix(port, 16).ok()?;
+	let word = |index: usize| {
+		let hex = ip.get(index * 8..index * 8 + 8)?;
+		Some(u32::from_str_radix(hex, 16).ok()?.to_ne_bytes())
+	};
+	let ip = if v6 {
+		let mut bytes = [0u8; 16];
+		for (index, chunk) in bytes.as_chunks_mut::<4>().0.iter_mut().enumerate() {
+			*chunk = word(index)?;
+		}
+		IpAddr::from(bytes)
+	} else {
+		IpAddr::from(word(0)?)
+	};
+	Some(SocketAddr::new(ip, port))
+}
+
+#[cfg(target_os = "windows")]
+fn listening_sockets() -> Result<Vec<(SocketAddr, Vec<i32>)>> {
+	const AF_INET: u32 = 2;
+	const AF_INET6: u32 = 23;
+	const TCP_TABLE_OWNER_PID_LISTENER: i32 = 3;
+	const ERROR_INSUFFICIENT_BUFFER: u32 = 122;
+	/// `MIB_TCPROW_OWNER_PID` and `MIB_TCP6ROW_OWNER_PID` sizes, in words.
+	const ROW_WORDS_V4: usize = 6;
+	const ROW_WORDS_V6: usize = 14;
+
+	#[link(name = "iphlpapi")]
+	unsafe extern "system" {
+		fn GetExtendedTcpTable(
+			pTcpTable: *mut std::ffi::c_void,
+			pdwSize: *mut u32,
+			bOrder: i32,
+			ulAf: u32,
+			TableClass: i32,
+			Reserved: u32,
+		) -> u32;
+	}
+
+	/// The table for `family` as words: the row count, then the rows.
+	fn table(family: u32) -> Result<Vec<u32>> {
+		let mut size = 0u32;
+		let mut words = Vec::new();
+		loop {
+			words.resize((size as usize).div_ceil(4).max(1), 0);
+			size = (words.len() * 4) as u32;
+			// SAFETY: `words` is a writable, u32-aligned buffer of `size` bytes.
+			let status = unsafe {
+				GetExtendedTcpTable(
+					words.as_mut_ptr().cast(),
+					&raw mut size,
+					0,
+					family,
+					TCP_TABLE_OWNER_PID_LISTENER,
+					0,
+				)
+			};
+			match status {
+				0 => return Ok(words),
+				ERROR_INSUFFICIENT_BUFFER => {},
+				code => {
+					return Err(Error::from_reason(format!(
+						"Failed to read the TCP table (error {code})"
+					)));
+				},
+			}
+		}
+	}
+
+	/// Port stored in network byte order in the low bytes of a word.
+	fn port(word: u32) -> u16 {
+		let bytes = word.to_ne_bytes();
+		u16::from_be_bytes([bytes[0], bytes[1]])
+	}
+
+	let mut sockets = Vec::new();
+	let v4 = table(AF_INET)?;
+	for row in v4[1..].chunks_exact(ROW_WORDS_V4).take(v4[0] as usize) {
+		let addr = SocketAddr::new(IpAddr::from(row[1].to_ne_bytes()), port(row[2]));
+		sockets.push((addr, vec![row[5] as i32]));
+	}
+	let v6 = table(AF_INET6)?;
+	for row in v6[1..].chunks_exact(ROW_WORDS_V6).take(v6[0] as usize) {
+		let mut bytes = [0u8; 16];
+		for (chunk, word) in bytes.as_chunks_mut::<4>().0.iter_mut().zip(&row[..4]) {
+			*chunk = word.to_ne_bytes();
+		}
+		sockets.push((SocketAddr::new(IpAddr::from(bytes), port(row[5])), vec![row[13] as i32]));
+	}
+	Ok(sockets)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn listening_sockets() -> Result<Vec<(SocketAddr, Vec<i32>)>> {
+	Err(Error::from_reason("Listing listening ports is not supported on this platform"))
+}
+
+fn list_listening_ports_sync() -> Result<Vec<ListeningPort>> {
+	let mut sockets = listening_sockets()?;
+	for (_, pids) in &mut sockets {
+		pids.sort_unstable();
+	}
+	sockets.sort_by(|(a, a_pids), (b, b_pids)| {
+		(a.port(), a.ip(), a_pids.first()).cmp(&(b.port(), b.ip(), b_pids.first()))
+	});
+	sockets.dedup_by(|(a, a_pids), (b, b_pids)| a == b && a_pids.first() == b_pids.first());
+
+	let mut pids: Vec<i32> = sockets
+		.iter()
+		.filter_map(|(_, pids)| pids.first().copied())
+		.collect();
+	pids.sort_unstable();
+	pids.dedup();
+	let names: HashMap<i32, String> = ps::process_names(&pids);
+	Ok(sockets
+		.into_iter()
+		.map(|(addr, pids)| {
+			let pid = pids.first().copied();
+			ListeningPort {
+				port: addr.port().into(),
+				address: addr.ip().to_string(),
+				pid,
+				process: pid.and_then(|pid| names.get(&pid).cloned()),
+			}
+		})
+		.collect())
+}
+
+/// List TCP sockets listening for connections, by port, with the process
+/// holding each one when it can be determined (processes of other users
+/// usually cannot be on Linux).
+///
+/// # Errors
+/// Rejects on platforms without support (macOS).
+#[napi(js_name = "listListeningPorts")]
+pub fn list_listening_ports() -> task::Async<Vec<ListeningPort>> {
+	task::blocking("system_info.ports", (), |_| list_listening_ports_sync())
+}
+
+/// Pids holding a socket listening on `port`, on any address.
+fn pids_listening_on_sync(port: u16) -> Result<Vec<i32>> {
+	let mut pids: Vec<i32> = listening_sockets()?
+		.into_iter()
+		.filter(|(addr, _)| addr.port() == port)
+		.flat_map(|(_, pids)| pids)
+		.collect();
+	pids.sort_unstable();
+	pids.dedup();
+	Ok(pids)
+}
+
+/// List the processes listening on TCP `port`, on any address, sorted by
+/// pid. Processes of other users usually cannot be seen on Linux.
+///
+/// # Errors
+/// Rejects for an invalid port, or on platforms without support (macOS).
+#[napi(js_name = "pidsListeningOn")]
+pub fn pids_listening_on(port: u32) -> task::Async<Vec<i32>> {
+	task::blocking("system_info.port_pids", (), move |_| pids_listening_on_sync(to_port(port)?))
+}
+
+/// Terminate every process listening on TCP `port`, with its descendants:
+/// SIGTERM, then SIGKILL for processes still running after `graceMs`
+/// (default: 1000). This process is never killed.
+///
+/// Returns one report per process that was part of a killed tree.
+///
+/// # Errors
+/// Rejects for an invalid port, or on platforms without support (macOS).
+#[napi(js_name = "killPortOwners")]
+pub fn kill_port_owners(
+	env: &Env,
+	port: u32,
+	grace_ms: Option<u32>,
+) -> Result<PromiseRaw<'_, Vec<ps::KillReport>>> {
+	let grace = Duration::from_millis(grace_ms.unwrap_or(DEFAULT_KILL_GRACE_MS).into());
+	task::future(env, "system_info.kill_port", async move {
+		let port = to_port(port)?;
+		let pids = napi::tokio::task::spawn_blocking(move || pids_listening_on_sync(port))
+			.await
+			.map_err(|err| Error::from_reason(format!("Port lookup failed: {err}")))??;
+		let own = std::process::id() as i32;
+		let mut reports: Vec<ps::KillReport> = Vec::new();
+		for pid in pids {
+			// Skip owners already killed as part of an earlier tree.
+			if pid == own || reports.iter().any(|report| report.pid == pid) {
+				continue;
+			}
+			reports.extend(ps::terminate_tree(pid, grace).await);
+		}
+		Ok(reports)
+	})
+}
@@ -0,0 +1,543 @@
+//! Battery and power source reporting.
+//!
+//! # Overview
+//! `getPowerInfo` reports the combined charge of the machine's batteries,
+//! whether they are charging, and whether it runs on mains power, so a long
+//! job can wait or warn on a laptop about to run out. Batteries of
+//! peripherals such as wireless mice are ignored.
+//!
+//! `subscribePowerEvents` polls the same state on a thread of its own and
+//! reports when the power source or charging state changes, and when the
+//! charge drops to `lowPercent` on battery.
+//!
+//! # Platform Implementation
+//! - **Linux**: `/sys/class/power_supply`
+//! - **macOS**: `IOKit` power source descriptions
+//! - **Windows**: `GetSystemPowerStatus`
+//!
+//! # Example
+//! ```ignore
+//! // JS: const power = native.getPowerInfo();
+//! // JS: if (power.source === "battery" && power.percent < 20) warn("low battery");
+//! // JS: const id = native.subscribePowerEvents({ lowPercent: 15 }, (err, event) => {});
+//! ```
+
+use std::{
+	collections::HashMap,
+	sync::{
+		Arc, LazyLock,
+		atomic::{AtomicU32, Ordering},
+	},
+	thread,
+	time::Duration,
+};
+
+use napi::{
+	Error, Result,
+	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+};
+use napi_derive::napi;
+use parking_lot::{Condvar, Mutex};
+
+const DEFAULT_INTERVAL_MS: u32 = 30_000;
+const DEFAULT_LOW_PERCENT: f64 = 20.0;
+
+static SUBSCRIPTIONS: LazyLock<Mutex<HashMap<u32, Arc<Subscription>>>> =
+	LazyLock::new(Mutex::default);
+static NEXT_SUBSCRIPTION_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Battery charge and power source.
+#[napi(object)]
+#[derive(Clone, PartialEq)]
+pub struct PowerInfo {
+	/// Whether the machine has a battery.
+	#[napi(js_name = "hasBattery")]
+	pub has_battery:       bool,
+	/// Charge of all batteries together, 0-100.
+	pub percent:           Option<f64>,
+	/// Battery state: "charging", "discharging", "full", "not-charging"
+	/// (plugged in but held, e.g. by a charge limit) or "unknown".
+	#[napi(ts_type = "\"charging\" | \"discharging\" | \"full\" | \"not-charging\" | \"unknown\"")]
+	pub state:             Option<String>,
+	/// Power source: "ac", "battery" or "unknown".
+	#[napi(ts_type = "\"ac\" | \"battery\" | \"unknown\"")]
+	pub source:            String,
+	/// Estimated minutes until empty while discharging, or until full while
+	/// charging.
+	#[napi(js_name = "minutesRemaining")]
+	pub minutes_remaining: Option<f64>,
+}
+
+impl PowerInfo {
+	fn new(percent: Option<f64>, state: &str, source: &str, minutes: Option<f64>) -> Self {
+		Self {
+			has_battery:       true,
+			percent:           percent.map(|percent| percent.clamp(0.0, 100.0)),
+			state:             Some(state.to_string()),
+			source:            source.to_string(),
+			minutes_remaining: minutes.filter(|minutes| minutes.is_finite() && *minutes >= 0.0),
+		}
+	}
+
+	fn no_battery(source: &str) -> Self {
+		Self {
+			has_battery:       false,
+			percent:           None,
+			state:             None,
+			source:            source.to_string(),
+			minutes_remaining: None,
+		}
+	}
+
+	fn is_low(&self, low_percent: f64) -> bool {
+		self.source != "ac" && self.percent.is_some_and(|percent| percent <= low_percent)
+	}
+}
+
+/// A change reported by `subscribePowerEvents`.
+#[napi(object)]
+pub struct PowerEvent {
+	/// What changed: the power "source", the charging "state", or the charge
+	/// dropped to `lowPercent` on battery ("low").
+	#[napi(ts_type = "\"source\" | \"state\" | \"low\"")]
+	pub reason: String,
+	/// Power state after the change.
+	pub power:  PowerInfo,
+}
+
+/// Options for `subscribePowerEvents`.
+#[napi(object)]
+#[derive(Default)]
+pub struct PowerEventOptions {
+	/// Interval between checks in milliseconds (default: 30000).
+	#[napi(js_name = "intervalMs")]
+	pub interval_ms: Option<u32>,
+	/// Charge, in percent, reported as low on battery (default: 20).
+	#[napi(js_name = "lowPercent")]
+	pub low_percent: Option<f64>,
+}
+
+#[derive(Default)]
+struct Subscription {
+	stopped: Mutex<bool>,
+	wake:    Condvar,
+}
+
+/// Read the current power state.
+pub fn read_power() -> PowerInfo {
+	platform::read()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+	use std::{fs, path::Path};
+
+	use super::PowerInfo;
+
+	fn attr(dir: &Path, name: &str) -> Option<String> {
+		fs::read_to_string(dir.join(name))
+			.ok()
+			.map(|value| value.trim().to_string())
+	}
+
+	fn number(dir: &Path, name: &str) -> Option<f64> {
+		attr(dir, name)?.parse().ok()
+	}
+
+	pub fn read() -> PowerInfo {
+		let mut mains: Option<bool> = None;
+		let mut statuses = Vec::new();
+		let mut capacities = Vec::new();
+		let (mut now, mut full, mut rate) = (0.0, 0.0, 0.0);
+		for entry in fs::read_dir("/sys/class/power_supply")
+			.into_iter()
+			.flatten()
+			.flatten()
+		{
+			let dir = entry.path();
+			let Some(kind) = attr(&dir, "type") else {
+				continue;
+			};
+			if kind == "Mains" || kind.starts_with("USB") {
+				if let Some(online) = attr(&dir, "online") {
+					mains = Some(mains.unwrap_or(false) || online == "1");
+				}
+				continue;
+			}
+			// Peripheral batteries report a `Device` scope.
+			if kind != "Battery" || attr(&dir, "scope").as_deref() == Some("Device") {
+				continue;
+			}
+			if attr(&dir, "present").as_deref() == Some("0") {
+				continue;
+			}
+			statuses.push(attr(&dir, "status").unwrap_or_default());
+			// Energy in µWh with power in µW, or charge in µAh with current in µA.
+			let measured = [("energy", "power"), ("charge", "current")]
+				.iter()
+				.find_map(|(amount, flow)| {
+					let battery_now = number(&dir, &format!("{amount}_now"))?;
+					let battery_full = number(&dir, &format!("{amount}_full"))?;
+					let flow = number(&dir, &format!("{flow}_now")).unwrap_or(0.0).abs();
+					Some((battery_now, battery_full, flow))
+				});
+			if let Some((battery_now, battery_full, flow)) = measured {
+				now += battery_now;
+				full += battery_full;
+				rate += flow;
+			} else if let Some(capacity) = number(&dir, "capacity") {
+				capacities.push(capacity);
+			}
+		}
+		if statuses.is_empty() {
+			let source = match mains {
+				Some(true) => "ac",
+				Some(false) => "battery",
+				None => "unknown",
+			};
+			return PowerInfo::no_battery(source);
+		}
+
+		let has = |status: &str| statuses.iter().any(|s| s == status);
+		let state = if has("Charging") {
+			"charging"
+		} else if has("Discharging") {
+			"discharging"
+		} else if has("Not charging") {
+			"not-charging"
+		} else if statuses.iter().all(|s| s == "Full") {
+			"full"
+		} else {
+			"unknown"
+		};
+		let source = match mains {
+			Some(true) => "ac",
+			Some(false) => "battery",
+			None if state == "discharging" => "battery",
+			None if state == "unknown" => "unknown",
+			None => "ac",
+		};
+		let percent = if full > 0.0 {
+			Some(now / full * 100.0)
+		} else if capacities.is_empty() {
+			None
+		} else {
+			Some(capacities.iter().sum::<f64>() / capacities.len() as f64)
+		};
+		let minutes = (rate > 0.0 && full > 0.0)
+			.then(|| match state {
+				"discharging" => Some(now / rate * 60.0),
+				"charging" => Some((full - now) / rate * 60.0),
+				_ => None,
+			})
+			.flatten();
+		PowerInfo::new(percent, state, source, minutes)
+	}
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+	use std::ffi::{CString, c_char, c_void};
+
+	use super::PowerInfo;
+
+	type CFTypeRef = *const c_void;
+
+	const UTF8: u32 = 0x0800_0100;
+	const SINT64: isize = 4;
+
+	#[link(name = "IOKit", kind = "framework")]
+	unsafe extern "C" {
+		fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+		fn IOPSCopyPowerSourcesList(blob: CFTypeRef) -> CFTypeRef;
+		fn IOPSGetPowerSourceDescription(blob: CFTypeRef, source: CFTypeRef) -> CFTypeRef;
+		fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFTypeRef;
+	}
+
+	#[link(name = "CoreFoundation", kind = "framework")]
+	unsafe extern "C" {
+		fn CFRelease(cf: CFTypeRef);
+		fn CFGetTypeID(cf: CFTypeRef) -> usize;
+		fn CFStringGetTypeID() -> usize;
+		fn CFNumberGetTypeID() -> usize;
+		fn CFBooleanGetTypeID() -> usize;
+		fn CFArrayGetCount(array: CFTypeRef) -> isize;
+		fn CFArrayGetValueAtIndex(array: CFTypeRef, index: isize) -> CFTypeRef;
+		fn CFDictionaryGetValue(dict: CFTypeRef, key: CFTypeRef) -> CFTypeRef;
+		fn CFStringCreateWithCString(
+			alloc: CFTypeRef,
+			string: *const c_char,
+			encoding: u32,
+		) -> CFTypeRef;
+		fn CFStringGetCString(
+			string: CFTypeRef,
+			buffer: *mut c_char,
+			size: isize,
+			encoding: u32,
+		) -> u8;
+		fn CFNumberGetValue(number: CFTypeRef, kind: isize, value: *mut c_void) -> u8;
+		fn CFBooleanGetValue(boolean: CFTypeRef) -> u8;
+	}
+
+	/// Contents of a CFString, if `value` is one.
+	fn string(value: CFTypeRef) -> Option<String> {
+		// SAFETY: `value` is a live CF object or null; the buffer outlives the
+		// call.
+		unsafe {
+			if value.is_null() || CFGetTypeID(value) != CFStringGetTypeID() {
+				return None;
+			}
+			let mut buffer = [0 as c_char; 128];
+			if CFStringGetCString(value, buffer.as_mut_ptr(), buffer.len() as isize, UTF8) == 0 {
+				return None;
+			}
+			Some(
+				std::ffi::CStr::from_ptr(buffer.as_ptr())
+					.to_string_lossy()
+					.into_owned(),
+			)
+		}
+	}
+
+	/// Value of `key` in the CFDictionary `dict`, not retained.
+	fn get(dict: CFTypeRef, key: &str) -> CFTypeRef {
+		let key = CString::new(key).unwrap_or_default();
+		// SAFETY: `dict` is a live dictionary; the key string is released after
+		// the lookup, and dictionary values are owned by the dictionary.
+		unsafe {
+			let key = CFStringCreateWithCString(std::ptr::null(), key.as_ptr(), UTF8);
+			if key.is_null() {
+				return std::ptr::null();
+			}
+			let value = CFDictionaryGetValue(dict, key);
+			CFRelease(key);
+			value
+		}
+	}
+
+	fn number(dict: CFTypeRef, key: &str) -> Option<f64> {
+		let value = get(dict, key);
+		let mut number: i64 = 0;
+		// SAFETY: `value` is checked to be a CFNumber before it is read.
+		let read = unsafe {
+			!value.is_null()
+				&& CFGetTypeID(value) == CFNumberGetTypeID()
+				&& CFNumberGetValue(value, SINT64, (&raw mut number).cast()) != 0
+		};
+		read.then_some(number as f64)
+	}
+
+	fn boolean(dict: CFTypeRef, key: &str) -> bool {
+		let value = get(dict, key);
+		// SAFETY: `value` is checked to be a CFBoolean before it is read.
+		unsafe {
+			!value.is_null()
+				&& CFGetTypeID(value) == CFBooleanGetTypeID()
+				&& CFBooleanGetValue(value) != 0
+		}
+	}
+
+	pub fn read() -> PowerInfo {
+		// SAFETY: the snapshot and list are released below; descriptions and the
+		// providing type are owned by the snapshot.
+		unsafe {
+			let blob = IOPSCopyPowerSourcesInfo();
+			if blob.is_null() {
+				return PowerInfo::no_battery("unknown");
+			}
+			let source = match string(IOPSGetProvidingPowerSourceType(blob)).as_deref() {
+				Some("AC Power") => "ac",
+				Some("Battery Power") => "battery",
+				_ => "unknown",
+			};
+			let list = IOPSCopyPowerSourcesList(blob);
+			let mut info = PowerInfo::no_battery(source);
+			let (mut now, mut full) = (0.0, 0.0);
+			let count = if list.is_null() {
+				0
+			} else {
+				CFArrayGetCount(list)
+			};
+			for index in 0..count {
+				let desc = IOPSGetPowerSourceDescription(blob, CFArrayGetValueAtIndex(list, index));
+				if desc.is_null()
+					|| string(get(desc, "Type")).as_deref() != Some("InternalBattery")
+					|| !boolean(desc, "Is Present")
+				{
+					continue;
+				}
+				now += number(desc, "Current Capacity").unwrap_or(0.0);
+				full += number(desc, "Max Capacity").unwrap_or(0.0);
+				let charging = boolean(desc, "Is Charging");
+				let state = if charging {
+					"charging"
+				} else if boolean(desc, "Is Charged") {
+					"full"
+				} else if source == "ac" {
+					"not-charging"
+				} else {
+					"discharging"
+				};
+				// Reported in minutes, -1 while still being estimated.
+				let minutes = match state {
+					"charging" => number(desc, "Time to Full Charge"),
+					"discharging" => number(desc, "Time to Empty"),
+					_ => None,
+				};
+				let percent = (full > 0.0).then(|| now / full * 100.0);
+				info = PowerInfo::new(percent, state, source, minutes);
+			}
+			if !list.is_null() {
+				CFRelease(list);
+			}
+			CFRelease(blob);
+			info
+		}
+	}
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+	use super::PowerInfo;
+
+	const AC_ONLINE: u8 = 1;
+	const AC_OFFLINE: u8 = 0;
+	const BATTERY_CHARGING: u8 = 8;
+	const NO_BATTERY: u8 = 128;
+	const UNKNOWN: u8 = 255;
+
+	#[repr(C)]
+	#[derive(Default)]
+	#[allow(non_snake_case)]
+	struct SYSTEM_POWER_STATUS {
+		ACLineStatus:        u8,
+		BatteryFlag:         u8,
+		BatteryLifePercent:  u8,
+		SystemStatusFlag:    u8,
+		BatteryLifeTime:     u32,
+		BatteryFullLifeTime: u32,
+	}
+
+	#[link(name = "kernel32")]
+	unsafe extern "system" {
+		fn GetSystemPowerStatus(lpSystemPowerStatus: *mut SYSTEM_POWER_STATUS) -> i32;
+	}
+
+	pub fn read() -> PowerInfo {
+		let mut status = SYSTEM_POWER_STATUS::default();
+		// SAFETY: `status` is a valid, writable SYSTEM_POWER_STATUS.
+		if unsafe { GetSystemPowerStatus(&raw mut status) } == 0 {
+			return PowerInfo::no_battery("unknown");
+		}
+		let source = match status.ACLineStatus {
+			AC_ONLINE => "ac",
+			AC_OFFLINE => "battery",
+			_ => "unknown",
+		};
+		if status.BatteryFlag == NO_BATTERY || status.BatteryFlag == UNKNOWN {
+			return PowerInfo::no_battery(source);
+		}
+		let percent =
+			(status.BatteryLifePercent != UNKNOWN).then_some(f64::from(status.BatteryLifePercent));
+		let state = if status.BatteryFlag & BATTERY_CHARGING != 0 {
+			"charging"
+		} else if source == "battery" {
+			"discharging"
+		} else if percent == Some(100.0) {
+			"full"
+		} else if source == "ac" {
+			"not-charging"
+		} else {
+			"unknown"
+		};
+		// Seconds, or u32::MAX when unknown; only estimated while discharging.
+		let minutes = (state == "discharging" && status.BatteryLifeTime != u32::MAX)
+			.then(|| f64::from(status.BatteryLifeTime) / 60.0);
+		PowerInfo::new(percent, state, source, minutes)
+	}
+}
+
+/// Read the battery charge, charging state and power source.
+#[napi(js_name = "getPowerInfo")]
+pub fn get_power_info() -> PowerInfo {
+	read_power()
+}
+
+/// Poll the power state and call `callback` when the power source or
+/// charging state changes, or the charge drops to `lowPercent` on battery.
+/// Returns an id for `unsubscribePowerEvents`.
+///
+/// # Errors
+/// Returns an error if the polling thread cannot be spawned.
+#[napi(js_name = "subscribePowerEvents")]
+pub fn subscribe_power_events(
+	options: Option<PowerEventOptions>,
+	#[napi(ts_arg_type = "(error: Error | null, event: PowerEvent) => void")]
+	callback: ThreadsafeFunction<PowerEvent>,
+) -> Result<u32> {
+	let options = options.unwrap_or_default();
+	let interval = Duration::from_millis(
+		options
+			.interval_ms
+			.unwrap_or(DEFAULT_INTERVAL_MS)
+			.max(1)
+			.into(),
+	);
+	let low_percent = options.low_percent.unwrap_or(DEFAULT_LOW_PERCENT);
+	let subscription = Arc::new(Subscription::default());
+	let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::Relaxed);
+	SUBSCRIPTIONS.lock().insert(id, subscription.clone());
+
+	thread::Builder::new()
+		.name("pi-power".to_string())
+		.spawn(move || {
+			let mut last = read_power();
+			let mut low = last.is_low(low_percent);
+			let emit = |reason: &str, power: &PowerInfo| {
+				callback.call(
+					Ok(PowerEvent { reason: reason.to_string(), power: power.clone() }),
+					ThreadsafeFunctionCallMode::NonBlocking,
+				);
+			};
+			loop {
+				{
+					let mut stopped = subscription.stopped.lock();
+					if !*stopped {
+						subscription.wake.wait_for(&mut stopped, interval);
+					}
+					if *stopped {
+						break;
+					}
+				}
+				let power = read_power();
+				if power.source != last.source {
+					emit("source", &power);
+				} else if power.state != last.state {
+					emit("state", &power);
+				}
+				let is_low = power.is_low(low_percent);
+				if is_low && !low {
+					emit("low", &power);
+				}
+				low = is_low;
+				last = power;
+			}
+		})
+		.map_err(|err| {
+			SUBSCRIPTIONS.lock().remove(&id);
+			Error::from_reason(format!("Failed to spawn power thread: {err}"))
+		})?;
+	Ok(id)
+}
+
+/// Stop a subscription started by `subscribePowerEvents`.
+/// Returns false if `id` is not an active subscription.
+#[napi(js_name = "unsubscribePowerEvents")]
+pub fn unsubscribe_power_events(id: u32) -> bool {
+	let Some(subscription) = SUBSCRIPTIONS.lock().remove(&id) else {
+		return false;
+	};
+	*subscription.stopped.lock() = true;
+	subscription.wake.notify_all();
+	true
+}
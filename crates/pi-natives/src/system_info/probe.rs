@@ -0,0 +1,273 @@
+//! DNS resolution and TCP latency probes.
+//!
+//! # Overview
+//! `resolveHost` and `probeLatency` tell apart the usual reasons a package
+//! install hangs: a name that does not resolve or resolves slowly (bad DNS),
+//! a public name resolving to a private address (captive portal or DNS
+//! filtering), and a host that resolves but does not accept connections
+//! (blocked registry). Every step has a timeout, reported as a result rather
+//! than an error; only an abort signal rejects.
+//!
+//! Names resolve through the system resolver (`getaddrinfo`), as the tools
+//! being diagnosed would.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const dns = await native.resolveHost("registry.npmjs.org");
+//! // JS: const { successes, avgMs } = await native.probeLatency("registry.npmjs.org", { attempts: 3 });
+//! ```
+
+use std::{
+	future::Future,
+	net::{IpAddr, SocketAddr},
+	time::{Duration, Instant},
+};
+
+use napi::{
+	Env, Error, Result,
+	bindgen_prelude::{PromiseRaw, Unknown},
+	tokio::{self, net::TcpStream, time},
+};
+use napi_derive::napi;
+
+use crate::task;
+
+const DEFAULT_RESOLVE_TIMEOUT_MS: u32 = 5_000;
+const DEFAULT_CONNECT_TIMEOUT_MS: u32 = 3_000;
+const DEFAULT_PORT: u16 = 443;
+const DEFAULT_ATTEMPTS: u32 = 3;
+const MAX_ATTEMPTS: u32 = 20;
+
+/// Options for `resolveHost`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ResolveHostOptions<'env> {
+	/// Timeout in milliseconds (default: 5000).
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+	/// Abort signal for cancelling the lookup.
+	pub signal:     Option<Unknown<'env>>,
+}
+
+/// Outcome of `resolveHost`.
+#[napi(object)]
+pub struct ResolvedHost {
+	/// Host name as given.
+	pub host:        String,
+	/// Resolved addresses, in resolver order.
+	pub addresses:   Vec<String>,
+	/// Time the lookup took, or until it timed out, in milliseconds.
+	#[napi(js_name = "durationMs")]
+	pub duration_ms: f64,
+	/// Whether every address is private, loopback or link-local, which for a
+	/// public name suggests a captive portal or DNS filtering.
+	pub private:     bool,
+	/// Why no addresses were found (e.g. "Timed out after 5000ms").
+	pub error:       Option<String>,
+}
+
+/// Options for `probeLatency`.
+#[napi(object)]
+#[derive(Default)]
+pub struct LatencyProbeOptions<'env> {
+	/// TCP port to connect to (default: 443).
+	pub port:       Option<u32>,
+	/// Connection attempts, made one after another (default: 3, at most 20).
+	pub attempts:   Option<u32>,
+	/// Timeout per step (lookup or connection) in milliseconds (default:
+	/// 3000).
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+	/// Abort signal for cancelling the probe.
+	pub signal:     Option<Unknown<'env>>,
+}
+
+/// One connection attempt of `probeLatency`.
+#[napi(object)]
+pub struct LatencyAttempt {
+	/// Time to connect in milliseconds, when the connection succeeded.
+	#[napi(js_name = "durationMs")]
+	pub duration_ms: Option<f64>,
+	/// Why the connection failed (e.g. "Connection refused", "Timed out
+	/// after 3000ms").
+	pub error:       Option<String>,
+}
+
+/// Outcome of `probeLatency`.
+#[napi(object)]
+pub struct LatencyProbe {
+	/// Host name as given.
+	pub host:      String,
+	/// Port connected to.
+	pub port:      u32,
+	/// Name resolution, done once before connecting.
+	pub dns:       ResolvedHost,
+	/// Address connected to: the first one resolved.
+	pub address:   Option<String>,
+	/// Connection attempts, in order; empty when the name did not resolve.
+	pub attempts:  Vec<LatencyAttempt>,
+	/// Number of attempts that connected.
+	pub successes: u32,
+	/// Fastest connection in milliseconds.
+	#[napi(js_name = "minMs")]
+	pub min_ms:    Option<f64>,
+	/// Average connection time in milliseconds.
+	#[napi(js_name = "avgMs")]
+	pub avg_ms:    Option<f64>,
+	/// Slowest connection in milliseconds.
+	#[napi(js_name = "maxMs")]
+	pub max_ms:    Option<f64>,
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+	start.elapsed().as_secs_f64() * 1000.0
+}
+
+const fn is_private(ip: IpAddr) -> bool {
+	match ip {
+		IpAddr::V4(ip) => {
+			ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified()
+		},
+		IpAddr::V6(ip) => {
+			ip.is_loopback()
+				|| ip.is_unspecified()
+				|| ip.is_unique_local()
+				|| ip.is_unicast_link_local()
+		},
+	}
+}
+
+/// Run `work`, rejecting if `ct` is aborted first.
+async fn cancellable<T>(ct: task::CancelToken, work: impl Future<Output = T>) -> Result<T> {
+	tokio::select! {
+		result = work => Ok(result),
+		reason = ct.wait() => Err(Error::from_reason(format!("Aborted: {reason:?}"))),
+	}
+}
+
+async fn resolve(host: String, timeout: Duration) -> (ResolvedHost, Vec<SocketAddr>) {
+	let start = Instant::now();
+	let result = time::timeout(timeout, tokio::net::lookup_host((host.clone(), 0))).await;
+	let duration_ms = elapsed_ms(start);
+	let (addrs, error) = match result {
+		Ok(Ok(addrs)) => {
+			let mut unique: Vec<SocketAddr> = Vec::new();
+			for addr in addrs {
+				if !unique.iter().any(|seen| seen.ip() == addr.ip()) {
+					unique.push(addr);
+				}
+			}
+			let error = unique
+				.is_empty()
+				.then(|| String::from("No addresses found"));
+			(unique, error)
+		},
+		Ok(Err(err)) => (Vec::new(), Some(err.to_string())),
+		Err(_) => (Vec::new(), Some(format!("Timed out after {}ms", timeout.as_millis()))),
+	};
+	let resolved = ResolvedHost {
+		host,
+		addresses: addrs.iter().map(|addr| addr.ip().to_string()).collect(),
+		duration_ms,
+		private: !addrs.is_empty() && addrs.iter().all(|addr| is_private(addr.ip())),
+		error,
+	};
+	(resolved, addrs)
+}
+
+async fn connect(addr: SocketAddr, timeout: Duration) -> LatencyAttempt {
+	let start = Instant::now();
+	match time::timeout(timeout, TcpStream::connect(addr)).await {
+		Ok(Ok(_)) => LatencyAttempt { duration_ms: Some(elapsed_ms(start)), error: None },
+		Ok(Err(err)) => LatencyAttempt { duration_ms: None, error: Some(err.to_string()) },
+		Err(_) => LatencyAttempt {
+			duration_ms: None,
+			error:       Some(format!("Timed out after {}ms", timeout.as_millis())),
+		},
+	}
+}
+
+async fn probe(host: String, port: u16, attempts: u32, timeout: Duration) -> LatencyProbe {
+	let (dns, addrs) = resolve(host.clone(), timeout).await;
+	let address = addrs.first().map(|addr| SocketAddr::new(addr.ip(), port));
+	let mut results = Vec::new();
+	if let Some(address) = address {
+		for _ in 0..attempts {
+			results.push(connect(address, timeout).await);
+		}
+	}
+	let times: Vec<f64> = results
+		.iter()
+		.filter_map(|attempt| attempt.duration_ms)
+		.collect();
+	LatencyProbe {
+		host,
+		port: u32::from(port),
+		dns,
+		address: address.map(|addr| addr.ip().to_string()),
+		attempts: results,
+		successes: times.len() as u32,
+		min_ms: times.iter().copied().reduce(f64::min),
+		avg_ms: (!times.is_empty()).then(|| times.iter().sum::<f64>() / times.len() as f64),
+		max_ms: times.iter().copied().reduce(f64::max),
+	}
+}
+
+/// Resolve a host name with the system resolver, timing the lookup.
+///
+/// # Errors
+/// Rejects only when aborted; lookup failures and timeouts are reported in
+/// `error`.
+#[napi(js_name = "resolveHost")]
+pub fn resolve_host<'env>(
+	env: &'env Env,
+	host: String,
+	options: Option<ResolveHostOptions<'env>>,
+) -> Result<PromiseRaw<'env, ResolvedHost>> {
+	let options = options.unwrap_or_default();
+	let timeout = Duration::from_millis(
+		options
+			.timeout_ms
+			.unwrap_or(DEFAULT_RESOLVE_TIMEOUT_MS)
+			.into(),
+	);
+	let ct = task::CancelToken::new(None, options.signal);
+	task::future(env, "system_info.resolve_host", async move {
+		cancellable(ct, async move { resolve(host, timeout).await.0 }).await
+	})
+}
+
+/// Resolve a host, then time TCP connections to it.
+///
+/// # Errors
+/// Rejects when `port` is not 1-65535 or when aborted; lookup and connection
+/// failures are reported in the result.
+#[napi(js_name = "probeLatency")]
+pub fn probe_latency<'env>(
+	env: &'env Env,
+	host: String,
+	options: Option<LatencyProbeOptions<'env>>,
+) -> Result<PromiseRaw<'env, LatencyProbe>> {
+	let options = options.unwrap_or_default();
+	let port = match options.port {
+		None => DEFAULT_PORT,
+		Some(port) => u16::try_from(port)
+			.ok()
+			.filter(|port| *port != 0)
+			.ok_or_else(|| Error::from_reason(format!("Invalid port: {port}")))?,
+	};
+	let attempts = options
+		.attempts
+		.unwrap_or(DEFAULT_ATTEMPTS)
+		.clamp(1, MAX_ATTEMPTS);
+	let timeout = Duration::from_millis(
+		options
+			.timeout_ms
+			.unwrap_or(DEFAULT_CONNECT_TIMEOUT_MS)
+			.into(),
+	);
+	let ct = task::CancelToken::new(None, options.signal);
+	task::future(env, "system_info.probe_latency", async move {
+		cancellable(ct, probe(host, port, attempts, timeout)).await
+	})
+}
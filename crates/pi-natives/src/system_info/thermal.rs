@@ -0,0 +1,306 @@
+//! CPU temperature, thermal pressure and frequency scaling.
+//!
+//! # Overview
+//! `getThermalInfo` reports what the OS exposes about the CPU being held
+//! back: temperature sensors, the current clock against its maximum and any
+//! lower limit imposed on it, and the thermal pressure level. `throttled`
+//! sums these up, so a slow build can be explained and its parallelism
+//! reduced. Every field but `sensors` and `throttled` is absent where the OS
+//! does not provide it.
+//!
+//! # Platform Implementation
+//! - **Linux**: sensors from hwmon; clocks and limits from `cpufreq`, whose
+//!   `scaling_max_freq` drops under thermal or power capping; throttle event
+//!   counts from `thermal_throttle` (Intel)
+//! - **macOS**: sensors from SMC; pressure from `NSProcessInfo.thermalState`
+//! - **Windows**: clocks and limits from `CallNtPowerInformation`
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { throttled, currentMhz, maxMhz } = native.getThermalInfo();
+//! ```
+
+use napi_derive::napi;
+use sysinfo::{Components, CpuRefreshKind, RefreshKind, System};
+
+/// Share of the maximum clock below which a frequency limit counts as
+/// throttling.
+const LIMITED: f64 = 0.9;
+
+/// Degrees below a sensor's critical temperature that count as throttling.
+const CRITICAL_MARGIN: f64 = 5.0;
+
+/// Sensor labels that belong to the CPU.
+const CPU_SENSORS: [&str; 8] =
+	["cpu", "core", "package", "tctl", "tdie", "k10temp", "coretemp", "soc"];
+
+/// A temperature sensor.
+#[napi(object)]
+pub struct ThermalSensor {
+	/// Sensor name, as the OS reports it (e.g. "coretemp Package id 0").
+	pub label:    String,
+	/// Temperature in degrees Celsius.
+	pub celsius:  f64,
+	/// Temperature at which the hardware shuts down or throttles hard.
+	pub critical: Option<f64>,
+}
+
+/// CPU temperature and frequency scaling.
+#[napi(object)]
+pub struct ThermalInfo {
+	/// Hottest CPU sensor, in degrees Celsius.
+	#[napi(js_name = "cpuCelsius")]
+	pub cpu_celsius:     Option<f64>,
+	/// All temperature sensors.
+	pub sensors:         Vec<ThermalSensor>,
+	/// Average current clock across cores, in MHz.
+	#[napi(js_name = "currentMhz")]
+	pub current_mhz:     Option<f64>,
+	/// Highest clock the CPU supports, in MHz.
+	#[napi(js_name = "maxMhz")]
+	pub max_mhz:         Option<f64>,
+	/// Clock the CPU is currently capped at by thermal or power management,
+	/// in MHz.
+	#[napi(js_name = "limitMhz")]
+	pub limit_mhz:       Option<f64>,
+	/// Thermal pressure: "nominal", "fair", "serious" or "critical" (macOS).
+	#[napi(ts_type = "\"nominal\" | \"fair\" | \"serious\" | \"critical\"")]
+	pub pressure:        Option<String>,
+	/// Thermal throttle events since boot, summed over cores (Linux, Intel);
+	/// compare two readings to see whether throttling is ongoing.
+	#[napi(js_name = "throttleEvents")]
+	pub throttle_events: Option<f64>,
+	/// Whether the CPU appears to be held back: serious or critical pressure,
+	/// a clock limit well below the maximum, or a CPU sensor near its
+	/// critical temperature.
+	pub throttled:       bool,
+}
+
+/// Platform readings of clocks, limits and pressure.
+#[derive(Default)]
+struct Scaling {
+	current_mhz:     Option<f64>,
+	max_mhz:         Option<f64>,
+	limit_mhz:       Option<f64>,
+	pressure:        Option<&'static str>,
+	throttle_events: Option<f64>,
+}
+
+fn is_cpu_sensor(label: &str) -> bool {
+	let label = label.to_ascii_lowercase();
+	CPU_SENSORS.iter().any(|name| label.contains(name))
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+	use std::{fs, path::Path};
+
+	use super::Scaling;
+
+	fn number(path: &Path) -> Option<f64> {
+		fs::read_to_string(path).ok()?.trim().parse().ok()
+	}
+
+	pub fn scaling() -> Scaling {
+		let mut current = Vec::new();
+		let mut scaling = Scaling::default();
+		let mut events = None;
+		let cpus = fs::read_dir("/sys/devices/system/cpu")
+			.into_iter()
+			.flatten()
+			.flatten();
+		for cpu in cpus {
+			let name = cpu.file_name();
+			let Some(index) = name.to_str().and_then(|name| name.strip_prefix("cpu")) else {
+				continue;
+			};
+			if index.is_empty() || !index.bytes().all(|byte| byte.is_ascii_digit()) {
+				continue;
+			}
+			let dir = cpu.path();
+			// Frequencies are in kHz.
+			let freq = dir.join("cpufreq");
+			if let Some(khz) = number(&freq.join("scaling_cur_freq")) {
+				current.push(khz / 1000.0);
+			}
+			if let Some(khz) = number(&freq.join("cpuinfo_max_freq")) {
+				scaling.max_mhz = Some(
+					scaling
+						.max_mhz
+						.map_or(khz / 1000.0, |max| max.max(khz / 1000.0)),
+				);
+			}
+			if let Some(khz) = number(&freq.join("scaling_max_freq")) {
+				scaling.limit_mhz = Some(
+					scaling
+						.limit_mhz
+						.map_or(khz / 1000.0, |limit| limit.min(khz / 1000.0)),
+				);
+			}
+			if let Some(count) = number(&dir.join("thermal_throttle/core_throttle_count")) {
+				events = Some(events.unwrap_or(0.0) + count);
+			}
+		}
+		if !current.is_empty() {
+			scaling.current_mhz = Some(current.iter().sum::<f64>() / current.len() as f64);
+		}
+		scaling.throttle_events = events;
+		scaling
+	}
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+	use objc2::{class, msg_send, runtime::AnyObject};
+
+	use super::Scaling;
+
+	#[link(name = "Foundation", kind = "framework")]
+	unsafe extern "C" {}
+
+	pub fn scaling() -> Scaling {
+		// SAFETY: `processInfo` returns the shared NSProcessInfo, and
+		// `thermalState` returns an NSInteger.
+		let state: isize = unsafe {
+			let info: *mut AnyObject = msg_send![class!(NSProcessInfo), processInfo];
+			msg_send![info, thermalState]
+		};
+		let pressure = match state {
+			0 => "nominal",
+			1 => "fair",
+			2 => "serious",
+			_ => "critical",
+		};
+		Scaling { pressure: Some(pressure), ..Scaling::default() }
+	}
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+	use std::{ffi::c_void, mem};
+
+	use super::Scaling;
+
+	const PROCESSOR_INFORMATION: i32 = 11;
+
+	#[repr(C)]
+	#[derive(Clone, Copy, Default)]
+	#[allow(non_snake_case, dead_code, reason = "mirrors the Win32 struct layout")]
+	struct PROCESSOR_POWER_INFORMATION {
+		Number:           u32,
+		MaxMhz:           u32,
+		CurrentMhz:       u32,
+		MhzLimit:         u32,
+		MaxIdleState:     u32,
+		CurrentIdleState: u32,
+	}
+
+	#[link(name = "powrprof")]
+	unsafe extern "system" {
+		fn CallNtPowerInformation(
+			InformationLevel: i32,
+			InputBuffer: *const c_void,
+			InputBufferLength: u32,
+			OutputBuffer: *mut c_void,
+			OutputBufferLength: u32,
+		) -> i32;
+	}
+
+	pub fn scaling() -> Scaling {
+		let count = std::thread::available_parallelism().map_or(1, usize::from);
+		let mut info = vec![PROCESSOR_POWER_INFORMATION::default(); count];
+		let len = (count * mem::size_of::<PROCESSOR_POWER_INFORMATION>()) as u32;
+		// SAFETY: `info` holds `len` writable bytes.
+		let status = unsafe {
+			CallNtPowerInformation(
+				PROCESSOR_INFORMATION,
+				std::ptr::null(),
+				0,
+				info.as_mut_ptr().cast(),
+				len,
+			)
+		};
+		if status != 0 {
+			return Scaling::default();
+		}
+		let current = info
+			.iter()
+			.map(|cpu| f64::from(cpu.CurrentMhz))
+			.sum::<f64>();
+		Scaling {
+			current_mhz: Some(current / count as f64),
+			max_mhz: info
+				.iter()
+				.map(|cpu| f64::from(cpu.MaxMhz))
+				.reduce(f64::max),
+			limit_mhz: info
+				.iter()
+				.map(|cpu| f64::from(cpu.MhzLimit))
+				.reduce(f64::min),
+			..Scaling::default()
+		}
+	}
+}
+
+/// Read CPU temperatures, clocks and thermal pressure.
+#[napi(js_name = "getThermalInfo")]
+pub fn get_thermal_info() -> ThermalInfo {
+	let components = Components::new_with_refreshed_list();
+	let sensors: Vec<ThermalSensor> = components
+		.list()
+		.iter()
+		.filter_map(|component| {
+			Some(ThermalSensor {
+				label:    component.label().to_string(),
+				celsius:  f64::from(
+					component
+						.temperature()
+						.filter(|celsius| celsius.is_finite())?,
+				),
+				critical: component
+					.critical()
+					.filter(|celsius| celsius.is_finite() && *celsius > 0.0)
+					.map(f64::from),
+			})
+		})
+		.collect();
+	let cpu_sensors = || sensors.iter().filter(|sensor| is_cpu_sensor(&sensor.label));
+	let cpu_celsius = cpu_sensors().map(|sensor| sensor.celsius).reduce(f64::max);
+
+	let mut scaling = platform::scaling();
+	if scaling.current_mhz.is_none() {
+		let system = System::new_with_specifics(
+			RefreshKind::nothing().with_cpu(CpuRefreshKind::nothing().with_frequency()),
+		);
+		let clocks: Vec<f64> = system
+			.cpus()
+			.iter()
+			.map(|cpu| cpu.frequency() as f64)
+			.filter(|&mhz| mhz > 0.0)
+			.collect();
+		if !clocks.is_empty() {
+			scaling.current_mhz = Some(clocks.iter().sum::<f64>() / clocks.len() as f64);
+		}
+	}
+
+	let throttled = matches!(scaling.pressure, Some("serious" | "critical"))
+		|| scaling
+			.limit_mhz
+			.zip(scaling.max_mhz)
+			.is_some_and(|(limit, max)| limit > 0.0 && limit < max * LIMITED)
+		|| cpu_sensors().any(|sensor| {
+			sensor
+				.critical
+				.is_some_and(|critical| sensor.celsius >= critical - CRITICAL_MARGIN)
+		});
+	ThermalInfo {
+		cpu_celsius,
+		sensors,
+		current_mhz: scaling.current_mhz,
+		max_mhz: scaling.max_mhz,
+		limit_mhz: scaling.limit_mhz,
+		pressure: scaling.pressure.map(str::to_string),
+		throttle_events: scaling.throttle_events,
+		throttled,
+	}
+}
@@ -0,0 +1,222 @@
+//! Detection of the current user and their privileges.
+//!
+//! # Platform Implementation
+//! - **Unix**: the effective user and groups from the user and group databases;
+//!   elevated when running as root; sudo likely when a member of `sudo`,
+//!   `wheel` or `admin` and `sudo` is on `PATH`
+//! - **Windows**: the process token's elevation; sudo likely when elevated or
+//!   when the user is an administrator running with a filtered (UAC) token
+
+use std::env;
+
+use napi_derive::napi;
+
+/// The user the agent runs as.
+#[napi(object)]
+pub struct UserInfo {
+	/// Login name.
+	pub username: Option<String>,
+	/// Effective user id (not available on Windows).
+	pub uid:      Option<u32>,
+	/// Groups the user belongs to (not available on Windows).
+	pub groups:   Vec<String>,
+	/// Whether the process runs as root or, on Windows, elevated.
+	pub elevated: bool,
+	/// Whether the user can likely gain elevated privileges: already elevated,
+	/// an administrator group member with `sudo` available, or a Windows
+	/// administrator who can accept a UAC prompt.
+	pub sudo:     bool,
+}
+
+/// Detect the current user.
+pub fn detect_user() -> UserInfo {
+	platform::detect_user()
+}
+
+/// Whether an executable named `name` is on `PATH`.
+#[cfg_attr(windows, allow(dead_code, reason = "Windows elevation has no sudo binary to find"))]
+fn on_path(name: &str) -> bool {
+	env::var_os("PATH")
+		.is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(name).is_file()))
+}
+
+#[cfg(unix)]
+mod platform {
+	use std::{env, ffi::CStr, mem::MaybeUninit, ptr};
+
+	use super::{UserInfo, on_path};
+
+	/// Groups whose members may use sudo by default.
+	const ADMIN_GROUPS: [&str; 3] = ["sudo", "wheel", "admin"];
+
+	/// Run a reentrant lookup with a buffer grown until it fits.
+	fn lookup<T>(mut call: impl FnMut(&mut [u8]) -> Option<Result<T, i32>>) -> Option<T> {
+		let mut buf = vec![0u8; 1024];
+		loop {
+			match call(&mut buf)? {
+				Ok(value) => return Some(value),
+				Err(libc::ERANGE) if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+				Err(_) => return None,
+			}
+		}
+	}
+
+	fn username(uid: libc::uid_t) -> Option<String> {
+		lookup(|buf| {
+			let mut pwd = MaybeUninit::<libc::passwd>::uninit();
+			let mut result = ptr::null_mut();
+			// SAFETY: all pointers are valid for the call; on success `result`
+			// points to `pwd`, whose strings live in `buf`.
+			let code = unsafe {
+				libc::getpwuid_r(
+					uid,
+					pwd.as_mut_ptr(),
+					buf.as_mut_ptr().cast(),
+					buf.len(),
+					&raw mut result,
+				)
+			};
+			if code != 0 {
+				return Some(Err(code));
+			}
+			if result.is_null() {
+				return None;
+			}
+			// SAFETY: the entry was found, so `pw_name` is a valid C string.
+			let name = unsafe { CStr::from_ptr((*result).pw_name) };
+			Some(Ok(name.to_string_lossy().into_owned()))
+		})
+	}
+
+	fn group_name(gid: libc::gid_t) -> Option<String> {
+		lookup(|buf| {
+			let mut grp = MaybeUninit::<libc::group>::uninit();
+			let mut result = ptr::null_mut();
+			// SAFETY: as in `username`.
+			let code = unsafe {
+				libc::getgrgid_r(
+					gid,
+					grp.as_mut_ptr(),
+					buf.as_mut_ptr().cast(),
+					buf.len(),
+					&raw mut result,
+				)
+			};
+			if code != 0 {
+				return Some(Err(code));
+			}
+			if result.is_null() {
+				return None;
+			}
+			// SAFETY: the entry was found, so `gr_name` is a valid C string.
+			let name = unsafe { CStr::from_ptr((*result).gr_name) };
+			Some(Ok(name.to_string_lossy().into_owned()))
+		})
+	}
+
+	fn group_ids() -> Vec<libc::gid_t> {
+		// SAFETY: a zero size only asks for the count.
+		let count = unsafe { libc::getgroups(0, ptr::null_mut()) };
+		let mut gids = vec![0; usize::try_from(count).unwrap_or(0)];
+		// SAFETY: `gids` holds `count` entries.
+		let count = unsafe { libc::getgroups(count, gids.as_mut_ptr()) };
+		gids.truncate(usize::try_from(count).unwrap_or(0));
+		// SAFETY: getegid has no failure modes.
+		let primary = unsafe { libc::getegid() };
+		if !gids.contains(&primary) {
+			gids.insert(0, primary);
+		}
+		gids
+	}
+
+	pub fn detect_user() -> UserInfo {
+		// SAFETY: geteuid has no failure modes.
+		let uid = unsafe { libc::geteuid() };
+		let groups: Vec<String> = group_ids().into_iter().filter_map(group_name).collect();
+		let elevated = uid == 0;
+		let sudo = elevated
+			|| (groups
+				.iter()
+				.any(|group| ADMIN_GROUPS.contains(&group.as_str()))
+				&& on_path("sudo"));
+		UserInfo {
+			username: username(uid).or_else(|| env::var("USER").ok()),
+			uid: Some(uid),
+			groups,
+			elevated,
+			sudo,
+		}
+	}
+}
+
+#[cfg(windows)]
+mod platform {
+	use std::{env, ffi::c_void, mem, ptr};
+
+	use super::UserInfo;
+
+	const TOKEN_QUERY: u32 = 0x0008;
+	const TOKEN_ELEVATION_TYPE: i32 = 18;
+	const TOKEN_ELEVATION: i32 = 20;
+	/// Administrator running with a filtered token, able to elevate.
+	const TOKEN_ELEVATION_TYPE_LIMITED: u32 = 3;
+
+	#[link(name = "kernel32")]
+	unsafe extern "system" {
+		fn GetCurrentProcess() -> *mut c_void;
+		fn CloseHandle(handle: *mut c_void) -> i32;
+	}
+
+	#[link(name = "advapi32")]
+	unsafe extern "system" {
+		fn OpenProcessToken(process: *mut c_void, access: u32, token: *mut *mut c_void) -> i32;
+		fn GetTokenInformation(
+			token: *mut c_void,
+			class: i32,
+			info: *mut c_void,
+			len: u32,
+			ret_len: *mut u32,
+		) -> i32;
+	}
+
+	/// A 32-bit token information value.
+	fn token_value(token: *mut c_void, class: i32) -> Option<u32> {
+		let mut value = 0u32;
+		let mut len = 0;
+		// SAFETY: `value` holds the 4 bytes both queried classes return.
+		let ok = unsafe {
+			GetTokenInformation(
+				token,
+				class,
+				ptr::from_mut(&mut value).cast(),
+				mem::size_of::<u32>() as u32,
+				&raw mut len,
+			)
+		};
+		(ok != 0).then_some(value)
+	}
+
+	pub fn detect_user() -> UserInfo {
+		let mut token = ptr::null_mut();
+		// SAFETY: the pseudo handle needs no closing; `token` is closed below.
+		let opened =
+			unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &raw mut token) } != 0;
+		let (elevated, limited) = if opened {
+			let elevated = token_value(token, TOKEN_ELEVATION).is_some_and(|value| value != 0);
+			let limited =
+				token_value(token, TOKEN_ELEVATION_TYPE) == Some(TOKEN_ELEVATION_TYPE_LIMITED);
+			// SAFETY: `token` was opened above.
+			unsafe { CloseHandle(token) };
+			(elevated, limited)
+		} else {
+			(false, false)
+		};
+		UserInfo {
+			username: env::var("USERNAME").ok(),
+			uid: None,
+			groups: Vec::new(),
+			elevated,
+			sudo: elevated || limited,
+		}
+	}
+}
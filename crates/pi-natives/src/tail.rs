@@ -0,0 +1,339 @@
+//! Reading and following the end of log files.
+//!
+//! # Overview
+//! Each `tailFile` call owns a thread that emits the file's last lines and,
+//! with `follow`, polls the file for appended data until `stopTail`. Complete
+//! lines are delivered to the callback in batches; a trailing partial line is
+//! held back until its newline arrives.
+//!
+//! # Rotation
+//! - **Replaced file**: the path now resolves to a different file (another
+//!   inode on unix, file index on Windows). The rest of the old file is read,
+//!   then the new file is followed from its start.
+//! - **Truncated in place** (`copytruncate`): the file shrank below the read
+//!   position and is read again from its start.
+//! - **Missing file**: the old file keeps being read until the path reappears.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const id = native.tailFile("build.log", { follow: true }, (err, event) => {});
+//! // JS: native.stopTail(id);
+//! ```
+
+use std::{
+	collections::HashMap,
+	fs::File,
+	io::{self, Read, Seek, SeekFrom},
+	mem,
+	path::{Path, PathBuf},
+	sync::{
+		Arc, LazyLock,
+		atomic::{AtomicBool, AtomicU32, Ordering},
+	},
+	thread,
+	time::Duration,
+};
+
+use napi::{
+	bindgen_prelude::*,
+	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+};
+use napi_derive::napi;
+use parking_lot::Mutex;
+use same_file::Handle;
+
+use crate::workspace;
+
+const DEFAULT_MAX_LINES: u32 = 10;
+const DEFAULT_POLL_MS: u32 = 250;
+
+/// Bytes read per call.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Lines delivered per event at most, so reading a large file from its start
+/// does not build one huge batch.
+const BATCH_LINES: usize = 1000;
+
+static TAILS: LazyLock<Mutex<HashMap<u32, Arc<AtomicBool>>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_TAIL_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Options for `tailFile`.
+#[napi(object)]
+pub struct TailOptions {
+	/// Start at the end of the file, emitting only its last `maxLines` lines
+	/// (default: true). When false the whole file is emitted from its start.
+	#[napi(js_name = "fromEnd")]
+	pub from_end:                Option<bool>,
+	/// Keep emitting lines appended to the file, across rotation, until
+	/// `stopTail` is called (default: false).
+	pub follow:                  Option<bool>,
+	/// Existing lines to emit with `fromEnd` (default: 10).
+	#[napi(js_name = "maxLines")]
+	pub max_lines:               Option<u32>,
+	/// Interval between checks for new data and rotation while following, in
+	/// milliseconds (default: 250).
+	#[napi(js_name = "pollMs")]
+	pub poll_ms:                 Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// A batch of lines read from a tailed file.
+#[napi(object)]
+#[derive(Default)]
+pub struct TailEvent {
+	/// Complete lines, decoded as UTF-8 (lossily) without line terminators.
+	pub lines:     Vec<String>,
+	/// Whether the path now refers to a new file and `lines` start at its
+	/// beginning.
+	pub rotated:   bool,
+	/// Whether the file was truncated in place and `lines` start at its
+	/// beginning.
+	pub truncated: bool,
+}
+
+/// Delivers lines to the callback, carrying rotation flags into the next
+/// event.
+struct Emitter<'a> {
+	callback:  &'a ThreadsafeFunction<TailEvent>,
+	rotated:   bool,
+	truncated: bool,
+	sent:      bool,
+}
+
+impl Emitter<'_> {
+	fn emit(&mut self, lines: Vec<String>) {
+		if lines.is_empty() && !self.rotated && !self.truncated {
+			return;
+		}
+		let event = TailEvent {
+			lines,
+			rotated: mem::take(&mut self.rotated),
+			truncated: mem::take(&mut self.truncated),
+		};
+		self.sent = true;
+		self
+			.callback
+			.call(Ok(event), ThreadsafeFunctionCallMode::NonBlocking);
+	}
+}
+
+/// An open file being read line by line.
+struct Follower {
+	handle:  Handle,
+	pos:     u64,
+	/// Bytes of a line whose newline has not been read yet.
+	partial: Vec<u8>,
+}
+
+impl Follower {
+	const fn new(handle: Handle) -> Self {
+		Self { handle, pos: 0, partial: Vec::new() }
+	}
+
+	fn seek(&mut self, pos: u64) -> io::Result<()> {
+		self.handle.as_file_mut().seek(SeekFrom::Start(pos))?;
+		self.pos = pos;
+		self.partial.clear();
+		Ok(())
+	}
+
+	/// Read up to the end of the file, emitting complete lines in batches.
+	fn read_available(&mut self, emitter: &mut Emitter) -> io::Result<()> {
+		let mut buf = vec![0; READ_CHUNK];
+		let mut lines = Vec::new();
+		loop {
+			let read = self.handle.as_file_mut().read(&mut buf)?;
+			if read == 0 {
+				break;
+			}
+			self.pos += read as u64;
+			self.split(&buf[..read], &mut lines);
+			if lines.len() >= BATCH_LINES {
+				emitter.emit(mem::take(&mut lines));
+			}
+		}
+		emitter.emit(lines);
+		Ok(())
+	}
+
+	fn split(&mut self, mut chunk: &[u8], lines: &mut Vec<String>) {
+		while let Some(newline) = chunk.iter().position(|&byte| byte == b'\n') {
+			self.partial.extend_from_slice(&chunk[..newline]);
+			lines.push(decode_line(&mem::take(&mut self.partial)));
+			chunk = &chunk[newline + 1..];
+		}
+		self.partial.extend_from_slice(chunk);
+	}
+
+	/// The held partial line, once no newline is coming for it.
+	fn take_partial(&mut self) -> Option<String> {
+		(!self.partial.is_empty()).then(|| decode_line(&mem::take(&mut self.partial)))
+	}
+}
+
+fn decode_line(line: &[u8]) -> String {
+	let line = line.strip_suffix(b"\r").unwrap_or(line);
+	String::from_utf8_lossy(line).into_owned()
+}
+
+/// Offset where the last `lines` lines of `file` begin. A final newline ends
+/// the last line rather than starting an empty one.
+fn tail_offset(file: &mut File, len: u64, lines: u32) -> io::Result<u64> {
+	if lines == 0 {
+		return Ok(len);
+	}
+	let mut buf = vec![0; READ_CHUNK];
+	let mut found = 0;
+	let mut end = len;
+	while end > 0 {
+		let start = end.saturating_sub(READ_CHUNK as u64);
+		let chunk = &mut buf[..(end - start) as usize];
+		file.seek(SeekFrom::Start(start))?;
+		file.read_exact(chunk)?;
+		for (idx, _) in chunk
+			.iter()
+			.enumerate()
+			.rev()
+			.filter(|&(_, &byte)| byte == b'\n')
+		{
+			let offset = start + idx as u64 + 1;
+			if offset == len {
+				continue;
+			}
+			found += 1;
+			if found == lines {
+				return Ok(offset);
+			}
+		}
+		end = start;
+	}
+	Ok(0)
+}
+
+/// Emit the existing lines, then follow the file until `stop` is set.
+fn run_tail(
+	path: &Path,
+	mut follower: Follower,
+	follow: bool,
+	poll: Duration,
+	stop: &AtomicBool,
+	callback: &ThreadsafeFunction<TailEvent>,
+) -> io::Result<()> {
+	let mut emitter = Emitter { callback, rotated: false, truncated: false, sent: false };
+	follower.read_available(&mut emitter)?;
+	if !follow {
+		let last = follower.take_partial().into_iter().collect();
+		emitter.emit(last);
+	}
+	if !emitter.sent {
+		callback.call(Ok(TailEvent::default()), ThreadsafeFunctionCallMode::NonBlocking);
+	}
+	if !follow {
+		return Ok(());
+	}
+
+	loop {
+		thread::sleep(poll);
+		if stop.load(Ordering::Relaxed) {
+			return Ok(());
+		}
+		match Handle::from_path(path) {
+			Ok(current) if current != follower.handle => {
+				follower.read_available(&mut emitter)?;
+				let last = follower.take_partial().into_iter().collect();
+				emitter.emit(last);
+				follower = Follower::new(current);
+				emitter.rotated = true;
+			},
+			Ok(_) => {
+				if follower.handle.as_file().metadata()?.len() < follower.pos {
+					follower.seek(0)?;
+					emitter.truncated = true;
+				}
+			},
+			// Moved away and not recreated yet.
+			Err(_) => {},
+		}
+		follower.read_available(&mut emitter)?;
+	}
+}
+
+/// Emit the last lines of the file at `path` to `callback` and, with
+/// `follow`, keep emitting appended lines across log rotation until
+/// `stopTail` is called with the returned id.
+///
+/// Without `follow` exactly one event is delivered, and a final line without
+/// a trailing newline is included. With `follow` the first event carries the
+/// existing lines (possibly none).
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or read, is outside the
+/// workspace, or the tail thread cannot be spawned. Read errors while following
+/// are passed to `callback` and end the tail.
+#[napi(js_name = "tailFile")]
+pub fn tail_file(
+	path: String,
+	options: Option<TailOptions>,
+	#[napi(ts_arg_type = "(error: Error | null, event: TailEvent) => void")]
+	callback: ThreadsafeFunction<TailEvent>,
+) -> Result<u32> {
+	let options = options.unwrap_or(TailOptions {
+		from_end:                None,
+		follow:                  None,
+		max_lines:               None,
+		poll_ms:                 None,
+		allow_outside_workspace: None,
+	});
+	workspace::check(&path, options.allow_outside_workspace)?;
+	let path = PathBuf::from(path);
+	let io_error =
+		|err: io::Error| Error::from_reason(format!("Failed to tail {}: {err}", path.display()));
+
+	let mut follower = Follower::new(Handle::from_path(&path).map_err(io_error)?);
+	let start = if options.from_end.unwrap_or(true) {
+		let file = follower.handle.as_file_mut();
+		let len = file.metadata().map_err(io_error)?.len();
+		tail_offset(file, len, options.max_lines.unwrap_or(DEFAULT_MAX_LINES)).map_err(io_error)?
+	} else {
+		0
+	};
+	follower.seek(start).map_err(io_error)?;
+
+	let follow = options.follow.unwrap_or(false);
+	let poll = Duration::from_millis(options.poll_ms.unwrap_or(DEFAULT_POLL_MS).max(1).into());
+	let stop = Arc::new(AtomicBool::new(false));
+	let id = NEXT_TAIL_ID.fetch_add(1, Ordering::Relaxed);
+	TAILS.lock().insert(id, stop.clone());
+
+	thread::Builder::new()
+		.name("pi-tail".to_string())
+		.spawn(move || {
+			if let Err(err) = run_tail(&path, follower, follow, poll, &stop, &callback) {
+				callback.call(
+					Err(Error::from_reason(format!("Tail error: {err}"))),
+					ThreadsafeFunctionCallMode::NonBlocking,
+				);
+			}
+			TAILS.lock().remove(&id);
+		})
+		.map_err(|err| {
+			TAILS.lock().remove(&id);
+			Error::from_reason(format!("Failed to spawn tail thread: {err}"))
+		})?;
+	Ok(id)
+}
+
+/// Stop a tail started by `tailFile`. No events are delivered afterwards,
+/// except one already being read.
+/// Returns false if `id` is not an active tail.
+#[napi(js_name = "stopTail")]
+pub fn stop_tail(id: u32) -> bool {
+	let Some(stop) = TAILS.lock().remove(&id) else {
+		return false;
+	};
+	stop.store(true, Ordering::Relaxed);
+	true
+}
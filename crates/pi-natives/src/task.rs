@@ -138,6 +138,12 @@ impl CancelToken {
 		result
 	}
 
+	/// Start the timeout now, replacing any earlier deadline.
+	pub fn set_timeout(&mut self, timeout_ms: Option<u32>) {
+		self.deadline =
+			timeout_ms.map(|timeout_ms| Instant::now() + Duration::from_millis(timeout_ms as u64));
+	}
+
 	/// Check if cancellation has been requested.
 	///
 	/// Returns `Ok(())` if work should continue, or an error if cancelled.
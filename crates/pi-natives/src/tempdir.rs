@@ -0,0 +1,290 @@
+//! Managed temporary directories.
+//!
+//! # Overview
+//! `createManagedTempDir` creates a private directory under the system
+//! temporary directory and tracks it, optionally under a session id, until it
+//! is released: by `releaseManagedTempDirs` when a session ends, or when the
+//! JS environment that created it shuts down.
+//!
+//! Each directory has an owner marker beside it holding the creating process
+//! id. Directories outlive a process only if it crashes (or, on Windows,
+//! calls `process.exit`); the first use of this module in a later process
+//! removes those whose owner is gone.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const dir = await native.createManagedTempDir("unzip", { sessionId });
+//! // JS: await native.releaseManagedTempDirs(sessionId);
+//! ```
+
+use std::{
+	collections::HashSet,
+	env, fs, io,
+	path::{Path, PathBuf},
+	process,
+	sync::{
+		LazyLock,
+		atomic::{AtomicU64, Ordering},
+	},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use parking_lot::Mutex;
+
+use crate::{ps, store, task};
+
+/// Extension of the owner marker beside each directory.
+const MARKER_EXT: &str = "owner";
+
+/// Longest label kept in a directory name.
+const MAX_LABEL: usize = 40;
+
+/// Directories created by this process.
+static DIRS: LazyLock<Mutex<Vec<Managed>>> = LazyLock::new(Mutex::default);
+
+/// Environments with a cleanup hook registered.
+static HOOKED: LazyLock<Mutex<HashSet<usize>>> = LazyLock::new(Mutex::default);
+
+/// Removes the directories of dead processes, once per process.
+static SWEEP: LazyLock<()> = LazyLock::new(|| {
+	if let Ok(root) = root() {
+		sweep(&root);
+	}
+});
+
+/// Removes the remaining directories when the process exits.
+#[cfg(unix)]
+static AT_EXIT: LazyLock<()> = LazyLock::new(|| {
+	extern "C" fn release_all() {
+		release(|_| true);
+	}
+	// SAFETY: `release_all` is a plain function that outlives the process.
+	unsafe { libc::atexit(release_all) };
+});
+
+/// Suffix keeping directories of this process apart.
+static NEXT: AtomicU64 = AtomicU64::new(0);
+
+struct Managed {
+	path:       PathBuf,
+	label:      String,
+	session_id: Option<String>,
+	created_at: i64,
+	/// Environment whose shutdown removes the directory.
+	env:        usize,
+}
+
+/// Options for `createManagedTempDir`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ManagedTempDirOptions {
+	/// Session the directory belongs to, for `releaseManagedTempDirs`.
+	#[napi(js_name = "sessionId")]
+	pub session_id: Option<String>,
+}
+
+/// A directory from `createManagedTempDir`.
+#[napi(object)]
+pub struct ManagedTempDir {
+	/// Absolute path.
+	pub path:       String,
+	/// Label it was created with.
+	pub label:      String,
+	/// Session it belongs to.
+	#[napi(js_name = "sessionId")]
+	pub session_id: Option<String>,
+	/// Creation time in milliseconds since the Unix epoch.
+	#[napi(js_name = "createdAt")]
+	pub created_at: i64,
+}
+
+/// Directory holding managed directories and their markers.
+///
+/// The name is predictable, so on unix an existing directory is only used
+/// when it is a real directory owned by this user with mode 0700; otherwise
+/// another user could have created it first and control what it holds.
+fn root() -> Result<PathBuf> {
+	#[cfg(unix)]
+	// SAFETY: getuid has no failure modes.
+	let uid = unsafe { libc::getuid() };
+	#[cfg(unix)]
+	let name = format!("omp-temp-{uid}");
+	#[cfg(not(unix))]
+	let name = String::from("omp-temp");
+	let root = env::temp_dir().join(name);
+	let mut builder = fs::DirBuilder::new();
+	builder.recursive(true);
+	#[cfg(unix)]
+	std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+	builder
+		.create(&root)
+		.map_err(|err| Error::from_reason(format!("Failed to create {}: {err}", root.display())))?;
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::MetadataExt;
+
+		let meta = fs::symlink_metadata(&root).map_err(|err| {
+			Error::from_reason(format!("Failed to inspect {}: {err}", root.display()))
+		})?;
+		if !meta.file_type().is_dir() || meta.uid() != uid || meta.mode() & 0o777 != 0o700 {
+			return Err(Error::from_reason(format!(
+				"Refusing to use {}: it must be a directory owned by this user with mode 0700",
+				root.display()
+			)));
+		}
+	}
+	Ok(root)
+}
+
+fn marker(path: &Path) -> PathBuf {
+	path.with_extension(MARKER_EXT)
+}
+
+/// Remove a directory, then its marker; a directory that cannot be removed
+/// keeps its marker for a later sweep.
+fn remove(path: &Path) -> bool {
+	match fs::remove_dir_all(path) {
+		Ok(()) => {},
+		Err(err) if err.kind() == io::ErrorKind::NotFound => {},
+		Err(_) => return false,
+	}
+	let _ = fs::remove_file(marker(path));
+	true
+}
+
+/// Remove the directories whose owner process is gone.
+fn sweep(root: &Path) {
+	let Ok(entries) = fs::read_dir(root) else {
+		return;
+	};
+	for entry in entries.flatten() {
+		let path = entry.path();
+		if path.extension().is_none_or(|ext| ext != MARKER_EXT) {
+			continue;
+		}
+		let owner = fs::read_to_string(&path)
+			.ok()
+			.and_then(|text| text.trim().parse::<i32>().ok());
+		if owner.is_some_and(|pid| pid == process::id() as i32 || ps::is_alive(pid)) {
+			continue;
+		}
+		remove(&path.with_extension(""));
+	}
+}
+
+/// Directory name for `label`: file-name safe, never empty.
+fn clean_label(label: &str) -> String {
+	let label: String = label
+		.chars()
+		.map(|char| {
+			if char.is_ascii_alphanumeric() || "-_".contains(char) {
+				char
+			} else {
+				'-'
+			}
+		})
+		.take(MAX_LABEL)
+		.collect();
+	if label.is_empty() {
+		String::from("tmp")
+	} else {
+		label
+	}
+}
+
+fn create(label: String, session_id: Option<String>, env: usize) -> Result<String> {
+	LazyLock::force(&SWEEP);
+	let root = root()?;
+	let mut builder = fs::DirBuilder::new();
+	#[cfg(unix)]
+	std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+	let pid = process::id();
+	let path = loop {
+		let path = root.join(format!(
+			"{}-{pid}-{}",
+			clean_label(&label),
+			NEXT.fetch_add(1, Ordering::Relaxed)
+		));
+		// The marker comes first, so no directory is ever left without one.
+		fs::write(marker(&path), pid.to_string()).map_err(|err| {
+			Error::from_reason(format!("Failed to create temporary directory: {err}"))
+		})?;
+		match builder.create(&path) {
+			Ok(()) => break path,
+			Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {},
+			Err(err) => {
+				let _ = fs::remove_file(marker(&path));
+				return Err(Error::from_reason(format!("Failed to create temporary directory: {err}")));
+			},
+		}
+	};
+	let path_str = path.to_string_lossy().into_owned();
+	DIRS
+		.lock()
+		.push(Managed { path, label, session_id, created_at: store::now_ms(), env });
+	Ok(path_str)
+}
+
+/// Remove the directories `select` picks, returning how many were removed.
+fn release(select: impl Fn(&Managed) -> bool) -> u32 {
+	let released: Vec<Managed> = {
+		let mut dirs = DIRS.lock();
+		let (released, kept) = dirs.drain(..).partition(|dir| select(dir));
+		*dirs = kept;
+		released
+	};
+	released.iter().filter(|dir| remove(&dir.path)).count() as u32
+}
+
+/// Create a private temporary directory whose name starts with `label`, and
+/// track it until released or the environment shuts down.
+///
+/// # Errors
+/// Returns an error if the directory cannot be created.
+#[napi(js_name = "createManagedTempDir")]
+pub fn create_managed_temp_dir(
+	env: &Env,
+	label: String,
+	options: Option<ManagedTempDirOptions>,
+) -> Result<task::Async<String>> {
+	let id = env.raw() as usize;
+	if HOOKED.lock().insert(id) {
+		env.add_env_cleanup_hook(id, |id| {
+			HOOKED.lock().remove(&id);
+			release(|dir| dir.env == id);
+		})?;
+		// `process.exit` skips environment cleanup but not `atexit`.
+		#[cfg(unix)]
+		LazyLock::force(&AT_EXIT);
+	}
+	let session_id = options.unwrap_or_default().session_id;
+	Ok(task::blocking("tempdir.create", (), move |_| create(label, session_id, id)))
+}
+
+/// Directories created in this process and not yet released, optionally only
+/// those of `sessionId`, oldest first.
+#[napi(js_name = "listManagedTempDirs")]
+pub fn list_managed_temp_dirs(options: Option<ManagedTempDirOptions>) -> Vec<ManagedTempDir> {
+	let session_id = options.unwrap_or_default().session_id;
+	DIRS
+		.lock()
+		.iter()
+		.filter(|dir| session_id.is_none() || dir.session_id == session_id)
+		.map(|dir| ManagedTempDir {
+			path:       dir.path.to_string_lossy().into_owned(),
+			label:      dir.label.clone(),
+			session_id: dir.session_id.clone(),
+			created_at: dir.created_at,
+		})
+		.collect()
+}
+
+/// Remove the directories of `sessionId`, or all created in this process,
+/// returning how many were removed.
+#[napi(js_name = "releaseManagedTempDirs")]
+pub fn release_managed_temp_dirs(session_id: Option<String>) -> task::Async<u32> {
+	task::blocking("tempdir.release", (), move |_| {
+		Ok(release(|dir| session_id.is_none() || dir.session_id == session_id))
+	})
+}
@@ -0,0 +1,356 @@
+//! Terminal capability detection.
+//!
+//! # Overview
+//! `termInfo` reports what the terminal this process writes to can display,
+//! so the renderer can degrade gracefully without checking environment
+//! variables itself:
+//! - **Color depth** from `NO_COLOR`/`FORCE_COLOR`, `COLORTERM`, `TERM` and the
+//!   terminal program
+//! - **Size** in cells and pixels from the terminal driver, falling back to
+//!   `COLUMNS`/`LINES`
+//! - **Program and multiplexer** from the variables terminals export
+//! - **Hyperlinks (OSC 8)** and **images** (kitty graphics, iTerm2 inline
+//!   images) for terminals known to support them, outside multiplexers that do
+//!   not pass them through
+//! - **Width quirks**: ambiguous-width characters under CJK locales, and emoji
+//!   presentation sequences drawn two cells wide
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { colorDepth, hyperlinks } = native.termInfo()
+//! ```
+
+use std::{env, io::IsTerminal as _};
+
+use napi_derive::napi;
+
+/// Capabilities of the terminal, as reported by `termInfo`.
+#[napi(object)]
+pub struct TermInfo {
+	/// Whether stdout is a terminal.
+	pub tty:                  bool,
+	/// Colors the terminal renders: "truecolor", "256", "16", or "none" when
+	/// output is not a terminal, `TERM` is "dumb" or `NO_COLOR` is set.
+	/// `FORCE_COLOR` (0-3) overrides detection.
+	#[napi(js_name = "colorDepth")]
+	pub color_depth:          String,
+	/// Width in cells.
+	pub columns:              Option<u32>,
+	/// Height in cells.
+	pub rows:                 Option<u32>,
+	/// Width in pixels, when the terminal reports it.
+	#[napi(js_name = "pixelWidth")]
+	pub pixel_width:          Option<u32>,
+	/// Height in pixels, when the terminal reports it.
+	#[napi(js_name = "pixelHeight")]
+	pub pixel_height:         Option<u32>,
+	/// Terminal program: "kitty", "ghostty", "wezterm", "iterm",
+	/// "apple-terminal", "vscode", "windows-terminal", "alacritty", "foot",
+	/// "konsole", "vte" (GNOME Terminal and other VTE-based terminals),
+	/// "mintty", or the raw `TERM_PROGRAM` value for others.
+	pub program:              Option<String>,
+	/// Multiplexer between this process and the terminal: "tmux", "screen"
+	/// or "zellij".
+	pub multiplexer:          Option<String>,
+	/// Whether OSC 8 hyperlinks are rendered. `FORCE_HYPERLINK` overrides
+	/// detection.
+	pub hyperlinks:           bool,
+	/// Whether the kitty graphics protocol is supported.
+	#[napi(js_name = "kittyGraphics")]
+	pub kitty_graphics:       bool,
+	/// Whether iTerm2 inline images (OSC 1337) are supported.
+	#[napi(js_name = "itermImages")]
+	pub iterm_images:         bool,
+	/// Cells taken by East Asian ambiguous-width characters such as `±` or
+	/// `○`: 2 under Chinese, Japanese and Korean locales, 1 otherwise.
+	#[napi(js_name = "ambiguousWidth")]
+	pub ambiguous_width:      u32,
+	/// Whether emoji presentation sequences (a text character followed by
+	/// U+FE0F, like `❤️`) take two cells rather than one.
+	#[napi(js_name = "emojiVariationWide")]
+	pub emoji_variation_wide: bool,
+}
+
+/// Detect the capabilities of the terminal this process writes to.
+#[napi(js_name = "termInfo")]
+pub fn term_info() -> TermInfo {
+	let tty = std::io::stdout().is_terminal();
+	let term = var("TERM").unwrap_or_default();
+	let program = detect_program(&term);
+	let multiplexer = detect_multiplexer();
+	let size = platform::window_size();
+	// Graphics and hyperlinks reach the terminal only when passed through.
+	let passthrough = multiplexer.is_none();
+	let program = program.as_deref();
+	TermInfo {
+		tty,
+		color_depth: color_depth(tty, &term, program).to_string(),
+		columns: size
+			.map(|size| size.columns)
+			.or_else(|| env_size("COLUMNS")),
+		rows: size.map(|size| size.rows).or_else(|| env_size("LINES")),
+		pixel_width: size.and_then(|size| size.pixel_width),
+		pixel_height: size.and_then(|size| size.pixel_height),
+		hyperlinks: hyperlinks(tty, program, multiplexer.as_deref()),
+		kitty_graphics: passthrough && matches!(program, Some("kitty" | "ghostty" | "wezterm")),
+		iterm_images: passthrough && matches!(program, Some("iterm" | "wezterm" | "mintty")),
+		ambiguous_width: if cjk_locale() { 2 } else { 1 },
+		emoji_variation_wide: matches!(
+			program,
+			Some("kitty" | "ghostty" | "wezterm" | "iterm" | "windows-terminal")
+		),
+		program: program.map(str::to_string),
+		multiplexer,
+	}
+}
+
+/// Non-empty value of an environment variable.
+fn var(name: &str) -> Option<String> {
+	env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn env_size(name: &str) -> Option<u32> {
+	var(name)?.parse().ok().filter(|&size| size > 0)
+}
+
+fn detect_program(term: &str) -> Option<String> {
+	// tmux replaces TERM_PROGRAM with its own name; the outer terminal's
+	// specific variables usually survive.
+	let term_program = var("TERM_PROGRAM").filter(|program| program != "tmux");
+	let known = match term_program.as_deref() {
+		Some("iTerm.app") => Some("iterm"),
+		Some("Apple_Terminal") => Some("apple-terminal"),
+		Some("WezTerm") => Some("wezterm"),
+		Some("ghostty") => Some("ghostty"),
+		Some("vscode") => Some("vscode"),
+		Some("mintty") => Some("mintty"),
+		Some(_) => None,
+		None => {
+			let is_set = |name: &str| var(name).is_some();
+			if is_set("KITTY_WINDOW_ID") || term == "xterm-kitty" {
+				Some("kitty")
+			} else if is_set("GHOSTTY_RESOURCES_DIR") || term == "xterm-ghostty" {
+				Some("ghostty")
+			} else if is_set("WEZTERM_PANE") {
+				Some("wezterm")
+			} else if var("LC_TERMINAL").as_deref() == Some("iTerm2") {
+				Some("iterm")
+			} else if is_set("WT_SESSION") {
+				Some("windows-terminal")
+			} else if is_set("ALACRITTY_WINDOW_ID") || term == "alacritty" {
+				Some("alacritty")
+			} else if term.starts_with("foot") {
+				Some("foot")
+			} else if is_set("KONSOLE_VERSION") {
+				Some("konsole")
+			} else if is_set("VTE_VERSION") {
+				Some("vte")
+			} else {
+				None
+			}
+		},
+	};
+	known.map(str::to_string).or(term_program)
+}
+
+fn detect_multiplexer() -> Option<String> {
+	let name = if var("TMUX").is_some() {
+		"tmux"
+	} else if var("ZELLIJ").is_some() {
+		"zellij"
+	} else if var("STY").is_some() {
+		"screen"
+	} else {
+		return None;
+	};
+	Some(name.to_string())
+}
+
+const TRUECOLOR_PROGRAMS: [&str; 9] = [
+	"kitty",
+	"ghostty",
+	"wezterm",
+	"iterm",
+	"vscode",
+	"windows-terminal",
+	"alacritty",
+	"foot",
+	"konsole",
+];
+
+fn color_depth(tty: bool, term: &str, program: Option<&str>) -> &'static str {
+	if var("NO_COLOR").is_some() {
+		return "none";
+	}
+	match var("FORCE_COLOR").as_deref() {
+		Some("0" | "false") => return "none",
+		Some("2") => return "256",
+		Some("3") => return "truecolor",
+		Some(_) => return "16",
+		None => {},
+	}
+	if !tty || term == "dumb" {
+		return "none";
+	}
+	if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit"))
+		|| term.ends_with("-direct")
+		|| program.is_some_and(|program| TRUECOLOR_PROGRAMS.contains(&program))
+	{
+		return "truecolor";
+	}
+	if term.contains("256") || program == Some("apple-terminal") {
+		return "256";
+	}
+	// Windows consoles have rendered 24-bit color since Windows 10.
+	if cfg!(windows) {
+		return "truecolor";
+	}
+	"16"
+}
+
+fn hyperlinks(tty: bool, program: Option<&str>, multiplexer: Option<&str>) -> bool {
+	if let Some(force) = var("FORCE_HYPERLINK") {
+		return force != "0";
+	}
+	if !tty || multiplexer == Some("screen") {
+		return false;
+	}
+	match program {
+		Some(
+			"kitty" | "ghostty" | "wezterm" | "iterm" | "vscode" | "windows-terminal" | "alacritty"
+			| "foot" | "konsole" | "mintty",
+		) => true,
+		// GNOME Terminal and friends since VTE 0.50.
+		Some("vte") => var("VTE_VERSION")
+			.and_then(|version| version.parse::<u32>().ok())
+			.is_some_and(|version| version >= 5000),
+		_ => false,
+	}
+}
+
+fn cjk_locale() -> bool {
+	let locale = var("LC_ALL")
+		.or_else(|| var("LC_CTYPE"))
+		.or_else(|| var("LANG"))
+		.unwrap_or_default();
+	["ja", "zh", "ko"]
+		.iter()
+		.any(|lang| locale.starts_with(lang))
+}
+
+#[derive(Clone, Copy)]
+struct WindowSize {
+	columns:      u32,
+	rows:         u32,
+	pixel_width:  Option<u32>,
+	pixel_height: Option<u32>,
+}
+
+#[cfg(unix)]
+mod platform {
+	use std::{fs::File, os::fd::AsRawFd as _};
+
+	use super::WindowSize;
+
+	fn query(fd: i32) -> Option<WindowSize> {
+		// SAFETY: `winsize` is plain data, valid when zeroed.
+		let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+		// SAFETY: TIOCGWINSZ only writes a `winsize` through the pointer.
+		if unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &raw mut size) } != 0 || size.ws_col == 0 {
+			return None;
+		}
+		let pixels = |value: u16| (value > 0).then_some(u32::from(value));
+		Some(WindowSize {
+			columns:      u32::from(size.ws_col),
+			rows:         u32::from(size.ws_row),
+			pixel_width:  pixels(size.ws_xpixel),
+			pixel_height: pixels(size.ws_ypixel),
+		})
+	}
+
+	/// Size of the terminal on stdout, stderr or stdin, or the controlling
+	/// terminal when all three are redirected.
+	pub fn window_size() -> Option<WindowSize> {
+		[libc::STDOUT_FILENO, libc::STDERR_FILENO, libc::STDIN_FILENO]
+			.into_iter()
+			.find_map(query)
+			.or_else(|| query(File::open("/dev/tty").ok()?.as_raw_fd()))
+	}
+}
+
+#[cfg(windows)]
+mod platform {
+	use super::WindowSize;
+
+	type HANDLE = *mut std::ffi::c_void;
+
+	const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+	const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+
+	#[repr(C)]
+	#[derive(Default)]
+	#[allow(non_snake_case, reason = "mirrors the Win32 struct layout")]
+	struct COORD {
+		X: i16,
+		Y: i16,
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	#[allow(non_snake_case, reason = "mirrors the Win32 struct layout")]
+	struct SMALL_RECT {
+		Left:   i16,
+		Top:    i16,
+		Right:  i16,
+		Bottom: i16,
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	#[allow(non_snake_case, reason = "mirrors the Win32 struct layout")]
+	struct CONSOLE_SCREEN_BUFFER_INFO {
+		dwSize:              COORD,
+		dwCursorPosition:    COORD,
+		wAttributes:         u16,
+		srWindow:            SMALL_RECT,
+		dwMaximumWindowSize: COORD,
+	}
+
+	#[link(name = "kernel32")]
+	unsafe extern "system" {
+		fn GetStdHandle(nStdHandle: u32) -> HANDLE;
+		fn GetConsoleScreenBufferInfo(
+			hConsoleOutput: HANDLE,
+			lpConsoleScreenBufferInfo: *mut CONSOLE_SCREEN_BUFFER_INFO,
+		) -> i32;
+	}
+
+	/// Size of the visible console window on stdout or stderr.
+	pub fn window_size() -> Option<WindowSize> {
+		[STD_OUTPUT_HANDLE, STD_ERROR_HANDLE]
+			.into_iter()
+			.find_map(|std_handle| {
+				let mut info = CONSOLE_SCREEN_BUFFER_INFO::default();
+				// SAFETY: `info` is a valid out-pointer for the call.
+				if unsafe { GetConsoleScreenBufferInfo(GetStdHandle(std_handle), &raw mut info) } == 0 {
+					return None;
+				}
+				let window = &info.srWindow;
+				Some(WindowSize {
+					columns:      u32::try_from(window.Right - window.Left + 1).ok()?,
+					rows:         u32::try_from(window.Bottom - window.Top + 1).ok()?,
+					pixel_width:  None,
+					pixel_height: None,
+				})
+			})
+	}
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+	use super::WindowSize;
+
+	pub const fn window_size() -> Option<WindowSize> {
+		None
+	}
+}
@@ -74,17 +74,20 @@ const ATTR_STRIKE: u16 = 1 << 8;
 type ColorVal = u32;
 const COLOR_NONE: ColorVal = 0;
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Default)]
 struct AnsiState {
 	attrs: u16,
 	fg:    ColorVal,
 	bg:    ColorVal,
+	/// OSC 8 sequence that opened the active hyperlink. SGR resets leave it
+	/// open.
+	link:  Option<Vec<u16>>,
 }
 
 impl AnsiState {
 	#[inline]
 	const fn new() -> Self {
-		Self { attrs: 0, fg: COLOR_NONE, bg: COLOR_NONE }
+		Self { attrs: 0, fg: COLOR_NONE, bg: COLOR_NONE, link: None }
 	}
 
 	#[inline]
@@ -94,7 +97,18 @@ impl AnsiState {
 
 	#[inline]
 	const fn reset(&mut self) {
-		*self = Self::new();
+		self.attrs = 0;
+		self.fg = COLOR_NONE;
+		self.bg = COLOR_NONE;
+	}
+
+	/// Track SGR attributes and OSC 8 hyperlinks from an escape sequence.
+	fn apply_seq_u16(&mut self, seq: &[u16]) {
+		if is_sgr_u16(seq) {
+			self.apply_sgr_u16(&seq[2..seq.len() - 1]);
+		} else if let Some(opens) = osc8_opens_link_u16(seq) {
+			self.link = opens.then(|| seq.to_vec());
+		}
 	}
 
 	fn apply_sgr_u16(&mut self, params: &[u16]) {
@@ -346,10 +360,24 @@ fn ansi_seq_len_u16(data: &[u16], pos: usize) -> Option<usize> {
 }
 
 #[inline]
-fn is_sgr_u16(seq: &[u16]) -> bool {
+const fn is_sgr_u16(seq: &[u16]) -> bool {
 	seq.len() >= 3 && seq[1] == b'[' as u16 && *seq.last().unwrap() == b'm' as u16
 }
 
+/// OSC 8 closing an open hyperlink.
+const OSC8_CLOSE: &[u16] = &[ESC, b']' as u16, b'8' as u16, b';' as u16, b';' as u16, 0x07];
+
+/// For an OSC 8 hyperlink sequence (`ESC ] 8 ; params ; uri ST`), whether it
+/// opens a link (non-empty `uri`) rather than closing one.
+fn osc8_opens_link_u16(seq: &[u16]) -> Option<bool> {
+	let body = seq.strip_prefix(&[ESC, b']' as u16, b'8' as u16, b';' as u16])?;
+	let body = body
+		.strip_suffix(&[0x07])
+		.or_else(|| body.strip_suffix(&[ESC, b'\\' as u16]))?;
+	let uri_start = body.iter().position(|&u| u == b';' as u16)? + 1;
+	Some(uri_start < body.len())
+}
+
 // ============================================================================
 // Grapheme / Width
 // ============================================================================
@@ -471,6 +499,12 @@ fn visible_width_u16(data: &[u16]) -> usize {
 	visible_width_u16_up_to(data, usize::MAX).0
 }
 
+/// Visible width of `text`, for callers working with Rust strings.
+pub(crate) fn visible_width_str(text: &str) -> usize {
+	let data: Vec<u16> = text.encode_utf16().collect();
+	visible_width_u16(&data)
+}
+
 // ============================================================================
 // wrapTextWithAnsi
 // ============================================================================
@@ -480,6 +514,9 @@ fn write_active_codes(state: &AnsiState, out: &mut Vec<u16>) {
 	if !state.is_empty() {
 		state.write_restore_u16(out);
 	}
+	if let Some(link) = &state.link {
+		out.extend_from_slice(link);
+	}
 }
 
 #[inline]
@@ -487,6 +524,10 @@ fn write_line_end_reset(state: &AnsiState, out: &mut Vec<u16>) {
 	if state.attrs & ATTR_UNDERLINE != 0 {
 		out.extend_from_slice(&[ESC, b'[' as u16, b'2' as u16, b'4' as u16, b'm' as u16]);
 	}
+	// A hyperlink left open would extend over whatever follows the line.
+	if state.link.is_some() {
+		out.extend_from_slice(OSC8_CLOSE);
+	}
 }
 
 fn update_state_from_text(data: &[u16], state: &mut AnsiState) {
@@ -495,10 +536,7 @@ fn update_state_from_text(data: &[u16], state: &mut AnsiState) {
 		if data[i] == ESC
 			&& let Some(seq_len) = ansi_seq_len_u16(data, i)
 		{
-			let seq = &data[i..i + seq_len];
-			if is_sgr_u16(seq) {
-				state.apply_sgr_u16(&seq[2..seq_len - 1]);
-			}
+			state.apply_seq_u16(&data[i..i + seq_len]);
 			i += seq_len;
 			continue;
 		}
@@ -590,9 +628,7 @@ fn break_long_word(word: &[u16], width: usize, state: &mut AnsiState) -> SmallVe
 		{
 			let seq = &word[i..i + seq_len];
 			current_line.extend_from_slice(seq);
-			if is_sgr_u16(seq) {
-				state.apply_sgr_u16(&seq[2..seq_len - 1]);
-			}
+			state.apply_seq_u16(seq);
 			i += seq_len;
 			continue;
 		}
@@ -686,13 +722,16 @@ fn wrap_single_line(line: &[u16], width: usize) -> SmallVec<[Vec<u16>; 4]> {
 			wrapped.push(line_to_wrap);
 
 			current_line = Vec::new();
-			write_active_codes(&state, &mut current_line);
 			if is_whitespace {
+				// Dropped at the break, but its codes still apply to what follows.
+				update_state_from_text(&token, &mut state);
+				write_active_codes(&state, &mut current_line);
 				current_width = 0;
-			} else {
-				current_line.extend_from_slice(&token);
-				current_width = token_width;
+				continue;
 			}
+			write_active_codes(&state, &mut current_line);
+			current_line.extend_from_slice(&token);
+			current_width = token_width;
 		} else {
 			current_line.extend_from_slice(&token);
 			current_width += token_width;
@@ -734,9 +773,14 @@ fn wrap_text_with_ansi_impl(text: &[u16], width: usize) -> SmallVec<[Vec<u16>; 4
 			}
 			line_with_prefix.extend_from_slice(line);
 
-			let wrapped = wrap_single_line(&line_with_prefix, width);
-			result.extend(wrapped);
+			let mut wrapped = wrap_single_line(&line_with_prefix, width);
 			update_state_from_text(line, &mut state);
+			if state.link.is_some()
+				&& let Some(last) = wrapped.last_mut()
+			{
+				last.extend_from_slice(OSC8_CLOSE);
+			}
+			result.extend(wrapped);
 			line_start = i + 1;
 		}
 	}
@@ -748,10 +792,20 @@ fn wrap_text_with_ansi_impl(text: &[u16], width: usize) -> SmallVec<[Vec<u16>; 4
 	result
 }
 
+/// `wrapTextWithAnsi` for callers working with Rust strings.
+pub(crate) fn wrap_str_with_ansi(text: &str, width: usize) -> Vec<String> {
+	let data: Vec<u16> = text.encode_utf16().collect();
+	wrap_text_with_ansi_impl(&data, width)
+		.into_iter()
+		.map(|line| String::from_utf16_lossy(&line))
+		.collect()
+}
+
 /// Wrap text to a visible width, preserving ANSI escape codes across line
 /// breaks.
 ///
-/// Returns UTF-16 lines with active SGR codes carried across line boundaries.
+/// Returns UTF-16 lines with active SGR codes and OSC 8 hyperlinks carried
+/// across line boundaries; each line closes the hyperlinks it leaves open.
 #[napi(js_name = "wrapTextWithAnsi")]
 pub fn wrap_text_with_ansi(text: JsString, width: u32) -> Result<Vec<Utf16String>> {
 	let text_u16 = text.into_utf16()?;
@@ -763,7 +817,8 @@ pub fn wrap_text_with_ansi(text: JsString, width: u32) -> Result<Vec<Utf16String
 // truncateToWidth
 // ============================================================================
 
-/// Truncate text to a visible width, preserving ANSI codes.
+/// Truncate text to a visible width, preserving ANSI codes and closing a
+/// hyperlink that is cut off.
 ///
 /// `ellipsis_kind`: 0 = "…", 1 = "...", 2 = "" (omit); pads with spaces when
 /// requested.
@@ -841,6 +896,7 @@ pub fn truncate_to_width(
 	let text_len = text.len();
 
 	let mut saw_sgr = false;
+	let mut link_open = false;
 
 	while i < text_len {
 		if text[i] == ESC {
@@ -849,6 +905,8 @@ pub fn truncate_to_width(
 				out.extend_from_slice(seq);
 				if is_sgr_u16(seq) {
 					saw_sgr = true;
+				} else if let Some(opens) = osc8_opens_link_u16(seq) {
+					link_open = opens;
 				}
 				i += seq_len;
 				continue;
@@ -895,6 +953,10 @@ pub fn truncate_to_width(
 		}
 	}
 
+	// Keep the ellipsis and padding out of a hyperlink cut off mid-text.
+	if link_open {
+		out.extend_from_slice(OSC8_CLOSE);
+	}
 	// Only reset if we actually copied SGR codes into the output.
 	if saw_sgr {
 		out.extend_from_slice(&[ESC, b'[' as u16, b'0' as u16, b'm' as u16]);
@@ -1377,4 +1439,31 @@ mod tests {
 		assert!(second.starts_with("\x1b[38;2;156;163;176m"));
 		assert!(second.contains("world"));
 	}
+
+	#[test]
+	fn test_wrap_text_with_ansi_carries_hyperlink() {
+		let data = to_u16("\x1b]8;;https://example.com\x07hello world\x1b]8;;\x07 after");
+		let lines: Vec<String> = wrap_text_with_ansi_impl(&data, 5)
+			.iter()
+			.map(|line| String::from_utf16_lossy(line))
+			.collect();
+		assert_eq!(lines, [
+			"\x1b]8;;https://example.com\x07hello\x1b]8;;\x07",
+			"\x1b]8;;https://example.com\x07world\x1b]8;;\x07",
+			"after",
+		]);
+	}
+
+	#[test]
+	fn test_wrap_text_with_ansi_closes_hyperlink_at_newline() {
+		let data = to_u16("\x1b]8;id=1;https://example.com\x1b\\one\ntwo\x1b]8;;\x1b\\");
+		let lines: Vec<String> = wrap_text_with_ansi_impl(&data, 10)
+			.iter()
+			.map(|line| String::from_utf16_lossy(line))
+			.collect();
+		assert_eq!(lines, [
+			"\x1b]8;id=1;https://example.com\x1b\\one\x1b]8;;\x07",
+			"\x1b]8;id=1;https://example.com\x1b\\two\x1b]8;;\x1b\\",
+		]);
+	}
 }
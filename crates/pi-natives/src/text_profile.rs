@@ -0,0 +1,112 @@
+//! Line ending, final newline and BOM profile of text files.
+//!
+//! # Overview
+//! Edits are made on plain, LF-separated text. A [`TextProfile`] records how
+//! a file actually stores its text, so edited content can be written back the
+//! same way instead of rewriting every line of a CRLF or BOM-prefixed file:
+//! - `readFileSmart` reports the profile of the file it read.
+//! - `writeFileAtomic` with `profile` converts line endings, restores or drops
+//!   the final newline, re-encodes and prepends the BOM.
+//! - `applyUnifiedDiff` keeps the profile of every file it patches, and the
+//!   ending of every line it leaves in place.
+//!
+//! Files mixing LF and CRLF are written with their dominant line ending,
+//! except by `applyUnifiedDiff`, where only added lines take it.
+
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use napi_derive::napi;
+
+/// How a text file stores its lines.
+#[napi(object)]
+#[derive(Clone)]
+pub struct TextProfile {
+	/// Dominant line ending; `lf` for files without line breaks.
+	#[napi(ts_type = "\"lf\" | \"crlf\"")]
+	pub eol:           String,
+	/// Whether both LF and CRLF line endings occur.
+	pub mixed:         bool,
+	/// Whether the file starts with a byte order mark.
+	pub bom:           bool,
+	/// Whether the last line ends with a line ending.
+	#[napi(js_name = "finalNewline")]
+	pub final_newline: bool,
+	/// Encoding label (e.g. "UTF-8", "UTF-16LE", "windows-1252").
+	pub encoding:      String,
+}
+
+/// Profile of decoded `text` (without BOM).
+pub fn detect(text: &str, encoding: &'static Encoding, bom: bool) -> TextProfile {
+	let newlines = text.matches('\n').count();
+	let crlf = text.matches("\r\n").count();
+	let lf = newlines - crlf;
+	TextProfile {
+		eol: if crlf > lf { "crlf" } else { "lf" }.to_string(),
+		mixed: crlf > 0 && lf > 0,
+		bom,
+		final_newline: text.ends_with('\n'),
+		encoding: encoding.name().to_string(),
+	}
+}
+
+/// `content` with the profile's line endings and final newline.
+pub fn normalize(profile: &TextProfile, content: &str) -> String {
+	let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+	let eol = if profile.eol == "crlf" { "\r\n" } else { "\n" };
+	let mut text = String::with_capacity(content.len() + content.len() / 32);
+	let mut lines = content.split('\n').peekable();
+	while let Some(line) = lines.next() {
+		text.push_str(line.strip_suffix('\r').unwrap_or(line));
+		if lines.peek().is_some() {
+			text.push_str(eol);
+		}
+	}
+	if profile.final_newline {
+		if !text.is_empty() && !text.ends_with('\n') {
+			text.push_str(eol);
+		}
+	} else if let Some(stripped) = text.strip_suffix(eol) {
+		text.truncate(stripped.len());
+	}
+	text
+}
+
+/// Encode `text` in `encoding`, prefixed with its BOM when `bom` is set.
+///
+/// # Errors
+/// Returns an error when `text` has characters `encoding` cannot represent.
+pub fn encode(text: &str, encoding: &'static Encoding, bom: bool) -> Result<Vec<u8>, String> {
+	let utf16 = |to_bytes: fn(u16) -> [u8; 2], bom_bytes: [u8; 2]| {
+		let mut bytes = Vec::with_capacity(text.len() * 2 + 2);
+		if bom {
+			bytes.extend(bom_bytes);
+		}
+		bytes.extend(text.encode_utf16().flat_map(to_bytes));
+		bytes
+	};
+	if encoding == UTF_16LE {
+		return Ok(utf16(u16::to_le_bytes, [0xff, 0xfe]));
+	}
+	if encoding == UTF_16BE {
+		return Ok(utf16(u16::to_be_bytes, [0xfe, 0xff]));
+	}
+	let (encoded, _, unmappable) = encoding.encode(text);
+	if unmappable {
+		return Err(format!("text cannot be encoded as {}", encoding.name()));
+	}
+	let mut bytes = Vec::with_capacity(encoded.len() + 3);
+	if bom && encoding == UTF_8 {
+		bytes.extend(b"\xEF\xBB\xBF");
+	}
+	bytes.extend_from_slice(&encoded);
+	Ok(bytes)
+}
+
+/// `content` normalized and encoded as described by `profile`.
+///
+/// # Errors
+/// Returns an error for unknown encodings and unencodable text.
+pub fn apply(profile: &TextProfile, content: &str) -> Result<Vec<u8>, String> {
+	let encoding = Encoding::for_label(profile.encoding.as_bytes())
+		.ok_or_else(|| format!("unknown encoding {}", profile.encoding))?;
+	encode(&normalize(profile, content), encoding, profile.bom)
+}
@@ -0,0 +1,415 @@
+//! Token counting and truncation with compiled-in BPE vocabularies.
+//!
+//! # Overview
+//! Models are mapped to a tiktoken encoding by name:
+//! - `o200k_base`: GPT-4o, GPT-4.1, GPT-4.5, GPT-5, o-series reasoning models
+//! - `cl100k_base`: GPT-4, GPT-3.5, embeddings, and models without a public
+//!   tokenizer (Claude, Gemini, ...), where it is an approximation
+//! - `p50k_base`, `r50k_base`: legacy completion models
+//!
+//! Encoding names are accepted as models, and provider prefixes such as
+//! `openai/` are ignored. Text is encoded as ordinary text, so special-token
+//! markup like `<|endoftext|>` in tool output is counted literally.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const n = await native.countTokens(output, "gpt-4o");
+//! // JS: const head = await native.truncateToTokens(output, "claude-sonnet-4", 8000);
+//! ```
+
+use napi_derive::napi;
+use tiktoken_rs::{
+	CoreBPE, cl100k_base_singleton, o200k_base_singleton, p50k_base_singleton, p50k_edit_singleton,
+	r50k_base_singleton,
+	tokenizer::{Tokenizer, get_tokenizer},
+};
+
+use crate::task;
+
+/// Encoding used for `model`.
+pub fn encoding(model: &str) -> &'static CoreBPE {
+	let model = model
+		.rsplit('/')
+		.next()
+		.unwrap_or(model)
+		.to_ascii_lowercase();
+	let tokenizer = match model.as_str() {
+		"o200k_base" => Tokenizer::O200kBase,
+		"cl100k_base" => Tokenizer::Cl100kBase,
+		"p50k_base" => Tokenizer::P50kBase,
+		"p50k_edit" => Tokenizer::P50kEdit,
+		"r50k_base" | "gpt2" => Tokenizer::R50kBase,
+		// Newer than the crate's model table.
+		name
+			if name.starts_with("gpt-5")
+				|| name.starts_with("gpt-4.5")
+				|| name.starts_with("gpt-oss")
+				|| name.starts_with("codex")
+				|| (name.starts_with('o') && name[1..].starts_with(|c: char| c.is_ascii_digit())) =>
+		{
+			Tokenizer::O200kBase
+		},
+		name => get_tokenizer(name).unwrap_or(Tokenizer::Cl100kBase),
+	};
+	match tokenizer {
+		Tokenizer::O200kBase => o200k_base_singleton(),
+		Tokenizer::Cl100kBase => cl100k_base_singleton(),
+		Tokenizer::P50kBase => p50k_base_singleton(),
+		Tokenizer::P50kEdit => p50k_edit_singleton(),
+		Tokenizer::R50kBase | Tokenizer::Gpt2 => r50k_base_singleton(),
+	}
+}
+
+/// Number of tokens in `text`.
+pub fn count(bpe: &CoreBPE, text: &str) -> usize {
+	bpe.encode_ordinary(text).len()
+}
+
+/// Longest prefix of `text` that encodes to at most `limit` tokens, cut on a
+/// character boundary.
+pub fn prefix<'a>(bpe: &CoreBPE, text: &'a str, limit: usize) -> &'a str {
+	let tokens = bpe.encode_ordinary(text);
+	if tokens.len() <= limit {
+		return text;
+	}
+	let mut end: usize = bpe
+		._decode_native_and_split(tokens[..limit].to_vec())
+		.map(|bytes| bytes.len())
+		.sum();
+	loop {
+		end = text.floor_char_boundary(end);
+		let head = &text[..end];
+		// Re-encoding a cut can merge differently at the edge; shrink until it
+		// fits.
+		if end == 0 || count(bpe, head) <= limit {
+			return head;
+		}
+		end -= 1;
+	}
+}
+
+/// Longest suffix of `text` that encodes to at most `limit` tokens, cut on a
+/// character boundary.
+pub fn suffix<'a>(bpe: &CoreBPE, text: &'a str, limit: usize) -> &'a str {
+	let tokens = bpe.encode_ordinary(text);
+	if tokens.len() <= limit {
+		return text;
+	}
+	let mut start: usize = bpe
+		._decode_native_and_split(tokens[..tokens.len() - limit].to_vec())
+		.map(|bytes| bytes.len())
+		.sum();
+	loop {
+		start = text.ceil_char_boundary(start);
+		let tail = &text[start..];
+		if start == text.len() || count(bpe, tail) <= limit {
+			return tail;
+		}
+		start += 1;
+	}
+}
+
+/// Options for `truncateMiddle`.
+#[napi(object)]
+pub struct TruncateMiddleOptions {
+	/// Token budget for the result, including the elision marker.
+	#[napi(js_name = "maxTokens")]
+	pub max_tokens: u32,
+	/// Model id or encoding name, as for `countTokens`.
+	pub model:      String,
+	/// Keep at most this many lines from the start (default: as many as fit
+	/// in half the budget, or more when the tail leaves room).
+	#[napi(js_name = "headLines")]
+	pub head_lines: Option<u32>,
+	/// Keep at most this many lines from the end (default: as many as fit).
+	#[napi(js_name = "tailLines")]
+	pub tail_lines: Option<u32>,
+}
+
+/// Result of `truncateMiddle`.
+#[napi(object)]
+pub struct TruncateMiddleResult {
+	/// Head and tail of the input around an elision marker line, or the input
+	/// unchanged when it fits.
+	pub text:            String,
+	/// Whether anything was elided.
+	pub truncated:       bool,
+	/// Tokens in `text`.
+	pub tokens:          u32,
+	/// Tokens in the input.
+	#[napi(js_name = "originalTokens")]
+	pub original_tokens: u32,
+	/// Input lines removed entirely or in part.
+	#[napi(js_name = "elidedLines")]
+	pub elided_lines:    u32,
+}
+
+fn to_u32(count: usize) -> u32 {
+	u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+fn elision_marker(lines: usize, tokens: usize) -> String {
+	let plural = |count: usize| if count == 1 { "" } else { "s" };
+	format!("[... {lines} line{}, {tokens} token{} elided ...]\n", plural(lines), plural(tokens))
+}
+
+/// Take further lines from `lines` while the running total stays within
+/// `budget` tokens and at most `cap` lines are taken.
+fn take_lines<'a>(
+	bpe: &CoreBPE,
+	lines: impl Iterator<Item = &'a str>,
+	cap: usize,
+	budget: usize,
+	taken: &mut usize,
+	tokens: &mut usize,
+) {
+	for line in lines.take(cap.saturating_sub(*taken)) {
+		let cost = count(bpe, line);
+		if *tokens + cost > budget {
+			break;
+		}
+		*tokens += cost;
+		*taken += 1;
+	}
+}
+
+/// Keep whole lines from both ends within `budget` tokens: the head gets half
+/// first, the tail the rest, then the head whatever the tail left. A side that
+/// cannot fit a single line keeps a token-exact part of its outermost line.
+/// Returns the text and the number of elided lines.
+fn elide_middle(
+	bpe: &CoreBPE,
+	lines: &[&str],
+	total: usize,
+	budget: usize,
+	head_cap: usize,
+	tail_cap: usize,
+) -> (String, usize) {
+	let (mut head, mut head_tokens, mut tail, mut tail_tokens) = (0, 0, 0, 0);
+	take_lines(bpe, lines.iter().copied(), head_cap, budget / 2, &mut head, &mut head_tokens);
+	take_lines(
+		bpe,
+		lines[head..].iter().rev().copied(),
+		tail_cap,
+		budget - head_tokens,
+		&mut tail,
+		&mut tail_tokens,
+	);
+	take_lines(
+		bpe,
+		lines[head..lines.len() - tail].iter().copied(),
+		head_cap,
+		budget - tail_tokens,
+		&mut head,
+		&mut head_tokens,
+	);
+
+	let mut head_part = "";
+	if head == 0 && head_cap > 0 {
+		let share = if tail == 0 && tail_cap > 0 { 2 } else { 1 };
+		head_part = prefix(bpe, lines[0], (budget - tail_tokens) / share);
+		head_tokens = count(bpe, head_part);
+	}
+	let mut tail_part = "";
+	if tail == 0 && tail_cap > 0 {
+		tail_part = suffix(bpe, lines[lines.len() - 1], budget - head_tokens);
+		tail_tokens = count(bpe, tail_part);
+	}
+
+	let mut out = lines[..head].concat();
+	out.push_str(head_part);
+	if !out.is_empty() && !out.ends_with('\n') {
+		out.push('\n');
+	}
+	let elided = lines.len() - head - tail;
+	out.push_str(&elision_marker(elided, total.saturating_sub(head_tokens + tail_tokens)));
+	out.push_str(tail_part);
+	out.push_str(&lines[lines.len() - tail..].concat());
+	(out, elided)
+}
+
+fn truncate_middle_sync(text: String, options: &TruncateMiddleOptions) -> TruncateMiddleResult {
+	let bpe = encoding(&options.model);
+	let max = options.max_tokens as usize;
+	let total = count(bpe, &text);
+	if total <= max {
+		return TruncateMiddleResult {
+			text,
+			truncated: false,
+			tokens: to_u32(total),
+			original_tokens: to_u32(total),
+			elided_lines: 0,
+		};
+	}
+
+	let lines: Vec<&str> = text.split_inclusive('\n').collect();
+	let head_cap = options
+		.head_lines
+		.map_or(usize::MAX, |lines| lines as usize);
+	let tail_cap = options
+		.tail_lines
+		.map_or(usize::MAX, |lines| lines as usize);
+	// The marker showing the largest possible counts bounds its own cost; the
+	// extra token covers a newline inserted after a partial head line.
+	let reserve = count(bpe, &elision_marker(lines.len(), total)) + 1;
+	let mut budget = max.saturating_sub(reserve);
+	let (out, tokens, elided) = loop {
+		if max < reserve {
+			// No room for the marker: plain truncation.
+			let head = prefix(bpe, &text, max).to_string();
+			let tokens = count(bpe, &head);
+			break (head, tokens, lines.len());
+		}
+		let (out, elided) = elide_middle(bpe, &lines, total, budget, head_cap, tail_cap);
+		let tokens = count(bpe, &out);
+		// Lines tokenized on their own can merge differently once joined.
+		if tokens <= max || budget == 0 {
+			break (out, tokens, elided);
+		}
+		budget = budget.saturating_sub(tokens - max);
+	};
+	TruncateMiddleResult {
+		text:            out,
+		truncated:       true,
+		tokens:          to_u32(tokens),
+		original_tokens: to_u32(total),
+		elided_lines:    to_u32(elided),
+	}
+}
+
+/// Count the tokens `model` would see for `text`.
+#[napi(js_name = "countTokens")]
+pub fn count_tokens(text: String, model: String) -> task::Async<u32> {
+	task::blocking("count_tokens", (), move |_| Ok(to_u32(count(encoding(&model), &text))))
+}
+
+/// Keep the longest prefix of `text` that fits in `limit` tokens for
+/// `model`. Text that already fits is returned unchanged.
+#[napi(js_name = "truncateToTokens")]
+pub fn truncate_to_tokens(text: String, model: String, limit: u32) -> task::Async<String> {
+	task::blocking("truncate_to_tokens", (), move |_| {
+		let head = prefix(encoding(&model), &text, limit as usize);
+		Ok(if head.len() == text.len() {
+			text
+		} else {
+			head.to_string()
+		})
+	})
+}
+
+/// Fit `text` into `maxTokens` for `model` by keeping its first and last
+/// lines around a `[... N lines, M tokens elided ...]` marker line.
+#[napi(js_name = "truncateMiddle")]
+pub fn truncate_middle(
+	text: String,
+	options: TruncateMiddleOptions,
+) -> task::Async<TruncateMiddleResult> {
+	task::blocking("truncate_middle", (), move |_| Ok(truncate_middle_sync(text, &options)))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fmt::Write as _;
+
+	use super::*;
+
+	const MODEL: &str = "gpt-4o";
+
+	fn options(
+		max_tokens: u32,
+		head_lines: Option<u32>,
+		tail_lines: Option<u32>,
+	) -> TruncateMiddleOptions {
+		TruncateMiddleOptions { max_tokens, model: MODEL.to_string(), head_lines, tail_lines }
+	}
+
+	fn numbered(lines: usize) -> String {
+		(1..=lines).fold(String::new(), |mut text, n| {
+			let _ = writeln!(text, "line {n}");
+			text
+		})
+	}
+
+	#[test]
+	fn test_encoding_names() {
+		assert!(std::ptr::eq(encoding("gpt-4o"), o200k_base_singleton()));
+		assert!(std::ptr::eq(encoding("openai/GPT-5-mini"), o200k_base_singleton()));
+		assert!(std::ptr::eq(encoding("o3"), o200k_base_singleton()));
+		assert!(std::ptr::eq(encoding("claude-sonnet-4"), cl100k_base_singleton()));
+		assert!(std::ptr::eq(encoding("r50k_base"), r50k_base_singleton()));
+	}
+
+	#[test]
+	fn test_prefix_and_suffix_fit() {
+		let bpe = encoding(MODEL);
+		let text = "héllo wörld, ".repeat(50);
+		for limit in [0, 1, 7, 40] {
+			let head = prefix(bpe, &text, limit);
+			assert!(count(bpe, head) <= limit);
+			assert!(text.starts_with(head));
+			let tail = suffix(bpe, &text, limit);
+			assert!(count(bpe, tail) <= limit);
+			assert!(text.ends_with(tail));
+		}
+		assert_eq!(prefix(bpe, "short", 10), "short");
+	}
+
+	#[test]
+	fn test_fitting_text_is_unchanged() {
+		let text = numbered(5);
+		let result = truncate_middle_sync(text.clone(), &options(1000, None, None));
+		assert!(!result.truncated);
+		assert_eq!(result.text, text);
+		assert_eq!(result.tokens, result.original_tokens);
+	}
+
+	#[test]
+	fn test_keeps_both_ends_within_budget() {
+		let result = truncate_middle_sync(numbered(200), &options(60, None, None));
+		assert!(result.truncated);
+		assert!(result.tokens <= 60);
+		assert_eq!(result.tokens as usize, count(encoding(MODEL), &result.text));
+		assert!(result.text.starts_with("line 1\n"));
+		assert!(result.text.ends_with("line 200\n"));
+		let marker = format!("[... {} lines, ", result.elided_lines);
+		assert!(result.text.contains(&marker));
+		assert_eq!(result.text.lines().count() as u32, 200 - result.elided_lines + 1);
+	}
+
+	#[test]
+	fn test_line_caps() {
+		let result = truncate_middle_sync(numbered(200), &options(200, Some(2), Some(3)));
+		assert_eq!(result.elided_lines, 195);
+		assert!(result.text.starts_with("line 1\nline 2\n[... 195 lines, "));
+		assert!(
+			result
+				.text
+				.ends_with("elided ...]\nline 198\nline 199\nline 200\n")
+		);
+
+		let result = truncate_middle_sync(numbered(200), &options(100, Some(0), None));
+		assert!(result.text.starts_with("[... "));
+		assert!(result.text.ends_with("line 200\n"));
+	}
+
+	#[test]
+	fn test_long_line_is_cut_token_exact() {
+		let text = "word ".repeat(500);
+		let result = truncate_middle_sync(text.clone(), &options(50, None, None));
+		assert!(result.truncated);
+		assert!(result.tokens <= 50);
+		assert_eq!(result.elided_lines, 1);
+		let (head, rest) = result.text.split_once('\n').unwrap();
+		assert!(text.starts_with(head) && !head.is_empty());
+		let tail = rest.split_once('\n').unwrap().1;
+		assert!(text.ends_with(tail) && !tail.is_empty());
+	}
+
+	#[test]
+	fn test_budget_too_small_for_marker() {
+		let result = truncate_middle_sync(numbered(100), &options(3, None, None));
+		assert!(result.truncated);
+		assert!(result.tokens <= 3);
+		assert!(result.text.starts_with("line"));
+	}
+}
@@ -0,0 +1,178 @@
+//! Installed toolchain discovery.
+//!
+//! # Overview
+//! `detectToolchains` finds common development tools on `PATH` (honoring
+//! `PATHEXT` on Windows) and runs each with its version flag, concurrently
+//! and under a timeout, so one hung tool cannot stall the report. A tool that
+//! is missing has no `path`; one found but failing or timing out has an
+//! `error` instead of a `version`.
+//!
+//! Known tools, checked by default: node, python, rustc, cargo, go, java,
+//! docker, kubectl, gcc, clang, make and cmake. Other names are run with
+//! `--version`.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const tools = await native.detectToolchains({ tools: ["node", "bun"] });
+//! // JS: tools[0] // { name: "node", path: "/usr/bin/node", version: "22.1.0", output: "v22.1.0" }
+//! ```
+
+use std::{path::Path, process::Stdio, sync::LazyLock, time::Duration};
+
+use napi::{
+	bindgen_prelude::*,
+	tokio::{self, process::Command, task::JoinSet},
+};
+use napi_derive::napi;
+use regex::Regex;
+
+use crate::{ps, task, which};
+
+const DEFAULT_TIMEOUT_MS: u32 = 5_000;
+
+/// `SIGKILL`; the signal is ignored on Windows.
+const KILL_SIGNAL: i32 = 9;
+
+/// Known tools: name, executables to look for in order, and version
+/// arguments.
+const TOOLS: &[(&str, &[&str], &[&str])] = &[
+	("node", &["node"], &["--version"]),
+	("python", &["python3", "python"], &["--version"]),
+	("rustc", &["rustc"], &["--version"]),
+	("cargo", &["cargo"], &["--version"]),
+	("go", &["go"], &["version"]),
+	// `-version` also works before Java 9, on stderr.
+	("java", &["java"], &["-version"]),
+	("docker", &["docker"], &["--version"]),
+	("kubectl", &["kubectl"], &["version", "--client"]),
+	("gcc", &["gcc"], &["--version"]),
+	("clang", &["clang"], &["--version"]),
+	("make", &["make"], &["--version"]),
+	("cmake", &["cmake"], &["--version"]),
+];
+
+/// First dotted version number in a tool's output.
+static VERSION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+(?:\.\d+)+").unwrap());
+
+/// Options for `detectToolchains`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ToolchainOptions {
+	/// Tools to check (default: all known tools).
+	pub tools:      Option<Vec<String>>,
+	/// Timeout per tool in milliseconds (default: 5000).
+	#[napi(js_name = "timeoutMs")]
+	pub timeout_ms: Option<u32>,
+}
+
+/// A tool checked by `detectToolchains`.
+#[napi(object)]
+pub struct Toolchain {
+	/// Tool name as requested.
+	pub name:    String,
+	/// Executable found on `PATH`; absent when the tool is not installed.
+	pub path:    Option<String>,
+	/// Version number (e.g. "22.1.0").
+	pub version: Option<String>,
+	/// First line of the version output (e.g. "Docker version 24.0.7, build
+	/// afdd53b").
+	pub output:  Option<String>,
+	/// Why the version could not be read, when the tool was found.
+	pub error:   Option<String>,
+}
+
+/// Executables and version arguments for `name`.
+fn spec(name: &str) -> (Vec<&str>, &'static [&'static str]) {
+	TOOLS.iter().find(|(tool, ..)| *tool == name).map_or_else(
+		|| (vec![name], &["--version"][..]),
+		|(_, candidates, args)| (candidates.to_vec(), *args),
+	)
+}
+
+/// Run `program` with `args` and return the first non-empty output line, or
+/// why there is none.
+async fn version_output(
+	program: &Path,
+	args: &[&str],
+	timeout: Duration,
+) -> std::result::Result<String, String> {
+	let mut cmd = Command::new(program);
+	cmd.args(args)
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.kill_on_drop(true);
+	#[cfg(unix)]
+	cmd.process_group(0);
+	let child = cmd.spawn().map_err(|err| format!("Failed to run: {err}"))?;
+	let pid = child.id();
+	let Ok(output) = tokio::time::timeout(timeout, child.wait_with_output()).await else {
+		// Wrapper scripts leave their children behind otherwise.
+		if let Some(pid) = pid {
+			ps::kill_tree(pid as i32, KILL_SIGNAL);
+			#[cfg(unix)]
+			ps::kill_process_group(pid as i32, KILL_SIGNAL);
+		}
+		return Err(format!("Timed out after {}ms", timeout.as_millis()));
+	};
+	let output = output.map_err(|err| format!("Failed to run: {err}"))?;
+	// Some tools (java, old pythons) print their version on stderr.
+	let text = [&output.stdout, &output.stderr]
+		.into_iter()
+		.map(|bytes| String::from_utf8_lossy(bytes))
+		.find_map(|text| {
+			text
+				.lines()
+				.map(str::trim)
+				.find(|line| !line.is_empty())
+				.map(str::to_string)
+		});
+	let status = output
+		.status
+		.code()
+		.map_or_else(|| String::from("a signal"), |code| format!("code {code}"));
+	match text {
+		Some(line) if output.status.success() => Ok(line),
+		Some(line) => Err(format!("Exited with {status}: {line}")),
+		None => Err(format!("Exited with {status} and no output")),
+	}
+}
+
+async fn detect(name: String, timeout: Duration) -> Toolchain {
+	let (candidates, args) = spec(&name);
+	let Some(path) = candidates.into_iter().find_map(which::find) else {
+		return Toolchain { name, path: None, version: None, output: None, error: None };
+	};
+	let (version, output, error) = match version_output(&path, args, timeout).await {
+		Ok(line) => (VERSION.find(&line).map(|m| m.as_str().to_string()), Some(line), None),
+		Err(err) => (None, None, Some(err)),
+	};
+	Toolchain { name, path: Some(path.to_string_lossy().into_owned()), version, output, error }
+}
+
+/// Find development tools on `PATH` and read their versions.
+///
+/// Tools are reported in the order requested.
+#[napi(js_name = "detectToolchains")]
+pub fn detect_toolchains(
+	env: &Env,
+	options: Option<ToolchainOptions>,
+) -> Result<PromiseRaw<'_, Vec<Toolchain>>> {
+	let options = options.unwrap_or_default();
+	let names = options
+		.tools
+		.unwrap_or_else(|| TOOLS.iter().map(|(name, ..)| (*name).to_string()).collect());
+	let timeout = Duration::from_millis(u64::from(options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)));
+	task::future(env, "detect_toolchains", async move {
+		let mut tasks = JoinSet::new();
+		for (index, name) in names.into_iter().enumerate() {
+			tasks.spawn(async move { (index, detect(name, timeout).await) });
+		}
+		let mut tools = Vec::new();
+		while let Some(result) = tasks.join_next().await {
+			tools.push(result.map_err(|err| Error::from_reason(format!("Tool check failed: {err}")))?);
+		}
+		tools.sort_by_key(|(index, _)| *index);
+		Ok(tools.into_iter().map(|(_, tool)| tool).collect())
+	})
+}
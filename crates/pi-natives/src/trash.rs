@@ -0,0 +1,551 @@
+//! Recoverable deletes through the platform trash.
+//!
+//! # Overview
+//! `trashPath` moves a file or directory to the trash of the platform: the
+//! freedesktop.org trash on Linux and other Unixes (the home trash, or
+//! `.Trash-<uid>` at the top of another filesystem), `NSFileManager` on
+//! macOS, and the Recycle Bin on Windows. Each item is recorded in the
+//! session store with the path it came from, so `restoreFromTrash` can move
+//! it back by id, also after a restart.
+//!
+//! Trashed items stay visible to the desktop, which can restore or empty
+//! them too (they keep their `.trashinfo` file on Linux and `$I` record on
+//! Windows); restoring an item that is no longer in the trash fails.
+//!
+//! # Example
+//! ```ignore
+//! // JS: const { id } = await native.trashPath("notes/draft.md");
+//! // JS: await native.restoreFromTrash(id);
+//! ```
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::{store, task, workspace};
+
+/// Options for `trashPath`.
+#[napi(object)]
+#[derive(Default)]
+pub struct TrashOptions {
+	/// Database file recording the item (default: the session store, as in
+	/// `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path:                 Option<String>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Options for `restoreFromTrash`.
+#[napi(object)]
+#[derive(Default)]
+pub struct RestoreFromTrashOptions {
+	/// Database file the item was recorded in (default: the session store, as
+	/// in `SessionStoreOptions`).
+	#[napi(js_name = "dbPath")]
+	pub db_path: Option<String>,
+}
+
+/// An item moved to the trash.
+#[napi(object)]
+pub struct TrashedItem {
+	/// Id to pass to `restoreFromTrash`.
+	pub id:            i64,
+	/// Where the item was, and is restored to.
+	#[napi(js_name = "originalPath")]
+	pub original_path: String,
+	/// Where the item is in the trash.
+	#[napi(js_name = "trashedPath")]
+	pub trashed_path:  String,
+	/// When the item was trashed, in milliseconds since the Unix epoch.
+	#[napi(js_name = "trashedAt")]
+	pub trashed_at:    f64,
+	/// When the item was restored, in milliseconds since the Unix epoch.
+	#[napi(js_name = "restoredAt")]
+	pub restored_at:   Option<f64>,
+}
+
+/// Where the platform put a trashed item.
+struct Trashed {
+	path: PathBuf,
+	/// Record of the item kept by the platform, removed on restore.
+	info: Option<PathBuf>,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+	//! freedesktop.org Trash specification 1.0.
+
+	use std::{
+		env,
+		ffi::OsString,
+		fmt::Write as _,
+		fs::{self, DirBuilder, OpenOptions},
+		io::{self, Write},
+		os::unix::{
+			ffi::OsStrExt,
+			fs::{DirBuilderExt, MetadataExt},
+		},
+		path::{Path, PathBuf},
+	};
+
+	use napi::{Error, Result};
+
+	use super::Trashed;
+
+	/// `$XDG_DATA_HOME/Trash`.
+	fn home_trash() -> Result<PathBuf> {
+		let data_home = env::var_os("XDG_DATA_HOME")
+			.filter(|dir| !dir.is_empty())
+			.map(PathBuf::from)
+			.or_else(|| env::home_dir().map(|home| home.join(".local/share")))
+			.ok_or_else(|| Error::from_reason("Failed to locate the home directory"))?;
+		Ok(data_home.join("Trash"))
+	}
+
+	/// Topmost directory on the same filesystem as `path`.
+	fn mount_top(path: &Path, device: u64) -> PathBuf {
+		let mut top = path;
+		while let Some(parent) = top.parent() {
+			if fs::metadata(parent).map_or(true, |meta| meta.dev() != device) {
+				break;
+			}
+			top = parent;
+		}
+		top.to_path_buf()
+	}
+
+	/// Percent-encode `path` for the `Path` key, keeping `/`.
+	fn encode(path: &Path) -> String {
+		let mut out = String::new();
+		for &byte in path.as_os_str().as_bytes() {
+			if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+				out.push(char::from(byte));
+			} else {
+				let _ = write!(out, "%{byte:02X}");
+			}
+		}
+		out
+	}
+
+	/// Local time as `YYYY-MM-DDThh:mm:ss`.
+	fn deletion_date() -> String {
+		// SAFETY: passing a null pointer only returns the current time.
+		let now = unsafe { libc::time(std::ptr::null_mut()) };
+		// SAFETY: `tm` is plain data; all-zero is a valid value.
+		let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+		// SAFETY: both pointers are valid for the duration of the call.
+		unsafe { libc::localtime_r(&raw const now, &raw mut tm) };
+		format!(
+			"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+			tm.tm_year + 1900,
+			tm.tm_mon + 1,
+			tm.tm_mday,
+			tm.tm_hour,
+			tm.tm_min,
+			tm.tm_sec
+		)
+	}
+
+	fn io_error(action: &str, path: &Path, err: &io::Error) -> Error {
+		Error::from_reason(format!("Failed to {action} {}: {err}", path.display()))
+	}
+
+	pub fn trash(path: &Path) -> Result<Trashed> {
+		let device = fs::symlink_metadata(path)
+			.map_err(|err| io_error("stat", path, &err))?
+			.dev();
+		let home_trash = home_trash()?;
+		let mut builder = DirBuilder::new();
+		builder.recursive(true).mode(0o700);
+		builder
+			.create(&home_trash)
+			.map_err(|err| io_error("create", &home_trash, &err))?;
+		let home_device = fs::metadata(&home_trash)
+			.map_err(|err| io_error("stat", &home_trash, &err))?
+			.dev();
+		let (trash, recorded) = if home_device == device {
+			(home_trash, path.to_path_buf())
+		} else {
+			let top = mount_top(path, device);
+			// SAFETY: getuid cannot fail.
+			let uid = unsafe { libc::getuid() };
+			let relative = path.strip_prefix(&top).unwrap_or(path).to_path_buf();
+			(top.join(format!(".Trash-{uid}")), relative)
+		};
+		let (files, info) = (trash.join("files"), trash.join("info"));
+		for dir in [&files, &info] {
+			builder
+				.create(dir)
+				.map_err(|err| io_error("create", dir, &err))?;
+		}
+
+		let name = path
+			.file_name()
+			.ok_or_else(|| Error::from_reason(format!("Cannot trash {}", path.display())))?;
+		let contents =
+			format!("[Trash Info]\nPath={}\nDeletionDate={}\n", encode(&recorded), deletion_date());
+		for attempt in 1u32.. {
+			let mut candidate = OsString::from(name);
+			if attempt > 1 {
+				candidate.push(format!(".{attempt}"));
+			}
+			let target = files.join(&candidate);
+			candidate.push(".trashinfo");
+			let info_path = info.join(&candidate);
+			if fs::symlink_metadata(&target).is_ok() {
+				continue;
+			}
+			// Creating the info file claims the name.
+			let mut file = match OpenOptions::new()
+				.write(true)
+				.create_new(true)
+				.open(&info_path)
+			{
+				Ok(file) => file,
+				Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+				Err(err) => return Err(io_error("create", &info_path, &err)),
+			};
+			let moved = file
+				.write_all(contents.as_bytes())
+				.and_then(|()| fs::rename(path, &target));
+			if let Err(err) = moved {
+				let _ = fs::remove_file(&info_path);
+				return Err(io_error("trash", path, &err));
+			}
+			return Ok(Trashed { path: target, info: Some(info_path) });
+		}
+		unreachable!("name attempts are unbounded")
+	}
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+	//! `-[NSFileManager trashItemAtURL:resultingItemURL:error:]`.
+
+	use std::{
+		ffi::{CStr, CString, c_char},
+		os::unix::ffi::OsStrExt,
+		path::{Path, PathBuf},
+	};
+
+	use napi::{Error, Result};
+	use objc2::{class, msg_send, rc::autoreleasepool, runtime::AnyObject};
+
+	use super::Trashed;
+
+	#[link(name = "Foundation", kind = "framework")]
+	unsafe extern "C" {}
+
+	/// UTF-8 contents of an `NSString`.
+	///
+	/// # Safety
+	/// `string` must be a valid `NSString` or null.
+	unsafe fn to_string(string: *mut AnyObject) -> Option<String> {
+		if string.is_null() {
+			return None;
+		}
+		// SAFETY: `string` is a valid NSString per the contract.
+		let utf8: *const c_char = unsafe { msg_send![string, UTF8String] };
+		// SAFETY: UTF8String returns a NUL-terminated buffer or null.
+		(!utf8.is_null()).then(|| {
+			unsafe { CStr::from_ptr(utf8) }
+				.to_string_lossy()
+				.into_owned()
+		})
+	}
+
+	pub fn trash(path: &Path) -> Result<Trashed> {
+		let c_path = CString::new(path.as_os_str().as_bytes())
+			.map_err(|_| Error::from_reason(format!("Invalid path {}", path.display())))?;
+		autoreleasepool(|_| {
+			// SAFETY: messages are sent to Foundation classes and the objects
+			// they return, with arguments of the declared types; returned
+			// objects are autoreleased and used only inside the pool.
+			unsafe {
+				let string: *mut AnyObject =
+					msg_send![class!(NSString), stringWithUTF8String: c_path.as_ptr()];
+				let url: *mut AnyObject = msg_send![class!(NSURL), fileURLWithPath: string];
+				let manager: *mut AnyObject = msg_send![class!(NSFileManager), defaultManager];
+				let mut resulting: *mut AnyObject = std::ptr::null_mut();
+				let mut error: *mut AnyObject = std::ptr::null_mut();
+				let trashed: bool = msg_send![
+					manager,
+					trashItemAtURL: url,
+					resultingItemURL: &raw mut resulting,
+					error: &raw mut error
+				];
+				if !trashed {
+					let reason = if error.is_null() {
+						None
+					} else {
+						to_string(msg_send![error, localizedDescription])
+					};
+					return Err(Error::from_reason(format!(
+						"Failed to trash {}: {}",
+						path.display(),
+						reason.as_deref().unwrap_or("unknown error")
+					)));
+				}
+				let trashed_path = if resulting.is_null() {
+					None
+				} else {
+					to_string(msg_send![resulting, path])
+				};
+				let trashed_path = trashed_path.ok_or_else(|| {
+					Error::from_reason(format!("Trashed {} to an unknown location", path.display()))
+				})?;
+				Ok(Trashed { path: PathBuf::from(trashed_path), info: None })
+			}
+		})
+	}
+}
+
+#[cfg(windows)]
+mod platform {
+	//! `SHFileOperationW` with `FOF_ALLOWUNDO`, then the `$I` record the
+	//! Recycle Bin wrote for the item to find where it went.
+
+	use std::{
+		ffi::c_void,
+		fs,
+		os::windows::ffi::OsStrExt,
+		path::{Path, PathBuf},
+	};
+
+	use napi::{Error, Result};
+
+	use super::Trashed;
+
+	const FO_DELETE: u32 = 3;
+	const FOF_SILENT: u16 = 0x0004;
+	const FOF_NOCONFIRMATION: u16 = 0x0010;
+	const FOF_ALLOWUNDO: u16 = 0x0040;
+	const FOF_NOERRORUI: u16 = 0x0400;
+
+	#[repr(C)]
+	#[allow(non_snake_case, reason = "mirrors the Win32 struct")]
+	struct SHFILEOPSTRUCTW {
+		hwnd:                  *mut c_void,
+		wFunc:                 u32,
+		pFrom:                 *const u16,
+		pTo:                   *const u16,
+		fFlags:                u16,
+		fAnyOperationsAborted: i32,
+		hNameMappings:         *mut c_void,
+		lpszProgressTitle:     *const u16,
+	}
+
+	#[link(name = "shell32")]
+	unsafe extern "system" {
+		fn SHFileOperationW(lpFileOp: *mut SHFILEOPSTRUCTW) -> i32;
+	}
+
+	/// Original path and deletion time in a `$I` record (version 1 or 2).
+	fn parse_record(record: &[u8]) -> Option<(String, i64)> {
+		let version = i64::from_le_bytes(record.get(0..8)?.try_into().ok()?);
+		let deleted = i64::from_le_bytes(record.get(16..24)?.try_into().ok()?);
+		let name = match version {
+			1 => record.get(24..24 + 520)?,
+			2 => {
+				let len = u32::from_le_bytes(record.get(24..28)?.try_into().ok()?) as usize;
+				record.get(28..28 + len * 2)?
+			},
+			_ => return None,
+		};
+		let units: Vec<u16> = name
+			.chunks_exact(2)
+			.map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+			.take_while(|&unit| unit != 0)
+			.collect();
+		Some((String::from_utf16_lossy(&units), deleted))
+	}
+
+	/// The newest `$R` item in the Recycle Bin of `path`'s drive that came
+	/// from `path`, with its `$I` record.
+	fn find_recycled(path: &Path) -> Option<(PathBuf, PathBuf)> {
+		let original = path.to_string_lossy();
+		let bin = path.ancestors().last()?.join("$Recycle.Bin");
+		let mut newest: Option<(i64, PathBuf, PathBuf)> = None;
+		// Only the current user's folder is readable.
+		for folder in fs::read_dir(bin).ok()?.flatten() {
+			let Ok(entries) = fs::read_dir(folder.path()) else {
+				continue;
+			};
+			for entry in entries.flatten() {
+				let name = entry.file_name().to_string_lossy().into_owned();
+				let Some(suffix) = name.strip_prefix("$I") else {
+					continue;
+				};
+				let Some((from, deleted)) = fs::read(entry.path())
+					.ok()
+					.and_then(|record| parse_record(&record))
+				else {
+					continue;
+				};
+				if from.eq_ignore_ascii_case(&original)
+					&& newest.as_ref().is_none_or(|(time, ..)| deleted > *time)
+				{
+					let item = folder.path().join(format!("$R{suffix}"));
+					newest = Some((deleted, item, entry.path()));
+				}
+			}
+		}
+		newest.map(|(_, item, record)| (item, record))
+	}
+
+	pub fn trash(path: &Path) -> Result<Trashed> {
+		let from: Vec<u16> = path.as_os_str().encode_wide().chain([0, 0]).collect();
+		let mut operation = SHFILEOPSTRUCTW {
+			hwnd:                  std::ptr::null_mut(),
+			wFunc:                 FO_DELETE,
+			pFrom:                 from.as_ptr(),
+			pTo:                   std::ptr::null(),
+			fFlags:                FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_SILENT | FOF_NOERRORUI,
+			fAnyOperationsAborted: 0,
+			hNameMappings:         std::ptr::null_mut(),
+			lpszProgressTitle:     std::ptr::null(),
+		};
+		// SAFETY: `operation` is fully initialized and `from` is a
+		// double-NUL-terminated wide string that outlives the call.
+		let status = unsafe { SHFileOperationW(&raw mut operation) };
+		if status != 0 || operation.fAnyOperationsAborted != 0 {
+			return Err(Error::from_reason(format!(
+				"Failed to move {} to the Recycle Bin (error {status:#x})",
+				path.display()
+			)));
+		}
+		let (item, record) = find_recycled(path).ok_or_else(|| {
+			Error::from_reason(format!(
+				"Moved {} to the Recycle Bin but could not find it there",
+				path.display()
+			))
+		})?;
+		Ok(Trashed { path: item, info: Some(record) })
+	}
+}
+
+fn describe(
+	id: i64,
+	original_path: String,
+	trashed_path: String,
+	trashed_at: i64,
+	restored_at: Option<i64>,
+) -> TrashedItem {
+	TrashedItem {
+		id,
+		original_path,
+		trashed_path,
+		trashed_at: trashed_at as f64,
+		restored_at: restored_at.map(|at| at as f64),
+	}
+}
+
+fn trash_path_sync(path: &str, options: TrashOptions) -> Result<TrashedItem> {
+	workspace::check(path, options.allow_outside_workspace)?;
+	let original = workspace::resolve_entry(Path::new(path))
+		.ok_or_else(|| Error::from_reason(format!("Cannot trash {path}")))??;
+	fs::symlink_metadata(&original)
+		.map_err(|err| Error::from_reason(format!("Failed to stat {path}: {err}")))?;
+	let store = store::open_store(options.db_path)?;
+	let trashed = platform::trash(&original)?;
+
+	let original_path = original.to_string_lossy().into_owned();
+	let trashed_path = trashed.path.to_string_lossy().into_owned();
+	let info_path = trashed
+		.info
+		.as_ref()
+		.map(|info| info.to_string_lossy().into_owned());
+	let trashed_at = store::now_ms();
+	let conn = store.lock();
+	conn
+		.execute(
+			"INSERT INTO trash (original_path, trashed_path, info_path, trashed_at)
+			 VALUES (?1, ?2, ?3, ?4)",
+			params![original_path, trashed_path, info_path, trashed_at],
+		)
+		.map_err(store::sql_error)?;
+	Ok(describe(conn.last_insert_rowid(), original_path, trashed_path, trashed_at, None))
+}
+
+/// The recorded item `id`, with its `info_path`.
+fn load_item(
+	conn: &Connection,
+	id: i64,
+) -> rusqlite::Result<Option<(TrashedItem, Option<String>)>> {
+	conn
+		.query_row(
+			"SELECT original_path, trashed_path, info_path, trashed_at, restored_at
+			 FROM trash WHERE id = ?1",
+			params![id],
+			|row| Ok((describe(id, row.get(0)?, row.get(1)?, row.get(3)?, row.get(4)?), row.get(2)?)),
+		)
+		.optional()
+}
+
+fn restore_sync(id: i64, options: RestoreFromTrashOptions) -> Result<TrashedItem> {
+	let store = store::open_store(options.db_path)?;
+	let conn = store.lock();
+	let (mut item, info_path) = load_item(&conn, id)
+		.map_err(store::sql_error)?
+		.ok_or_else(|| Error::from_reason(format!("No trashed item with id {id}")))?;
+	if item.restored_at.is_some() {
+		return Err(Error::from_reason(format!("Trashed item {id} was already restored")));
+	}
+	let original = Path::new(&item.original_path);
+	let trashed = Path::new(&item.trashed_path);
+	if fs::symlink_metadata(trashed).is_err() {
+		return Err(Error::from_reason(format!("{} is no longer in the trash", item.original_path)));
+	}
+	if fs::symlink_metadata(original).is_ok() {
+		return Err(Error::from_reason(format!("{} already exists", item.original_path)));
+	}
+	if let Some(parent) = original.parent() {
+		fs::create_dir_all(parent).map_err(|err| {
+			Error::from_reason(format!("Failed to create {}: {err}", parent.display()))
+		})?;
+	}
+	fs::rename(trashed, original).map_err(|err| {
+		Error::from_reason(format!("Failed to restore {}: {err}", item.original_path))
+	})?;
+	if let Some(info_path) = info_path {
+		let _ = fs::remove_file(info_path);
+	}
+
+	let restored_at = store::now_ms();
+	conn
+		.execute("UPDATE trash SET restored_at = ?1 WHERE id = ?2", params![restored_at, id])
+		.map_err(store::sql_error)?;
+	item.restored_at = Some(restored_at as f64);
+	Ok(item)
+}
+
+/// Move `path` to the platform trash instead of deleting it, recording it so
+/// `restoreFromTrash` can bring it back.
+///
+/// # Errors
+/// Returns an error if the path is outside the workspace, does not exist, or
+/// cannot be moved to the trash.
+#[napi(js_name = "trashPath")]
+pub fn trash_path(path: String, options: Option<TrashOptions>) -> task::Async<TrashedItem> {
+	task::blocking("trash_path", (), move |_| trash_path_sync(&path, options.unwrap_or_default()))
+}
+
+/// Move an item trashed with `trashPath` back to where it was.
+///
+/// # Errors
+/// Returns an error for an unknown or already restored id, when the item is
+/// no longer in the trash, or when its original path is taken.
+#[napi(js_name = "restoreFromTrash")]
+pub fn restore_from_trash(
+	id: i64,
+	options: Option<RestoreFromTrashOptions>,
+) -> task::Async<TrashedItem> {
+	task::blocking("restore_from_trash", (), move |_| restore_sync(id, options.unwrap_or_default()))
+}
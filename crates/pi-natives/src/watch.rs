@@ -0,0 +1,323 @@
+//! Filesystem watching with debounced, coalesced change events.
+//!
+//! # Overview
+//! Each `watchPaths` call owns a [`notify`] watcher plus a debounce thread.
+//! Raw events are collected until the paths go quiet for `debounceMs`, then
+//! merged per path and delivered to the callback as one batch:
+//! - create then modify reports a single create
+//! - create then delete within a batch reports nothing
+//! - delete then create reports a modify
+//! - rename halves are paired into a single rename carrying the old path
+//!
+//! # Platform Implementation
+//! - **Linux**: inotify; renames are paired by the kernel cookie
+//! - **macOS**: `FSEvents`; ambiguous renames are resolved by checking whether
+//!   the path still exists
+//! - **Windows**: `ReadDirectoryChangesW`; consecutive rename halves are paired
+//!
+//! # Example
+//! ```ignore
+//! // JS: const id = native.watchPaths(["/repo/src"], { debounceMs: 50 }, (err, events) => {});
+//! // JS: native.unwatch(id);
+//! ```
+
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+	sync::{
+		LazyLock,
+		atomic::{AtomicU32, Ordering},
+		mpsc::{self, RecvTimeoutError},
+	},
+	thread,
+	time::{Duration, Instant},
+};
+
+use napi::{
+	bindgen_prelude::*,
+	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+};
+use napi_derive::napi;
+use notify::{
+	Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+	event::{ModifyKind, RenameMode},
+};
+use parking_lot::Mutex;
+
+use crate::workspace;
+
+const DEFAULT_DEBOUNCE_MS: u32 = 100;
+
+/// A busy tree never goes quiet; flush at least this many debounce windows
+/// after the first event of a batch.
+const MAX_BATCH_WINDOWS: u32 = 10;
+
+static WATCHERS: LazyLock<Mutex<HashMap<u32, RecommendedWatcher>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_WATCH_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Kind of filesystem change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[napi]
+pub enum WatchEventKind {
+	/// Path was created.
+	Create = 1,
+	/// Path contents or metadata changed.
+	Modify = 2,
+	/// Path was removed.
+	Delete = 3,
+	/// Path was renamed from `oldPath`.
+	Rename = 4,
+}
+
+/// A coalesced filesystem change.
+#[napi(object)]
+pub struct WatchEvent {
+	/// Kind of change.
+	pub kind:     WatchEventKind,
+	/// Affected path (the new path for renames).
+	pub path:     String,
+	/// Previous path, for renames.
+	#[napi(js_name = "oldPath")]
+	pub old_path: Option<String>,
+}
+
+/// Options for `watchPaths`.
+#[napi(object)]
+pub struct WatchOptions {
+	/// Watch directories recursively (default: true).
+	pub recursive:               Option<bool>,
+	/// Quiet period before a batch of events is delivered, in milliseconds
+	/// (default: 100).
+	#[napi(js_name = "debounceMs")]
+	pub debounce_ms:             Option<u32>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+}
+
+/// Pending change for one path within a batch.
+struct Pending {
+	kind:     WatchEventKind,
+	old_path: Option<PathBuf>,
+}
+
+/// Merges raw events into at most one change per path.
+#[derive(Default)]
+struct Coalescer {
+	pending:     HashMap<PathBuf, Pending>,
+	/// First half of a rename still waiting for its destination.
+	rename_from: Option<PathBuf>,
+}
+
+impl Coalescer {
+	fn push(&mut self, event: Event) {
+		let mut paths = event.paths.into_iter();
+		match event.kind {
+			EventKind::Create(_) => paths.for_each(|path| self.record(path, WatchEventKind::Create)),
+			EventKind::Remove(_) => paths.for_each(|path| self.record(path, WatchEventKind::Delete)),
+			EventKind::Modify(ModifyKind::Name(mode)) => match mode {
+				RenameMode::Both => {
+					if let (Some(from), Some(to)) = (paths.next(), paths.next()) {
+						self.rename(from, to);
+					}
+				},
+				RenameMode::From => {
+					if let Some(from) = paths.next() {
+						self.flush_rename_from();
+						self.rename_from = Some(from);
+					}
+				},
+				RenameMode::To => {
+					if let Some(to) = paths.next() {
+						match self.rename_from.take() {
+							Some(from) => self.rename(from, to),
+							None => self.record(to, WatchEventKind::Create),
+						}
+					}
+				},
+				RenameMode::Any | RenameMode::Other => paths.for_each(|path| {
+					let kind = if path.exists() {
+						WatchEventKind::Create
+					} else {
+						WatchEventKind::Delete
+					};
+					self.record(path, kind);
+				}),
+			},
+			EventKind::Modify(_) | EventKind::Any => {
+				paths.for_each(|path| self.record(path, WatchEventKind::Modify));
+			},
+			EventKind::Access(_) | EventKind::Other => {},
+		}
+	}
+
+	fn record(&mut self, path: PathBuf, kind: WatchEventKind) {
+		let Some(prev) = self.pending.remove(&path) else {
+			self.pending.insert(path, Pending { kind, old_path: None });
+			return;
+		};
+		let merged = match (prev.kind, kind) {
+			(WatchEventKind::Create, WatchEventKind::Delete) => return,
+			(WatchEventKind::Create, WatchEventKind::Modify) => prev,
+			(WatchEventKind::Delete, WatchEventKind::Create) => {
+				Pending { kind: WatchEventKind::Modify, old_path: None }
+			},
+			(WatchEventKind::Rename, WatchEventKind::Modify) => prev,
+			(WatchEventKind::Rename, WatchEventKind::Delete) => {
+				// The renamed file is gone, so report its original path as deleted.
+				if let Some(old_path) = prev.old_path {
+					self.record(old_path, WatchEventKind::Delete);
+				}
+				return;
+			},
+			_ => Pending { kind, old_path: None },
+		};
+		self.pending.insert(path, merged);
+	}
+
+	fn rename(&mut self, from: PathBuf, to: PathBuf) {
+		// inotify reports both the paired halves and a combined event.
+		if self.pending.get(&to).is_some_and(|prev| {
+			prev.kind == WatchEventKind::Rename && prev.old_path.as_deref() == Some(from.as_path())
+		}) {
+			return;
+		}
+		let pending = match self.pending.remove(&from) {
+			Some(Pending { kind: WatchEventKind::Create, .. }) => {
+				Pending { kind: WatchEventKind::Create, old_path: None }
+			},
+			Some(Pending { kind: WatchEventKind::Rename, old_path: Some(origin) }) => {
+				Pending { kind: WatchEventKind::Rename, old_path: Some(origin) }
+			},
+			_ => Pending { kind: WatchEventKind::Rename, old_path: Some(from) },
+		};
+		self.pending.insert(to, pending);
+	}
+
+	/// An unpaired rename source left the watched tree.
+	fn flush_rename_from(&mut self) {
+		if let Some(from) = self.rename_from.take() {
+			self.record(from, WatchEventKind::Delete);
+		}
+	}
+
+	fn drain(&mut self) -> Vec<WatchEvent> {
+		self.flush_rename_from();
+		let mut events: Vec<WatchEvent> = self
+			.pending
+			.drain()
+			.map(|(path, pending)| WatchEvent {
+				kind:     pending.kind,
+				path:     path_to_string(&path),
+				old_path: pending.old_path.as_deref().map(path_to_string),
+			})
+			.collect();
+		events.sort_by(|a, b| a.path.cmp(&b.path));
+		events
+	}
+}
+
+fn path_to_string(path: &Path) -> String {
+	path.to_string_lossy().into_owned()
+}
+
+/// Collect raw events into debounced batches until the watcher is dropped.
+fn run_debouncer(
+	rx: &mpsc::Receiver<notify::Result<Event>>,
+	debounce: Duration,
+	callback: &ThreadsafeFunction<Vec<WatchEvent>>,
+) {
+	let mut coalescer = Coalescer::default();
+	let max_batch = debounce * MAX_BATCH_WINDOWS;
+	while let Ok(first) = rx.recv() {
+		let started = Instant::now();
+		let mut next = Some(first);
+		while let Some(result) = next.take() {
+			match result {
+				Ok(event) => coalescer.push(event),
+				Err(err) => {
+					callback.call(
+						Err(Error::from_reason(format!("Watch error: {err}"))),
+						ThreadsafeFunctionCallMode::NonBlocking,
+					);
+				},
+			}
+			if started.elapsed() >= max_batch {
+				break;
+			}
+			match rx.recv_timeout(debounce) {
+				Ok(result) => next = Some(result),
+				Err(RecvTimeoutError::Timeout) => {},
+				// Unwatched: drop whatever is pending.
+				Err(RecvTimeoutError::Disconnected) => return,
+			}
+		}
+		let events = coalescer.drain();
+		if !events.is_empty() {
+			callback.call(Ok(events), ThreadsafeFunctionCallMode::NonBlocking);
+		}
+	}
+}
+
+/// Watch `paths` for changes, delivering debounced batches of events to
+/// `callback` until `unwatch` is called with the returned id.
+///
+/// # Errors
+/// Returns an error if the platform watcher cannot be created or any path
+/// cannot be watched (e.g. it does not exist).
+#[napi(js_name = "watchPaths")]
+pub fn watch_paths(
+	paths: Vec<String>,
+	options: Option<WatchOptions>,
+	#[napi(ts_arg_type = "(error: Error | null, events: WatchEvent[]) => void")]
+	callback: ThreadsafeFunction<Vec<WatchEvent>>,
+) -> Result<u32> {
+	let options = options.unwrap_or(WatchOptions {
+		recursive:               None,
+		debounce_ms:             None,
+		allow_outside_workspace: None,
+	});
+	for path in &paths {
+		workspace::check(path, options.allow_outside_workspace)?;
+	}
+	let mode = if options.recursive.unwrap_or(true) {
+		RecursiveMode::Recursive
+	} else {
+		RecursiveMode::NonRecursive
+	};
+	let debounce = Duration::from_millis(
+		options
+			.debounce_ms
+			.unwrap_or(DEFAULT_DEBOUNCE_MS)
+			.max(1)
+			.into(),
+	);
+
+	let (tx, rx) = mpsc::channel();
+	let mut watcher = notify::recommended_watcher(move |result| {
+		let _ = tx.send(result);
+	})
+	.map_err(|err| Error::from_reason(format!("Failed to create watcher: {err}")))?;
+	for path in &paths {
+		watcher
+			.watch(Path::new(path), mode)
+			.map_err(|err| Error::from_reason(format!("Failed to watch {path}: {err}")))?;
+	}
+
+	thread::Builder::new()
+		.name("pi-watch".to_string())
+		.spawn(move || run_debouncer(&rx, debounce, &callback))
+		.map_err(|err| Error::from_reason(format!("Failed to spawn watch thread: {err}")))?;
+
+	let id = NEXT_WATCH_ID.fetch_add(1, Ordering::Relaxed);
+	WATCHERS.lock().insert(id, watcher);
+	Ok(id)
+}
+
+/// Stop a watcher started by `watchPaths`. Pending events are discarded.
+/// Returns false if `id` is not an active watcher.
+#[napi]
+pub fn unwatch(id: u32) -> bool {
+	WATCHERS.lock().remove(&id).is_some()
+}
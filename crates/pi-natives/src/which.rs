@@ -0,0 +1,210 @@
+//! Command resolution without `which` or `where`.
+//!
+//! # Overview
+//! `resolveCommand` reports what a shell session would run for a command
+//! name: a shell keyword, a builtin of the session's shell, or an executable
+//! found on `PATH`. Lookups honor `PATHEXT` on Windows, resolve relative
+//! `PATH` entries and paths against `cwd`, and list every match, as
+//! `which -a` does.
+//!
+//! Executables that are version manager shims (asdf, mise, volta, pyenv,
+//! rbenv, nodenv, goenv, jenv, scoop) are flagged, since they pick the real
+//! program at run time, per directory.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.resolveCommand("node") // { kind: "file", path: "/home/u/.volta/bin/node", shim: "volta", ... }
+//! // JS: await native.resolveCommand("cd")   // { kind: "builtin", ... }
+//! ```
+
+use std::{
+	collections::HashMap,
+	env,
+	ffi::OsString,
+	fs,
+	path::{Component, Path, PathBuf},
+};
+
+use napi_derive::napi;
+
+use crate::{shell, task};
+
+/// Bash reserved words.
+const KEYWORDS: [&str; 22] = [
+	"!", "[[", "]]", "{", "}", "case", "coproc", "do", "done", "elif", "else", "esac", "fi", "for",
+	"function", "if", "in", "select", "then", "time", "until", "while",
+];
+
+/// Version managers whose `shims` directory is named after them.
+const SHIM_MANAGERS: [&str; 9] =
+	["asdf", "mise", "rtx", "pyenv", "rbenv", "nodenv", "goenv", "jenv", "scoop"];
+
+/// Options for `resolveCommand`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ResolveCommandOptions {
+	/// Directory relative paths resolve against (default: the current
+	/// directory).
+	pub cwd: Option<String>,
+	/// Environment to take `PATH` and `PATHEXT` from (default: this
+	/// process's).
+	pub env: Option<HashMap<String, String>>,
+}
+
+/// What a command name resolves to.
+#[napi(object)]
+pub struct ResolvedCommand {
+	/// Name as given.
+	pub name:      String,
+	/// What runs: a shell keyword, a builtin of the session's shell, an
+	/// executable file, or nothing.
+	#[napi(ts_type = "\"keyword\" | \"builtin\" | \"file\" | \"not-found\"")]
+	pub kind:      String,
+	/// First executable found; also set for builtins that have one.
+	pub path:      Option<String>,
+	/// Every executable found, in `PATH` order.
+	#[napi(js_name = "allPaths")]
+	pub all_paths: Vec<String>,
+	/// Version manager that `path` is a shim of (e.g. "asdf", "mise",
+	/// "volta").
+	pub shim:      Option<String>,
+}
+
+/// File names `name` may have on disk.
+fn file_names(name: &str, pathext: Option<&str>) -> Vec<String> {
+	if cfg!(windows) && Path::new(name).extension().is_none() {
+		return pathext
+			.unwrap_or(".COM;.EXE;.BAT;.CMD")
+			.split(';')
+			.filter(|ext| !ext.is_empty())
+			.map(|ext| format!("{name}{}", ext.to_ascii_lowercase()))
+			.collect();
+	}
+	vec![name.to_string()]
+}
+
+fn is_executable(path: &Path) -> bool {
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		path
+			.metadata()
+			.is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+	}
+	#[cfg(not(unix))]
+	path.is_file()
+}
+
+/// Every executable `name` resolves to: the path itself when it contains a
+/// separator, otherwise the matches in the directories of `path`.
+fn find_all(name: &str, path: Option<OsString>, pathext: Option<&str>, cwd: &Path) -> Vec<PathBuf> {
+	let names = file_names(name, pathext);
+	if name.contains('/') || (cfg!(windows) && name.contains('\\')) {
+		return names
+			.iter()
+			.map(|name| {
+				cwd.join(name)
+					.components()
+					.filter(|part| part != &Component::CurDir)
+					.collect::<PathBuf>()
+			})
+			.filter(|candidate| is_executable(candidate))
+			.take(1)
+			.collect();
+	}
+	let Some(path) = path else {
+		return Vec::new();
+	};
+	let mut found = Vec::new();
+	for dir in env::split_paths(&path).filter(|dir| !dir.as_os_str().is_empty()) {
+		let dir = cwd.join(dir);
+		for name in &names {
+			let candidate = dir.join(name);
+			if is_executable(&candidate) && !found.contains(&candidate) {
+				found.push(candidate);
+			}
+		}
+	}
+	found
+}
+
+/// First executable named `name` on this process's `PATH`.
+pub(crate) fn find(name: &str) -> Option<PathBuf> {
+	let cwd = env::current_dir().unwrap_or_default();
+	let pathext = env::var("PATHEXT").ok();
+	find_all(name, env::var_os("PATH"), pathext.as_deref(), &cwd)
+		.into_iter()
+		.next()
+}
+
+/// Version manager `path` is a shim of.
+fn shim_manager(path: &Path) -> Option<String> {
+	// volta and mise shims link to one dispatcher binary.
+	if let Ok(target) = fs::read_link(path) {
+		let stem = target.file_stem().and_then(|stem| stem.to_str());
+		match stem {
+			Some("volta-shim") => return Some(String::from("volta")),
+			Some("mise") => return Some(String::from("mise")),
+			_ => {},
+		}
+	}
+	let dir = path.parent()?;
+	let owner = dir.parent()?.file_name()?.to_str()?.trim_start_matches('.');
+	match dir.file_name()?.to_str()? {
+		"shims" => SHIM_MANAGERS.contains(&owner).then(|| owner.to_string()),
+		"bin" if owner == "volta" => Some(String::from("volta")),
+		_ => None,
+	}
+}
+
+fn resolve(name: String, options: ResolveCommandOptions) -> ResolvedCommand {
+	let cwd = options
+		.cwd
+		.map_or_else(|| env::current_dir().unwrap_or_default(), PathBuf::from);
+	let (path, pathext) = match options.env {
+		Some(vars) => {
+			// Windows variable names are case-insensitive.
+			let get = |key: &str| {
+				vars
+					.iter()
+					.find(|(name, _)| {
+						name.as_str() == key || (cfg!(windows) && name.eq_ignore_ascii_case(key))
+					})
+					.map(|(_, value)| value.clone())
+			};
+			(get("PATH").map(OsString::from), get("PATHEXT"))
+		},
+		None => (env::var_os("PATH"), env::var("PATHEXT").ok()),
+	};
+	let all = find_all(&name, path, pathext.as_deref(), &cwd);
+	let kind = if KEYWORDS.contains(&name.as_str()) {
+		"keyword"
+	} else if shell::is_builtin(&name) {
+		"builtin"
+	} else if all.is_empty() {
+		"not-found"
+	} else {
+		"file"
+	};
+	let first = all.first();
+	ResolvedCommand {
+		kind: kind.to_string(),
+		path: first.map(|path| path.to_string_lossy().into_owned()),
+		shim: first.and_then(|path| shim_manager(path)),
+		all_paths: all
+			.iter()
+			.map(|path| path.to_string_lossy().into_owned())
+			.collect(),
+		name,
+	}
+}
+
+/// Resolve a command name the way a shell session would: keyword, builtin,
+/// or executable on `PATH`.
+#[napi(js_name = "resolveCommand")]
+pub fn resolve_command(
+	name: String,
+	options: Option<ResolveCommandOptions>,
+) -> task::Async<ResolvedCommand> {
+	task::blocking("resolve_command", (), move |_| Ok(resolve(name, options.unwrap_or_default())))
+}
@@ -0,0 +1,158 @@
+//! Workspace boundary for file operations.
+//!
+//! # Overview
+//! `setWorkspaceRoots` confines the native file APIs (reads and writes,
+//! searches and watches, patches, archives, formatting, hashing, tailing, PDF,
+//! image and source parsing) to the given directories. A path is
+//! made absolute against the working directory and resolved a component at
+//! a time, following symlinks (dangling ones too) before applying `..`, so
+//! neither `../` nor a link pointing out of the workspace escapes it. Each
+//! of those calls takes `allowOutsideWorkspace` to skip the check for a path
+//! the user asked for explicitly.
+//!
+//! Directory walks do not follow symlinks, so checking the starting path is
+//! enough to keep them inside.
+//!
+//! # Example
+//! ```ignore
+//! // JS: native.setWorkspaceRoots([process.cwd()]);
+//! // JS: await native.readFileSmart("../../etc/passwd"); // rejects
+//! ```
+
+use std::{
+	env, fs,
+	path::{Component, Path, PathBuf},
+	sync::LazyLock,
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use parking_lot::RwLock;
+
+static ROOTS: LazyLock<RwLock<Vec<PathBuf>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Most symlinks followed while resolving a path.
+const MAX_LINKS: usize = 40;
+
+/// `path` made absolute, with symlinks (dangling ones included) resolved
+/// and `.` and `..` removed, as `realpath` would but for paths that do not
+/// exist yet.
+pub fn resolve(path: &Path) -> Result<PathBuf> {
+	let absolute = if path.is_absolute() {
+		path.to_path_buf()
+	} else {
+		env::current_dir()
+			.map_err(|err| Error::from_reason(format!("Failed to resolve cwd: {err}")))?
+			.join(path)
+	};
+	let mut links = 0;
+	resolve_from(PathBuf::new(), &absolute, &mut links)
+}
+
+fn resolve_from(mut out: PathBuf, path: &Path, links: &mut usize) -> Result<PathBuf> {
+	for component in path.components() {
+		match component {
+			Component::Prefix(_) | Component::RootDir => out.push(component),
+			Component::CurDir => {},
+			Component::ParentDir => {
+				out.pop();
+			},
+			Component::Normal(name) => {
+				out.push(name);
+				let is_link = fs::symlink_metadata(&out).is_ok_and(|meta| meta.is_symlink());
+				if !is_link {
+					continue;
+				}
+				*links += 1;
+				if *links > MAX_LINKS {
+					return Err(Error::from_reason(format!(
+						"Too many levels of symbolic links resolving {}",
+						path.display()
+					)));
+				}
+				let target = fs::read_link(&out).map_err(|err| {
+					Error::from_reason(format!("Failed to read link {}: {err}", out.display()))
+				})?;
+				out.pop();
+				out = resolve_from(out, &target, links)?;
+			},
+		}
+	}
+	Ok(out)
+}
+
+/// `path` made absolute with symlinks resolved up to, but not including, its
+/// last component, so a symlink names itself rather than its target. `None`
+/// when `path` ends in `.` or `..` or is a root.
+pub fn resolve_entry(path: &Path) -> Option<Result<PathBuf>> {
+	let (parent, name) = (path.parent()?, path.file_name()?);
+	let parent = if parent.as_os_str().is_empty() {
+		Path::new(".")
+	} else {
+		parent
+	};
+	Some(resolve(parent).map(|parent| parent.join(name)))
+}
+
+/// Workspace roots, with symlinks resolved; empty when file operations are
+/// not confined.
+pub fn roots() -> Vec<PathBuf> {
+	ROOTS.read().clone()
+}
+
+/// Check that `path` lies inside the workspace roots, unless none are set or
+/// `allow_outside` is true.
+///
+/// # Errors
+/// Returns an error naming the path when it resolves outside every root.
+pub fn check(path: impl AsRef<Path>, allow_outside: Option<bool>) -> Result<()> {
+	if allow_outside == Some(true) {
+		return Ok(());
+	}
+	let roots = ROOTS.read();
+	if roots.is_empty() {
+		return Ok(());
+	}
+	let path = path.as_ref();
+	let resolved = resolve(path)?;
+	if roots.iter().any(|root| resolved.starts_with(root)) {
+		Ok(())
+	} else {
+		Err(Error::from_reason(format!(
+			"Path {} is outside the workspace (resolves to {})",
+			path.display(),
+			resolved.display()
+		)))
+	}
+}
+
+/// Confine native file operations to `paths` and the files below them,
+/// replacing earlier roots; an empty list lifts the boundary.
+///
+/// # Errors
+/// Returns an error if a root does not exist; the previous roots stay in
+/// effect.
+#[napi(js_name = "setWorkspaceRoots")]
+pub fn set_workspace_roots(paths: Vec<String>) -> Result<()> {
+	let roots = paths
+		.iter()
+		.map(|path| {
+			fs::metadata(path).map_err(|err| {
+				Error::from_reason(format!("Failed to resolve workspace root {path}: {err}"))
+			})?;
+			resolve(Path::new(path))
+		})
+		.collect::<Result<Vec<_>>>()?;
+	*ROOTS.write() = roots;
+	Ok(())
+}
+
+/// Current workspace roots, with symlinks resolved; empty when file
+/// operations are not confined.
+#[napi(js_name = "getWorkspaceRoots")]
+pub fn get_workspace_roots() -> Vec<String> {
+	roots()
+		.iter()
+		.map(|root| root.to_string_lossy().into_owned())
+		.collect()
+}
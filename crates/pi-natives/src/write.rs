@@ -0,0 +1,248 @@
+//! Atomic file writes with conflict detection.
+//!
+//! # Overview
+//! Content is written to a temporary file next to the target, then renamed
+//! over it, so readers never observe a partially written file.
+//!
+//! When `expectedHash` is given, the current file is hashed (SHA-256, hex)
+//! right before the rename; if it no longer matches, the write is abandoned
+//! with a conflict error instead of clobbering changes made out-of-band.
+//!
+//! Symlinks are followed: the link target is replaced, not the link.
+//!
+//! With `profile` (as reported by `readFileSmart`), content is written with
+//! the file's line endings, final newline, encoding and BOM, so an edit only
+//! changes the lines it touched.
+//!
+//! Each write is recorded in the edit journal (see `journal`), under
+//! `operationId` or an operation of its own, so it can be undone.
+//!
+//! # Example
+//! ```ignore
+//! // JS: await native.writeFileAtomic("src/main.rs", text, { expectedHash: hashFromRead })
+//! ```
+
+use std::{
+	fs::{self, OpenOptions},
+	io::{self, Write},
+	path::{Path, PathBuf},
+	sync::atomic::{AtomicU32, Ordering},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+	hash, journal, task,
+	text_profile::{self, TextProfile},
+	workspace,
+};
+
+static TEMP_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Options for `writeFileAtomic`.
+#[napi(object)]
+#[derive(Default)]
+pub struct WriteFileOptions {
+	/// Hex SHA-256 of the file contents the caller last read. The write fails
+	/// with a conflict if the file on disk differs. An empty string expects
+	/// the file not to exist.
+	#[napi(js_name = "expectedHash")]
+	pub expected_hash:           Option<String>,
+	/// Keep the permissions of the file being replaced (default: true).
+	#[napi(js_name = "preserveMode")]
+	pub preserve_mode:           Option<bool>,
+	/// Flush the file and its directory to disk before returning (default:
+	/// false).
+	pub fsync:                   Option<bool>,
+	/// Write `content` with this line ending, final newline, encoding and
+	/// BOM instead of as UTF-8 as given.
+	pub profile:                 Option<TextProfile>,
+	/// Skip the boundary set with `setWorkspaceRoots` (default: false).
+	#[napi(js_name = "allowOutsideWorkspace")]
+	pub allow_outside_workspace: Option<bool>,
+	/// Journal the write under this operation from `beginOperation`
+	/// (default: an operation of its own).
+	#[napi(js_name = "operationId")]
+	pub operation_id:            Option<i64>,
+}
+
+/// Result of `writeFileAtomic`.
+#[napi(object)]
+pub struct WriteFileResult {
+	/// Hex SHA-256 of the written contents, for use as the next
+	/// `expectedHash`.
+	pub hash:         String,
+	/// Number of bytes written.
+	pub bytes:        f64,
+	/// Operation the write was journaled under; absent when the journal is
+	/// off.
+	#[napi(js_name = "operationId")]
+	pub operation_id: Option<i64>,
+}
+
+/// Hex-encoded SHA-256 of `bytes`.
+pub fn content_hash(bytes: &[u8]) -> String {
+	hash::Algorithm::Sha256.hex_digest(bytes)
+}
+
+/// Hash of the file at `path`, or an empty string if it does not exist.
+fn current_hash(path: &Path) -> io::Result<String> {
+	match fs::read(path) {
+		Ok(bytes) => Ok(content_hash(&bytes)),
+		Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+		Err(err) => Err(err),
+	}
+}
+
+/// Resolve symlinks so the rename replaces the link target.
+fn resolve_target(path: &Path) -> PathBuf {
+	if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink()) {
+		fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+	} else {
+		path.to_path_buf()
+	}
+}
+
+fn temp_path(target: &Path) -> PathBuf {
+	let name = target
+		.file_name()
+		.map_or_else(|| "file".into(), |name| name.to_string_lossy());
+	let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+	target.with_file_name(format!(".{name}.{}.{counter}.tmp", std::process::id()))
+}
+
+/// Flush directory metadata so the rename itself is durable.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+	fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(windows)]
+#[allow(clippy::unnecessary_wraps, reason = "directories cannot be synced on Windows")]
+const fn sync_dir(_dir: &Path) -> io::Result<()> {
+	Ok(())
+}
+
+fn write_temp(
+	temp: &Path,
+	content: &[u8],
+	mode_from: Option<&fs::Metadata>,
+	fsync: bool,
+) -> io::Result<()> {
+	let mut file = OpenOptions::new().write(true).create_new(true).open(temp)?;
+	file.write_all(content)?;
+	if let Some(meta) = mode_from {
+		file.set_permissions(meta.permissions())?;
+	}
+	if fsync {
+		file.sync_all()?;
+	}
+	Ok(())
+}
+
+pub(crate) fn write_file_sync(
+	path: &str,
+	content: &str,
+	options: WriteFileOptions,
+) -> Result<WriteFileResult> {
+	let target = resolve_target(Path::new(path));
+	let existing = fs::metadata(&target).ok();
+	let preserve_mode = options.preserve_mode.unwrap_or(true);
+	let fsync = options.fsync.unwrap_or(false);
+
+	let encoded;
+	let content = match &options.profile {
+		Some(profile) => {
+			encoded = text_profile::apply(profile, content)
+				.map_err(|err| Error::from_reason(format!("Failed to encode {path}: {err}")))?;
+			encoded.as_slice()
+		},
+		None => content.as_bytes(),
+	};
+
+	let temp = temp_path(&target);
+	let mode_from = existing.as_ref().filter(|_| preserve_mode);
+	if let Err(err) = write_temp(&temp, content, mode_from, fsync) {
+		let _ = fs::remove_file(&temp);
+		return Err(Error::from_reason(format!("Failed to write {path}: {err}")));
+	}
+
+	if let Some(expected) = &options.expected_hash {
+		let actual = match current_hash(&target) {
+			Ok(actual) => actual,
+			Err(err) => {
+				let _ = fs::remove_file(&temp);
+				return Err(Error::from_reason(format!("Failed to read {path}: {err}")));
+			},
+		};
+		if !actual.eq_ignore_ascii_case(expected) {
+			let _ = fs::remove_file(&temp);
+			let describe = |hash: &str| {
+				if hash.is_empty() {
+					"no file".to_string()
+				} else {
+					hash.to_string()
+				}
+			};
+			return Err(Error::from_reason(format!(
+				"Conflict: {path} was modified since it was read (expected {}, found {})",
+				describe(expected),
+				describe(&actual)
+			)));
+		}
+	}
+
+	if let Err(err) = fs::rename(&temp, &target) {
+		let _ = fs::remove_file(&temp);
+		return Err(Error::from_reason(format!("Failed to replace {path}: {err}")));
+	}
+	if fsync && let Some(dir) = target.parent() {
+		sync_dir(dir).map_err(|err| Error::from_reason(format!("Failed to sync {path}: {err}")))?;
+	}
+
+	Ok(WriteFileResult {
+		hash:         content_hash(content),
+		bytes:        content.len() as f64,
+		operation_id: None,
+	})
+}
+
+/// Atomically replace `target` with `bytes`, keeping its permissions.
+pub(crate) fn replace_file(target: &Path, bytes: &[u8]) -> io::Result<()> {
+	let existing = fs::metadata(target).ok();
+	let temp = temp_path(target);
+	let written =
+		write_temp(&temp, bytes, existing.as_ref(), false).and_then(|()| fs::rename(&temp, target));
+	if written.is_err() {
+		let _ = fs::remove_file(&temp);
+	}
+	written
+}
+
+/// Atomically replace a file's contents, optionally failing if it changed
+/// since it was last read.
+///
+/// # Errors
+/// Returns an error starting with `Conflict:` when `expectedHash` does not
+/// match the file on disk, or an I/O error if the write fails.
+#[napi(js_name = "writeFileAtomic")]
+pub fn write_file_atomic(
+	path: String,
+	content: String,
+	options: Option<WriteFileOptions>,
+) -> task::Async<WriteFileResult> {
+	task::blocking("write_file_atomic", (), move |_| {
+		let options = options.unwrap_or_default();
+		workspace::check(&path, options.allow_outside_workspace)?;
+		let mut journal = journal::Pending::begin(options.operation_id, "writeFileAtomic")?;
+		if let Some(journal) = &mut journal {
+			journal.capture(&workspace::resolve(Path::new(&path))?)?;
+		}
+		let mut result = write_file_sync(&path, &content, options)?;
+		if let Some(journal) = journal {
+			result.operation_id = Some(journal.finish()?);
+		}
+		Ok(result)
+	})
+}
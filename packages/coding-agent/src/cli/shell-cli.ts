@@ -123,6 +123,8 @@ export async function runShellCommand(cmd: ShellCommandArgs): Promise<void> {
 							process.stderr.write(`${err.message}\n`);
 							return;
 						}
+						// `run` has no binary, JSON-lines or prompt modes, so only text arrives.
+						if (typeof chunk !== "string") return;
 						if (chunk.length > 0) {
 							lastChar = chunk[chunk.length - 1] ?? null;
 						}
@@ -91,7 +91,7 @@ export async function executeBash(command: string, options?: BashExecutorOptions
 					signal,
 				},
 				(err, chunk) => {
-					if (!err) {
+					if (!err && typeof chunk === "string") {
 						enqueueChunk(chunk);
 					}
 				},
@@ -0,0 +1,15 @@
+/**
+ * Tar, tar.gz, tar.zst and zip extraction and creation with path-traversal protection.
+ */
+
+import { native } from "../native";
+
+export type {
+	ArchiveEntryType,
+	ArchiveFormat,
+	CreateArchiveResult,
+	ExtractArchiveOptions,
+	ExtractArchiveResult,
+} from "./types";
+
+export const { createArchive, extractArchive } = native;
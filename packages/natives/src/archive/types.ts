@@ -0,0 +1,75 @@
+/**
+ * Types for archive extraction and creation.
+ */
+
+import type { Cancellable } from "../bindings";
+
+/** Archive format to create. */
+export type ArchiveFormat = "tar" | "tar.gz" | "tar.zst" | "zip";
+
+/** Archive entry type. */
+export type ArchiveEntryType = "file" | "directory" | "symlink";
+
+/** Options for extracting an archive. */
+export interface ExtractArchiveOptions extends Cancellable {
+	/** Leading path components to remove from entry names, like `tar --strip-components`. */
+	stripComponents?: number;
+	/** Entry types to extract (default: files and directories). Other entries are skipped. */
+	allowedTypes?: ArchiveEntryType[];
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** Result of extracting an archive. */
+export interface ExtractArchiveResult {
+	/** Regular files written. */
+	files: number;
+	/** Directories created. */
+	directories: number;
+	/** Symlinks created. */
+	symlinks: number;
+	/** Bytes of file content written. */
+	bytes: number;
+	/** Entry names skipped because their type is not allowed or not supported. */
+	skipped: string[];
+}
+
+/** Result of creating an archive. */
+export interface CreateArchiveResult {
+	/** Files, directories and symlinks added. */
+	entries: number;
+	/** Size of the written archive in bytes. */
+	bytes: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Extract a tar, tar.gz, tar.zst or zip archive, recognized by its contents. Entries that
+		 * would land outside `dest` (`..`, absolute paths, symlinks pointing out) fail the extraction;
+		 * hard links and special files are skipped.
+		 * @param path Archive to extract.
+		 * @param dest Destination directory, created if missing.
+		 * @param options Component stripping, allowed entry types and cancellation.
+		 */
+		extractArchive(
+			path: string,
+			dest: string,
+			options?: ExtractArchiveOptions | null,
+		): Promise<ExtractArchiveResult>;
+		/**
+		 * Pack files and directories into an archive. Each path is stored under its own name,
+		 * directories recursively; symlinks are stored as links.
+		 * @param paths Files and directories to add.
+		 * @param dest Archive to write.
+		 * @param format Archive format (default: inferred from the `dest` extension).
+		 * @param allowOutsideWorkspace Skip the boundary set with `setWorkspaceRoots` (default: false).
+		 */
+		createArchive(
+			paths: string[],
+			dest: string,
+			format?: ArchiveFormat | null,
+			allowOutsideWorkspace?: boolean | null,
+		): Promise<CreateArchiveResult>;
+	}
+}
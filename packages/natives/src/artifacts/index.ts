@@ -0,0 +1,9 @@
+/**
+ * Content-addressable store for tool outputs, addressed by `agent://` ids.
+ */
+
+import { native } from "../native";
+
+export type { ArtifactStoreOptions, GcArtifactsPolicy, GcArtifactsResult, StoredArtifact } from "./types";
+
+export const { gcArtifacts, getArtifact, putArtifact } = native;
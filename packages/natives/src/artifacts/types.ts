@@ -0,0 +1,69 @@
+/**
+ * Types for the content-addressable artifact store.
+ */
+
+/** Options selecting the artifact store. */
+export interface ArtifactStoreOptions {
+	/** Store directory (default: `~/.omp/agent/artifacts`). */
+	dir?: string;
+}
+
+/** Result of storing an artifact. */
+export interface StoredArtifact {
+	/** `agent://<hash>` id of the artifact. */
+	id: string;
+	/** BLAKE3 hash of the content, lowercase hex. */
+	hash: string;
+	/** Content size in bytes. */
+	size: number;
+	/** Compressed size on disk in bytes. */
+	storedSize: number;
+	/** Whether the content was already stored. */
+	deduplicated: boolean;
+}
+
+/** Eviction policy for artifact garbage collection. */
+export interface GcArtifactsPolicy extends ArtifactStoreOptions {
+	/** Remove artifacts not stored or read for this many milliseconds. */
+	maxAgeMs?: number;
+	/** Remove the least recently used artifacts until the store is at most this many bytes on disk. */
+	maxTotalBytes?: number;
+	/** Ids of artifacts to keep regardless of age and size. */
+	keep?: string[];
+}
+
+/** Result of artifact garbage collection. */
+export interface GcArtifactsResult {
+	/** Artifacts removed. */
+	removed: number;
+	/** Bytes on disk freed. */
+	freedBytes: number;
+	/** Artifacts left in the store. */
+	remaining: number;
+	/** Bytes on disk left in the store. */
+	remainingBytes: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Store an artifact zstd-compressed under the BLAKE3 hash of its content. Identical content is
+		 * stored once.
+		 * @param data Content bytes, or a path to a file to store.
+		 * @param options Store selection.
+		 */
+		putArtifact(data: Uint8Array | string, options?: ArtifactStoreOptions | null): Promise<StoredArtifact>;
+		/**
+		 * Read an artifact, verifying it against its hash.
+		 * @param id `agent://<hash>` id, or the bare hash.
+		 * @param options Store selection.
+		 * @returns The content, or null when it is not stored.
+		 */
+		getArtifact(id: string, options?: ArtifactStoreOptions | null): Promise<Buffer | null>;
+		/**
+		 * Remove expired artifacts and, least recently used first, those over the size budget.
+		 * @param policy Age and size limits and artifacts to keep.
+		 */
+		gcArtifacts(policy?: GcArtifactsPolicy | null): Promise<GcArtifactsResult>;
+	}
+}
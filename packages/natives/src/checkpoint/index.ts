@@ -0,0 +1,15 @@
+/**
+ * Checkpoints of a directory tree.
+ */
+
+import { native } from "../native";
+
+export type {
+	Checkpoint,
+	CheckpointChange,
+	CreateCheckpointOptions,
+	DiffCheckpointOptions,
+	RestoreCheckpointOptions,
+} from "./types";
+
+export const { createCheckpoint, diffCheckpoint, restoreCheckpoint } = native;
@@ -0,0 +1,98 @@
+/**
+ * Types for checkpoints of a directory tree.
+ */
+
+/** Options for `createCheckpoint`. */
+export interface CreateCheckpointOptions {
+	/** What the checkpoint is, e.g. the turn it was taken before. */
+	label?: string;
+	/** Leave out files ignored by `.gitignore` and similar files (default: true). */
+	gitignore?: boolean;
+	/** Refuse to checkpoint more entries than this (default: 20000). */
+	maxFiles?: number;
+	/** Skip files larger than this many bytes (default: 10 MiB). */
+	maxFileSize?: number;
+	/** Database file (default: the session store, as in `SessionStoreOptions`). */
+	dbPath?: string;
+	/** Artifact store for file contents (default: `~/.omp/agent/artifacts`). */
+	artifactsDir?: string;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** Options for `diffCheckpoint`. */
+export interface DiffCheckpointOptions {
+	/** Later checkpoint of the same root to compare with (default: the tree as it is now). */
+	against?: number;
+	/** Database file (default: the session store, as in `SessionStoreOptions`). */
+	dbPath?: string;
+}
+
+/** Options for `restoreCheckpoint`. */
+export interface RestoreCheckpointOptions {
+	/** Database file (default: the session store, as in `SessionStoreOptions`). */
+	dbPath?: string;
+	/** Artifact store the checkpoint was created with (default: `~/.omp/agent/artifacts`). */
+	artifactsDir?: string;
+}
+
+/** A recorded checkpoint. */
+export interface Checkpoint {
+	/** Id to pass to `diffCheckpoint` and `restoreCheckpoint`. */
+	id: number;
+	/** Absolute root directory, with symlinks resolved. */
+	root: string;
+	/** Label given to `createCheckpoint`. */
+	label?: string;
+	/** Files and symlinks recorded. */
+	files: number;
+	/** Directories recorded. */
+	directories: number;
+	/** Total size of the recorded files, in bytes. */
+	bytes: number;
+	/** Files over `maxFileSize`, relative to the root, left out. */
+	skipped: string[];
+	/** Creation time in milliseconds since the Unix epoch. */
+	createdAt: number;
+}
+
+/** An entry that differs from a checkpoint. */
+export interface CheckpointChange {
+	/** Path relative to the root, with `/` separators. */
+	path: string;
+	/** How the entry changed since the checkpoint. */
+	status: "added" | "modified" | "deleted";
+	/** Kind of the entry, now or, when deleted, in the checkpoint. */
+	kind: "file" | "symlink" | "directory";
+	/** Artifact id of the file content in the checkpoint, readable with `getArtifact`. */
+	before?: string;
+	/** Artifact id of the file content in the checkpoint compared with. */
+	after?: string;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Record the files, symlinks and directories under a root, with file contents in the artifact
+		 * store, so the tree can be compared with and restored later. Works without git.
+		 * @param root Directory to checkpoint.
+		 * @param options Label, ignore rules, limits and stores.
+		 * @returns The checkpoint.
+		 */
+		createCheckpoint(root: string, options?: CreateCheckpointOptions | null): Promise<Checkpoint>;
+		/**
+		 * Entries that changed since a checkpoint, by path.
+		 * @param id Checkpoint id.
+		 * @param options Later checkpoint to compare with, instead of the tree as it is now.
+		 */
+		diffCheckpoint(id: number, options?: DiffCheckpointOptions | null): Promise<CheckpointChange[]>;
+		/**
+		 * Put the tree back the way it was at a checkpoint: changed and deleted entries are rewritten and
+		 * entries created since are removed.
+		 * @param id Checkpoint id.
+		 * @param options Stores the checkpoint was created with.
+		 * @returns The changes undone.
+		 */
+		restoreCheckpoint(id: number, options?: RestoreCheckpointOptions | null): Promise<CheckpointChange[]>;
+	}
+}
@@ -90,3 +90,64 @@ export async function readImageFromClipboard(): Promise<ClipboardImage | null> {
 
 	return native.readImageFromClipboard();
 }
+
+/**
+ * Write text to the system clipboard.
+ *
+ * Unlike {@link copyToClipboard}, failures are reported instead of ignored
+ * and no OSC 52 sequence is emitted. On Termux, uses `termux-clipboard-set`.
+ *
+ * @param text - UTF-8 text to place on the clipboard.
+ * @throws When no clipboard is available or writing fails.
+ */
+export async function writeClipboardText(text: string): Promise<void> {
+	if (process.env.TERMUX_VERSION) {
+		execSync("termux-clipboard-set", { input: text, timeout: 5000 });
+		return;
+	}
+
+	if (!hasDisplay) {
+		throw new Error("No clipboard available: no display server");
+	}
+
+	await native.writeClipboardText(text);
+}
+
+/**
+ * Read text from the system clipboard.
+ *
+ * On Termux, uses `termux-clipboard-get`. Returns null when no display
+ * server is available (headless/SSH without forwarding).
+ *
+ * @returns Clipboard text or null when no text is available.
+ */
+export async function readClipboardText(): Promise<string | null> {
+	if (process.env.TERMUX_VERSION) {
+		try {
+			return execSync("termux-clipboard-get", { encoding: "utf8", timeout: 5000 });
+		} catch {
+			return null;
+		}
+	}
+
+	if (!hasDisplay) {
+		return null;
+	}
+
+	return native.readClipboardText();
+}
+
+/**
+ * Place an image on the system clipboard.
+ *
+ * @param data - Encoded image bytes (PNG, JPEG, WebP, or GIF).
+ * @throws On Termux (no image clipboard support), when no display server is
+ * available, or when the image cannot be decoded or written.
+ */
+export async function writeImageToClipboard(data: Uint8Array): Promise<void> {
+	if (process.env.TERMUX_VERSION || !hasDisplay) {
+		throw new Error("No image clipboard available");
+	}
+
+	await native.writeImageToClipboard(data);
+}
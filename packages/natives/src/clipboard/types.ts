@@ -23,5 +23,20 @@ declare module "../bindings" {
 		 * @returns PNG payload or null when no image is available.
 		 */
 		readImageFromClipboard(): Promise<ClipboardImage | null>;
+		/**
+		 * Write text to the system clipboard.
+		 * @param text - UTF-8 text to place on the clipboard.
+		 */
+		writeClipboardText(text: string): Promise<void>;
+		/**
+		 * Read text from the clipboard.
+		 * @returns Clipboard text or null when the clipboard holds no text.
+		 */
+		readClipboardText(): Promise<string | null>;
+		/**
+		 * Place an image on the clipboard.
+		 * @param data - Encoded image bytes (PNG, JPEG, WebP, or GIF).
+		 */
+		writeImageToClipboard(data: Uint8Array): Promise<void>;
 	}
 }
@@ -0,0 +1,10 @@
+/**
+ * Structured diagnostics from compiler, linter and test runner output.
+ */
+
+import { native } from "../native";
+
+export type { Diagnostic, DiagnosticOptions, DiagnosticParserConstructor, DiagnosticTool } from "./types";
+
+export const { DiagnosticParser, parseDiagnostics } = native;
+export type DiagnosticParser = import("./types").DiagnosticParser;
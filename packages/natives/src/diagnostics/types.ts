@@ -0,0 +1,66 @@
+/**
+ * Types for compiler, linter and test runner output parsing.
+ */
+
+/** Toolchain output format. */
+export type DiagnosticTool = "cargo" | "tsc" | "eslint" | "pytest" | "go" | "gcc";
+
+/** Options for diagnostics parsing. */
+export interface DiagnosticOptions {
+	/** Only recognize this tool's format (default: all). */
+	tool?: DiagnosticTool;
+}
+
+/** A compiler error, lint warning or test failure. */
+export interface Diagnostic {
+	/** Format the diagnostic was recognized as. */
+	tool: DiagnosticTool;
+	/** File as printed by the tool, usually relative to its working directory. */
+	file: string;
+	/** 1-based line, when reported. */
+	line?: number;
+	/** 1-based column, when reported. */
+	column?: number;
+	/** 1-based last line of the span, when reported. */
+	endLine?: number;
+	/** 1-based column after the span, when reported. */
+	endColumn?: number;
+	/** Severity; tools' other levels (help, remark, message) map to `note`. */
+	severity: "error" | "warning" | "note";
+	/** Message, including continuation lines. */
+	message: string;
+	/**
+	 * Error code, lint rule or exception type (`E0308`, `TS2322`, `no-unused-vars`,
+	 * `-Wunused-variable`, `AssertionError`).
+	 */
+	code?: string;
+	/** Source excerpt and notes printed with the diagnostic. */
+	detail?: string;
+}
+
+/** Incremental diagnostics parser for streamed output. */
+export interface DiagnosticParser {
+	/** Feed an output chunk and return the diagnostics it completed. Chunks may split lines anywhere. */
+	push(chunk: string): Diagnostic[];
+	/** Return the diagnostics still held back at the end of output. */
+	finish(): Diagnostic[];
+}
+
+/** Native diagnostics parser constructor. */
+export interface DiagnosticParserConstructor {
+	new (options?: DiagnosticOptions | null): DiagnosticParser;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Parse complete cargo, tsc, eslint, pytest, go or gcc/clang output into diagnostics, in
+		 * output order. Multi-line diagnostics are kept together.
+		 * @param output Tool output; ANSI colors are ignored.
+		 * @param options Tool to restrict recognition to.
+		 */
+		parseDiagnostics(output: string, options?: DiagnosticOptions | null): Diagnostic[];
+		/** Incremental diagnostics parser constructor, for streamed output. */
+		DiagnosticParser: DiagnosticParserConstructor;
+	}
+}
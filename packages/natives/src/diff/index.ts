@@ -0,0 +1,9 @@
+/**
+ * Unified and split diff rendering.
+ */
+
+import { native } from "../native";
+
+export type { DiffMode, DiffRow, DiffRowLine, DiffSpan, RenderDiffOptions, RenderDiffResult } from "./types";
+
+export const { renderDiff } = native;
@@ -0,0 +1,77 @@
+/**
+ * Types for diff rendering.
+ */
+
+/** Diff layout: removed lines above their replacement, or old and new side by side. */
+export type DiffMode = "unified" | "split";
+
+/** Options for rendering a diff. */
+export interface RenderDiffOptions {
+	/** Layout (default: "unified"). */
+	mode?: DiffMode;
+	/** Unchanged lines shown around each change (default: 3). */
+	context?: number;
+	/** Line diff algorithm (default: "histogram"). */
+	algorithm?: "histogram" | "myers";
+	/** Mark the words that changed within replaced lines (default: true). */
+	wordLevel?: boolean;
+	/** Also render the rows as ANSI-styled text (default: false). */
+	ansi?: boolean;
+	/** Width of the ANSI text in `split` mode, in cells (default: 160); longer lines are cut off with "…". */
+	width?: number;
+}
+
+/** A run of text within a diff line. */
+export interface DiffSpan {
+	/** Text of the run. */
+	text: string;
+	/** Whether the run differs from the paired line on the other side. */
+	changed: boolean;
+}
+
+/** One side of a diff row. */
+export interface DiffRowLine {
+	/** Line number, 1-indexed. */
+	line: number;
+	/** Text of the line, without its terminator, in runs. */
+	spans: DiffSpan[];
+}
+
+/** A row of a rendered diff. */
+export interface DiffRow {
+	/**
+	 * "hunk" starts a hunk, "context" is an unchanged line, "removed" and "added" are one-sided,
+	 * and "changed" pairs a removed line with its replacement (`split` mode only).
+	 */
+	kind: "hunk" | "context" | "removed" | "added" | "changed";
+	/** Hunk header like `@@ -1,4 +1,5 @@`, for "hunk" rows. */
+	header?: string;
+	/** Old line, for "context", "removed" and "changed" rows. */
+	old?: DiffRowLine;
+	/** New line, for "context", "added" and "changed" rows. */
+	new?: DiffRowLine;
+}
+
+/** Result of rendering a diff. */
+export interface RenderDiffResult {
+	/** Hunk headers and lines, in order. */
+	rows: DiffRow[];
+	/** Number of added lines. */
+	added: number;
+	/** Number of removed lines. */
+	removed: number;
+	/** The rows as ANSI-styled lines joined with "\n", when `ansi` is set. */
+	ansi?: string;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Diff `oldText` against `newText` by line and lay the changes out for display, with changed words marked.
+		 * @param oldText Text before the change.
+		 * @param newText Text after the change.
+		 * @param options Layout, context, algorithm and ANSI output.
+		 */
+		renderDiff(oldText: string, newText: string, options?: RenderDiffOptions | null): Promise<RenderDiffResult>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Directory tree summaries for prompts.
+ */
+
+import { native } from "../native";
+
+export type { DirTreeOptions, DirTreeResult } from "./types";
+
+export const { dirTree } = native;
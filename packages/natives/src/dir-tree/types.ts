@@ -0,0 +1,46 @@
+/**
+ * Types for directory tree summaries.
+ */
+
+import type { Cancellable } from "../bindings";
+
+/** Options for summarizing a directory tree. */
+export interface DirTreeOptions extends Cancellable {
+	/** Directory levels to expand below the root (default: 3). */
+	maxDepth?: number;
+	/** Maximum number of entries to list (default: 200). */
+	maxEntries?: number;
+	/** Respect .gitignore files (default: true). */
+	respectGitignore?: boolean;
+	/** Include hidden files (default: false). */
+	hidden?: boolean;
+	/** Append file sizes (default: false). */
+	includeSizes?: boolean;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** A rendered directory tree. */
+export interface DirTreeResult {
+	/** Rendered tree, one entry per line with two spaces of indentation per level, ending with a newline. */
+	tree: string;
+	/** Number of entries listed in `tree`, excluding the root. */
+	shown: number;
+	/** Number of entries found within `maxDepth`. */
+	total: number;
+	/** Whether entries were left out to stay within `maxEntries`. */
+	truncated: boolean;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Summarize the directory tree under `root` for prompts.
+		 * The entry budget is shared breadth-first; `node_modules` and directories at the depth limit are listed with
+		 * their entry count, and directories cut short end with a `… N more` line.
+		 * @param root Directory to summarize.
+		 * @param options Depth, entry budget, ignore handling and sizes.
+		 */
+		dirTree(root: string, options?: DirTreeOptions | null): Promise<DirTreeResult>;
+	}
+}
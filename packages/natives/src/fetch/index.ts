@@ -0,0 +1,9 @@
+/**
+ * HTTP requests with size, time and redirect limits.
+ */
+
+import { native } from "../native";
+
+export type { FetchOptions, FetchResult } from "./types";
+
+export const { fetchUrl } = native;
@@ -0,0 +1,59 @@
+/**
+ * Types for HTTP requests.
+ */
+
+import type { Cancellable } from "../bindings";
+
+/** Options for an HTTP request. */
+export interface FetchOptions extends Cancellable {
+	/** URL to request (http or https). */
+	url: string;
+	/** HTTP method (default: `GET`). */
+	method?: string;
+	/** Request headers. */
+	headers?: Record<string, string>;
+	/** Request body. */
+	body?: string | Uint8Array;
+	/** Stop reading the response body after this many bytes and report it as `truncated` (default: no limit). */
+	maxBytes?: number;
+	/** Follow redirects (default: true). When false a redirect response is returned as is. */
+	allowRedirects?: boolean;
+	/** Maximum number of redirects to follow before failing (default: 10). */
+	maxRedirects?: number;
+	/** Write the response body to this file instead of returning it. */
+	outputPath?: string;
+}
+
+/** Response of an HTTP request. */
+export interface FetchResult {
+	/** HTTP status code. */
+	status: number;
+	/** Canonical reason phrase for the status code. */
+	statusText: string;
+	/** Final URL, after redirects. */
+	url: string;
+	/** Response headers with lowercase names; repeated headers are joined with `, `. */
+	headers: Record<string, string>;
+	/** Raw response body, after content decoding. Absent with `outputPath`. */
+	body?: Uint8Array;
+	/** Body decoded with the charset of a textual `content-type`. Absent for binary content and with `outputPath`. */
+	text?: string;
+	/** Body bytes received (written to `outputPath` when given). */
+	bytes: number;
+	/** Whether the body was cut off at `maxBytes`. */
+	truncated: boolean;
+	/** Wall-clock duration of the request in milliseconds. */
+	durationMs: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Make an HTTP request with rustls, system proxies and gzip/brotli/zstd/deflate decoding.
+		 * Non-2xx statuses resolve normally; connection failures, too many redirects, aborts and
+		 * timeouts (`timeoutMs` covers the whole request) reject.
+		 * @param options URL, method, headers, body, size and redirect limits, and cancellation.
+		 */
+		fetchUrl(options: FetchOptions): Promise<FetchResult>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Format-on-edit through the project's formatter.
+ */
+
+import { native } from "../native";
+
+export type { FormatLineRange, FormatOptions, FormatResult, FormatterName } from "./types";
+
+export const { formatFile } = native;
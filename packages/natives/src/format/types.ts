@@ -0,0 +1,57 @@
+/**
+ * Types for formatting files with the project's formatter.
+ */
+
+import type { Cancellable } from "../bindings";
+
+/** Supported formatters; `auto` chooses one from the file extension and project configuration. */
+export type FormatterName = "auto" | "rustfmt" | "prettier" | "biome" | "black" | "ruff" | "gofmt";
+
+/** An inclusive, 1-indexed range of lines. */
+export interface FormatLineRange {
+	/** First line. */
+	start: number;
+	/** Last line. */
+	end: number;
+}
+
+/** Options for formatting a file. The timeout defaults to 10 seconds. */
+export interface FormatOptions extends Cancellable {
+	/** Formatter to run (default: `auto`). */
+	formatter?: FormatterName;
+	/** Only keep changes touching these lines of the input. */
+	range?: FormatLineRange;
+	/** Content to format instead of the file on disk. The path still selects the formatter and its configuration. */
+	text?: string;
+	/**
+	 * Write the result back to the file when it changed (default: false). Fails with a conflict if the file
+	 * changed while it was formatted.
+	 */
+	write?: boolean;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** Result of formatting a file. */
+export interface FormatResult {
+	/** Formatter that ran. */
+	formatter: Exclude<FormatterName, "auto">;
+	/** Whether the formatted text differs from the input. */
+	changed: boolean;
+	/** The formatted text. */
+	text: string;
+	/** Unified diff from the input to `text`; empty when unchanged. */
+	diff: string;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Format a file with the project's formatter (rustfmt, Prettier, Biome, Black, Ruff or gofmt)
+		 * and return the result and a diff against the input.
+		 * @param path File to format; also selects the formatter and its configuration.
+		 * @param options Formatter, line range, replacement text and write-back.
+		 */
+		formatFile(path: string, options?: FormatOptions | null): Promise<FormatResult>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Fuzzy matching for file and symbol pickers.
+ */
+
+import { native } from "../native";
+
+export type { FuzzyMatch, FuzzyMatchOptions } from "./types";
+
+export const { fuzzyMatch } = native;
@@ -0,0 +1,34 @@
+/**
+ * Types for fuzzy matching.
+ */
+
+/** Options for fuzzy matching. */
+export interface FuzzyMatchOptions {
+	/** Maximum number of matches to return (default: 100). */
+	limit?: number;
+	/** Match case exactly (default: only when the query has an uppercase letter). */
+	caseSensitive?: boolean;
+}
+
+/** A candidate matching the query. */
+export interface FuzzyMatch {
+	/** Index of the candidate in the input. */
+	index: number;
+	/** Match score (higher is better). */
+	score: number;
+	/** UTF-16 offsets of the matched characters, ascending. */
+	positions: number[];
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Rank candidates against a query with fzf-style scoring, best first.
+		 * Whitespace-separated query terms must all match; an empty query matches every candidate in input order.
+		 * @param query Query, e.g. "srmain" or "main test".
+		 * @param candidates Strings to match, e.g. file paths or symbol names.
+		 * @param options Result limit and case sensitivity.
+		 */
+		fuzzyMatch(query: string, candidates: string[], options?: FuzzyMatchOptions | null): Promise<FuzzyMatch[]>;
+	}
+}
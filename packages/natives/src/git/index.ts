@@ -0,0 +1,23 @@
+/**
+ * Git status, diff and log without the git CLI, powered by gitoxide.
+ */
+
+import { native } from "../native";
+
+export type {
+	GitBlame,
+	GitBlameLine,
+	GitBlameOptions,
+	GitCommit,
+	GitDiff,
+	GitDiffOptions,
+	GitFileDiff,
+	GitLineRange,
+	GitLogOptions,
+	GitStatus,
+	GitStatusEntry,
+	GitStatusOptions,
+} from "./types";
+export { GitChangeKind } from "./types";
+
+export const { gitStatus, gitDiff, gitLog, gitBlame } = native;
@@ -0,0 +1,199 @@
+/**
+ * Types for native git repository inspection.
+ */
+
+import type { Cancellable } from "../bindings";
+
+/** Kind of change to a path. */
+export const enum GitChangeKind {
+	/** Newly added (or intent-to-add) path. */
+	Added = 1,
+	/** Content or mode changed. */
+	Modified = 2,
+	/** Path removed. */
+	Deleted = 3,
+	/** Moved from `oldPath`. */
+	Renamed = 4,
+	/** Copied from `oldPath`. */
+	Copied = 5,
+	/** Changed between file, symlink, or submodule. */
+	TypeChange = 6,
+	/** Not tracked by the index. */
+	Untracked = 7,
+	/** Unresolved merge conflict. */
+	Conflicted = 8,
+}
+
+/** Options for reading repository status. */
+export interface GitStatusOptions extends Cancellable {
+	/** Include untracked files (default: true). */
+	untracked?: boolean;
+}
+
+/** Status of a single path. */
+export interface GitStatusEntry {
+	/** Path relative to the repository root (uses `/` separators). */
+	path: string;
+	/** Previous path of a rename or copy. */
+	oldPath?: string;
+	/** Change between `HEAD` and the index. */
+	staged?: GitChangeKind;
+	/** Change between the index and the worktree. */
+	unstaged?: GitChangeKind;
+}
+
+/** Repository status. */
+export interface GitStatus {
+	/** Absolute path of the worktree root. */
+	root: string;
+	/** Checked-out branch, absent when `HEAD` is detached. */
+	branch?: string;
+	/** Commit `HEAD` points to, absent before the first commit. */
+	head?: string;
+	/** Changed paths, sorted by path. */
+	entries: GitStatusEntry[];
+}
+
+/** Options for diffing a repository. */
+export interface GitDiffOptions extends Cancellable {
+	/** Directory inside the repository. */
+	root: string;
+	/** Diff `HEAD` against the index instead of the index against the worktree (default: false). */
+	staged?: boolean;
+	/** Limit the diff to these paths (files or directories, relative to the repository root). */
+	paths?: string[];
+	/** Lines of context around each hunk (default: 3). */
+	context?: number;
+}
+
+/** Diff of a single file. */
+export interface GitFileDiff {
+	/** Path relative to the repository root (uses `/` separators). */
+	path: string;
+	/** Previous path of a rename or copy. */
+	oldPath?: string;
+	/** Kind of change. */
+	kind: GitChangeKind;
+	/** Whether either side is binary; binary files have no hunks. */
+	binary: boolean;
+	/** Number of added lines. */
+	additions: number;
+	/** Number of removed lines. */
+	deletions: number;
+	/** Unified diff of this file, including its `diff --git` header. */
+	patch: string;
+}
+
+/** Result of diffing a repository. */
+export interface GitDiff {
+	/** Changed files, sorted by path. */
+	files: GitFileDiff[];
+	/** Concatenated patch of all files, as printed by `git diff`. */
+	patch: string;
+}
+
+/** Options for listing commits. */
+export interface GitLogOptions extends Cancellable {
+	/** Directory inside the repository. */
+	root: string;
+	/** Maximum number of commits to return (default: 50). */
+	limit?: number;
+	/** Revision to start from (default: "HEAD"). */
+	ref?: string;
+	/** Follow only the first parent of merge commits (default: false). */
+	firstParent?: boolean;
+}
+
+/** A commit returned by `gitLog`. */
+export interface GitCommit {
+	/** Full commit hash. */
+	sha: string;
+	/** Parent commit hashes. */
+	parents: string[];
+	/** Author name. */
+	author: string;
+	/** Author email. */
+	email: string;
+	/** Author time in milliseconds since the Unix epoch. */
+	time: number;
+	/** First line of the message. */
+	summary: string;
+	/** Full commit message. */
+	message: string;
+}
+
+/** An inclusive, 1-indexed range of lines. */
+export interface GitLineRange {
+	/** First line. */
+	start: number;
+	/** Last line. */
+	end: number;
+}
+
+/** Options for blaming a file. */
+export interface GitBlameOptions extends Cancellable {
+	/** Only blame these lines. */
+	range?: GitLineRange;
+	/** Revision to blame the file at (default: "HEAD"). */
+	ref?: string;
+}
+
+/** Attribution of a single line. */
+export interface GitBlameLine {
+	/** Line number in the blamed file, 1-indexed. */
+	line: number;
+	/** Line text without its terminator. */
+	content: string;
+	/** Hash of the commit that last changed the line. */
+	sha: string;
+	/** Author name. */
+	author: string;
+	/** Author email. */
+	email: string;
+	/** Author time in milliseconds since the Unix epoch. */
+	time: number;
+	/** First line of the commit message. */
+	summary: string;
+	/** Line number in that commit's version of the file, 1-indexed. */
+	originalLine: number;
+	/** Path of the file in that commit, when it was since renamed. */
+	originalPath?: string;
+}
+
+/** Result of blaming a file. */
+export interface GitBlame {
+	/** Path relative to the repository root (uses `/` separators). */
+	path: string;
+	/** Commit the file was blamed at. */
+	head: string;
+	/** Blamed lines in file order. */
+	lines: GitBlameLine[];
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Report staged, unstaged and untracked changes of the repository containing `root`.
+		 * @param root Directory inside the repository.
+		 * @param options Untracked file listing and cancellation.
+		 */
+		gitStatus(root: string, options?: GitStatusOptions): Promise<GitStatus>;
+		/**
+		 * Diff the index against the worktree, or `HEAD` against the index when `staged` is set.
+		 * @param options Repository, side, path filter and context size.
+		 */
+		gitDiff(options: GitDiffOptions): Promise<GitDiff>;
+		/**
+		 * List commits reachable from a revision, newest first.
+		 * @param options Repository, starting revision and limit.
+		 */
+		gitLog(options: GitLogOptions): Promise<GitCommit[]>;
+		/**
+		 * Attribute each line of a file to the commit that last changed it.
+		 * Lines are blamed as of `ref`; uncommitted changes are not considered.
+		 * @param path File to blame, absolute or relative to the working directory.
+		 * @param options Line range, revision and cancellation.
+		 */
+		gitBlame(path: string, options?: GitBlameOptions): Promise<GitBlame>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Shell-style glob expansion with the same semantics on every platform.
+ */
+
+import { native } from "../native";
+
+export type { ExpandGlobOptions } from "./types";
+
+export const { expandGlob } = native;
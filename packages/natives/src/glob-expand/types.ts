@@ -0,0 +1,31 @@
+/**
+ * Types for shell-style glob expansion.
+ */
+
+/** Options for `expandGlob`. */
+export interface ExpandGlobOptions {
+	/** Directory relative patterns are expanded in (default: the working directory). */
+	cwd?: string;
+	/** Let wildcards match names starting with `.` (default: false). */
+	dot?: boolean;
+	/** Let `**` descend into symlinked directories (default: false). */
+	follow?: boolean;
+	/** Stop after this many paths. */
+	maxResults?: number;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Expand a pattern the way bash does with `globstar` and `extglob` on: braces and `{1..9}`
+		 * sequences, `*`, `?`, `[...]`, `**` and `?(a|b)`-style groups. Paths are relative to `cwd`
+		 * for relative patterns and sorted per brace alternative; patterns ending in `/` match only
+		 * directories.
+		 * @param pattern Glob pattern.
+		 * @param options Directory and matching options.
+		 */
+		expandGlob(pattern: string, options?: ExpandGlobOptions | null): Promise<string[]>;
+	}
+}
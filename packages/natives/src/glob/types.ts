@@ -21,6 +21,10 @@ export interface GlobOptions extends Cancellable {
 	path: string;
 	/** Filter by file type: "file", "dir", or "symlink". */
 	fileType?: FileType;
+	/** Filter by language type, as in `grep` (e.g. "ts", "rust"). */
+	type?: string;
+	/** Maximum directory depth below the search root (1 = direct children). */
+	maxDepth?: number;
 	/** Include hidden files (default: false). */
 	hidden?: boolean;
 	/** Maximum number of results to return. */
@@ -33,6 +37,8 @@ export interface GlobOptions extends Cancellable {
 	sortByMtime?: boolean;
 	/** Include node_modules entries even when pattern does not mention node_modules. */
 	includeNodeModules?: boolean;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
 }
 
 /** A single filesystem match. */
@@ -43,6 +49,8 @@ export interface GlobMatch {
 	fileType: FileType;
 	/** Modification time in milliseconds since epoch, if available. */
 	mtime?: number;
+	/** Size in bytes (regular files only). */
+	size?: number;
 }
 
 /** Result of a find operation. */
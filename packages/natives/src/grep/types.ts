@@ -36,6 +36,8 @@ export interface GrepOptions extends Cancellable {
 	maxColumns?: number;
 	/** Output mode */
 	mode?: "content" | "filesWithMatches" | "count";
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
 }
 
 /** A context line returned around a match. */
@@ -52,6 +54,8 @@ export interface GrepMatch {
 	path: string;
 	/** 1-indexed line number (0 for count-only entries). */
 	lineNumber: number;
+	/** 1-indexed byte column of the first match (0 for count-only entries). */
+	column: number;
 	/** Matched line content (empty for count-only entries). */
 	line: string;
 	/** Context lines before the match. */
@@ -110,6 +114,8 @@ export interface SearchOptions {
 export interface SearchMatch {
 	/** 1-indexed line number. */
 	lineNumber: number;
+	/** 1-indexed byte column of the first match on the line. */
+	column: number;
 	/** Matched line content. */
 	line: string;
 	/** Context lines before the match. */
@@ -146,6 +152,8 @@ export interface FuzzyFindOptions extends Cancellable {
 	cache?: boolean;
 	/** Maximum number of matches to return (default: 100). */
 	maxResults?: number;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
 }
 
 /** A single match in fuzzy find results. */
@@ -170,7 +178,10 @@ declare module "../bindings" {
 	interface NativeBindings {
 		/** Fuzzy file path search for autocomplete. */
 		fuzzyFind(options: FuzzyFindOptions): Promise<FuzzyFindResult>;
-		/** Search files for a regex pattern. */
+		/**
+		 * Search files for a regex pattern.
+		 * `onMatch` receives each file's matches as soon as that file is searched.
+		 */
 		grep(options: GrepOptions, onMatch?: TsFunc<GrepMatch>): Promise<GrepResult>;
 		/** Search in-memory content for a regex pattern. */
 		search(content: string | Uint8Array, options: SearchOptions): SearchResult;
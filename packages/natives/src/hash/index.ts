@@ -0,0 +1,9 @@
+/**
+ * BLAKE3, SHA-256 and MD5 hashing of files and in-memory data.
+ */
+
+import { native } from "../native";
+
+export type { HashAlgorithm } from "./types";
+
+export const { hashFile, hashString } = native;
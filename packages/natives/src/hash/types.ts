@@ -0,0 +1,33 @@
+/**
+ * Types for content hashing.
+ */
+
+/**
+ * Hash algorithm: `blake3` (fastest), `sha256` (matches `readFileSmart`/`writeFileAtomic` hashes)
+ * or `md5` (for published checksums).
+ */
+export type HashAlgorithm = "blake3" | "sha256" | "md5";
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Hash a file's contents as lowercase hex, streaming it from disk.
+		 * @param path File to hash.
+		 * @param algorithm Hash algorithm (default: `sha256`).
+		 * @param signal Abort signal for cancelling the operation.
+		 * @param allowOutsideWorkspace Skip the boundary set with `setWorkspaceRoots` (default: false).
+		 */
+		hashFile(
+			path: string,
+			algorithm?: HashAlgorithm | null,
+			signal?: AbortSignal,
+			allowOutsideWorkspace?: boolean | null,
+		): Promise<string>;
+		/**
+		 * Hash a string (as UTF-8) or buffer as lowercase hex.
+		 * @param data Data to hash.
+		 * @param algorithm Hash algorithm (default: `sha256`).
+		 */
+		hashString(data: string | Uint8Array, algorithm?: HashAlgorithm | null): string;
+	}
+}
@@ -4,6 +4,7 @@
 
 import { native } from "../native";
 
-export type { HighlightColors } from "./types";
+export type { HighlightColors, HighlightOptions } from "./types";
 
-export const { highlightCode, supportsLanguage, getSupportedLanguages } = native;
+export const { highlightCode, highlightCodeWithTheme, supportsLanguage, getSupportedLanguages, getHighlightThemes } =
+	native;
@@ -31,6 +31,16 @@ export interface HighlightColors {
 	deleted?: string;
 }
 
+/** Options for `highlightCodeWithTheme`. */
+export interface HighlightOptions {
+	/** Language name or extension (default: plain text). */
+	language?: string;
+	/** Theme from `getHighlightThemes` (default: `base16-ocean.dark`). */
+	theme?: string;
+	/** `ansi` (default) for 24-bit terminal colors, or `html` for a `<pre>` block with inline styles. */
+	output?: "ansi" | "html";
+}
+
 declare module "../bindings" {
 	interface NativeBindings {
 		/**
@@ -52,5 +62,18 @@ declare module "../bindings" {
 		 * @returns Syntect language names supported by the native highlighter.
 		 */
 		getSupportedLanguages(): string[];
+		/**
+		 * Highlight code with one of the bundled themes instead of a semantic palette.
+		 * @param code Source code to highlight.
+		 * @param options Language, theme and output format.
+		 * @returns Code with 24-bit ANSI colors, or a `<pre>` block of styled HTML.
+		 * @throws For an unknown theme or output format.
+		 */
+		highlightCodeWithTheme(code: string, options?: HighlightOptions): string;
+		/**
+		 * Get the names of the bundled themes.
+		 * @returns Theme names accepted by `highlightCodeWithTheme`.
+		 */
+		getHighlightThemes(): string[];
 	}
 }
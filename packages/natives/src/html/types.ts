@@ -8,6 +8,15 @@ export interface HtmlToMarkdownOptions {
 	cleanContent?: boolean;
 	/** Skip images during conversion. */
 	skipImages?: boolean;
+	/** Resolve relative link and image URLs against this URL. */
+	baseUrl?: string;
+	/** Keep links as Markdown links (default: true). When false only their text is kept. */
+	includeLinks?: boolean;
+	/**
+	 * Maximum length of the Markdown in characters. Longer output is cut at a paragraph or line
+	 * break where possible and ends with a `[content truncated]` marker.
+	 */
+	maxLength?: number;
 }
 
 declare module "../bindings" {
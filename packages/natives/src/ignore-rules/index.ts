@@ -0,0 +1,9 @@
+/**
+ * Standalone `.gitignore`-style matcher.
+ */
+
+import { native } from "../native";
+
+export type { IgnoreMatcher, IgnoreRulesOptions } from "./types";
+
+export const { compileIgnoreRules } = native;
@@ -0,0 +1,45 @@
+/**
+ * Types for the standalone `.gitignore`-style matcher.
+ */
+
+/** Options for `compileIgnoreRules`. */
+export interface IgnoreRulesOptions {
+	/** Directory patterns and paths are relative to (default: the working directory). */
+	root?: string;
+	/** Gitignore lines, applied after every rule file. */
+	patterns?: string[];
+	/** Ignore files whose lines apply relative to the root. */
+	files?: string[];
+	/**
+	 * Also apply the `.gitignore`, `.ignore` and `.git/info/exclude` files under the root, as directory
+	 * walks do (default: false).
+	 */
+	gitignore?: boolean;
+}
+
+/** Compiled ignore rules, from `compileIgnoreRules`. */
+export interface IgnoreMatcher {
+	/** Absolute root the rules apply under. */
+	readonly root: string;
+	/**
+	 * Whether a path is ignored. Relative paths are relative to the root; paths outside it are never
+	 * ignored.
+	 * @param path Path to check.
+	 * @param isDir Whether the path is a directory (default: whether it is one on disk).
+	 */
+	matches(path: string, isDir?: boolean | null): boolean;
+	/** The paths that are not ignored, in order. */
+	filter(paths: string[]): string[];
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Compile gitignore patterns and ignore files into a matcher with the semantics `glob`,
+		 * `fuzzyFind` and `grep` use: deeper rules take precedence, `!` re-includes, and paths inside
+		 * ignored directories are ignored.
+		 * @param options Root, patterns and rule files.
+		 */
+		compileIgnoreRules(options?: IgnoreRulesOptions | null): IgnoreMatcher;
+	}
+}
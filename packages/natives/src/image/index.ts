@@ -4,7 +4,17 @@
 
 import { native } from "../native";
 
-export { ImageFormat, type PhotonImageConstructor, SamplingFilter } from "./types";
+export {
+	ImageFormat,
+	type ImageFormatName,
+	type ImageInfo,
+	type PhotonImageConstructor,
+	type ResizedImage,
+	type ResizeImageOptions,
+	SamplingFilter,
+} from "./types";
+
+export const { imageInfo, resizeImage } = native;
 
 /** PhotonImage class for image manipulation. Use PhotonImage.parse() to create instances. */
 export const PhotonImage = native.PhotonImage;
@@ -54,6 +54,61 @@ export interface PhotonImageConstructor {
 	prototype: PhotonImage;
 }
 
+/** Image format by name. */
+export type ImageFormatName = "png" | "jpeg" | "webp" | "gif";
+
+/** Header information of an image file. */
+export interface ImageInfo {
+	/** Stored width in pixels. */
+	width: number;
+	/** Stored height in pixels. */
+	height: number;
+	/** Width once the EXIF orientation is applied. */
+	displayWidth: number;
+	/** Height once the EXIF orientation is applied. */
+	displayHeight: number;
+	/** Image format. */
+	format: ImageFormatName;
+	/** MIME type of the format. */
+	mimeType: string;
+	/** EXIF orientation (1-8; 1 when the image is stored upright or has no EXIF data). */
+	orientation: number;
+	/** Whether the image has an alpha channel. */
+	hasAlpha: boolean;
+	/** File size in bytes. */
+	bytes: number;
+}
+
+/** Options for resizing an image file. */
+export interface ResizeImageOptions {
+	/** Maximum output width in pixels (default: no limit). */
+	maxWidth?: number;
+	/** Maximum output height in pixels (default: no limit). */
+	maxHeight?: number;
+	/** Output format (default: the input format). */
+	formatOut?: ImageFormatName;
+	/** JPEG quality, 1-100 (default: 85). Ignored for other formats. */
+	quality?: number;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** Encoded output of resizing an image file. */
+export interface ResizedImage {
+	/** Encoded image bytes. */
+	data: Uint8Array;
+	/** Output width in pixels. */
+	width: number;
+	/** Output height in pixels. */
+	height: number;
+	/** Output format. */
+	format: ImageFormatName;
+	/** MIME type of the output format. */
+	mimeType: string;
+	/** Whether the image was scaled down to fit the limits. */
+	resized: boolean;
+}
+
 declare module "../bindings" {
 	/** Native bindings for image operations. */
 	interface NativeBindings {
@@ -61,5 +116,18 @@ declare module "../bindings" {
 		SamplingFilter: typeof SamplingFilter;
 		/** Photon image constructor exposed by the native module. */
 		PhotonImage: PhotonImageConstructor;
+		/**
+		 * Read an image file's dimensions, format and EXIF orientation without decoding its pixels.
+		 * @param path PNG, JPEG, WebP or GIF file.
+		 * @param allowOutsideWorkspace Skip the boundary set with `setWorkspaceRoots` (default: false).
+		 */
+		imageInfo(path: string, allowOutsideWorkspace?: boolean | null): Promise<ImageInfo>;
+		/**
+		 * Decode an image file, rotate it upright per its EXIF orientation, scale it down to fit
+		 * `maxWidth` x `maxHeight` keeping its aspect ratio, and encode it. Never scales up.
+		 * @param path PNG, JPEG, WebP or GIF file.
+		 * @param options Size limits, output format and JPEG quality.
+		 */
+		resizeImage(path: string, options?: ResizeImageOptions | null): Promise<ResizedImage>;
 	}
 }
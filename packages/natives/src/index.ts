@@ -6,7 +6,14 @@
 // Clipboard
 // =============================================================================
 
-export { type ClipboardImage, copyToClipboard, readImageFromClipboard } from "./clipboard";
+export {
+	type ClipboardImage,
+	copyToClipboard,
+	readClipboardText,
+	readImageFromClipboard,
+	writeClipboardText,
+	writeImageToClipboard,
+} from "./clipboard";
 
 // =============================================================================
 // Grep (ripgrep-based regex search)
@@ -39,12 +46,24 @@ export {
 	glob,
 	invalidateFsScanCache,
 } from "./glob";
+export { type ExpandGlobOptions, expandGlob } from "./glob-expand";
+export { compileIgnoreRules, type IgnoreMatcher, type IgnoreRulesOptions } from "./ignore-rules";
 
 // =============================================================================
 // Image processing (photon-compatible API)
 // =============================================================================
 
-export { ImageFormat, PhotonImage, SamplingFilter } from "./image";
+export {
+	ImageFormat,
+	type ImageFormatName,
+	type ImageInfo,
+	imageInfo,
+	PhotonImage,
+	type ResizedImage,
+	type ResizeImageOptions,
+	resizeImage,
+	SamplingFilter,
+} from "./image";
 
 // =============================================================================
 // Text utilities
@@ -62,17 +81,38 @@ export {
 	wrapTextWithAnsi,
 } from "./text";
 
+// =============================================================================
+// Token counting
+// =============================================================================
+
+export {
+	countTokens,
+	type TruncateMiddleOptions,
+	type TruncateMiddleResult,
+	truncateMiddle,
+	truncateToTokens,
+} from "./tokens";
+
 // =============================================================================
 // Syntax highlighting
 // =============================================================================
 
 export {
+	getHighlightThemes,
 	getSupportedLanguages,
 	type HighlightColors,
 	highlightCode,
+	highlightCodeWithTheme,
+	type HighlightOptions,
 	supportsLanguage,
 } from "./highlight";
 
+// =============================================================================
+// Markdown rendering
+// =============================================================================
+
+export { type RenderMarkdownOptions, renderMarkdown } from "./markdown";
+
 // =============================================================================
 // Keyboard sequence helpers
 // =============================================================================
@@ -87,30 +127,455 @@ export {
 	parseKittySequence,
 } from "./keys";
 
+// =============================================================================
+// Terminal capabilities
+// =============================================================================
+
+export { type TermInfo, termInfo } from "./term";
+
 // =============================================================================
 // HTML to Markdown
 // =============================================================================
 
 export { type HtmlToMarkdownOptions, htmlToMarkdown } from "./html";
 
+// =============================================================================
+// File reading and writing
+// =============================================================================
+
+export { type ReadFileOptions, type ReadFileResult, readFileSmart, type TextProfile } from "./read";
+export { type WriteFileOptions, type WriteFileResult, writeFileAtomic } from "./write";
+export { getWorkspaceRoots, setWorkspaceRoots } from "./workspace";
+export { type RemovePathOptions, type RemovePathResult, removePathSafe } from "./remove";
+export { restoreFromTrash, type TrashedItem, type TrashOptions, trashPath } from "./trash";
+export {
+	createManagedTempDir,
+	listManagedTempDirs,
+	type ManagedTempDir,
+	type ManagedTempDirOptions,
+	releaseManagedTempDirs,
+} from "./tempdir";
+
+// =============================================================================
+// Hashing
+// =============================================================================
+
+export { type HashAlgorithm, hashFile, hashString } from "./hash";
+
+// =============================================================================
+// Archives
+// =============================================================================
+
+export {
+	type ArchiveEntryType,
+	type ArchiveFormat,
+	type CreateArchiveResult,
+	createArchive,
+	type ExtractArchiveOptions,
+	type ExtractArchiveResult,
+	extractArchive,
+} from "./archive";
+
+// =============================================================================
+// HTTP fetch
+// =============================================================================
+
+export { type FetchOptions, type FetchResult, fetchUrl } from "./fetch";
+
+// =============================================================================
+// Readiness probes
+// =============================================================================
+
+export { type ReadyResult, type WaitForReadyOptions, waitForReady } from "./ready";
+
+// =============================================================================
+// PDF text extraction
+// =============================================================================
+
+export { extractPdfText, type PdfPage, type PdfTextOptions, type PdfTextResult } from "./pdf";
+
+// =============================================================================
+// Desktop notifications
+// =============================================================================
+
+export { type NotificationOptions, type NotificationUrgency, sendNotification } from "./notify";
+
+// =============================================================================
+// Secrets
+// =============================================================================
+
+export { deleteSecret, getSecret, storeSecret } from "./secrets";
+
+// =============================================================================
+// Secret detection
+// =============================================================================
+
+export {
+	redactSecrets,
+	scanSecretFiles,
+	scanSecrets,
+	type SecretFile,
+	type SecretFileScanOptions,
+	type SecretMatch,
+	type SecretScanOptions,
+} from "./secret-scan";
+
+// =============================================================================
+// Session store
+// =============================================================================
+
+export {
+	type AppendedEvent,
+	appendEvent,
+	type DateRange,
+	type QuerySessionOptions,
+	querySession,
+	searchSessions,
+	type SearchSessionsOptions,
+	type SessionEvent,
+	type SessionSearchHit,
+	type SessionStoreOptions,
+	type StoredSessionEvent,
+	type VacuumSessionsOptions,
+	type VacuumSessionsResult,
+	vacuumSessions,
+} from "./store";
+
+// =============================================================================
+// Artifact store
+// =============================================================================
+
+export {
+	type ArtifactStoreOptions,
+	gcArtifacts,
+	type GcArtifactsPolicy,
+	type GcArtifactsResult,
+	getArtifact,
+	putArtifact,
+	type StoredArtifact,
+} from "./artifacts";
+
+// =============================================================================
+// Edit journal
+// =============================================================================
+
+export {
+	type BeginOperationOptions,
+	beginOperation,
+	type EditJournalOptions,
+	type JournalOperation,
+	type ListOperationsOptions,
+	listRecentOperations,
+	type OperationFile,
+	setEditJournal,
+	type UndoOperationOptions,
+	undoOperation,
+} from "./journal";
+
+// =============================================================================
+// Checkpoints
+// =============================================================================
+
+export {
+	type Checkpoint,
+	type CheckpointChange,
+	type CreateCheckpointOptions,
+	createCheckpoint,
+	type DiffCheckpointOptions,
+	diffCheckpoint,
+	type RestoreCheckpointOptions,
+	restoreCheckpoint,
+} from "./checkpoint";
+
+// =============================================================================
+// Diagnostics parsing
+// =============================================================================
+
+export {
+	type Diagnostic,
+	type DiagnosticOptions,
+	DiagnosticParser,
+	type DiagnosticTool,
+	parseDiagnostics,
+} from "./diagnostics";
+
+// =============================================================================
+// LSP client
+// =============================================================================
+
+export {
+	lspCloseFile,
+	lspDefinition,
+	type LspDiagnostic,
+	lspDiagnostics,
+	type LspDiagnosticsOptions,
+	type LspFileEdit,
+	type LspFileOperation,
+	type LspHover,
+	lspHover,
+	type LspLocation,
+	lspOpenFile,
+	type LspOpenOptions,
+	type LspPosition,
+	type LspRange,
+	lspReferences,
+	lspRename,
+	type LspServerInfo,
+	lspStart,
+	type LspStartOptions,
+	lspStop,
+	type LspTextEdit,
+	type LspWorkspaceEdit,
+} from "./lsp";
+
+// =============================================================================
+// Formatting
+// =============================================================================
+
+export {
+	formatFile,
+	type FormatLineRange,
+	type FormatOptions,
+	type FormatResult,
+	type FormatterName,
+} from "./format";
+
+// =============================================================================
+// Three-way merge
+// =============================================================================
+
+export {
+	type MergeConflict,
+	type MergeLabels,
+	type MergeOptions,
+	type MergeResult,
+	type MergeStyle,
+	threeWayMerge,
+} from "./merge";
+
+// =============================================================================
+// Diff rendering
+// =============================================================================
+
+export {
+	type DiffMode,
+	type DiffRow,
+	type DiffRowLine,
+	type DiffSpan,
+	type RenderDiffOptions,
+	type RenderDiffResult,
+	renderDiff,
+} from "./diff";
+
+// =============================================================================
+// Fuzzy matching
+// =============================================================================
+
+export { type FuzzyMatch, fuzzyMatch, type FuzzyMatchOptions } from "./fuzzy";
+
+// =============================================================================
+// Directory tree
+// =============================================================================
+
+export { dirTree, type DirTreeOptions, type DirTreeResult } from "./dir-tree";
+
+// =============================================================================
+// Project detection
+// =============================================================================
+
+export {
+	detectProject,
+	type MonorepoInfo,
+	type ProjectCommand,
+	type ProjectInfo,
+	type WorkspacePackage,
+} from "./project";
+export { detectPackageManager, type PackageManager } from "./package-manager";
+export { detectToolchains, type Toolchain, type ToolchainOptions } from "./toolchain";
+export { type ResolveCommandOptions, type ResolvedCommand, resolveCommand } from "./which";
+
+// =============================================================================
+// Git
+// =============================================================================
+
+export {
+	type GitBlame,
+	gitBlame,
+	type GitBlameLine,
+	type GitBlameOptions,
+	GitChangeKind,
+	type GitCommit,
+	type GitDiff,
+	gitDiff,
+	type GitDiffOptions,
+	type GitFileDiff,
+	type GitLineRange,
+	gitLog,
+	type GitLogOptions,
+	type GitStatus,
+	gitStatus,
+	type GitStatusEntry,
+	type GitStatusOptions,
+} from "./git";
+
+// =============================================================================
+// Syntax
+// =============================================================================
+
+export {
+	type FileOutline,
+	fileOutline,
+	type NodeAtPosition,
+	nodeAtPosition,
+	type OutlineSymbol,
+	SymbolKind,
+	type SyntaxNode,
+} from "./syntax";
+
+// =============================================================================
+// Symbol index
+// =============================================================================
+
+export {
+	closeWorkspaceIndex,
+	type IndexWorkspaceOptions,
+	indexWorkspace,
+	type QuerySymbolsOptions,
+	querySymbols,
+	type SymbolMatch,
+	type WorkspaceIndexStats,
+} from "./symbols";
+
+// =============================================================================
+// Unified diff application
+// =============================================================================
+
+export {
+	type ApplyDiffOptions,
+	type ApplyDiffResult,
+	applyUnifiedDiff,
+	FilePatchKind,
+	type FilePatchResult,
+	type HunkResult,
+} from "./patch";
+
 // =============================================================================
 // System info
 // =============================================================================
 
-export { getSystemInfo, type SystemInfo } from "./system-info";
+export {
+	checkDiskSpace,
+	type DiskSpace,
+	type EnvironmentInfo,
+	findFreePort,
+	type GpuInfo,
+	getNetworkInfo,
+	getPowerInfo,
+	getSystemInfo,
+	getThermalInfo,
+	isPortFree,
+	killPortOwners,
+	type LatencyAttempt,
+	type LatencyProbe,
+	type LatencyProbeOptions,
+	type ListeningPort,
+	listListeningPorts,
+	type LocaleInfo,
+	type NetworkInfo,
+	type NetworkInfoOptions,
+	type NetworkInterface,
+	pidsListeningOn,
+	type PortRange,
+	type PowerEvent,
+	type PowerEventOptions,
+	type PowerInfo,
+	probeLatency,
+	type ResolvedHost,
+	resolveHost,
+	type ResolveHostOptions,
+	subscribePowerEvents,
+	type SystemInfo,
+	type ThermalInfo,
+	type ThermalSensor,
+	unsubscribePowerEvents,
+	type UserInfo,
+} from "./system-info";
 
 // =============================================================================
 // Shell execution (brush-core)
 // =============================================================================
 
 export {
+	analyzeShellCommand,
+	attachExecution,
+	type AttachExecutionOptions,
+	type AuditLogEntry,
+	type AuditLogFilter,
+	type AuditLogOptions,
+	type AuditOutcome,
+	buildCommand,
+	type BuildCommandOptions,
+	cancelShellExecution,
+	checkShellSyntax,
+	type CommandLineCompletions,
+	type CommandPolicy,
+	type CommandPolicyAction,
+	type CommandPolicyDecision,
+	type CommandPolicyOptions,
+	type CommandPolicyRule,
+	completeCommandLine,
+	type CompleteCommandLineOptions,
+	type DetachedExecution,
+	type DetachedShellOptions,
+	evaluateCommandPolicy,
+	type ExecutionOutputOptions,
 	executeShell,
+	executeShellDetached,
+	expandShellWords,
+	type ExpandShellWordsOptions,
+	getExecutionOutput,
+	listDetachedExecutions,
+	listShellExecutions,
+	queryAuditLog,
+	type RedactionOptions,
+	type RedactionRule,
+	setAuditLog,
+	setCommandPolicy,
+	setMaxConcurrentExecutions,
+	setRedactionRules,
 	Shell,
+	type ShellAnalyzedAssignment,
+	type ShellAnalyzedCommand,
+	type ShellAnalyzedRedirect,
+	type ShellBuiltinPolicy,
+	type ShellCommandAnalysis,
+	ShellDialect,
+	type ShellEnvironment,
 	type ShellExecuteOptions,
 	type ShellExecuteResult,
+	type ShellExecutionInfo,
+	type ShellExecutionOutput,
+	type ShellInputPrompt,
+	type ShellIsolatedHome,
+	type ShellJsonLine,
+	type ShellNetworkAllowlist,
 	type ShellOptions,
+	type ShellOutputChunk,
+	shellQuote,
+	ShellRedirectKind,
+	type ShellResourceLimits,
+	type ShellResourceUsage,
+	type ShellRisk,
+	ShellRiskKind,
 	type ShellRunOptions,
 	type ShellRunResult,
+	type ShellSandbox,
+	type ShellStateDelta,
+	type ShellSyntaxError,
+	type ShellTermination,
+	type ShellTiming,
+	stopExecution,
+	type StopExecutionOptions,
 } from "./shell";
 
 // =============================================================================
@@ -122,7 +587,33 @@ export { type PtyRunResult, PtySession, type PtyStartOptions } from "./pty";
 // Process management
 // =============================================================================
 
-export { killTree, listDescendants } from "./ps";
+export {
+	enableSubreaper,
+	KillOutcome,
+	type KillReport,
+	killTree,
+	killTreeGraceful,
+	listDescendants,
+	type ProcessNode,
+	type ProcessStats,
+	processStats,
+	processTree,
+	processTreeStats,
+	type ReapedProcess,
+	reapZombies,
+} from "./ps";
+
+// =============================================================================
+// Filesystem watching
+// =============================================================================
+
+export { unwatch, type WatchEvent, WatchEventKind, type WatchOptions, watchPaths } from "./watch";
+
+// =============================================================================
+// Log tailing
+// =============================================================================
+
+export { stopTail, type TailEvent, type TailOptions, tailFile } from "./tail";
 
 // =============================================================================
 // Work profiling
@@ -0,0 +1,16 @@
+/**
+ * Undo journal for file edits.
+ */
+
+import { native } from "../native";
+
+export type {
+	BeginOperationOptions,
+	EditJournalOptions,
+	JournalOperation,
+	ListOperationsOptions,
+	OperationFile,
+	UndoOperationOptions,
+} from "./types";
+
+export const { beginOperation, listRecentOperations, setEditJournal, undoOperation } = native;
@@ -0,0 +1,86 @@
+/**
+ * Types for the undo journal of file edits.
+ */
+
+/** Options for `setEditJournal`. */
+export interface EditJournalOptions {
+	/** Journal file edits (default: true). */
+	enabled?: boolean;
+	/** Database file (default: the session store, as in `SessionStoreOptions`). */
+	dbPath?: string;
+	/** Artifact store for prior contents (default: `~/.omp/agent/artifacts`). */
+	artifactsDir?: string;
+}
+
+/** Options for `beginOperation`. */
+export interface BeginOperationOptions {
+	/** What the operation is, e.g. the tool call it groups. */
+	label?: string;
+}
+
+/** Options for `undoOperation`. */
+export interface UndoOperationOptions {
+	/** Restore files even if they changed after the operation (default: false). */
+	force?: boolean;
+}
+
+/** Options for `listRecentOperations`. */
+export interface ListOperationsOptions {
+	/** Maximum number of operations (default: 20). */
+	limit?: number;
+	/** Leave out operations that were undone (default: false). */
+	excludeUndone?: boolean;
+}
+
+/** A file changed by an operation. */
+export interface OperationFile {
+	/** Absolute path of the file. */
+	path: string;
+	/** Artifact id of the content before the operation; absent when the file did not exist. */
+	before?: string;
+}
+
+/** A journaled operation. */
+export interface JournalOperation {
+	/** Id to pass to `undoOperation`. */
+	id: number;
+	/** Label given to `beginOperation`, or the function that wrote the files. */
+	label?: string;
+	/** Files changed, in the order they were first changed. */
+	files: OperationFile[];
+	/** Creation time in milliseconds since the Unix epoch. */
+	createdAt: number;
+	/** When the operation was undone, in milliseconds since the Unix epoch. */
+	undoneAt?: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Turn the edit journal on or off, or move it to another database or artifact store.
+		 * @param options Journal settings.
+		 */
+		setEditJournal(options: EditJournalOptions): void;
+		/**
+		 * Open an operation for `writeFileAtomic` and `applyUnifiedDiff` calls to share through
+		 * `operationId`, so they are undone together.
+		 * @param options Label of the operation.
+		 * @returns The operation id.
+		 */
+		beginOperation(options?: BeginOperationOptions | null): Promise<number>;
+		/**
+		 * Put every file changed by an operation back the way it was before it: prior contents are
+		 * restored, created files removed. Rejects with a `Conflict:` error, restoring nothing, when a
+		 * file changed after the operation and `force` is not set.
+		 * @param id Operation id.
+		 * @param options Conflict handling.
+		 * @returns The undone operation.
+		 */
+		undoOperation(id: number, options?: UndoOperationOptions | null): Promise<JournalOperation>;
+		/**
+		 * Journaled operations, newest first.
+		 * @param options Limit and filter.
+		 */
+		listRecentOperations(options?: ListOperationsOptions | null): Promise<JournalOperation[]>;
+	}
+}
@@ -0,0 +1,33 @@
+/**
+ * Language server client: definitions, references, hover, rename and diagnostics.
+ */
+
+import { native } from "../native";
+
+export type {
+	LspDiagnostic,
+	LspDiagnosticsOptions,
+	LspFileEdit,
+	LspFileOperation,
+	LspHover,
+	LspLocation,
+	LspOpenOptions,
+	LspPosition,
+	LspRange,
+	LspServerInfo,
+	LspStartOptions,
+	LspTextEdit,
+	LspWorkspaceEdit,
+} from "./types";
+
+export const {
+	lspCloseFile,
+	lspDefinition,
+	lspDiagnostics,
+	lspHover,
+	lspOpenFile,
+	lspReferences,
+	lspRename,
+	lspStart,
+	lspStop,
+} = native;
@@ -0,0 +1,164 @@
+/**
+ * Types for the language server client.
+ */
+
+/** Options for starting a language server. */
+export interface LspStartOptions {
+	/** Server executable. */
+	command: string;
+	/** Server arguments. */
+	args?: string[];
+	/** Workspace root, also the server's working directory. */
+	rootPath: string;
+	/** Environment variables to add for the server. */
+	env?: Record<string, string>;
+	/** Server-specific `initializationOptions`. */
+	initializationOptions?: unknown;
+	/** Settings sent with `workspace/didChangeConfiguration` and used to answer `workspace/configuration`, keyed by section. */
+	settings?: Record<string, unknown>;
+	/** Timeout in milliseconds for the server to answer `initialize` (default: 30000). */
+	timeoutMs?: number;
+	/** Re-sync open files when they are saved to disk (default: true). */
+	watch?: boolean;
+}
+
+/** A started language server. */
+export interface LspServerInfo {
+	/** Id for the other `lsp*` calls. */
+	id: number;
+	/** Server process id. */
+	pid?: number;
+	/** Name the server reported. */
+	name?: string;
+	/** Version the server reported. */
+	version?: string;
+	/** Capabilities the server reported. */
+	capabilities: Record<string, unknown>;
+}
+
+/** Position in a document. */
+export interface LspPosition {
+	/** 0-based line. */
+	line: number;
+	/** 0-based offset in UTF-16 code units. */
+	character: number;
+}
+
+/** Range in a document; `end` is exclusive. */
+export interface LspRange {
+	start: LspPosition;
+	end: LspPosition;
+}
+
+/** Range in a file. */
+export interface LspLocation {
+	/** Absolute path of the file. */
+	path: string;
+	range: LspRange;
+}
+
+/** Hover information. */
+export interface LspHover {
+	/** Hover text, usually Markdown. */
+	contents: string;
+	/** Range the hover applies to. */
+	range?: LspRange;
+}
+
+/** Replacement of a range of text. */
+export interface LspTextEdit {
+	range: LspRange;
+	newText: string;
+}
+
+/** Edits to one file. */
+export interface LspFileEdit {
+	/** Absolute path of the file. */
+	path: string;
+	/** Edits, all relative to the current content of the file. */
+	edits: LspTextEdit[];
+}
+
+/** File created, renamed or deleted by a workspace edit. */
+export interface LspFileOperation {
+	kind: "create" | "rename" | "delete";
+	/** Absolute path of the file (the old path for renames). */
+	path: string;
+	/** New path, for renames. */
+	newPath?: string;
+}
+
+/** Changes computed by the server; not applied. */
+export interface LspWorkspaceEdit {
+	files: LspFileEdit[];
+	/** File operations, in the order the server listed them. */
+	operations: LspFileOperation[];
+}
+
+/** Diagnostic published by the server. */
+export interface LspDiagnostic {
+	/** Absolute path of the file. */
+	path: string;
+	range: LspRange;
+	severity: "error" | "warning" | "information" | "hint";
+	message: string;
+	/** Tool that produced the diagnostic, e.g. `rustc` or `ts`. */
+	source?: string;
+	code?: string;
+}
+
+/** Options for opening a file. */
+export interface LspOpenOptions {
+	/** Content to sync instead of the file on disk. */
+	text?: string;
+	/** Language id (default: derived from the file extension). */
+	languageId?: string;
+}
+
+/** Options for reading diagnostics. */
+export interface LspDiagnosticsOptions {
+	/** Only this file's diagnostics, opening it if needed (default: all files). */
+	path?: string;
+	/** Wait up to this many milliseconds for the server to publish diagnostics for the latest content of `path`. */
+	waitMs?: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Start a language server and complete the `initialize` handshake.
+		 * @param options Server command, workspace root and settings.
+		 */
+		lspStart(options: LspStartOptions): Promise<LspServerInfo>;
+		/**
+		 * Shut a language server down, killing its processes if it does not exit in time.
+		 * Unknown ids are ignored.
+		 */
+		lspStop(id: number): Promise<void>;
+		/**
+		 * Open a file on the server, or sync its new content if already open. Paths are
+		 * resolved against the workspace root.
+		 */
+		lspOpenFile(id: number, path: string, options?: LspOpenOptions | null): Promise<void>;
+		/** Close a file on the server. */
+		lspCloseFile(id: number, path: string): Promise<void>;
+		/** Locations where the symbol at `position` is defined. Opens the file if needed. */
+		lspDefinition(id: number, path: string, position: LspPosition): Promise<LspLocation[]>;
+		/**
+		 * Locations referencing the symbol at `position`.
+		 * @param includeDeclaration Include the declaration itself (default: true).
+		 */
+		lspReferences(
+			id: number,
+			path: string,
+			position: LspPosition,
+			includeDeclaration?: boolean | null,
+		): Promise<LspLocation[]>;
+		/** Hover information for the symbol at `position`, or `null` if there is none. */
+		lspHover(id: number, path: string, position: LspPosition): Promise<LspHover | null>;
+		/** Edits renaming the symbol at `position` across the workspace. The edits are returned, not applied. */
+		lspRename(id: number, path: string, position: LspPosition, newName: string): Promise<LspWorkspaceEdit>;
+		/** Diagnostics the server published, for one file or all files, sorted by path. */
+		lspDiagnostics(id: number, options?: LspDiagnosticsOptions | null): Promise<LspDiagnostic[]>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Terminal markdown rendering.
+ */
+
+import { native } from "../native";
+
+export type { RenderMarkdownOptions } from "./types";
+
+export const { renderMarkdown } = native;
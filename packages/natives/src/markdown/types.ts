@@ -0,0 +1,28 @@
+/**
+ * Types for terminal markdown rendering.
+ */
+
+/** Options for rendering markdown to the terminal. */
+export interface RenderMarkdownOptions {
+	/** Width to wrap to, in cells (default: 80). */
+	width?: number;
+	/** Theme for code blocks, from `getHighlightThemes` (default: `base16-ocean.dark`). */
+	theme?: string;
+	/** Write links as OSC 8 hyperlinks instead of following them with their URL (default: false). */
+	hyperlinks?: boolean;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Render markdown as ANSI text, laid out like the TUI `Markdown` component.
+		 * Supports headings, lists (including task lists), block quotes, tables, highlighted code
+		 * blocks, emphasis, code spans and links.
+		 * @param markdown Markdown source.
+		 * @param options Wrap width, code theme and hyperlinks.
+		 * @returns Lines joined with "\n", wrapped to `width`.
+		 * @throws For an unknown code block theme.
+		 */
+		renderMarkdown(markdown: string, options?: RenderMarkdownOptions | null): string;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Three-way merge of concurrent edits.
+ */
+
+import { native } from "../native";
+
+export type { MergeConflict, MergeLabels, MergeOptions, MergeResult, MergeStyle } from "./types";
+
+export const { threeWayMerge } = native;
@@ -0,0 +1,57 @@
+/**
+ * Types for three-way text merging.
+ */
+
+/** Conflict marker style, as in git's `merge.conflictStyle`. */
+export type MergeStyle = "merge" | "diff3" | "zdiff3";
+
+/** Conflict marker labels. */
+export interface MergeLabels {
+	/** Label after `<<<<<<<` (default: "ours"). */
+	ours?: string;
+	/** Label after `|||||||` (default: "base"). */
+	base?: string;
+	/** Label after `>>>>>>>` (default: "theirs"). */
+	theirs?: string;
+}
+
+/** Options for a three-way merge. */
+export interface MergeOptions {
+	/** Conflict style (default: "merge"); `diff3` and `zdiff3` include the base lines. */
+	style?: MergeStyle;
+	/** Resolve conflicts by taking this side, or both (ours first), instead of writing markers. */
+	favor?: "ours" | "theirs" | "union";
+	/** Conflict marker labels. */
+	labels?: MergeLabels;
+}
+
+/** A conflict in the merged text. */
+export interface MergeConflict {
+	/** Line of the `<<<<<<<` marker, 1-indexed. */
+	start: number;
+	/** Line of the `>>>>>>>` marker, 1-indexed. */
+	end: number;
+}
+
+/** Result of a three-way merge. */
+export interface MergeResult {
+	/** Merged text, with conflict markers unless `clean`. */
+	text: string;
+	/** Whether every change merged without conflict. */
+	clean: boolean;
+	/** Conflicts left in `text`, in order. */
+	conflicts: MergeConflict[];
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Merge the changes `ours` and `theirs` made to `base`, line by line, like `git merge-file`.
+		 * @param base Common ancestor, e.g. the text the edit was made against.
+		 * @param ours One changed version, e.g. the edited text.
+		 * @param theirs The other changed version, e.g. the file now on disk.
+		 * @param options Conflict style, resolution and labels.
+		 */
+		threeWayMerge(base: string, ours: string, theirs: string, options?: MergeOptions | null): Promise<MergeResult>;
+	}
+}
@@ -16,19 +16,59 @@ import type { NativeBindings } from "./bindings";
 import { embeddedAddon } from "./embedded-addon";
 
 // Import types to trigger declaration merging
+import "./archive/types";
+import "./artifacts/types";
+import "./checkpoint/types";
 import "./clipboard/types";
+import "./diagnostics/types";
+import "./diff/types";
+import "./dir-tree/types";
+import "./fetch/types";
+import "./format/types";
+import "./fuzzy/types";
+import "./git/types";
 import "./glob/types";
+import "./glob-expand/types";
 import "./grep/types";
+import "./hash/types";
 import "./highlight/types";
 import "./html/types";
+import "./ignore-rules/types";
 import "./image/types";
+import "./journal/types";
 import "./keys/types";
+import "./lsp/types";
+import "./markdown/types";
+import "./merge/types";
+import "./notify/types";
+import "./package-manager/types";
+import "./patch/types";
+import "./pdf/types";
+import "./project/types";
 import "./ps/types";
 import "./pty/types";
+import "./read/types";
+import "./ready/types";
+import "./remove/types";
+import "./secret-scan/types";
+import "./secrets/types";
 import "./shell/types";
+import "./store/types";
+import "./symbols/types";
+import "./syntax/types";
 import "./system-info/types";
+import "./tail/types";
+import "./tempdir/types";
+import "./term/types";
 import "./text/types";
+import "./tokens/types";
+import "./toolchain/types";
+import "./trash/types";
+import "./watch/types";
+import "./which/types";
+import "./write/types";
 import "./work/types";
+import "./workspace/types";
 
 export type { NativeBindings, TsFunc } from "./bindings";
 
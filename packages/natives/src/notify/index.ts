@@ -0,0 +1,9 @@
+/**
+ * Desktop notifications for long-running tasks.
+ */
+
+import { native } from "../native";
+
+export type { NotificationOptions, NotificationUrgency } from "./types";
+
+export const { sendNotification } = native;
@@ -0,0 +1,32 @@
+/**
+ * Types for desktop notifications.
+ */
+
+/** Notification urgency. */
+export type NotificationUrgency = "low" | "normal" | "critical";
+
+/** Options for a desktop notification. */
+export interface NotificationOptions {
+	/** Notification title. */
+	title: string;
+	/** Notification text. */
+	body?: string;
+	/** Urgency (default: `normal`). Critical notifications stay visible until dismissed on Linux; ignored on macOS. */
+	urgency?: NotificationUrgency;
+	/** Application name shown with the notification (default: `omp`). */
+	appName?: string;
+	/** How long the notification stays visible, in milliseconds. Only honored on Linux. */
+	timeoutMs?: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Show a desktop notification via D-Bus (Linux/BSD), Notification Center (macOS) or toast
+		 * (Windows). Resolves once the notification service accepted it; rejects when none is
+		 * available.
+		 * @param options Title, body, urgency and display options.
+		 */
+		sendNotification(options: NotificationOptions): Promise<void>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Package manager detection with lockfile-aware commands.
+ */
+
+import { native } from "../native";
+
+export type { PackageManager } from "./types";
+
+export const { detectPackageManager } = native;
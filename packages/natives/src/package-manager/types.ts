@@ -0,0 +1,41 @@
+/**
+ * Types for package manager detection.
+ */
+
+/** A package manager governing a directory. */
+export interface PackageManager {
+	/** Manager name: "npm", "pnpm", "yarn", "bun", "deno", "uv", "poetry", "pdm", "pipenv", "pip", "cargo", "go", "bundler" or "composer". */
+	name: string;
+	/** Ecosystem the manager belongs to. */
+	ecosystem: "javascript" | "python" | "rust" | "go" | "ruby" | "php";
+	/** Directory the manager works from, holding its lockfile or manifest. */
+	root: string;
+	/** Lockfile name in `root`, when there is one. */
+	lockfile?: string;
+	/** Install the declared dependencies. */
+	install: string;
+	/** Install exactly what the lockfile records, failing if it is out of date (for CI); absent without a lockfile. */
+	frozenInstall?: string;
+	/** Add a dependency; `<package>` stands for its name. */
+	add: string;
+	/** Add a development dependency. */
+	addDev?: string;
+	/** Remove a dependency. */
+	remove: string;
+	/** Run a script defined by the project; `<script>` stands for its name. */
+	run?: string;
+	/** Run a command with the project's dependencies available; `<command>` stands for it. */
+	exec?: string;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Detect the package managers governing a directory from lockfiles and workspace configuration,
+		 * searching up to the repository root, with their canonical commands.
+		 * @param root Directory to detect for, e.g. a workspace member.
+		 * @returns Managers nearest first; the first is the one to use. Empty when no manifest is found.
+		 */
+		detectPackageManager(root: string): Promise<PackageManager[]>;
+	}
+}
@@ -0,0 +1,10 @@
+/**
+ * Unified diff application without external `git apply` / `patch` binaries.
+ */
+
+import { native } from "../native";
+
+export type { ApplyDiffOptions, ApplyDiffResult, FilePatchResult, HunkResult } from "./types";
+export { FilePatchKind } from "./types";
+
+export const { applyUnifiedDiff } = native;
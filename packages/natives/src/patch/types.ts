@@ -0,0 +1,88 @@
+/**
+ * Types for native unified diff application.
+ */
+
+/** Options for applying a unified diff. */
+export interface ApplyDiffOptions {
+	/** Unified diff text, possibly covering several files. */
+	diff: string;
+	/** Directory that paths in the diff are relative to. */
+	root: string;
+	/** Maximum number of leading/trailing context lines that may be ignored when locating a hunk (default: 0). */
+	fuzz?: number;
+	/** Compute results without writing anything (default: false). */
+	dryRun?: boolean;
+	/** Journal the changes under this operation from `beginOperation` (default: an operation of their own). */
+	operationId?: number;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** What a file patch does to its file. */
+export const enum FilePatchKind {
+	/** Existing file is edited in place. */
+	Modify = 1,
+	/** File is created. */
+	Create = 2,
+	/** File is deleted. */
+	Delete = 3,
+	/** File is moved from `oldPath`, possibly with edits. */
+	Rename = 4,
+}
+
+/** Outcome of a single hunk. */
+export interface HunkResult {
+	/** Starting line of the hunk in the original file, from its header. */
+	oldStart: number;
+	/** Starting line of the hunk in the new file, from its header. */
+	newStart: number;
+	/** Whether the hunk applied. */
+	applied: boolean;
+	/** 1-indexed line in the original file where the hunk matched. */
+	line?: number;
+	/** Lines between the header position and where the hunk matched. */
+	offset: number;
+	/** Context lines ignored to make the hunk match. */
+	fuzz: number;
+	/** Why the hunk failed to apply. */
+	error?: string;
+}
+
+/** Outcome of the patch for one file. */
+export interface FilePatchResult {
+	/** Target path relative to the root (the deleted path for deletions). */
+	path: string;
+	/** Source path for renames. */
+	oldPath?: string;
+	/** What the patch does to the file. */
+	kind: FilePatchKind;
+	/** Whether every hunk applied and the file-level checks passed. */
+	applied: boolean;
+	/** Per-hunk results, in diff order. */
+	hunks: HunkResult[];
+	/** Resulting file contents with every applicable hunk applied; absent for deletions and file-level errors. */
+	content?: string;
+	/** File-level error (missing file, unsafe path, binary patch, ...). */
+	error?: string;
+}
+
+/** Result of applying a unified diff. */
+export interface ApplyDiffResult {
+	/** Whether every file applied cleanly. Files are written only when true and `dryRun` is not set. */
+	applied: boolean;
+	/** Per-file results, in diff order. */
+	files: FilePatchResult[];
+	/** Operation the changes were journaled under; absent when nothing was written or the journal is off. */
+	operationId?: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Apply a unified diff to files under `root`.
+		 * Files are only written when every hunk of every file applies.
+		 * @param options Diff text, root directory, fuzz and dry-run options.
+		 */
+		applyUnifiedDiff(options: ApplyDiffOptions): Promise<ApplyDiffResult>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * PDF text extraction with per-page layout hints.
+ */
+
+import { native } from "../native";
+
+export type { PdfPage, PdfTextOptions, PdfTextResult } from "./types";
+
+export const { extractPdfText } = native;
@@ -0,0 +1,56 @@
+/**
+ * Types for PDF text extraction.
+ */
+
+import type { Cancellable } from "../bindings";
+
+/** Options for extracting text from a PDF. */
+export interface PdfTextOptions extends Cancellable {
+	/** 1-based page numbers to extract (default: all pages). */
+	pages?: number[];
+	/** Extract at most this many pages; the rest are reported as `truncated`. */
+	maxPages?: number;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** Text and layout hints of one PDF page. */
+export interface PdfPage {
+	/** 1-based page number. */
+	page: number;
+	/** Page text, with lines separated by `\n` and paragraphs by a blank line. */
+	text: string;
+	/** Page width in points. */
+	width: number;
+	/** Page height in points. */
+	height: number;
+	/** Most common font size on the page, in points (0 for pages without text). */
+	fontSize: number;
+	/** Lines set in a noticeably larger font than the body text, in page order. */
+	headings: string[];
+	/** Why the page could not be parsed; `text` is empty when set. */
+	error?: string;
+}
+
+/** Result of extracting text from a PDF. */
+export interface PdfTextResult {
+	/** Total pages in the document. */
+	pageCount: number;
+	/** Extracted pages, in page order. */
+	pages: PdfPage[];
+	/** Whether requested pages were left out because of `maxPages`. */
+	truncated: boolean;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Extract the text of a PDF page by page, with page size, body font size and headings.
+		 * Pages that fail to parse carry an `error`; unreadable, non-PDF and password-protected
+		 * files reject.
+		 * @param path PDF file to read.
+		 * @param options Page selection, page limit and cancellation.
+		 */
+		extractPdfText(path: string, options?: PdfTextOptions | null): Promise<PdfTextResult>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Project language, framework and tooling detection.
+ */
+
+import { native } from "../native";
+
+export type { MonorepoInfo, ProjectCommand, ProjectInfo, WorkspacePackage } from "./types";
+
+export const { detectProject } = native;
@@ -0,0 +1,60 @@
+/**
+ * Types for project detection.
+ */
+
+/** A command for working on the project. */
+export interface ProjectCommand {
+	/** What the command does. */
+	kind: "build" | "test" | "lint" | "format" | "typecheck" | "dev";
+	/** Shell command, run from the project root. */
+	command: string;
+	/** Manifest the command comes from, relative to the root. */
+	source: string;
+}
+
+/** A package in a monorepo. */
+export interface WorkspacePackage {
+	/** Package name from its manifest, when declared. */
+	name?: string;
+	/** Directory relative to the root, using forward slashes. */
+	path: string;
+	/** Manifest file name (e.g. "Cargo.toml", "package.json"). */
+	manifest: string;
+}
+
+/** Monorepo layout. */
+export interface MonorepoInfo {
+	/** Workspace mechanisms in use (e.g. "cargo", "pnpm", "turbo", "nx"). */
+	tools: string[];
+	/** Member packages, sorted by path. */
+	packages: WorkspacePackage[];
+}
+
+/** Languages, tooling and layout of a project. */
+export interface ProjectInfo {
+	/** Languages, those of root manifests first (e.g. "rust", "typescript"). */
+	languages: string[];
+	/** Frameworks and notable libraries (e.g. "react", "tokio", "pytest"). */
+	frameworks: string[];
+	/** Package managers and build tools (e.g. "cargo", "pnpm", "uv"). */
+	packageManagers: string[];
+	/** Commands for working on the project, root manifests first. */
+	commands: ProjectCommand[];
+	/** CI systems configured (e.g. "github-actions"). */
+	ci: string[];
+	/** Workspace layout, when the root is a monorepo. */
+	monorepo?: MonorepoInfo;
+	/** Manifests and lockfiles found at the root. */
+	manifests: string[];
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Detect the languages, frameworks, package managers, commands, CI and monorepo layout of a project from its
+		 * manifests, lockfiles and CI configuration. Nothing is executed.
+		 * @param root Project root directory.
+		 */
+		detectProject(root: string): Promise<ProjectInfo>;
+	}
+}
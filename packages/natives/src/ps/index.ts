@@ -7,4 +7,16 @@ import { native } from "../native";
 
 setNativeKillTree(native.killTree);
 
-export const { killTree, listDescendants } = native;
+export type { KillReport, ProcessNode, ProcessStats, ReapedProcess } from "./types";
+export { KillOutcome } from "./types";
+
+export const {
+	enableSubreaper,
+	killTree,
+	killTreeGraceful,
+	listDescendants,
+	processStats,
+	processTree,
+	processTreeStats,
+	reapZombies,
+} = native;
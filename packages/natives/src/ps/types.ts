@@ -2,7 +2,73 @@
  * Types for process management.
  */
 
-export {};
+/** Outcome of a graceful tree kill for a single process. */
+export const enum KillOutcome {
+	/** The process was already gone before it could be signalled. */
+	NotFound = 1,
+	/** The process exited within the grace period after SIGTERM. */
+	Terminated = 2,
+	/** The process outlived the grace period and was SIGKILLed. */
+	Killed = 3,
+	/** The process is still alive after SIGKILL (e.g. permission denied). */
+	Survived = 4,
+}
+
+/** Per-process result of `killTreeGraceful`. */
+export interface KillReport {
+	/** Process id. */
+	pid: number;
+	/** What happened to the process. */
+	outcome: KillOutcome;
+}
+
+/** Snapshot of a process and its descendants. */
+export interface ProcessNode {
+	/** Process id. */
+	pid: number;
+	/** Parent process id, if known. */
+	ppid?: number;
+	/** Executable name. */
+	name: string;
+	/** Full command line (argv). */
+	cmdline: string[];
+	/** Resident set size in bytes. */
+	rssBytes: number;
+	/** CPU usage in percent over a short sample; can exceed 100 on multi-core machines. */
+	cpu: number;
+	/** Start time in milliseconds since the Unix epoch. */
+	startTime: number;
+	/** Direct children. */
+	children: ProcessNode[];
+}
+
+/** Resource usage of a process, or the sum over a process tree. */
+export interface ProcessStats {
+	/** Root process id. */
+	pid: number;
+	/** Number of processes included (1 for a single process). */
+	processCount: number;
+	/** User plus system CPU time in milliseconds. */
+	cpuTimeMs: number;
+	/** Resident set size in bytes. */
+	rssBytes: number;
+	/** Open file descriptors (handles on Windows), when the platform reports them. */
+	openFiles?: number;
+	/** Bytes read from storage since the process started. */
+	readBytes: number;
+	/** Bytes written to storage since the process started. */
+	writtenBytes: number;
+}
+
+/** An adopted orphan reaped by `reapZombies`. */
+export interface ReapedProcess {
+	/** Process id. */
+	pid: number;
+	/** Exit code, when the process exited normally. */
+	exitCode?: number;
+	/** Terminating signal, when the process was killed by one. */
+	signal?: number;
+}
 
 declare module "../bindings" {
 	/** Native process-management bindings implemented in pi-natives. */
@@ -14,11 +80,49 @@ declare module "../bindings" {
 		 * @returns Number of processes successfully killed.
 		 */
 		killTree(pid: number, signal: number): number;
+		/**
+		 * SIGTERM a process tree, wait up to `graceMs`, then SIGKILL stragglers.
+		 * Descendants spawned during the grace period are killed as well.
+		 * @param pid Root process id.
+		 * @param graceMs Milliseconds to wait for a clean exit before escalating.
+		 * @returns One report per process in the tree.
+		 */
+		killTreeGraceful(pid: number, graceMs: number): Promise<KillReport[]>;
+		/**
+		 * Snapshot a process tree with name, command line, RSS, CPU% and start time.
+		 * @param pid Root process id.
+		 * @returns The root node with nested children, or null when the process doesn't exist.
+		 */
+		processTree(pid: number): Promise<ProcessNode | null>;
+		/**
+		 * Sample CPU time, RSS, open files and IO bytes of a single process.
+		 * @param pid Process id.
+		 * @returns Usage for the process, or null when it doesn't exist.
+		 */
+		processStats(pid: number): ProcessStats | null;
+		/**
+		 * Sample CPU time, RSS, open files and IO bytes summed over a process and its descendants.
+		 * @param pid Root process id.
+		 * @returns Aggregated usage, or null when the root process doesn't exist.
+		 */
+		processTreeStats(pid: number): ProcessStats | null;
 		/**
 		 * List all descendant PIDs of a process (children, grandchildren, etc.).
 		 * @param pid Root process id.
 		 * @returns Empty array when the process has no children or doesn't exist.
 		 */
 		listDescendants(pid: number): number[];
+		/**
+		 * Make this process a child subreaper (Linux only), so processes double-forked by shell
+		 * commands are re-parented to it instead of init and stay reachable by cancellation.
+		 * @returns False when the platform has no subreaper support.
+		 */
+		enableSubreaper(): boolean;
+		/**
+		 * Reap exited orphans that were re-parented to this process by subreaper mode.
+		 * Runs automatically after every shell execution; call it to collect later exits.
+		 * @returns The reaped processes with their exit status.
+		 */
+		reapZombies(): ReapedProcess[];
 	}
 }
@@ -18,6 +18,10 @@ export interface PtyStartOptions extends Cancellable {
 	cols?: number;
 	/** PTY row count. */
 	rows?: number;
+	/** Record the session as an asciinema v2 cast in the artifact store (default: false). */
+	record?: boolean;
+	/** Artifact store directory for the recording (default: `~/.omp/agent/artifacts`). */
+	recordDir?: string;
 }
 
 /**
@@ -30,6 +34,8 @@ export interface PtyRunResult {
 	cancelled: boolean;
 	/** Whether the command timed out. */
 	timedOut: boolean;
+	/** `agent://` id of the cast recording, when `record` was set; read it with `getArtifact`. */
+	recording?: string;
 }
 
 /** Stateful PTY session instance. */
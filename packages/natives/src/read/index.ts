@@ -0,0 +1,9 @@
+/**
+ * Encoding-aware file reading with binary detection and size guards.
+ */
+
+import { native } from "../native";
+
+export type { ReadFileOptions, ReadFileResult, TextProfile } from "./types";
+
+export const { readFileSmart } = native;
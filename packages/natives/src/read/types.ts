@@ -0,0 +1,67 @@
+/**
+ * Types for encoding-aware file reading.
+ */
+
+/** How a text file stores its lines, for writing edits back the same way. */
+export interface TextProfile {
+	/** Dominant line ending; `lf` for files without line breaks. */
+	eol: "lf" | "crlf";
+	/** Whether both LF and CRLF line endings occur. Such files are written with the dominant one. */
+	mixed: boolean;
+	/** Whether the file starts with a byte order mark. */
+	bom: boolean;
+	/** Whether the last line ends with a line ending. */
+	finalNewline: boolean;
+	/** Encoding label (e.g. "UTF-8", "UTF-16LE", "windows-1252"). */
+	encoding: string;
+}
+
+/** Options for reading a file. */
+export interface ReadFileOptions {
+	/** First line to return, 1-indexed (default: 1). */
+	offset?: number;
+	/** Maximum number of lines to return. */
+	limit?: number;
+	/** Maximum number of bytes to read from the file (default: 16 MiB). */
+	maxBytes?: number;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** Result of reading a file. */
+export interface ReadFileResult {
+	/** Requested lines, decoded to UTF-8 without line terminators. Empty for binary files. */
+	lines: string[];
+	/** Line number of the first entry in `lines`, 1-indexed. */
+	startLine: number;
+	/** Number of lines in the portion of the file that was read. */
+	totalLines: number;
+	/** Detected encoding label (e.g. "UTF-8", "UTF-16LE", "windows-1252"), or "binary". */
+	encoding: string;
+	/** Whether the file looks binary. */
+	binary: boolean;
+	/** File size in bytes. */
+	size: number;
+	/** Whether the file was larger than `maxBytes` and only its head was read. */
+	truncated: boolean;
+	/** Whether lines exist past the returned window. */
+	hasMore: boolean;
+	/** Hex SHA-256 of the file's bytes, usable as `expectedHash` for `writeFileAtomic`. Absent when truncated. */
+	hash?: string;
+	/**
+	 * Line ending, final newline, BOM and encoding, to pass to `writeFileAtomic`. Absent for binary files;
+	 * `finalNewline` is assumed when truncated.
+	 */
+	profile?: TextProfile;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Read a file as UTF-8 lines with encoding detection and a size guard.
+		 * @param path File to read.
+		 * @param options Line window and byte limit.
+		 */
+		readFileSmart(path: string, options?: ReadFileOptions): Promise<ReadFileResult>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Readiness probes for ports, URLs and paths.
+ */
+
+import { native } from "../native";
+
+export type { ReadyResult, WaitForReadyOptions } from "./types";
+
+export const { waitForReady } = native;
@@ -0,0 +1,44 @@
+/**
+ * Types for service readiness probes.
+ */
+
+import type { Cancellable } from "../bindings";
+
+/** What to wait for; exactly one of `port`, `url` and `path` must be given. */
+export interface WaitForReadyOptions extends Cancellable {
+	/** TCP port that must accept connections. */
+	port?: number;
+	/** Host to connect to with `port` (default: "localhost", trying each of its addresses). */
+	host?: string;
+	/** http or https URL that must answer a GET with a 2xx status. */
+	url?: string;
+	/** File or directory that must exist. */
+	path?: string;
+	/** Give up after this many milliseconds (default: 30000). */
+	timeoutMs?: number;
+	/** Delay between attempts in milliseconds (default: 250). */
+	intervalMs?: number;
+}
+
+/** Result of a successful readiness wait. */
+export interface ReadyResult {
+	/** Number of attempts, including the successful one. */
+	attempts: number;
+	/** Time until the target was ready, in milliseconds. */
+	elapsedMs: number;
+	/** HTTP status of the successful response, for `url`. */
+	status?: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Poll until a TCP port accepts connections, a URL answers with a 2xx status, or a path
+		 * exists. Each attempt is cut off after 5 seconds.
+		 * @param options Target, timeout, poll interval and abort signal.
+		 * @throws For invalid options, on abort, and when the target is not ready within `timeoutMs`
+		 * (the message includes the last failure).
+		 */
+		waitForReady(options: WaitForReadyOptions): Promise<ReadyResult>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Symlink-safe recursive delete.
+ */
+
+import { native } from "../native";
+
+export type { RemovePathOptions, RemovePathResult } from "./types";
+
+export const { removePathSafe } = native;
@@ -0,0 +1,50 @@
+/**
+ * Types for symlink-safe recursive deletes.
+ */
+
+/** Options for `removePathSafe`. */
+export interface RemovePathOptions {
+	/** Directory the path must be inside, in addition to the roots set with `setWorkspaceRoots`. */
+	root?: string;
+	/** Refuse to remove more entries than this (default: 10000). */
+	maxEntries?: number;
+	/** Report what would be removed without removing it (default: false). */
+	dryRun?: boolean;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** Result of `removePathSafe`. */
+export interface RemovePathResult {
+	/**
+	 * Paths removed, or that would be with `dryRun`, each directory after its contents. Empty when
+	 * the path does not exist.
+	 */
+	entries: string[];
+	/** Regular files and other non-directories among `entries`. */
+	files: number;
+	/** Directories among `entries`. */
+	directories: number;
+	/** Symlinks among `entries`, removed without touching their targets. */
+	symlinks: number;
+	/** Total size of the files, in bytes. */
+	bytes: number;
+	/** Whether the entries were removed. */
+	removed: boolean;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Recursively remove a path like `rm -rf`, but only inside `root` (or the workspace roots),
+		 * never the root itself, the home directory, or the working directory or its parents.
+		 * Symlinks on the way to the path are resolved before the check; symlinks inside it are
+		 * removed as links, never followed, and other filesystems are not entered. Nothing is
+		 * removed when there are more than `maxEntries` entries.
+		 * @param path File or directory to remove.
+		 * @param options Boundary, entry cap and dry run.
+		 * @returns The entries removed, or that would be with `dryRun`.
+		 */
+		removePathSafe(path: string, options?: RemovePathOptions | null): Promise<RemovePathResult>;
+	}
+}
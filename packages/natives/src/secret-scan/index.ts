@@ -0,0 +1,9 @@
+/**
+ * Secret detection and redaction.
+ */
+
+import { native } from "../native";
+
+export type { SecretFile, SecretFileScanOptions, SecretMatch, SecretScanOptions } from "./types";
+
+export const { redactSecrets, scanSecretFiles, scanSecrets } = native;
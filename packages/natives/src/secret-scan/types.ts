@@ -0,0 +1,71 @@
+/**
+ * Types for secret detection.
+ */
+
+import type { Cancellable } from "../bindings";
+
+/** Options for scanning text for secrets. */
+export interface SecretScanOptions {
+	/**
+	 * Minimum Shannon entropy, in bits per character, for values of secret-looking assignments and generic tokens
+	 * (default: 3.0).
+	 */
+	minEntropy?: number;
+	/** Rule ids to skip (e.g. "jwt"). */
+	disabledRules?: string[];
+}
+
+/** A likely secret in scanned text. */
+export interface SecretMatch {
+	/** Id of the rule that matched (e.g. "aws-access-key", "secret-assignment"). */
+	rule: string;
+	/** UTF-16 offset of the secret. */
+	start: number;
+	/** UTF-16 offset just past the secret. */
+	end: number;
+	/** Line of the secret's start, 1-indexed. */
+	line: number;
+}
+
+/** Options for scanning a directory for credential files. */
+export interface SecretFileScanOptions extends SecretScanOptions, Cancellable {
+	/** Scan every text file instead of only known credential files (default: false). */
+	allFiles?: boolean;
+	/** Skip files larger than this many bytes (default: 1 MiB). */
+	maxFileSize?: number;
+}
+
+/** Secrets found in a file. */
+export interface SecretFile {
+	/** Path relative to the scanned root, using forward slashes. */
+	path: string;
+	/** Whether the file is a known credential file (e.g. `.env`, `.npmrc`). */
+	known: boolean;
+	/** Secrets in the file, in order. */
+	matches: SecretMatch[];
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Find likely secrets in text: known token formats, private keys, passwords in URLs, and high-entropy values
+		 * assigned to secret-looking names.
+		 * @param text Text to scan, e.g. a chunk of tool output.
+		 * @param options Entropy threshold and disabled rules.
+		 */
+		scanSecrets(text: string, options?: SecretScanOptions | null): SecretMatch[];
+		/**
+		 * Replace likely secrets in text with `[REDACTED:<rule>]`.
+		 * @param text Text to redact.
+		 * @param options Entropy threshold and disabled rules.
+		 */
+		redactSecrets(text: string, options?: SecretScanOptions | null): string;
+		/**
+		 * Find credential files (`.env`, `.npmrc`, private keys, ...) under a directory, ignoring `.gitignore`, and the
+		 * secrets in them. Known credential files are listed even without matches.
+		 * @param root Directory to scan.
+		 * @param options File selection, size limit and scan options.
+		 */
+		scanSecretFiles(root: string, options?: SecretFileScanOptions | null): Promise<SecretFile[]>;
+	}
+}
@@ -0,0 +1,7 @@
+/**
+ * Credential storage in the OS keychain (Secret Service, Keychain, Credential Manager).
+ */
+
+import { native } from "../native";
+
+export const { deleteSecret, getSecret, storeSecret } = native;
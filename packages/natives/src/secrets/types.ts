@@ -0,0 +1,29 @@
+/**
+ * Types for OS keychain credential storage.
+ */
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Store a secret in the OS keychain, replacing any existing value.
+		 * @param service Service the secret belongs to.
+		 * @param key Name of the secret within the service.
+		 * @param value Secret value.
+		 */
+		storeSecret(service: string, key: string, value: string): Promise<void>;
+		/**
+		 * Read a secret from the OS keychain.
+		 * @param service Service the secret belongs to.
+		 * @param key Name of the secret within the service.
+		 * @returns The secret, or null when none is stored.
+		 */
+		getSecret(service: string, key: string): Promise<string | null>;
+		/**
+		 * Delete a secret from the OS keychain.
+		 * @param service Service the secret belongs to.
+		 * @param key Name of the secret within the service.
+		 * @returns Whether a secret was stored.
+		 */
+		deleteSecret(service: string, key: string): Promise<boolean>;
+	}
+}
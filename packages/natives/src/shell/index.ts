@@ -3,24 +3,147 @@
  */
 
 import { native } from "../native";
-import type { ShellExecuteOptions, ShellExecuteResult } from "./types";
+import type {
+	AttachExecutionOptions,
+	DetachedExecution,
+	ShellExecuteOptions,
+	ShellExecuteResult,
+	ShellInputPrompt,
+	ShellJsonLine,
+	ShellOutputChunk,
+} from "./types";
 
-export type { ShellExecuteOptions, ShellExecuteResult, ShellOptions, ShellRunOptions, ShellRunResult } from "./types";
+export type {
+	AttachExecutionOptions,
+	AuditLogEntry,
+	AuditLogFilter,
+	AuditLogOptions,
+	AuditOutcome,
+	BuildCommandOptions,
+	CommandLineCompletions,
+	CommandPolicy,
+	CommandPolicyAction,
+	CommandPolicyDecision,
+	CommandPolicyOptions,
+	CommandPolicyRule,
+	CompleteCommandLineOptions,
+	DetachedExecution,
+	DetachedShellOptions,
+	ExecutionOutputOptions,
+	ExpandShellWordsOptions,
+	RedactionOptions,
+	RedactionRule,
+	ShellAnalyzedAssignment,
+	ShellAnalyzedCommand,
+	ShellAnalyzedRedirect,
+	ShellBuiltinPolicy,
+	ShellCommandAnalysis,
+	ShellEnvironment,
+	ShellExecuteOptions,
+	ShellExecuteResult,
+	ShellExecutionInfo,
+	ShellExecutionOutput,
+	ShellInputPrompt,
+	ShellIsolatedHome,
+	ShellJsonLine,
+	ShellNetworkAllowlist,
+	ShellOptions,
+	ShellOutputChunk,
+	ShellResourceLimits,
+	ShellResourceUsage,
+	ShellRisk,
+	ShellRunOptions,
+	ShellRunResult,
+	ShellSandbox,
+	ShellStateDelta,
+	ShellSyntaxError,
+	ShellTermination,
+	ShellTiming,
+	StopExecutionOptions,
+} from "./types";
 
-export const { Shell } = native;
+export { ShellDialect, ShellRedirectKind, ShellRiskKind } from "./types";
+
+export const {
+	analyzeShellCommand,
+	buildCommand,
+	cancelShellExecution,
+	checkShellSyntax,
+	completeCommandLine,
+	evaluateCommandPolicy,
+	executeShellDetached,
+	expandShellWords,
+	getExecutionOutput,
+	listDetachedExecutions,
+	listShellExecutions,
+	queryAuditLog,
+	setAuditLog,
+	setCommandPolicy,
+	setMaxConcurrentExecutions,
+	setRedactionRules,
+	Shell,
+	shellQuote,
+	stopExecution,
+} = native;
 export type Shell = import("./types").Shell;
 
 /**
  * Execute a shell command using brush-core.
  *
  * @param options - Execution options including command, cwd, env, timeout
- * @param onChunk - Optional callback for streaming output chunks
+ * @param onChunk - Optional callback for streaming output chunks; receives `ShellOutputChunk`s when
+ * `binaryOutput` is set, `ShellJsonLine`s when `jsonLines` is set, and `ShellInputPrompt`s when
+ * `promptIdleMs` is set
  * @returns Promise resolving to execution result with exit code and status
  */
+export async function executeShell(
+	options: ShellExecuteOptions & { jsonLines: true; promptIdleMs: number },
+	onChunk?: (chunk: string | ShellJsonLine | ShellInputPrompt) => void,
+): Promise<ShellExecuteResult>;
+export async function executeShell(
+	options: ShellExecuteOptions & { jsonLines: true },
+	onChunk?: (chunk: string | ShellJsonLine) => void,
+): Promise<ShellExecuteResult>;
+export async function executeShell(
+	options: ShellExecuteOptions & { binaryOutput: true; promptIdleMs: number },
+	onChunk?: (chunk: ShellOutputChunk | ShellInputPrompt) => void,
+): Promise<ShellExecuteResult>;
+export async function executeShell(
+	options: ShellExecuteOptions & { promptIdleMs: number },
+	onChunk?: (chunk: string | ShellInputPrompt) => void,
+): Promise<ShellExecuteResult>;
+export async function executeShell(
+	options: ShellExecuteOptions & { binaryOutput: true },
+	onChunk?: (chunk: ShellOutputChunk) => void,
+): Promise<ShellExecuteResult>;
 export async function executeShell(
 	options: ShellExecuteOptions,
 	onChunk?: (chunk: string) => void,
+): Promise<ShellExecuteResult>;
+export async function executeShell(
+	options: ShellExecuteOptions,
+	onChunk?: (chunk: any) => void,
 ): Promise<ShellExecuteResult> {
-	const wrappedCallback = onChunk ? (err: Error | null, chunk: string) => !err && onChunk(chunk) : undefined;
+	const wrappedCallback = onChunk
+		? (err: Error | null, chunk: string | ShellOutputChunk | ShellInputPrompt | ShellJsonLine) =>
+				!err && onChunk(chunk)
+		: undefined;
 	return native.executeShell(options, wrappedCallback);
 }
+
+/**
+ * Replay the logged output of a detached execution and, unless `follow` is false, stream new output
+ * until the command ends.
+ *
+ * @param id - Execution id from `executeShellDetached` or `listDetachedExecutions`
+ * @param onChunk - Callback for output chunks
+ * @param options - Whether to follow, abort signal for detaching, and the session database
+ * @returns Promise resolving to the execution's state once streaming ends
+ */
+export async function attachExecution(
+	id: number,
+	onChunk: (chunk: string) => void,
+	options?: AttachExecutionOptions,
+): Promise<DetachedExecution> {
+	return native.attachExecution(id, (err: Error | null, chunk: string) => !err && onChunk(chunk), options);
+}
@@ -3,6 +3,7 @@
  */
 
 import type { Cancellable, TsFunc } from "../bindings";
+import type { SessionStoreOptions } from "../store/types";
 
 /**
  * Configuration for a persistent brush-core shell session.
@@ -12,6 +13,39 @@ export interface ShellOptions {
 	sessionEnv?: Record<string, string>;
 	/** Optional snapshot path to source for bash sessions. */
 	snapshotPath?: string;
+	/** Builtins the session may use. */
+	builtinPolicy?: ShellBuiltinPolicy;
+}
+
+/**
+ * Builtins a session may use. A disabled builtin is gone as far as the shell is concerned, so a
+ * program of the same name on `PATH` runs in its place; any restriction also disables `enable`.
+ */
+export interface ShellBuiltinPolicy {
+	/** Builtins to keep; all others are disabled (default: all). */
+	allow?: string[];
+	/** Builtins to disable, such as `trap` or `ulimit`. */
+	deny?: string[];
+	/**
+	 * Refuse `cd`, `pushd` and `popd` into directories outside the roots set with
+	 * `setWorkspaceRoots`; the change runs and is undone with an error (default: false).
+	 */
+	confineCd?: boolean;
+}
+
+/**
+ * Home directory of its own for a command: `HOME` and the XDG base directories (on Windows also
+ * `USERPROFILE`, `APPDATA` and `LOCALAPPDATA`) point into it, so tools writing `~/.config` or
+ * `~/.cache` leave the user's dotfiles alone. Variables set in `env` win.
+ */
+export interface ShellIsolatedHome {
+	/**
+	 * Directory to use, created if missing and kept afterwards (default: a temporary directory
+	 * removed after the command).
+	 */
+	path?: string;
+	/** Directory copied into the home first; files already there are kept. */
+	template?: string;
 }
 
 /**
@@ -24,6 +58,15 @@ export interface ShellRunOptions extends Cancellable {
 	cwd?: string;
 	/** Environment variables to apply for this command. */
 	env?: Record<string, string>;
+	/** Kill the command once it has produced no output for this many milliseconds. */
+	idleTimeoutMs?: number;
+	/** Run the command in a home of its own: `true` for a temporary one. */
+	isolatedHome?: boolean | ShellIsolatedHome;
+	/**
+	 * Called with the execution id as soon as the command is registered, before it waits for a slot, for
+	 * `cancelShellExecution` and `getExecutionOutput`.
+	 */
+	onStart?: TsFunc<number>;
 }
 
 /**
@@ -36,6 +79,82 @@ export interface ShellRunResult {
 	cancelled: boolean;
 	/** Whether the command timed out. */
 	timedOut: boolean;
+	/** Whether the timeout was `idleTimeoutMs` rather than `timeoutMs`. */
+	idleTimedOut: boolean;
+}
+
+/**
+ * Resource limits applied to commands spawned by a shell execution.
+ *
+ * Enforced with `setrlimit` on unix and a Job Object on Windows. Builtins run
+ * in-process and are not constrained.
+ */
+export interface ShellResourceLimits {
+	/** Maximum address space per process, in bytes. */
+	maxMemoryBytes?: number;
+	/** Maximum CPU time per process, in seconds. */
+	maxCpuSeconds?: number;
+	/** Maximum open file descriptors per process (unix only). */
+	maxOpenFiles?: number;
+	/**
+	 * Maximum memory of all of a command's processes together, in bytes. This and the other
+	 * whole-command limits run the command in a cgroup of its own, and need Linux with cgroup v2
+	 * delegation; elsewhere the execution fails.
+	 */
+	maxTotalMemoryBytes?: number;
+	/** Maximum CPU bandwidth of all of a command's processes together, in CPUs (e.g. `1.5`). */
+	maxCpus?: number;
+	/** Maximum number of processes and threads a command may run at once. */
+	maxProcesses?: number;
+	/**
+	 * Run the command in a cgroup of its own even without whole-command limits, so `usage` is
+	 * reported and CPU time is exact. Ignored where cgroups are not available.
+	 */
+	cgroup?: boolean;
+	/**
+	 * Maximum combined stdout/stderr bytes before the command is killed.
+	 * @deprecated Use `maxOutputBytes` with `killOnOutputLimit`, which take precedence.
+	 */
+	maxOutputBytes?: number;
+}
+
+/** Resource usage of a command that ran in a cgroup of its own. */
+export interface ShellResourceUsage {
+	/** Highest memory use of all of the command's processes together, in bytes (Linux 5.19+). */
+	peakMemoryBytes?: number;
+	/** Most processes and threads running at once (Linux 6.1+). */
+	peakProcesses?: number;
+	/** Time the command was held back by `maxCpus`, in milliseconds. */
+	throttledMs?: number;
+}
+
+/**
+ * Filesystem and network confinement for commands spawned by a shell execution.
+ *
+ * Commands may read system directories, the working directory, and `read`
+ * paths, and may write only below `write` paths. Enforced with Landlock and
+ * seccomp on Linux and `sandbox-exec` on macOS; not yet supported on Windows,
+ * where requesting a sandbox fails the execution.
+ * Builtins run in-process and are not confined, but redirection targets and
+ * sourced files are checked against the same paths.
+ */
+export interface ShellSandbox {
+	/** Extra readable paths, including everything below them. */
+	read?: string[];
+	/** Readable and writable paths, including everything below them. */
+	write?: string[];
+	/** Allow network access (default: false). */
+	network?: boolean;
+}
+
+/**
+ * Hosts that commands may connect to besides loopback. Names are resolved when
+ * the execution starts and again when a command connects to an unknown address;
+ * DNS to the system resolvers is allowed. Linux only.
+ */
+export interface ShellNetworkAllowlist {
+	/** Host names or IP addresses. */
+	allowHosts: string[];
 }
 
 /**
@@ -52,11 +171,366 @@ export interface ShellExecuteOptions extends Cancellable {
 	sessionEnv?: Record<string, string>;
 	/** Optional snapshot path to source for bash sessions. */
 	snapshotPath?: string;
+	/** Builtins the session may use. */
+	builtinPolicy?: ShellBuiltinPolicy;
+	/**
+	 * Kill the command once it has produced no output for this many milliseconds, independently
+	 * of `timeoutMs`, so steadily printing commands can run long while silent hangs end early.
+	 */
+	idleTimeoutMs?: number;
+	/** Resource limits for spawned processes. */
+	limits?: ShellResourceLimits;
+	/** Confine spawned processes to allowlisted paths, without network access unless allowed. */
+	sandbox?: ShellSandbox;
+	/**
+	 * Network egress policy for spawned processes (default: `"allow"`). `"deny"`
+	 * allows only loopback and unix sockets (Linux and macOS); an allowlist
+	 * also admits the listed hosts (Linux). Not a security boundary: use
+	 * `sandbox` to cut off network access entirely.
+	 */
+	network?: "allow" | "deny" | ShellNetworkAllowlist;
+	/** Run the command in a home of its own: `true` for a temporary one. A sandbox may write to it. */
+	isolatedHome?: boolean | ShellIsolatedHome;
+	/**
+	 * Program that runs the command (default: `"brush"`). `"powershell"` runs it
+	 * with `pwsh`, or Windows PowerShell when `pwsh` is not on `PATH`; `"cmd"`
+	 * runs it with `cmd.exe` (Windows only). `snapshotPath` and `captureState`
+	 * only apply to brush.
+	 */
+	interpreter?: "brush" | "powershell" | "cmd";
+	/**
+	 * CPU and I/O scheduling priority of spawned processes (default: `"normal"`). `"background"`
+	 * yields to interactive work (nice 10 and the lowest best-effort I/O priority on Linux, the
+	 * below-normal priority class on Windows); `"high"` runs ahead of it where the process is
+	 * permitted to raise its priority.
+	 */
+	priority?: "background" | "normal" | "high";
+	/** Stop streaming after this many combined stdout/stderr bytes. */
+	maxOutputBytes?: number;
+	/** Stop streaming after this many output lines. */
+	maxOutputLines?: number;
+	/**
+	 * Kill the command once an output limit is hit (default: false, the rest of
+	 * the output is discarded and the command runs to completion).
+	 */
+	killOnOutputLimit?: boolean;
+	/**
+	 * Report a `ShellInputPrompt` through `onChunk` when the command has been silent this long
+	 * while one of its processes is still running and its last output line looks like a prompt
+	 * (`Password:`, `[y/N]`, `Press any key`, ...). Reported once per silence.
+	 */
+	promptIdleMs?: number;
+	/** Remove ANSI escape sequences from output before it is streamed. */
+	stripAnsi?: boolean;
+	/** Report exported environment changes and the final working directory in `state`. */
+	captureState?: boolean;
+	/** Report the working directory and environment the command started with in `environment`. */
+	captureEnvironment?: boolean;
+	/** Stream output as raw `ShellOutputChunk`s instead of decoded strings. */
+	binaryOutput?: boolean;
+	/**
+	 * Deliver output lines holding a JSON object or array as parsed `ShellJsonLine`s, and other
+	 * output as strings. Lines split across reads are reassembled first. Cannot be combined with
+	 * `binaryOutput`.
+	 */
+	jsonLines?: boolean;
+	/**
+	 * Keep this many bytes of streamed output for `getExecutionOutput` while the command runs
+	 * (default: 65536, 0 disables).
+	 */
+	scrollbackBytes?: number;
+	/**
+	 * Called with the execution id as soon as the command is registered, before it waits for a slot, for
+	 * `cancelShellExecution` and `getExecutionOutput`.
+	 */
+	onStart?: TsFunc<number>;
+}
+
+/** Raw output chunk streamed in `binaryOutput` mode. */
+export interface ShellOutputChunk {
+	/** Output bytes, exactly as the command wrote them (after ANSI stripping and output limits). */
+	data: Buffer;
+	/**
+	 * Whether `data` is valid UTF-8 on its own. Characters split across reads are held back, so
+	 * text output always arrives in whole characters.
+	 */
+	utf8: boolean;
+}
+
+/** The signal that ended a command. */
+export interface ShellTermination {
+	/** Process that the signal killed. */
+	pid: number;
+	/** Signal number. */
+	signal: number;
+	/** Signal name, like `SIGKILL` (`SIG<n>` for unknown signals). */
+	signalName: string;
+	/** Whether the process dumped core. */
+	coreDumped: boolean;
+}
+
+/** A running shell execution, as reported by `listShellExecutions`. */
+export interface ShellExecutionInfo {
+	/** Id to pass to `cancelShellExecution` and `getExecutionOutput`. */
+	id: number;
+	/** Command string being executed. */
+	command: string;
+	/** Start of the execution, in milliseconds since the Unix epoch. */
+	startedAt: number;
+	/** Processes of the command that are still running. */
+	pids: number[];
+	/** Output bytes the command has written so far, before ANSI stripping and output limits. */
+	outputBytes: number;
+	/** Whether the execution is waiting for a slot under `setMaxConcurrentExecutions` rather than running. */
+	queued: boolean;
+}
+
+/** Options for `getExecutionOutput`. */
+export interface ExecutionOutputOptions {
+	/** Offset in the execution's output to start at (default: the oldest byte still kept). */
+	fromByte?: number;
+	/** Return at most this many bytes (default: everything kept). */
+	maxBytes?: number;
+}
+
+/** Output replayed by `getExecutionOutput`. */
+export interface ShellExecutionOutput {
+	/** Output from `start` to `end`, as streamed (after redaction, ANSI stripping and output limits). */
+	text: string;
+	/** Offset of the first returned byte; greater than `fromByte` when that output was dropped. */
+	start: number;
+	/** Offset just past the last returned byte; pass it as `fromByte` to continue. */
+	end: number;
+	/** Bytes streamed so far. */
+	totalBytes: number;
+}
+
+/** Options for `executeShellDetached`. */
+export interface DetachedShellOptions extends SessionStoreOptions {
+	/** Command run with `/bin/sh -c`. */
+	command: string;
+	/** Working directory (default: this process's). */
+	cwd?: string;
+	/** Variables added to this process's environment. */
+	env?: Record<string, string>;
+	/** Output kept in the log; older output is dropped (default: 1 MiB). */
+	maxLogBytes?: number;
+	/** Log file (default: `detached/` next to the session database). */
+	logPath?: string;
+}
+
+/** A detached execution and its state. */
+export interface DetachedExecution {
+	/** Id to pass to `attachExecution` and `stopExecution`. */
+	id: number;
+	/** Command being executed. */
+	command: string;
+	/** Working directory, when one was given. */
+	cwd?: string;
+	/** Process id of the shell running the command. */
+	pid: number;
+	/** Log file holding the output. */
+	logPath: string;
+	/** Start of the execution, in milliseconds since the Unix epoch. */
+	startedAt: number;
+	/** Whether the command (or a process holding its output) still runs. */
+	running: boolean;
+	/** Exit code, once the command exited normally. */
+	exitCode?: number;
+	/** The signal that ended the command. */
+	termination?: ShellTermination;
+	/** Output bytes written so far, including those dropped from the log. */
+	outputBytes: number;
+}
+
+/** Options for `attachExecution`. */
+export interface AttachExecutionOptions extends SessionStoreOptions {
+	/**
+	 * Keep streaming new output until the command ends (default: true); when false, only the logged
+	 * output is replayed.
+	 */
+	follow?: boolean;
+	/** Abort signal for detaching; the command keeps running. */
+	signal?: AbortSignal;
+}
+
+/** Options for `stopExecution`. */
+export interface StopExecutionOptions extends SessionStoreOptions {
+	/** Milliseconds between SIGTERM and SIGKILL (default: 2000). */
+	graceMs?: number;
+	/** Also delete the record and the log (default: false). */
+	remove?: boolean;
+}
+
+/** A line of output holding JSON, streamed with `jsonLines`. */
+export interface ShellJsonLine {
+	/** The parsed object or array. */
+	json: unknown;
+	/** The line as written, without its line terminator. */
+	line: string;
+}
+
+/** Streamed with `promptIdleMs` when a command appears to be waiting for input. */
+export interface ShellInputPrompt {
+	/** The last output line, which looks like a prompt. */
+	prompt: string;
+	/** How long the command had been silent, in milliseconds. */
+	idleMs: number;
+}
+
+/** A pattern to redact from command output, for `setRedactionRules`. */
+export interface RedactionRule {
+	/** Text to redact, or a regular expression with `regex`. */
+	pattern: string;
+	/** Treat `pattern` as a regular expression (default: false). */
+	regex?: boolean;
+	/** Match case-insensitively (default: false). */
+	ignoreCase?: boolean;
+	/** Text replacing each match (default: "[REDACTED]"). */
+	replacement?: string;
+}
+
+/** Options for `setRedactionRules`. */
+export interface RedactionOptions {
+	/** Also redact secrets found by the `scanSecrets` detectors, as `[REDACTED:<rule>]` (default: false). */
+	detectSecrets?: boolean;
+}
+
+/** Options for `setAuditLog`. */
+export interface AuditLogOptions extends SessionStoreOptions {
+	/** Record executions (default: true). */
+	enabled?: boolean;
+}
+
+/** How an audited execution ended. */
+export type AuditOutcome = "exited" | "killed" | "cancelled" | "timeout" | "error";
+
+/** Filter for `queryAuditLog`; every field given must match. */
+export interface AuditLogFilter extends SessionStoreOptions {
+	/** Earliest start time in milliseconds since the Unix epoch, inclusive. */
+	since?: number;
+	/** Latest start time in milliseconds since the Unix epoch, inclusive. */
+	until?: number;
+	/** Text the command contains. */
+	command?: string;
+	/** Working directory. */
+	cwd?: string;
+	/** How the execution ended. */
+	outcome?: AuditOutcome;
+	/** Exit code. */
+	exitCode?: number;
+	/** Maximum number of entries (default: 100). */
+	limit?: number;
+}
+
+/** An execution recorded in the audit log. */
+export interface AuditLogEntry {
+	/** Position in the log. */
+	id: number;
+	/** Command, with the redaction rules applied. */
+	command: string;
+	/** Working directory, when known; `Shell.run` commands without `cwd` run wherever the session is. */
+	cwd?: string;
+	/** BLAKE3 hash of the environment the command was given, lowercase hex. */
+	envHash: string;
+	/** Exit code, when the command exited. */
+	exitCode?: number;
+	/**
+	 * "exited", "killed" (for output or idle limits), "cancelled", "timeout", or "error" when the
+	 * command could not be run.
+	 */
+	outcome: AuditOutcome;
+	/** Wall-clock duration in milliseconds, including time queued. */
+	durationMs: number;
+	/** Bytes of output the command produced. */
+	outputBytes: number;
+	/** Start of the execution, in milliseconds since the Unix epoch. */
+	startedAt: number;
+}
+
+/** Working directory and environment a command started with, reported with `captureEnvironment`. */
+export interface ShellEnvironment {
+	/** Resolved working directory. */
+	cwd: string;
+	/**
+	 * Exported environment variables, after inheritance (minus the shell's own variables for
+	 * brush), `sessionEnv`, the snapshot and `env`.
+	 */
+	env: Record<string, string>;
+}
+
+/** Shell state left behind by a command, reported with `captureState`. */
+export interface ShellStateDelta {
+	/** Working directory after the command finished. */
+	cwd: string;
+	/** Exported variables the command added or changed, with their new values. */
+	env: Record<string, string>;
+	/** Exported variables the command unset or stopped exporting. */
+	removedEnv: string[];
 }
 
 /**
 /** Internal result from the native brush-core binding. */
-export interface ShellExecuteResult extends ShellRunResult {}
+export interface ShellExecuteResult extends ShellRunResult {
+	/**
+	 * Whether output was cut off by `maxOutputBytes` or `maxOutputLines`. A
+	 * `[output truncated after ...]` marker chunk is streamed when this happens.
+	 */
+	outputTruncated: boolean;
+	/** Whether the command was killed for hitting an output limit. */
+	outputLimitExceeded: boolean;
+	/** The signal that ended the command, when its exit code reports one (unix). */
+	termination?: ShellTermination;
+	/**
+	 * Whether the Linux OOM killer killed one of the command's processes, not necessarily the one
+	 * that ended it. Read from the command's own cgroup when it has one; otherwise from the memory
+	 * cgroup's `oom_kill` counter, which also counts kills of other processes sharing the cgroup,
+	 * or from the kernel log when there is no memory cgroup.
+	 */
+	oomKilled: boolean;
+	/** Peak resource usage, when the command ran in a cgroup of its own (see `ShellResourceLimits`). */
+	usage?: ShellResourceUsage;
+	/** Environment and cwd changes, when `captureState` was set and the command ran to completion. */
+	state?: ShellStateDelta;
+	/**
+	 * Working directory and environment the command started with, when `captureEnvironment` was
+	 * set and the command ran to completion.
+	 */
+	environment?: ShellEnvironment;
+	/** Where the time went. */
+	timing: ShellTiming;
+}
+
+/**
+ * Wall-clock and CPU timing of a shell execution. Phase durations and CPU times are absent when
+ * the command was cancelled or timed out.
+ */
+export interface ShellTiming {
+	/** Start of the execution, in milliseconds since the Unix epoch. */
+	startedAt: number;
+	/** Arrival of the first output byte, in milliseconds since the Unix epoch. */
+	firstOutputAt?: number;
+	/** End of the execution, in milliseconds since the Unix epoch. */
+	endedAt: number;
+	/** Total wall-clock duration in milliseconds. */
+	durationMs: number;
+	/** Time spent waiting for a slot under `setMaxConcurrentExecutions`, in milliseconds. */
+	queuedMs: number;
+	/** Session startup (environment import, snapshot sourcing) in milliseconds. */
+	setupMs?: number;
+	/** Time from starting the command until it exited, in milliseconds. */
+	commandMs?: number;
+	/** Time spent draining output after the command exited, in milliseconds. */
+	drainMs?: number;
+	/**
+	 * User CPU time of the command's processes in milliseconds. Exact when the command ran in a
+	 * cgroup of its own; otherwise on unix this counts children reaped during the execution, so
+	 * concurrent executions inflate each other's figures.
+	 */
+	userCpuMs?: number;
+	/** System CPU time of the command's processes in milliseconds. */
+	systemCpuMs?: number;
+}
 
 /** Native Shell class instance. */
 export interface Shell {
@@ -66,7 +540,10 @@ export interface Shell {
 	 * @param onChunk Optional callback for streamed output.
 	 * @returns Promise resolving to the command result.
 	 */
-	run(options: ShellRunOptions, onChunk?: TsFunc<string>): Promise<ShellRunResult>;
+	run(
+		options: ShellRunOptions,
+		onChunk?: TsFunc<string | ShellOutputChunk | ShellInputPrompt | ShellJsonLine>,
+	): Promise<ShellRunResult>;
 	/**
 	 * Abort all running commands in this session.
 	 * @param reason Optional reason for the abort.
@@ -74,6 +551,229 @@ export interface Shell {
 	abort(reason?: string): void;
 }
 
+/** Kind of a risky construct found by `analyzeShellCommand`. */
+export const enum ShellRiskKind {
+	/** `rm` with `-r`, `-R` or `--recursive`. */
+	RecursiveDelete = 1,
+	/** `sudo`, `doas`, `su`, `pkexec` or `run0`. */
+	Elevation = 2,
+	/** A shell or interpreter reading its script from a pipe or process substitution. */
+	PipeToShell = 3,
+	/** `eval`. */
+	Eval = 4,
+	/** Program name produced by an expansion (e.g. `$CMD args`). */
+	DynamicCommand = 5,
+}
+
+/** Kind of a redirection found by `analyzeShellCommand`. */
+export const enum ShellRedirectKind {
+	/** `<` */
+	Read = 1,
+	/** `>`, or `&>` for stdout and stderr. */
+	Write = 2,
+	/** `>>`, or `&>>` for stdout and stderr. */
+	Append = 3,
+	/** `<>` */
+	ReadWrite = 4,
+	/** `>|` */
+	Clobber = 5,
+	/** `<&` or `>&` */
+	Duplicate = 6,
+	/** `<<` (target is the delimiter). */
+	HereDoc = 7,
+	/** `<<<` */
+	HereString = 8,
+}
+
+/**
+ * A simple command found by `analyzeShellCommand`. Lines and character columns are
+ * 1-indexed; items inside a command substitution are reported at the word containing it.
+ */
+export interface ShellAnalyzedCommand {
+	/** Program name with quoting removed, or its source text when `dynamic`. */
+	program: string;
+	/** Arguments with quoting removed; arguments containing expansions are given as source text. */
+	args: string[];
+	/** Whether the program name contains an expansion. */
+	dynamic: boolean;
+	/** Source text of the command. */
+	text: string;
+	line: number;
+	column: number;
+}
+
+/** A redirection found by `analyzeShellCommand`. */
+export interface ShellAnalyzedRedirect {
+	kind: ShellRedirectKind;
+	/** Redirected descriptor, when given explicitly. */
+	fd?: number;
+	/** Target file, descriptor or here-doc delimiter. */
+	target: string;
+	line: number;
+	column: number;
+}
+
+/** A variable assignment found by `analyzeShellCommand`. */
+export interface ShellAnalyzedAssignment {
+	/** Variable name (with subscript for array elements). */
+	name: string;
+	/** Assigned value as source text. */
+	value: string;
+	/**
+	 * Program whose environment alone gets the variable (`FOO=1 make`, `env FOO=1 make`);
+	 * absent for shell variable assignments.
+	 */
+	command?: string;
+	line: number;
+	column: number;
+}
+
+/** A risky construct found by `analyzeShellCommand`. */
+export interface ShellRisk {
+	kind: ShellRiskKind;
+	/** Source text of the command it was found in. */
+	text: string;
+	line: number;
+	column: number;
+}
+
+/** Result of `analyzeShellCommand`. */
+export interface ShellCommandAnalysis {
+	/** Programs run, including those behind wrappers like `sudo`, in order of first appearance. */
+	programs: string[];
+	/** Simple commands in source order, with substitutions after the command containing them. */
+	commands: ShellAnalyzedCommand[];
+	redirections: ShellAnalyzedRedirect[];
+	/** Files written by output redirections or `tee`, in order of first appearance. */
+	filesWritten: string[];
+	assignments: ShellAnalyzedAssignment[];
+	risks: ShellRisk[];
+}
+
+/** Decision of a command policy. */
+export type CommandPolicyAction = "allow" | "deny" | "ask";
+
+/**
+ * A rule deciding the commands it matches. Conditions left out match any command; a rule without
+ * conditions matches every command.
+ */
+export interface CommandPolicyRule {
+	/** Name reported with the decision. */
+	name?: string;
+	/** Decision for matching commands. */
+	action: CommandPolicyAction;
+	/**
+	 * Program names or globs (`git`, `py*`), matched against the program and any wrappers it runs
+	 * under, without their directory.
+	 */
+	programs?: string[];
+	/**
+	 * Path globs, matched against the files a command redirects from or to and the operands that
+	 * look like paths, after resolving them against `cwd` and `~`. Allow rules match when every
+	 * path matches; deny and ask rules when any path does.
+	 */
+	paths?: string[];
+	/**
+	 * Match commands that use the network (`curl`, `git push`, `npm install`, ...) when true, and
+	 * those that do not when false.
+	 */
+	network?: boolean;
+	/** Match commands run through `sudo`, `doas`, `su`, `pkexec` or `run0` when true, and those that are not when false. */
+	sudo?: boolean;
+}
+
+/** Rules for `setCommandPolicy`. */
+export interface CommandPolicy {
+	/** Rules in priority order. */
+	rules: CommandPolicyRule[];
+	/** Decision for commands no rule matches (default: "ask"). */
+	default?: CommandPolicyAction;
+}
+
+/** Options for `evaluateCommandPolicy`. */
+export interface CommandPolicyOptions {
+	/** Directory relative paths are resolved against (default: this process's working directory). */
+	cwd?: string;
+}
+
+/** Result of `evaluateCommandPolicy`. */
+export interface CommandPolicyDecision {
+	/** Strictest decision for the commands in the input. */
+	action: CommandPolicyAction;
+	/** The rule that decided, absent when the default applied. */
+	rule?: CommandPolicyRule;
+	/** Position of `rule` in the policy. */
+	ruleIndex?: number;
+	/** Source text of the simple command that decided, absent for input without commands. */
+	command?: string;
+}
+
+/** Command language for `shellQuote` and `buildCommand`. */
+export const enum ShellDialect {
+	/** POSIX shells (bash, brush, zsh, ...). */
+	Posix = 1,
+	/** Windows PowerShell and PowerShell 7. */
+	PowerShell = 2,
+}
+
+/** Options for `buildCommand`. */
+export interface BuildCommandOptions {
+	/** Program to run. */
+	program: string;
+	/** Arguments to pass. */
+	args?: string[];
+	/** Environment variables to set for the program (for PowerShell, for the rest of the session). */
+	env?: Record<string, string>;
+	/** Directory to change to first; the command does not run if that fails. */
+	cwd?: string;
+	/** Command language (default: POSIX). */
+	dialect?: ShellDialect;
+}
+
+/** Options for `expandShellWords`. */
+export interface ExpandShellWordsOptions {
+	/** Variables to set on top of the process environment. */
+	env?: Record<string, string>;
+	/** Working directory, for `$PWD` and `~+` (default: the process's). */
+	cwd?: string;
+}
+
+/** Options for `completeCommandLine`. */
+export interface CompleteCommandLineOptions {
+	/** Directory relative paths complete in (default: the working directory). */
+	cwd?: string;
+	/** Variables to set on top of the process environment. */
+	env?: Record<string, string>;
+	/** Shell snapshot to source first, for its functions, aliases and completion specs. */
+	snapshotPath?: string;
+}
+
+/** Completions for the word under the cursor, from `completeCommandLine`. */
+export interface CommandLineCompletions {
+	/** Offset of the text the candidates replace. */
+	start: number;
+	/** Offset where the replaced text ends: the cursor. */
+	end: number;
+	/** Replacements; file names are escaped for the shell and directories end in `/`. */
+	candidates: string[];
+}
+
+/** First syntax error in a script, from `checkShellSyntax`. */
+export interface ShellSyntaxError {
+	/** What is wrong, like ``syntax error near unexpected token `fi'``. */
+	message: string;
+	/** 1-based line the error starts on. */
+	line: number;
+	/** 1-based column, in characters, the error starts at. */
+	column: number;
+	/** 1-based line the error ends on. */
+	endLine: number;
+	/** 1-based column just past the error. */
+	endColumn: number;
+	/** The line, numbered, with the error marked by carets below it. */
+	excerpt: string;
+}
+
 /** Native Shell class constructor. */
 export interface ShellConstructor {
 	/**
@@ -92,7 +792,178 @@ declare module "../bindings" {
 		 * @param onChunk Optional callback for streamed output.
 		 * @returns Promise resolving to the command result.
 		 */
-		executeShell(options: ShellExecuteOptions, onChunk?: TsFunc<string>): Promise<ShellExecuteResult>;
+		executeShell(
+			options: ShellExecuteOptions,
+			onChunk?: TsFunc<string | ShellOutputChunk | ShellInputPrompt | ShellJsonLine>,
+		): Promise<ShellExecuteResult>;
+
+		/**
+		 * List the `executeShell` calls and `Shell.run` commands currently running, oldest first.
+		 */
+		listShellExecutions(): ShellExecutionInfo[];
+
+		/**
+		 * Cancel a running shell execution, as if its abort signal fired; its result reports
+		 * `cancelled`.
+		 * @param id Execution id from `listShellExecutions`.
+		 * @returns False when no execution with `id` is running.
+		 */
+		cancelShellExecution(id: number): boolean;
+
+		/**
+		 * Read the scrollback of a running shell execution: the last `scrollbackBytes` of the
+		 * output it streamed, with byte offsets for resuming, e.g. to re-render a command's output
+		 * after reconnecting.
+		 * @param id Execution id from `listShellExecutions`.
+		 * @param options Offset to start at and most bytes to return.
+		 * @returns Null when no execution with `id` is running.
+		 */
+		getExecutionOutput(id: number, options?: ExecutionOutputOptions): ShellExecutionOutput | null;
+
+		/**
+		 * Start a command with `/bin/sh -c` in a session of its own, detached from this process: it
+		 * keeps running after the call and after this process exits. Its output goes to a log file
+		 * keeping the last `maxLogBytes`, and the execution is recorded in the session database.
+		 * Not supported on Windows.
+		 * @param options Command, working directory, environment, log size and location.
+		 * @returns The execution, with the id for `attachExecution` and `stopExecution`.
+		 */
+		executeShellDetached(options: DetachedShellOptions): Promise<DetachedExecution>;
+
+		/**
+		 * List detached executions, oldest first, with their state.
+		 */
+		listDetachedExecutions(options?: SessionStoreOptions | null): Promise<DetachedExecution[]>;
+
+		/**
+		 * Replay the logged output of a detached execution and, with `follow`, stream new output
+		 * until the command ends. Output is redacted like that of `executeShell`.
+		 * @param id Execution id from `executeShellDetached` or `listDetachedExecutions`.
+		 * @param onChunk Callback for output.
+		 * @returns The execution's state once streaming ends; rejects for unknown ids and when
+		 * `signal` aborts, which leaves the command running.
+		 */
+		attachExecution(
+			id: number,
+			onChunk: TsFunc<string>,
+			options?: AttachExecutionOptions | null,
+		): Promise<DetachedExecution>;
+
+		/**
+		 * Terminate a detached execution's process tree: SIGTERM, then SIGKILL for processes still
+		 * running after `graceMs`.
+		 * @param id Execution id from `executeShellDetached` or `listDetachedExecutions`.
+		 * @returns The final state, or `null` for an unknown id.
+		 */
+		stopExecution(id: number, options?: StopExecutionOptions | null): Promise<DetachedExecution | null>;
+
+		/**
+		 * Limit how many `executeShell` calls and `Shell.run` commands run at once. Executions
+		 * beyond the limit wait in first-come order, reported as `queued` by
+		 * `listShellExecutions`; their `timeoutMs` starts once they leave the queue.
+		 * @param max Maximum concurrent executions; `0` or omitted removes the limit.
+		 */
+		setMaxConcurrentExecutions(max?: number | null): void;
+
+		/**
+		 * Set the patterns redacted from the output of every `executeShell` call and `Shell.run`
+		 * command before it reaches JS, replacing earlier ones; commands already running keep
+		 * their rules. Output is redacted a line at a time, so a partial line is held back until
+		 * its newline arrives or the command is quiet for 100ms.
+		 * @param rules Patterns to redact; an empty list without `detectSecrets` turns redaction off.
+		 * @throws When a pattern is empty or not a valid regular expression.
+		 */
+		setRedactionRules(rules: RedactionRule[], options?: RedactionOptions | null): void;
+
+		/**
+		 * Turn the audit log on or off, or move it to another database. Every `executeShell` call
+		 * and `Shell.run` command is appended to the log once it ends, with its command redacted
+		 * by the `setRedactionRules` patterns; entries cannot be changed or deleted. Executions
+		 * already running are logged where they started.
+		 */
+		setAuditLog(options: AuditLogOptions): void;
+
+		/**
+		 * Read audited shell executions matching `filter`, newest first.
+		 * @param filter Time range, command text, working directory, outcome and exit code to match.
+		 */
+		queryAuditLog(filter?: AuditLogFilter | null): Promise<AuditLogEntry[]>;
+
+		/**
+		 * Parse a command without running it and report the commands, redirections, written
+		 * files, assignments and risky constructs in it. Wrappers like `sudo`, `env` and
+		 * `xargs` are looked through; aliases, functions and expansions are not resolved.
+		 * @param command Shell input to analyze.
+		 * @returns Promise rejecting when the input does not parse.
+		 */
+		analyzeShellCommand(command: string): Promise<ShellCommandAnalysis>;
+
+		/**
+		 * Set the rules `evaluateCommandPolicy` applies, replacing earlier ones.
+		 * @throws For an unknown action or an invalid glob; the previous rules stay in effect.
+		 */
+		setCommandPolicy(policy: CommandPolicy): void;
+
+		/**
+		 * Decide whether a command may run under the rules set with `setCommandPolicy`. The
+		 * command is parsed as by `analyzeShellCommand` and every simple command in it, behind
+		 * wrappers like `sudo` and inside substitutions, is checked: the first matching rule
+		 * decides for each, and the input gets the strictest decision (`deny` over `ask` over
+		 * `allow`).
+		 * @param command Shell input to check.
+		 * @returns Promise rejecting when the input does not parse.
+		 */
+		evaluateCommandPolicy(command: string, options?: CommandPolicyOptions | null): Promise<CommandPolicyDecision>;
+
+		/**
+		 * Quote arguments as separate words and join them with spaces. POSIX arguments are
+		 * single-quoted, newlines included; PowerShell arguments with control characters use
+		 * backtick escapes.
+		 * @param args Arguments to quote.
+		 * @param dialect Command language (default: POSIX).
+		 * @throws For POSIX, if an argument contains NUL.
+		 */
+		shellQuote(args: string[], dialect?: ShellDialect): string;
+
+		/**
+		 * Build a command running a program with quoted arguments, after setting
+		 * environment variables and changing directory.
+		 * @param options Program, arguments, environment, directory and dialect.
+		 * @throws If an environment variable name is not a valid identifier.
+		 */
+		buildCommand(options: BuildCommandOptions): string;
+
+		/**
+		 * Parse a script with the parser sessions run it with and report its first syntax error, or
+		 * `null` if it parses. Unterminated quotes and here-documents are reported where they open.
+		 * Nothing runs.
+		 * @param command Script to check.
+		 */
+		checkShellSyntax(command: string): Promise<ShellSyntaxError | null>;
+
+		/**
+		 * Expand text into words as a fresh shell session would expand a command's arguments: tilde,
+		 * parameter, arithmetic and brace expansion, field splitting and quote removal. Nothing runs:
+		 * command substitutions and operators such as `;` are rejected, and globs are left as they are.
+		 * @param text Text to expand, such as a config value.
+		 * @param options Extra variables and working directory.
+		 * @throws For a command substitution, an operator, or a bad substitution.
+		 */
+		expandShellWords(text: string, options?: ExpandShellWordsOptions | null): Promise<string[]>;
+
+		/**
+		 * Complete the word before the cursor with the shell's completion engine: commands, builtins,
+		 * functions, aliases and keywords in command position, paths elsewhere, and whatever specs
+		 * registered with `complete` offer. Command substitutions in the word are not run.
+		 * @param line Command line being edited.
+		 * @param cursor Cursor offset in UTF-16 code units (default: the end of the line).
+		 * @param options Directory, extra variables and snapshot to source.
+		 */
+		completeCommandLine(
+			line: string,
+			cursor?: number | null,
+			options?: CompleteCommandLineOptions | null,
+		): Promise<CommandLineCompletions>;
 
 		/** Shell class constructor for creating sessions. */
 		Shell: ShellConstructor;
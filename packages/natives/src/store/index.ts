@@ -0,0 +1,20 @@
+/**
+ * SQLite-backed session persistence for conversation events, tool calls and artifacts.
+ */
+
+import { native } from "../native";
+
+export type {
+	AppendedEvent,
+	DateRange,
+	QuerySessionOptions,
+	SearchSessionsOptions,
+	SessionEvent,
+	SessionSearchHit,
+	SessionStoreOptions,
+	StoredSessionEvent,
+	VacuumSessionsOptions,
+	VacuumSessionsResult,
+} from "./types";
+
+export const { appendEvent, querySession, searchSessions, vacuumSessions } = native;
@@ -0,0 +1,167 @@
+/**
+ * Types for the SQLite session store.
+ */
+
+/** Options selecting the session database. */
+export interface SessionStoreOptions {
+	/** Database file (default: `$PI_CODING_AGENT_DIR/sessions.db`, falling back to `~/.omp/agent`). */
+	dbPath?: string;
+}
+
+/** An event to append to a session. */
+export interface SessionEvent {
+	/** Session the event belongs to; created on its first event. */
+	sessionId: string;
+	/** Event kind, e.g. `message`, `tool_call` or `artifact`. */
+	kind: string;
+	/** Message role, for message events. */
+	role?: string;
+	/** Tool name, for tool call events. */
+	toolName?: string;
+	/** Event payload, usually JSON. */
+	content: string;
+	/** Binary payload, e.g. artifact bytes. */
+	data?: Uint8Array;
+	/** Event time in milliseconds since the epoch (default: now). */
+	timestamp?: number;
+}
+
+/** An event read back from a session. */
+export interface StoredSessionEvent {
+	/** Database-wide event id. */
+	id: number;
+	/** Session the event belongs to. */
+	sessionId: string;
+	/** 1-based position of the event in its session. */
+	seq: number;
+	/** Event kind. */
+	kind: string;
+	/** Message role, for message events. */
+	role?: string;
+	/** Tool name, for tool call events. */
+	toolName?: string;
+	/** Event payload. */
+	content: string;
+	/** Binary payload. */
+	data?: Uint8Array;
+	/** Event time in milliseconds since the epoch. */
+	timestamp: number;
+}
+
+/** Position of an appended event. */
+export interface AppendedEvent {
+	/** Database-wide event id. */
+	id: number;
+	/** 1-based position of the event in its session. */
+	seq: number;
+}
+
+/** Options for reading a session. */
+export interface QuerySessionOptions extends SessionStoreOptions {
+	/** Only return events of these kinds. */
+	kinds?: string[];
+	/** Only return events after this sequence number. */
+	afterSeq?: number;
+	/** Maximum number of events to return (default: all). */
+	limit?: number;
+}
+
+/** Options for deleting sessions and compacting the database. */
+export interface VacuumSessionsOptions extends SessionStoreOptions {
+	/** Delete sessions without events for this many milliseconds. */
+	olderThanMs?: number;
+	/** Delete these sessions. */
+	sessionIds?: string[];
+}
+
+/** Result of deleting sessions and compacting the database. */
+export interface VacuumSessionsResult {
+	/** Sessions deleted. */
+	deletedSessions: number;
+	/** Events deleted with them. */
+	deletedEvents: number;
+	/** Database size in bytes before compaction. */
+	bytesBefore: number;
+	/** Database size in bytes after compaction. */
+	bytesAfter: number;
+}
+
+/** Time window in milliseconds since the epoch. */
+export interface DateRange {
+	/** Earliest event time, inclusive. */
+	from?: number;
+	/** Latest event time, inclusive. */
+	to?: number;
+}
+
+/** Options for full-text search over sessions. */
+export interface SearchSessionsOptions extends SessionStoreOptions {
+	/** Maximum number of hits (default: 20). */
+	limit?: number;
+	/** Only match events in this time window. */
+	dateRange?: DateRange;
+	/** Only match events of these kinds. */
+	kinds?: string[];
+	/** Only match events of this session. */
+	sessionId?: string;
+	/**
+	 * Treat the query as an FTS5 query expression (phrases, `OR`, `NOT`, prefixes) instead of
+	 * plain words that must all appear (default: false).
+	 */
+	raw?: boolean;
+	/** Marker placed before matched words in snippets (default: `**`). */
+	highlightStart?: string;
+	/** Marker placed after matched words in snippets (default: `**`). */
+	highlightEnd?: string;
+}
+
+/** An event matching a session search. */
+export interface SessionSearchHit {
+	/** Database-wide event id. */
+	id: number;
+	/** Session the event belongs to. */
+	sessionId: string;
+	/** Position of the event in its session. */
+	seq: number;
+	/** Event kind. */
+	kind: string;
+	/** Message role, for message events. */
+	role?: string;
+	/** Tool name, for tool call events. */
+	toolName?: string;
+	/** Excerpt of the event payload with the matched words highlighted. */
+	snippet: string;
+	/** Event time in milliseconds since the epoch. */
+	timestamp: number;
+	/** BM25 relevance; lower is more relevant. */
+	rank: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Append an event to a session, creating the session on its first event.
+		 * @param event Event to append.
+		 * @param options Database selection.
+		 */
+		appendEvent(event: SessionEvent, options?: SessionStoreOptions | null): Promise<AppendedEvent>;
+		/**
+		 * Read a session's events in order; unknown sessions have none.
+		 * @param sessionId Session to read.
+		 * @param options Kind filter, resume position, limit and database selection.
+		 */
+		querySession(sessionId: string, options?: QuerySessionOptions | null): Promise<StoredSessionEvent[]>;
+		/**
+		 * Delete stale or given sessions with their events, then compact the database.
+		 * @param options Sessions to delete and database selection.
+		 */
+		vacuumSessions(options?: VacuumSessionsOptions | null): Promise<VacuumSessionsResult>;
+		/**
+		 * Full-text search over the payloads and tool names of all stored events, most relevant
+		 * first. Plain queries match events containing every word, ignoring case and diacritics.
+		 * @param query Words to find, or an FTS5 expression with `raw`.
+		 * @param options Limit, time window, kind and session filters, and snippet markers.
+		 */
+		searchSessions(query: string, options?: SearchSessionsOptions | null): Promise<SessionSearchHit[]>;
+	}
+}
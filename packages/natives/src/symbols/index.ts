@@ -0,0 +1,9 @@
+/**
+ * Workspace-wide symbol index built on tree-sitter outlines.
+ */
+
+import { native } from "../native";
+
+export type { IndexWorkspaceOptions, QuerySymbolsOptions, SymbolMatch, WorkspaceIndexStats } from "./types";
+
+export const { indexWorkspace, querySymbols, closeWorkspaceIndex } = native;
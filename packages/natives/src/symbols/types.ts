@@ -0,0 +1,101 @@
+/**
+ * Types for the workspace symbol index.
+ */
+
+import type { Cancellable } from "../bindings";
+import type { SymbolKind } from "../syntax/types";
+
+/** Options for indexing a workspace. */
+export interface IndexWorkspaceOptions extends Cancellable {
+	/** Keep the index current by watching the workspace (default: true). */
+	watch?: boolean;
+	/** Read and write the on-disk index (default: true). */
+	persist?: boolean;
+	/** Location of the on-disk index (default: `~/.omp/agent/symbols/<root hash>.idx`). */
+	cachePath?: string;
+	/** Include hidden files (default: false). */
+	hidden?: boolean;
+}
+
+/** Result of indexing a workspace. */
+export interface WorkspaceIndexStats {
+	/** Canonical workspace root. */
+	root: string;
+	/** Indexed source files. */
+	files: number;
+	/** Indexed definitions. */
+	symbols: number;
+	/** Files parsed by this call (new or changed since the last index). */
+	parsed: number;
+	/** Files whose definitions were reused from the previous index. */
+	reused: number;
+	/** Files dropped because they were deleted or are now ignored. */
+	removed: number;
+	/** Where the index was written, absent when not persisted. */
+	cachePath?: string;
+	/** Whether the workspace is being watched for changes. */
+	watching: boolean;
+}
+
+/** Options for querying indexed symbols. */
+export interface QuerySymbolsOptions {
+	/** Only search this indexed workspace (default: all indexed workspaces). */
+	root?: string;
+	/** Only return definitions of this kind. */
+	kind?: SymbolKind;
+	/**
+	 * Also match prefixes, substrings and subsequences of names, best first
+	 * (default: false, which matches whole names case-insensitively).
+	 */
+	fuzzy?: boolean;
+	/** Maximum number of results (default: 100). */
+	limit?: number;
+}
+
+/** A definition found in the index. Lines and byte columns are 1-indexed. */
+export interface SymbolMatch {
+	/** Symbol name. */
+	name: string;
+	/** Kind of definition. */
+	kind: SymbolKind;
+	/** Name of the enclosing definition (e.g. the class of a method). */
+	container?: string;
+	/** Declaration up to its body, on one line. */
+	signature: string;
+	/** Workspace root the file belongs to. */
+	root: string;
+	/** File path relative to `root`, using forward slashes. */
+	path: string;
+	/** First line of the definition. */
+	startLine: number;
+	/** Byte column where the definition starts. */
+	startColumn: number;
+	/** Last line of the definition. */
+	endLine: number;
+	/** Byte column just past the end of the definition. */
+	endColumn: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Index the definitions in every supported source file under `root`.
+		 * Only files changed since the previous index (in memory or on disk) are re-parsed,
+		 * and a watcher keeps the index current until `closeWorkspaceIndex`.
+		 * @param root Workspace directory.
+		 * @param options Watching, persistence and cancellation.
+		 */
+		indexWorkspace(root: string, options?: IndexWorkspaceOptions): Promise<WorkspaceIndexStats>;
+		/**
+		 * Find definitions in indexed workspaces.
+		 * @param name Symbol name, optionally qualified by its container ("Server.start", "Server::start").
+		 * @param options Workspace, kind filter, fuzzy matching and limit.
+		 */
+		querySymbols(name: string, options?: QuerySymbolsOptions): Promise<SymbolMatch[]>;
+		/**
+		 * Stop watching a workspace and drop its in-memory index; the on-disk index is kept.
+		 * @returns False if the workspace is not indexed.
+		 */
+		closeWorkspaceIndex(root: string): boolean;
+	}
+}
@@ -0,0 +1,10 @@
+/**
+ * Syntax-aware file outlines and node lookup, powered by tree-sitter.
+ */
+
+import { native } from "../native";
+
+export type { FileOutline, NodeAtPosition, OutlineSymbol, SyntaxNode } from "./types";
+export { SymbolKind } from "./types";
+
+export const { fileOutline, nodeAtPosition } = native;
@@ -0,0 +1,109 @@
+/**
+ * Types for tree-sitter based syntax inspection.
+ */
+
+/** Kind of a definition in a file outline. */
+export const enum SymbolKind {
+	/** Free function. */
+	Function = 1,
+	/** Function defined inside a type. */
+	Method = 2,
+	/** Class (or Swift actor). */
+	Class = 3,
+	/** Struct or union. */
+	Struct = 4,
+	/** Enumeration. */
+	Enum = 5,
+	/** Interface or protocol. */
+	Interface = 6,
+	/** Trait. */
+	Trait = 7,
+	/** Rust `impl` block or Swift extension. */
+	Impl = 8,
+	/** Module or namespace. */
+	Module = 9,
+	/** Type alias. */
+	Type = 10,
+}
+
+/** A definition in a file outline. Lines and byte columns are 1-indexed. */
+export interface OutlineSymbol {
+	/** Symbol name (for Rust impl blocks, e.g. "Display for Foo"). */
+	name: string;
+	/** Kind of definition. */
+	kind: SymbolKind;
+	/** Declaration up to its body, on one line. */
+	signature: string;
+	/** First line of the definition. */
+	startLine: number;
+	/** Byte column where the definition starts. */
+	startColumn: number;
+	/** Last line of the definition. */
+	endLine: number;
+	/** Byte column just past the end of the definition. */
+	endColumn: number;
+	/** Definitions nested inside this one. */
+	children: OutlineSymbol[];
+}
+
+/** Result of `fileOutline`. */
+export interface FileOutline {
+	/** Language the file was parsed as (e.g. "rust", "typescript"). */
+	language: string;
+	/** Top-level definitions in file order. */
+	symbols: OutlineSymbol[];
+}
+
+/** A syntax node and its range. Lines and byte columns are 1-indexed. */
+export interface SyntaxNode {
+	/** Grammar node type (e.g. "call_expression"). */
+	kind: string;
+	/** First line of the node. */
+	startLine: number;
+	/** Byte column where the node starts. */
+	startColumn: number;
+	/** Last line of the node. */
+	endLine: number;
+	/** Byte column just past the end of the node. */
+	endColumn: number;
+	/** Source text of the node, truncated to 4 KiB; absent for ancestors. */
+	text?: string;
+}
+
+/** Result of `nodeAtPosition`. */
+export interface NodeAtPosition {
+	/** Language the file was parsed as. */
+	language: string;
+	/** Innermost named node at the position. */
+	node: SyntaxNode;
+	/** Enclosing nodes from the parent outwards, excluding the root. */
+	ancestors: SyntaxNode[];
+	/** Definitions enclosing the position, outermost first (without children). */
+	scope: OutlineSymbol[];
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Outline the functions, classes, methods and types defined in a source file.
+		 * Supports Rust, TypeScript/TSX, JavaScript, Python, Go, Java, C, C++, C#,
+		 * Ruby, PHP, Bash and Swift, chosen by file extension.
+		 * @param path Source file to parse.
+		 * @param allowOutsideWorkspace Skip the boundary set with `setWorkspaceRoots` (default: false).
+		 */
+		fileOutline(path: string, allowOutsideWorkspace?: boolean | null): Promise<FileOutline>;
+		/**
+		 * Find the innermost syntax node at a position, with its ancestors and enclosing definitions.
+		 * @param path Source file to parse.
+		 * @param line 1-indexed line.
+		 * @param column 1-indexed byte column.
+		 * @param allowOutsideWorkspace Skip the boundary set with `setWorkspaceRoots` (default: false).
+		 */
+		nodeAtPosition(
+			path: string,
+			line: number,
+			column: number,
+			allowOutsideWorkspace?: boolean | null,
+		): Promise<NodeAtPosition>;
+	}
+}
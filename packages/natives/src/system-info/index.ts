@@ -4,6 +4,43 @@
 
 import { native } from "../native";
 
-export type { SystemInfo } from "./types";
+export type {
+	DiskSpace,
+	EnvironmentInfo,
+	GpuInfo,
+	LatencyAttempt,
+	LatencyProbe,
+	LatencyProbeOptions,
+	ListeningPort,
+	LocaleInfo,
+	NetworkInfo,
+	NetworkInfoOptions,
+	NetworkInterface,
+	PortRange,
+	PowerEvent,
+	PowerEventOptions,
+	PowerInfo,
+	ResolvedHost,
+	ResolveHostOptions,
+	SystemInfo,
+	ThermalInfo,
+	ThermalSensor,
+	UserInfo,
+} from "./types";
 
-export const { getSystemInfo } = native;
+export const {
+	checkDiskSpace,
+	findFreePort,
+	getNetworkInfo,
+	getPowerInfo,
+	getSystemInfo,
+	getThermalInfo,
+	isPortFree,
+	killPortOwners,
+	listListeningPorts,
+	pidsListeningOn,
+	probeLatency,
+	resolveHost,
+	subscribePowerEvents,
+	unsubscribePowerEvents,
+} = native;
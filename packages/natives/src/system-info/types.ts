@@ -2,6 +2,37 @@
  * Types for system information.
  */
 
+import type { Cancellable, TsFunc } from "../bindings";
+import type { KillReport } from "../ps/types";
+
+/** A detected graphics adapter. */
+export interface GpuInfo {
+	/** Vendor name (e.g. "NVIDIA", "AMD", "Intel", "Apple"). */
+	vendor: string;
+	/** Adapter model name. */
+	model: string;
+	/** Dedicated video memory in bytes, when known. */
+	vramBytes?: number;
+}
+
+/** Execution environment the agent is running in. */
+export interface EnvironmentInfo {
+	/** Container runtime: "docker", "podman", "lxc", "systemd-nspawn", "kubernetes", or "container" when unknown. */
+	container?: string;
+	/**
+	 * Hypervisor: "kvm", "qemu", "vmware", "virtualbox", "hyper-v", "xen", "parallels", "amazon", "google", "bhyve",
+	 * or "vm" when the vendor is unknown.
+	 */
+	virtualMachine?: string;
+	/** WSL version (1 or 2) when running under the Windows Subsystem for Linux. */
+	wsl?: number;
+	/**
+	 * CI provider: "github-actions", "gitlab", "circleci", "buildkite", "jenkins", "travis", "azure-pipelines",
+	 * "teamcity", "bitbucket", "codebuild", or "ci" for other providers that set `CI`.
+	 */
+	ci?: string;
+}
+
 /** Snapshot of system details reported by native probes. */
 export interface SystemInfo {
 	/** Operating system name (e.g. Linux, macOS, Windows). */
@@ -24,18 +55,332 @@ export interface SystemInfo {
 	wm?: string;
 	/** Primary CPU brand/model string. */
 	cpu?: string;
-	/** Primary GPU identifier, when available. */
+	/** Primary GPU summary (model and VRAM), when available. */
 	gpu?: string;
 	/** System memory summary (used/total). */
 	memory?: string;
 	/** Disk usage summary (used/total) for primary mount. */
 	disk?: string;
+	/** Total physical memory in bytes. */
+	totalMemory: number;
+	/** Memory available for new allocations without swapping, in bytes. */
+	availableMemory: number;
+	/** Total swap space in bytes. */
+	totalSwap: number;
+	/** Swap space in use, in bytes. */
+	usedSwap: number;
+	/** Load averages over 1, 5 and 15 minutes (not available on Windows). */
+	loadAverage?: [number, number, number];
+	/** Number of logical CPUs. */
+	logicalCores: number;
+	/** Number of physical CPU cores, when known. */
+	physicalCores?: number;
+	/** Detected graphics adapters, discrete GPUs first. */
+	gpus: GpuInfo[];
+	/** Container, virtual machine, WSL, and CI detection. */
+	environment: EnvironmentInfo;
+	/** Battery charge and power source. */
+	power: PowerInfo;
+	/** Seconds since the system booted. */
+	uptimeSeconds: number;
+	/** Boot time in milliseconds since the Unix epoch. */
+	bootTime: number;
+	/** The user the agent runs as, and their privileges. */
+	user: UserInfo;
+	/** Locale, time zone and keyboard layout. */
+	locale: LocaleInfo;
+}
+
+/** Locale, time zone and keyboard layout of the user. */
+export interface LocaleInfo {
+	/** Locale for messages (e.g. "en_US.UTF-8", "de-DE"). */
+	locale?: string;
+	/** Locale for dates and times, which `LC_TIME` may set apart from `locale`. */
+	timeLocale?: string;
+	/** IANA time zone name (e.g. "Europe/Berlin"). */
+	timezone?: string;
+	/** Current offset from UTC in minutes, east positive. */
+	utcOffsetMinutes: number;
+	/** Keyboard layout (e.g. "us", "de", or on Windows the input language such as "de-DE"); not available on macOS. */
+	keyboardLayout?: string;
+}
+
+/** The user the agent runs as. */
+export interface UserInfo {
+	/** Login name. */
+	username?: string;
+	/** Effective user id (not available on Windows). */
+	uid?: number;
+	/** Groups the user belongs to (not available on Windows). */
+	groups: string[];
+	/** Whether the process runs as root or, on Windows, elevated. */
+	elevated: boolean;
+	/** Whether the user can likely gain elevated privileges: already elevated, an administrator group member with `sudo` available, or a Windows administrator who can accept a UAC prompt. */
+	sudo: boolean;
+}
+
+/** Battery charge and power source. Batteries of peripherals such as wireless mice are ignored. */
+export interface PowerInfo {
+	/** Whether the machine has a battery. */
+	hasBattery: boolean;
+	/** Charge of all batteries together, 0-100. */
+	percent?: number;
+	/** Battery state; `"not-charging"` means plugged in but held, e.g. by a charge limit. */
+	state?: "charging" | "discharging" | "full" | "not-charging" | "unknown";
+	/** Power source. */
+	source: "ac" | "battery" | "unknown";
+	/** Estimated minutes until empty while discharging, or until full while charging. */
+	minutesRemaining?: number;
+}
+
+/** A change reported by `subscribePowerEvents`. */
+export interface PowerEvent {
+	/** What changed: the power source, the charging state, or the charge dropped to `lowPercent` on battery. */
+	reason: "source" | "state" | "low";
+	/** Power state after the change. */
+	power: PowerInfo;
+}
+
+/** Options for `subscribePowerEvents`. */
+export interface PowerEventOptions {
+	/** Interval between checks in milliseconds (default: 30000). */
+	intervalMs?: number;
+	/** Charge, in percent, reported as low on battery (default: 20). */
+	lowPercent?: number;
+}
+
+/** A temperature sensor. */
+export interface ThermalSensor {
+	/** Sensor name, as the OS reports it (e.g. "coretemp Package id 0"). */
+	label: string;
+	/** Temperature in degrees Celsius. */
+	celsius: number;
+	/** Temperature at which the hardware shuts down or throttles hard. */
+	critical?: number;
+}
+
+/** CPU temperature and frequency scaling. Fields the OS does not provide are absent. */
+export interface ThermalInfo {
+	/** Hottest CPU sensor, in degrees Celsius. */
+	cpuCelsius?: number;
+	/** All temperature sensors. */
+	sensors: ThermalSensor[];
+	/** Average current clock across cores, in MHz. */
+	currentMhz?: number;
+	/** Highest clock the CPU supports, in MHz. */
+	maxMhz?: number;
+	/** Clock the CPU is currently capped at by thermal or power management, in MHz. */
+	limitMhz?: number;
+	/** Thermal pressure (macOS). */
+	pressure?: "nominal" | "fair" | "serious" | "critical";
+	/** Thermal throttle events since boot, summed over cores (Linux, Intel); compare two readings to see whether throttling is ongoing. */
+	throttleEvents?: number;
+	/** Whether the CPU appears to be held back: serious or critical pressure, a clock limit well below the maximum, or a CPU sensor near its critical temperature. */
+	throttled: boolean;
+}
+
+/** A network interface and its addresses. */
+export interface NetworkInterface {
+	/** Interface name (e.g. "eth0", "en0", "Ethernet"). */
+	name: string;
+	/** Hardware address, when the interface has one. */
+	mac?: string;
+	/** Addresses in CIDR notation (e.g. "192.168.1.20/24"). */
+	addresses: string[];
+	/** Whether every address is a loopback address. */
+	loopback: boolean;
+}
+
+/** Options for collecting network info. */
+export interface NetworkInfoOptions {
+	/** Probe internet connectivity (default: true). */
+	probe?: boolean;
+	/** Timeout for the connectivity probe in milliseconds (default: 1500). */
+	timeoutMs?: number;
+}
+
+/** Network interfaces, routing, and connectivity summary. */
+export interface NetworkInfo {
+	/** Network interfaces with their addresses. */
+	interfaces: NetworkInterface[];
+	/** Local IPv4 address used for outbound traffic. */
+	primaryIpv4?: string;
+	/** Local IPv6 address used for outbound traffic. */
+	primaryIpv6?: string;
+	/** Whether a default route (gateway) exists for IPv4 or IPv6. */
+	defaultRoute: boolean;
+	/** Whether a public host was reachable; absent when probing is disabled. */
+	online?: boolean;
+}
+
+/** A TCP socket listening for connections. */
+export interface ListeningPort {
+	/** Port number. */
+	port: number;
+	/** Local address listened on; `0.0.0.0` or `::` for every address. */
+	address: string;
+	/** Process owning the socket (the lowest pid when several share it), when it can be determined. */
+	pid?: number;
+	/** Executable name of the owning process. */
+	process?: string;
+}
+
+/** Space on the filesystem holding a path, from `checkDiskSpace`. */
+export interface DiskSpace {
+	/** Bytes available for writing. */
+	availableBytes: number;
+	/** Size of the filesystem in bytes. */
+	totalBytes: number;
+	/** Whether `requiredBytes` fit in the available space. */
+	sufficient: boolean;
+}
+
+/** Inclusive range of ports for `findFreePort`. */
+export interface PortRange {
+	/** First port to try. */
+	start: number;
+	/** Last port to try. */
+	end: number;
+}
+
+/** Options for `resolveHost`. The timeout defaults to 5 seconds. */
+export interface ResolveHostOptions extends Cancellable {}
+
+/** Outcome of `resolveHost`. */
+export interface ResolvedHost {
+	/** Host name as given. */
+	host: string;
+	/** Resolved addresses, in resolver order. */
+	addresses: string[];
+	/** Time the lookup took, or until it timed out, in milliseconds. */
+	durationMs: number;
+	/**
+	 * Whether every address is private, loopback or link-local, which for a public name suggests a
+	 * captive portal or DNS filtering.
+	 */
+	private: boolean;
+	/** Why no addresses were found (e.g. "Timed out after 5000ms"). */
+	error?: string;
+}
+
+/** Options for `probeLatency`. The timeout applies to each step (lookup or connection) and defaults to 3 seconds. */
+export interface LatencyProbeOptions extends Cancellable {
+	/** TCP port to connect to (default: 443). */
+	port?: number;
+	/** Connection attempts, made one after another (default: 3, at most 20). */
+	attempts?: number;
+}
+
+/** One connection attempt of `probeLatency`. */
+export interface LatencyAttempt {
+	/** Time to connect in milliseconds, when the connection succeeded. */
+	durationMs?: number;
+	/** Why the connection failed (e.g. "Connection refused", "Timed out after 3000ms"). */
+	error?: string;
+}
+
+/** Outcome of `probeLatency`. */
+export interface LatencyProbe {
+	/** Host name as given. */
+	host: string;
+	/** Port connected to. */
+	port: number;
+	/** Name resolution, done once before connecting. */
+	dns: ResolvedHost;
+	/** Address connected to: the first one resolved. */
+	address?: string;
+	/** Connection attempts, in order; empty when the name did not resolve. */
+	attempts: LatencyAttempt[];
+	/** Number of attempts that connected. */
+	successes: number;
+	/** Fastest connection in milliseconds. */
+	minMs?: number;
+	/** Average connection time in milliseconds. */
+	avgMs?: number;
+	/** Slowest connection in milliseconds. */
+	maxMs?: number;
 }
 
 declare module "../bindings" {
 	/** Native bindings that expose system info collection. */
 	interface NativeBindings {
-		/** Get system information (OS, CPU, memory, and disk summaries). */
+		/** Get system information (OS, CPU, cores, memory, swap, load, and disk summaries). */
 		getSystemInfo(): SystemInfo;
+		/**
+		 * Get network interfaces, primary addresses, default route presence and, unless disabled,
+		 * whether a public host is reachable (probed by IP, without DNS).
+		 */
+		getNetworkInfo(options?: NetworkInfoOptions): Promise<NetworkInfo>;
+		/**
+		 * Report the space available to this user on the filesystem holding a path. Archive
+		 * extraction, checkpoints and artifact writes run the same check and fail up front.
+		 * @param path File or directory; a path that does not exist yet is checked at its nearest
+		 * existing ancestor.
+		 * @param requiredBytes Bytes the caller needs, for `sufficient` (default: 0).
+		 */
+		checkDiskSpace(path: string, requiredBytes?: number | null): Promise<DiskSpace>;
+		/** Read the battery charge, charging state and power source. */
+		getPowerInfo(): PowerInfo;
+		/**
+		 * Poll the power state and report when the power source or charging state changes, or the
+		 * charge drops to `lowPercent` on battery.
+		 * @param options Polling interval and low charge threshold.
+		 * @param callback Receives each change.
+		 * @returns Subscription id to pass to `unsubscribePowerEvents`.
+		 */
+		subscribePowerEvents(options: PowerEventOptions | undefined | null, callback: TsFunc<PowerEvent>): number;
+		/**
+		 * Stop a subscription started by `subscribePowerEvents`.
+		 * @returns False if the id is not an active subscription.
+		 */
+		unsubscribePowerEvents(id: number): boolean;
+		/** Read CPU temperatures, clocks and thermal pressure. */
+		getThermalInfo(): ThermalInfo;
+		/**
+		 * Whether a server could listen on `port`: on every local address (IPv4 and IPv6, wildcard
+		 * and loopback), or only on `host` when given.
+		 * @param port Port number, 1-65535.
+		 * @param host IP address to check instead of every local address.
+		 * @throws For an invalid port or host.
+		 */
+		isPortFree(port: number, host?: string | null): boolean;
+		/**
+		 * Find a port a server could listen on on every local address.
+		 * @param range Ports to try in order; the OS picks one when omitted.
+		 * @returns The first free port, or `null` when none is free.
+		 * @throws For an invalid or reversed range.
+		 */
+		findFreePort(range?: PortRange | null): number | null;
+		/**
+		 * List TCP sockets listening for connections, sorted by port, with the process holding each
+		 * one when it can be determined (processes of other users usually cannot be on Linux).
+		 * Rejects on macOS, which is not supported yet.
+		 */
+		listListeningPorts(): Promise<ListeningPort[]>;
+		/**
+		 * List the processes listening on a TCP port, on any address, sorted by pid.
+		 * @param port Port number, 1-65535.
+		 * @returns Process ids; empty when the port is free or its owners cannot be seen.
+		 */
+		pidsListeningOn(port: number): Promise<number[]>;
+		/**
+		 * Terminate every process listening on a TCP port, with its descendants: SIGTERM, then
+		 * SIGKILL for processes still running after `graceMs`. This process is never killed.
+		 * @param port Port number, 1-65535.
+		 * @param graceMs Milliseconds to wait for a clean exit before escalating (default: 1000).
+		 * @returns One report per process in the killed trees.
+		 */
+		killPortOwners(port: number, graceMs?: number | null): Promise<KillReport[]>;
+		/**
+		 * Resolve a host name with the system resolver, timing the lookup. Failures and timeouts are
+		 * reported in `error`; rejects only when aborted.
+		 */
+		resolveHost(host: string, options?: ResolveHostOptions | null): Promise<ResolvedHost>;
+		/**
+		 * Resolve a host, then time sequential TCP connections to it, telling apart bad DNS, captive
+		 * portals and blocked hosts. Failures are reported per step; rejects only when aborted.
+		 * @throws For a port outside 1-65535.
+		 */
+		probeLatency(host: string, options?: LatencyProbeOptions | null): Promise<LatencyProbe>;
 	}
 }
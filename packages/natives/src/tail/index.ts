@@ -0,0 +1,34 @@
+/**
+ * Reading and following log files without a `tail -f` child process.
+ */
+
+import * as path from "node:path";
+import { native } from "../native";
+import type { TailEvent, TailOptions } from "./types";
+
+export type { TailEvent, TailOptions } from "./types";
+
+/**
+ * Emit the last lines of a file and, with `follow`, keep emitting lines appended to it across
+ * log rotation. Read errors are passed to `onError` when provided and end the tail.
+ * @returns Tail id to pass to `stopTail`.
+ */
+export function tailFile(
+	filePath: string,
+	options: TailOptions | undefined,
+	onEvent: (event: TailEvent) => void,
+	onError?: (error: Error) => void,
+): number {
+	return native.tailFile(path.resolve(filePath), options, (err, event) => {
+		if (err) onError?.(err);
+		else onEvent(event);
+	});
+}
+
+/**
+ * Stop a tail started by `tailFile`.
+ * @returns False if the id is not an active tail.
+ */
+export function stopTail(id: number): boolean {
+	return native.stopTail(id);
+}
@@ -0,0 +1,50 @@
+/**
+ * Types for reading and following log files.
+ */
+
+import type { TsFunc } from "../bindings";
+
+/** Options for tailing a file. */
+export interface TailOptions {
+	/** Start at the end of the file, emitting only its last `maxLines` lines (default: true). */
+	fromEnd?: boolean;
+	/** Keep emitting lines appended to the file, across rotation, until `stopTail` (default: false). */
+	follow?: boolean;
+	/** Existing lines to emit with `fromEnd` (default: 10). */
+	maxLines?: number;
+	/** Interval between checks for new data and rotation while following, in milliseconds (default: 250). */
+	pollMs?: number;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** A batch of lines read from a tailed file. */
+export interface TailEvent {
+	/** Complete lines, decoded as UTF-8 without line terminators. */
+	lines: string[];
+	/** Whether the path now refers to a new file and `lines` start at its beginning. */
+	rotated: boolean;
+	/** Whether the file was truncated in place and `lines` start at its beginning. */
+	truncated: boolean;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Emit the last lines of a file and, with `follow`, keep emitting appended lines. Rotation
+		 * is detected when the path resolves to a different file (inode change) or the file shrinks;
+		 * following then continues from the start of the new contents.
+		 * @param path File to read.
+		 * @param options Start position, follow mode and line count.
+		 * @param callback Receives each batch of lines, or the error that ended the tail. Without
+		 * `follow` it is called exactly once.
+		 * @returns Tail id to pass to `stopTail`.
+		 */
+		tailFile(path: string, options: TailOptions | undefined | null, callback: TsFunc<TailEvent>): number;
+		/**
+		 * Stop a tail started by `tailFile`.
+		 * @returns False if the id is not an active tail.
+		 */
+		stopTail(id: number): boolean;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Temporary directories removed with their session, or after a crash.
+ */
+
+import { native } from "../native";
+
+export type { ManagedTempDir, ManagedTempDirOptions } from "./types";
+
+export const { createManagedTempDir, listManagedTempDirs, releaseManagedTempDirs } = native;
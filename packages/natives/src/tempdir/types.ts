@@ -0,0 +1,46 @@
+/**
+ * Types for managed temporary directories.
+ */
+
+/** Options for `createManagedTempDir` and `listManagedTempDirs`. */
+export interface ManagedTempDirOptions {
+	/** Session the directory belongs to, for `releaseManagedTempDirs`. */
+	sessionId?: string;
+}
+
+/** A directory from `createManagedTempDir`. */
+export interface ManagedTempDir {
+	/** Absolute path. */
+	path: string;
+	/** Label it was created with. */
+	label: string;
+	/** Session it belongs to. */
+	sessionId?: string;
+	/** Creation time in milliseconds since the Unix epoch. */
+	createdAt: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Create a private temporary directory and track it until `releaseManagedTempDirs` or the
+		 * environment shuts down. Directories left behind by a crashed process are removed the
+		 * next time one is created.
+		 * @param label Start of the directory name; characters unsafe in file names become `-`.
+		 * @param options Session the directory belongs to.
+		 * @returns Absolute path of the new, empty directory.
+		 */
+		createManagedTempDir(label: string, options?: ManagedTempDirOptions | null): Promise<string>;
+		/**
+		 * Directories created in this process and not yet released, oldest first.
+		 * @param options Only list those of `sessionId`.
+		 */
+		listManagedTempDirs(options?: ManagedTempDirOptions | null): ManagedTempDir[];
+		/**
+		 * Remove the directories of a session, typically when it ends.
+		 * @param sessionId Session whose directories to remove (default: all created in this process).
+		 * @returns How many directories were removed.
+		 */
+		releaseManagedTempDirs(sessionId?: string | null): Promise<number>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Terminal capability detection powered by native bindings.
+ */
+
+import { native } from "../native";
+
+export type { TermInfo } from "./types";
+
+export const { termInfo } = native;
@@ -0,0 +1,53 @@
+/**
+ * Types for terminal capability detection.
+ */
+
+/** Capabilities of the terminal, as reported by `termInfo`. */
+export interface TermInfo {
+	/** Whether stdout is a terminal. */
+	tty: boolean;
+	/**
+	 * Colors the terminal renders; `"none"` when output is not a terminal, `TERM` is `dumb` or
+	 * `NO_COLOR` is set. `FORCE_COLOR` (0-3) overrides detection.
+	 */
+	colorDepth: "truecolor" | "256" | "16" | "none";
+	/** Width in cells. */
+	columns?: number;
+	/** Height in cells. */
+	rows?: number;
+	/** Width in pixels, when the terminal reports it. */
+	pixelWidth?: number;
+	/** Height in pixels, when the terminal reports it. */
+	pixelHeight?: number;
+	/**
+	 * Terminal program: `kitty`, `ghostty`, `wezterm`, `iterm`, `apple-terminal`, `vscode`,
+	 * `windows-terminal`, `alacritty`, `foot`, `konsole`, `vte` (GNOME Terminal and other VTE-based
+	 * terminals), `mintty`, or the raw `TERM_PROGRAM` value for others.
+	 */
+	program?: string;
+	/** Multiplexer between this process and the terminal. */
+	multiplexer?: "tmux" | "screen" | "zellij";
+	/** Whether OSC 8 hyperlinks are rendered. `FORCE_HYPERLINK` overrides detection. */
+	hyperlinks: boolean;
+	/** Whether the kitty graphics protocol is supported. */
+	kittyGraphics: boolean;
+	/** Whether iTerm2 inline images (OSC 1337) are supported. */
+	itermImages: boolean;
+	/**
+	 * Cells taken by East Asian ambiguous-width characters such as `±` or `○`: 2 under Chinese,
+	 * Japanese and Korean locales, 1 otherwise.
+	 */
+	ambiguousWidth: 1 | 2;
+	/** Whether emoji presentation sequences (a text character followed by U+FE0F) take two cells. */
+	emojiVariationWide: boolean;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Detect the capabilities of the terminal this process writes to: color depth, size, program,
+		 * hyperlink and image support, and character width quirks.
+		 */
+		termInfo(): TermInfo;
+	}
+}
@@ -48,7 +48,8 @@ declare module "../bindings" {
 		sanitizeText(text: string): string;
 
 		/**
-		 * Wrap text to a visible width, preserving ANSI codes across line breaks.
+		 * Wrap text to a visible width, preserving ANSI codes across line breaks. Each line closes
+		 * the OSC 8 hyperlinks it leaves open, and the next line reopens them.
 		 * @param text UTF-16 input text with optional ANSI escapes.
 		 * @param width Maximum visible width per line.
 		 */
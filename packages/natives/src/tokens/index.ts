@@ -0,0 +1,9 @@
+/**
+ * Token counting and truncation with compiled-in model tokenizers.
+ */
+
+import { native } from "../native";
+
+export type { TruncateMiddleOptions, TruncateMiddleResult } from "./types";
+
+export const { countTokens, truncateToTokens, truncateMiddle } = native;
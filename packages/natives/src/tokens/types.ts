@@ -0,0 +1,60 @@
+/**
+ * Types for native token counting.
+ */
+
+/** Options for truncating the middle of a text. */
+export interface TruncateMiddleOptions {
+	/** Token budget for the result, including the elision marker. */
+	maxTokens: number;
+	/** Model id or encoding name, as for `countTokens`. */
+	model: string;
+	/**
+	 * Keep at most this many lines from the start (default: as many as fit in half the budget,
+	 * or more when the tail leaves room).
+	 */
+	headLines?: number;
+	/** Keep at most this many lines from the end (default: as many as fit). */
+	tailLines?: number;
+}
+
+/** Result of truncating the middle of a text. */
+export interface TruncateMiddleResult {
+	/** Head and tail of the input around an elision marker line, or the input unchanged when it fits. */
+	text: string;
+	/** Whether anything was elided. */
+	truncated: boolean;
+	/** Tokens in `text`. */
+	tokens: number;
+	/** Tokens in the input. */
+	originalTokens: number;
+	/** Input lines removed entirely or in part. */
+	elidedLines: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Count the tokens a model would see for `text`.
+		 * OpenAI models use their exact encoding; models without a public tokenizer
+		 * (Claude, Gemini, ...) are approximated with `cl100k_base`.
+		 * @param text Text to encode (special-token markup is counted literally).
+		 * @param model Model id (e.g. "gpt-4o", "openai/gpt-5") or encoding name (e.g. "o200k_base").
+		 */
+		countTokens(text: string, model: string): Promise<number>;
+		/**
+		 * Keep the longest prefix of `text` that fits in `limit` tokens, cut on a character boundary.
+		 * @param text Text to truncate; returned unchanged when it already fits.
+		 * @param model Model id or encoding name, as for `countTokens`.
+		 * @param limit Maximum number of tokens.
+		 */
+		truncateToTokens(text: string, model: string, limit: number): Promise<string>;
+		/**
+		 * Fit `text` into a token budget by keeping its first and last lines around a
+		 * `[... N lines, M tokens elided ...]` marker line. A side whose outermost line alone
+		 * exceeds the budget keeps a token-exact part of it (e.g. for minified output).
+		 * @param text Text to truncate.
+		 * @param options Token budget, model and head/tail line limits.
+		 */
+		truncateMiddle(text: string, options: TruncateMiddleOptions): Promise<TruncateMiddleResult>;
+	}
+}
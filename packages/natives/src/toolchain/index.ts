@@ -0,0 +1,9 @@
+/**
+ * Installed development tools and their versions.
+ */
+
+import { native } from "../native";
+
+export type { Toolchain, ToolchainOptions } from "./types";
+
+export const { detectToolchains } = native;
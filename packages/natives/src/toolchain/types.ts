@@ -0,0 +1,37 @@
+/**
+ * Types for installed toolchain discovery.
+ */
+
+/** Options for `detectToolchains`. */
+export interface ToolchainOptions {
+	/** Tools to check (default: node, python, rustc, cargo, go, java, docker, kubectl, gcc, clang, make, cmake). */
+	tools?: string[];
+	/** Timeout per tool in milliseconds (default: 5000). */
+	timeoutMs?: number;
+}
+
+/** A tool checked by `detectToolchains`. */
+export interface Toolchain {
+	/** Tool name as requested. */
+	name: string;
+	/** Executable found on `PATH`; absent when the tool is not installed. */
+	path?: string;
+	/** Version number (e.g. "22.1.0"). */
+	version?: string;
+	/** First line of the version output (e.g. "Docker version 24.0.7, build afdd53b"). */
+	output?: string;
+	/** Why the version could not be read, when the tool was found. */
+	error?: string;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Find development tools on `PATH` and read their versions, running each concurrently under
+		 * a timeout. Unknown tool names are run with `--version`.
+		 * @param options Tools to check and the timeout per tool.
+		 * @returns One entry per tool, in the order requested.
+		 */
+		detectToolchains(options?: ToolchainOptions | null): Promise<Toolchain[]>;
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Recoverable deletes through the platform trash.
+ */
+
+import { native } from "../native";
+
+export type { TrashedItem, TrashOptions } from "./types";
+
+export const { restoreFromTrash, trashPath } = native;
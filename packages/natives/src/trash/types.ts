@@ -0,0 +1,46 @@
+/**
+ * Types for recoverable deletes through the platform trash.
+ */
+
+import type { SessionStoreOptions } from "../store/types";
+
+/** Options for `trashPath`. */
+export interface TrashOptions extends SessionStoreOptions {
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+/** An item moved to the trash. */
+export interface TrashedItem {
+	/** Id to pass to `restoreFromTrash`. */
+	id: number;
+	/** Where the item was, and is restored to. */
+	originalPath: string;
+	/** Where the item is in the trash. */
+	trashedPath: string;
+	/** When the item was trashed, in milliseconds since the Unix epoch. */
+	trashedAt: number;
+	/** When the item was restored, in milliseconds since the Unix epoch. */
+	restoredAt?: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Move a file or directory to the platform trash (freedesktop.org trash, macOS Trash or the
+		 * Windows Recycle Bin) instead of deleting it, and record it in the session store.
+		 * @param path File or directory to trash; a symlink is trashed itself, not its target.
+		 * @param options Database and workspace boundary.
+		 * @returns The trashed item, with the id to restore it by.
+		 */
+		trashPath(path: string, options?: TrashOptions | null): Promise<TrashedItem>;
+		/**
+		 * Move an item trashed with `trashPath` back to its original path.
+		 * @param id Id returned by `trashPath`.
+		 * @param options Database the item was recorded in.
+		 * @returns The restored item. Rejects when the item is no longer in the trash or its
+		 * original path is taken.
+		 */
+		restoreFromTrash(id: number, options?: SessionStoreOptions | null): Promise<TrashedItem>;
+	}
+}
@@ -0,0 +1,39 @@
+/**
+ * Filesystem watching powered by notify.
+ */
+
+import * as path from "node:path";
+import { native } from "../native";
+import type { WatchEvent, WatchOptions } from "./types";
+
+export type { WatchEvent, WatchOptions } from "./types";
+export { WatchEventKind } from "./types";
+
+/**
+ * Watch files or directories for changes.
+ * Reported paths are absolute. Watcher errors are passed to `onError` when provided.
+ * @returns Watch id to pass to `unwatch`.
+ */
+export function watchPaths(
+	paths: string[],
+	options: WatchOptions | undefined,
+	onEvents: (events: WatchEvent[]) => void,
+	onError?: (error: Error) => void,
+): number {
+	return native.watchPaths(
+		paths.map(p => path.resolve(p)),
+		options,
+		(err, events) => {
+			if (err) onError?.(err);
+			else onEvents(events);
+		},
+	);
+}
+
+/**
+ * Stop a watcher started by `watchPaths`.
+ * @returns False if the id is not an active watcher.
+ */
+export function unwatch(id: number): boolean {
+	return native.unwatch(id);
+}
@@ -0,0 +1,56 @@
+/**
+ * Types for filesystem watching.
+ */
+
+import type { TsFunc } from "../bindings";
+
+/** Kind of filesystem change. */
+export const enum WatchEventKind {
+	/** Path was created. */
+	Create = 1,
+	/** Path contents or metadata changed. */
+	Modify = 2,
+	/** Path was removed. */
+	Delete = 3,
+	/** Path was renamed from `oldPath`. */
+	Rename = 4,
+}
+
+/** A coalesced filesystem change. */
+export interface WatchEvent {
+	/** Kind of change. */
+	kind: WatchEventKind;
+	/** Affected path (the new path for renames). */
+	path: string;
+	/** Previous path, for renames. */
+	oldPath?: string;
+}
+
+/** Options for watching paths. */
+export interface WatchOptions {
+	/** Watch directories recursively (default: true). */
+	recursive?: boolean;
+	/** Quiet period before a batch of events is delivered, in milliseconds (default: 100). */
+	debounceMs?: number;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Watch paths for changes. Events are debounced and coalesced per path,
+		 * then delivered in batches.
+		 * @param paths Files or directories to watch.
+		 * @param options Recursion and debounce options.
+		 * @param callback Receives each batch of events, or an error reported by the watcher.
+		 * @returns Watch id to pass to `unwatch`.
+		 */
+		watchPaths(paths: string[], options: WatchOptions | undefined | null, callback: TsFunc<WatchEvent[]>): number;
+		/**
+		 * Stop a watcher. Pending events are discarded.
+		 * @returns False if the id is not an active watcher.
+		 */
+		unwatch(id: number): boolean;
+	}
+}
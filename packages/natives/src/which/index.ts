@@ -0,0 +1,9 @@
+/**
+ * Command resolution without `which` or `where`.
+ */
+
+import { native } from "../native";
+
+export type { ResolveCommandOptions, ResolvedCommand } from "./types";
+
+export const { resolveCommand } = native;
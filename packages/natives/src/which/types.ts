@@ -0,0 +1,37 @@
+/**
+ * Types for command resolution.
+ */
+
+/** Options for `resolveCommand`. */
+export interface ResolveCommandOptions {
+	/** Directory relative paths resolve against (default: the current directory). */
+	cwd?: string;
+	/** Environment to take `PATH` and `PATHEXT` from (default: this process's). */
+	env?: Record<string, string>;
+}
+
+/** What a command name resolves to. */
+export interface ResolvedCommand {
+	/** Name as given. */
+	name: string;
+	/** What runs: a shell keyword, a builtin of the session's shell, an executable file, or nothing. */
+	kind: "keyword" | "builtin" | "file" | "not-found";
+	/** First executable found; also set for builtins that have one. */
+	path?: string;
+	/** Every executable found, in `PATH` order. */
+	allPaths: string[];
+	/** Version manager that `path` is a shim of (e.g. "asdf", "mise", "volta"). */
+	shim?: string;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Resolve a command name the way a shell session would: keyword, builtin, or executable on
+		 * `PATH` (with `PATHEXT` on Windows). Version manager shims are flagged.
+		 * @param name Command name, or a path relative to `cwd`.
+		 * @param options Directory and environment to resolve in.
+		 */
+		resolveCommand(name: string, options?: ResolveCommandOptions | null): Promise<ResolvedCommand>;
+	}
+}
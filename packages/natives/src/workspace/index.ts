@@ -0,0 +1,7 @@
+/**
+ * Workspace boundary for native file operations.
+ */
+
+import { native } from "../native";
+
+export const { getWorkspaceRoots, setWorkspaceRoots } = native;
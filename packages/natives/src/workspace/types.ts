@@ -0,0 +1,21 @@
+/**
+ * Types for the workspace boundary of file operations.
+ */
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Confine the native file APIs (reads and writes, searches and watches, archives, formatting,
+		 * hashing, tailing, PDF, image and source parsing) to these directories, replacing earlier roots. Paths are resolved against the working
+		 * directory with symlinks followed before `..` is applied, so neither `../` nor a link
+		 * escapes the roots; pass `allowOutsideWorkspace` to those calls to skip the check.
+		 * @param paths Root directories; an empty list lifts the boundary.
+		 * @throws When a root does not exist; the previous roots stay in effect.
+		 */
+		setWorkspaceRoots(paths: string[]): void;
+		/**
+		 * Current workspace roots with symlinks resolved; empty when file operations are not confined.
+		 */
+		getWorkspaceRoots(): string[];
+	}
+}
@@ -0,0 +1,9 @@
+/**
+ * Atomic, conflict-detecting file writes.
+ */
+
+import { native } from "../native";
+
+export type { WriteFileOptions, WriteFileResult } from "./types";
+
+export const { writeFileAtomic } = native;
@@ -0,0 +1,50 @@
+/**
+ * Types for atomic file writes.
+ */
+
+import type { TextProfile } from "../read/types";
+
+/** Options for writing a file atomically. */
+export interface WriteFileOptions {
+	/**
+	 * Hex SHA-256 of the file contents the caller last read (see `ReadFileResult.hash`).
+	 * The write fails with a `Conflict:` error if the file on disk differs.
+	 * An empty string expects the file not to exist.
+	 */
+	expectedHash?: string;
+	/** Keep the permissions of the file being replaced (default: true). */
+	preserveMode?: boolean;
+	/** Flush the file and its directory to disk before returning (default: false). */
+	fsync?: boolean;
+	/**
+	 * Write `content` with this line ending, final newline, encoding and BOM (see `ReadFileResult.profile`)
+	 * instead of as UTF-8 as given, so edits to CRLF or BOM-prefixed files only change the edited lines.
+	 */
+	profile?: TextProfile;
+	/** Skip the boundary set with `setWorkspaceRoots` (default: false). */
+	allowOutsideWorkspace?: boolean;
+	/** Journal the write under this operation from `beginOperation` (default: an operation of its own). */
+	operationId?: number;
+}
+
+/** Result of writing a file atomically. */
+export interface WriteFileResult {
+	/** Hex SHA-256 of the written contents, for use as the next `expectedHash`. */
+	hash: string;
+	/** Number of bytes written. */
+	bytes: number;
+	/** Operation the write was journaled under; absent when the journal is off. */
+	operationId?: number;
+}
+
+declare module "../bindings" {
+	interface NativeBindings {
+		/**
+		 * Atomically replace a file's contents via a temp file and rename.
+		 * @param path File to write; symlinks are followed.
+		 * @param content New file contents.
+		 * @param options Conflict detection, mode preservation and durability options.
+		 */
+		writeFileAtomic(path: string, content: string, options?: WriteFileOptions): Promise<WriteFileResult>;
+	}
+}